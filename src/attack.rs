@@ -0,0 +1,194 @@
+//! MITRE ATT&CK technique mapping
+//! Created: 2026-10-16 10:15:03 UTC
+//! Author: kartik4091
+//!
+//! Tags unified findings with the ATT&CK techniques they are evidence of,
+//! so reports can be pivoted into detection frameworks. The mapping is by
+//! finding id prefix; a finding collects the techniques of every matching
+//! entry. Custom rules and YARA rules can name techniques directly with a
+//! tag such as `T1204.002` or `attack.t1204.002`.
+
+use crate::detectors::Finding;
+
+/// An ATT&CK (Enterprise) technique or sub-technique
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Technique {
+    /// Technique id, e.g. `T1204.002`
+    pub id: &'static str,
+
+    /// Technique name
+    pub name: &'static str,
+}
+
+impl Technique {
+    /// Link to the technique on attack.mitre.org
+    pub fn url(&self) -> String {
+        format!("https://attack.mitre.org/techniques/{}/", self.id.replace('.', "/"))
+    }
+}
+
+/// Techniques referenced by the mapping
+pub const TECHNIQUES: [Technique; 11] = [
+    Technique {
+        id: "T1027",
+        name: "Obfuscated Files or Information",
+    },
+    Technique {
+        id: "T1027.009",
+        name: "Embedded Payloads",
+    },
+    Technique {
+        id: "T1036.008",
+        name: "Masquerade File Type",
+    },
+    Technique {
+        id: "T1059.007",
+        name: "JavaScript",
+    },
+    Technique {
+        id: "T1070.006",
+        name: "Timestomp",
+    },
+    Technique {
+        id: "T1105",
+        name: "Ingress Tool Transfer",
+    },
+    Technique {
+        id: "T1203",
+        name: "Exploitation for Client Execution",
+    },
+    Technique {
+        id: "T1204.001",
+        name: "Malicious Link",
+    },
+    Technique {
+        id: "T1204.002",
+        name: "Malicious File",
+    },
+    Technique {
+        id: "T1552.001",
+        name: "Credentials In Files",
+    },
+    Technique {
+        id: "T1566.001",
+        name: "Spearphishing Attachment",
+    },
+];
+
+/// Finding id prefixes and the techniques they map to
+const MAPPING: &[(&str, &[&str])] = &[
+    ("actions.java_script", &["T1059.007", "T1204.002"]),
+    ("actions.launch", &["T1204.002"]),
+    ("actions.go_to_r", &["T1105"]),
+    ("actions.go_to_e", &["T1027.009"]),
+    ("actions.uri", &["T1204.001"]),
+    ("reader_features.java_script", &["T1059.007", "T1204.002"]),
+    ("reader_features.launch", &["T1204.002"]),
+    ("xfa.script", &["T1059.007"]),
+    ("forms.calculation_script", &["T1059.007"]),
+    ("obfuscated_tokens", &["T1027"]),
+    ("filter_anomalies", &["T1027"]),
+    ("stream_entropy", &["T1027"]),
+    ("object_streams", &["T1027"]),
+    ("invisible_text", &["T1027"]),
+    ("layers", &["T1027"]),
+    ("fonts", &["T1027"]),
+    ("annotations", &["T1027"]),
+    ("parser_divergences", &["T1027", "T1203"]),
+    ("hybrid_xref", &["T1027"]),
+    ("polyglots", &["T1027.009"]),
+    ("trailing_data", &["T1027.009"]),
+    ("media", &["T1027.009"]),
+    ("attachments.type_mismatch", &["T1036.008"]),
+    ("limits", &["T1203"]),
+    ("exploits", &["T1203", "T1204.002"]),
+    ("shellcode", &["T1203"]),
+    ("shellcode.heap_spray", &["T1059.007"]),
+    ("decoder_anomalies", &["T1203"]),
+    ("clamav", &["T1204.002"]),
+    ("virustotal", &["T1204.002"]),
+    ("signatures.backdating", &["T1070.006"]),
+    ("uris", &["T1204.001", "T1566.001"]),
+    ("phishing", &["T1566.001"]),
+    ("secrets", &["T1552.001"]),
+];
+
+/// Looks up a technique by id
+pub fn technique(id: &str) -> Option<&'static Technique> {
+    TECHNIQUES.iter().find(|t| t.id.eq_ignore_ascii_case(id))
+}
+
+/// Techniques a finding id maps to, without duplicates
+pub fn techniques(finding_id: &str) -> Vec<&'static str> {
+    let mut found: Vec<&'static str> = Vec::new();
+    for (prefix, ids) in MAPPING {
+        let matches = finding_id
+            .strip_prefix(prefix)
+            .map_or(false, |rest| rest.is_empty() || rest.starts_with('.'));
+        if matches {
+            for &id in *ids {
+                if !found.contains(&id) {
+                    found.push(id);
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Technique id named by a rule tag (`T1204.002`, `attack.t1204.002`)
+pub fn technique_tag(tag: &str) -> Option<String> {
+    let id = tag.strip_prefix("attack.").unwrap_or(tag).to_ascii_uppercase();
+    let (base, sub) = match id.split_once('.') {
+        Some((base, sub)) => (base, Some(sub)),
+        None => (id.as_str(), None),
+    };
+    let digits = |s: &str, n: usize| s.len() == n && s.bytes().all(|b| b.is_ascii_digit());
+    let valid = base.strip_prefix('T').map_or(false, |n| digits(n, 4)) && sub.map_or(true, |s| digits(s, 3));
+    valid.then_some(id)
+}
+
+/// Adds the mapped techniques to each finding
+pub fn tag(findings: &mut [Finding]) {
+    for finding in findings {
+        for id in techniques(&finding.id) {
+            if !finding.techniques.iter().any(|t| t == id) {
+                finding.techniques.push(id.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_mapping() {
+        assert_eq!(techniques("actions.java_script"), vec!["T1059.007", "T1204.002"]);
+        assert_eq!(techniques("obfuscated_tokens.escaped_keyword"), vec!["T1027"]);
+        assert!(techniques("actions.java_scripting").is_empty());
+        assert!(techniques("header.missing_eof").is_empty());
+    }
+
+    #[test]
+    fn test_every_mapped_technique_is_known() {
+        for (_, ids) in MAPPING {
+            for id in *ids {
+                assert!(technique(id).is_some(), "{} is not in TECHNIQUES", id);
+            }
+        }
+        assert_eq!(
+            technique("T1204.002").unwrap().url(),
+            "https://attack.mitre.org/techniques/T1204/002/"
+        );
+    }
+
+    #[test]
+    fn test_technique_tags() {
+        assert_eq!(technique_tag("attack.t1204.002").as_deref(), Some("T1204.002"));
+        assert_eq!(technique_tag("T1027").as_deref(), Some("T1027"));
+        assert_eq!(technique_tag("malware"), None);
+        assert_eq!(technique_tag("T12"), None);
+    }
+}
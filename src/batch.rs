@@ -0,0 +1,442 @@
+//! Input selection and failure isolation for batch runs
+//! Created: 2026-10-16 11:13:18 UTC
+//! Author: kartik4091
+//!
+//! Turns command-line inputs into the list of files to process. An input
+//! is a file, a directory — its PDF files, and with `recursive` those of
+//! every directory below it — or a glob pattern such as `corpus/**/*.pdf`
+//! for shells that do not expand it. Include patterns replace the `.pdf`
+//! extension test for files found in directories; exclude patterns drop
+//! any file. Inputs that cannot be read are returned as failures next to
+//! the files that were found, so a batch carries on past them, and
+//! [`isolated`] turns a panic while processing one file into an error for
+//! that file alone.
+//!
+//! The input `-` stands for standard input, read with [`spool`]: in memory
+//! up to a limit, and beyond it into an unlinked temporary file that is
+//! mapped instead, so large documents piped in do not have to fit in memory.
+//! Input over a size cap is refused, so a stream that never ends cannot
+//! fill the disk either; [`check_size`] applies the same cap to files
+//! before they are read. The spool directory and the cap can be set in the
+//! `general` section of a configuration file, read with [`InputConfig`].
+
+use std::{
+    future::Future,
+    ops::Deref,
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+};
+
+use futures::FutureExt;
+use glob::{MatchOptions, Pattern};
+use memmap2::Mmap;
+use serde::Deserialize;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tracing::{debug, info, warn};
+
+use crate::error::{Error, Result};
+
+/// Input naming standard input
+pub const STDIN: &str = "-";
+
+/// Default amount of standard input held in memory before spooling to disk (64 MiB)
+pub const DEFAULT_STDIN_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Default cap on the size of an input file or of standard input (512 MiB)
+pub const DEFAULT_MAX_INPUT_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Patterns match anywhere in the path and ignore case
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+/// Which files of the inputs to process
+#[derive(Debug, Clone, Default)]
+pub struct FileSelection {
+    /// Descend into subdirectories of input directories
+    pub recursive: bool,
+
+    /// Patterns a file found in a directory must match; `*.pdf` when empty
+    pub include: Vec<Pattern>,
+
+    /// Patterns excluding a file wherever it was found
+    pub exclude: Vec<Pattern>,
+}
+
+/// Files selected from the inputs, and inputs that could not be read
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Files to process, in input order and path order within each input
+    pub files: Vec<PathBuf>,
+
+    /// Unreadable inputs and directories, with the reason
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+impl FileSelection {
+    /// Selection with the given include and exclude glob patterns
+    pub fn new(recursive: bool, include: &[String], exclude: &[String]) -> Result<Self> {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    Pattern::new(pattern)
+                        .map_err(|e| Error::Analysis(format!("Invalid file pattern '{}': {}", pattern, e)))
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(Self {
+            recursive,
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    /// Expands files, directories and glob patterns into the files to process
+    pub async fn expand(&self, inputs: &[PathBuf]) -> Selection {
+        let mut selection = Selection::default();
+        let mut stdin = false;
+        for input in inputs {
+            if is_stdin(input) {
+                if stdin {
+                    let reason = "Standard input can only be read once".to_string();
+                    selection.failures.push((input.clone(), reason));
+                } else {
+                    selection.files.push(input.clone());
+                }
+                stdin = true;
+                continue;
+            }
+            let metadata = match tokio::fs::metadata(input).await {
+                Ok(metadata) => metadata,
+                Err(_) if is_pattern(input) => {
+                    self.expand_pattern(input, &mut selection).await;
+                    continue;
+                }
+                Err(e) => {
+                    selection.failures.push((input.clone(), e.to_string()));
+                    continue;
+                }
+            };
+            if metadata.is_dir() {
+                self.expand_directory(input, &mut selection).await;
+            } else if !self.excluded(input) {
+                selection.files.push(input.clone());
+            }
+        }
+        debug!(
+            "Selected {} files ({} inputs failed)",
+            selection.files.len(),
+            selection.failures.len()
+        );
+        selection
+    }
+
+    async fn expand_pattern(&self, pattern: &Path, selection: &mut Selection) {
+        let paths = match glob::glob_with(&pattern.to_string_lossy(), MATCH_OPTIONS) {
+            Ok(paths) => paths,
+            Err(e) => {
+                selection.failures.push((pattern.to_path_buf(), e.to_string()));
+                return;
+            }
+        };
+        let mut matched = 0;
+        for entry in paths {
+            matched += 1;
+            match entry {
+                Ok(path) if path.is_dir() => self.expand_directory(&path, selection).await,
+                Ok(path) if !self.excluded(&path) => selection.files.push(path),
+                Ok(_) => {}
+                Err(e) => selection.failures.push((e.path().to_path_buf(), e.error().to_string())),
+            }
+        }
+        if matched == 0 {
+            selection
+                .failures
+                .push((pattern.to_path_buf(), "No files match the pattern".to_string()));
+        }
+    }
+
+    async fn expand_directory(&self, dir: &Path, selection: &mut Selection) {
+        let mut found = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("Cannot read {}: {}", dir.display(), e);
+                    selection.failures.push((dir, e.to_string()));
+                    continue;
+                }
+            };
+            loop {
+                let entry = match entries.next_entry().await {
+                    Ok(Some(entry)) => entry,
+                    Ok(None) => break,
+                    Err(e) => {
+                        selection.failures.push((dir.clone(), e.to_string()));
+                        break;
+                    }
+                };
+                let path = entry.path();
+                match entry.file_type().await {
+                    Ok(kind) if kind.is_dir() => {
+                        if self.recursive {
+                            pending.push(path);
+                        }
+                    }
+                    Ok(_) if self.included(&path) && !self.excluded(&path) => found.push(path),
+                    Ok(_) => {}
+                    Err(e) => selection.failures.push((path, e.to_string())),
+                }
+            }
+        }
+        found.sort();
+        selection.files.extend(found);
+    }
+
+    fn included(&self, path: &Path) -> bool {
+        if self.include.is_empty() {
+            return path.extension().map_or(false, |e| e.eq_ignore_ascii_case("pdf"));
+        }
+        self.include.iter().any(|p| p.matches_path_with(path, MATCH_OPTIONS))
+    }
+
+    fn excluded(&self, path: &Path) -> bool {
+        self.exclude.iter().any(|p| p.matches_path_with(path, MATCH_OPTIONS))
+    }
+}
+
+/// Whether an input that does not exist reads as a glob pattern
+fn is_pattern(input: &Path) -> bool {
+    input.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// Whether an input stands for standard input
+pub fn is_stdin(input: &Path) -> bool {
+    input.as_os_str() == STDIN
+}
+
+/// Input settings of a configuration file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct InputConfig {
+    /// Directory standard input is spooled to
+    pub temp_dir: Option<PathBuf>,
+
+    /// Largest input in bytes
+    pub max_file_size: Option<u64>,
+}
+
+impl InputConfig {
+    /// Reads the `general` section of a YAML configuration file; other
+    /// sections and keys are ignored
+    pub fn load(path: &Path) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct ConfigFile {
+            #[serde(default)]
+            general: InputConfig,
+        }
+
+        let text = std::fs::read_to_string(path)?;
+        let file: ConfigFile = serde_yaml::from_str(&text)
+            .map_err(|e| Error::Analysis(format!("Config {}: {}", path.display(), e)))?;
+        Ok(file.general)
+    }
+}
+
+fn too_large(max_size: u64) -> Error {
+    Error::Analysis(format!("Input exceeds the size limit of {} bytes", max_size))
+}
+
+/// Refuses a file of more than `max_size` bytes before it is read
+pub async fn check_size(path: &Path, max_size: u64) -> Result<()> {
+    let size = tokio::fs::metadata(path).await?.len();
+    if size > max_size {
+        return Err(too_large(max_size));
+    }
+    Ok(())
+}
+
+/// Document read from a stream
+#[derive(Debug)]
+pub enum Spooled {
+    /// Held in memory
+    Memory(Vec<u8>),
+
+    /// Written to an unlinked temporary file and mapped
+    Disk(Mmap),
+}
+
+impl Deref for Spooled {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Spooled::Memory(data) => data,
+            Spooled::Disk(map) => map,
+        }
+    }
+}
+
+/// Reads a stream to its end, keeping up to `memory_limit` bytes in memory
+/// and spooling larger input to a temporary file in `temp_dir`. Input of
+/// more than `max_size` bytes is an error; no more than that is written.
+pub async fn spool(
+    mut reader: impl AsyncRead + Unpin,
+    memory_limit: usize,
+    max_size: u64,
+    temp_dir: &Path,
+) -> Result<Spooled> {
+    let in_memory = (memory_limit as u64).min(max_size);
+    let mut buffer = Vec::new();
+    (&mut reader).take(in_memory + 1).read_to_end(&mut buffer).await?;
+    let buffered = buffer.len() as u64;
+    if buffered <= in_memory {
+        return Ok(Spooled::Memory(buffer));
+    }
+    if buffered > max_size {
+        return Err(too_large(max_size));
+    }
+
+    let file = tempfile::tempfile_in(temp_dir)
+        .map_err(|e| Error::Analysis(format!("Cannot spool input to {}: {}", temp_dir.display(), e)))?;
+    let mut writer = tokio::fs::File::from_std(file.try_clone()?);
+    writer.write_all(&buffer).await?;
+    drop(buffer);
+    // One byte past the cap tells an oversized input from one that fits;
+    // the unnamed file is gone once dropped
+    let copied = tokio::io::copy(&mut (&mut reader).take(max_size - buffered + 1), &mut writer).await?;
+    if buffered + copied > max_size {
+        return Err(too_large(max_size));
+    }
+    writer.flush().await?;
+    info!("Spooled {} bytes of input to {}", buffered + copied, temp_dir.display());
+    // SAFETY: the file has no name, so nothing outside this process can
+    // change it while it is mapped
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(Spooled::Disk(map))
+}
+
+/// Runs the processing of one file, reporting a panic as an error so that
+/// the rest of the batch still runs
+pub async fn isolated<T, E>(work: impl Future<Output = std::result::Result<T, E>>) -> anyhow::Result<T>
+where
+    E: Into<anyhow::Error>,
+{
+    match AssertUnwindSafe(work).catch_unwind().await {
+        Ok(result) => result.map_err(Into::into),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_string());
+            Err(anyhow::anyhow!("Processing panicked: {}", message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_expand_filters_and_failures() {
+        let dir = tempdir().unwrap();
+        for name in ["a.pdf", "b.PDF", "notes.txt", "sub/c.pdf", "sub/drafts/d.pdf"] {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, b"%PDF-1.7").unwrap();
+        }
+        let missing = dir.path().join("missing.pdf");
+        let inputs = [dir.path().to_path_buf(), missing.clone()];
+
+        let flat = FileSelection::default().expand(&inputs).await;
+        assert_eq!(flat.files, [dir.path().join("a.pdf"), dir.path().join("b.PDF")]);
+        assert_eq!(flat.failures.len(), 1);
+        assert_eq!(flat.failures[0].0, missing);
+
+        let selection = FileSelection::new(true, &[], &["*/drafts/*".to_string()]).unwrap();
+        let deep = selection.expand(&inputs[..1]).await;
+        assert_eq!(deep.files.len(), 3);
+        assert!(deep.files.contains(&dir.path().join("sub/c.pdf")));
+
+        let pattern = dir.path().join("**/*.pdf");
+        let globbed = FileSelection::new(false, &[], &["*/a.pdf".to_string()])
+            .unwrap()
+            .expand(&[pattern])
+            .await;
+        assert_eq!(globbed.files.len(), 3);
+        assert!(!globbed.files.contains(&dir.path().join("a.pdf")));
+
+        let text = FileSelection::new(false, &["*.txt".to_string()], &[]).unwrap();
+        assert_eq!(text.expand(&inputs[..1]).await.files, [dir.path().join("notes.txt")]);
+        assert!(FileSelection::new(false, &["[".to_string()], &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_spool_memory_and_disk() {
+        let dir = tempdir().unwrap();
+        let data: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+
+        let small = spool(&data[..], data.len(), u64::MAX, dir.path()).await.unwrap();
+        assert!(matches!(small, Spooled::Memory(_)));
+        assert_eq!(&*small, &data[..]);
+
+        let large = spool(&data[..], 1000, 10_000, dir.path()).await.unwrap();
+        assert!(matches!(large, Spooled::Disk(_)));
+        assert_eq!(&*large, &data[..]);
+        assert!(spool(&data[..], 1000, u64::MAX, &dir.path().join("missing")).await.is_err());
+
+        let error = spool(&data[..], 1000, 9_999, dir.path()).await.unwrap_err();
+        assert!(error.to_string().contains("size limit"));
+        assert!(spool(&data[..], 20_000, 9_999, dir.path()).await.is_err());
+
+        let selection = FileSelection::default()
+            .expand(&[PathBuf::from(STDIN), PathBuf::from(STDIN)])
+            .await;
+        assert_eq!(selection.files, [PathBuf::from(STDIN)]);
+        assert_eq!(selection.failures.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_input_config_and_size_check() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("pdx.yaml");
+        std::fs::write(&path, "general:\n  temp_dir: /var/spool/pdx\n  max_file_size: 4\n  workers: 2\nlogging: {}\n")
+            .unwrap();
+        let config = InputConfig::load(&path).unwrap();
+        assert_eq!(config.temp_dir, Some(PathBuf::from("/var/spool/pdx")));
+        assert_eq!(config.max_file_size, Some(4));
+
+        std::fs::write(&path, "logging: {}\n").unwrap();
+        assert!(InputConfig::load(&path).unwrap().max_file_size.is_none());
+        std::fs::write(&path, "general: [").unwrap();
+        assert!(InputConfig::load(&path).is_err());
+
+        let file = dir.path().join("doc.pdf");
+        std::fs::write(&file, b"%PDF-").unwrap();
+        assert!(check_size(&file, 5).await.is_ok());
+        assert!(check_size(&file, 4).await.unwrap_err().to_string().contains("size limit"));
+    }
+
+    #[tokio::test]
+    async fn test_isolated_catches_panics() {
+        let ok = isolated(async { Ok::<_, Error>(1) }).await.unwrap();
+        assert_eq!(ok, 1);
+
+        let panicked = isolated(async {
+            if ok == 1 {
+                panic!("corrupt input");
+            }
+            Ok::<_, Error>(2)
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(panicked.to_string(), "Processing panicked: corrupt input");
+    }
+}
@@ -0,0 +1,220 @@
+//! Up-front cost estimation and analysis budgeting
+//! Created: 2026-10-16 08:52:14 UTC
+//! Author: kartik4091
+//!
+//! Gateways need predictable latency, so the analyzer estimates how
+//! expensive a file will be from cheap byte-level signals before loading
+//! it, and drops the costliest passes when the estimate exceeds the
+//! configured budget. Every degradation is recorded as a warning so that a
+//! partial report is never mistaken for a clean one.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    document::{
+        objects::scan_objects,
+        parser::{is_delimiter, is_whitespace},
+        revisions::{find, split_revisions},
+    },
+    AnalysisOptions,
+};
+
+/// Cost units charged per indirect object
+const OBJECT_COST: u64 = 2;
+
+/// Cost units charged per image XObject (decoding dominates)
+const IMAGE_COST: u64 = 32;
+
+/// Cheap signals used to predict analysis expense
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    /// File size in bytes
+    pub size: u64,
+
+    /// Indirect objects found by a raw scan
+    pub objects: usize,
+
+    /// Image XObjects
+    pub images: usize,
+
+    /// Incremental revisions
+    pub revisions: usize,
+
+    /// Whether the file declares an /Encrypt dictionary
+    pub encrypted: bool,
+}
+
+impl CostEstimate {
+    /// Cost of a single pass over the document, in roughly KiB-of-work units
+    pub fn base_cost(&self) -> u64 {
+        let cost = self.size / 1024 + self.objects as u64 * OBJECT_COST + self.images as u64 * IMAGE_COST;
+        // Every stream has to be decrypted before it can be inspected
+        if self.encrypted {
+            cost * 2
+        } else {
+            cost
+        }
+    }
+
+    /// Total cost of an analysis with the given options
+    pub fn cost(&self, options: &AnalysisOptions) -> u64 {
+        let base = self.base_cost();
+        let mut cost = base;
+        if options.diff_revisions {
+            // Every revision is materialized on its own
+            cost += base * self.revisions as u64;
+        }
+        if options.simulate_viewers {
+            cost += self.objects as u64 * OBJECT_COST * 3;
+        }
+        cost
+    }
+}
+
+/// How much of the analysis is performed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Depth {
+    /// Structural passes only; the document is not loaded
+    Minimal,
+
+    /// Single-pass object analysis without optional multi-pass features
+    Reduced,
+
+    /// Everything requested
+    Full,
+}
+
+/// Maximum analysis cost allowed per file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnalysisBudget {
+    /// Cost units (see [`CostEstimate::cost`])
+    pub max_cost: u64,
+}
+
+/// Outcome of budgeting a file
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// Depth the analysis runs at
+    pub depth: Depth,
+
+    /// Options with unaffordable passes disabled
+    pub options: AnalysisOptions,
+
+    /// Degradations applied, for the report
+    pub warnings: Vec<String>,
+}
+
+impl Plan {
+    /// Runs everything requested
+    pub fn full(options: &AnalysisOptions) -> Self {
+        Self { depth: Depth::Full, options: options.clone(), warnings: Vec::new() }
+    }
+}
+
+impl AnalysisBudget {
+    /// Creates a budget of `max_cost` units
+    pub fn new(max_cost: u64) -> Self {
+        Self { max_cost }
+    }
+
+    /// Chooses the deepest analysis that fits the budget
+    pub fn plan(&self, estimate: &CostEstimate, options: &AnalysisOptions) -> Plan {
+        let requested = estimate.cost(options);
+        if requested <= self.max_cost {
+            return Plan::full(options);
+        }
+
+        let mut reduced = options.clone();
+        reduced.diff_revisions = false;
+        reduced.simulate_viewers = false;
+
+        let mut warnings = Vec::new();
+        if options.diff_revisions {
+            warnings.push("Revision diffing skipped: estimated cost exceeds budget".to_string());
+        }
+        if options.simulate_viewers {
+            warnings.push("Viewer simulation skipped: estimated cost exceeds budget".to_string());
+        }
+
+        let depth = if estimate.cost(&reduced) <= self.max_cost {
+            Depth::Reduced
+        } else {
+            warnings.push(
+                "Object-level analysis skipped: estimated cost exceeds budget; only structural checks ran"
+                    .to_string(),
+            );
+            Depth::Minimal
+        };
+
+        warnings.insert(
+            0,
+            format!(
+                "Analysis degraded to {:?} depth (estimated cost {} > budget {})",
+                depth, requested, self.max_cost
+            ),
+        );
+        Plan { depth, options: reduced, warnings }
+    }
+}
+
+/// Estimates analysis cost from raw bytes without loading the document
+pub fn estimate(data: &[u8]) -> CostEstimate {
+    CostEstimate {
+        size: data.len() as u64,
+        objects: scan_objects(data).len(),
+        images: count_name(data, b"/Image"),
+        revisions: split_revisions(data).len(),
+        encrypted: find(data, b"/Encrypt", 0).is_some(),
+    }
+}
+
+/// Counts occurrences of a name token (so `/Image` does not match `/ImageMask`)
+fn count_name(data: &[u8], name: &[u8]) -> usize {
+    let mut count = 0;
+    let mut cursor = 0;
+    while let Some(pos) = find(data, name, cursor) {
+        cursor = pos + name.len();
+        if data.get(cursor).map_or(true, |&b| is_whitespace(b) || is_delimiter(b)) {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_estimate() {
+        let mut data = fixtures::simple_pdf();
+        data.extend_from_slice(b"\n9 0 obj\n<< /Subtype /Image /SMask 10 0 R /ImageMask false >>\nendobj\n");
+
+        let estimate = estimate(&data);
+        assert_eq!(estimate.objects, 7);
+        assert_eq!(estimate.images, 1);
+        assert_eq!(estimate.revisions, 1);
+        assert!(!estimate.encrypted);
+    }
+
+    #[test]
+    fn test_plan_depths() {
+        let estimate = CostEstimate { size: 64 * 1024, objects: 100, images: 2, revisions: 4, encrypted: false };
+        let options = AnalysisOptions { diff_revisions: true, ..Default::default() };
+        let base = estimate.base_cost();
+
+        let full = AnalysisBudget::new(u64::MAX).plan(&estimate, &options);
+        assert_eq!(full.depth, Depth::Full);
+        assert!(full.warnings.is_empty());
+
+        let reduced = AnalysisBudget::new(base).plan(&estimate, &options);
+        assert_eq!(reduced.depth, Depth::Reduced);
+        assert!(!reduced.options.diff_revisions);
+        assert_eq!(reduced.warnings.len(), 2);
+
+        let minimal = AnalysisBudget::new(base - 1).plan(&estimate, &options);
+        assert_eq!(minimal.depth, Depth::Minimal);
+        assert_eq!(minimal.warnings.len(), 3);
+    }
+}
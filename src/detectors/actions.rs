@@ -0,0 +1,552 @@
+//! Action graph resolution and dangerous action grading
+//! Created: 2026-10-16 09:25:25 UTC
+//! Author: kartik4091
+//!
+//! Actions hang off the catalog (/OpenAction, /AA, the /JavaScript name
+//! tree), pages, annotations and form fields, and each can chain further
+//! actions through /Next. This pass resolves every trigger and chain into
+//! a flat list, records what each action targets, and grades it by what
+//! it can do and whether it fires without user interaction.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{decode_text, name_value, printable_preview, stream_data};
+
+/// Longest /Next chain followed before giving up
+const MAX_CHAIN: usize = 256;
+
+/// Length of script excerpts used as targets
+const SCRIPT_PREVIEW: usize = 80;
+
+/// Triggers that fire without user interaction, per owner level
+const AUTO_TRIGGERS: [&[u8]; 10] = [b"WC", b"WS", b"DS", b"WP", b"DP", b"O", b"PO", b"PV", b"PI", b"C"];
+
+/// Object type an action trigger belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub enum ActionLevel {
+    /// Catalog, document additional actions and document-level scripts
+    Document,
+
+    /// Page additional actions
+    Page,
+
+    /// Annotation activation and additional actions
+    Annotation,
+
+    /// Form field (widget) actions
+    Field,
+}
+
+/// A resolved action
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResolvedAction {
+    /// Action object, `None` for inline action dictionaries
+    pub id: Option<ObjectId>,
+
+    /// Object holding the trigger
+    pub owner: Option<ObjectId>,
+
+    /// Level of the owner
+    pub level: ActionLevel,
+
+    /// Trigger entry, e.g. `OpenAction`, `AA/O` or `Names/JavaScript`
+    pub trigger: String,
+
+    /// Position in the /Next chain (0 for the triggered action)
+    pub chain: usize,
+
+    /// Action type (/S)
+    pub kind: String,
+
+    /// URL, file or script excerpt the action acts on
+    pub target: Option<String>,
+
+    /// Whether the action fires without user interaction
+    pub auto_triggered: bool,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Every resolved action of a document
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ActionReport {
+    /// Actions in resolution order, each action object once; one reached
+    /// through several triggers is listed under the first automatic one
+    pub actions: Vec<ResolvedAction>,
+
+    /// Actions a /Next chain leads back to while they are still on it
+    pub chain_loops: Vec<ObjectId>,
+}
+
+impl ActionReport {
+    /// Actions graded Medium or above
+    pub fn dangerous(&self) -> impl Iterator<Item = &ResolvedAction> {
+        self.actions.iter().filter(|a| a.severity >= Severity::Medium)
+    }
+}
+
+/// Where an action chain starts
+struct Trigger {
+    owner: Option<ObjectId>,
+    level: ActionLevel,
+    name: String,
+    auto: bool,
+}
+
+struct Resolver<'a> {
+    doc: &'a Document,
+    report: ActionReport,
+    /// Index in `report.actions` of every action object recorded
+    recorded: BTreeMap<ObjectId, usize>,
+}
+
+/// Resolves all document, page, annotation and field actions
+#[instrument(skip(doc))]
+pub fn analyze(doc: &Document) -> ActionReport {
+    let mut resolver = Resolver {
+        doc,
+        report: ActionReport::default(),
+        recorded: BTreeMap::new(),
+    };
+
+    let root = doc.trailer.get(b"Root").and_then(Object::as_reference).ok();
+    if let Some(catalog) = root.and_then(|id| doc.get_dictionary(id).ok()) {
+        resolver.catalog(root, catalog);
+    }
+
+    for (&id, object) in &doc.objects {
+        let Ok(dict) = object.as_dict() else {
+            continue;
+        };
+        if Some(id) == root {
+            continue;
+        }
+        let level = match name_value(dict, b"Type") {
+            Some(b"Page") => ActionLevel::Page,
+            _ if name_value(dict, b"Subtype") == Some(b"Widget") || dict.has(b"FT") => ActionLevel::Field,
+            Some(b"Annot") => ActionLevel::Annotation,
+            _ if dict.has(b"Subtype") && dict.has(b"Rect") => ActionLevel::Annotation,
+            _ => continue,
+        };
+        if level != ActionLevel::Page {
+            if let Ok(action) = dict.get(b"A") {
+                resolver.chain(
+                    action,
+                    Trigger {
+                        owner: Some(id),
+                        level,
+                        name: "A".into(),
+                        auto: false,
+                    },
+                );
+            }
+        }
+        resolver.additional(Some(id), level, dict);
+    }
+
+    let Resolver { report, .. } = resolver;
+    debug!(
+        "Resolved {} actions, {} dangerous",
+        report.actions.len(),
+        report.dangerous().count()
+    );
+    report
+}
+
+impl<'a> Resolver<'a> {
+    fn catalog(&mut self, root: Option<ObjectId>, catalog: &Dictionary) {
+        if let Ok(open) = catalog.get(b"OpenAction") {
+            // An array is a destination, not an action
+            if !matches!(self.resolve(open), Some(Object::Array(_))) {
+                let trigger = Trigger {
+                    owner: root,
+                    level: ActionLevel::Document,
+                    name: "OpenAction".into(),
+                    auto: true,
+                };
+                self.chain(open, trigger);
+            }
+        }
+        self.additional(root, ActionLevel::Document, catalog);
+
+        let scripts = catalog
+            .get(b"Names")
+            .ok()
+            .and_then(|names| self.resolve(names))
+            .and_then(|names| names.as_dict().ok())
+            .and_then(|names| names.get(b"JavaScript").ok());
+        if let Some(tree) = scripts {
+            let mut seen = BTreeSet::new();
+            self.name_tree(root, tree, &mut seen, 0);
+        }
+    }
+
+    /// Follows an /AA dictionary, one chain per trigger key
+    fn additional(&mut self, owner: Option<ObjectId>, level: ActionLevel, dict: &Dictionary) {
+        let Some(Object::Dictionary(aa)) = dict.get(b"AA").ok().and_then(|aa| self.resolve(aa)) else {
+            return;
+        };
+        for (key, action) in aa.iter() {
+            // Page /C fires on close, field /C on recalculation; both are automatic
+            let auto = AUTO_TRIGGERS.contains(&key.as_slice()) || level == ActionLevel::Document;
+            let name = format!("AA/{}", String::from_utf8_lossy(key));
+            self.chain(
+                action,
+                Trigger {
+                    owner,
+                    level,
+                    name,
+                    auto,
+                },
+            );
+        }
+    }
+
+    /// Visits the document-level scripts of a /JavaScript name tree
+    fn name_tree(&mut self, owner: Option<ObjectId>, node: &Object, seen: &mut BTreeSet<ObjectId>, depth: usize) {
+        if let Object::Reference(id) = node {
+            if !seen.insert(*id) {
+                return;
+            }
+        }
+        let Some(Object::Dictionary(node)) = self.resolve(node) else {
+            return;
+        };
+        if depth > MAX_CHAIN {
+            return;
+        }
+
+        if let Ok(Object::Array(names)) = node.get(b"Names") {
+            for pair in names.chunks(2) {
+                let [name, action] = pair else {
+                    continue;
+                };
+                let label = match name {
+                    Object::String(bytes, _) => decode_text(bytes),
+                    _ => "?".into(),
+                };
+                let trigger = Trigger {
+                    owner,
+                    level: ActionLevel::Document,
+                    name: format!("Names/JavaScript ({})", label),
+                    auto: true,
+                };
+                self.chain(action, trigger);
+            }
+        }
+        if let Ok(Object::Array(kids)) = node.get(b"Kids") {
+            for kid in kids {
+                self.name_tree(owner, kid, seen, depth + 1);
+            }
+        }
+    }
+
+    /// Records an action and everything reachable through /Next.
+    ///
+    /// The walk is depth first: only an action that is still on the path
+    /// from the trigger closes a loop, while one reached again through
+    /// another branch (two /Next arrays joining) is simply not repeated.
+    fn chain<'o>(&mut self, start: &'o Object, trigger: Trigger)
+    where
+        'a: 'o,
+    {
+        let mut visited = BTreeSet::new();
+        // Action objects from the trigger down to the one being visited
+        let mut path: Vec<Option<ObjectId>> = Vec::new();
+        let mut pending = vec![(start, 0)];
+
+        while let Some((object, position)) = pending.pop() {
+            if position > MAX_CHAIN {
+                break;
+            }
+            path.truncate(position);
+            let id = object.as_reference().ok();
+            if let Some(id) = id {
+                if path.contains(&Some(id)) {
+                    if !self.report.chain_loops.contains(&id) {
+                        self.report.chain_loops.push(id);
+                    }
+                    continue;
+                }
+                if !visited.insert(id) {
+                    continue;
+                }
+            }
+            let Some(Object::Dictionary(action)) = self.resolve(object) else {
+                continue;
+            };
+            let Some(kind) = name_value(action, b"S") else {
+                continue;
+            };
+            path.push(id);
+
+            let resolved = self.describe(id, &trigger, position, kind, action);
+            match id.and_then(|id| self.recorded.get(&id).copied()) {
+                // Reached again from another trigger: keep the automatic one
+                Some(index) => {
+                    if resolved.auto_triggered && !self.report.actions[index].auto_triggered {
+                        self.report.actions[index] = resolved;
+                    }
+                }
+                None => {
+                    if let Some(id) = id {
+                        self.recorded.insert(id, self.report.actions.len());
+                    }
+                    self.report.actions.push(resolved);
+                }
+            }
+
+            match action.get(b"Next") {
+                Ok(Object::Array(next)) => pending.extend(next.iter().rev().map(|n| (n, position + 1))),
+                Ok(next) => pending.push((next, position + 1)),
+                Err(_) => {}
+            }
+        }
+    }
+
+    fn describe(
+        &self,
+        id: Option<ObjectId>,
+        trigger: &Trigger,
+        chain: usize,
+        kind: &[u8],
+        action: &Dictionary,
+    ) -> ResolvedAction {
+        let target = self.target(kind, action);
+        let external = target.as_deref().map_or(false, is_external);
+        let auto = trigger.auto;
+
+        let severity = match kind {
+            b"Launch" if auto => Severity::Critical,
+            b"Launch" => Severity::High,
+            b"JavaScript" if auto => Severity::High,
+            b"JavaScript" | b"GoToE" => Severity::Medium,
+            b"GoToR" if external => Severity::High,
+            b"GoToR" => Severity::Medium,
+            b"SubmitForm" | b"ImportData" if external && auto => Severity::High,
+            b"SubmitForm" | b"ImportData" if external => Severity::Medium,
+            b"URI" if auto => Severity::Medium,
+            b"URI" | b"SubmitForm" | b"ImportData" | b"Rendition" | b"Movie" | b"Sound" => Severity::Low,
+            _ => Severity::Info,
+        };
+
+        let kind = String::from_utf8_lossy(kind).into_owned();
+        let owner = trigger
+            .owner
+            .map_or("inline".to_string(), |(num, gen)| format!("{} {}", num, gen));
+        let mut description = format!("{} action via {} of {:?} {}", kind, trigger.name, trigger.level, owner);
+        if chain > 0 {
+            description.push_str(&format!(" (chained, step {})", chain));
+        }
+        if let Some(target) = &target {
+            description.push_str(&format!(": {}", target));
+        }
+        if auto {
+            description.push_str(" [automatic]");
+        }
+
+        ResolvedAction {
+            id,
+            owner: trigger.owner,
+            level: trigger.level,
+            trigger: trigger.name.clone(),
+            chain,
+            kind,
+            target,
+            auto_triggered: auto,
+            severity,
+            description,
+        }
+    }
+
+    /// What the action points at, depending on its type
+    fn target(&self, kind: &[u8], action: &Dictionary) -> Option<String> {
+        match kind {
+            b"URI" => self.text(action.get(b"URI").ok()?),
+            b"Launch" => {
+                let file = action.get(b"F").ok().or_else(|| {
+                    action
+                        .get(b"Win")
+                        .ok()
+                        .and_then(|w| self.resolve(w)?.as_dict().ok()?.get(b"F").ok())
+                })?;
+                self.file_spec(file)
+            }
+            b"GoToR" | b"GoToE" | b"SubmitForm" | b"ImportData" => self.file_spec(action.get(b"F").ok()?),
+            b"JavaScript" => {
+                let script = match self.resolve(action.get(b"JS").ok()?)? {
+                    Object::String(bytes, _) => decode_text(bytes),
+                    Object::Stream(stream) => String::from_utf8_lossy(&stream_data(stream).ok()?).into_owned(),
+                    _ => return None,
+                };
+                Some(printable_preview(script.trim().as_bytes(), SCRIPT_PREVIEW))
+            }
+            b"Named" => name_value(action, b"N").map(|n| String::from_utf8_lossy(n).into_owned()),
+            _ => None,
+        }
+    }
+
+    /// File name or URL of a file specification (string or dictionary)
+    fn file_spec(&self, spec: &Object) -> Option<String> {
+        match self.resolve(spec)? {
+            Object::Dictionary(dict) => [&b"UF"[..], b"F", b"DOS", b"Unix"]
+                .iter()
+                .find_map(|key| dict.get(key).ok().and_then(|value| self.text(value))),
+            other => self.text(other),
+        }
+    }
+
+    fn text(&self, object: &Object) -> Option<String> {
+        match self.resolve(object)? {
+            Object::String(bytes, _) => Some(decode_text(bytes)),
+            _ => None,
+        }
+    }
+
+    /// Follows a reference; the result borrows from the document, not the resolver
+    fn resolve<'o>(&self, object: &'o Object) -> Option<&'o Object>
+    where
+        'a: 'o,
+    {
+        match object {
+            Object::Reference(id) => self.doc.get_object(*id).ok(),
+            other => Some(other),
+        }
+    }
+}
+
+/// Whether a target leaves the machine (URL or UNC path)
+pub fn is_external(target: &str) -> bool {
+    let target = target.trim().to_ascii_lowercase();
+    ["http://", "https://", "ftp://", "mailto:", "\\\\", "//"]
+        .iter()
+        .any(|prefix| target.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_plain_document_has_no_actions() {
+        let report = analyze(&fixtures::document());
+        assert!(report.actions.is_empty());
+        assert!(report.chain_loops.is_empty());
+    }
+
+    #[test]
+    fn test_open_action_chain_is_followed() {
+        let mut doc = fixtures::document();
+        let launch = doc.add_object(dictionary! {
+            "S" => "Launch",
+            "F" => Object::string_literal("cmd.exe"),
+        });
+        let js = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.alert(1)"),
+            "Next" => launch,
+        });
+        doc.get_dictionary_mut(launch).unwrap().set("Next", js);
+        doc.get_dictionary_mut(fixtures::CATALOG_ID)
+            .unwrap()
+            .set("OpenAction", js);
+
+        let report = analyze(&doc);
+        let resolved: Vec<_> = report
+            .actions
+            .iter()
+            .map(|a| (a.kind.as_str(), a.chain, a.severity))
+            .collect();
+        assert_eq!(
+            resolved,
+            vec![("JavaScript", 0, Severity::High), ("Launch", 1, Severity::Critical)]
+        );
+        assert_eq!(report.actions[1].target.as_deref(), Some("cmd.exe"));
+        assert_eq!(report.chain_loops, vec![js]);
+    }
+
+    #[test]
+    fn test_joining_chains_are_not_loops() {
+        let mut doc = fixtures::document();
+        let js = doc.add_object(dictionary! { "S" => "JavaScript", "JS" => Object::string_literal("app.alert(1)") });
+        let left = doc.add_object(dictionary! { "S" => "Named", "N" => "NextPage", "Next" => js });
+        let right = doc.add_object(dictionary! { "S" => "Named", "N" => "PrevPage", "Next" => js });
+        let next: Vec<Object> = vec![left.into(), right.into()];
+        let start = doc.add_object(dictionary! { "S" => "Named", "N" => "FirstPage", "Next" => next });
+        doc.get_dictionary_mut(fixtures::CATALOG_ID)
+            .unwrap()
+            .set("OpenAction", start);
+
+        let report = analyze(&doc);
+        assert!(report.chain_loops.is_empty());
+        let ids: Vec<_> = report.actions.iter().map(|a| a.id.unwrap()).collect();
+        assert_eq!(ids, vec![start, left, js, right]);
+    }
+
+    #[test]
+    fn test_shared_action_is_listed_once() {
+        let mut doc = fixtures::document();
+        let js = doc.add_object(dictionary! { "S" => "JavaScript", "JS" => Object::string_literal("app.alert(1)") });
+        let rect = || vec![0.into(), 0.into(), 10.into(), 10.into()];
+        doc.add_object(dictionary! { "Type" => "Annot", "Subtype" => "Link", "Rect" => rect(), "A" => js });
+        doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Screen",
+            "Rect" => rect(),
+            "AA" => dictionary! { "PO" => js },
+        });
+
+        let report = analyze(&doc);
+        assert_eq!(report.actions.len(), 1);
+        assert!(report.actions[0].auto_triggered);
+        assert_eq!(report.actions[0].trigger, "AA/PO");
+        assert_eq!(report.actions[0].severity, Severity::High);
+    }
+
+    #[test]
+    fn test_annotation_actions_are_graded() {
+        let mut doc = fixtures::document();
+        doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            "A" => dictionary! { "S" => "URI", "URI" => Object::string_literal("https://example.com") },
+        });
+        doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            "A" => dictionary! {
+                "S" => "GoToR",
+                "F" => dictionary! { "FS" => "URL", "F" => Object::string_literal("\\\\attacker\\share\\x.pdf") },
+            },
+        });
+
+        let report = analyze(&doc);
+        let resolved: Vec<_> = report
+            .actions
+            .iter()
+            .map(|a| (a.kind.as_str(), a.level, a.severity))
+            .collect();
+        assert_eq!(
+            resolved,
+            vec![
+                ("URI", ActionLevel::Annotation, Severity::Low),
+                ("GoToR", ActionLevel::Annotation, Severity::High)
+            ]
+        );
+        assert_eq!(report.dangerous().count(), 1);
+    }
+}
@@ -0,0 +1,359 @@
+//! Annotation inventory and suspicious placement detection
+//! Created: 2026-10-16 09:30:48 UTC
+//! Author: kartik4091
+//!
+//! Annotations are where a document's interactive surface lives: links,
+//! widgets and screen annotations carry actions, and their appearance
+//! streams are drawn over the page. A script-bearing annotation that is
+//! zero-sized, placed off the page or flagged hidden is invisible to the
+//! reader while still reachable through events and the scripting API.
+
+use std::collections::BTreeSet;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{name_value, number, page_box, rectangle, resolve};
+
+/// Annotation flag bits (PDF 32000-1, 12.5.3)
+const FLAG_INVISIBLE: i64 = 1 << 0;
+const FLAG_HIDDEN: i64 = 1 << 1;
+const FLAG_NO_VIEW: i64 = 1 << 5;
+
+/// Width or height below which an annotation cannot be seen or clicked
+const MIN_VISIBLE: f64 = 1.0;
+
+/// Longest /Next chain followed when collecting action types
+const MAX_CHAIN: usize = 64;
+
+/// One annotation
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AnnotationInfo {
+    /// Annotation object, `None` for inline dictionaries
+    pub id: Option<ObjectId>,
+
+    /// Page listing the annotation in /Annots
+    pub page: Option<ObjectId>,
+
+    /// Annotation subtype
+    pub subtype: String,
+
+    /// Normalized /Rect
+    pub rect: Option<[f64; 4]>,
+
+    /// Annotation flags (/F)
+    pub flags: i64,
+
+    /// Hidden flag set
+    pub hidden: bool,
+
+    /// NoView flag set
+    pub no_view: bool,
+
+    /// Actions as `trigger:type`, e.g. `A:URI` or `AA/E:JavaScript`
+    pub actions: Vec<String>,
+
+    /// Appearance streams (/AP normal, rollover and down states)
+    pub appearance_streams: Vec<ObjectId>,
+}
+
+impl AnnotationInfo {
+    /// Whether any action (including chained ones) runs JavaScript
+    pub fn has_script(&self) -> bool {
+        self.actions.iter().any(|a| a.ends_with(":JavaScript"))
+    }
+}
+
+/// Suspicious annotation pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum AnnotationIssue {
+    /// Rect has no visible area
+    ZeroSize,
+
+    /// Rect lies entirely outside the page
+    OffPage,
+
+    /// Hidden, NoView or Invisible flag set
+    Hidden,
+
+    /// Not listed by any page, so never drawn
+    Unplaced,
+}
+
+/// An annotation whose actions are hidden from the reader
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SuspiciousAnnotation {
+    /// Annotation object
+    pub id: Option<ObjectId>,
+
+    /// Page listing the annotation
+    pub page: Option<ObjectId>,
+
+    /// Pattern found
+    pub issue: AnnotationIssue,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Every annotation and the suspicious ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct AnnotationReport {
+    /// Annotations, page by page, then those on no page
+    pub annotations: Vec<AnnotationInfo>,
+
+    /// Action-carrying annotations placed out of sight
+    pub suspicious: Vec<SuspiciousAnnotation>,
+}
+
+/// Enumerates annotations and flags action-carrying ones that cannot be seen
+#[instrument(skip(doc))]
+pub fn analyze(doc: &Document) -> AnnotationReport {
+    let mut report = AnnotationReport::default();
+    let mut placed = BTreeSet::new();
+
+    for (&page_id, object) in &doc.objects {
+        let Ok(page) = object.as_dict() else {
+            continue;
+        };
+        if name_value(page, b"Type") != Some(b"Page") {
+            continue;
+        }
+        let Ok(annots) = page.get(b"Annots").map(|a| resolve(doc, a)).and_then(Object::as_array) else {
+            continue;
+        };
+        let page_box = page_box(doc, page);
+        for entry in annots {
+            let id = entry.as_reference().ok();
+            let Ok(dict) = resolve(doc, entry).as_dict() else {
+                continue;
+            };
+            if let Some(id) = id {
+                placed.insert(id);
+            }
+            inspect(doc, id, Some(page_id), page_box, dict, &mut report);
+        }
+    }
+
+    for (&id, object) in &doc.objects {
+        let Ok(dict) = object.as_dict() else {
+            continue;
+        };
+        let is_annotation = name_value(dict, b"Type") == Some(b"Annot")
+            || (name_value(dict, b"Type").is_none() && dict.has(b"Subtype") && dict.has(b"Rect"));
+        if is_annotation && !placed.contains(&id) {
+            inspect(doc, Some(id), None, None, dict, &mut report);
+        }
+    }
+
+    debug!(
+        "Found {} annotations, {} suspicious",
+        report.annotations.len(),
+        report.suspicious.len()
+    );
+    report
+}
+
+fn inspect(
+    doc: &Document,
+    id: Option<ObjectId>,
+    page: Option<ObjectId>,
+    page_box: Option<[f64; 4]>,
+    dict: &Dictionary,
+    report: &mut AnnotationReport,
+) {
+    let flags = dict.get(b"F").ok().and_then(number).unwrap_or(0.0) as i64;
+    let info = AnnotationInfo {
+        id,
+        page,
+        subtype: name_value(dict, b"Subtype").map_or("?".into(), |s| String::from_utf8_lossy(s).into_owned()),
+        rect: dict.get(b"Rect").ok().and_then(|r| rectangle(doc, r)),
+        flags,
+        hidden: flags & FLAG_HIDDEN != 0,
+        no_view: flags & FLAG_NO_VIEW != 0,
+        actions: actions(doc, dict),
+        appearance_streams: appearance_streams(doc, dict),
+    };
+
+    if !info.actions.is_empty() {
+        let mut issues = Vec::new();
+        if page.is_none() {
+            issues.push((AnnotationIssue::Unplaced, "is not on any page".to_string()));
+        }
+        if let Some([x0, y0, x1, y1]) = info.rect {
+            if x1 - x0 < MIN_VISIBLE || y1 - y0 < MIN_VISIBLE {
+                issues.push((
+                    AnnotationIssue::ZeroSize,
+                    format!("has a {:.1} x {:.1} rect", x1 - x0, y1 - y0),
+                ));
+            } else if let Some([px0, py0, px1, py1]) = page_box {
+                if x1 <= px0 || x0 >= px1 || y1 <= py0 || y0 >= py1 {
+                    issues.push((AnnotationIssue::OffPage, "lies outside the page".to_string()));
+                }
+            }
+        }
+        if info.hidden || info.no_view || flags & FLAG_INVISIBLE != 0 {
+            issues.push((AnnotationIssue::Hidden, format!("is flagged hidden (/F {})", flags)));
+        }
+
+        let script = info.has_script();
+        for (issue, reason) in issues {
+            let severity = match (issue, script) {
+                (AnnotationIssue::Unplaced, true) => Severity::Medium,
+                (AnnotationIssue::Unplaced, false) => Severity::Low,
+                (_, true) => Severity::High,
+                (_, false) => Severity::Medium,
+            };
+            let name = id.map_or("inline".to_string(), |(num, gen)| format!("{} {}", num, gen));
+            report.suspicious.push(SuspiciousAnnotation {
+                id,
+                page,
+                issue,
+                severity,
+                description: format!(
+                    "{} annotation {} {} but carries {}",
+                    info.subtype,
+                    name,
+                    reason,
+                    info.actions.join(", ")
+                ),
+            });
+        }
+    }
+
+    report.annotations.push(info);
+}
+
+/// Action types of /A and every /AA trigger, following /Next chains
+fn actions(doc: &Document, dict: &Dictionary) -> Vec<String> {
+    let mut found = Vec::new();
+    if let Ok(action) = dict.get(b"A") {
+        chain(doc, "A", action, &mut found);
+    }
+    if let Ok(aa) = dict.get(b"AA").map(|aa| resolve(doc, aa)).and_then(Object::as_dict) {
+        for (key, action) in aa.iter() {
+            chain(doc, &format!("AA/{}", String::from_utf8_lossy(key)), action, &mut found);
+        }
+    }
+    found
+}
+
+fn chain(doc: &Document, trigger: &str, start: &Object, found: &mut Vec<String>) {
+    let mut seen = BTreeSet::new();
+    let mut next = Some(start);
+
+    while let Some(object) = next.take() {
+        if seen.len() > MAX_CHAIN || object.as_reference().map_or(false, |id| !seen.insert(id)) {
+            break;
+        }
+        let Ok(action) = resolve(doc, object).as_dict() else {
+            break;
+        };
+        if let Some(kind) = name_value(action, b"S") {
+            found.push(format!("{}:{}", trigger, String::from_utf8_lossy(kind)));
+        }
+        next = match action.get(b"Next") {
+            Ok(Object::Array(items)) => items.first(),
+            Ok(item) => Some(item),
+            Err(_) => None,
+        };
+    }
+}
+
+/// Appearance stream objects, including those of individual states
+fn appearance_streams(doc: &Document, dict: &Dictionary) -> Vec<ObjectId> {
+    let mut streams = Vec::new();
+    let Ok(ap) = dict.get(b"AP").map(|ap| resolve(doc, ap)).and_then(Object::as_dict) else {
+        return streams;
+    };
+    for key in [&b"N"[..], b"R", b"D"] {
+        match ap.get(key) {
+            Ok(Object::Reference(id)) if matches!(doc.get_object(*id), Ok(Object::Stream(_))) => streams.push(*id),
+            Ok(entry) => {
+                if let Ok(states) = resolve(doc, entry).as_dict() {
+                    streams.extend(states.iter().filter_map(|(_, state)| state.as_reference().ok()));
+                }
+            }
+            Err(_) => {}
+        }
+    }
+    streams
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    fn annotate(doc: &mut Document, annot: Dictionary) -> ObjectId {
+        let id = doc.add_object(annot);
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Annots", vec![id.into()]);
+        id
+    }
+
+    #[test]
+    fn test_visible_link_is_not_suspicious() {
+        let mut doc = fixtures::document();
+        annotate(
+            &mut doc,
+            dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Link",
+                "Rect" => vec![100.into(), 100.into(), 200.into(), 120.into()],
+                "A" => dictionary! { "S" => "URI", "URI" => Object::string_literal("https://example.com") },
+            },
+        );
+
+        let report = analyze(&doc);
+        assert_eq!(report.annotations.len(), 1);
+        assert_eq!(report.annotations[0].page, Some(fixtures::PAGE_ID));
+        assert_eq!(report.annotations[0].actions, vec!["A:URI".to_string()]);
+        assert!(report.suspicious.is_empty());
+    }
+
+    #[test]
+    fn test_hidden_script_annotations_are_flagged() {
+        let mut doc = fixtures::document();
+        let script =
+            doc.add_object(dictionary! { "S" => "JavaScript", "JS" => Object::string_literal("app.alert(1)") });
+        let zero = annotate(
+            &mut doc,
+            dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Widget",
+                "Rect" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+                "AA" => dictionary! { "PO" => script },
+            },
+        );
+        let off_page = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Screen",
+            "Rect" => vec![2000.into(), 2000.into(), 2100.into(), 2100.into()],
+            "F" => 2,
+            "A" => script,
+        });
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Annots", vec![zero.into(), off_page.into()]);
+
+        let report = analyze(&doc);
+        let issues: Vec<_> = report.suspicious.iter().map(|s| (s.id, s.issue, s.severity)).collect();
+        assert_eq!(
+            issues,
+            vec![
+                (Some(zero), AnnotationIssue::ZeroSize, Severity::High),
+                (Some(off_page), AnnotationIssue::OffPage, Severity::High),
+                (Some(off_page), AnnotationIssue::Hidden, Severity::High),
+            ]
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Content-based type identification of embedded files
+//! Created: 2026-10-16 09:25:47 UTC
+//! Author: kartik4091
+//!
+//! Both the /Subtype MIME type of an embedded file stream and the file
+//! name in its specification are chosen by whoever built the document, and
+//! droppers routinely label executables as text or images. Each attachment
+//! is identified from its leading bytes instead, and every claim that does
+//! not match the content is reported.
+
+use lopdf::{Document, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    attachments::{extract, EmbeddedFile},
+    magic::{identify, MagicKind},
+    name_value,
+};
+
+/// Content family implied by a file extension; `None` stands for plain
+/// text, which has no magic bytes
+const EXTENSIONS: &[(&str, Option<MagicKind>)] = &[
+    ("pdf", Some(MagicKind::Document)),
+    ("doc", Some(MagicKind::Document)),
+    ("xls", Some(MagicKind::Document)),
+    ("ppt", Some(MagicKind::Document)),
+    ("msg", Some(MagicKind::Document)),
+    ("rtf", Some(MagicKind::Document)),
+    ("docx", Some(MagicKind::Archive)),
+    ("xlsx", Some(MagicKind::Archive)),
+    ("pptx", Some(MagicKind::Archive)),
+    ("odt", Some(MagicKind::Archive)),
+    ("ods", Some(MagicKind::Archive)),
+    ("jar", Some(MagicKind::Archive)),
+    ("apk", Some(MagicKind::Archive)),
+    ("zip", Some(MagicKind::Archive)),
+    ("rar", Some(MagicKind::Archive)),
+    ("7z", Some(MagicKind::Archive)),
+    ("gz", Some(MagicKind::Archive)),
+    ("tgz", Some(MagicKind::Archive)),
+    ("bz2", Some(MagicKind::Archive)),
+    ("xz", Some(MagicKind::Archive)),
+    ("cab", Some(MagicKind::Archive)),
+    ("png", Some(MagicKind::Image)),
+    ("jpg", Some(MagicKind::Image)),
+    ("jpeg", Some(MagicKind::Image)),
+    ("gif", Some(MagicKind::Image)),
+    ("tif", Some(MagicKind::Image)),
+    ("tiff", Some(MagicKind::Image)),
+    ("exe", Some(MagicKind::Executable)),
+    ("dll", Some(MagicKind::Executable)),
+    ("scr", Some(MagicKind::Executable)),
+    ("com", Some(MagicKind::Executable)),
+    ("lnk", Some(MagicKind::Executable)),
+    ("class", Some(MagicKind::Executable)),
+    ("swf", Some(MagicKind::Executable)),
+    ("html", Some(MagicKind::Script)),
+    ("htm", Some(MagicKind::Script)),
+    ("svg", Some(MagicKind::Script)),
+    ("xml", Some(MagicKind::Script)),
+    ("txt", None),
+    ("csv", None),
+    ("log", None),
+    ("json", None),
+    ("md", None),
+];
+
+/// Identified type of one embedded file
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AttachmentType {
+    /// Embedded file stream
+    pub id: ObjectId,
+
+    /// File name from the file specification
+    pub name: String,
+
+    /// Decoded size in bytes
+    pub size: usize,
+
+    /// MIME type declared by the stream's /Subtype
+    pub declared_mime: Option<String>,
+
+    /// MIME type identified from the content
+    pub detected_mime: Option<String>,
+
+    /// Human-readable name of the identified format
+    pub detected: Option<String>,
+
+    /// Family of the identified format
+    pub kind: Option<MagicKind>,
+}
+
+/// A declared type or file name that contradicts an attachment's content
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TypeMismatch {
+    /// Embedded file stream
+    pub id: ObjectId,
+
+    /// The contradicted claim, e.g. `extension .txt`
+    pub claim: String,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Types of all attachments and the mismatches found
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct AttachmentReport {
+    /// Every embedded file, in object order
+    pub files: Vec<AttachmentType>,
+
+    /// Claims contradicted by content
+    pub mismatches: Vec<TypeMismatch>,
+}
+
+/// Identifies every embedded file and checks its declared type and name
+#[instrument(skip(doc))]
+pub fn inspect(doc: &Document) -> AttachmentReport {
+    let mut report = AttachmentReport::default();
+
+    for file in extract(doc) {
+        let magic = identify(&file.data);
+        let declared_mime = doc
+            .get_object(file.id)
+            .and_then(|object| object.as_stream())
+            .ok()
+            .and_then(|stream| name_value(&stream.dict, b"Subtype"))
+            .map(|mime| String::from_utf8_lossy(mime).into_owned());
+
+        let attachment = AttachmentType {
+            id: file.id,
+            name: file.name.clone(),
+            size: file.data.len(),
+            declared_mime,
+            detected_mime: magic.map(|m| m.mime.to_string()),
+            detected: magic.map(|m| m.description.to_string()),
+            kind: magic.map(|m| m.kind),
+        };
+
+        if let Some(mime) = &attachment.declared_mime {
+            if let Some(expected) = mime_family(mime) {
+                check(&file, &attachment, format!("declared type {}", mime), expected, &mut report.mismatches);
+            }
+        }
+        if let Some((extension, expected)) = extension_family(&file.name) {
+            check(&file, &attachment, format!("extension .{}", extension), expected, &mut report.mismatches);
+        }
+        report.files.push(attachment);
+    }
+
+    debug!("Identified {} attachments, {} type mismatches", report.files.len(), report.mismatches.len());
+    report
+}
+
+fn check(
+    file: &EmbeddedFile,
+    attachment: &AttachmentType,
+    claim: String,
+    expected: Option<MagicKind>,
+    mismatches: &mut Vec<TypeMismatch>,
+) {
+    if attachment.kind == expected {
+        return;
+    }
+
+    let severity = match attachment.kind {
+        Some(MagicKind::Executable) => Severity::High,
+        Some(MagicKind::Script) | Some(MagicKind::Archive) => Severity::Medium,
+        _ => Severity::Low,
+    };
+    let actual = attachment.detected.as_deref().unwrap_or("unrecognized content");
+    mismatches.push(TypeMismatch {
+        id: file.id,
+        description: format!("Attachment {} has {} but contains {}", file.name, claim, actual),
+        claim,
+        severity,
+    });
+}
+
+/// Content family of a MIME type, or `None` when it names no specific format
+fn mime_family(mime: &str) -> Option<Option<MagicKind>> {
+    let mime = mime.to_ascii_lowercase();
+    let family = match mime.as_str() {
+        "application/octet-stream" => return None,
+        "image/svg+xml" | "text/html" | "text/xml" | "application/xml" | "application/xhtml+xml" => {
+            Some(MagicKind::Script)
+        }
+        "application/pdf" | "application/msword" | "application/rtf" => Some(MagicKind::Document),
+        "application/zip" | "application/java-archive" | "application/x-7z-compressed" | "application/gzip"
+        | "application/vnd.rar" => Some(MagicKind::Archive),
+        "application/x-msdownload"
+        | "application/x-dosexec"
+        | "application/vnd.microsoft.portable-executable"
+        | "application/x-executable" => Some(MagicKind::Executable),
+        m if m.starts_with("application/vnd.openxmlformats") || m.starts_with("application/vnd.oasis") => {
+            Some(MagicKind::Archive)
+        }
+        m if m.starts_with("application/vnd.ms-") => Some(MagicKind::Document),
+        m if m.starts_with("image/") => Some(MagicKind::Image),
+        m if m.starts_with("text/") => None,
+        _ => return None,
+    };
+    Some(family)
+}
+
+/// Lowercase extension of a file name and the family it implies
+fn extension_family(name: &str) -> Option<(String, Option<MagicKind>)> {
+    let (_, extension) = name.rsplit_once('.')?;
+    let extension = extension.to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|&(_, family)| (extension, family))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Object, Stream};
+
+    fn attach(doc: &mut Document, name: &str, subtype: &str, data: &[u8]) -> ObjectId {
+        let id = doc.add_object(Stream::new(
+            dictionary! { "Type" => "EmbeddedFile", "Subtype" => Object::Name(subtype.as_bytes().to_vec()) },
+            data.to_vec(),
+        ));
+        doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal(name),
+            "EF" => dictionary! { "F" => id },
+        });
+        id
+    }
+
+    #[test]
+    fn test_matching_attachments_are_clean() {
+        let mut doc = fixtures::document();
+        attach(&mut doc, "report.pdf", "application/pdf", b"%PDF-1.7\n");
+        attach(&mut doc, "notes.txt", "text/plain", b"meeting at noon");
+        attach(&mut doc, "data.bin", "application/octet-stream", b"\x00\x01\x02");
+
+        let report = inspect(&doc);
+        assert_eq!(report.files.len(), 3);
+        assert_eq!(report.files[0].detected_mime.as_deref(), Some("application/pdf"));
+        assert!(report.mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_disguised_executable_is_flagged() {
+        let mut doc = fixtures::document();
+        let id = attach(&mut doc, "document.txt", "text/plain", b"MZ\x90\x00\x03\x00");
+
+        let report = inspect(&doc);
+        let claims: Vec<_> = report.mismatches.iter().map(|m| (m.id, m.claim.as_str(), m.severity)).collect();
+        assert_eq!(
+            claims,
+            vec![(id, "declared type text/plain", Severity::High), (id, "extension .txt", Severity::High)]
+        );
+        assert!(report.mismatches[1].description.contains("Windows PE executable"));
+    }
+}
@@ -0,0 +1,260 @@
+//! Carving of deleted and unindexed objects
+//! Created: 2026-10-16 08:40:54 UTC
+//! Author: kartik4091
+//!
+//! Incremental updates never remove bytes: an object that is "deleted" or
+//! replaced in a later revision keeps its old body in the file. This pass
+//! compares every object header found in the byte stream against the
+//! effective cross-reference table and recovers the ones no reader will
+//! ever display.
+
+use std::collections::BTreeMap;
+
+use lopdf::{xref::XrefEntry, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::document::{
+    objects::{scan_objects, RawObject},
+    parser::{parse_indirect, skip_whitespace},
+    printable_preview, stream_data,
+    taxonomy::ObjectType,
+    xref::XrefState,
+};
+
+/// Maximum number of characters kept in an artifact preview
+const PREVIEW_LEN: usize = 160;
+
+/// Why a carved object is no longer part of the live document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum CarveReason {
+    /// A later revision redefined the object number
+    Superseded,
+
+    /// A later xref section marked the object number as free
+    Freed,
+
+    /// The object number is not indexed by any xref section
+    Unindexed,
+}
+
+/// A recoverable object found outside the live document
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CarvedObject {
+    /// Object number and generation from the raw header
+    pub id: ObjectId,
+
+    /// File offset of the object header
+    pub offset: usize,
+
+    /// Number of bytes spanned by the object
+    pub length: usize,
+
+    /// Why the object is not live
+    pub reason: CarveReason,
+
+    /// Object type, when the body could be parsed
+    pub object_type: Option<ObjectType>,
+
+    /// Size of the decoded stream data, for stream objects
+    pub decoded_length: Option<usize>,
+
+    /// Printable excerpt of the decoded content
+    pub preview: Option<String>,
+
+    /// Parse or decode failure, if any
+    pub error: Option<String>,
+}
+
+/// Recovers every object body that the effective xref no longer points at
+#[instrument(skip(data, doc))]
+pub fn carve_objects(data: &[u8], doc: &Document) -> Vec<CarvedObject> {
+    let carved: Vec<CarvedObject> = scan_objects(data)
+        .into_iter()
+        .filter_map(|raw| classify(data, doc, &raw).map(|reason| recover(data, &raw, reason)))
+        .collect();
+
+    debug!("Carved {} objects", carved.len());
+    carved
+}
+
+/// Carves only what an incremental update starting at `start` made
+/// unreachable: previously live definitions it replaced or freed, and its
+/// own objects that the new cross-reference data does not point at.
+///
+/// `before` and `after` are the effective xref entries without and with the
+/// update, as built by [`crate::document::xref::effective_entries`].
+#[instrument(skip(data, before, after))]
+pub fn carve_appended(
+    data: &[u8],
+    start: usize,
+    before: &BTreeMap<u32, XrefState>,
+    after: &BTreeMap<u32, XrefState>,
+) -> Vec<CarvedObject> {
+    let mut carved = Vec::new();
+
+    for (&number, state) in before {
+        let XrefState::InUse { offset, generation } = *state else {
+            continue;
+        };
+        let reason = match after.get(&number) {
+            Some(current) if current == state => continue,
+            Some(XrefState::Free { .. }) => CarveReason::Freed,
+            _ => CarveReason::Superseded,
+        };
+
+        let offset = skip_whitespace(data, offset as usize);
+        let Some(raw) = scan_objects(&data[offset.min(start)..start])
+            .into_iter()
+            .next()
+            .filter(|raw| raw.offset == 0 && raw.id == (number, generation as u16))
+        else {
+            continue;
+        };
+        let raw = RawObject { offset: raw.offset + offset, end: raw.end + offset, ..raw };
+        carved.push(recover(data, &raw, reason));
+    }
+
+    for raw in scan_objects(&data[start..]) {
+        let raw = RawObject { offset: raw.offset + start, end: raw.end + start, ..raw };
+        if let Some(reason) = classify_state(data, after.get(&raw.id.0), &raw) {
+            carved.push(recover(data, &raw, reason));
+        }
+    }
+
+    debug!("Carved {} objects from appended data", carved.len());
+    carved
+}
+
+fn classify_state(data: &[u8], state: Option<&XrefState>, raw: &RawObject) -> Option<CarveReason> {
+    match state {
+        Some(XrefState::InUse { offset, generation }) => {
+            let live = skip_whitespace(data, *offset as usize);
+            if live == raw.offset && *generation == raw.id.1 as u64 {
+                None
+            } else {
+                Some(CarveReason::Superseded)
+            }
+        }
+        Some(XrefState::Compressed { .. }) => Some(CarveReason::Superseded),
+        Some(XrefState::Free { .. }) => Some(CarveReason::Freed),
+        None => Some(CarveReason::Unindexed),
+    }
+}
+
+fn classify(data: &[u8], doc: &Document, raw: &RawObject) -> Option<CarveReason> {
+    match doc.reference_table.get(raw.id.0) {
+        Some(XrefEntry::Normal { offset, generation }) => {
+            let live = skip_whitespace(data, *offset as usize);
+            if live == raw.offset && *generation == raw.id.1 {
+                None
+            } else {
+                Some(CarveReason::Superseded)
+            }
+        }
+        Some(XrefEntry::Compressed { .. }) => Some(CarveReason::Superseded),
+        Some(XrefEntry::Free) | Some(XrefEntry::UnusableFree) => Some(CarveReason::Freed),
+        None => Some(CarveReason::Unindexed),
+    }
+}
+
+fn recover(data: &[u8], raw: &RawObject, reason: CarveReason) -> CarvedObject {
+    let mut carved = CarvedObject {
+        id: raw.id,
+        offset: raw.offset,
+        length: raw.len(),
+        reason,
+        object_type: None,
+        decoded_length: None,
+        preview: None,
+        error: None,
+    };
+
+    let parsed = match parse_indirect(&data[..raw.end], raw.offset) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            carved.error = Some(e.to_string());
+            carved.preview = Some(printable_preview(&data[raw.offset..raw.end], PREVIEW_LEN));
+            return carved;
+        }
+    };
+
+    carved.object_type = Some(ObjectType::of(&parsed.object));
+    match &parsed.object {
+        Object::Stream(stream) => match stream_data(stream) {
+            Ok(decoded) => {
+                carved.decoded_length = Some(decoded.len());
+                carved.preview = Some(printable_preview(&decoded, PREVIEW_LEN));
+            }
+            Err(e) => carved.error = Some(e.to_string()),
+        },
+        _ => {
+            let end = raw.end.min(raw.offset + PREVIEW_LEN);
+            carved.preview = Some(printable_preview(&data[raw.offset..end], PREVIEW_LEN));
+        }
+    }
+
+    carved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_clean_document_has_nothing_to_carve() {
+        let data = fixtures::simple_pdf();
+        let doc = Document::load_mem(&data).unwrap();
+        assert!(carve_objects(&data, &doc).is_empty());
+    }
+
+    #[test]
+    fn test_superseded_object_is_carved() {
+        let base = fixtures::simple_pdf();
+        let data = fixtures::append_revision(
+            &base,
+            &[(4, "<< /Length 11 >>\nstream\nreplacement\nendstream")],
+        );
+        let doc = Document::load_mem(&data).unwrap();
+
+        let carved = carve_objects(&data, &doc);
+        assert_eq!(carved.len(), 1);
+        assert_eq!(carved[0].id, (4, 0));
+        assert_eq!(carved[0].reason, CarveReason::Superseded);
+        assert!(carved[0].preview.as_deref().unwrap().contains("Hello World!"));
+    }
+
+    #[test]
+    fn test_carve_appended_matches_full_carve() {
+        use crate::document::xref::{effective_entries, read_sections};
+
+        let base = fixtures::simple_pdf();
+        let data = fixtures::append_revision(
+            &base,
+            &[(4, "<< /Length 11 >>\nstream\nreplacement\nendstream")],
+        );
+        let before = effective_entries(&read_sections(&base).unwrap());
+        let after = effective_entries(&read_sections(&data).unwrap());
+
+        let carved = carve_appended(&data, base.len(), &before, &after);
+        assert_eq!(carved.len(), 1);
+        assert_eq!(carved[0].id, (4, 0));
+        assert_eq!(carved[0].reason, CarveReason::Superseded);
+        assert!(carved[0].offset < base.len());
+    }
+
+    #[test]
+    fn test_unindexed_object_is_carved() {
+        // Bytes after %%EOF are never indexed by any xref section
+        let mut data = fixtures::simple_pdf();
+        data.extend_from_slice(b"\n42 0 obj\n(secret note)\nendobj\n");
+
+        let doc = Document::load_mem(&data).unwrap();
+        let carved = carve_objects(&data, &doc);
+        assert_eq!(carved.len(), 1);
+        assert_eq!(carved[0].reason, CarveReason::Unindexed);
+        assert!(carved[0].preview.as_deref().unwrap().contains("secret note"));
+    }
+}
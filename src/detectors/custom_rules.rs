@@ -0,0 +1,440 @@
+//! User-defined declarative detection rules
+//! Created: 2026-10-16 10:06:03 UTC
+//! Author: kartik4091
+//!
+//! Teams often have local detection logic — a vendor's template that
+//! should never carry JavaScript, a producer string seen in a campaign —
+//! that does not belong in the crate. Rules are written in YAML or TOML
+//! files in a rules directory and matched against every indirect object:
+//!
+//! ```yaml
+//! rules:
+//!   - id: js-launch-url
+//!     description: JavaScript action opening a URL
+//!     severity: high
+//!     tags: [javascript, phishing]
+//!     match:
+//!       object_type: Action/JavaScript
+//!       keys: [JS]
+//!       strings: { JS: "app\\.launchURL" }
+//! ```
+//!
+//! Every condition given must hold. Object conditions apply to the
+//! top-level dictionary of an indirect object (the stream dictionary for
+//! streams); metadata conditions apply to the document's Info entries and
+//! XMP packet. A rule with only metadata conditions matches the document
+//! once.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use regex::{bytes::Regex as BytesRegex, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::{
+    document::{decode_text, metadata_text, name_value, stream_data, taxonomy::ObjectType},
+    error::{Error, Result},
+};
+
+/// Rule file extensions loaded from a directory
+pub const RULE_EXTENSIONS: [&str; 3] = ["yaml", "yml", "toml"];
+
+/// Conditions of a rule, as written in a rule file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleConditions {
+    /// Object type as reported by the taxonomy, either the kind alone
+    /// (`Action`) or with its subtype (`Action/JavaScript`); case-insensitive
+    pub object_type: Option<String>,
+
+    /// Keys the dictionary must contain
+    pub keys: Vec<String>,
+
+    /// Regexes over name values, by key
+    pub names: BTreeMap<String, String>,
+
+    /// Regexes over decoded string values, by key
+    pub strings: BTreeMap<String, String>,
+
+    /// Regex over decoded stream content
+    pub stream: Option<String>,
+
+    /// Regexes over Info entries (or `XMP` for the XMP packet), by key
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// A rule as written in a rule file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleDefinition {
+    /// Unique rule identifier
+    pub id: String,
+
+    /// What a match means, used in the finding description
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Severity of a match, medium by default
+    #[serde(default = "default_severity", with = "severity_name")]
+    pub severity: Severity,
+
+    /// Free-form tags copied onto matches
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Conditions that must all hold
+    #[serde(rename = "match")]
+    pub conditions: RuleConditions,
+}
+
+fn default_severity() -> Severity {
+    Severity::Medium
+}
+
+/// Severities are written in lowercase, as on the command line
+mod severity_name {
+    use std::str::FromStr;
+
+    use super::*;
+
+    pub fn serialize<S: Serializer>(severity: &Severity, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(severity)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Severity, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Severity::from_str(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Contents of a rule file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    #[serde(default)]
+    rules: Vec<RuleDefinition>,
+}
+
+/// A rule with its patterns compiled
+#[derive(Debug, Clone)]
+struct Rule {
+    definition: RuleDefinition,
+    names: Vec<(String, Regex)>,
+    strings: Vec<(String, Regex)>,
+    stream: Option<BytesRegex>,
+    metadata: Vec<(String, Regex)>,
+}
+
+impl Rule {
+    fn compile(definition: RuleDefinition) -> Result<Self> {
+        let conditions = &definition.conditions;
+        if conditions == &RuleConditions::default() {
+            return Err(Error::Analysis(format!("Rule {} has no conditions", definition.id)));
+        }
+
+        let compile =
+            |pattern: &str| Regex::new(pattern).map_err(|e| Error::Analysis(format!("Rule {}: {}", definition.id, e)));
+        let compile_all = |patterns: &BTreeMap<String, String>| -> Result<Vec<(String, Regex)>> {
+            patterns
+                .iter()
+                .map(|(key, pattern)| Ok((key.clone(), compile(pattern)?)))
+                .collect()
+        };
+
+        Ok(Self {
+            names: compile_all(&conditions.names)?,
+            strings: compile_all(&conditions.strings)?,
+            stream: conditions
+                .stream
+                .as_deref()
+                .map(BytesRegex::new)
+                .transpose()
+                .map_err(|e| Error::Analysis(format!("Rule {}: {}", definition.id, e)))?,
+            metadata: compile_all(&conditions.metadata)?,
+            definition,
+        })
+    }
+
+    /// Whether the rule has conditions on objects, not only on metadata
+    fn targets_objects(&self) -> bool {
+        let conditions = &self.definition.conditions;
+        conditions.object_type.is_some()
+            || !conditions.keys.is_empty()
+            || !self.names.is_empty()
+            || !self.strings.is_empty()
+            || self.stream.is_some()
+    }
+
+    fn matches_metadata(&self, metadata: &[(String, String)]) -> bool {
+        self.metadata
+            .iter()
+            .all(|(key, pattern)| metadata.iter().any(|(k, value)| k == key && pattern.is_match(value)))
+    }
+
+    fn matches_object(&self, object: &Object) -> bool {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &stream.dict,
+            _ => return false,
+        };
+
+        let conditions = &self.definition.conditions;
+        let type_ok = conditions
+            .object_type
+            .as_deref()
+            .map_or(true, |expected| type_matches(&ObjectType::of(object), expected));
+        let keys_ok = conditions.keys.iter().all(|key| dict.has(key.as_bytes()));
+        let names_ok = self.names.iter().all(|(key, pattern)| {
+            name_value(dict, key.as_bytes()).map_or(false, |name| pattern.is_match(&String::from_utf8_lossy(name)))
+        });
+        let strings_ok = self
+            .strings
+            .iter()
+            .all(|(key, pattern)| string_matches(dict, key, pattern));
+        if !(type_ok && keys_ok && names_ok && strings_ok) {
+            return false;
+        }
+        match (&self.stream, object) {
+            (None, _) => true,
+            (Some(pattern), Object::Stream(stream)) => {
+                stream_data(stream).map_or(false, |data| pattern.is_match(&data))
+            }
+            (Some(_), _) => false,
+        }
+    }
+
+    fn to_match(&self, object: Option<ObjectId>) -> RuleMatch {
+        let definition = &self.definition;
+        let location = match object {
+            Some(id) => format!("object {} {}", id.0, id.1),
+            None => "document metadata".to_string(),
+        };
+        RuleMatch {
+            rule: definition.id.clone(),
+            tags: definition.tags.clone(),
+            object,
+            severity: definition.severity,
+            description: match &definition.description {
+                Some(description) => format!("Rule {} matched {}: {}", definition.id, location, description),
+                None => format!("Rule {} matched {}", definition.id, location),
+            },
+        }
+    }
+}
+
+fn type_matches(object_type: &ObjectType, expected: &str) -> bool {
+    expected.eq_ignore_ascii_case(&object_type.to_string()) || expected.eq_ignore_ascii_case(object_type.kind())
+}
+
+fn string_matches(dict: &Dictionary, key: &str, pattern: &Regex) -> bool {
+    match dict.get(key.as_bytes()) {
+        Ok(Object::String(bytes, _)) => pattern.is_match(&decode_text(bytes)),
+        _ => false,
+    }
+}
+
+/// Compiled set of rules
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+
+    /// Rule files the set was loaded from
+    pub sources: Vec<PathBuf>,
+}
+
+impl RuleSet {
+    /// Compiles rule definitions, rejecting duplicate identifiers
+    pub fn new(definitions: Vec<RuleDefinition>) -> Result<Self> {
+        let mut seen = BTreeSet::new();
+        let mut rules = Vec::with_capacity(definitions.len());
+        for definition in definitions {
+            if !seen.insert(definition.id.clone()) {
+                return Err(Error::Analysis(format!("Duplicate rule id {}", definition.id)));
+            }
+            rules.push(Rule::compile(definition)?);
+        }
+        Ok(Self {
+            rules,
+            sources: Vec::new(),
+        })
+    }
+
+    /// Loads every `.yaml`, `.yml` and `.toml` file of a directory
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut sources: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.extension()
+                    .and_then(|e| e.to_str())
+                    .map_or(false, |e| RULE_EXTENSIONS.contains(&e))
+            })
+            .collect();
+        sources.sort();
+
+        let mut definitions = Vec::new();
+        for source in &sources {
+            definitions.extend(read_file(source)?);
+        }
+        let mut set = Self::new(definitions)?;
+        debug!("Loaded {} rules from {} files", set.rules.len(), sources.len());
+        set.sources = sources;
+        Ok(set)
+    }
+
+    /// Rule definitions, in load order
+    pub fn definitions(&self) -> impl Iterator<Item = &RuleDefinition> {
+        self.rules.iter().map(|rule| &rule.definition)
+    }
+
+    /// Number of rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Whether the set holds no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<RuleDefinition>> {
+    let text = std::fs::read_to_string(path)?;
+    let file: RuleFile = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|e| e.to_string()),
+        _ => serde_yaml::from_str(&text).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| Error::Analysis(format!("Rule file {}: {}", path.display(), e)))?;
+    Ok(file.rules)
+}
+
+/// An object or document matching a rule
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RuleMatch {
+    /// Rule identifier
+    pub rule: String,
+
+    /// Rule tags
+    pub tags: Vec<String>,
+
+    /// Matching object, `None` for metadata-only rules
+    pub object: Option<ObjectId>,
+
+    /// Severity assigned by the rule
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Evaluates every rule against the document
+#[instrument(skip(rules, doc))]
+pub fn evaluate(rules: &RuleSet, doc: &Document) -> Vec<RuleMatch> {
+    let metadata = metadata_text(doc);
+    let mut matches = Vec::new();
+
+    for rule in rules.rules.iter().filter(|rule| rule.matches_metadata(&metadata)) {
+        if !rule.targets_objects() {
+            matches.push(rule.to_match(None));
+            continue;
+        }
+        for (&id, object) in &doc.objects {
+            if rule.matches_object(object) {
+                matches.push(rule.to_match(Some(id)));
+            }
+        }
+    }
+
+    debug!("{} rule matches", matches.len());
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    fn rules(yaml: &str) -> RuleSet {
+        let file: RuleFile = serde_yaml::from_str(yaml).unwrap();
+        RuleSet::new(file.rules).unwrap()
+    }
+
+    #[test]
+    fn test_object_conditions() {
+        let rules = rules(
+            r#"
+rules:
+  - id: js-launch-url
+    description: JavaScript action opening a URL
+    severity: high
+    tags: [javascript]
+    match:
+      object_type: action/javascript
+      keys: [JS]
+      strings: { JS: "app\\.launchURL" }
+"#,
+        );
+        let mut doc = fixtures::document();
+        let hit = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.launchURL('https://example.com')"),
+        });
+        doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.alert(1)"),
+        });
+
+        let matches = evaluate(&rules, &doc);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].object, Some(hit));
+        assert_eq!(matches[0].severity, Severity::High);
+        assert_eq!(matches[0].tags, vec!["javascript".to_string()]);
+    }
+
+    #[test]
+    fn test_stream_and_metadata_conditions() {
+        let rules = rules(
+            r#"
+rules:
+  - id: hello-stream
+    match:
+      stream: "Hello W[a-z]+"
+  - id: campaign-producer
+    severity: low
+    match:
+      metadata: { Producer: "^EvilWriter" }
+"#,
+        );
+        let mut doc = fixtures::document();
+        let info = doc.add_object(dictionary! { "Producer" => Object::string_literal("EvilWriter 2.1") });
+        doc.trailer.set("Info", info);
+
+        let matches = evaluate(&rules, &doc);
+        let rules: Vec<_> = matches.iter().map(|m| (m.rule.as_str(), m.object)).collect();
+        assert_eq!(rules, vec![("hello-stream", Some((4, 0))), ("campaign-producer", None)]);
+    }
+
+    #[test]
+    fn test_load_directory_and_reject_invalid_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("local.toml"),
+            "[[rules]]\nid = \"no-fonts\"\nseverity = \"info\"\n[rules.match]\nobject_type = \"Font\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let set = RuleSet::load(dir.path()).unwrap();
+        assert_eq!(set.len(), 1);
+        assert_eq!(evaluate(&set, &fixtures::document()).len(), 1);
+
+        std::fs::write(dir.path().join("bad.yaml"), "rules:\n  - id: empty\n    match: {}\n").unwrap();
+        assert!(RuleSet::load(dir.path()).is_err());
+    }
+}
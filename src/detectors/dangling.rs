@@ -0,0 +1,189 @@
+//! Detection of references to missing or free objects
+//! Created: 2026-10-16 09:04:13 UTC
+//! Author: kartik4091
+//!
+//! A reference whose target is absent resolves to null, but readers differ
+//! on how hard they look: some rebuild the xref and pick up unindexed
+//! definitions, others stop at the free entry. Dangling references are
+//! therefore both a corruption symptom and a way to show different content
+//! to different tools.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    objects::scan_objects,
+    taxonomy::ObjectType,
+    xref::{effective_entries, read_sections, XrefState},
+};
+
+/// Why a reference cannot be resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum DanglingKind {
+    /// No xref entry and no definition anywhere in the file
+    Missing,
+
+    /// The xref marks the object number free
+    Free,
+
+    /// The object exists with a different generation number
+    GenerationMismatch,
+
+    /// A definition exists in the bytes but no xref entry points at it
+    Unindexed,
+}
+
+/// Where a reference occurs
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReferenceSite {
+    /// Referring object, `None` for the trailer
+    pub object: Option<ObjectId>,
+
+    /// What the referring object is (e.g. `Page 2`, `Annot`, `Action`)
+    pub context: String,
+
+    /// Key path inside the referring object
+    pub path: String,
+}
+
+/// A target that cannot be resolved, with every place referring to it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DanglingReference {
+    /// Referenced object number and generation
+    pub target: ObjectId,
+
+    /// Why the reference does not resolve
+    pub kind: DanglingKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Places the target is referenced from
+    pub sites: Vec<ReferenceSite>,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Finds every reference in the document that does not resolve
+#[instrument(skip(data, doc))]
+pub fn detect(data: &[u8], doc: &Document) -> Vec<DanglingReference> {
+    let xref = read_sections(data).map(|s| effective_entries(&s)).unwrap_or_default();
+    let defined: BTreeSet<ObjectId> = scan_objects(data).into_iter().map(|raw| raw.id).collect();
+    let pages: BTreeMap<ObjectId, u32> = doc.get_pages().into_iter().map(|(number, id)| (id, number)).collect();
+
+    let mut sites: BTreeMap<ObjectId, Vec<ReferenceSite>> = BTreeMap::new();
+    let mut record = |object: Option<ObjectId>, context: &str, found: Vec<(ObjectId, String)>| {
+        for (target, path) in found {
+            if !doc.objects.contains_key(&target) {
+                sites.entry(target).or_default().push(ReferenceSite {
+                    object,
+                    context: context.to_string(),
+                    path,
+                });
+            }
+        }
+    };
+
+    let mut found = Vec::new();
+    for (key, value) in doc.trailer.iter() {
+        references(value, format!("/{}", String::from_utf8_lossy(key)), &mut found);
+    }
+    record(None, "trailer", std::mem::take(&mut found));
+
+    for (&id, object) in &doc.objects {
+        references(object, String::new(), &mut found);
+        let context = match pages.get(&id) {
+            Some(number) => format!("Page {}", number),
+            None => ObjectType::of(object).to_string(),
+        };
+        record(Some(id), &context, std::mem::take(&mut found));
+    }
+
+    let dangling: Vec<DanglingReference> = sites
+        .into_iter()
+        .map(|(target, sites)| {
+            let kind = if defined.contains(&target) {
+                DanglingKind::Unindexed
+            } else if doc.objects.keys().any(|id| id.0 == target.0) {
+                DanglingKind::GenerationMismatch
+            } else if matches!(xref.get(&target.0), Some(XrefState::Free { .. })) {
+                DanglingKind::Free
+            } else {
+                DanglingKind::Missing
+            };
+            let severity = match kind {
+                DanglingKind::Unindexed => Severity::High,
+                DanglingKind::Free | DanglingKind::GenerationMismatch => Severity::Medium,
+                DanglingKind::Missing => Severity::Low,
+            };
+            let description = format!(
+                "{:?} object {} {} referenced from {}",
+                kind,
+                target.0,
+                target.1,
+                sites.iter().map(|s| format!("{}{}", s.context, s.path)).collect::<Vec<_>>().join(", ")
+            );
+            DanglingReference { target, kind, severity, sites, description }
+        })
+        .collect();
+
+    debug!("Found {} dangling references", dangling.len());
+    dangling
+}
+
+/// Collects references with the key path leading to each
+fn references(obj: &Object, path: String, out: &mut Vec<(ObjectId, String)>) {
+    let dict = match obj {
+        Object::Reference(id) => return out.push((*id, path)),
+        Object::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                references(item, format!("{}[{}]", path, index), out);
+            }
+            return;
+        }
+        Object::Dictionary(dict) => dict,
+        Object::Stream(stream) => &stream.dict,
+        _ => return,
+    };
+    for (key, value) in dict.iter() {
+        references(value, format!("{}/{}", path, String::from_utf8_lossy(key)), out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_clean_document() {
+        let data = fixtures::simple_pdf();
+        let doc = Document::load_mem(&data).unwrap();
+        assert!(detect(&data, &doc).is_empty());
+    }
+
+    #[test]
+    fn test_missing_and_unindexed_targets() {
+        let mut data = fixtures::append_revision(
+            &fixtures::simple_pdf(),
+            &[(5, "<< /Type /Page /Parent 1 0 R /Contents 4 0 R /Annots [40 0 R 41 0 R] >>")],
+        );
+        // Defined after the last xref section, so no entry points at it
+        data.extend_from_slice(b"41 0 obj\n<< /Type /Annot /Subtype /Link >>\nendobj\n");
+        let doc = Document::load_mem(&data).unwrap();
+
+        let dangling = detect(&data, &doc);
+        assert_eq!(dangling.len(), 2);
+        assert_eq!(dangling[0].kind, DanglingKind::Missing);
+        assert_eq!(dangling[0].sites[0].context, "Page 1");
+        assert_eq!(dangling[0].sites[0].path, "/Annots[0]");
+        assert_eq!(dangling[1].kind, DanglingKind::Unindexed);
+        assert_eq!(dangling[1].severity, Severity::High);
+    }
+}
@@ -0,0 +1,378 @@
+//! JBIG2 and CCITT decoder abuse heuristics
+//! Created: 2026-10-16 10:28:03 UTC
+//! Author: kartik4091
+//!
+//! Bi-level image codecs are a recurring source of reader memory
+//! corruption, and exploit streams rarely look like real scans: tens of
+//! thousands of segments, symbol dictionaries exporting more symbols than
+//! they define or import, page bitmaps of billions of pixels, or fax
+//! parameters no scanner writes. These checks read only segment headers
+//! and decode parameters; no image data is decoded.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::{
+    document::{jbig2, resolve},
+    limits,
+};
+
+/// Segments in one stream beyond which the data is not an image
+const MAX_SEGMENTS: usize = 65_536;
+
+/// Symbols a single dictionary may export or define
+const MAX_SYMBOLS: u64 = 1 << 20;
+
+/// Pixels of a page bitmap; 2^31 bits is already a 256 MiB buffer
+const MAX_PAGE_PIXELS: u64 = 1 << 31;
+
+/// Widest fax line accepted; real devices stay well below this
+const MAX_COLUMNS: i64 = 1 << 16;
+
+/// Page height marking "unknown, striped"
+const UNKNOWN_HEIGHT: u32 = 0xFFFF_FFFF;
+
+/// Kind of decoder abuse indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum DecoderAnomalyKind {
+    /// More JBIG2 segments than any image needs
+    SegmentFlood,
+
+    /// JBIG2 segment running past the end of the stream
+    TruncatedSegment,
+
+    /// Symbol dictionary declaring an absurd number of symbols
+    OversizedSymbolDictionary,
+
+    /// Symbol dictionary exporting more symbols than it defines and imports
+    InconsistentSymbolCount,
+
+    /// JBIG2 page bitmap too large to allocate
+    OversizedPage,
+
+    /// Codec dimensions disagree with the image's /Width or /Height
+    DimensionMismatch,
+
+    /// CCITTFaxDecode parameter outside its valid range
+    InvalidFaxParameter,
+}
+
+/// A decoder abuse indicator on one stream
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DecoderAnomaly {
+    /// Indicator kind
+    pub kind: DecoderAnomalyKind,
+
+    /// Image stream
+    pub id: ObjectId,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Checks every JBIG2Decode and CCITTFaxDecode stream of the document
+#[instrument(skip_all)]
+pub fn check(doc: &Document) -> Vec<DecoderAnomaly> {
+    let mut anomalies = Vec::new();
+    for (&id, object) in &doc.objects {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        let mut report = |kind, severity, description: String| {
+            anomalies.push(DecoderAnomaly {
+                kind,
+                id,
+                severity,
+                description: format!("Object {} {}: {}", id.0, id.1, description),
+            })
+        };
+        if jbig2::is_jbig2(stream) {
+            check_jbig2(doc, stream, &mut report);
+        }
+        if let Some(parms) = fax_parms(doc, stream) {
+            check_fax(stream, parms, &mut report);
+        }
+    }
+    debug!("Found {} decoder abuse indicators", anomalies.len());
+    anomalies
+}
+
+fn check_jbig2(doc: &Document, stream: &Stream, report: &mut impl FnMut(DecoderAnomalyKind, Severity, String)) {
+    let Some(data) = jbig2::encoded_data(stream) else {
+        return;
+    };
+    let globals_data = jbig2::globals_data(doc, stream).unwrap_or_default();
+    let globals = jbig2::parse(&globals_data);
+    let parsed = jbig2::parse(&data);
+
+    if parsed.segments.len() > MAX_SEGMENTS {
+        report(
+            DecoderAnomalyKind::SegmentFlood,
+            Severity::Medium,
+            format!("{} JBIG2 segments in one image", parsed.segments.len()),
+        );
+    }
+    if let Some(offset) = parsed.truncated_at {
+        report(
+            DecoderAnomalyKind::TruncatedSegment,
+            Severity::Medium,
+            format!("JBIG2 segment at offset {} runs past the end of the stream", offset),
+        );
+    }
+
+    // Symbols exported by each dictionary, for the imports of later ones
+    let mut exported: BTreeMap<u32, u64> = BTreeMap::new();
+    for (segment, data) in globals
+        .segments
+        .iter()
+        .map(|s| (s, &globals_data[s.data.clone()]))
+        .chain(parsed.segments.iter().map(|s| (s, &data[s.data.clone()])))
+    {
+        match segment.kind {
+            jbig2::SYMBOL_DICTIONARY => {
+                let Some((exports, defines)) = symbol_counts(data) else {
+                    continue;
+                };
+                let imports: u64 = segment.referred.iter().filter_map(|r| exported.get(r)).sum();
+                if exports > MAX_SYMBOLS || defines > MAX_SYMBOLS {
+                    report(
+                        DecoderAnomalyKind::OversizedSymbolDictionary,
+                        Severity::High,
+                        format!(
+                            "JBIG2 symbol dictionary {} declares {} new and {} exported symbols",
+                            segment.number, defines, exports
+                        ),
+                    );
+                } else if exports > imports + defines {
+                    report(
+                        DecoderAnomalyKind::InconsistentSymbolCount,
+                        Severity::High,
+                        format!(
+                            "JBIG2 symbol dictionary {} exports {} symbols but has only {} new and {} imported",
+                            segment.number, exports, defines, imports
+                        ),
+                    );
+                }
+                exported.insert(segment.number, exports);
+            }
+            jbig2::PAGE_INFORMATION => check_page(stream, segment.number, data, report),
+            _ => {}
+        }
+    }
+}
+
+/// Exported and new symbol counts of a symbol dictionary segment (T.88
+/// 7.4.2.1)
+fn symbol_counts(data: &[u8]) -> Option<(u64, u64)> {
+    let flags = u16::from_be_bytes([*data.first()?, *data.get(1)?]);
+    let huffman = flags & 0x0001 != 0;
+    let refinement = flags & 0x0002 != 0;
+    let template = (flags >> 10) & 0x3;
+    let refinement_template = (flags >> 12) & 0x1;
+
+    let mut at = 2;
+    if !huffman {
+        at += if template == 0 { 8 } else { 2 };
+    }
+    if refinement && refinement_template == 0 {
+        at += 4;
+    }
+    let u32_at = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as u64)
+    };
+    Some((u32_at(at)?, u32_at(at + 4)?))
+}
+
+/// Page bitmap size against allocation limits and the image dictionary
+fn check_page(
+    stream: &Stream,
+    number: u32,
+    data: &[u8],
+    report: &mut impl FnMut(DecoderAnomalyKind, Severity, String),
+) {
+    let u32_at = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let (Some(width), Some(height)) = (u32_at(0), u32_at(4)) else {
+        return;
+    };
+
+    if height != UNKNOWN_HEIGHT && width as u64 * height as u64 > MAX_PAGE_PIXELS {
+        report(
+            DecoderAnomalyKind::OversizedPage,
+            Severity::High,
+            format!(
+                "JBIG2 page information segment {} declares a {}x{} bitmap",
+                number, width, height
+            ),
+        );
+    }
+    if let Some(declared) = dimension(&stream.dict, b"Width").filter(|&w| w != width as i64) {
+        report(
+            DecoderAnomalyKind::DimensionMismatch,
+            Severity::Medium,
+            format!("JBIG2 page width {} differs from image /Width {}", width, declared),
+        );
+    }
+}
+
+/// CCITTFaxDecode parameters of the stream, if it uses that filter
+fn fax_parms<'a>(doc: &'a Document, stream: &'a Stream) -> Option<Option<&'a Dictionary>> {
+    let filters = limits::filters(&stream.dict);
+    let index = filters.iter().position(|f| f.as_slice() == b"CCITTFaxDecode")?;
+    let parms = stream.dict.get(b"DecodeParms").ok().map(|p| match resolve(doc, p) {
+        Object::Array(items) => items.get(index).map(|p| resolve(doc, p)),
+        other => Some(other),
+    });
+    Some(parms.flatten().and_then(|p| p.as_dict().ok()))
+}
+
+fn check_fax(
+    stream: &Stream,
+    parms: Option<&Dictionary>,
+    report: &mut impl FnMut(DecoderAnomalyKind, Severity, String),
+) {
+    let mut invalid = |detail: String| report(DecoderAnomalyKind::InvalidFaxParameter, Severity::High, detail);
+    let parameter = |key: &[u8]| parms.and_then(|p| p.get(key).ok());
+
+    if let Some(k) = parameter(b"K") {
+        if k.as_i64().is_err() {
+            invalid(format!("CCITT /K is not an integer: {:?}", k));
+        }
+    }
+    let columns = match parameter(b"Columns") {
+        Some(value) => match value.as_i64() {
+            Ok(columns) if columns > 0 && columns <= MAX_COLUMNS => Some(columns),
+            _ => {
+                invalid(format!("CCITT /Columns out of range: {:?}", value));
+                None
+            }
+        },
+        None => Some(1728),
+    };
+    let rows = match parameter(b"Rows") {
+        Some(value) => match value.as_i64() {
+            Ok(rows) if rows >= 0 => Some(rows),
+            _ => {
+                invalid(format!("CCITT /Rows out of range: {:?}", value));
+                None
+            }
+        },
+        None => None,
+    };
+
+    for (name, key, value) in [
+        ("Columns", b"Width".as_slice(), columns),
+        ("Rows", b"Height".as_slice(), rows),
+    ] {
+        let (Some(value), Some(declared)) = (value, dimension(&stream.dict, key)) else {
+            continue;
+        };
+        if value != declared {
+            report(
+                DecoderAnomalyKind::DimensionMismatch,
+                Severity::Medium,
+                format!(
+                    "CCITT /{} {} differs from image /{} {}",
+                    name,
+                    value,
+                    String::from_utf8_lossy(key),
+                    declared
+                ),
+            );
+        }
+    }
+}
+
+fn dimension(dict: &Dictionary, key: &[u8]) -> Option<i64> {
+    dict.get(key).ok()?.as_i64().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    /// A short-form segment header followed by its data
+    fn segment(number: u32, kind: u8, referred: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut out = number.to_be_bytes().to_vec();
+        out.push(kind);
+        out.push((referred.len() as u8) << 5);
+        out.extend_from_slice(referred);
+        out.push(1);
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    #[test]
+    fn test_symbol_and_page_counts() {
+        // Huffman-coded dictionary exporting 4096 symbols out of 1 new one
+        let mut dictionary = vec![0, 1];
+        dictionary.extend_from_slice(&4096u32.to_be_bytes());
+        dictionary.extend_from_slice(&1u32.to_be_bytes());
+        let mut page = 100_000u32.to_be_bytes().to_vec();
+        page.extend_from_slice(&100_000u32.to_be_bytes());
+        page.extend_from_slice(&[0; 11]);
+
+        let mut data = segment(0, jbig2::SYMBOL_DICTIONARY, &[], &dictionary);
+        data.extend(segment(1, jbig2::PAGE_INFORMATION, &[], &page));
+
+        let mut doc = fixtures::document();
+        let image = doc.add_object(Stream::new(
+            dictionary! { "Subtype" => "Image", "Width" => 100_000, "Filter" => "JBIG2Decode" },
+            data,
+        ));
+        let kinds: Vec<_> = check(&doc).iter().map(|a| (a.kind, a.id)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (DecoderAnomalyKind::InconsistentSymbolCount, image),
+                (DecoderAnomalyKind::OversizedPage, image),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fax_parameters() {
+        let mut doc = fixtures::document();
+        doc.add_object(Stream::new(
+            dictionary! {
+                "Subtype" => "Image",
+                "Width" => 1728,
+                "Height" => 2200,
+                "Filter" => "CCITTFaxDecode",
+                "DecodeParms" => dictionary! { "K" => -1, "Columns" => 1728, "Rows" => 2200 },
+            },
+            vec![0; 16],
+        ));
+        assert!(check(&doc).is_empty());
+
+        let crafted = doc.add_object(Stream::new(
+            dictionary! {
+                "Subtype" => "Image",
+                "Width" => 8,
+                "Filter" => "CCITTFaxDecode",
+                "DecodeParms" => dictionary! { "K" => 0.5, "Columns" => 0x7FFF_FFFF },
+            },
+            vec![0; 16],
+        ));
+        let anomalies = check(&doc);
+        assert_eq!(anomalies.len(), 2);
+        assert!(anomalies.iter().all(|a| a.id == crafted));
+        assert!(anomalies
+            .iter()
+            .all(|a| a.kind == DecoderAnomalyKind::InvalidFaxParameter));
+    }
+}
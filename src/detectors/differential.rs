@@ -0,0 +1,265 @@
+//! Parser-differential simulation across viewer behaviors
+//! Created: 2026-10-16 08:36:51 UTC
+//! Author: kartik4091
+//!
+//! The specification leaves several malformed constructs undefined, and
+//! viewers resolve them differently. This pass re-reads every object under
+//! a small set of viewer models and reports the objects whose resolved
+//! content is not the same for all of them — i.e. the places where a file
+//! can look different depending on which application opens it.
+//!
+//! The models encode the default behaviors documented for each engine:
+//!
+//! | Viewer  | Duplicate keys | Object lookup             | Stream extent |
+//! |---------|----------------|---------------------------|---------------|
+//! | Acrobat | first wins     | xref offset               | `endstream`   |
+//! | PDFium  | last wins      | xref offset               | /Length       |
+//! | MuPDF   | last wins      | last definition in file   | /Length       |
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{xref::XrefEntry, Document, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::document::{
+    object_digest,
+    objects::scan_objects,
+    parser::{parse_indirect_with, KeyPolicy, LengthPolicy, ParseOptions},
+    printable_preview,
+    taxonomy::ObjectType,
+    to_hex,
+};
+
+/// How a viewer picks between several definitions of the same object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObjectLookup {
+    /// Follow the cross-reference table
+    Xref,
+
+    /// Use the last `N G obj` definition in the byte stream
+    LastDefinition,
+}
+
+/// Resolution behavior attributed to a viewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewerModel {
+    /// Viewer name used in reports
+    pub name: &'static str,
+
+    /// Object lookup strategy
+    pub lookup: ObjectLookup,
+
+    /// Parser options for duplicate keys and stream extent
+    pub parse: ParseOptions,
+}
+
+/// Viewer models compared by the simulation
+pub const VIEWER_MODELS: [ViewerModel; 3] = [
+    ViewerModel {
+        name: "Acrobat",
+        lookup: ObjectLookup::Xref,
+        parse: ParseOptions {
+            duplicate_keys: KeyPolicy::First,
+            stream_length: LengthPolicy::Endstream,
+        },
+    },
+    ViewerModel {
+        name: "PDFium",
+        lookup: ObjectLookup::Xref,
+        parse: ParseOptions {
+            duplicate_keys: KeyPolicy::Last,
+            stream_length: LengthPolicy::Declared,
+        },
+    },
+    ViewerModel {
+        name: "MuPDF",
+        lookup: ObjectLookup::LastDefinition,
+        parse: ParseOptions {
+            duplicate_keys: KeyPolicy::Last,
+            stream_length: LengthPolicy::Declared,
+        },
+    },
+];
+
+/// Ambiguous construct that caused a divergence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub enum Ambiguity {
+    /// A dictionary repeats a key with different values
+    DuplicateKeys,
+
+    /// The xref points at a different definition than the last one in the file
+    ConflictingDefinitions,
+
+    /// Declared /Length and the `endstream` keyword disagree
+    StreamLength,
+}
+
+/// How one viewer model resolved an object
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ViewerResolution {
+    /// Viewer name
+    pub viewer: String,
+
+    /// Offset of the definition that was used
+    pub offset: Option<usize>,
+
+    /// Type of the resolved object
+    pub object_type: Option<ObjectType>,
+
+    /// Hex digest of the resolved object, `None` if it failed to parse
+    pub digest: Option<String>,
+
+    /// Printable excerpt of the resolved object
+    pub preview: Option<String>,
+}
+
+/// An object that resolves to different content across viewers
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ParserDivergence {
+    /// Affected object
+    pub id: ObjectId,
+
+    /// Constructs responsible for the divergence
+    pub ambiguities: Vec<Ambiguity>,
+
+    /// Per-viewer resolution
+    pub resolutions: Vec<ViewerResolution>,
+}
+
+/// Resolves every object under each viewer model and reports divergences
+#[instrument(skip(data, doc))]
+pub fn simulate_viewers(data: &[u8], doc: &Document) -> Vec<ParserDivergence> {
+    let mut last_definition: BTreeMap<ObjectId, usize> = BTreeMap::new();
+    for raw in scan_objects(data) {
+        last_definition.insert(raw.id, raw.offset);
+    }
+
+    let mut ids: BTreeSet<ObjectId> = last_definition.keys().copied().collect();
+    for (&num, entry) in &doc.reference_table.entries {
+        if let XrefEntry::Normal { generation, .. } = entry {
+            ids.insert((num, *generation));
+        }
+    }
+
+    let divergences: Vec<ParserDivergence> = ids
+        .into_iter()
+        .filter_map(|id| {
+            let xref_offset = match doc.reference_table.get(id.0) {
+                Some(XrefEntry::Normal { offset, generation }) if *generation == id.1 => {
+                    Some(*offset as usize)
+                }
+                _ => None,
+            };
+            compare(data, id, xref_offset, last_definition.get(&id).copied())
+        })
+        .collect();
+
+    debug!("Found {} parser divergences", divergences.len());
+    divergences
+}
+
+fn compare(
+    data: &[u8],
+    id: ObjectId,
+    xref_offset: Option<usize>,
+    last_offset: Option<usize>,
+) -> Option<ParserDivergence> {
+    let mut ambiguities = BTreeSet::new();
+    let mut resolutions = Vec::new();
+
+    for model in &VIEWER_MODELS {
+        let offset = match model.lookup {
+            ObjectLookup::Xref => xref_offset,
+            ObjectLookup::LastDefinition => last_offset.or(xref_offset),
+        };
+
+        let parsed = offset.and_then(|offset| parse_indirect_with(data, offset, model.parse).ok());
+        if let Some(parsed) = &parsed {
+            if parsed.duplicate_keys > 0 {
+                ambiguities.insert(Ambiguity::DuplicateKeys);
+            }
+        }
+
+        resolutions.push(ViewerResolution {
+            viewer: model.name.to_string(),
+            offset,
+            object_type: parsed.as_ref().map(|p| ObjectType::of(&p.object)),
+            digest: parsed.as_ref().map(|p| to_hex(&object_digest(&p.object))),
+            preview: parsed.as_ref().zip(offset).map(|(p, offset)| {
+                printable_preview(&data[offset..p.end.min(data.len())], 80)
+            }),
+        });
+    }
+
+    let distinct: BTreeSet<_> = resolutions.iter().map(|r| r.digest.clone()).collect();
+    if distinct.len() <= 1 {
+        return None;
+    }
+
+    if xref_offset.is_some() && last_offset.is_some() && xref_offset != last_offset {
+        ambiguities.insert(Ambiguity::ConflictingDefinitions);
+    }
+    if length_disagrees(data, &resolutions) {
+        ambiguities.insert(Ambiguity::StreamLength);
+    }
+
+    Some(ParserDivergence {
+        id,
+        ambiguities: ambiguities.into_iter().collect(),
+        resolutions,
+    })
+}
+
+/// Detects stream extent disagreement by re-parsing the xref definition
+/// with both length policies
+fn length_disagrees(data: &[u8], resolutions: &[ViewerResolution]) -> bool {
+    resolutions.iter().filter_map(|r| r.offset).any(|offset| {
+        let parse = |stream_length| {
+            parse_indirect_with(data, offset, ParseOptions { stream_length, ..Default::default() })
+                .ok()
+                .and_then(|p| p.object.as_stream().ok().map(|s| s.content.len()))
+        };
+        parse(LengthPolicy::Declared) != parse(LengthPolicy::Endstream)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_clean_document_is_consistent() {
+        let data = fixtures::simple_pdf();
+        let doc = Document::load_mem(&data).unwrap();
+        assert!(simulate_viewers(&data, &doc).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_keys_diverge() {
+        let base = fixtures::simple_pdf();
+        let data = fixtures::append_revision(
+            &base,
+            &[(7, "<< /Type /Action /S /URI /URI (https://a.example) /URI (https://b.example) >>")],
+        );
+        let doc = Document::load_mem(&data).unwrap();
+
+        let divergences = simulate_viewers(&data, &doc);
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].id, (7, 0));
+        assert!(divergences[0].ambiguities.contains(&Ambiguity::DuplicateKeys));
+    }
+
+    #[test]
+    fn test_shadow_definition_after_xref_diverges() {
+        let mut data = fixtures::simple_pdf();
+        data.extend_from_slice(b"\n6 0 obj\n<< /Type /Catalog /Pages 1 0 R /OpenAction 9 0 R >>\nendobj\n");
+        let doc = Document::load_mem(&data).unwrap();
+
+        let divergences = simulate_viewers(&data, &doc);
+        let catalog = divergences.iter().find(|d| d.id == fixtures::CATALOG_ID).unwrap();
+        assert!(catalog.ambiguities.contains(&Ambiguity::ConflictingDefinitions));
+    }
+}
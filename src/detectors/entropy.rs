@@ -0,0 +1,214 @@
+//! Per-stream entropy and packed payload detection
+//! Created: 2026-10-16 09:17:46 UTC
+//! Author: kartik4091
+//!
+//! Once its filters are undone, ordinary stream content — page operators,
+//! fonts, metadata — is far from random. Decoded data that still looks
+//! random is either media with its own compression (images, font
+//! programs, attachments) or a payload that was packed or encrypted before
+//! being stored, which is how droppers smuggle their second stage.
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    magic::{identify, MagicKind},
+    name_value, shannon_entropy, stream_data,
+    taxonomy::ObjectType,
+};
+
+/// Decoded entropy (bits per byte) above which data counts as packed
+const PACKED_ENTROPY: f64 = 7.2;
+
+/// Streams smaller than this are too short for a meaningful estimate
+const MIN_PACKED_SIZE: usize = 512;
+
+/// Filters whose output is already compressed media
+const MEDIA_FILTERS: [&[u8]; 4] = [b"DCTDecode", b"JPXDecode", b"JBIG2Decode", b"CCITTFaxDecode"];
+
+/// Entropy measurements of one stream
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StreamEntropy {
+    /// Stream object
+    pub id: ObjectId,
+
+    /// Stream type
+    pub object_type: ObjectType,
+
+    /// Stored size in bytes
+    pub raw_size: usize,
+
+    /// Entropy of the stored bytes
+    pub raw_entropy: f64,
+
+    /// Decoded size, `None` if the filters fail
+    pub decoded_size: Option<usize>,
+
+    /// Entropy of the decoded bytes
+    pub decoded_entropy: Option<f64>,
+}
+
+/// A stream whose decoded content looks packed or encrypted
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PackedStream {
+    /// Stream object
+    pub id: ObjectId,
+
+    /// Entropy that triggered the finding
+    pub entropy: f64,
+
+    /// Format recognized at the start of the decoded data
+    pub format: Option<String>,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Entropy of every stream and the streams flagged as packed
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct EntropyReport {
+    /// Per-stream measurements, in object order
+    pub streams: Vec<StreamEntropy>,
+
+    /// Likely packed or encrypted payloads
+    pub packed: Vec<PackedStream>,
+}
+
+/// Measures every stream of the document.
+///
+/// Encrypted documents are measured but not flagged, since all of their
+/// streams are expected to look random. Neither are streams whose filters
+/// fail: their stored bytes are still compressed, so they look random too.
+#[instrument(skip(doc))]
+pub fn analyze(doc: &Document) -> EntropyReport {
+    let encrypted = doc.trailer.has(b"Encrypt");
+    let mut report = EntropyReport::default();
+
+    for (&id, object) in &doc.objects {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        let object_type = ObjectType::of(object);
+        if matches!(object_type, ObjectType::ObjectStream | ObjectType::XrefStream) {
+            continue;
+        }
+
+        let decoded = stream_data(stream).ok();
+        let measured = StreamEntropy {
+            id,
+            object_type,
+            raw_size: stream.content.len(),
+            raw_entropy: shannon_entropy(&stream.content),
+            decoded_size: decoded.as_ref().map(Vec::len),
+            decoded_entropy: decoded.as_deref().map(shannon_entropy),
+        };
+
+        if let (Some(data), Some(entropy)) = (&decoded, measured.decoded_entropy) {
+            if !encrypted && data.len() >= MIN_PACKED_SIZE && entropy > PACKED_ENTROPY && !is_media(&stream.dict) {
+                report.packed.push(packed(id, &measured.object_type, entropy, data));
+            }
+        }
+        report.streams.push(measured);
+    }
+
+    debug!("Measured {} streams, {} look packed", report.streams.len(), report.packed.len());
+    report
+}
+
+fn packed(id: ObjectId, object_type: &ObjectType, entropy: f64, data: &[u8]) -> PackedStream {
+    let magic = identify(data);
+    let severity = match (magic.map(|m| m.kind), object_type) {
+        (Some(MagicKind::Executable), _) => Severity::High,
+        // Attachments are often archives; they are reported by type elsewhere
+        (_, ObjectType::EmbeddedFile) => Severity::Low,
+        (Some(MagicKind::Archive), _) | (Some(MagicKind::Image), _) => Severity::Low,
+        _ => Severity::Medium,
+    };
+    let format = magic.map(|m| m.description.to_string());
+    let described = format.as_ref().map(|f| format!(", {}", f)).unwrap_or_default();
+
+    PackedStream {
+        id,
+        entropy,
+        format,
+        severity,
+        description: format!(
+            "{} stream {} {} has {:.2} bits/byte of decoded entropy{}",
+            object_type, id.0, id.1, entropy, described
+        ),
+    }
+}
+
+/// Images and font programs carry their own compression
+fn is_media(dict: &Dictionary) -> bool {
+    let filters: Vec<&[u8]> = match dict.get(b"Filter") {
+        Ok(Object::Name(name)) => vec![name.as_slice()],
+        Ok(Object::Array(items)) => items.iter().filter_map(|f| f.as_name().ok()).collect(),
+        _ => Vec::new(),
+    };
+
+    matches!(name_value(dict, b"Subtype"), Some(b"Image" | b"Type1C" | b"CIDFontType0C" | b"OpenType"))
+        || filters.iter().any(|f| MEDIA_FILTERS.contains(f))
+        || [&b"Length1"[..], b"Length2", b"Length3"].iter().any(|key| dict.has(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    /// Deterministic bytes with close to 8 bits/byte of entropy
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plain_content_is_not_packed() {
+        let report = analyze(&fixtures::document());
+        assert_eq!(report.streams.len(), 1);
+        assert!(report.streams[0].decoded_entropy.unwrap() < PACKED_ENTROPY);
+        assert!(report.packed.is_empty());
+    }
+
+    #[test]
+    fn test_packed_payload_is_flagged() {
+        let mut doc = fixtures::document();
+        let payload = doc.add_object(Stream::new(dictionary! {}, noise(4096)));
+        doc.add_object(Stream::new(dictionary! { "Subtype" => "Image" }, noise(4096)));
+        let mut exe = b"MZ\x90\x00".to_vec();
+        exe.extend(noise(4096));
+        let exe = doc.add_object(Stream::new(dictionary! {}, exe));
+
+        let report = analyze(&doc);
+        let flagged: Vec<_> = report.packed.iter().map(|p| (p.id, p.severity)).collect();
+        assert_eq!(flagged, vec![(payload, Severity::Medium), (exe, Severity::High)]);
+        assert_eq!(report.streams.len(), 4);
+    }
+
+    #[test]
+    fn test_undecodable_stream_is_not_flagged() {
+        let mut doc = fixtures::document();
+        let broken = doc.add_object(Stream::new(dictionary! { "Filter" => "FlateDecode" }, noise(4096)));
+
+        let report = analyze(&doc);
+        let measured = report.streams.iter().find(|s| s.id == broken).unwrap();
+        assert_eq!(measured.decoded_size, None);
+        assert!(measured.raw_entropy > PACKED_ENTROPY);
+        assert!(report.packed.is_empty());
+    }
+}
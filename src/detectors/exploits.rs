@@ -0,0 +1,313 @@
+//! Known-exploit signatures
+//! Created: 2026-10-16 10:22:14 UTC
+//! Author: kartik4091
+//!
+//! A small signature pack for PDF exploit patterns that are still
+//! recycled by commodity kits: vulnerable Acrobat JavaScript APIs called
+//! the way public exploits call them, TrueType `SING` tables with an
+//! unterminated name, Flate predictors with an absurd colour count, TIFF
+//! images smuggled into XFA, and JBIG2 segments referring to segments
+//! that do not precede them. A match names the CVE the pattern is known
+//! from; since the same pattern can be reused against a patched reader,
+//! it is reported as probable.
+
+use std::collections::BTreeSet;
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{xfa::XfaReport, Severity};
+use crate::document::{javascript, jbig2, resolve, stream_data, walk_dictionaries};
+
+/// Predictor colour counts above this are not produced by any writer
+const MAX_PREDICTOR_COLORS: i64 = 256;
+
+/// Length of the `uniqueName` field of a SING table
+const SING_NAME_LENGTH: usize = 28;
+
+/// JavaScript API abuse, as `(CVE, signature, pattern, severity)`
+const SCRIPT_SIGNATURES: [(&str, &str, &str, Severity); 7] = [
+    (
+        "CVE-2008-2992",
+        "util.printf format overflow",
+        r#"util\.printf\s*\(\s*["']%[0-9.]{4,}f"#,
+        Severity::Critical,
+    ),
+    (
+        "CVE-2007-5659",
+        "Collab.collectEmailInfo overflow",
+        r"Collab\.collectEmailInfo\s*\(",
+        Severity::High,
+    ),
+    (
+        "CVE-2009-0927",
+        "Collab.getIcon overflow",
+        r"Collab\.getIcon\s*\(",
+        Severity::High,
+    ),
+    (
+        "CVE-2009-1492",
+        "getAnnots negative page",
+        r"getAnnots\s*\(\s*-\d+\s*,",
+        Severity::High,
+    ),
+    (
+        "CVE-2009-1493",
+        "spell.customDictionaryOpen overflow",
+        r"spell\.customDictionaryOpen\s*\(",
+        Severity::High,
+    ),
+    (
+        "CVE-2009-4324",
+        "media.newPlayer use-after-free",
+        r"media\.newPlayer\s*\(\s*null",
+        Severity::Critical,
+    ),
+    (
+        "CVE-2010-4091",
+        "printSeps memory corruption",
+        r"\.printSeps\s*\(",
+        Severity::High,
+    ),
+];
+
+/// A structural or script pattern of a known exploit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ExploitMatch {
+    /// Probable CVE identifier
+    pub cve: String,
+
+    /// Signature name
+    pub signature: String,
+
+    /// Object carrying the pattern, if known
+    pub object: Option<ObjectId>,
+
+    /// Finding severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Matches the signature pack against the document and its XFA packets
+#[instrument(skip_all)]
+pub fn scan(doc: &Document, xfa: Option<&XfaReport>) -> Vec<ExploitMatch> {
+    let mut matches = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut report = |cve: &str, signature: &str, object: Option<ObjectId>, severity: Severity| {
+        if !seen.insert((cve.to_string(), object)) {
+            return;
+        }
+        let location = match object {
+            Some(id) => format!(" in object {} {}", id.0, id.1),
+            None => String::new(),
+        };
+        matches.push(ExploitMatch {
+            cve: cve.to_string(),
+            signature: signature.to_string(),
+            object,
+            severity,
+            description: format!("{}{} (probable {})", signature, location, cve),
+        });
+    };
+
+    let signatures: Vec<_> = SCRIPT_SIGNATURES
+        .iter()
+        .map(|&(cve, name, pattern, severity)| {
+            (cve, name, Regex::new(pattern).expect("valid exploit pattern"), severity)
+        })
+        .collect();
+    let mut scripts: Vec<(Option<ObjectId>, String)> = javascript(doc)
+        .into_iter()
+        .map(|(id, script)| (Some(id), script))
+        .collect();
+    if let Some(xfa) = xfa {
+        scripts.extend(xfa.packets.iter().map(|packet| (packet.id, packet.xml.clone())));
+    }
+    for (object, script) in &scripts {
+        for (cve, name, pattern, severity) in &signatures {
+            if pattern.is_match(script) {
+                report(cve, name, *object, *severity);
+            }
+        }
+    }
+
+    // TIFF (II*\0 or MM\0*) base64-encoded as an XFA image
+    let tiff = Regex::new(r"(?s)<image[^>]*>\s*(?:SUkqA|TU0AK)").expect("valid XFA pattern");
+    for packet in xfa.map(|x| x.packets.as_slice()).unwrap_or_default() {
+        if tiff.is_match(&packet.xml) {
+            report("CVE-2010-0188", "TIFF image in XFA form", packet.id, Severity::Critical);
+        }
+    }
+
+    for (&id, object) in &doc.objects {
+        if let Object::Stream(stream) = object {
+            if predictor_colors(doc, stream).map_or(false, |colors| colors > MAX_PREDICTOR_COLORS) {
+                report(
+                    "CVE-2009-3459",
+                    "Flate predictor colour count overflow",
+                    Some(id),
+                    Severity::High,
+                );
+            }
+            if jbig2::is_jbig2(stream) && has_undefined_reference(doc, stream) {
+                report(
+                    "CVE-2009-0658",
+                    "JBIG2 segment refers to an undefined segment",
+                    Some(id),
+                    Severity::High,
+                );
+            }
+        }
+        walk_dictionaries(object, &mut |dict| {
+            for (program, font) in font_programs(doc, dict) {
+                if stream_data(font).map_or(false, |data| has_unterminated_sing(&data)) {
+                    report(
+                        "CVE-2010-2883",
+                        "TrueType SING table name overflow",
+                        Some(program),
+                        Severity::Critical,
+                    );
+                }
+            }
+        });
+    }
+
+    debug!("Matched {} exploit signatures", matches.len());
+    matches
+}
+
+/// Largest `/Colors` of the stream's decode parameters
+fn predictor_colors(doc: &Document, stream: &Stream) -> Option<i64> {
+    let parms = resolve(doc, stream.dict.get(b"DecodeParms").ok()?);
+    let colors = |parms: &Object| resolve(doc, parms).as_dict().ok()?.get(b"Colors").ok()?.as_i64().ok();
+    match parms {
+        Object::Array(items) => items.iter().filter_map(colors).max(),
+        other => colors(other),
+    }
+}
+
+/// Whether a segment refers to one that neither the globals nor earlier
+/// segments define
+fn has_undefined_reference(doc: &Document, stream: &Stream) -> bool {
+    let Some(data) = jbig2::encoded_data(stream) else {
+        return false;
+    };
+    let mut defined: BTreeSet<u32> = jbig2::globals(doc, stream)
+        .map(|globals| globals.segments.iter().map(|s| s.number).collect())
+        .unwrap_or_default();
+    for segment in jbig2::parse(&data).segments {
+        if segment.referred.iter().any(|r| !defined.contains(r)) {
+            return true;
+        }
+        defined.insert(segment.number);
+    }
+    false
+}
+
+/// Embedded TrueType/OpenType programs of a font descriptor
+fn font_programs<'a>(doc: &'a Document, dict: &'a Dictionary) -> Vec<(ObjectId, &'a Stream)> {
+    ["FontFile2", "FontFile3"]
+        .iter()
+        .filter_map(|key| match dict.get(key.as_bytes()).ok()? {
+            Object::Reference(id) => Some((*id, doc.get_object(*id).ok()?.as_stream().ok()?)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether an sfnt font has a SING table whose `uniqueName` is not
+/// NUL-terminated within its 28 bytes
+fn has_unterminated_sing(font: &[u8]) -> bool {
+    let u16_at = |at: usize| font.get(at..at + 2).map(|b| u16::from_be_bytes([b[0], b[1]]) as usize);
+    let u32_at = |at: usize| {
+        font.get(at..at + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+
+    let Some(tables) = u16_at(4) else {
+        return false;
+    };
+    for index in 0..tables {
+        let record = 12 + index * 16;
+        if font.get(record..record + 4) != Some(b"SING".as_slice()) {
+            continue;
+        }
+        let Some(offset) = u32_at(record + 8) else {
+            return false;
+        };
+        // uniqueName follows the 16-byte SING header
+        let start = offset.saturating_add(16);
+        return match font.get(start..) {
+            Some(name) => !name.iter().take(SING_NAME_LENGTH).any(|&b| b == 0),
+            None => false,
+        };
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_script_signatures() {
+        let mut doc = fixtures::document();
+        let action = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("var n = 12; util.printf(\"%45000.45000f\", n); this.print();"),
+        });
+
+        let matches = scan(&doc, None);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].cve, "CVE-2008-2992");
+        assert_eq!(matches[0].object, Some(action));
+        assert_eq!(matches[0].severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_sing_table_overflow() {
+        let mut font = vec![0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        font.extend_from_slice(b"SING");
+        font.extend_from_slice(&[0; 4]);
+        font.extend_from_slice(&28u32.to_be_bytes());
+        font.extend_from_slice(&64u32.to_be_bytes());
+        font.extend_from_slice(&[0; 16]);
+        font.extend_from_slice(&[b'A'; 48]);
+        assert!(has_unterminated_sing(&font));
+
+        let mut doc = fixtures::document();
+        let program = doc.add_object(Stream::new(dictionary! {}, font));
+        doc.add_object(dictionary! { "Type" => "FontDescriptor", "FontFile2" => program });
+        let matches = scan(&doc, None);
+        assert_eq!(matches[0].cve, "CVE-2010-2883");
+        assert_eq!(matches[0].object, Some(program));
+    }
+
+    #[test]
+    fn test_jbig2_undefined_reference() {
+        // Segment 1 (text region) refers to segment 5, which never appears
+        let mut data = vec![0, 0, 0, 1, 6, 0x20, 5, 1];
+        data.extend_from_slice(&2u32.to_be_bytes());
+        data.extend_from_slice(&[0, 0]);
+
+        let mut doc = fixtures::document();
+        let image = doc.add_object(Stream::new(dictionary! { "Filter" => "JBIG2Decode" }, data));
+        let matches = scan(&doc, None);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].cve, "CVE-2009-0658");
+        assert_eq!(matches[0].object, Some(image));
+
+        let clean = Stream::new(
+            dictionary! { "Filter" => "JBIG2Decode" },
+            vec![0, 0, 0, 0, 48, 0, 1, 0, 0, 0, 0],
+        );
+        assert!(!has_undefined_reference(&doc, &clean));
+    }
+}
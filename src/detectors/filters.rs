@@ -0,0 +1,252 @@
+//! Decode filter chain anomaly detection
+//! Created: 2026-10-16 09:08:26 UTC
+//! Author: kartik4091
+//!
+//! Legitimate writers use one compression filter, occasionally preceded by
+//! an image codec's parameters. Stacked text encodings, repeated filters,
+//! image codecs on non-image data and unknown filter names serve no purpose
+//! other than making content harder for scanners to read.
+
+use lopdf::{Dictionary, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{name_value, objects::scan_objects, parser::parse_indirect, stream_data};
+
+/// Filters defined by ISO 32000-1, Table 6
+const STANDARD_FILTERS: [&[u8]; 10] = [
+    b"ASCIIHexDecode",
+    b"ASCII85Decode",
+    b"LZWDecode",
+    b"FlateDecode",
+    b"RunLengthDecode",
+    b"CCITTFaxDecode",
+    b"JBIG2Decode",
+    b"DCTDecode",
+    b"JPXDecode",
+    b"Crypt",
+];
+
+/// Abbreviations only valid in inline images
+const INLINE_ABBREVIATIONS: [&[u8]; 7] = [b"AHx", b"A85", b"LZW", b"Fl", b"RL", b"CCF", b"DCT"];
+
+/// Image codecs, which must be the last filter in a chain
+const IMAGE_CODECS: [&[u8]; 4] = [b"CCITTFaxDecode", b"JBIG2Decode", b"DCTDecode", b"JPXDecode"];
+
+/// Text encodings of binary data
+const ASCII_FILTERS: [&[u8]; 2] = [b"ASCIIHexDecode", b"ASCII85Decode"];
+
+/// Kind of filter chain anomaly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum FilterAnomalyKind {
+    /// Filter name not defined by the specification
+    UnknownFilter,
+
+    /// Inline image abbreviation used on a stream object
+    AbbreviatedName,
+
+    /// More than two filters in one chain
+    LongChain,
+
+    /// The same filter applied more than once
+    RepeatedFilter,
+
+    /// Several ASCII encodings stacked
+    StackedAsciiEncoding,
+
+    /// Image codec followed by further filters
+    MisplacedImageCodec,
+
+    /// Image codec on a stream that is not an image
+    ImageCodecOnNonImage,
+
+    /// /Filter on a dictionary that has no stream data
+    FilterWithoutStream,
+
+    /// /DecodeParms does not line up with /Filter
+    ParmsMismatch,
+
+    /// The chain fails to decode
+    DecodeFailure,
+}
+
+/// A single filter chain anomaly
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FilterAnomaly {
+    /// Anomaly kind
+    pub kind: FilterAnomalyKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Affected object
+    pub id: ObjectId,
+
+    /// Filter chain as declared
+    pub filters: Vec<String>,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Checks the filter chain of every object definition in the file
+#[instrument(skip(data))]
+pub fn check(data: &[u8]) -> Vec<FilterAnomaly> {
+    let mut anomalies = Vec::new();
+    for raw in scan_objects(data) {
+        let Ok(parsed) = parse_indirect(data, raw.offset) else {
+            continue;
+        };
+        match &parsed.object {
+            Object::Stream(stream) => {
+                let found = check_chain(&stream.dict);
+                let decodes = stream_data(stream).is_ok();
+                let filters = filter_names(&stream.dict);
+                let mut push = |kind, severity, description: String| {
+                    anomalies.push(FilterAnomaly {
+                        kind,
+                        severity,
+                        id: parsed.id,
+                        filters: filters.iter().map(|f| String::from_utf8_lossy(f).into_owned()).collect(),
+                        description: format!("Object {} {}: {}", parsed.id.0, parsed.id.1, description),
+                    })
+                };
+                for (kind, severity, description) in found {
+                    push(kind, severity, description);
+                }
+                // Unknown filters already explain a decode failure
+                if !decodes && filters.iter().all(|f| STANDARD_FILTERS.contains(&f.as_slice())) {
+                    push(FilterAnomalyKind::DecodeFailure, Severity::Medium, "filter chain fails to decode".into());
+                }
+            }
+            Object::Dictionary(dict) if dict.has(b"Filter") && !dict.has(b"Length") => {
+                anomalies.push(FilterAnomaly {
+                    kind: FilterAnomalyKind::FilterWithoutStream,
+                    severity: Severity::Low,
+                    id: parsed.id,
+                    filters: filter_names(dict).iter().map(|f| String::from_utf8_lossy(f).into_owned()).collect(),
+                    description: format!("Object {} {}: /Filter on a dictionary without stream data", parsed.id.0, parsed.id.1),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    debug!("Found {} filter chain anomalies", anomalies.len());
+    anomalies
+}
+
+/// Filter names from a /Filter name or array
+fn filter_names(dict: &Dictionary) -> Vec<Vec<u8>> {
+    match dict.get(b"Filter") {
+        Ok(Object::Name(name)) => vec![name.clone()],
+        Ok(Object::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_name().ok().map(<[u8]>::to_vec))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn check_chain(dict: &Dictionary) -> Vec<(FilterAnomalyKind, Severity, String)> {
+    let filters = filter_names(dict);
+    let mut found = Vec::new();
+    let name = |f: &[u8]| String::from_utf8_lossy(f).into_owned();
+
+    for filter in &filters {
+        if INLINE_ABBREVIATIONS.contains(&filter.as_slice()) {
+            found.push((
+                FilterAnomalyKind::AbbreviatedName,
+                Severity::Low,
+                format!("inline image abbreviation /{} on a stream", name(filter)),
+            ));
+        } else if !STANDARD_FILTERS.contains(&filter.as_slice()) {
+            found.push((FilterAnomalyKind::UnknownFilter, Severity::Medium, format!("unknown filter /{}", name(filter))));
+        }
+    }
+
+    if filters.len() > 2 {
+        let chain: Vec<String> = filters.iter().map(|f| name(f)).collect();
+        found.push((FilterAnomalyKind::LongChain, Severity::Medium, format!("{} chained filters: {}", filters.len(), chain.join(" -> "))));
+    }
+
+    for (index, filter) in filters.iter().enumerate() {
+        if filters[..index].contains(filter) {
+            found.push((FilterAnomalyKind::RepeatedFilter, Severity::Medium, format!("/{} applied more than once", name(filter))));
+            break;
+        }
+    }
+
+    if filters.iter().filter(|f| ASCII_FILTERS.contains(&f.as_slice())).count() > 1 {
+        found.push((FilterAnomalyKind::StackedAsciiEncoding, Severity::Medium, "several ASCII encodings stacked".into()));
+    }
+
+    if let Some(position) = filters.iter().position(|f| IMAGE_CODECS.contains(&f.as_slice())) {
+        if position + 1 < filters.len() {
+            found.push((
+                FilterAnomalyKind::MisplacedImageCodec,
+                Severity::High,
+                format!("/{} is followed by further filters", name(&filters[position])),
+            ));
+        }
+        if name_value(dict, b"Subtype") != Some(b"Image") {
+            found.push((
+                FilterAnomalyKind::ImageCodecOnNonImage,
+                Severity::High,
+                format!("/{} on a stream that is not an image", name(&filters[position])),
+            ));
+        }
+    }
+
+    if let Ok(Object::Array(parms)) = dict.get(b"DecodeParms") {
+        if parms.len() != filters.len() {
+            found.push((
+                FilterAnomalyKind::ParmsMismatch,
+                Severity::Low,
+                format!("{} /DecodeParms entries for {} filters", parms.len(), filters.len()),
+            ));
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_clean_document() {
+        assert!(check(&fixtures::simple_pdf()).is_empty());
+    }
+
+    #[test]
+    fn test_obfuscated_chain() {
+        let data = fixtures::append_revision(
+            &fixtures::simple_pdf(),
+            &[(7, "<< /Length 2 /Filter [/ASCIIHexDecode /ASCII85Decode /FlateDecode /FlateDecode] >>\nstream\n>>\nendstream")],
+        );
+        let kinds: Vec<_> = check(&data).into_iter().map(|a| a.kind).collect();
+
+        assert!(kinds.contains(&FilterAnomalyKind::LongChain));
+        assert!(kinds.contains(&FilterAnomalyKind::RepeatedFilter));
+        assert!(kinds.contains(&FilterAnomalyKind::StackedAsciiEncoding));
+    }
+
+    #[test]
+    fn test_unknown_and_misplaced_filters() {
+        let data = fixtures::append_revision(
+            &fixtures::simple_pdf(),
+            &[(7, "<< /Length 1 /Filter [/DCTDecode /FlateDecode /Rot13Decode] >>\nstream\nx\nendstream")],
+        );
+        let kinds: Vec<_> = check(&data).into_iter().map(|a| a.kind).collect();
+
+        assert!(kinds.contains(&FilterAnomalyKind::UnknownFilter));
+        assert!(kinds.contains(&FilterAnomalyKind::MisplacedImageCodec));
+        assert!(kinds.contains(&FilterAnomalyKind::ImageCodecOnNonImage));
+        assert!(!kinds.contains(&FilterAnomalyKind::DecodeFailure));
+    }
+}
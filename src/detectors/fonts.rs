@@ -0,0 +1,911 @@
+//! Font inventory and consistency checks
+//! Created: 2026-10-16 09:45:31 UTC
+//! Author: kartik4091
+//!
+//! Lists every font with its type, embedding status, subset tag and
+//! descriptor metrics. Producers embed one subset per font when a document
+//! is created; a second subset of the same base font appearing in a later
+//! incremental update usually means text was edited afterwards, with only
+//! the glyphs needed for the new text embedded. Descriptors that disagree
+//! with their font dictionary point at hand-made or patched fonts.
+//!
+//! A ToUnicode CMap decides what text is copied or searched, while the
+//! encoding decides which glyph is drawn. When the two disagree on letters
+//! or digits, the document displays one text and extracts another — a
+//! known way to mask content from indexers and reviewers.
+//!
+//! The same masking can be done in the glyphs themselves: an embedded
+//! subset draws a "6" for the code that extracts as "8". Glyph outlines of
+//! embedded TrueType programs are fingerprinted and compared across every
+//! font of the same family in the document; one outline standing for two
+//! different letters or digits of the same script is flagged. Latin, Greek
+//! and Cyrillic lookalikes legitimately share outlines and are not
+//! compared. Each outline is also held against reference contour counts
+//! for its character: an "8" drawn with the two contours of a "6" does not
+//! look like an "8" in any typeface.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{xref::XrefEntry, Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::{
+    document::{
+        name_value, number, rectangle, resolve,
+        revisions::{split_revisions, Revision},
+        stream_data,
+    },
+    text::{font::Font, truetype::TrueType},
+};
+
+/// Remapped letters or digits above which masking is considered deliberate
+const SYSTEMATIC_REMAPS: usize = 2;
+
+/// Characters of one script that legitimately share a glyph in some typefaces
+const LOOKALIKES: [&str; 1] = ["Il"];
+
+/// Reference outlines: contours of letters and digits whose topology is
+/// the same in virtually every text typeface
+const REFERENCE_CONTOURS: [(char, usize); 32] = [
+    ('1', 1),
+    ('2', 1),
+    ('3', 1),
+    ('5', 1),
+    ('6', 2),
+    ('7', 1),
+    ('8', 3),
+    ('9', 2),
+    ('A', 2),
+    ('B', 3),
+    ('C', 1),
+    ('D', 2),
+    ('E', 1),
+    ('F', 1),
+    ('H', 1),
+    ('L', 1),
+    ('O', 2),
+    ('P', 2),
+    ('R', 2),
+    ('T', 1),
+    ('V', 1),
+    ('X', 1),
+    ('Z', 1),
+    ('b', 2),
+    ('d', 2),
+    ('e', 2),
+    ('i', 2),
+    ('l', 1),
+    ('o', 2),
+    ('p', 2),
+    ('q', 2),
+    ('x', 1),
+];
+
+/// Base fonts every reader provides without embedding
+const STANDARD_14: [&str; 14] = [
+    "Times-Roman",
+    "Times-Bold",
+    "Times-Italic",
+    "Times-BoldItalic",
+    "Helvetica",
+    "Helvetica-Bold",
+    "Helvetica-Oblique",
+    "Helvetica-BoldOblique",
+    "Courier",
+    "Courier-Bold",
+    "Courier-Oblique",
+    "Courier-BoldOblique",
+    "Symbol",
+    "ZapfDingbats",
+];
+
+/// Metrics from a /FontDescriptor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FontDescriptor {
+    /// /FontName
+    pub font_name: Option<String>,
+
+    /// /Flags
+    pub flags: Option<i64>,
+
+    /// /ItalicAngle
+    pub italic_angle: Option<f64>,
+
+    /// /Ascent
+    pub ascent: Option<f64>,
+
+    /// /Descent
+    pub descent: Option<f64>,
+
+    /// /CapHeight
+    pub cap_height: Option<f64>,
+
+    /// /StemV
+    pub stem_v: Option<f64>,
+
+    /// /FontBBox
+    pub bbox: Option<[f64; 4]>,
+}
+
+/// A code whose extracted text differs from the glyph its encoding draws
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Remapping {
+    /// Character code
+    pub code: u8,
+
+    /// Character the encoding draws
+    pub displayed: char,
+
+    /// Text the ToUnicode CMap yields for copy and search
+    pub extracted: String,
+}
+
+/// A font dictionary
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FontInfo {
+    /// Font object
+    pub id: ObjectId,
+
+    /// /Subtype (Type1, TrueType, Type0, Type3, ...)
+    pub subtype: String,
+
+    /// /BaseFont including any subset tag
+    pub base_font: String,
+
+    /// Six-letter subset tag, e.g. `ABCDEF` in `ABCDEF+Helvetica`
+    pub subset_prefix: Option<String>,
+
+    /// Whether the font program is in the file
+    pub embedded: bool,
+
+    /// Descriptor key holding the program (FontFile, FontFile2, FontFile3/Type1C, ...)
+    pub program: Option<String>,
+
+    /// Descriptor, from the descendant font for Type0
+    pub descriptor: Option<FontDescriptor>,
+
+    /// Revision that last wrote the font object
+    pub revision: Option<usize>,
+
+    /// ToUnicode entries contradicting the encoding
+    pub remapped: Vec<Remapping>,
+}
+
+impl FontInfo {
+    /// Base font name without the subset tag
+    pub fn family(&self) -> &str {
+        strip_subset(&self.base_font)
+    }
+}
+
+/// Kind of font inconsistency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum FontIssue {
+    /// Several subsets of one base font were written by different revisions
+    MultipleSubsets,
+
+    /// /FontName in the descriptor differs from /BaseFont
+    NameMismatch,
+
+    /// Subset tag on a font without an embedded program
+    UnembeddedSubset,
+
+    /// /Widths does not cover /FirstChar to /LastChar
+    WidthsMismatch,
+
+    /// ToUnicode maps glyphs to other characters than the encoding draws
+    ToUnicodeRemap,
+
+    /// One glyph outline is used for different characters, or a glyph's
+    /// shape contradicts the character it extracts as
+    GlyphSubstitution,
+}
+
+/// A flagged font or group of fonts
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FontFinding {
+    /// Fonts involved
+    pub fonts: Vec<ObjectId>,
+
+    /// Kind of inconsistency
+    pub issue: FontIssue,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Fonts in the document and their inconsistencies
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FontReport {
+    /// Every font dictionary
+    pub fonts: Vec<FontInfo>,
+
+    /// Inconsistencies found
+    pub findings: Vec<FontFinding>,
+}
+
+/// Enumerates fonts and checks them for signs of tampering
+#[instrument(skip(data, doc))]
+pub fn analyze(data: &[u8], doc: &Document) -> FontReport {
+    let revisions = split_revisions(data);
+    let mut report = FontReport::default();
+    let mut outlines = BTreeMap::new();
+
+    for (&id, object) in &doc.objects {
+        let Ok(dict) = object.as_dict() else {
+            continue;
+        };
+        if name_value(dict, b"Type") != Some(b"Font") {
+            continue;
+        }
+        let subtype = name_value(dict, b"Subtype").unwrap_or_default();
+        // Descendant fonts are reported through their Type0 parent
+        if subtype.starts_with(b"CIDFontType") {
+            continue;
+        }
+        report.fonts.push(inspect(doc, id, dict, &revisions));
+        outlines.insert(id, glyph_outlines(doc, dict));
+        check_widths(doc, id, dict, &mut report.findings);
+    }
+
+    for font in &report.fonts {
+        if font.subset_prefix.is_some() && !font.embedded && font.subtype != "Type3" {
+            report.findings.push(FontFinding {
+                fonts: vec![font.id],
+                issue: FontIssue::UnembeddedSubset,
+                severity: Severity::Low,
+                description: format!(
+                    "Font {} is tagged as a subset but has no embedded program",
+                    font.base_font
+                ),
+            });
+        }
+        let descriptor_name = font.descriptor.as_ref().and_then(|d| d.font_name.as_deref());
+        if !font.remapped.is_empty() {
+            report.findings.push(remap_finding(font));
+        }
+        if let Some(name) = descriptor_name.filter(|name| strip_subset(name) != font.family()) {
+            report.findings.push(FontFinding {
+                fonts: vec![font.id],
+                issue: FontIssue::NameMismatch,
+                severity: Severity::Low,
+                description: format!("Font {} has a descriptor for {}", font.base_font, name),
+            });
+        }
+    }
+    report.findings.extend(subset_findings(&report.fonts));
+    report.findings.extend(substitution_findings(&report.fonts, &outlines));
+
+    debug!("Found {} fonts, {} flagged", report.fonts.len(), report.findings.len());
+    report
+}
+
+/// Subset tag of a base font name, if any
+pub fn subset_prefix(name: &str) -> Option<&str> {
+    let (prefix, _) = name.split_once('+')?;
+    (prefix.len() == 6 && prefix.bytes().all(|b| b.is_ascii_uppercase())).then_some(prefix)
+}
+
+/// Base font name without the subset tag
+pub fn strip_subset(name: &str) -> &str {
+    match subset_prefix(name) {
+        Some(prefix) => &name[prefix.len() + 1..],
+        None => name,
+    }
+}
+
+/// Whether a base font is one of the standard 14
+pub fn is_standard(name: &str) -> bool {
+    STANDARD_14.contains(&name)
+}
+
+fn inspect(doc: &Document, id: ObjectId, dict: &Dictionary, revisions: &[Revision]) -> FontInfo {
+    let subtype = String::from_utf8_lossy(name_value(dict, b"Subtype").unwrap_or_default()).into_owned();
+    let base_font = String::from_utf8_lossy(name_value(dict, b"BaseFont").unwrap_or_default()).into_owned();
+
+    // Composite fonts keep their descriptor in the descendant
+    let descriptor = descendant(doc, dict)
+        .unwrap_or(dict)
+        .get(b"FontDescriptor")
+        .ok()
+        .and_then(|d| resolve(doc, d).as_dict().ok());
+    let program = descriptor.and_then(|d| program(doc, d));
+
+    FontInfo {
+        id,
+        subset_prefix: subset_prefix(&base_font).map(str::to_string),
+        embedded: program.is_some() || subtype == "Type3",
+        program,
+        descriptor: descriptor.map(|d| read_descriptor(doc, d)),
+        revision: revision_of(doc, id, revisions),
+        remapped: remappings(doc, dict, &base_font),
+        subtype,
+        base_font,
+    }
+}
+
+/// First descendant font of a Type0 font
+fn descendant<'a>(doc: &'a Document, dict: &'a Dictionary) -> Option<&'a Dictionary> {
+    dict.get(b"DescendantFonts")
+        .ok()
+        .and_then(|d| resolve(doc, d).as_array().ok())
+        .and_then(|fonts| fonts.first())
+        .and_then(|font| resolve(doc, font).as_dict().ok())
+}
+
+/// Key of the embedded font program, with the FontFile3 subtype
+fn program(doc: &Document, descriptor: &Dictionary) -> Option<String> {
+    for key in ["FontFile", "FontFile2", "FontFile3"] {
+        let Ok(stream) = descriptor.get(key.as_bytes()).map(|f| resolve(doc, f)) else {
+            continue;
+        };
+        let Ok(stream) = stream.as_stream() else {
+            continue;
+        };
+        return Some(match name_value(&stream.dict, b"Subtype") {
+            Some(subtype) => format!("{}/{}", key, String::from_utf8_lossy(subtype)),
+            None => key.to_string(),
+        });
+    }
+    None
+}
+
+fn read_descriptor(doc: &Document, dict: &Dictionary) -> FontDescriptor {
+    let value = |key: &[u8]| dict.get(key).ok().and_then(|v| number(resolve(doc, v)));
+    FontDescriptor {
+        font_name: name_value(dict, b"FontName").map(|n| String::from_utf8_lossy(n).into_owned()),
+        flags: dict.get(b"Flags").ok().and_then(|f| resolve(doc, f).as_i64().ok()),
+        italic_angle: value(b"ItalicAngle"),
+        ascent: value(b"Ascent"),
+        descent: value(b"Descent"),
+        cap_height: value(b"CapHeight"),
+        stem_v: value(b"StemV"),
+        bbox: dict.get(b"FontBBox").ok().and_then(|b| rectangle(doc, b)),
+    }
+}
+
+/// ToUnicode entries of a simple font that contradict its encoding.
+///
+/// Fonts without an /Encoding only have a known one when they are
+/// standard text fonts; other fonts draw through their built-in encoding.
+fn remappings(doc: &Document, dict: &Dictionary, base_font: &str) -> Vec<Remapping> {
+    if !dict.has(b"ToUnicode") || name_value(dict, b"Subtype") == Some(b"Type0") {
+        return Vec::new();
+    }
+    let family = strip_subset(base_font);
+    let known_encoding = dict.has(b"Encoding") || (is_standard(family) && !matches!(family, "Symbol" | "ZapfDingbats"));
+    if !known_encoding {
+        return Vec::new();
+    }
+
+    let font = Font::load(doc, dict);
+    font.unicode_map()
+        .filter_map(|(code, extracted)| {
+            let [code] = *code else {
+                return None;
+            };
+            let displayed = font.encoded_char(code)?;
+            contradicts(displayed, extracted).then(|| Remapping {
+                code,
+                displayed,
+                extracted: extracted.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Whether extracted text misrepresents a displayed letter or digit
+fn contradicts(displayed: char, extracted: &str) -> bool {
+    if !displayed.is_alphanumeric() {
+        return false;
+    }
+    let extracted = extracted.trim();
+    if extracted.chars().eq(std::iter::once(displayed)) {
+        return false;
+    }
+    !matches!(
+        (displayed, extracted),
+        ('ﬀ', "ff") | ('ﬁ', "fi") | ('ﬂ', "fl") | ('ﬃ', "ffi") | ('ﬄ', "ffl")
+    )
+}
+
+fn remap_finding(font: &FontInfo) -> FontFinding {
+    let examples: Vec<String> = font
+        .remapped
+        .iter()
+        .take(5)
+        .map(|r| format!("'{}' copies as \"{}\"", r.displayed, r.extracted))
+        .collect();
+    FontFinding {
+        fonts: vec![font.id],
+        issue: FontIssue::ToUnicodeRemap,
+        severity: if font.remapped.len() > SYSTEMATIC_REMAPS {
+            Severity::High
+        } else {
+            Severity::Medium
+        },
+        description: format!(
+            "Font {} displays different text than it extracts for {} codes: {}",
+            font.base_font,
+            font.remapped.len(),
+            examples.join(", ")
+        ),
+    }
+}
+
+/// A glyph drawn for a letter or digit
+struct Outline {
+    /// Character the code extracts as
+    character: char,
+
+    /// Outline fingerprint
+    digest: String,
+
+    /// Number of contours
+    contours: usize,
+}
+
+/// Writing system of a letter or digit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Script {
+    Digit,
+    Latin,
+    Greek,
+    Cyrillic,
+}
+
+impl Script {
+    /// Script of a character; `None` for scripts that are not compared
+    fn of(c: char) -> Option<Self> {
+        match c {
+            '0'..='9' => Some(Self::Digit),
+            'A'..='Z' | 'a'..='z' | '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' => Some(Self::Latin),
+            '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' => Some(Self::Greek),
+            '\u{0400}'..='\u{052F}' => Some(Self::Cyrillic),
+            _ => None,
+        }
+    }
+}
+
+/// Outlines of the letters and digits of an embedded TrueType program.
+///
+/// Composite fonts are covered for Identity encodings, where the code is
+/// the CID; simple fonts through the program's own cmap.
+fn glyph_outlines(doc: &Document, dict: &Dictionary) -> Vec<Outline> {
+    let descendant = descendant(doc, dict);
+    let Some(program) = descendant
+        .unwrap_or(dict)
+        .get(b"FontDescriptor")
+        .ok()
+        .and_then(|d| resolve(doc, d).as_dict().ok())
+        .and_then(|d| d.get(b"FontFile2").ok())
+        .and_then(|f| resolve(doc, f).as_stream().ok())
+        .and_then(|s| stream_data(s).ok())
+    else {
+        return Vec::new();
+    };
+    let Some(truetype) = TrueType::parse(&program) else {
+        return Vec::new();
+    };
+    let font = Font::load(doc, dict);
+
+    let glyphs: Vec<(Vec<u8>, Option<u16>)> = match descendant {
+        Some(descendant) => {
+            if !matches!(name_value(dict, b"Encoding"), Some(b"Identity-H") | Some(b"Identity-V")) {
+                return Vec::new();
+            }
+            let cid_to_gid = match descendant.get(b"CIDToGIDMap").map(|m| resolve(doc, m)) {
+                Ok(Object::Stream(map)) => Some(stream_data(map).unwrap_or_default()),
+                _ => None,
+            };
+            font.unicode_map()
+                .filter_map(|(code, _)| {
+                    let cid = match *code {
+                        [high, low] => u16::from_be_bytes([high, low]),
+                        _ => return None,
+                    };
+                    let gid = match &cid_to_gid {
+                        Some(map) => map
+                            .get(cid as usize * 2..cid as usize * 2 + 2)
+                            .map(|b| u16::from_be_bytes([b[0], b[1]])),
+                        None => Some(cid),
+                    };
+                    Some((code.to_vec(), gid))
+                })
+                .collect()
+        }
+        None => (0..=u8::MAX)
+            .map(|code| {
+                let gid = truetype
+                    .glyph_for_code(code)
+                    .or_else(|| font.encoded_char(code).and_then(|c| truetype.glyph_for_char(c)));
+                (vec![code], gid)
+            })
+            .collect(),
+    };
+
+    glyphs
+        .into_iter()
+        .filter_map(|(code, gid)| {
+            let text = font.decode_code(&code);
+            let mut chars = text.chars();
+            let (Some(character), None) = (chars.next(), chars.next()) else {
+                return None;
+            };
+            if !character.is_alphanumeric() {
+                return None;
+            }
+            let gid = gid?;
+            Some(Outline {
+                character,
+                digest: truetype.outline_digest(gid)?,
+                contours: truetype.contours(gid)?,
+            })
+        })
+        .collect()
+}
+
+/// Outlines standing for different characters of one script within a font
+/// family, and outlines that do not match their character's reference
+fn substitution_findings(fonts: &[FontInfo], outlines: &BTreeMap<ObjectId, Vec<Outline>>) -> Vec<FontFinding> {
+    type Shared<'a> = BTreeMap<(Script, &'a str), BTreeMap<char, BTreeSet<ObjectId>>>;
+    let mut families: BTreeMap<&str, Shared> = BTreeMap::new();
+    for font in fonts {
+        for outline in outlines.get(&font.id).into_iter().flatten() {
+            let Some(script) = Script::of(outline.character) else {
+                continue;
+            };
+            families
+                .entry(font.family())
+                .or_default()
+                .entry((script, outline.digest.as_str()))
+                .or_default()
+                .entry(outline.character)
+                .or_default()
+                .insert(font.id);
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (family, digests) in families {
+        for characters in digests.into_values() {
+            let distinct: Vec<char> = characters.keys().copied().collect();
+            let lookalike = LOOKALIKES
+                .iter()
+                .any(|group| distinct.iter().all(|&c| group.contains(c)));
+            if distinct.len() < 2 || lookalike {
+                continue;
+            }
+            let ids: BTreeSet<ObjectId> = characters.into_values().flatten().collect();
+            findings.push(FontFinding {
+                fonts: ids.into_iter().collect(),
+                issue: FontIssue::GlyphSubstitution,
+                severity: Severity::High,
+                description: format!(
+                    "{} draws an identical glyph for {}; displayed and extracted text differ",
+                    family,
+                    distinct
+                        .iter()
+                        .map(|c| format!("'{}'", c))
+                        .collect::<Vec<_>>()
+                        .join(" and ")
+                ),
+            });
+        }
+    }
+
+    for font in fonts {
+        let contradicted: Vec<&Outline> = outlines
+            .get(&font.id)
+            .into_iter()
+            .flatten()
+            .filter(|outline| {
+                REFERENCE_CONTOURS
+                    .iter()
+                    .any(|&(c, contours)| c == outline.character && contours != outline.contours)
+            })
+            .collect();
+        if contradicted.is_empty() {
+            continue;
+        }
+        // One odd glyph can be a display face; several are deliberate
+        let severity = if contradicted.len() > SYSTEMATIC_REMAPS {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+        findings.push(FontFinding {
+            fonts: vec![font.id],
+            issue: FontIssue::GlyphSubstitution,
+            severity,
+            description: format!(
+                "{} draws glyphs unlike the characters they extract as: {}",
+                font.family(),
+                contradicted
+                    .iter()
+                    .take(5)
+                    .map(|o| format!("'{}' has {} contours", o.character, o.contours))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        });
+    }
+    findings
+}
+
+/// Revision whose xref section points at the object's definition
+fn revision_of(doc: &Document, id: ObjectId, revisions: &[Revision]) -> Option<usize> {
+    let offset = match doc.reference_table.get(id.0)? {
+        XrefEntry::Normal { offset, .. } => *offset as usize,
+        XrefEntry::Compressed { container, .. } => match doc.reference_table.get(*container)? {
+            XrefEntry::Normal { offset, .. } => *offset as usize,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    revisions
+        .iter()
+        .find(|revision| offset >= revision.start && offset < revision.end)
+        .map(|revision| revision.index)
+}
+
+/// Simple fonts must give one width per code from /FirstChar to /LastChar
+fn check_widths(doc: &Document, id: ObjectId, dict: &Dictionary, findings: &mut Vec<FontFinding>) {
+    let integer = |key: &[u8]| dict.get(key).ok().and_then(|v| resolve(doc, v).as_i64().ok());
+    let widths = dict.get(b"Widths").ok().and_then(|w| resolve(doc, w).as_array().ok());
+    let (Some(first), Some(last), Some(widths)) = (integer(b"FirstChar"), integer(b"LastChar"), widths) else {
+        return;
+    };
+    // Code ranges too wide to count are left alone
+    let Some(expected) = last.checked_sub(first).and_then(|span| span.checked_add(1)) else {
+        return;
+    };
+    if expected != widths.len() as i64 {
+        findings.push(FontFinding {
+            fonts: vec![id],
+            issue: FontIssue::WidthsMismatch,
+            severity: Severity::Low,
+            description: format!(
+                "Font {} has {} widths for codes {}..={}",
+                String::from_utf8_lossy(name_value(dict, b"BaseFont").unwrap_or_default()),
+                widths.len(),
+                first,
+                last
+            ),
+        });
+    }
+}
+
+/// Base fonts with subsets written by more than one revision
+fn subset_findings(fonts: &[FontInfo]) -> Vec<FontFinding> {
+    let mut families: BTreeMap<&str, Vec<&FontInfo>> = BTreeMap::new();
+    for font in fonts.iter().filter(|font| font.subset_prefix.is_some()) {
+        families.entry(font.family()).or_default().push(font);
+    }
+
+    families
+        .into_iter()
+        .filter_map(|(family, subsets)| {
+            let prefixes: BTreeSet<_> = subsets.iter().filter_map(|f| f.subset_prefix.as_deref()).collect();
+            let revisions: BTreeSet<usize> = subsets.iter().filter_map(|f| f.revision).collect();
+            if prefixes.len() < 2 || revisions.len() < 2 {
+                return None;
+            }
+            Some(FontFinding {
+                fonts: subsets.iter().map(|f| f.id).collect(),
+                issue: FontIssue::MultipleSubsets,
+                severity: Severity::Medium,
+                description: format!(
+                    "{} subsets of {} were added in revisions {}; text may have been edited after creation",
+                    prefixes.len(),
+                    family,
+                    revisions.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(", ")
+                ),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_standard_font_inventory() {
+        let data = fixtures::simple_pdf();
+        let doc = Document::load_mem(&data).unwrap();
+        let report = analyze(&data, &doc);
+
+        assert_eq!(report.fonts.len(), 1);
+        let font = &report.fonts[0];
+        assert_eq!(font.base_font, "Helvetica");
+        assert!(is_standard(font.family()));
+        assert!(!font.embedded);
+        assert_eq!(font.revision, Some(0));
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_subset_added_in_later_revision() {
+        let mut doc = fixtures::document();
+        let program = doc.add_object(Stream::new(dictionary! { "Subtype" => "Type1C" }, vec![0; 16]));
+        let descriptor = doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontName" => "ABCDEF+Arial",
+            "Flags" => 32,
+            "FontFile3" => program,
+        });
+        doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "ABCDEF+Arial",
+            "FontDescriptor" => descriptor,
+        });
+        let base = fixtures::save(doc);
+        let data = fixtures::append_revision(
+            &base,
+            &[
+                (
+                    20,
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /QRSTUV+Arial /FontDescriptor 21 0 R >>",
+                ),
+                (21, "<< /Type /FontDescriptor /FontName /Helvetica /Flags 32 >>"),
+            ],
+        );
+        let doc = Document::load_mem(&data).unwrap();
+        let report = analyze(&data, &doc);
+
+        let original = report.fonts.iter().find(|f| f.base_font == "ABCDEF+Arial").unwrap();
+        assert!(original.embedded);
+        assert_eq!(original.program.as_deref(), Some("FontFile3/Type1C"));
+        assert_eq!(original.descriptor.as_ref().unwrap().flags, Some(32));
+
+        let issues: Vec<FontIssue> = report.findings.iter().map(|f| f.issue).collect();
+        assert!(issues.contains(&FontIssue::MultipleSubsets));
+        assert!(issues.contains(&FontIssue::NameMismatch));
+        assert!(issues.contains(&FontIssue::UnembeddedSubset));
+    }
+
+    #[test]
+    fn test_tounicode_remap() {
+        let mut doc = fixtures::document();
+        let cmap = b"/CIDInit /ProcSet findresource begin 12 dict begin begincmap\n\
+            1 begincodespacerange <00> <FF> endcodespacerange\n\
+            3 beginbfchar <36> <0038> <37> <0037> <FB01> <00660069> endbfchar\n\
+            1 beginbfrange <61> <63> <0078> endbfrange\n\
+            endcmap CMapName currentdict /CMap defineresource pop end end";
+        let to_unicode = doc.add_object(Stream::new(dictionary! {}, cmap.to_vec()));
+        let font = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+            "ToUnicode" => to_unicode,
+        });
+        let data = fixtures::save(doc);
+        let doc = Document::load_mem(&data).unwrap();
+        let report = analyze(&data, &doc);
+
+        let info = report.fonts.iter().find(|f| f.id == font).unwrap();
+        let remapped: Vec<(char, &str)> = info
+            .remapped
+            .iter()
+            .map(|r| (r.displayed, r.extracted.as_str()))
+            .collect();
+        assert_eq!(remapped, vec![('6', "8"), ('a', "x"), ('b', "y"), ('c', "z")]);
+
+        let finding = report
+            .findings
+            .iter()
+            .find(|f| f.issue == FontIssue::ToUnicodeRemap)
+            .unwrap();
+        assert_eq!(finding.severity, Severity::High);
+    }
+
+    #[test]
+    fn test_glyph_substitution() {
+        let six = fixtures::glyph(&[(0, 0), (400, 0), (400, 700), (0, 350)], &[]);
+        let seven = fixtures::glyph(&[(0, 700), (400, 700), (100, 0)], &[]);
+        let program = fixtures::truetype(&[six.clone(), seven, six]);
+
+        let mut doc = fixtures::document();
+        let file = doc.add_object(Stream::new(dictionary! {}, program));
+        let descriptor = doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontName" => "ABCDEF+Arial",
+            "FontFile2" => file,
+        });
+        let cid_font = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "CIDFontType2",
+            "BaseFont" => "ABCDEF+Arial",
+            "FontDescriptor" => descriptor,
+            "CIDToGIDMap" => "Identity",
+        });
+        let cmap = b"begincmap 1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+            3 beginbfchar <0001> <0036> <0002> <0037> <0003> <0038> endbfchar endcmap";
+        let to_unicode = doc.add_object(Stream::new(dictionary! {}, cmap.to_vec()));
+        let font = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => "ABCDEF+Arial",
+            "Encoding" => "Identity-H",
+            "DescendantFonts" => vec![cid_font.into()],
+            "ToUnicode" => to_unicode,
+        });
+        let data = fixtures::save(doc);
+        let doc = Document::load_mem(&data).unwrap();
+        let report = analyze(&data, &doc);
+
+        let info = report.fonts.iter().find(|f| f.id == font).unwrap();
+        assert_eq!(info.program.as_deref(), Some("FontFile2"));
+        let finding = report
+            .findings
+            .iter()
+            .find(|f| f.issue == FontIssue::GlyphSubstitution)
+            .unwrap();
+        assert_eq!(finding.fonts, vec![font]);
+        assert!(finding.description.contains("'6' and '8'"));
+    }
+
+    #[test]
+    fn test_substitution_compares_one_script_and_references() {
+        let doc = Document::with_version("1.5");
+        let font = inspect(
+            &doc,
+            (1, 0),
+            &dictionary! { "Subtype" => "TrueType", "BaseFont" => "ABCDEF+Arial" },
+            &[],
+        );
+        let outline = |character, digest: &str, contours| Outline {
+            character,
+            digest: digest.to_string(),
+            contours,
+        };
+        // Latin and Cyrillic A share an outline; the "8" is drawn like a "6"
+        let outlines = BTreeMap::from([(
+            font.id,
+            vec![
+                outline('A', "a", 2),
+                outline('\u{0410}', "a", 2),
+                outline('8', "six", 2),
+            ],
+        )]);
+
+        let findings = substitution_findings(&[font], &outlines);
+        assert_eq!(findings.len(), 1, "{:?}", findings);
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert!(findings[0].description.contains("'8' has 2 contours"));
+    }
+
+    #[test]
+    fn test_subset_prefix() {
+        assert_eq!(subset_prefix("ABCDEF+Times-Roman"), Some("ABCDEF"));
+        assert_eq!(strip_subset("ABCDEF+Times-Roman"), "Times-Roman");
+        assert_eq!(subset_prefix("Abcdef+Times"), None);
+        assert_eq!(strip_subset("Helvetica"), "Helvetica");
+    }
+
+    #[test]
+    fn test_widths_count() {
+        let doc = fixtures::document();
+        let font = |first: i64, last: i64| {
+            dictionary! { "FirstChar" => first, "LastChar" => last, "Widths" => vec![500.into()] }
+        };
+        let mut findings = Vec::new();
+
+        check_widths(&doc, (20, 0), &font(65, 65), &mut findings);
+        check_widths(&doc, (20, 0), &font(i64::MIN, i64::MAX), &mut findings);
+        assert!(findings.is_empty());
+        check_widths(&doc, (20, 0), &font(65, 66), &mut findings);
+        assert_eq!(findings[0].issue, FontIssue::WidthsMismatch);
+    }
+}
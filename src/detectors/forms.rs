@@ -0,0 +1,396 @@
+//! AcroForm structure and field analysis
+//! Created: 2026-10-16 09:33:00 UTC
+//! Author: kartik4091
+//!
+//! Form fields run scripts on keystrokes, formatting, validation and
+//! recalculation, and calculation scripts fire whenever any field changes
+//! without the user touching the field itself. Submit actions can send
+//! everything typed into a form to an arbitrary server. This pass lists
+//! every field with its inherited attributes and scripts and flags those
+//! two patterns.
+
+use std::collections::BTreeSet;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{actions::is_external, Severity};
+use crate::document::{
+    array_references, decode_text, display_value, name_value, printable_preview, resolve, stream_data,
+};
+
+/// Deepest field hierarchy followed
+const MAX_DEPTH: usize = 32;
+
+/// Length of script excerpts
+const SCRIPT_PREVIEW: usize = 80;
+
+/// Field flag bits (PDF 32000-1, 12.7.4)
+const FLAG_PUSHBUTTON: i64 = 1 << 16;
+const FLAG_RADIO: i64 = 1 << 15;
+const FLAG_COMBO: i64 = 1 << 17;
+
+/// A script attached to a field or one of its widgets
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FieldScript {
+    /// Trigger entry, e.g. `AA/C` or `A`
+    pub trigger: String,
+
+    /// Script excerpt
+    pub preview: String,
+}
+
+/// A terminal form field
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormField {
+    /// Field object
+    pub id: ObjectId,
+
+    /// Fully qualified field name (partial names joined by '.')
+    pub name: String,
+
+    /// Field type: text, checkbox, radio, pushbutton, combo, list or signature
+    pub field_type: String,
+
+    /// Field flags (/Ff), inherited
+    pub flags: i64,
+
+    /// Current value (/V), inherited
+    pub value: Option<String>,
+
+    /// Default value (/DV), inherited
+    pub default_value: Option<String>,
+
+    /// JavaScript on the field and its widgets
+    pub scripts: Vec<FieldScript>,
+
+    /// Targets of submit and import actions
+    pub submit_targets: Vec<String>,
+}
+
+/// Suspicious form pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum FormIssue {
+    /// Calculation script, run on every field change
+    CalculationScript,
+
+    /// Form data submitted to an external URL
+    ExternalSubmit,
+}
+
+/// A flagged form field
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormFinding {
+    /// Field object
+    pub id: ObjectId,
+
+    /// Pattern found
+    pub issue: FormIssue,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Interactive form structure
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FormReport {
+    /// /NeedAppearances: viewers regenerate field appearances
+    pub need_appearances: bool,
+
+    /// /SigFlags
+    pub sig_flags: i64,
+
+    /// An /XFA entry is present alongside the AcroForm
+    pub has_xfa: bool,
+
+    /// Fields in calculation order (/CO)
+    pub calculation_order: Vec<ObjectId>,
+
+    /// Terminal fields in tree order
+    pub fields: Vec<FormField>,
+
+    /// Flagged fields
+    pub findings: Vec<FormFinding>,
+}
+
+/// Attributes inherited down the field tree
+#[derive(Clone, Default)]
+struct Inherited {
+    name: Vec<String>,
+    field_type: Option<Vec<u8>>,
+    flags: i64,
+    value: Option<String>,
+    default_value: Option<String>,
+}
+
+/// Parses the /AcroForm dictionary, if the document has one
+#[instrument(skip(doc))]
+pub fn analyze(doc: &Document) -> Option<FormReport> {
+    let catalog = doc
+        .trailer
+        .get(b"Root")
+        .ok()
+        .and_then(|root| resolve(doc, root).as_dict().ok())?;
+    let form = resolve(doc, catalog.get(b"AcroForm").ok()?).as_dict().ok()?;
+
+    let mut report = FormReport {
+        need_appearances: matches!(form.get(b"NeedAppearances"), Ok(Object::Boolean(true))),
+        sig_flags: form.get(b"SigFlags").and_then(Object::as_i64).unwrap_or(0),
+        has_xfa: form.has(b"XFA"),
+        calculation_order: array_references(doc, form.get(b"CO").ok()),
+        ..Default::default()
+    };
+
+    let mut visited = BTreeSet::new();
+    for id in array_references(doc, form.get(b"Fields").ok()) {
+        visit(doc, id, &Inherited::default(), 0, &mut visited, &mut report);
+    }
+
+    for field in &report.fields {
+        let calculated = field.scripts.iter().any(|s| s.trigger == "AA/C");
+        if calculated {
+            let ordered = report.calculation_order.contains(&field.id);
+            report.findings.push(FormFinding {
+                id: field.id,
+                issue: FormIssue::CalculationScript,
+                severity: Severity::Medium,
+                description: format!(
+                    "Field {} runs JavaScript on every recalculation{}",
+                    field.name,
+                    if ordered { " (listed in /CO)" } else { "" }
+                ),
+            });
+        }
+        for target in field.submit_targets.iter().filter(|t| is_external(t)) {
+            report.findings.push(FormFinding {
+                id: field.id,
+                issue: FormIssue::ExternalSubmit,
+                severity: Severity::Medium,
+                description: format!("Field {} submits form data to {}", field.name, target),
+            });
+        }
+    }
+
+    debug!(
+        "Found {} form fields, {} flagged",
+        report.fields.len(),
+        report.findings.len()
+    );
+    Some(report)
+}
+
+fn visit(
+    doc: &Document,
+    id: ObjectId,
+    parent: &Inherited,
+    depth: usize,
+    visited: &mut BTreeSet<ObjectId>,
+    report: &mut FormReport,
+) {
+    if depth > MAX_DEPTH || !visited.insert(id) {
+        return;
+    }
+    let Ok(dict) = doc.get_dictionary(id) else {
+        return;
+    };
+
+    let mut inherited = parent.clone();
+    if let Ok(Object::String(partial, _)) = dict.get(b"T") {
+        inherited.name.push(decode_text(partial));
+    }
+    if let Some(field_type) = name_value(dict, b"FT") {
+        inherited.field_type = Some(field_type.to_vec());
+    }
+    if let Ok(flags) = dict.get(b"Ff").and_then(Object::as_i64) {
+        inherited.flags = flags;
+    }
+    if let Ok(value) = dict.get(b"V") {
+        inherited.value = Some(display_value(resolve(doc, value)));
+    }
+    if let Ok(value) = dict.get(b"DV") {
+        inherited.default_value = Some(display_value(resolve(doc, value)));
+    }
+
+    // Kids carrying /T are child fields; the rest are widget annotations
+    let kids = array_references(doc, dict.get(b"Kids").ok());
+    let (fields, widgets): (Vec<_>, Vec<_>) = kids
+        .into_iter()
+        .partition(|kid| doc.get_dictionary(*kid).map_or(false, |k| k.has(b"T")));
+
+    if fields.is_empty() {
+        let mut scripts = Vec::new();
+        let mut submit_targets = Vec::new();
+        collect_actions(doc, dict, &mut scripts, &mut submit_targets);
+        for widget in widgets.iter().filter_map(|w| doc.get_dictionary(*w).ok()) {
+            collect_actions(doc, widget, &mut scripts, &mut submit_targets);
+        }
+        report.fields.push(FormField {
+            id,
+            name: inherited.name.join("."),
+            field_type: type_name(inherited.field_type.as_deref(), inherited.flags).to_string(),
+            flags: inherited.flags,
+            value: inherited.value,
+            default_value: inherited.default_value,
+            scripts,
+            submit_targets,
+        });
+    } else {
+        for kid in fields {
+            visit(doc, kid, &inherited, depth + 1, visited, report);
+        }
+    }
+}
+
+/// Scripts and submit targets of /A and /AA, including /Next chains
+fn collect_actions(doc: &Document, dict: &Dictionary, scripts: &mut Vec<FieldScript>, targets: &mut Vec<String>) {
+    let mut triggers = Vec::new();
+    if let Ok(action) = dict.get(b"A") {
+        triggers.push(("A".to_string(), action));
+    }
+    if let Ok(aa) = dict.get(b"AA").map(|aa| resolve(doc, aa)).and_then(Object::as_dict) {
+        triggers.extend(
+            aa.iter()
+                .map(|(key, action)| (format!("AA/{}", String::from_utf8_lossy(key)), action)),
+        );
+    }
+
+    for (trigger, start) in triggers {
+        let mut seen = BTreeSet::new();
+        let mut next = Some(start);
+        while let Some(object) = next.take() {
+            if seen.len() > MAX_DEPTH || object.as_reference().map_or(false, |id| !seen.insert(id)) {
+                break;
+            }
+            let Ok(action) = resolve(doc, object).as_dict() else {
+                break;
+            };
+            match name_value(action, b"S") {
+                Some(b"JavaScript") => {
+                    let script = match action.get(b"JS").map(|js| resolve(doc, js)) {
+                        Ok(Object::String(bytes, _)) => decode_text(bytes),
+                        Ok(Object::Stream(stream)) => {
+                            String::from_utf8_lossy(&stream_data(stream).unwrap_or_default()).into_owned()
+                        }
+                        _ => String::new(),
+                    };
+                    scripts.push(FieldScript {
+                        trigger: trigger.clone(),
+                        preview: printable_preview(script.trim().as_bytes(), SCRIPT_PREVIEW),
+                    });
+                }
+                Some(b"SubmitForm") | Some(b"ImportData") => {
+                    if let Some(target) = action.get(b"F").ok().and_then(|f| file_spec(doc, f)) {
+                        targets.push(target);
+                    }
+                }
+                _ => {}
+            }
+            next = match action.get(b"Next") {
+                Ok(Object::Array(items)) => items.first(),
+                Ok(item) => Some(item),
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+/// URL or file name of a file specification
+fn file_spec(doc: &Document, spec: &Object) -> Option<String> {
+    match resolve(doc, spec) {
+        Object::String(bytes, _) => Some(decode_text(bytes)),
+        Object::Dictionary(dict) => [&b"UF"[..], b"F"].iter().find_map(|key| match dict.get(key).ok()? {
+            Object::String(bytes, _) => Some(decode_text(bytes)),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Readable field type from /FT and the flags that refine it
+fn type_name(field_type: Option<&[u8]>, flags: i64) -> &'static str {
+    match field_type {
+        Some(b"Tx") => "text",
+        Some(b"Btn") if flags & FLAG_PUSHBUTTON != 0 => "pushbutton",
+        Some(b"Btn") if flags & FLAG_RADIO != 0 => "radio",
+        Some(b"Btn") => "checkbox",
+        Some(b"Ch") if flags & FLAG_COMBO != 0 => "combo",
+        Some(b"Ch") => "list",
+        Some(b"Sig") => "signature",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_document_without_form() {
+        assert!(analyze(&fixtures::document()).is_none());
+    }
+
+    #[test]
+    fn test_inherited_field_attributes() {
+        let mut doc = fixtures::document();
+        let child = doc
+            .add_object(dictionary! { "T" => Object::string_literal("city"), "V" => Object::string_literal("Paris") });
+        let parent = doc.add_object(dictionary! {
+            "T" => Object::string_literal("address"),
+            "FT" => "Tx",
+            "Kids" => vec![child.into()],
+        });
+        fixtures::with_form(&mut doc, dictionary! { "Fields" => vec![parent.into()] });
+
+        let report = analyze(&doc).unwrap();
+        assert_eq!(report.fields.len(), 1);
+        assert_eq!(report.fields[0].name, "address.city");
+        assert_eq!(report.fields[0].field_type, "text");
+        assert_eq!(report.fields[0].value.as_deref(), Some("Paris"));
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_calculation_script_and_external_submit() {
+        let mut doc = fixtures::document();
+        let total = doc.add_object(dictionary! {
+            "T" => Object::string_literal("total"),
+            "FT" => "Tx",
+            "AA" => dictionary! {
+                "C" => dictionary! { "S" => "JavaScript", "JS" => Object::string_literal("event.value = 1;") },
+            },
+        });
+        let widget = doc.add_object(dictionary! {
+            "Subtype" => "Widget",
+            "A" => dictionary! {
+                "S" => "SubmitForm",
+                "F" => dictionary! { "FS" => "URL", "F" => Object::string_literal("https://collect.example/form") },
+            },
+        });
+        let send = doc.add_object(dictionary! {
+            "T" => Object::string_literal("send"),
+            "FT" => "Btn",
+            "Ff" => FLAG_PUSHBUTTON,
+            "Kids" => vec![widget.into()],
+        });
+        fixtures::with_form(
+            &mut doc,
+            dictionary! { "Fields" => vec![total.into(), send.into()], "CO" => vec![total.into()] },
+        );
+
+        let report = analyze(&doc).unwrap();
+        assert_eq!(report.fields[1].field_type, "pushbutton");
+        let issues: Vec<_> = report.findings.iter().map(|f| (f.id, f.issue)).collect();
+        assert_eq!(
+            issues,
+            vec![(total, FormIssue::CalculationScript), (send, FormIssue::ExternalSubmit)]
+        );
+        assert!(report.findings[0].description.contains("/CO"));
+    }
+}
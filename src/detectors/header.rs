@@ -0,0 +1,230 @@
+//! File header anomaly detection
+//! Created: 2026-10-16 08:50:47 UTC
+//! Author: kartik4091
+//!
+//! Acrobat accepts a `%PDF-` header anywhere in the first 1024 bytes, which
+//! lets a file start with another format's magic bytes and slip past
+//! signature-based filters. The header version is also only a hint: the
+//! catalog /Version entry overrides it when later.
+
+use lopdf::{Document, Object};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::revisions::find;
+
+/// Window in which lenient readers look for the header
+pub const HEADER_WINDOW: usize = 1024;
+
+/// Kind of header anomaly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum HeaderAnomalyKind {
+    /// No `%PDF-` marker at all
+    MissingHeader,
+
+    /// Bytes precede the `%PDF-` marker
+    LeadingJunk,
+
+    /// Header version is not a valid `major.minor` PDF version
+    InvalidVersion,
+
+    /// Catalog /Version disagrees with the header
+    VersionMismatch,
+
+    /// No binary comment line after the header
+    MissingBinaryComment,
+}
+
+/// A single header finding
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HeaderAnomaly {
+    /// Anomaly kind
+    pub kind: HeaderAnomalyKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Header properties and anomalies
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct HeaderReport {
+    /// Offset of the `%PDF-` marker
+    pub offset: Option<usize>,
+
+    /// Version declared by the header
+    pub version: Option<String>,
+
+    /// Version declared by the catalog
+    pub catalog_version: Option<String>,
+
+    /// Whether a binary comment line follows the header
+    pub binary_comment: bool,
+
+    /// Detected anomalies
+    pub anomalies: Vec<HeaderAnomaly>,
+}
+
+/// Inspects the file header and compares it with the catalog
+#[instrument(skip(data, doc))]
+pub fn check(data: &[u8], doc: Option<&Document>) -> HeaderReport {
+    let mut report = HeaderReport {
+        catalog_version: doc.and_then(catalog_version),
+        ..Default::default()
+    };
+
+    let Some(offset) = find(data, b"%PDF-", 0) else {
+        report.anomalies.push(HeaderAnomaly {
+            kind: HeaderAnomalyKind::MissingHeader,
+            severity: Severity::High,
+            description: "No %PDF- header found".into(),
+        });
+        return report;
+    };
+    report.offset = Some(offset);
+
+    if offset > 0 {
+        let (severity, reach) = if offset <= HEADER_WINDOW {
+            (Severity::Medium, "accepted by Acrobat")
+        } else {
+            (Severity::High, "only accepted by lenient parsers")
+        };
+        report.anomalies.push(HeaderAnomaly {
+            kind: HeaderAnomalyKind::LeadingJunk,
+            severity,
+            description: format!("{} bytes precede the header ({})", offset, reach),
+        });
+    }
+
+    let line_end = data[offset..]
+        .iter()
+        .position(|&b| b == b'\r' || b == b'\n')
+        .map_or(data.len(), |pos| offset + pos);
+    let version = String::from_utf8_lossy(&data[offset + 5..line_end]).trim().to_string();
+    if !is_valid_version(&version) {
+        report.anomalies.push(HeaderAnomaly {
+            kind: HeaderAnomalyKind::InvalidVersion,
+            severity: Severity::Low,
+            description: format!("Header version {:?} is not a known PDF version", version),
+        });
+    }
+
+    if let Some(catalog) = &report.catalog_version {
+        if *catalog != version {
+            // A later catalog version is the sanctioned way to upgrade in an update
+            let severity = if is_valid_version(catalog) && catalog.as_str() > version.as_str() {
+                Severity::Info
+            } else {
+                Severity::Low
+            };
+            report.anomalies.push(HeaderAnomaly {
+                kind: HeaderAnomalyKind::VersionMismatch,
+                severity,
+                description: format!("Header declares {}, catalog declares {}", version, catalog),
+            });
+        }
+    }
+
+    report.binary_comment = has_binary_comment(data, line_end);
+    if !report.binary_comment {
+        report.anomalies.push(HeaderAnomaly {
+            kind: HeaderAnomalyKind::MissingBinaryComment,
+            severity: Severity::Info,
+            description: "No binary comment line after the header".into(),
+        });
+    }
+
+    report.version = Some(version);
+    debug!("Header at {:?} with {} anomalies", report.offset, report.anomalies.len());
+    report
+}
+
+fn catalog_version(doc: &Document) -> Option<String> {
+    let root = doc.trailer.get(b"Root").ok()?.as_reference().ok()?;
+    match doc.get_dictionary(root).ok()?.get(b"Version").ok()? {
+        Object::Name(name) => Some(String::from_utf8_lossy(name).into_owned()),
+        _ => None,
+    }
+}
+
+fn is_valid_version(version: &str) -> bool {
+    matches!(
+        version,
+        "1.0" | "1.1" | "1.2" | "1.3" | "1.4" | "1.5" | "1.6" | "1.7" | "2.0"
+    )
+}
+
+/// Whether the line after the header is a comment with at least four
+/// bytes of 128 or above, as writers emit to mark the file as binary
+fn has_binary_comment(data: &[u8], header_end: usize) -> bool {
+    let start = data[header_end..]
+        .iter()
+        .position(|&b| b != b'\r' && b != b'\n')
+        .map_or(data.len(), |pos| header_end + pos);
+    if data.get(start) != Some(&b'%') {
+        return false;
+    }
+    data[start..]
+        .iter()
+        .take_while(|&&b| b != b'\r' && b != b'\n')
+        .filter(|&&b| b >= 128)
+        .count()
+        >= 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    fn kinds(report: &HeaderReport) -> Vec<HeaderAnomalyKind> {
+        report.anomalies.iter().map(|a| a.kind).collect()
+    }
+
+    #[test]
+    fn test_regular_header() {
+        let data = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n1 0 obj\nnull\nendobj\n";
+        let report = check(data, None);
+
+        assert_eq!(report.offset, Some(0));
+        assert_eq!(report.version.as_deref(), Some("1.7"));
+        assert!(report.binary_comment);
+        assert!(report.anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_leading_junk_and_bad_version() {
+        let mut data = b"MZ\x90\x00".to_vec();
+        data.extend_from_slice(b"%PDF-1.9\n1 0 obj\nnull\nendobj\n");
+
+        let report = check(&data, None);
+        assert_eq!(report.offset, Some(4));
+        let kinds = kinds(&report);
+        assert!(kinds.contains(&HeaderAnomalyKind::LeadingJunk));
+        assert!(kinds.contains(&HeaderAnomalyKind::InvalidVersion));
+        assert!(kinds.contains(&HeaderAnomalyKind::MissingBinaryComment));
+    }
+
+    #[test]
+    fn test_catalog_version_mismatch() {
+        let mut doc = fixtures::document();
+        doc.get_dictionary_mut(fixtures::CATALOG_ID)
+            .unwrap()
+            .set("Version", Object::Name(b"1.3".to_vec()));
+        let data = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n";
+
+        let report = check(data, Some(&doc));
+        assert_eq!(kinds(&report), vec![HeaderAnomalyKind::VersionMismatch]);
+        assert_eq!(report.anomalies[0].severity, Severity::Low);
+    }
+
+    #[test]
+    fn test_missing_header() {
+        let report = check(b"GIF89a", None);
+        assert_eq!(kinds(&report), vec![HeaderAnomalyKind::MissingHeader]);
+    }
+}
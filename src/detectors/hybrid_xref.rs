@@ -0,0 +1,246 @@
+//! Hybrid-reference (xref table + /XRefStm stream) consistency checking
+//! Created: 2026-10-16 08:42:40 UTC
+//! Author: kartik4091
+//!
+//! A hybrid-reference file carries a classic xref table for PDF 1.4 readers
+//! and a cross-reference stream, referenced by /XRefStm, for newer ones. The
+//! two are supposed to describe the same objects; when they do not, readers
+//! of different generations load different content.
+
+use std::collections::BTreeMap;
+
+use lopdf::Object;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    display_value, object_digest,
+    parser::parse_indirect,
+    xref::{read_sections, XrefKind, XrefSection, XrefState},
+};
+
+/// Disagreement between the table and stream views of one object
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HybridDivergence {
+    /// Object number
+    pub number: u32,
+
+    /// State seen by table-only readers
+    pub table: XrefState,
+
+    /// State seen by stream-aware readers
+    pub stream: XrefState,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Comparison of one table with its /XRefStm stream
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HybridSection {
+    /// Offset of the classic xref table
+    pub table_offset: u64,
+
+    /// Offset of the cross-reference stream
+    pub stream_offset: u64,
+
+    /// Objects the two views disagree on
+    pub divergences: Vec<HybridDivergence>,
+
+    /// Trailer keys whose values differ between the two views
+    pub trailer_conflicts: Vec<String>,
+}
+
+/// Hybrid-reference analysis of a document
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct HybridReport {
+    /// Each table/stream pair found in the xref chain
+    pub sections: Vec<HybridSection>,
+}
+
+impl HybridReport {
+    /// Whether the two views agree everywhere
+    pub fn is_consistent(&self) -> bool {
+        self.sections
+            .iter()
+            .all(|s| s.divergences.is_empty() && s.trailer_conflicts.is_empty())
+    }
+}
+
+/// Trailer keys both views must agree on
+const TRAILER_KEYS: [&[u8]; 4] = [b"Root", b"Info", b"Encrypt", b"ID"];
+
+/// Detects hybrid-reference sections and compares both views.
+/// Returns `None` for documents that are not hybrid.
+#[instrument(skip(data))]
+pub fn check(data: &[u8]) -> Option<HybridReport> {
+    let sections = read_sections(data).ok()?;
+    let mut report = HybridReport::default();
+
+    for (i, table) in sections.iter().enumerate() {
+        if table.kind != XrefKind::Table || table.xref_stm().is_none() {
+            continue;
+        }
+        let Some(stream) = sections.get(i + 1).filter(|s| s.via_xref_stm) else {
+            continue;
+        };
+        report.sections.push(compare(data, table, stream));
+    }
+
+    debug!("Found {} hybrid xref sections", report.sections.len());
+    (!report.sections.is_empty()).then_some(report)
+}
+
+fn compare(data: &[u8], table: &XrefSection, stream: &XrefSection) -> HybridSection {
+    let table_entries: BTreeMap<u32, XrefState> =
+        table.entries.iter().map(|e| (e.number, e.state)).collect();
+
+    let divergences = stream
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            let table_state = *table_entries.get(&entry.number)?;
+            classify(data, entry.number, table_state, entry.state)
+        })
+        .collect();
+
+    let trailer_conflicts = TRAILER_KEYS
+        .iter()
+        .filter_map(|key| {
+            let a = table.trailer.get(key).ok().map(display_value);
+            let b = stream.trailer.get(key).ok().map(display_value);
+            // The stream dictionary may omit keys the table trailer provides
+            (a.is_some() && b.is_some() && a != b).then(|| {
+                format!(
+                    "/{}: table {} vs stream {}",
+                    String::from_utf8_lossy(key),
+                    a.unwrap_or_default(),
+                    b.unwrap_or_default()
+                )
+            })
+        })
+        .collect();
+
+    HybridSection {
+        table_offset: table.offset,
+        stream_offset: stream.offset,
+        divergences,
+        trailer_conflicts,
+    }
+}
+
+fn classify(data: &[u8], number: u32, table: XrefState, stream: XrefState) -> Option<HybridDivergence> {
+    let (severity, description) = match (table, stream) {
+        // Expected layout: the table hides compressed objects from old readers
+        (XrefState::Free { .. }, XrefState::Compressed { .. }) => return None,
+        (XrefState::Free { .. }, XrefState::Free { .. }) => return None,
+        (XrefState::InUse { offset: a, .. }, XrefState::InUse { offset: b, .. }) if a == b => {
+            return None
+        }
+        (XrefState::InUse { offset: a, .. }, XrefState::InUse { offset: b, .. }) => {
+            if same_content(data, a, b) {
+                (Severity::Low, format!("Offsets {} and {} hold identical definitions", a, b))
+            } else {
+                (Severity::High, format!("Table offset {} and stream offset {} hold different objects", a, b))
+            }
+        }
+        (XrefState::InUse { offset, .. }, XrefState::Compressed { stream, .. }) => (
+            Severity::High,
+            format!(
+                "Legacy readers load the object at {} while others load it from object stream {}",
+                offset, stream
+            ),
+        ),
+        (XrefState::InUse { .. }, XrefState::Free { .. }) => (
+            Severity::Medium,
+            "Object is live for legacy readers but free for stream-aware readers".into(),
+        ),
+        (XrefState::Free { .. }, XrefState::InUse { .. }) => (
+            Severity::Medium,
+            "Object is free for legacy readers but live for stream-aware readers".into(),
+        ),
+        (_, _) => (Severity::Medium, "Table and stream entries disagree".into()),
+    };
+
+    Some(HybridDivergence { number, table, stream, severity, description })
+}
+
+fn same_content(data: &[u8], a: u64, b: u64) -> bool {
+    let parse = |offset: u64| -> Option<Object> {
+        parse_indirect(data, offset as usize).ok().map(|p| p.object)
+    };
+    match (parse(a), parse(b)) {
+        (Some(x), Some(y)) => object_digest(&x) == object_digest(&y),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    /// Builds a hybrid file whose table and stream disagree on object 2
+    fn hybrid_pdf(stream_offset_for_2: Option<usize>) -> Vec<u8> {
+        let mut data = b"%PDF-1.5\n".to_vec();
+        let first = data.len();
+        data.extend_from_slice(b"1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n");
+        let second = data.len();
+        data.extend_from_slice(b"2 0 obj\n<< /Type /Pages /Kids [] /Count 0 >>\nendobj\n");
+        let shadow = data.len();
+        data.extend_from_slice(b"2 0 obj\n<< /Type /Pages /Kids [] /Count 0 /Shadow true >>\nendobj\n");
+
+        let target = stream_offset_for_2.map(|_| shadow).unwrap_or(second);
+        let stream_offset = data.len();
+        let rows: Vec<u8> = [(1u8, first), (1, target)]
+            .iter()
+            .flat_map(|(kind, offset)| [*kind, (offset >> 8) as u8, *offset as u8, 0])
+            .collect();
+        data.extend_from_slice(
+            format!(
+                "3 0 obj\n<< /Type /XRef /Size 3 /Index [1 2] /W [1 2 1] /Length {} >>\nstream\n",
+                rows.len()
+            )
+            .as_bytes(),
+        );
+        data.extend_from_slice(&rows);
+        data.extend_from_slice(b"\nendstream\nendobj\n");
+
+        let xref = data.len();
+        data.extend_from_slice(
+            format!(
+                "xref\n0 3\n0000000000 65535 f \n{:010} 00000 n \n{:010} 00000 n \n\
+                 trailer\n<< /Size 4 /Root 1 0 R /XRefStm {} >>\nstartxref\n{}\n%%EOF\n",
+                first, second, stream_offset, xref
+            )
+            .as_bytes(),
+        );
+        data
+    }
+
+    #[test]
+    fn test_not_hybrid() {
+        assert!(check(&fixtures::simple_pdf()).is_none());
+    }
+
+    #[test]
+    fn test_consistent_hybrid() {
+        let report = check(&hybrid_pdf(None)).unwrap();
+        assert!(report.is_consistent());
+    }
+
+    #[test]
+    fn test_divergent_hybrid() {
+        let report = check(&hybrid_pdf(Some(0))).unwrap();
+        assert!(!report.is_consistent());
+
+        let divergence = &report.sections[0].divergences[0];
+        assert_eq!(divergence.number, 2);
+        assert_eq!(divergence.severity, Severity::High);
+    }
+}
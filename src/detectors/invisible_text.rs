@@ -0,0 +1,220 @@
+//! Invisible text detection
+//! Created: 2026-10-16 09:35:34 UTC
+//! Author: kartik4091
+//!
+//! Text can be present in a page's content, and therefore extracted,
+//! indexed and read by machines, while never showing up on screen: render
+//! mode 3 paints nothing, white glyphs vanish on a white page, glyphs can
+//! be scaled down to a fraction of a point or placed outside the page.
+//! This pass interprets each page's content stream and reports text that
+//! is hidden by any of these techniques.
+
+use std::collections::BTreeMap;
+
+use lopdf::{content::Content, Document, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::{
+    document::{
+        content::{contains, interpret, intersects, Color, ContentEvent, IDENTITY},
+        page_box, printable_preview,
+    },
+    text::font::PageFonts,
+};
+
+/// Device-space glyph size below which text cannot be read
+const MIN_APPARENT_SIZE: f64 = 1.0;
+
+/// How the text is hidden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub enum Concealment {
+    /// Render mode 3 (or 7): glyphs are neither filled nor stroked
+    RenderMode,
+
+    /// Glyph color equals the background, e.g. white on white
+    MatchesBackground,
+
+    /// Glyphs scaled to near-zero size
+    Tiny,
+
+    /// Glyphs placed outside the page box
+    OffPage,
+}
+
+impl Concealment {
+    fn describe(&self) -> &'static str {
+        match self {
+            Self::RenderMode => "invisible render mode",
+            Self::MatchesBackground => "color matching the background",
+            Self::Tiny => "near-zero glyph size",
+            Self::OffPage => "placement outside the page",
+        }
+    }
+}
+
+/// Text on one page hidden by one technique
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct InvisibleText {
+    /// Page object
+    pub page: ObjectId,
+
+    /// One-based page number
+    pub page_number: u32,
+
+    /// Technique
+    pub technique: Concealment,
+
+    /// Hidden text, one text operator per line
+    pub text: String,
+
+    /// Number of text operators
+    pub runs: usize,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Interprets every page and reports text that would not be visible
+#[instrument(skip(doc))]
+pub fn detect(doc: &Document) -> Vec<InvisibleText> {
+    let mut found = Vec::new();
+
+    for (page_number, page) in doc.get_pages() {
+        let Some(content) = doc
+            .get_page_content(page)
+            .ok()
+            .and_then(|data| Content::decode(&data).ok())
+        else {
+            continue;
+        };
+        let page_box = doc.get_dictionary(page).ok().and_then(|dict| page_box(doc, dict));
+        let fonts = PageFonts::load(doc, page);
+        let events = interpret(&content.operations, IDENTITY, &fonts);
+
+        // Scanned pages carry an invisible OCR text layer over the image
+        let paints_xobjects = events.iter().any(|e| matches!(e, ContentEvent::XObject { .. }));
+        let mut hidden: BTreeMap<Concealment, Vec<String>> = BTreeMap::new();
+
+        for (position, event) in events.iter().enumerate() {
+            let ContentEvent::Text(text) = event else {
+                continue;
+            };
+            let shown = fonts.decode(&text.font, &text.bytes);
+            if shown.trim().is_empty() {
+                continue;
+            }
+
+            let technique = if text.is_invisible_mode() {
+                Concealment::RenderMode
+            } else if text.apparent_size < MIN_APPARENT_SIZE {
+                Concealment::Tiny
+            } else if page_box.map_or(false, |page_box| !intersects(&text.bbox, &page_box)) {
+                Concealment::OffPage
+            } else if text.paint_color().matches(&background(&events[..position], &text.bbox)) {
+                Concealment::MatchesBackground
+            } else {
+                continue;
+            };
+            hidden.entry(technique).or_default().push(shown);
+        }
+
+        for (technique, runs) in hidden {
+            let text = runs.join("\n");
+            let severity = match technique {
+                Concealment::RenderMode if paints_xobjects => Severity::Low,
+                _ => Severity::Medium,
+            };
+            found.push(InvisibleText {
+                page,
+                page_number,
+                technique,
+                description: format!(
+                    "{} text runs on page {} hidden by {}: \"{}\"",
+                    runs.len(),
+                    page_number,
+                    technique.describe(),
+                    printable_preview(text.replace('\n', " ").as_bytes(), 60)
+                ),
+                runs: runs.len(),
+                text,
+                severity,
+            });
+        }
+    }
+
+    debug!("Found {} invisible text groups", found.len());
+    found
+}
+
+/// Color under a box: the last fill covering it, or the white page.
+///
+/// Images, forms and shadings painted under the box leave the background
+/// unknown, which matches no text color.
+fn background(painted: &[ContentEvent], bbox: &[f64; 4]) -> Color {
+    painted
+        .iter()
+        .rev()
+        .find_map(|event| match event {
+            ContentEvent::Fill { bbox: fill, color } if contains(fill, bbox) => Some(*color),
+            ContentEvent::XObject { bbox: image, .. } | ContentEvent::InlineImage { bbox: image, .. }
+                if intersects(image, bbox) =>
+            {
+                Some(Color::Other)
+            }
+            ContentEvent::Shading { .. } => Some(Color::Other),
+            _ => None,
+        })
+        .unwrap_or(Color::Gray(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures::{self, with_content};
+
+    #[test]
+    fn test_visible_text_is_not_reported() {
+        assert!(detect(&fixtures::document()).is_empty());
+        let doc = with_content(b"0 0 0 rg 0 0 595 842 re f 1 g BT /F1 12 Tf 72 700 Td (white on black) Tj ET");
+        assert!(detect(&doc).is_empty());
+    }
+
+    #[test]
+    fn test_text_over_images_and_spot_colors_is_not_reported() {
+        let doc = with_content(
+            b"q 200 0 0 50 70 690 cm /Im0 Do Q 1 g BT /F1 12 Tf 72 700 Td (over image) Tj ET \
+              /CS0 cs 1 sc BT /F1 12 Tf 72 600 Td (spot color) Tj ET /Sh0 sh 1 g BT /F1 12 Tf 72 500 Td (shaded) Tj ET",
+        );
+        assert!(detect(&doc).is_empty(), "{:?}", detect(&doc));
+    }
+
+    #[test]
+    fn test_white_on_white_and_render_mode() {
+        let doc = with_content(b"BT /F1 12 Tf 72 700 Td 1 1 1 rg (hidden note) Tj 0 g 0 -20 Td 3 Tr (ocr layer) Tj ET");
+        let found = detect(&doc);
+        let techniques: Vec<_> = found.iter().map(|f| (f.technique, f.text.as_str())).collect();
+        assert_eq!(
+            techniques,
+            vec![
+                (Concealment::RenderMode, "ocr layer"),
+                (Concealment::MatchesBackground, "hidden note")
+            ]
+        );
+        assert!(found
+            .iter()
+            .all(|f| f.severity == Severity::Medium && f.page_number == 1));
+    }
+
+    #[test]
+    fn test_tiny_and_off_page_text() {
+        let doc = with_content(b"BT /F1 0.1 Tf 72 700 Td (tiny) Tj /F1 12 Tf 5000 0 Td (far away) Tj ET");
+        let techniques: Vec<_> = detect(&doc).iter().map(|f| f.technique).collect();
+        assert_eq!(techniques, vec![Concealment::Tiny, Concealment::OffPage]);
+    }
+}
@@ -0,0 +1,284 @@
+//! Language detection of document text
+//! Created: 2026-10-16 10:01:19 UTC
+//! Author: kartik4091
+//!
+//! Detects the language of each page's text and compares the dominant one
+//! with the language the document claims (catalog /Lang, XMP dc:language).
+//! Campaign kits are often localized by swapping the text of a template, so
+//! a claimed locale that does not match the content is a useful triage
+//! signal.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Document, Object};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::{
+    document::{decode_text, metadata_text},
+    text,
+};
+
+/// Pages with fewer letters than this are too short to classify
+const MIN_LETTERS: usize = 40;
+
+/// ISO 639-1 codes of the languages the detector knows, with their ISO 639-3 codes
+const ISO_639_1: [(&str, &str); 40] = [
+    ("af", "afr"),
+    ("ar", "ara"),
+    ("bg", "bul"),
+    ("bn", "ben"),
+    ("cs", "ces"),
+    ("da", "dan"),
+    ("de", "deu"),
+    ("el", "ell"),
+    ("en", "eng"),
+    ("es", "spa"),
+    ("et", "est"),
+    ("fa", "pes"),
+    ("fi", "fin"),
+    ("fr", "fra"),
+    ("he", "heb"),
+    ("hi", "hin"),
+    ("hr", "hrv"),
+    ("hu", "hun"),
+    ("id", "ind"),
+    ("it", "ita"),
+    ("ja", "jpn"),
+    ("ko", "kor"),
+    ("lt", "lit"),
+    ("lv", "lav"),
+    ("nl", "nld"),
+    ("no", "nob"),
+    ("nb", "nob"),
+    ("pl", "pol"),
+    ("pt", "por"),
+    ("ro", "ron"),
+    ("ru", "rus"),
+    ("sk", "slk"),
+    ("sl", "slv"),
+    ("sr", "srp"),
+    ("sv", "swe"),
+    ("th", "tha"),
+    ("tr", "tur"),
+    ("uk", "ukr"),
+    ("vi", "vie"),
+    ("zh", "cmn"),
+];
+
+/// A language the document declares
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ClaimedLanguage {
+    /// Where it is declared (`Lang` or `dc:language`)
+    pub source: String,
+
+    /// Language tag as written (BCP 47)
+    pub tag: String,
+}
+
+/// Detected language of one page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PageLanguage {
+    /// One-based page number
+    pub page: u32,
+
+    /// ISO 639-3 code
+    pub language: String,
+
+    /// English name of the language
+    pub name: String,
+
+    /// Writing system
+    pub script: String,
+
+    /// Detector confidence (0.0 to 1.0)
+    pub confidence: f64,
+
+    /// Letters the detection was based on
+    pub letters: usize,
+}
+
+/// Claimed language that differs from the content
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LanguageMismatch {
+    /// Declared tags
+    pub claimed: Vec<String>,
+
+    /// Dominant detected language (ISO 639-3)
+    pub detected: String,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Languages declared by and detected in the document
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LanguageReport {
+    /// Declared languages
+    pub claimed: Vec<ClaimedLanguage>,
+
+    /// Pages with enough text to classify
+    pub pages: Vec<PageLanguage>,
+
+    /// Language of most of the classified text (ISO 639-3)
+    pub dominant: Option<String>,
+
+    /// Declared languages not matching the content
+    pub mismatch: Option<LanguageMismatch>,
+}
+
+/// Detects page languages and checks them against the declared ones
+#[instrument(skip(doc))]
+pub fn analyze(doc: &Document) -> LanguageReport {
+    let claimed = claimed_languages(doc);
+
+    let pages: Vec<PageLanguage> = text::extract(doc)
+        .into_iter()
+        .filter_map(|page| {
+            let text = page.text();
+            let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+            if letters < MIN_LETTERS {
+                return None;
+            }
+            let info = whatlang::detect(&text)?;
+            Some(PageLanguage {
+                page: page.page_number,
+                language: info.lang().code().to_string(),
+                name: info.lang().eng_name().to_string(),
+                script: info.script().name().to_string(),
+                confidence: info.confidence(),
+                letters,
+            })
+        })
+        .collect();
+
+    let mut weights: BTreeMap<&str, usize> = BTreeMap::new();
+    for page in &pages {
+        *weights.entry(page.language.as_str()).or_default() += page.letters;
+    }
+    let dominant = weights
+        .into_iter()
+        .max_by_key(|(_, letters)| *letters)
+        .map(|(language, _)| language.to_string());
+
+    let mismatch = dominant.as_ref().filter(|_| !claimed.is_empty()).and_then(|detected| {
+        let known: Vec<&ClaimedLanguage> = claimed.iter().filter(|c| iso_639_3(&c.tag).is_some()).collect();
+        let matches = known.iter().any(|c| iso_639_3(&c.tag) == Some(detected.as_str()));
+        // Tags the detector cannot produce say nothing about a mismatch
+        (!known.is_empty() && !matches).then(|| LanguageMismatch {
+            claimed: known.iter().map(|c| c.tag.clone()).collect(),
+            detected: detected.clone(),
+            severity: Severity::Low,
+            description: format!(
+                "Document declares {} but its text is {}",
+                known.iter().map(|c| c.tag.as_str()).collect::<Vec<_>>().join(", "),
+                pages
+                    .iter()
+                    .find(|p| &p.language == detected)
+                    .map_or(detected.as_str(), |p| p.name.as_str())
+            ),
+        })
+    });
+
+    debug!("Classified {} pages, dominant language {:?}", pages.len(), dominant);
+    LanguageReport {
+        claimed,
+        pages,
+        dominant,
+        mismatch,
+    }
+}
+
+/// ISO 639-3 code for the primary subtag of a BCP 47 tag
+fn iso_639_3(tag: &str) -> Option<&'static str> {
+    let primary = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+    ISO_639_1
+        .iter()
+        .find(|(two, three)| *two == primary || *three == primary)
+        .map(|(_, three)| *three)
+}
+
+fn claimed_languages(doc: &Document) -> Vec<ClaimedLanguage> {
+    let mut claimed = Vec::new();
+    if let Ok(Object::String(bytes, _)) = doc.catalog().and_then(|catalog| catalog.get(b"Lang")) {
+        claimed.push(ClaimedLanguage {
+            source: "Lang".to_string(),
+            tag: decode_text(bytes).trim().to_string(),
+        });
+    }
+
+    let pattern = Regex::new(r"(?s)<dc:language>.*?</dc:language>").expect("valid XMP pattern");
+    let item = Regex::new(r"<rdf:li[^>]*>([^<]+)</rdf:li>").expect("valid XMP pattern");
+    for (key, value) in metadata_text(doc) {
+        if key != "XMP" {
+            continue;
+        }
+        for block in pattern.find_iter(&value) {
+            for tag in item.captures_iter(block.as_str()) {
+                let tag = tag[1].trim().to_string();
+                // "x-default" and "x-unknown" are placeholders, not languages
+                if !tag.starts_with("x-") {
+                    claimed.push(ClaimedLanguage {
+                        source: "dc:language".to_string(),
+                        tag,
+                    });
+                }
+            }
+        }
+    }
+    claimed.retain(|c| !c.tag.is_empty());
+    claimed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    fn document_with_text(text: &str, lang: &str) -> Document {
+        let mut doc = fixtures::document();
+        let content = doc.add_object(Stream::new(
+            dictionary! {},
+            format!("BT /F1 10 Tf 72 700 Td ({}) Tj ET", text).into_bytes(),
+        ));
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Contents", content);
+        doc.get_dictionary_mut(fixtures::CATALOG_ID)
+            .unwrap()
+            .set("Lang", Object::string_literal(lang));
+        doc
+    }
+
+    #[test]
+    fn test_matching_language() {
+        let doc = document_with_text(
+            "Please review the attached invoice and confirm the payment details before the end of the week.",
+            "en-US",
+        );
+        let report = analyze(&doc);
+        assert_eq!(report.claimed[0].tag, "en-US");
+        assert_eq!(report.dominant.as_deref(), Some("eng"));
+        assert!(report.mismatch.is_none());
+    }
+
+    #[test]
+    fn test_declared_language_mismatch() {
+        let doc = document_with_text(
+            "Bitte senden Sie uns Ihre Zahlungsdaten innerhalb von vierundzwanzig Stunden, sonst wird Ihr Konto gesperrt.",
+            "en-GB",
+        );
+        let report = analyze(&doc);
+        let mismatch = report.mismatch.unwrap();
+        assert_eq!(mismatch.detected, "deu");
+        assert_eq!(mismatch.claimed, vec!["en-GB".to_string()]);
+        assert_eq!(iso_639_3("pt-BR"), Some("por"));
+    }
+}
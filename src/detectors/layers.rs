@@ -0,0 +1,450 @@
+//! Optional content group (layer) hidden-content detection
+//! Created: 2026-10-16 09:36:35 UTC
+//! Author: kartik4091
+//!
+//! Optional content groups let a document switch parts of its content on
+//! and off. Layers that are off in the default configuration are never
+//! shown unless the reader opens the layers panel, and a layer can be set
+//! to appear only when the document is printed. This pass reports the
+//! layers hidden by default and extracts the content bound to them, from
+//! marked-content sequences in page streams as well as XObjects and
+//! annotations carrying /OC.
+
+use std::collections::BTreeSet;
+
+use lopdf::{
+    content::{Content, Operation},
+    Dictionary, Document, Object, ObjectId,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{array_references, decode_text, name_value, printable_preview, resolve, stream_data};
+
+/// Length of raw content excerpts
+const EXCERPT: usize = 200;
+
+/// One optional content group
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Layer {
+    /// OCG object
+    pub id: ObjectId,
+
+    /// Layer name shown in the layers panel
+    pub name: String,
+
+    /// Off in the default configuration
+    pub hidden: bool,
+
+    /// Listed in /Locked: the reader cannot toggle it
+    pub locked: bool,
+
+    /// Hidden on screen but switched on for printing (/AS with a Print event)
+    pub print_only: bool,
+}
+
+/// Where hidden content was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum HiddenKind {
+    /// `/OC /Name BDC ... EMC` sequence in a page content stream
+    MarkedContent,
+
+    /// XObject with /OC
+    XObject,
+
+    /// Annotation with /OC
+    Annotation,
+}
+
+/// Content bound to layers that are hidden by default
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HiddenContent {
+    /// Kind of content
+    pub kind: HiddenKind,
+
+    /// Page whose content stream holds the sequence
+    pub page: Option<ObjectId>,
+
+    /// XObject or annotation object
+    pub object: Option<ObjectId>,
+
+    /// Layers that keep the content hidden
+    pub layers: Vec<ObjectId>,
+
+    /// Text shown by the content
+    pub text: String,
+
+    /// Raw content excerpt
+    pub excerpt: String,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Optional content configuration of a document
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LayerReport {
+    /// Layers listed in /OCProperties /OCGs
+    pub layers: Vec<Layer>,
+
+    /// Content hidden by the default configuration
+    pub hidden_content: Vec<HiddenContent>,
+}
+
+impl LayerReport {
+    /// Layers that are off in the default configuration
+    pub fn hidden(&self) -> impl Iterator<Item = &Layer> {
+        self.layers.iter().filter(|layer| layer.hidden)
+    }
+}
+
+/// Parses /OCProperties and extracts the content of hidden layers
+#[instrument(skip(doc))]
+pub fn analyze(doc: &Document) -> LayerReport {
+    let mut report = LayerReport::default();
+    let Some(properties) = doc
+        .trailer
+        .get(b"Root")
+        .ok()
+        .and_then(|root| resolve(doc, root).as_dict().ok())
+        .and_then(|catalog| catalog.get(b"OCProperties").ok())
+        .and_then(|p| resolve(doc, p).as_dict().ok())
+    else {
+        return report;
+    };
+
+    let groups = array_references(doc, properties.get(b"OCGs").ok());
+    let config = properties.get(b"D").ok().and_then(|d| resolve(doc, d).as_dict().ok());
+    let list = |key: &[u8]| -> BTreeSet<ObjectId> {
+        array_references(doc, config.and_then(|c| c.get(key).ok()))
+            .into_iter()
+            .collect()
+    };
+    let (on, off, locked) = (list(b"ON"), list(b"OFF"), list(b"Locked"));
+    let base_off = config.and_then(|c| name_value(c, b"BaseState")) == Some(b"OFF");
+    let printed = print_events(doc, config);
+
+    for id in groups {
+        let Ok(group) = doc.get_dictionary(id) else {
+            continue;
+        };
+        let hidden = off.contains(&id) || (base_off && !on.contains(&id));
+        report.layers.push(Layer {
+            id,
+            name: match group.get(b"Name").map(|n| resolve(doc, n)) {
+                Ok(Object::String(bytes, _)) => decode_text(bytes),
+                _ => format!("<object {} {}>", id.0, id.1),
+            },
+            hidden,
+            locked: locked.contains(&id),
+            print_only: hidden && printed.contains(&id) && prints(doc, group),
+        });
+    }
+
+    let hidden: BTreeSet<ObjectId> = report.hidden().map(|layer| layer.id).collect();
+    if hidden.is_empty() {
+        return report;
+    }
+
+    for (_, page) in doc.get_pages() {
+        marked_content(doc, page, &hidden, &mut report.hidden_content);
+    }
+
+    for (&id, object) in &doc.objects {
+        let (dict, kind) = match object {
+            Object::Stream(stream) => (&stream.dict, HiddenKind::XObject),
+            Object::Dictionary(dict) if dict.has(b"Rect") => (dict, HiddenKind::Annotation),
+            _ => continue,
+        };
+        let Some(layers) = dict.get(b"OC").ok().and_then(|oc| hidden_layers(doc, oc, &hidden)) else {
+            continue;
+        };
+        let (text, excerpt) = match object {
+            Object::Stream(stream) => {
+                let data = stream_data(stream).unwrap_or_default();
+                let text = Content::decode(&data)
+                    .map(|c| shown_text(&c.operations))
+                    .unwrap_or_default();
+                (text, printable_preview(&data, EXCERPT))
+            }
+            _ => match dict.get(b"Contents").map(|c| resolve(doc, c)) {
+                Ok(Object::String(bytes, _)) => (decode_text(bytes), String::new()),
+                _ => (String::new(), String::new()),
+            },
+        };
+        report
+            .hidden_content
+            .push(hidden_content(kind, None, Some(id), layers, text, excerpt));
+    }
+
+    debug!(
+        "Found {} layers, {} hidden, {} hidden content items",
+        report.layers.len(),
+        hidden.len(),
+        report.hidden_content.len()
+    );
+    report
+}
+
+/// Extracts `/OC` marked-content sequences of hidden layers from a page
+fn marked_content(doc: &Document, page: ObjectId, hidden: &BTreeSet<ObjectId>, found: &mut Vec<HiddenContent>) {
+    let Some(content) = doc
+        .get_page_content(page)
+        .ok()
+        .and_then(|data| Content::decode(&data).ok())
+    else {
+        return;
+    };
+    let (inline, referenced) = doc.get_page_resources(page);
+    let mut resources: Vec<&Dictionary> = inline.into_iter().collect();
+    resources.extend(referenced.into_iter().filter_map(|id| doc.get_dictionary(id).ok()));
+
+    // Each open BMC/BDC pushes the hidden layers it starts, if any
+    let mut stack: Vec<Option<(usize, Vec<ObjectId>)>> = Vec::new();
+    for (index, operation) in content.operations.iter().enumerate() {
+        match operation.operator.as_str() {
+            "BMC" => stack.push(None),
+            "BDC" => {
+                let inside_hidden = stack.iter().any(Option::is_some);
+                let layers = match operation.operands.as_slice() {
+                    [Object::Name(tag), property] if tag == b"OC" && !inside_hidden => {
+                        property_list(doc, &resources, property).and_then(|oc| hidden_layers(doc, oc, hidden))
+                    }
+                    _ => None,
+                };
+                stack.push(layers.map(|layers| (index, layers)));
+            }
+            "EMC" => {
+                if let Some(Some((start, layers))) = stack.pop() {
+                    let operations = &content.operations[start + 1..index];
+                    let raw = Content {
+                        operations: operations.to_vec(),
+                    }
+                    .encode()
+                    .unwrap_or_default();
+                    found.push(hidden_content(
+                        HiddenKind::MarkedContent,
+                        Some(page),
+                        None,
+                        layers,
+                        shown_text(operations),
+                        printable_preview(&raw, EXCERPT),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn hidden_content(
+    kind: HiddenKind,
+    page: Option<ObjectId>,
+    object: Option<ObjectId>,
+    layers: Vec<ObjectId>,
+    text: String,
+    excerpt: String,
+) -> HiddenContent {
+    let location = match (page, object) {
+        (_, Some((num, gen))) => format!("object {} {}", num, gen),
+        (Some((num, gen)), None) => format!("page {} {}", num, gen),
+        (None, None) => "document".to_string(),
+    };
+    let (severity, shown) = if text.trim().is_empty() {
+        (Severity::Low, String::new())
+    } else {
+        (
+            Severity::Medium,
+            format!(": \"{}\"", printable_preview(text.trim().as_bytes(), 60)),
+        )
+    };
+    HiddenContent {
+        kind,
+        page,
+        object,
+        description: format!("{:?} in hidden layer at {}{}", kind, location, shown),
+        layers,
+        text,
+        excerpt,
+        severity,
+    }
+}
+
+/// Resolves a BDC property operand: a /Properties name or an inline dictionary
+fn property_list<'a>(doc: &'a Document, resources: &[&'a Dictionary], property: &'a Object) -> Option<&'a Object> {
+    match property {
+        Object::Name(name) => resources.iter().find_map(|resources| {
+            let properties = resolve(doc, resources.get(b"Properties").ok()?).as_dict().ok()?;
+            properties.get(name).ok()
+        }),
+        other => Some(other),
+    }
+}
+
+/// Hidden layers controlling an /OC value, or `None` if it is visible by default.
+///
+/// Optional content membership dictionaries are evaluated with their
+/// visibility policy (/P), defaulting to AnyOn.
+fn hidden_layers(doc: &Document, oc: &Object, hidden: &BTreeSet<ObjectId>) -> Option<Vec<ObjectId>> {
+    if let Ok(id) = oc.as_reference() {
+        if hidden.contains(&id) {
+            return Some(vec![id]);
+        }
+    }
+    let dict = resolve(doc, oc).as_dict().ok()?;
+    if name_value(dict, b"Type") != Some(b"OCMD") {
+        return None;
+    }
+
+    let members = match dict.get(b"OCGs") {
+        Ok(Object::Reference(id)) if doc.get_dictionary(*id).is_ok() => vec![*id],
+        Ok(other) => array_references(doc, Some(other)),
+        Err(_) => Vec::new(),
+    };
+    let off: Vec<ObjectId> = members.iter().copied().filter(|id| hidden.contains(id)).collect();
+    let visible = match name_value(dict, b"P") {
+        Some(b"AllOn") => off.is_empty(),
+        Some(b"AnyOff") => !off.is_empty(),
+        Some(b"AllOff") => off.len() == members.len(),
+        _ => off.len() < members.len(),
+    };
+    (!visible && !members.is_empty()).then(|| if off.is_empty() { members } else { off })
+}
+
+/// Layers named in /AS entries with a Print event
+fn print_events(doc: &Document, config: Option<&Dictionary>) -> BTreeSet<ObjectId> {
+    let mut found = BTreeSet::new();
+    let entries = config
+        .and_then(|c| c.get(b"AS").ok())
+        .and_then(|a| resolve(doc, a).as_array().ok())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for entry in entries.iter().filter_map(|e| resolve(doc, e).as_dict().ok()) {
+        if name_value(entry, b"Event") == Some(b"Print") {
+            found.extend(array_references(doc, entry.get(b"OCGs").ok()));
+        }
+    }
+    found
+}
+
+/// Whether the group's usage dictionary turns it on for printing
+fn prints(doc: &Document, group: &Dictionary) -> bool {
+    group
+        .get(b"Usage")
+        .ok()
+        .and_then(|u| resolve(doc, u).as_dict().ok())
+        .and_then(|u| u.get(b"Print").ok())
+        .and_then(|p| resolve(doc, p).as_dict().ok())
+        .map_or(false, |p| name_value(p, b"PrintState") == Some(b"ON"))
+}
+
+/// Strings shown by text operators, one line per operator
+fn shown_text(operations: &[Operation]) -> String {
+    let mut lines = Vec::new();
+    for operation in operations {
+        let strings: Vec<&[u8]> = match (operation.operator.as_str(), operation.operands.last()) {
+            ("Tj" | "'" | "\"", Some(Object::String(bytes, _))) => vec![bytes],
+            ("TJ", Some(Object::Array(items))) => items
+                .iter()
+                .filter_map(|item| match item {
+                    Object::String(bytes, _) => Some(bytes.as_slice()),
+                    _ => None,
+                })
+                .collect(),
+            _ => continue,
+        };
+        lines.push(strings.into_iter().map(decode_text).collect::<String>());
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    /// Adds a visible and a hidden layer and returns their ids
+    fn with_layers(doc: &mut Document) -> (ObjectId, ObjectId) {
+        let visible = doc.add_object(dictionary! { "Type" => "OCG", "Name" => Object::string_literal("Visible") });
+        let hidden = doc.add_object(dictionary! {
+            "Type" => "OCG",
+            "Name" => Object::string_literal("Secret"),
+            "Usage" => dictionary! { "Print" => dictionary! { "PrintState" => "ON" } },
+        });
+        doc.get_dictionary_mut(fixtures::CATALOG_ID).unwrap().set(
+            "OCProperties",
+            dictionary! {
+                "OCGs" => vec![visible.into(), hidden.into()],
+                "D" => dictionary! {
+                    "OFF" => vec![hidden.into()],
+                    "AS" => vec![dictionary! {
+                        "Event" => "Print",
+                        "OCGs" => vec![hidden.into()],
+                        "Category" => vec!["Print".into()],
+                    }.into()],
+                },
+            },
+        );
+        (visible, hidden)
+    }
+
+    #[test]
+    fn test_document_without_layers() {
+        let report = analyze(&fixtures::document());
+        assert!(report.layers.is_empty());
+        assert!(report.hidden_content.is_empty());
+    }
+
+    #[test]
+    fn test_hidden_marked_content_is_extracted() {
+        let mut doc = fixtures::document();
+        let (visible, hidden) = with_layers(&mut doc);
+        doc.get_dictionary_mut((3, 0))
+            .unwrap()
+            .set("Properties", dictionary! { "L0" => visible, "L1" => hidden });
+        let content = b"/OC /L0 BDC BT (shown) Tj ET EMC /OC /L1 BDC BT [(wire ) (funds)] TJ ET EMC".to_vec();
+        let contents = doc.add_object(Stream::new(dictionary! {}, content));
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Contents", contents);
+
+        let report = analyze(&doc);
+        let layers: Vec<_> = report.hidden().map(|l| (l.name.as_str(), l.print_only)).collect();
+        assert_eq!(layers, vec![("Secret", true)]);
+
+        assert_eq!(report.hidden_content.len(), 1);
+        let found = &report.hidden_content[0];
+        assert_eq!(found.kind, HiddenKind::MarkedContent);
+        assert_eq!(found.page, Some(fixtures::PAGE_ID));
+        assert_eq!(found.layers, vec![hidden]);
+        assert_eq!(found.text, "wire funds");
+        assert_eq!(found.severity, Severity::Medium);
+    }
+
+    #[test]
+    fn test_membership_dictionary_on_xobject() {
+        let mut doc = fixtures::document();
+        let (visible, hidden) = with_layers(&mut doc);
+        let membership = doc.add_object(dictionary! {
+            "Type" => "OCMD",
+            "OCGs" => vec![visible.into(), hidden.into()],
+            "P" => "AllOn",
+        });
+        let form = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Form", "OC" => membership },
+            b"BT (overlay) Tj ET".to_vec(),
+        ));
+
+        let report = analyze(&doc);
+        assert_eq!(report.hidden_content.len(), 1);
+        assert_eq!(report.hidden_content[0].kind, HiddenKind::XObject);
+        assert_eq!(report.hidden_content[0].object, Some(form));
+        assert_eq!(report.hidden_content[0].text, "overlay");
+    }
+}
@@ -0,0 +1,363 @@
+//! Linearization parameter validation
+//! Created: 2026-10-16 09:15:40 UTC
+//! Author: kartik4091
+//!
+//! A linearized ("fast web view") file opens with a parameter dictionary
+//! describing its own layout: total length, hint stream location, first
+//! page object and page count. Writers compute these when they lay out the
+//! file, so parameters that no longer match the bytes mean the file was
+//! edited afterwards — or that the dictionary was copied in to make a
+//! crafted file look like the output of a mainstream producer.
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{header::HEADER_WINDOW, Severity};
+use crate::document::{
+    name_value, objects::scan_objects, parser::parse_indirect, revisions::find, xref::read_sections,
+};
+
+/// Slack allowed between a declared length and the measured one, for the
+/// end-of-line markers writers disagree on
+const LENGTH_TOLERANCE: u64 = 4;
+
+/// Distance allowed between /T and the start of the main xref section
+const XREF_TOLERANCE: u64 = 32;
+
+/// Kind of linearization anomaly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum LinearizationAnomalyKind {
+    /// The parameter dictionary is not the first object in the file
+    NotFirstObject,
+
+    /// A required parameter is missing or malformed
+    MissingParameter,
+
+    /// /L is shorter than the file and the extra bytes are incremental updates
+    UpdatedAfterLinearization,
+
+    /// /L does not match the file length
+    LengthMismatch,
+
+    /// /H does not point at a stream object of the declared length
+    HintStreamMismatch,
+
+    /// /O is not a page object
+    FirstPageMismatch,
+
+    /// /E lies outside the file
+    FirstPageEndMismatch,
+
+    /// /N disagrees with the page tree
+    PageCountMismatch,
+
+    /// /T does not point at a cross-reference section
+    XrefOffsetMismatch,
+}
+
+/// A single linearization anomaly
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LinearizationAnomaly {
+    /// Anomaly kind
+    pub kind: LinearizationAnomalyKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Linearization parameters as declared (ISO 32000-1, Table F.1)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LinearizationParams {
+    /// Parameter dictionary object
+    pub id: ObjectId,
+
+    /// Offset of the parameter dictionary
+    pub offset: usize,
+
+    /// File length (/L)
+    pub file_length: Option<u64>,
+
+    /// Primary hint stream offset and length (/H)
+    pub hint_stream: Option<(u64, u64)>,
+
+    /// First page object number (/O)
+    pub first_page_object: Option<u32>,
+
+    /// Offset of the end of the first page (/E)
+    pub first_page_end: Option<u64>,
+
+    /// Page count (/N)
+    pub page_count: Option<u64>,
+
+    /// Offset of the main cross-reference section (/T)
+    pub main_xref_offset: Option<u64>,
+}
+
+/// Linearization parameters and their inconsistencies
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LinearizationReport {
+    /// Declared parameters
+    pub params: LinearizationParams,
+
+    /// Parameters that do not match the file
+    pub anomalies: Vec<LinearizationAnomaly>,
+}
+
+/// Validates the linearization dictionary, if the file has one
+#[instrument(skip(data, doc))]
+pub fn check(data: &[u8], doc: Option<&Document>) -> Option<LinearizationReport> {
+    let objects = scan_objects(data);
+    let (position, parsed) = objects.iter().enumerate().find_map(|(position, raw)| {
+        let parsed = parse_indirect(data, raw.offset).ok()?;
+        parsed
+            .object
+            .as_dict()
+            .ok()?
+            .has(b"Linearized")
+            .then_some((position, parsed))
+    })?;
+    let dict = parsed.object.as_dict().ok()?;
+    let offset = objects[position].offset;
+
+    let mut report = LinearizationReport {
+        params: params(parsed.id, offset, dict),
+        anomalies: Vec::new(),
+    };
+    let mut push = |kind, severity, description: String| {
+        report.anomalies.push(LinearizationAnomaly {
+            kind,
+            severity,
+            description,
+        })
+    };
+    let params = report.params.clone();
+
+    if position > 0 || offset > HEADER_WINDOW {
+        push(
+            LinearizationAnomalyKind::NotFirstObject,
+            Severity::Medium,
+            format!(
+                "Linearization dictionary is object #{} at offset {}, not the first object",
+                position + 1,
+                offset
+            ),
+        );
+    }
+
+    let required = [
+        ("L", params.file_length.is_some()),
+        ("H", params.hint_stream.is_some()),
+        ("O", params.first_page_object.is_some()),
+        ("E", params.first_page_end.is_some()),
+        ("N", params.page_count.is_some()),
+        ("T", params.main_xref_offset.is_some()),
+    ];
+    for (key, present) in required {
+        if !present {
+            push(
+                LinearizationAnomalyKind::MissingParameter,
+                Severity::Medium,
+                format!("Linearization parameter /{} is missing or malformed", key),
+            );
+        }
+    }
+
+    let size = data.len() as u64;
+    if let Some(length) = params.file_length.filter(|&l| l != size) {
+        if length < size && ends_revision(data, length as usize) {
+            push(
+                LinearizationAnomalyKind::UpdatedAfterLinearization,
+                Severity::Low,
+                format!(
+                    "{} bytes of incremental updates follow the linearized file ({} bytes)",
+                    size - length,
+                    length
+                ),
+            );
+        } else {
+            push(
+                LinearizationAnomalyKind::LengthMismatch,
+                Severity::Medium,
+                format!("/L declares {} bytes but the file has {}", length, size),
+            );
+        }
+    }
+
+    if let Some((hint_offset, hint_length)) = params.hint_stream {
+        let hint = objects.iter().find(|raw| raw.offset as u64 == hint_offset);
+        let is_stream = hint
+            .and_then(|raw| parse_indirect(data, raw.offset).ok())
+            .map_or(false, |parsed| parsed.object.as_stream().is_ok());
+        match hint {
+            Some(raw) if is_stream && (raw.len() as u64).abs_diff(hint_length) <= LENGTH_TOLERANCE => {}
+            Some(raw) if is_stream => push(
+                LinearizationAnomalyKind::HintStreamMismatch,
+                Severity::Medium,
+                format!(
+                    "/H declares a {}-byte hint stream but object {} {} spans {} bytes",
+                    hint_length,
+                    raw.id.0,
+                    raw.id.1,
+                    raw.len()
+                ),
+            ),
+            _ => push(
+                LinearizationAnomalyKind::HintStreamMismatch,
+                Severity::Medium,
+                format!("/H offset {} does not start a stream object", hint_offset),
+            ),
+        }
+    }
+
+    if let Some(number) = params.first_page_object {
+        let is_page = objects
+            .iter()
+            .filter(|raw| raw.id.0 == number)
+            .filter_map(|raw| parse_indirect(data, raw.offset).ok())
+            .any(|parsed| {
+                parsed
+                    .object
+                    .as_dict()
+                    .map_or(false, |d| name_value(d, b"Type") == Some(b"Page"))
+            });
+        if !is_page {
+            push(
+                LinearizationAnomalyKind::FirstPageMismatch,
+                Severity::Medium,
+                format!("/O names object {}, which is not a page", number),
+            );
+        }
+    }
+
+    if let Some(end) = params.first_page_end.filter(|&e| e > size || e <= offset as u64) {
+        push(
+            LinearizationAnomalyKind::FirstPageEndMismatch,
+            Severity::Medium,
+            format!("/E offset {} lies outside the file", end),
+        );
+    }
+
+    if let (Some(declared), Some(doc)) = (params.page_count, doc) {
+        let actual = doc.get_pages().len() as u64;
+        if declared != actual {
+            push(
+                LinearizationAnomalyKind::PageCountMismatch,
+                Severity::Medium,
+                format!("/N declares {} pages but the page tree has {}", declared, actual),
+            );
+        }
+    }
+
+    if let Some(t) = params.main_xref_offset {
+        let sections = read_sections(data).unwrap_or_default();
+        if !sections.iter().any(|s| t >= s.offset && t - s.offset <= XREF_TOLERANCE) {
+            push(
+                LinearizationAnomalyKind::XrefOffsetMismatch,
+                Severity::Medium,
+                format!("/T offset {} is not the start of a cross-reference section", t),
+            );
+        }
+    }
+
+    debug!("Found {} linearization anomalies", report.anomalies.len());
+    Some(report)
+}
+
+fn params(id: ObjectId, offset: usize, dict: &Dictionary) -> LinearizationParams {
+    let integer = |key: &[u8]| {
+        dict.get(key)
+            .ok()
+            .and_then(|v| v.as_i64().ok())
+            .and_then(|v| u64::try_from(v).ok())
+    };
+    let hint_stream = match dict.get(b"H") {
+        Ok(Object::Array(items)) if items.len() >= 2 => {
+            let value = |i: usize| items[i].as_i64().ok().and_then(|v| u64::try_from(v).ok());
+            value(0).zip(value(1))
+        }
+        _ => None,
+    };
+
+    LinearizationParams {
+        id,
+        offset,
+        file_length: integer(b"L"),
+        hint_stream,
+        first_page_object: integer(b"O").and_then(|o| u32::try_from(o).ok()),
+        first_page_end: integer(b"E"),
+        page_count: integer(b"N"),
+        main_xref_offset: integer(b"T"),
+    }
+}
+
+/// Whether `len` bytes end just after a `%%EOF` marker
+fn ends_revision(data: &[u8], len: usize) -> bool {
+    let Some(prefix) = data.get(..len) else {
+        return false;
+    };
+    let trimmed = prefix
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(&prefix[..0], |i| &prefix[..=i]);
+    trimmed.ends_with(b"%%EOF") && find(data, b"%%EOF", len).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    /// Inserts a linearization dictionary as the first object without
+    /// fixing up the offsets of the objects after it
+    fn linearized(data: &[u8], params: &str) -> Vec<u8> {
+        let body = data.iter().position(|&b| b == b'\n').unwrap() + 1;
+        let mut out = data[..body].to_vec();
+        out.extend_from_slice(format!("99 0 obj\n<< /Linearized 1 {} >>\nendobj\n", params).as_bytes());
+        out.extend_from_slice(&data[body..]);
+        out
+    }
+
+    #[test]
+    fn test_not_linearized() {
+        assert!(check(&fixtures::simple_pdf(), None).is_none());
+    }
+
+    #[test]
+    fn test_fake_parameters_are_flagged() {
+        let data = linearized(&fixtures::simple_pdf(), "/L 1 /H [5 10] /O 4 /E 999999 /N 3 /T 7");
+        let report = check(&data, Some(&fixtures::document())).unwrap();
+
+        let kinds: Vec<_> = report.anomalies.iter().map(|a| a.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                LinearizationAnomalyKind::LengthMismatch,
+                LinearizationAnomalyKind::HintStreamMismatch,
+                LinearizationAnomalyKind::FirstPageMismatch,
+                LinearizationAnomalyKind::FirstPageEndMismatch,
+                LinearizationAnomalyKind::PageCountMismatch,
+                LinearizationAnomalyKind::XrefOffsetMismatch,
+            ]
+        );
+        assert_eq!(report.params.page_count, Some(3));
+    }
+
+    #[test]
+    fn test_update_after_linearization() {
+        let base = linearized(&fixtures::simple_pdf(), "/L 0000000000");
+        let length = base.len();
+        let base = String::from_utf8_lossy(&base).replace("/L 0000000000", &format!("/L {:010}", length));
+        let data = fixtures::append_revision(base.as_bytes(), &[(7, "<< /Type /Annot >>")]);
+
+        let report = check(&data, None).unwrap();
+        assert_eq!(report.params.file_length, Some(length as u64));
+        let kinds: Vec<_> = report.anomalies.iter().map(|a| a.kind).collect();
+        assert!(kinds.contains(&LinearizationAnomalyKind::UpdatedAfterLinearization));
+        assert!(!kinds.contains(&LinearizationAnomalyKind::LengthMismatch));
+    }
+}
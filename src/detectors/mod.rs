@@ -0,0 +1,252 @@
+//! Forensic detection passes run by the PDF analyzer
+//! Created: 2026-10-16 08:38:24 UTC
+//! Author: kartik4091
+//!
+//! Each pass takes the raw file bytes and/or the loaded lopdf document and
+//! returns plain report structures; none of them mutate the document.
+
+use std::{fmt, str::FromStr};
+
+use lopdf::ObjectId;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub mod carving;
+pub mod differential;
+pub mod reader_features;
+pub mod xref_anomalies;
+pub mod hybrid_xref;
+pub mod signatures;
+pub mod trailer_id;
+pub mod header;
+pub mod trailing;
+pub mod slack;
+pub mod object_streams;
+pub mod orphans;
+pub mod dangling;
+pub mod stream_length;
+pub mod filters;
+pub mod obfuscation;
+pub mod page_tree;
+pub mod linearization;
+pub mod entropy;
+pub mod attachments;
+pub mod polyglot;
+pub mod actions;
+pub mod annotations;
+pub mod forms;
+pub mod xfa;
+pub mod multimedia;
+pub mod layers;
+pub mod invisible_text;
+pub mod redaction;
+pub mod fonts;
+pub mod uris;
+pub mod qr;
+pub mod pii;
+pub mod secrets;
+pub mod language;
+pub mod phishing;
+pub mod yara;
+pub mod custom_rules;
+pub mod scripting;
+pub mod plugins;
+pub mod exploits;
+pub mod shellcode;
+pub mod decoders;
+
+/// Severity attached to structural findings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum Severity {
+    /// Informational, expected in benign files
+    Info,
+
+    /// Unusual but commonly produced by legitimate writers
+    Low,
+
+    /// Inconsistent structure worth a closer look
+    Medium,
+
+    /// Structure that makes viewers disagree or hides content
+    High,
+
+    /// Strong indicator of deliberate manipulation
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Info => "info",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "info" => Ok(Self::Info),
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            other => Err(format!("Unknown severity: {}", other)),
+        }
+    }
+}
+
+/// How certain a detection is that it found what it reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum Confidence {
+    /// Heuristic that benign files often trigger
+    Low,
+
+    /// Heuristic that is usually right
+    #[default]
+    Medium,
+
+    /// Direct observation of the file's structure or content
+    High,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Broad kind of risk a finding contributes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum RiskCategory {
+    /// File structure and cross-reference integrity
+    Structure,
+
+    /// Hidden, packed or disguised content
+    Concealment,
+
+    /// Scripts, actions, forms and other interactive content
+    ActiveContent,
+
+    /// Links and lures aimed at the reader
+    Phishing,
+
+    /// Sensitive data the document leaks
+    DataExposure,
+
+    /// User-supplied rules, scripts, plugins and analyzers
+    Custom,
+}
+
+impl fmt::Display for RiskCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Structure => "structure",
+            Self::Concealment => "concealment",
+            Self::ActiveContent => "active content",
+            Self::Phishing => "phishing",
+            Self::DataExposure => "data exposure",
+            Self::Custom => "custom",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A finding in the form shared by every analyzer and output format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Finding {
+    /// Stable identifier of the kind of finding, `<analyzer>.<kind>`
+    pub id: String,
+
+    /// One-line summary
+    pub title: String,
+
+    /// Finding severity
+    pub severity: Severity,
+
+    /// How certain the detection is
+    #[serde(default)]
+    pub confidence: Confidence,
+
+    /// Kind of risk the finding contributes to
+    pub category: RiskCategory,
+
+    /// Supporting details such as offsets, decoded values or matched text
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<String>,
+
+    /// Objects the finding is about
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objects: Vec<ObjectId>,
+
+    /// How to neutralize the issue, when there is a known way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+
+    /// MITRE ATT&CK technique ids the finding is evidence of
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub techniques: Vec<String>,
+}
+
+impl Finding {
+    /// Creates a medium-confidence finding without evidence or objects
+    pub fn new(id: impl Into<String>, severity: Severity, category: RiskCategory, title: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            severity,
+            confidence: Confidence::default(),
+            category,
+            evidence: Vec::new(),
+            objects: Vec::new(),
+            remediation: None,
+            techniques: Vec::new(),
+        }
+    }
+
+    /// Sets the confidence
+    pub fn confidence(mut self, confidence: Confidence) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// Adds a piece of evidence
+    pub fn evidence(mut self, evidence: impl Into<String>) -> Self {
+        self.evidence.push(evidence.into());
+        self
+    }
+
+    /// Adds object references
+    pub fn objects(mut self, objects: impl IntoIterator<Item = ObjectId>) -> Self {
+        self.objects.extend(objects);
+        self
+    }
+
+    /// Sets the remediation hint
+    pub fn remediation(mut self, hint: impl Into<String>) -> Self {
+        self.remediation = Some(hint.into());
+        self
+    }
+
+    /// Adds ATT&CK technique ids
+    pub fn techniques(mut self, ids: impl IntoIterator<Item = String>) -> Self {
+        self.techniques.extend(ids);
+        self
+    }
+
+    /// Name of the detector that reported the finding
+    pub fn detector(&self) -> &str {
+        self.id.split('.').next().unwrap_or(&self.id)
+    }
+}
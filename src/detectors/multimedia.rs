@@ -0,0 +1,297 @@
+//! Rich media, 3D, sound and Flash content detection
+//! Created: 2026-10-16 09:35:53 UTC
+//! Author: kartik4091
+//!
+//! Rich media and 3D annotations hand embedded SWF, U3D and PRC payloads to
+//! renderer plugins (the Flash player, the U3D and PRC parsers) that have a
+//! long history of memory-corruption exploits. This pass lists multimedia
+//! annotations, identifies the payload streams by their format signatures
+//! and can extract them for offline analysis.
+
+use std::path::Path;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::{
+    document::{name_value, resolve, sha256_hex, stream_data, taxonomy::ObjectType},
+    error::Result,
+};
+
+/// Multimedia payload format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum MediaFormat {
+    /// Shockwave Flash (FWS, CWS or ZWS)
+    Flash,
+
+    /// Universal 3D
+    U3d,
+
+    /// Product Representation Compact (3D)
+    Prc,
+
+    /// Sound object samples
+    Sound,
+
+    /// FLV or MP4 video
+    Video,
+}
+
+impl MediaFormat {
+    /// File extension used when extracting the payload
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Flash => "swf",
+            Self::U3d => "u3d",
+            Self::Prc => "prc",
+            Self::Sound => "snd",
+            Self::Video => "video",
+        }
+    }
+}
+
+/// A multimedia annotation
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MediaAnnotation {
+    /// Annotation object
+    pub id: ObjectId,
+
+    /// Annotation subtype: RichMedia, 3D, Sound, Movie or Screen
+    pub subtype: String,
+
+    /// Content activates when the page is opened or becomes visible
+    pub auto_activated: bool,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// A multimedia payload stream
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MediaPayload {
+    /// Stream object
+    pub id: ObjectId,
+
+    /// Payload format
+    pub format: MediaFormat,
+
+    /// Decoded size in bytes
+    pub size: usize,
+
+    /// Hex SHA-256 of the decoded payload
+    pub sha256: String,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Where the payload was written, when extraction was requested
+    pub extracted_to: Option<String>,
+}
+
+/// Multimedia content of a document
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct MediaReport {
+    /// Multimedia annotations in object order
+    pub annotations: Vec<MediaAnnotation>,
+
+    /// Payload streams in object order
+    pub payloads: Vec<MediaPayload>,
+}
+
+/// Lists multimedia annotations and payload streams
+#[instrument(skip(doc))]
+pub fn analyze(doc: &Document) -> MediaReport {
+    let mut report = MediaReport::default();
+
+    for (&id, object) in &doc.objects {
+        match object {
+            Object::Dictionary(dict) => {
+                if let Some(annotation) = annotation(doc, id, dict) {
+                    report.annotations.push(annotation);
+                }
+            }
+            Object::Stream(stream) => {
+                let skip = matches!(
+                    ObjectType::of(object),
+                    ObjectType::ObjectStream | ObjectType::XrefStream | ObjectType::Font { .. }
+                ) || name_value(&stream.dict, b"Subtype") == Some(b"Image");
+                if skip {
+                    continue;
+                }
+                let Ok(data) = stream_data(stream) else {
+                    continue;
+                };
+                if let Some(format) = payload_format(&stream.dict, &data) {
+                    report.payloads.push(MediaPayload {
+                        id,
+                        format,
+                        size: data.len(),
+                        sha256: sha256_hex(&data),
+                        severity: match format {
+                            MediaFormat::Flash | MediaFormat::U3d | MediaFormat::Prc => Severity::High,
+                            MediaFormat::Sound | MediaFormat::Video => Severity::Low,
+                        },
+                        extracted_to: None,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    debug!(
+        "Found {} multimedia annotations, {} payloads",
+        report.annotations.len(),
+        report.payloads.len()
+    );
+    report
+}
+
+/// Writes every payload to `dir` as `<num>_<gen>.<ext>` and records the destinations
+pub async fn extract(doc: &Document, report: &mut MediaReport, dir: &Path) -> Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    for payload in &mut report.payloads {
+        let Ok(Object::Stream(stream)) = doc.get_object(payload.id) else {
+            continue;
+        };
+        let path = dir.join(format!(
+            "{}_{}.{}",
+            payload.id.0,
+            payload.id.1,
+            payload.format.extension()
+        ));
+        tokio::fs::write(&path, stream_data(stream)?).await?;
+        payload.extracted_to = Some(path.to_string_lossy().into_owned());
+    }
+    Ok(())
+}
+
+fn annotation(doc: &Document, id: ObjectId, dict: &Dictionary) -> Option<MediaAnnotation> {
+    let subtype = name_value(dict, b"Subtype")?;
+    if !matches!(subtype, b"RichMedia" | b"3D" | b"Sound" | b"Movie" | b"Screen") || !dict.has(b"Rect") {
+        return None;
+    }
+
+    // 3D activation is under /3DA /A, rich media under /RichMediaSettings /Activation /Condition
+    let activation = match subtype {
+        b"3D" => dict
+            .get(b"3DA")
+            .ok()
+            .and_then(|a| resolve(doc, a).as_dict().ok())
+            .and_then(|a| name_value(a, b"A")),
+        b"RichMedia" => dict
+            .get(b"RichMediaSettings")
+            .ok()
+            .and_then(|s| resolve(doc, s).as_dict().ok())
+            .and_then(|s| s.get(b"Activation").ok())
+            .and_then(|a| resolve(doc, a).as_dict().ok())
+            .and_then(|a| name_value(a, b"Condition")),
+        _ => None,
+    };
+    let auto_activated = matches!(activation, Some(b"PO") | Some(b"PV"));
+
+    let subtype = String::from_utf8_lossy(subtype).into_owned();
+    let severity = match (subtype.as_str(), auto_activated) {
+        ("RichMedia" | "3D", _) | (_, true) => Severity::High,
+        ("Screen", false) => Severity::Low,
+        _ => Severity::Medium,
+    };
+    Some(MediaAnnotation {
+        id,
+        description: format!(
+            "{} annotation {} {}{}",
+            subtype,
+            id.0,
+            id.1,
+            if auto_activated { " activates on page open" } else { "" }
+        ),
+        subtype,
+        auto_activated,
+        severity,
+    })
+}
+
+/// Identifies a payload from its signature, or the stream's declared type
+fn payload_format(dict: &Dictionary, data: &[u8]) -> Option<MediaFormat> {
+    if [&b"FWS"[..], b"CWS", b"ZWS"]
+        .iter()
+        .any(|magic| data.starts_with(magic))
+    {
+        return Some(MediaFormat::Flash);
+    }
+    if data.starts_with(b"U3D\0") || name_value(dict, b"Subtype") == Some(b"U3D") {
+        return Some(MediaFormat::U3d);
+    }
+    if data.starts_with(b"PRC") || name_value(dict, b"Subtype") == Some(b"PRC") {
+        return Some(MediaFormat::Prc);
+    }
+    if name_value(dict, b"Type") == Some(b"Sound") {
+        return Some(MediaFormat::Sound);
+    }
+    if data.starts_with(b"FLV\x01") || data.get(4..8) == Some(b"ftyp") {
+        return Some(MediaFormat::Video);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_plain_document_has_no_media() {
+        let report = analyze(&fixtures::document());
+        assert!(report.annotations.is_empty());
+        assert!(report.payloads.is_empty());
+    }
+
+    #[test]
+    fn test_flash_asset_and_3d_annotation() {
+        let mut doc = fixtures::document();
+        let swf = doc.add_object(Stream::new(
+            dictionary! { "Type" => "EmbeddedFile" },
+            b"CWS\x0a\x00\x00\x00\x00compressed movie".to_vec(),
+        ));
+        let annotation = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "3D",
+            "Rect" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+            "3DA" => dictionary! { "A" => "PO" },
+        });
+
+        let report = analyze(&doc);
+        assert_eq!(report.payloads.len(), 1);
+        assert_eq!(report.payloads[0].id, swf);
+        assert_eq!(report.payloads[0].format, MediaFormat::Flash);
+        assert_eq!(report.payloads[0].severity, Severity::High);
+
+        assert_eq!(report.annotations[0].id, annotation);
+        assert!(report.annotations[0].auto_activated);
+        assert_eq!(report.annotations[0].severity, Severity::High);
+    }
+
+    #[tokio::test]
+    async fn test_extract() {
+        let mut doc = fixtures::document();
+        doc.add_object(Stream::new(
+            dictionary! { "Subtype" => "U3D" },
+            b"U3D\0 model data".to_vec(),
+        ));
+        let mut report = analyze(&doc);
+
+        let dir = tempfile::tempdir().unwrap();
+        extract(&doc, &mut report, dir.path()).await.unwrap();
+
+        let path = report.payloads[0].extracted_to.clone().unwrap();
+        assert!(path.ends_with(".u3d"));
+        assert_eq!(std::fs::read(path).unwrap(), b"U3D\0 model data");
+    }
+}
@@ -0,0 +1,435 @@
+//! Lexical obfuscation of names and strings
+//! Created: 2026-10-16 09:10:45 UTC
+//! Author: kartik4091
+//!
+//! Names may spell any character as `#xx` and literal strings may spell
+//! any byte as an octal escape, so `/J#61vaScript` and `(\141pp.alert)`
+//! mean the same to a reader as their plain forms while slipping past
+//! scanners that search the raw bytes for keywords. This pass tokenizes the
+//! file (skipping stream data and comments), decodes every name and string
+//! and reports the tokens whose escaping hides a keyword or serves no
+//! purpose.
+
+use lopdf::ObjectId;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    decode_text,
+    objects::scan_objects,
+    parser::{is_delimiter, is_whitespace},
+    printable_preview,
+    revisions::find,
+};
+
+/// Names that trigger actions or hide content
+const SENSITIVE_NAMES: [&[u8]; 16] = [
+    b"JavaScript",
+    b"JS",
+    b"OpenAction",
+    b"AA",
+    b"Launch",
+    b"URI",
+    b"SubmitForm",
+    b"ImportData",
+    b"GoToR",
+    b"GoToE",
+    b"EmbeddedFile",
+    b"EmbeddedFiles",
+    b"RichMedia",
+    b"XFA",
+    b"AcroForm",
+    b"ObjStm",
+];
+
+/// Lowercase fragments of script and command text; plain URLs and dotted
+/// names are left out, as writers hex-encode those routinely
+const SCRIPT_KEYWORDS: [&str; 10] = [
+    "javascript",
+    "eval(",
+    "unescape(",
+    "fromcharcode",
+    "app.alert(",
+    "app.opendoc(",
+    "exportdataobject",
+    "launchurl",
+    "cmd.exe",
+    "powershell",
+];
+
+/// Escapes of plain characters before a string counts as over-escaped
+const MIN_NEEDLESS_ESCAPES: usize = 4;
+
+/// Longest excerpt kept from a token
+const PREVIEW_LEN: usize = 80;
+
+/// Kind of escaping anomaly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ObfuscationKind {
+    /// A sensitive name written with `#xx` escapes
+    EscapedKeyword,
+
+    /// A name escaping letters or digits that need no escaping
+    NeedlessNameEscape,
+
+    /// A string that only reveals script or command text once decoded
+    EncodedScript,
+
+    /// A literal string with most of its characters octal-escaped
+    ExcessiveStringEscaping,
+}
+
+/// An obfuscated name or string token
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ObfuscatedToken {
+    /// Anomaly kind
+    pub kind: ObfuscationKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Offset of the token in the file
+    pub offset: usize,
+
+    /// Object containing the token, if it lies inside one
+    pub object: Option<ObjectId>,
+
+    /// Token as written
+    pub raw: String,
+
+    /// Token after decoding its escapes
+    pub decoded: String,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Lexical token of interest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    /// Name, without the leading slash
+    Name { offset: usize, end: usize },
+
+    /// Literal string including its parentheses
+    Literal { offset: usize, end: usize },
+
+    /// Hex string including its angle brackets
+    Hex { offset: usize, end: usize },
+}
+
+/// Scans every name and string token in the file for escaping anomalies
+#[instrument(skip(data))]
+pub fn detect(data: &[u8]) -> Vec<ObfuscatedToken> {
+    let objects = scan_objects(data);
+    // Objects are in file order, so the owner is the last one starting before the token
+    let owner = |offset: usize| {
+        let index = objects.partition_point(|o| o.offset <= offset).checked_sub(1)?;
+        Some(objects[index]).filter(|o| offset < o.end).map(|o| o.id)
+    };
+
+    let mut found = Vec::new();
+    for token in tokenize(data) {
+        let (offset, end, finding) = match token {
+            Token::Name { offset, end } => (offset, end, check_name(&data[offset + 1..end])),
+            Token::Literal { offset, end } => (offset, end, check_literal(&data[offset + 1..end - 1])),
+            Token::Hex { offset, end } => (offset, end, check_hex(&data[offset + 1..end - 1])),
+        };
+        let Some((kind, severity, decoded)) = finding else {
+            continue;
+        };
+
+        let object = owner(offset);
+        let raw = printable_preview(&data[offset..end], PREVIEW_LEN);
+        let location = match object {
+            Some(id) => format!("object {} {}", id.0, id.1),
+            None => format!("offset {}", offset),
+        };
+        let description = match kind {
+            ObfuscationKind::EscapedKeyword => format!("Escaped keyword {} decodes to /{} in {}", raw, decoded, location),
+            ObfuscationKind::NeedlessNameEscape => format!("Name {} escapes plain characters in {}", raw, location),
+            ObfuscationKind::EncodedScript => format!("Encoded string in {} hides script text: {}", location, decoded),
+            ObfuscationKind::ExcessiveStringEscaping => {
+                format!("Over-escaped string in {} decodes to: {}", location, decoded)
+            }
+        };
+        found.push(ObfuscatedToken { kind, severity, offset, object, raw, decoded, description });
+    }
+
+    debug!("Found {} obfuscated tokens", found.len());
+    found
+}
+
+fn check_name(raw: &[u8]) -> Option<(ObfuscationKind, Severity, String)> {
+    if !raw.contains(&b'#') {
+        return None;
+    }
+    let (decoded, needless) = decode_name(raw);
+    let text = String::from_utf8_lossy(&decoded).into_owned();
+
+    if SENSITIVE_NAMES.contains(&decoded.as_slice()) {
+        Some((ObfuscationKind::EscapedKeyword, Severity::High, text))
+    } else if needless > 0 {
+        Some((ObfuscationKind::NeedlessNameEscape, Severity::Low, text))
+    } else {
+        None
+    }
+}
+
+fn check_literal(raw: &[u8]) -> Option<(ObfuscationKind, Severity, String)> {
+    let (decoded, needless) = decode_literal(raw);
+    if needless == 0 {
+        return None;
+    }
+    let text = decode_text(&decoded);
+
+    if hides_script(raw, &text) {
+        Some((ObfuscationKind::EncodedScript, Severity::High, preview(&text)))
+    } else if needless >= MIN_NEEDLESS_ESCAPES && needless * 2 >= decoded.len() {
+        Some((ObfuscationKind::ExcessiveStringEscaping, Severity::Medium, preview(&text)))
+    } else {
+        None
+    }
+}
+
+fn check_hex(raw: &[u8]) -> Option<(ObfuscationKind, Severity, String)> {
+    let text = decode_text(&decode_hex(raw));
+    hides_script(raw, &text).then(|| (ObfuscationKind::EncodedScript, Severity::High, preview(&text)))
+}
+
+/// Whether decoding reveals a script keyword absent from the raw bytes
+fn hides_script(raw: &[u8], decoded: &str) -> bool {
+    let raw = String::from_utf8_lossy(raw).to_ascii_lowercase();
+    let decoded = decoded.to_ascii_lowercase();
+    SCRIPT_KEYWORDS.iter().any(|k| decoded.contains(k) && !raw.contains(k))
+}
+
+fn preview(text: &str) -> String {
+    printable_preview(text.as_bytes(), PREVIEW_LEN)
+}
+
+/// Finds name and string tokens outside stream data and comments
+fn tokenize(data: &[u8]) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < data.len() {
+        match data[i] {
+            b'%' => {
+                while i < data.len() && data[i] != b'\r' && data[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' => {
+                let offset = i;
+                i += 1;
+                while i < data.len() && !is_whitespace(data[i]) && !is_delimiter(data[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Name { offset, end: i });
+            }
+            b'(' => {
+                let offset = i;
+                i = literal_end(data, i);
+                tokens.push(Token::Literal { offset, end: i });
+            }
+            b'<' if data.get(i + 1) == Some(&b'<') => i += 2,
+            b'<' => {
+                let offset = i;
+                i = find(data, b">", i).map_or(data.len(), |pos| pos + 1);
+                if data[i - 1] == b'>' {
+                    tokens.push(Token::Hex { offset, end: i });
+                }
+            }
+            b's' if is_stream_keyword(data, i) => {
+                i = find(data, b"endstream", i + 6).map_or(data.len(), |pos| pos + 9);
+            }
+            _ => i += 1,
+        }
+    }
+
+    tokens
+}
+
+/// Whether `stream` at `pos` starts stream data rather than ending it
+fn is_stream_keyword(data: &[u8], pos: usize) -> bool {
+    data[pos..].starts_with(b"stream")
+        && pos > 0
+        && (is_whitespace(data[pos - 1]) || data[pos - 1] == b'>')
+        && matches!(data.get(pos + 6), Some(b'\r') | Some(b'\n'))
+}
+
+/// Offset just past the parenthesis closing the literal string at `start`
+fn literal_end(data: &[u8], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut i = start;
+    while i < data.len() {
+        match data[i] {
+            b'\\' => i += 1,
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    data.len()
+}
+
+/// Decodes `#xx` escapes, counting escapes of regular alphanumerics
+fn decode_name(raw: &[u8]) -> (Vec<u8>, usize) {
+    let mut decoded = Vec::with_capacity(raw.len());
+    let mut needless = 0;
+    let mut i = 0;
+
+    while i < raw.len() {
+        let escaped = (raw[i] == b'#')
+            .then(|| raw.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                needless += usize::from(byte.is_ascii_alphanumeric());
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(raw[i]);
+                i += 1;
+            }
+        }
+    }
+
+    (decoded, needless)
+}
+
+/// Decodes literal string escapes, counting octal escapes of printable
+/// characters that could have been written as-is
+fn decode_literal(raw: &[u8]) -> (Vec<u8>, usize) {
+    let mut decoded = Vec::with_capacity(raw.len());
+    let mut needless = 0;
+    let mut i = 0;
+
+    while i < raw.len() {
+        if raw[i] != b'\\' {
+            decoded.push(raw[i]);
+            i += 1;
+            continue;
+        }
+
+        i += 1;
+        let Some(&next) = raw.get(i) else {
+            break;
+        };
+        match next {
+            b'0'..=b'7' => {
+                let digits = raw[i..].iter().take(3).take_while(|b| (b'0'..=b'7').contains(b)).count();
+                let value = raw[i..i + digits].iter().fold(0u32, |acc, &d| acc * 8 + u32::from(d - b'0'));
+                let byte = value as u8;
+                needless += usize::from(byte.is_ascii_alphanumeric() || byte == b' ' || byte == b'.');
+                decoded.push(byte);
+                i += digits;
+            }
+            b'\r' => {
+                // Line continuation
+                i += 1;
+                if raw.get(i) == Some(&b'\n') {
+                    i += 1;
+                }
+            }
+            b'\n' => i += 1,
+            other => {
+                decoded.push(match other {
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b't' => b'\t',
+                    b'b' => 0x08,
+                    b'f' => 0x0C,
+                    b => b,
+                });
+                i += 1;
+            }
+        }
+    }
+
+    (decoded, needless)
+}
+
+/// Decodes hex string digits, ignoring whitespace and padding an odd digit
+fn decode_hex(raw: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = raw
+        .iter()
+        .filter_map(|&b| (b as char).to_digit(16).map(|d| d as u8))
+        .collect();
+    digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_clean_document_has_no_findings() {
+        assert!(detect(&fixtures::simple_pdf()).is_empty());
+    }
+
+    #[test]
+    fn test_escaped_keyword_is_reported_per_occurrence() {
+        let data = fixtures::append_revision(
+            &fixtures::simple_pdf(),
+            &[
+                (7, "<< /Type /Action /S /J#61vaScript /JS (app.alert\\(1\\)) >>"),
+                (8, "<< /Type /Action /S /Java#53cript /J#53 (x) >>"),
+                (9, "<< /Font#20Name 1 >>"),
+            ],
+        );
+
+        let found = detect(&data);
+        let keywords: Vec<_> = found
+            .iter()
+            .filter(|t| t.kind == ObfuscationKind::EscapedKeyword)
+            .map(|t| (t.object, t.decoded.as_str()))
+            .collect();
+        assert_eq!(
+            keywords,
+            vec![(Some((7, 0)), "JavaScript"), (Some((8, 0)), "JavaScript"), (Some((8, 0)), "JS")]
+        );
+        // #20 is a necessary escape
+        assert!(found.iter().all(|t| t.object != Some((9, 0))));
+    }
+
+    #[test]
+    fn test_encoded_strings() {
+        let data = fixtures::append_revision(
+            &fixtures::simple_pdf(),
+            &[
+                (7, "<< /S /JavaScript /JS (\\141\\160\\160.alert\\(1\\)) >>"),
+                (8, "<< /S /JavaScript /JS <6576616C28782929> >>"),
+                (9, "<< /Title (\\110\\145\\154\\154\\157) >>"),
+                (10, "<< /S /URI /URI <68747470733A2F2F6170702E6578616D706C652E636F6D2F> >>"),
+            ],
+        );
+
+        let found = detect(&data);
+        let kinds: Vec<_> = found.iter().map(|t| (t.object.unwrap().0, t.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (7, ObfuscationKind::EncodedScript),
+                (8, ObfuscationKind::EncodedScript),
+                (9, ObfuscationKind::ExcessiveStringEscaping),
+            ]
+        );
+        assert_eq!(found[2].decoded, "Hello");
+    }
+}
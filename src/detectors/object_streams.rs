@@ -0,0 +1,365 @@
+//! Deep inspection of compressed object streams
+//! Created: 2026-10-16 08:58:39 UTC
+//! Author: kartik4091
+//!
+//! Objects inside /ObjStm streams are invisible to tools that only look at
+//! the raw file. This pass decodes every object stream — including those
+//! no longer referenced — lists its contents, and compares each compressed
+//! object with any direct definition of the same number.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    object_digest,
+    objects::scan_objects,
+    objstm::{decode, is_object_stream},
+    parser::parse_indirect,
+    taxonomy::ObjectType,
+    xref::{effective_entries, read_sections, XrefState},
+};
+
+/// An object listed in an object stream
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContainedObject {
+    /// Object number
+    pub number: u32,
+
+    /// Index within the stream
+    pub index: usize,
+
+    /// Object type, when the object parsed
+    pub object_type: Option<ObjectType>,
+
+    /// Whether the effective xref resolves the number to this entry
+    pub live: bool,
+}
+
+/// A decoded object stream
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ObjectStreamInfo {
+    /// Object stream id
+    pub id: ObjectId,
+
+    /// Offset of the stream object in the file
+    pub offset: usize,
+
+    /// Value of /N
+    pub declared: Option<usize>,
+
+    /// Stream extended through /Extends
+    pub extends: Option<ObjectId>,
+
+    /// Contained objects
+    pub objects: Vec<ContainedObject>,
+
+    /// Decode failure, if any
+    pub error: Option<String>,
+}
+
+/// Kind of object stream finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ObjStmFindingKind {
+    /// A compressed object replaces an earlier direct definition with different content
+    OverridesEarlier,
+
+    /// A compressed copy that readers never load differs from the live direct object
+    ShadowCopy,
+
+    /// The same number is stored in several object streams
+    DuplicateAcrossStreams,
+
+    /// /N disagrees with the number of objects in the header
+    CountMismatch,
+}
+
+/// A single object stream finding
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ObjStmFinding {
+    /// Finding kind
+    pub kind: ObjStmFindingKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Object stream the finding concerns
+    pub stream: ObjectId,
+
+    /// Affected object number
+    pub number: Option<u32>,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Object stream inventory and findings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ObjStmReport {
+    /// Every object stream found in the file
+    pub streams: Vec<ObjectStreamInfo>,
+
+    /// Inconsistencies involving compressed objects
+    pub findings: Vec<ObjStmFinding>,
+}
+
+/// Decodes every object stream and cross-checks its contents
+#[instrument(skip(data))]
+pub fn inspect(data: &[u8]) -> ObjStmReport {
+    let effective = read_sections(data).map(|s| effective_entries(&s)).unwrap_or_default();
+    let mut report = ObjStmReport::default();
+
+    // Direct definitions in file order, keyed by object number
+    let mut direct: BTreeMap<u32, Vec<(usize, Object)>> = BTreeMap::new();
+    let mut streams = Vec::new();
+    for raw in scan_objects(data) {
+        let Ok(parsed) = parse_indirect(data, raw.offset) else {
+            continue;
+        };
+        match &parsed.object {
+            Object::Stream(stream) if is_object_stream(stream) => {
+                streams.push((raw.id, raw.offset, stream.clone()));
+            }
+            object => direct.entry(raw.id.0).or_default().push((raw.offset, object.clone())),
+        }
+    }
+
+    let mut seen: BTreeMap<u32, ObjectId> = BTreeMap::new();
+    for (id, offset, stream) in streams {
+        let mut info = ObjectStreamInfo {
+            id,
+            offset,
+            declared: None,
+            extends: None,
+            objects: Vec::new(),
+            error: None,
+        };
+
+        let decoded = match decode(&stream) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                info.error = Some(e.to_string());
+                report.streams.push(info);
+                continue;
+            }
+        };
+        info.declared = Some(decoded.declared);
+        info.extends = decoded.extends;
+
+        if decoded.objects.len() != decoded.declared {
+            report.findings.push(ObjStmFinding {
+                kind: ObjStmFindingKind::CountMismatch,
+                severity: Severity::Low,
+                stream: id,
+                number: None,
+                description: format!(
+                    "/N declares {} objects, header lists {}",
+                    decoded.declared,
+                    decoded.objects.len()
+                ),
+            });
+        }
+
+        for entry in &decoded.objects {
+            let live = matches!(
+                effective.get(&entry.number),
+                Some(XrefState::Compressed { stream, index })
+                    if *stream == id.0 as u64 && *index == entry.index as u64
+            );
+            info.objects.push(ContainedObject {
+                number: entry.number,
+                index: entry.index,
+                object_type: entry.object.as_ref().ok().map(ObjectType::of),
+                live,
+            });
+
+            if let Some(other) = seen.insert(entry.number, id) {
+                if other != id {
+                    report.findings.push(ObjStmFinding {
+                        kind: ObjStmFindingKind::DuplicateAcrossStreams,
+                        severity: Severity::Medium,
+                        stream: id,
+                        number: Some(entry.number),
+                        description: format!(
+                            "Object {} is stored in object streams {} and {}",
+                            entry.number, other.0, id.0
+                        ),
+                    });
+                }
+            }
+
+            let Ok(object) = &entry.object else {
+                continue;
+            };
+            if let Some(finding) = compare_direct(id, offset, entry.number, object, live, &direct, &effective) {
+                report.findings.push(finding);
+            }
+        }
+
+        report.streams.push(info);
+    }
+
+    debug!(
+        "Inspected {} object streams, {} findings",
+        report.streams.len(),
+        report.findings.len()
+    );
+    report
+}
+
+fn compare_direct(
+    stream: ObjectId,
+    stream_offset: usize,
+    number: u32,
+    object: &Object,
+    live: bool,
+    direct: &BTreeMap<u32, Vec<(usize, Object)>>,
+    effective: &BTreeMap<u32, XrefState>,
+) -> Option<ObjStmFinding> {
+    let definitions = direct.get(&number)?;
+    let digest = object_digest(object);
+
+    if live {
+        // The compressed copy wins; report earlier direct objects it silently replaced
+        let (offset, _) = definitions
+            .iter()
+            .filter(|(offset, _)| *offset < stream_offset)
+            .find(|(_, def)| object_digest(def) != digest)?;
+        return Some(ObjStmFinding {
+            kind: ObjStmFindingKind::OverridesEarlier,
+            severity: Severity::Medium,
+            stream,
+            number: Some(number),
+            description: format!(
+                "Compressed object {} in stream {} overrides the different direct definition at {}",
+                number, stream.0, offset
+            ),
+        });
+    }
+
+    // A direct definition is live; a differing compressed copy added after it
+    // is a shadow, while one it replaced is just an older revision
+    let live_offset = match effective.get(&number) {
+        Some(XrefState::InUse { offset, .. }) => *offset as usize,
+        _ => return None,
+    };
+    if stream_offset < live_offset {
+        return None;
+    }
+    let (_, live_object) = definitions.iter().find(|(offset, _)| *offset == live_offset)?;
+    (object_digest(live_object) != digest).then(|| ObjStmFinding {
+        kind: ObjStmFindingKind::ShadowCopy,
+        severity: Severity::High,
+        stream,
+        number: Some(number),
+        description: format!(
+            "Object stream {} holds a different, unreferenced version of object {}",
+            stream.0, number
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{fixtures, objstm::tests::object_stream, revisions::find_startxref};
+
+    /// Appends an object stream as object 20, then an xref stream section
+    /// pointing the `compressed` numbers into it
+    fn with_object_stream(base: &[u8], objects: &[(u32, &str)], compressed: &[u32]) -> Vec<u8> {
+        let stream = object_stream(objects);
+        let body = format!(
+            "<< /Type /ObjStm /N {} /First {} /Length {} >>\nstream\n{}\nendstream",
+            objects.len(),
+            stream.dict.get(b"First").unwrap().as_i64().unwrap(),
+            stream.content.len(),
+            String::from_utf8_lossy(&stream.content)
+        );
+        let mut data = fixtures::append_revision(base, &[(20, &body)]);
+        if compressed.is_empty() {
+            return data;
+        }
+
+        let prev = find_startxref(&data).unwrap();
+        let mut rows = Vec::new();
+        let mut index = Vec::new();
+        for &number in compressed {
+            let position = objects.iter().position(|(n, _)| *n == number).unwrap();
+            rows.extend_from_slice(&[2, 0, 20, position as u8]);
+            index.push(format!("{} 1", number));
+        }
+
+        let xref_offset = data.len();
+        data.extend_from_slice(
+            format!(
+                "30 0 obj\n<< /Type /XRef /Size 31 /Root {} 0 R /Prev {} /Index [{}] /W [1 2 1] /Length {} >>\nstream\n",
+                fixtures::CATALOG_ID.0,
+                prev,
+                index.join(" "),
+                rows.len()
+            )
+            .as_bytes(),
+        );
+        data.extend_from_slice(&rows);
+        data.extend_from_slice(format!("\nendstream\nendobj\nstartxref\n{}\n%%EOF\n", xref_offset).as_bytes());
+        data
+    }
+
+    #[test]
+    fn test_lists_contained_objects() {
+        let data = with_object_stream(&fixtures::simple_pdf(), &[(10, "<< /Type /Annot >>"), (11, "42")], &[]);
+        let report = inspect(&data);
+
+        assert_eq!(report.streams.len(), 1);
+        let stream = &report.streams[0];
+        assert_eq!(stream.id, (20, 0));
+        assert_eq!(stream.objects.len(), 2);
+        assert_eq!(
+            stream.objects[0].object_type,
+            Some(ObjectType::Annotation { subtype: None })
+        );
+        assert!(!stream.objects[0].live);
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_shadow_copy_of_direct_object() {
+        let data = with_object_stream(
+            &fixtures::simple_pdf(),
+            &[(fixtures::CATALOG_ID.0, "<< /Type /Catalog /OpenAction 99 0 R >>")],
+            &[],
+        );
+        let report = inspect(&data);
+        assert_eq!(report.findings[0].kind, ObjStmFindingKind::ShadowCopy);
+        assert_eq!(report.findings[0].number, Some(fixtures::CATALOG_ID.0));
+    }
+
+    #[test]
+    fn test_stream_superseded_by_later_direct_object() {
+        let data = with_object_stream(
+            &fixtures::simple_pdf(),
+            &[(fixtures::CATALOG_ID.0, "<< /Type /Catalog /OpenAction 99 0 R >>")],
+            &[],
+        );
+        let data = fixtures::append_revision(&data, &[(fixtures::CATALOG_ID.0, "<< /Type /Catalog /Pages 1 0 R >>")]);
+        let report = inspect(&data);
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_compressed_override_of_earlier_definition() {
+        let data = with_object_stream(
+            &fixtures::simple_pdf(),
+            &[(fixtures::PAGE_ID.0, "<< /Type /Page /Parent 1 0 R /Annots [] >>")],
+            &[fixtures::PAGE_ID.0],
+        );
+        let report = inspect(&data);
+
+        assert!(report.streams[0].objects[0].live);
+        assert_eq!(report.findings[0].kind, ObjStmFindingKind::OverridesEarlier);
+    }
+}
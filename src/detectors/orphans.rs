@@ -0,0 +1,179 @@
+//! Detection of objects unreachable from the trailer
+//! Created: 2026-10-16 08:58:48 UTC
+//! Author: kartik4091
+//!
+//! Viewers only ever load what the trailer leads to. Objects that are still
+//! in the effective cross-reference but no longer referenced — pages cut
+//! out of the page tree, replaced images, content left over from "deleted"
+//! material — survive in the file and are recovered here.
+
+use std::collections::BTreeSet;
+
+use lopdf::{Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{collect_references, name_value, taxonomy::ObjectType};
+
+/// What an unreachable object appears to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum OrphanCategory {
+    /// Page or page tree node
+    Page,
+
+    /// Image XObject
+    Image,
+
+    /// Content stream or form XObject
+    Content,
+
+    /// Font, font descriptor or font program
+    Font,
+
+    /// Action or JavaScript
+    Action,
+
+    /// Embedded file or file specification
+    EmbeddedFile,
+
+    /// Annotation
+    Annotation,
+
+    /// Anything else
+    Other,
+}
+
+/// An object present in the file but unreachable from the trailer
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OrphanObject {
+    /// Object number and generation
+    pub id: ObjectId,
+
+    /// Object type
+    pub object_type: ObjectType,
+
+    /// Category
+    pub category: OrphanCategory,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Objects reachable from the trailer by following references
+pub fn reachable(doc: &Document) -> BTreeSet<ObjectId> {
+    let mut pending = Vec::new();
+    for (_, value) in doc.trailer.iter() {
+        collect_references(value, &mut pending);
+    }
+
+    let mut seen = BTreeSet::new();
+    while let Some(id) = pending.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        if let Some(object) = doc.objects.get(&id) {
+            collect_references(object, &mut pending);
+        }
+    }
+    seen
+}
+
+/// Lists every unreachable object, excluding file structure objects
+#[instrument(skip(doc))]
+pub fn detect(doc: &Document) -> Vec<OrphanObject> {
+    let reachable = reachable(doc);
+    let orphans: Vec<OrphanObject> = doc
+        .objects
+        .iter()
+        .filter(|(id, object)| !reachable.contains(id) && !is_structural(object))
+        .map(|(&id, object)| {
+            let category = categorize(object);
+            let object_type = ObjectType::of(object);
+            OrphanObject {
+                id,
+                severity: severity(category),
+                description: format!("Unreachable {:?} object {} {} ({})", category, id.0, id.1, object_type),
+                object_type,
+                category,
+            }
+        })
+        .collect();
+
+    debug!("Found {} unreachable objects", orphans.len());
+    orphans
+}
+
+/// Cross-reference streams, object streams and linearization dictionaries
+/// are legitimately unreferenced
+fn is_structural(object: &Object) -> bool {
+    let dict = match object {
+        Object::Dictionary(dict) => dict,
+        Object::Stream(stream) => &stream.dict,
+        _ => return false,
+    };
+    matches!(name_value(dict, b"Type"), Some(b"XRef") | Some(b"ObjStm")) || dict.has(b"Linearized")
+}
+
+fn categorize(object: &Object) -> OrphanCategory {
+    let (dict, is_stream) = match object {
+        Object::Dictionary(dict) => (dict, false),
+        Object::Stream(stream) => (&stream.dict, true),
+        _ => return OrphanCategory::Other,
+    };
+
+    match (name_value(dict, b"Type"), name_value(dict, b"Subtype")) {
+        (Some(b"Page") | Some(b"Pages"), _) => OrphanCategory::Page,
+        (_, Some(b"Image")) => OrphanCategory::Image,
+        (_, Some(b"Form")) => OrphanCategory::Content,
+        (Some(b"Font") | Some(b"FontDescriptor"), _) => OrphanCategory::Font,
+        (Some(b"EmbeddedFile") | Some(b"Filespec"), _) => OrphanCategory::EmbeddedFile,
+        (Some(b"Annot"), _) => OrphanCategory::Annotation,
+        (Some(b"Action"), _) => OrphanCategory::Action,
+        _ if dict.has(b"S") && (dict.has(b"JS") || dict.has(b"URI") || dict.has(b"F")) => OrphanCategory::Action,
+        _ if dict.has(b"Length1") || dict.has(b"Length2") => OrphanCategory::Font,
+        _ if is_stream => OrphanCategory::Content,
+        _ => OrphanCategory::Other,
+    }
+}
+
+fn severity(category: OrphanCategory) -> Severity {
+    match category {
+        OrphanCategory::Page | OrphanCategory::Action | OrphanCategory::EmbeddedFile => Severity::Medium,
+        OrphanCategory::Image | OrphanCategory::Content | OrphanCategory::Annotation => Severity::Low,
+        OrphanCategory::Font | OrphanCategory::Other => Severity::Info,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_clean_document_has_no_orphans() {
+        assert!(detect(&fixtures::document()).is_empty());
+    }
+
+    #[test]
+    fn test_hidden_page_and_stale_content() {
+        let mut doc = fixtures::document();
+        let content = doc.add_object(Stream::new(dictionary! {}, b"BT (secret) Tj ET".to_vec()));
+        let page = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => (1, 0),
+            "Contents" => content,
+        });
+
+        let orphans = detect(&doc);
+        let categories: Vec<_> = orphans.iter().map(|o| (o.id, o.category)).collect();
+        // The page references its content, but nothing references the page
+        assert_eq!(categories, vec![(content, OrphanCategory::Content), (page, OrphanCategory::Page)]);
+        assert_eq!(orphans[1].severity, Severity::Medium);
+    }
+}
@@ -0,0 +1,324 @@
+//! Page tree consistency checks
+//! Created: 2026-10-16 09:09:57 UTC
+//! Author: kartik4091
+//!
+//! Viewers disagree on how to handle a malformed page tree: some trust
+//! /Count, some walk /Kids, some give up on cycles and others fall back to
+//! scanning for page objects. Each of these gaps lets a file show a
+//! different set of pages to different readers, so this pass walks the tree
+//! from the catalog and reports every place where it is not well formed.
+
+use std::collections::BTreeSet;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::name_value;
+
+/// Deepest page tree walked before giving up
+const MAX_DEPTH: usize = 256;
+
+/// Kind of page tree anomaly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum PageTreeAnomalyKind {
+    /// The catalog has no usable /Pages entry
+    MissingRoot,
+
+    /// A node is its own ancestor
+    Loop,
+
+    /// A node is listed under more than one parent (or twice under one)
+    SharedNode,
+
+    /// The tree is nested deeper than [`MAX_DEPTH`]
+    TooDeep,
+
+    /// /Count disagrees with the pages actually under the node
+    CountMismatch,
+
+    /// A /Kids entry is not a page or page tree node
+    InvalidKid,
+
+    /// /Parent does not point back at the node listing it
+    ParentMismatch,
+
+    /// A page in the tree has no /Contents
+    MissingContents,
+
+    /// A page object that the tree does not reach
+    OutsideTree,
+}
+
+/// A single page tree anomaly
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PageTreeAnomaly {
+    /// Anomaly kind
+    pub kind: PageTreeAnomalyKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Affected node or page
+    pub id: Option<ObjectId>,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+struct Walker<'a> {
+    doc: &'a Document,
+    visited: BTreeSet<ObjectId>,
+    found: Vec<PageTreeAnomaly>,
+}
+
+/// Walks the page tree from the catalog and checks every node
+#[instrument(skip(doc))]
+pub fn check(doc: &Document) -> Vec<PageTreeAnomaly> {
+    let mut walker = Walker {
+        doc,
+        visited: BTreeSet::new(),
+        found: Vec::new(),
+    };
+
+    let root = doc
+        .trailer
+        .get(b"Root")
+        .and_then(Object::as_reference)
+        .and_then(|id| doc.get_dictionary(id))
+        .and_then(|catalog| catalog.get(b"Pages"))
+        .and_then(Object::as_reference);
+    match root {
+        Ok(root) => {
+            walker.visit(root, None, &mut Vec::new());
+        }
+        Err(_) => walker.push(
+            PageTreeAnomalyKind::MissingRoot,
+            Severity::Medium,
+            None,
+            "Catalog has no /Pages reference".into(),
+        ),
+    }
+
+    let Walker { visited, mut found, .. } = walker;
+    for (&id, object) in &doc.objects {
+        let is_page = object
+            .as_dict()
+            .map_or(false, |dict| name_value(dict, b"Type") == Some(b"Page"));
+        if is_page && !visited.contains(&id) {
+            found.push(PageTreeAnomaly {
+                kind: PageTreeAnomalyKind::OutsideTree,
+                severity: Severity::Medium,
+                id: Some(id),
+                description: format!("Page {} {} is not part of the page tree", id.0, id.1),
+            });
+        }
+    }
+
+    debug!("Found {} page tree anomalies", found.len());
+    found
+}
+
+impl Walker<'_> {
+    fn push(&mut self, kind: PageTreeAnomalyKind, severity: Severity, id: Option<ObjectId>, description: String) {
+        self.found.push(PageTreeAnomaly {
+            kind,
+            severity,
+            id,
+            description,
+        });
+    }
+
+    /// Checks a node and returns the number of pages under it
+    fn visit(&mut self, id: ObjectId, parent: Option<ObjectId>, path: &mut Vec<ObjectId>) -> i64 {
+        let (num, gen) = id;
+        if path.contains(&id) {
+            self.push(
+                PageTreeAnomalyKind::Loop,
+                Severity::High,
+                Some(id),
+                format!("Page tree node {} {} is its own ancestor", num, gen),
+            );
+            return 0;
+        }
+        if path.len() >= MAX_DEPTH {
+            self.push(
+                PageTreeAnomalyKind::TooDeep,
+                Severity::Medium,
+                Some(id),
+                format!("Page tree deeper than {} levels at {} {}", MAX_DEPTH, num, gen),
+            );
+            return 0;
+        }
+        if !self.visited.insert(id) {
+            self.push(
+                PageTreeAnomalyKind::SharedNode,
+                Severity::Medium,
+                Some(id),
+                format!("Page tree node {} {} is listed more than once", num, gen),
+            );
+            return 0;
+        }
+
+        let Ok(dict) = self.doc.get_dictionary(id) else {
+            self.push(
+                PageTreeAnomalyKind::InvalidKid,
+                Severity::Medium,
+                Some(id),
+                format!("Kid {} {} is missing or not a dictionary", num, gen),
+            );
+            return 0;
+        };
+
+        if let Some(parent) = parent {
+            if dict.get(b"Parent").and_then(Object::as_reference).ok() != Some(parent) {
+                self.push(
+                    PageTreeAnomalyKind::ParentMismatch,
+                    Severity::Low,
+                    Some(id),
+                    format!("/Parent of {} {} does not point at {} {}", num, gen, parent.0, parent.1),
+                );
+            }
+        }
+
+        match node_kind(dict) {
+            Some(b"Pages") => {
+                path.push(id);
+                let actual = self.visit_kids(id, dict, path);
+                path.pop();
+
+                let declared = dict.get(b"Count").and_then(Object::as_i64).ok();
+                if declared != Some(actual) {
+                    let declared = declared.map_or("missing".to_string(), |c| c.to_string());
+                    self.push(
+                        PageTreeAnomalyKind::CountMismatch,
+                        Severity::Medium,
+                        Some(id),
+                        format!(
+                            "Node {} {} declares /Count {} but holds {} pages",
+                            num, gen, declared, actual
+                        ),
+                    );
+                }
+                actual
+            }
+            Some(b"Page") => {
+                if !dict.has(b"Contents") {
+                    self.push(
+                        PageTreeAnomalyKind::MissingContents,
+                        Severity::Low,
+                        Some(id),
+                        format!("Page {} {} has no /Contents", num, gen),
+                    );
+                }
+                1
+            }
+            _ => {
+                self.push(
+                    PageTreeAnomalyKind::InvalidKid,
+                    Severity::Medium,
+                    Some(id),
+                    format!("Kid {} {} is not a page or page tree node", num, gen),
+                );
+                0
+            }
+        }
+    }
+
+    fn visit_kids(&mut self, id: ObjectId, dict: &Dictionary, path: &mut Vec<ObjectId>) -> i64 {
+        let kids = match dict.get(b"Kids") {
+            Ok(Object::Reference(kids_id)) => self.doc.get_object(*kids_id).and_then(Object::as_array),
+            Ok(kids) => kids.as_array(),
+            Err(e) => Err(e),
+        };
+        let Ok(kids) = kids else {
+            self.push(
+                PageTreeAnomalyKind::InvalidKid,
+                Severity::Medium,
+                Some(id),
+                format!("Node {} {} has no /Kids array", id.0, id.1),
+            );
+            return 0;
+        };
+
+        let mut count = 0;
+        for kid in kids {
+            match kid {
+                Object::Reference(kid) => count += self.visit(*kid, Some(id), path),
+                other => self.push(
+                    PageTreeAnomalyKind::InvalidKid,
+                    Severity::Medium,
+                    Some(id),
+                    format!(
+                        "Node {} {} lists a direct {} as a kid",
+                        id.0,
+                        id.1,
+                        other.enum_variant()
+                    ),
+                ),
+            }
+        }
+        count
+    }
+}
+
+/// `Pages` or `Page`, inferring the kind from /Kids when /Type is missing
+fn node_kind(dict: &Dictionary) -> Option<&[u8]> {
+    match name_value(dict, b"Type") {
+        Some(kind @ (b"Pages" | b"Page")) => Some(kind),
+        Some(_) => None,
+        None if dict.has(b"Kids") => Some(b"Pages"),
+        None => Some(b"Page"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    fn kinds(doc: &Document) -> Vec<PageTreeAnomalyKind> {
+        check(doc).iter().map(|a| a.kind).collect()
+    }
+
+    #[test]
+    fn test_well_formed_tree() {
+        assert!(check(&fixtures::document()).is_empty());
+    }
+
+    #[test]
+    fn test_loop_and_count_mismatch() {
+        let mut doc = fixtures::document();
+        let pages = (1, 0);
+        let inner = doc.add_object(dictionary! {
+            "Type" => "Pages",
+            "Parent" => pages,
+            "Kids" => vec![pages.into()],
+            "Count" => 0,
+        });
+        let root = doc.get_dictionary_mut(pages).unwrap();
+        root.set("Kids", vec![fixtures::PAGE_ID.into(), inner.into()]);
+        root.set("Count", 3);
+
+        assert_eq!(
+            kinds(&doc),
+            vec![PageTreeAnomalyKind::Loop, PageTreeAnomalyKind::CountMismatch]
+        );
+    }
+
+    #[test]
+    fn test_pages_outside_tree_and_without_contents() {
+        let mut doc = fixtures::document();
+        let hidden = doc.add_object(dictionary! { "Type" => "Page", "Parent" => (1, 0), "Contents" => (4, 0) });
+        doc.get_dictionary_mut(fixtures::PAGE_ID).unwrap().remove(b"Contents");
+
+        let found = check(&doc);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].kind, PageTreeAnomalyKind::MissingContents);
+        assert_eq!(found[1].kind, PageTreeAnomalyKind::OutsideTree);
+        assert_eq!(found[1].id, Some(hidden));
+    }
+}
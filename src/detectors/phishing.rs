@@ -0,0 +1,425 @@
+//! Phishing likelihood from combined signals
+//! Created: 2026-10-16 10:04:53 UTC
+//! Author: kartik4091
+//!
+//! No single trait makes a PDF a phishing lure — invoices have QR codes,
+//! tax forms ask for account numbers. This pass combines the signals other
+//! passes already collect (lookalike and listed URLs, QR-delivered links,
+//! credential fields, external form submission) with a few of its own
+//! (urgent wording, brand names next to foreign links, image-only pages
+//! that are one big link) into a 0–100 score.
+
+use std::collections::BTreeSet;
+
+use lopdf::{Document, Object};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{
+    forms::{FormIssue, FormReport},
+    uris::{Obfuscation, UriOrigin, UriReport, POPULAR_DOMAINS},
+    Severity,
+};
+use crate::{
+    document::{name_value, resolve},
+    integrations::reputation::Reputation,
+    text,
+};
+
+/// Highest phishing score
+pub const MAX_SCORE: u32 = 100;
+
+/// Pages with fewer letters than this count as image-only
+const IMAGE_ONLY_LETTERS: usize = 20;
+
+/// Wording that pressures the reader into acting at once
+const URGENT_PHRASES: [&str; 16] = [
+    "urgent",
+    "immediately",
+    "action required",
+    "within 24 hours",
+    "within 48 hours",
+    "account will be",
+    "account has been suspended",
+    "account suspended",
+    "verify your account",
+    "verify your identity",
+    "confirm your identity",
+    "unusual activity",
+    "unusual sign-in",
+    "password expires",
+    "password will expire",
+    "final notice",
+];
+
+/// Field names asking for credentials or payment data
+const CREDENTIAL_FIELDS: [&str; 12] = [
+    "password",
+    "passwd",
+    "pwd",
+    "passcode",
+    "pin",
+    "cvv",
+    "cvc",
+    "ssn",
+    "cardnumber",
+    "card_number",
+    "login",
+    "username",
+];
+
+/// Password field flag (PDF 32000-1, table 228)
+const FLAG_PASSWORD: i64 = 1 << 13;
+
+/// Signal contributing to the phishing score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub enum PhishingSignal {
+    /// Link to a homoglyph lookalike of a popular domain
+    LookalikeDomain,
+
+    /// Link listed by a reputation service
+    ListedUrl,
+
+    /// Link delivered as a QR code
+    QrCodeLink,
+
+    /// Link hidden by encoding tricks
+    ObfuscatedLink,
+
+    /// Form fields asking for passwords, PINs or card data
+    CredentialFields,
+
+    /// Form data submitted to an external URL
+    ExternalSubmit,
+
+    /// Pressuring wording in the text
+    UrgentLanguage,
+
+    /// Brand named in the text while every link goes elsewhere
+    BrandMismatch,
+
+    /// Page consisting of an image and a link
+    ImageOnlyLure,
+}
+
+impl PhishingSignal {
+    /// Score contribution
+    pub fn weight(&self) -> u32 {
+        match self {
+            Self::ListedUrl => 40,
+            Self::LookalikeDomain => 30,
+            Self::CredentialFields | Self::ExternalSubmit => 25,
+            Self::QrCodeLink | Self::ImageOnlyLure => 20,
+            Self::UrgentLanguage | Self::BrandMismatch => 15,
+            Self::ObfuscatedLink => 10,
+        }
+    }
+}
+
+/// A signal with what triggered it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SignalHit {
+    /// Signal
+    pub signal: PhishingSignal,
+
+    /// What triggered it
+    pub evidence: String,
+}
+
+/// Phishing likelihood band
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub enum Likelihood {
+    /// Score below 30
+    #[default]
+    Low,
+
+    /// Score from 30 to 59
+    Medium,
+
+    /// Score of 60 or more
+    High,
+}
+
+/// Combined phishing assessment
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PhishingAssessment {
+    /// Score from 0 to [`MAX_SCORE`]
+    pub score: u32,
+
+    /// Likelihood band of the score
+    pub likelihood: Likelihood,
+
+    /// Signals found, one entry per signal
+    pub signals: Vec<SignalHit>,
+}
+
+impl PhishingAssessment {
+    /// Severity for the policy: only medium and high likelihoods count
+    pub fn severity(&self) -> Option<Severity> {
+        match self.likelihood {
+            Likelihood::Low => None,
+            Likelihood::Medium => Some(Severity::Medium),
+            Likelihood::High => Some(Severity::High),
+        }
+    }
+
+    /// One-line summary
+    pub fn description(&self) -> String {
+        let signals: Vec<String> = self.signals.iter().map(|hit| format!("{:?}", hit.signal)).collect();
+        format!(
+            "Phishing likelihood {:?} (score {}): {}",
+            self.likelihood,
+            self.score,
+            signals.join(", ")
+        )
+    }
+
+    /// Adds signals from reputation verdicts attached after the assessment
+    pub fn record_reputation(&mut self, uris: &UriReport) {
+        if let Some(uri) = uris
+            .uris
+            .iter()
+            .find(|uri| uri.reputation.iter().any(|v| v.reputation >= Reputation::Suspicious))
+        {
+            self.add(PhishingSignal::ListedUrl, format!("{} is listed", uri.decoded));
+        }
+    }
+
+    fn add(&mut self, signal: PhishingSignal, evidence: String) {
+        if self.signals.iter().any(|hit| hit.signal == signal) {
+            return;
+        }
+        self.signals.push(SignalHit { signal, evidence });
+        self.score = self
+            .signals
+            .iter()
+            .map(|hit| hit.signal.weight())
+            .sum::<u32>()
+            .min(MAX_SCORE);
+        self.likelihood = match self.score {
+            0..=29 => Likelihood::Low,
+            30..=59 => Likelihood::Medium,
+            _ => Likelihood::High,
+        };
+    }
+}
+
+/// Scores the document from its text, links and forms
+#[instrument(skip(doc, uris, forms))]
+pub fn assess(doc: &Document, uris: &UriReport, forms: Option<&FormReport>) -> PhishingAssessment {
+    let mut assessment = PhishingAssessment::default();
+
+    if let Some(uri) = uris.uris.iter().find(|uri| uri.lookalike_of.is_some()) {
+        let target = uri.lookalike_of.as_deref().unwrap_or_default();
+        assessment.add(
+            PhishingSignal::LookalikeDomain,
+            format!("{} imitates {}", uri.decoded, target),
+        );
+    }
+    assessment.record_reputation(uris);
+    if let Some(uri) = uris
+        .uris
+        .iter()
+        .find(|uri| uri.sources.iter().any(|(o, _)| *o == UriOrigin::QrCode))
+    {
+        assessment.add(PhishingSignal::QrCodeLink, format!("QR code links to {}", uri.decoded));
+    }
+    if let Some(uri) = uris
+        .uris
+        .iter()
+        .find(|uri| uri.obfuscation.iter().any(|o| *o != Obfuscation::Punycode))
+    {
+        assessment.add(
+            PhishingSignal::ObfuscatedLink,
+            format!("{} ({:?})", uri.decoded, uri.obfuscation),
+        );
+    }
+
+    if let Some(forms) = forms {
+        let credential: Vec<&str> = forms
+            .fields
+            .iter()
+            .filter(|field| field.flags & FLAG_PASSWORD != 0 || is_credential_name(&field.name))
+            .map(|field| field.name.as_str())
+            .collect();
+        if !credential.is_empty() {
+            assessment.add(
+                PhishingSignal::CredentialFields,
+                format!("fields {}", credential.join(", ")),
+            );
+        }
+        if let Some(finding) = forms.findings.iter().find(|f| f.issue == FormIssue::ExternalSubmit) {
+            assessment.add(PhishingSignal::ExternalSubmit, finding.description.clone());
+        }
+    }
+    if let Some(uri) = uris
+        .uris
+        .iter()
+        .find(|uri| uri.sources.iter().any(|(o, _)| *o == UriOrigin::SubmitForm))
+    {
+        assessment.add(
+            PhishingSignal::ExternalSubmit,
+            format!("form submits to {}", uri.decoded),
+        );
+    }
+
+    let pages = text::extract(doc);
+    let text = pages
+        .iter()
+        .map(|page| page.text())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .to_lowercase();
+    let urgent: Vec<&str> = URGENT_PHRASES
+        .iter()
+        .copied()
+        .filter(|phrase| text.contains(phrase))
+        .collect();
+    if urgent.len() >= 2 {
+        assessment.add(PhishingSignal::UrgentLanguage, format!("\"{}\"", urgent.join("\", \"")));
+    }
+
+    let hosts: BTreeSet<&str> = uris.uris.iter().filter_map(|uri| uri.host.as_deref()).collect();
+    let brand = POPULAR_DOMAINS.iter().find(|brand| {
+        brand.len() > 4 && contains_word(&text, brand) && !hosts.is_empty() && !hosts.iter().any(|h| h.contains(*brand))
+    });
+    if let Some(brand) = brand {
+        let hosts: Vec<&str> = hosts.iter().copied().collect();
+        assessment.add(
+            PhishingSignal::BrandMismatch,
+            format!("mentions {} but links to {}", brand, hosts.join(", ")),
+        );
+    }
+
+    for page in &pages {
+        let letters = page.text().chars().filter(|c| c.is_alphabetic()).count();
+        if letters < IMAGE_ONLY_LETTERS && has_image(doc, page.page) && has_link(doc, page.page) {
+            assessment.add(
+                PhishingSignal::ImageOnlyLure,
+                format!("page {} is an image with a link", page.page_number),
+            );
+        }
+    }
+
+    debug!(
+        "Phishing score {} from {} signals",
+        assessment.score,
+        assessment.signals.len()
+    );
+    assessment
+}
+
+fn is_credential_name(name: &str) -> bool {
+    // Fully qualified names: only the last partial name describes the field
+    let last = name
+        .rsplit('.')
+        .next()
+        .unwrap_or(name)
+        .to_ascii_lowercase()
+        .replace([' ', '-'], "");
+    CREDENTIAL_FIELDS.iter().any(|field| last.contains(field))
+}
+
+/// Whole-word match, so "apple" does not fire on "pineapple"
+fn contains_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(i, _)| {
+        let before = text[..i].chars().next_back().map_or(true, |c| !c.is_alphanumeric());
+        let after = text[i + word.len()..]
+            .chars()
+            .next()
+            .map_or(true, |c| !c.is_alphanumeric());
+        before && after
+    })
+}
+
+fn has_image(doc: &Document, page: lopdf::ObjectId) -> bool {
+    let Ok((resources, inherited)) = doc.get_page_resources(page) else {
+        return false;
+    };
+    resources
+        .into_iter()
+        .chain(inherited.into_iter().filter_map(|id| doc.get_dictionary(id).ok()))
+        .filter_map(|resources| {
+            resources
+                .get(b"XObject")
+                .ok()
+                .and_then(|x| resolve(doc, x).as_dict().ok())
+        })
+        .flat_map(|xobjects| xobjects.iter())
+        .filter_map(|(_, xobject)| resolve(doc, xobject).as_stream().ok())
+        .any(|stream| name_value(&stream.dict, b"Subtype") == Some(b"Image"))
+}
+
+fn has_link(doc: &Document, page: lopdf::ObjectId) -> bool {
+    let Some(Object::Array(annots)) = doc
+        .get_dictionary(page)
+        .ok()
+        .and_then(|page| page.get(b"Annots").ok())
+        .map(|a| resolve(doc, a))
+    else {
+        return false;
+    };
+    annots
+        .iter()
+        .filter_map(|annot| resolve(doc, annot).as_dict().ok())
+        .any(|annot| {
+            name_value(annot, b"Subtype") == Some(b"Link")
+                && annot
+                    .get(b"A")
+                    .ok()
+                    .and_then(|a| resolve(doc, a).as_dict().ok())
+                    .map_or(false, |action| name_value(action, b"S") == Some(b"URI"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        detectors::{forms, uris},
+        document::fixtures,
+    };
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_benign_document_scores_zero() {
+        let doc = fixtures::document();
+        let assessment = assess(&doc, &uris::extract(&doc), forms::analyze(&doc).as_ref());
+        assert_eq!(assessment.score, 0);
+        assert_eq!(assessment.likelihood, Likelihood::Low);
+        assert!(assessment.severity().is_none());
+    }
+
+    #[test]
+    fn test_lure_page_combines_signals() {
+        let mut doc = fixtures::document();
+        let content = doc.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F1 10 Tf 72 700 Td (Your PayPal account has been suspended. Verify your account immediately.) Tj ET"
+                .to_vec(),
+        ));
+        let action = doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::string_literal("https://secure-login.example/paypal"),
+        });
+        let link = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            "A" => action,
+        });
+        let page = doc.get_dictionary_mut(fixtures::PAGE_ID).unwrap();
+        page.set("Contents", content);
+        page.set("Annots", vec![link.into()]);
+
+        let assessment = assess(&doc, &uris::extract(&doc), None);
+        let signals: Vec<PhishingSignal> = assessment.signals.iter().map(|hit| hit.signal).collect();
+        assert_eq!(
+            signals,
+            vec![PhishingSignal::UrgentLanguage, PhishingSignal::BrandMismatch]
+        );
+        assert_eq!(assessment.score, 30);
+        assert_eq!(assessment.likelihood, Likelihood::Medium);
+    }
+}
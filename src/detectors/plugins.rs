@@ -0,0 +1,246 @@
+//! Sandboxed WebAssembly analyzer plugins
+//! Created: 2026-10-16 10:10:22 UTC
+//! Author: kartik4091
+//!
+//! Third-party detection modules are WebAssembly components implementing
+//! the `pdx:plugin/analyzer` interface in `wit/pdx-plugin.wit`. Every
+//! `.wasm` file in the plugins directory is loaded; each plugin receives
+//! the document's indirect objects and returns findings.
+//!
+//! Plugins get no imports at all, so they cannot touch the filesystem or
+//! the network, and each call runs in a fresh store with fuel and memory
+//! limits. The runtime is behind the `plugins` feature; without it the
+//! report types exist but no plugins can be loaded.
+
+use lopdf::{Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::Severity;
+use crate::document::decode_text;
+
+/// Plugin file extension loaded from a directory
+pub const PLUGIN_EXTENSION: &str = "wasm";
+
+/// Fuel (roughly, WebAssembly instructions) a plugin may consume per document
+pub const MAX_FUEL: u64 = 1_000_000_000;
+
+/// Largest linear memory a plugin may grow to, in bytes
+pub const MAX_MEMORY: usize = 256 * 1024 * 1024;
+
+/// A finding returned by a plugin
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PluginFinding {
+    /// Plugin name, as it reports itself
+    pub plugin: String,
+
+    /// Object the finding is about, if any
+    pub object: Option<ObjectId>,
+
+    /// Severity chosen by the plugin
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Converts an object to the JSON form described in the WIT interface
+pub fn object_json(object: &Object) -> Value {
+    match object {
+        Object::Null => Value::Null,
+        Object::Boolean(b) => json!(b),
+        Object::Integer(i) => json!(i),
+        Object::Real(r) => json!(r),
+        Object::Name(name) => json!(String::from_utf8_lossy(name)),
+        Object::String(bytes, _) => json!(decode_text(bytes)),
+        Object::Array(items) => Value::Array(items.iter().map(object_json).collect()),
+        Object::Dictionary(dict) | Object::Stream(lopdf::Stream { dict, .. }) => Value::Object(
+            dict.iter()
+                .map(|(key, value)| (String::from_utf8_lossy(key).into_owned(), object_json(value)))
+                .collect::<Map<_, _>>(),
+        ),
+        Object::Reference(id) => json!({ "ref": [id.0, id.1] }),
+    }
+}
+
+#[cfg(feature = "plugins")]
+pub use runtime::{run, Plugins};
+
+#[cfg(feature = "plugins")]
+mod runtime {
+    use std::path::{Path, PathBuf};
+
+    use lopdf::{Document, Object};
+    use tracing::{debug, instrument};
+    use wasmtime::{
+        component::{Component, Linker},
+        Config, Engine, Store, StoreLimits, StoreLimitsBuilder,
+    };
+
+    use super::{object_json, PluginFinding, Severity, MAX_FUEL, MAX_MEMORY, PLUGIN_EXTENSION};
+    use crate::{
+        document::{stream_data, taxonomy::ObjectType},
+        error::{Error, Result},
+    };
+
+    wasmtime::component::bindgen!({
+        path: "wit",
+        world: "plugin",
+    });
+
+    use exports::pdx::plugin::analyzer;
+
+    /// Compiled plugins sharing one engine
+    pub struct Plugins {
+        engine: Engine,
+        components: Vec<(PathBuf, Component)>,
+    }
+
+    impl std::fmt::Debug for Plugins {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let sources: Vec<_> = self.components.iter().map(|(path, _)| path).collect();
+            f.debug_struct("Plugins").field("sources", &sources).finish()
+        }
+    }
+
+    impl Plugins {
+        /// Compiles every `.wasm` component in a directory
+        pub fn load(dir: &Path) -> Result<Self> {
+            let mut config = Config::new();
+            config.wasm_component_model(true).consume_fuel(true);
+            let engine = Engine::new(&config).map_err(|e| Error::Analysis(format!("Plugin runtime: {}", e)))?;
+
+            let mut sources: Vec<PathBuf> = std::fs::read_dir(dir)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.extension().map_or(false, |e| e == PLUGIN_EXTENSION))
+                .collect();
+            sources.sort();
+
+            let components = sources
+                .into_iter()
+                .map(|path| {
+                    let component = Component::from_file(&engine, &path)
+                        .map_err(|e| Error::Analysis(format!("Plugin {}: {}", path.display(), e)))?;
+                    Ok((path, component))
+                })
+                .collect::<Result<_>>()?;
+            Ok(Self { engine, components })
+        }
+
+        /// Paths of the loaded plugins
+        pub fn sources(&self) -> impl Iterator<Item = &Path> {
+            self.components.iter().map(|(path, _)| path.as_path())
+        }
+    }
+
+    /// Runs every plugin over the document's objects.
+    ///
+    /// A plugin that fails to instantiate, traps or runs out of fuel does
+    /// not stop the others; its error is returned alongside the findings.
+    #[instrument(skip_all)]
+    pub fn run(plugins: &Plugins, doc: &Document) -> (Vec<PluginFinding>, Vec<String>) {
+        let objects: Vec<analyzer::PdfObject> = doc
+            .objects
+            .iter()
+            .map(|(&id, object)| analyzer::PdfObject {
+                id: analyzer::ObjectId {
+                    number: id.0,
+                    generation: id.1,
+                },
+                object_type: ObjectType::of(object).to_string(),
+                json: object_json(object).to_string(),
+                stream_data: match object {
+                    Object::Stream(stream) => stream_data(stream).ok(),
+                    _ => None,
+                },
+            })
+            .collect();
+
+        let mut findings = Vec::new();
+        let mut errors = Vec::new();
+        for (path, component) in &plugins.components {
+            match run_one(&plugins.engine, component, &objects) {
+                Ok(found) => findings.extend(found),
+                Err(e) => errors.push(format!("Plugin {} failed: {}", path.display(), e)),
+            }
+        }
+
+        debug!("{} plugin findings, {} plugin errors", findings.len(), errors.len());
+        (findings, errors)
+    }
+
+    fn run_one(
+        engine: &Engine,
+        component: &Component,
+        objects: &[analyzer::PdfObject],
+    ) -> wasmtime::Result<Vec<PluginFinding>> {
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+        let mut store: Store<StoreLimits> = Store::new(engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(MAX_FUEL)?;
+
+        // No host functions are linked: plugins cannot perform any I/O
+        let linker = Linker::new(engine);
+        let plugin = Plugin::instantiate(&mut store, component, &linker)?;
+        let analyzer = plugin.pdx_plugin_analyzer();
+
+        let name = analyzer.call_name(&mut store)?;
+        let findings = analyzer.call_analyze(&mut store, objects)?;
+        Ok(findings
+            .into_iter()
+            .map(|finding| PluginFinding {
+                plugin: name.clone(),
+                object: finding.object.map(|id| (id.number, id.generation)),
+                severity: severity(finding.severity),
+                description: finding.description,
+            })
+            .collect())
+    }
+
+    fn severity(severity: analyzer::Severity) -> Severity {
+        match severity {
+            analyzer::Severity::Info => Severity::Info,
+            analyzer::Severity::Low => Severity::Low,
+            analyzer::Severity::Medium => Severity::Medium,
+            analyzer::Severity::High => Severity::High,
+            analyzer::Severity::Critical => Severity::Critical,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_invalid_plugin_is_rejected() {
+            let dir = tempfile::tempdir().unwrap();
+            std::fs::write(dir.path().join("broken.wasm"), b"not a component").unwrap();
+            std::fs::write(dir.path().join("readme.txt"), b"ignored").unwrap();
+            assert!(Plugins::load(dir.path()).is_err());
+
+            std::fs::remove_file(dir.path().join("broken.wasm")).unwrap();
+            assert_eq!(Plugins::load(dir.path()).unwrap().sources().count(), 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_object_json() {
+        let doc = fixtures::document();
+        let page = object_json(doc.get_object(fixtures::PAGE_ID).unwrap());
+        assert_eq!(page["Type"], "Page");
+        assert_eq!(page["Contents"], json!({ "ref": [4, 0] }));
+    }
+
+    #[test]
+    fn test_strings_are_decoded() {
+        let object = Object::Array(vec![Object::string_literal("abc"), Object::Integer(3), Object::Null]);
+        assert_eq!(object_json(&object), json!(["abc", 3, null]));
+    }
+}
@@ -0,0 +1,221 @@
+//! Polyglot detection: files that are also valid in another format
+//! Created: 2026-10-16 09:26:11 UTC
+//! Author: kartik4091
+//!
+//! PDF readers tolerate junk before the header and after `%%EOF`, while
+//! ZIP readers locate their data from the end of the file and HTML parsers
+//! render any markup they come across. One byte stream can therefore open
+//! as a harmless document in a PDF viewer and as a JAR, an archive or a
+//! web page elsewhere. This pass reports every secondary format the file
+//! would be accepted as.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{header::HEADER_WINDOW, Severity};
+use crate::document::magic::{identify, MagicKind};
+
+/// End of central directory record signature
+const EOCD_SIGNATURE: &[u8] = b"PK\x05\x06";
+
+/// Central directory file header signature
+const CENTRAL_SIGNATURE: &[u8] = b"PK\x01\x02";
+
+/// Fixed size of the end of central directory record
+const EOCD_SIZE: usize = 22;
+
+/// Upper bound on central directory entries read for classification
+const MAX_ENTRIES: usize = 4096;
+
+/// Markup that makes an HTML parser treat the file as a page
+const HTML_MARKERS: [&[u8]; 7] = [b"<!doctype html", b"<html", b"<head", b"<body", b"<script", b"<iframe", b"<!--"];
+
+/// A format other than PDF that the file is also valid as
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Polyglot {
+    /// Human-readable format name
+    pub format: String,
+
+    /// MIME type of the format
+    pub mime: String,
+
+    /// Offset where the secondary format's data begins
+    pub offset: usize,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Reports every secondary format the file parses as
+#[instrument(skip(data))]
+pub fn detect(data: &[u8]) -> Vec<Polyglot> {
+    let mut found = Vec::new();
+
+    let archive = zip_archive(data);
+    if let Some(archive) = &archive {
+        found.push(archive.clone());
+    }
+    if let Some(html) = html(data) {
+        found.push(html);
+    }
+
+    // Leading magic bytes, unless they belong to a format reported above
+    if let Some(magic) = identify(data) {
+        let reported = (archive.is_some() && magic.kind == MagicKind::Archive) || magic.mime == "text/html";
+        if magic.mime != "application/pdf" && !reported {
+            let severity = if magic.kind == MagicKind::Executable { Severity::High } else { Severity::Medium };
+            found.push(Polyglot {
+                format: magic.description.to_string(),
+                mime: magic.mime.to_string(),
+                offset: 0,
+                severity,
+                description: format!("File starts as a valid {} ahead of the PDF header", magic.description),
+            });
+        }
+    }
+
+    debug!("Found {} secondary formats", found.len());
+    found
+}
+
+/// A ZIP archive located from the end of the file, classified by its entries
+fn zip_archive(data: &[u8]) -> Option<Polyglot> {
+    let window = data.len().saturating_sub(EOCD_SIZE + u16::MAX as usize);
+    let eocd = window + data[window..].windows(EOCD_SIGNATURE.len()).rposition(|w| w == EOCD_SIGNATURE)?;
+    let record = data.get(eocd..eocd + EOCD_SIZE)?;
+
+    let entries = u16::from_le_bytes([record[10], record[11]]) as usize;
+    let size = u32::from_le_bytes([record[12], record[13], record[14], record[15]]) as usize;
+    let declared = u32::from_le_bytes([record[16], record[17], record[18], record[19]]) as usize;
+
+    // Archive readers accept prepended data by trusting the directory size
+    let directory = eocd.checked_sub(size)?;
+    if !data[directory..].starts_with(CENTRAL_SIGNATURE) {
+        return None;
+    }
+    let shift = directory.checked_sub(declared)?;
+
+    let names = entry_names(&data[directory..eocd], entries);
+    let (format, mime, severity) = if names.iter().any(|n| n == "AndroidManifest.xml" || n == "classes.dex") {
+        ("Android package", "application/vnd.android.package-archive", Severity::High)
+    } else if names.iter().any(|n| n.eq_ignore_ascii_case("META-INF/MANIFEST.MF") || n.ends_with(".class")) {
+        ("JAR archive", "application/java-archive", Severity::High)
+    } else if names.iter().any(|n| n == "[Content_Types].xml") {
+        ("Office Open XML document", "application/vnd.openxmlformats-officedocument", Severity::Medium)
+    } else {
+        ("ZIP archive", "application/zip", Severity::Medium)
+    };
+
+    Some(Polyglot {
+        format: format.to_string(),
+        mime: mime.to_string(),
+        offset: shift,
+        severity,
+        description: format!(
+            "File is also a {} with {} entries (central directory at offset {})",
+            format, entries, directory
+        ),
+    })
+}
+
+/// File names listed in a central directory
+fn entry_names(directory: &[u8], entries: usize) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut pos = 0;
+
+    while names.len() < entries.min(MAX_ENTRIES) && directory[pos..].starts_with(CENTRAL_SIGNATURE) {
+        let Some(header) = directory.get(pos..pos + 46) else {
+            break;
+        };
+        let field = |at: usize| u16::from_le_bytes([header[at], header[at + 1]]) as usize;
+        let (name_len, extra_len, comment_len) = (field(28), field(30), field(32));
+        let Some(name) = directory.get(pos + 46..pos + 46 + name_len) else {
+            break;
+        };
+        names.push(String::from_utf8_lossy(name).into_owned());
+        pos += 46 + name_len + extra_len + comment_len;
+        if pos >= directory.len() {
+            break;
+        }
+    }
+    names
+}
+
+/// HTML markup near the header, where content sniffing looks for it
+fn html(data: &[u8]) -> Option<Polyglot> {
+    let window = data[..data.len().min(HEADER_WINDOW)].to_ascii_lowercase();
+    let (offset, marker) = HTML_MARKERS
+        .iter()
+        .filter_map(|marker| window.windows(marker.len()).position(|w| w == *marker).map(|pos| (pos, *marker)))
+        .min()?;
+
+    let header = window.windows(5).position(|w| w == b"%pdf-");
+    let placement = match header {
+        Some(header) if offset < header => "before the PDF header",
+        _ => "in the file header",
+    };
+    let severity = if marker == b"<script" { Severity::High } else { Severity::Medium };
+
+    Some(Polyglot {
+        format: "HTML document".to_string(),
+        mime: "text/html".to_string(),
+        offset,
+        severity,
+        description: format!(
+            "File renders as HTML: {} markup at offset {} {}",
+            String::from_utf8_lossy(marker),
+            offset,
+            placement
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use std::io::{Cursor, Write};
+
+    fn zip(names: &[&str]) -> Vec<u8> {
+        let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for name in names {
+            archive.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            archive.write_all(b"content").unwrap();
+        }
+        archive.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_plain_pdf_is_not_polyglot() {
+        assert!(detect(&fixtures::simple_pdf()).is_empty());
+    }
+
+    #[test]
+    fn test_appended_jar_is_detected() {
+        let mut data = fixtures::simple_pdf();
+        let pdf_len = data.len();
+        data.extend(zip(&["META-INF/MANIFEST.MF", "Main.class"]));
+
+        let found = detect(&data);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].mime, "application/java-archive");
+        assert_eq!(found[0].severity, Severity::High);
+        assert_eq!(found[0].offset, pdf_len);
+    }
+
+    #[test]
+    fn test_html_wrapped_header_is_detected() {
+        let mut data = b"<html><!--".to_vec();
+        data.extend(fixtures::simple_pdf());
+        data.extend(b"--><script>alert(1)</script></html>");
+
+        let found = detect(&data);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].mime, "text/html");
+        assert!(found[0].description.contains("before the PDF header"));
+    }
+}
@@ -0,0 +1,300 @@
+//! QR code detection in embedded images
+//! Created: 2026-10-16 09:57:43 UTC
+//! Author: kartik4091
+//!
+//! "Quishing" documents carry their link as a QR code, out of reach of
+//! scanners that only look at link annotations and text. This pass decodes
+//! image XObjects to grayscale and runs QR detection on them; decoded
+//! payloads are reported here and their URLs join the URI findings.
+
+use lopdf::{Document, Object, ObjectId, Stream};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::document::{name_value, resolve, stream_data};
+
+/// Images with more pixels than this are not scanned
+const MAX_PIXELS: usize = 16 * 1024 * 1024;
+
+/// Smallest side that can hold a version 1 code
+const MIN_SIDE: usize = 21;
+
+/// A decoded QR code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct QrCode {
+    /// Image XObject containing the code
+    pub image: ObjectId,
+
+    /// Decoded payload
+    pub payload: String,
+
+    /// Pixel corners of the code within the image
+    pub bounds: [(i32, i32); 4],
+}
+
+/// Decodes QR codes in every image XObject
+#[instrument(skip(doc))]
+pub fn detect(doc: &Document) -> Vec<QrCode> {
+    let mut codes = Vec::new();
+    for (&id, object) in &doc.objects {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        if name_value(&stream.dict, b"Subtype") != Some(b"Image") {
+            continue;
+        }
+        let Some((width, height, pixels)) = grayscale(doc, stream) else {
+            continue;
+        };
+
+        let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(width, height, |x, y| pixels[y * width + x]);
+        for grid in prepared.detect_grids() {
+            if let Ok((_, payload)) = grid.decode() {
+                codes.push(QrCode {
+                    image: id,
+                    payload,
+                    bounds: grid.bounds.map(|point| (point.x, point.y)),
+                });
+            }
+        }
+    }
+    debug!("Decoded {} QR codes", codes.len());
+    codes
+}
+
+/// Decodes an image to 8-bit grayscale, top row first.
+///
+/// Supports unfiltered, Flate and DCT data in gray, RGB, CMYK, ICC-based
+/// and indexed color spaces, and image masks. Other encodings (JPX,
+/// JBIG2, CCITT) are skipped.
+pub fn grayscale(doc: &Document, image: &Stream) -> Option<(usize, usize, Vec<u8>)> {
+    let integer = |key: &[u8]| {
+        image
+            .dict
+            .get(key)
+            .ok()
+            .map(|v| resolve(doc, v))
+            .and_then(|v| v.as_i64().ok())
+    };
+    let (width, height) = (
+        usize::try_from(integer(b"Width")?).ok()?,
+        usize::try_from(integer(b"Height")?).ok()?,
+    );
+    if width < MIN_SIDE || height < MIN_SIDE || width.checked_mul(height)? > MAX_PIXELS {
+        return None;
+    }
+
+    let filters: Vec<&[u8]> = match image.dict.get(b"Filter").map(|f| resolve(doc, f)) {
+        Ok(Object::Name(name)) => vec![name],
+        Ok(Object::Array(names)) => names.iter().filter_map(|n| n.as_name().ok()).collect(),
+        _ => Vec::new(),
+    };
+    if filters.last() == Some(&&b"DCTDecode"[..]) {
+        if filters.len() != 1 {
+            return None;
+        }
+        let decoded = image::load_from_memory_with_format(&image.content, image::ImageFormat::Jpeg).ok()?;
+        let luma = decoded.to_luma8();
+        return Some((luma.width() as usize, luma.height() as usize, luma.into_raw()));
+    }
+    if filters.iter().any(|f| !matches!(*f, b"FlateDecode" | b"Fl")) {
+        return None;
+    }
+
+    let mask = image
+        .dict
+        .get(b"ImageMask")
+        .ok()
+        .and_then(|v| v.as_bool().ok())
+        .unwrap_or(false);
+    let bits = if mask {
+        1
+    } else {
+        integer(b"BitsPerComponent").unwrap_or(8) as usize
+    };
+    if !matches!(bits, 1 | 2 | 4 | 8) {
+        return None;
+    }
+    let palette = if mask {
+        Palette::Direct(1)
+    } else {
+        palette(doc, image.dict.get(b"ColorSpace").ok()?)?
+    };
+    let components = match &palette {
+        Palette::Direct(components) => *components,
+        Palette::Indexed(_) => 1,
+    };
+
+    let data = stream_data(image).ok()?;
+    let row_bytes = (width * components * bits + 7) / 8;
+    if data.len() < row_bytes * height {
+        return None;
+    }
+    // A /Decode of [1 0] swaps dark and light (mainly seen on masks)
+    let inverted = match image.dict.get(b"Decode") {
+        Ok(Object::Array(decode)) => decode.first().and_then(|v| v.as_i64().ok()) == Some(1),
+        _ => false,
+    };
+    let max = (1u32 << bits) - 1;
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in data.chunks(row_bytes).take(height) {
+        for x in 0..width {
+            let sample = |c: usize| {
+                let index = (x * components + c) * bits;
+                let byte = row[index / 8] as u32;
+                (byte >> (8 - bits - index % 8)) & max
+            };
+            let gray = match &palette {
+                Palette::Indexed(colors) => colors.get(sample(0) as usize).copied().unwrap_or(0),
+                Palette::Direct(components) => {
+                    let values: Vec<u8> = (0..*components).map(|c| (sample(c) * 255 / max) as u8).collect();
+                    luminance(&values)
+                }
+            };
+            pixels.push(if inverted { 255 - gray } else { gray });
+        }
+    }
+    Some((width, height, pixels))
+}
+
+/// How samples map to gray
+enum Palette {
+    /// Samples are color components
+    Direct(usize),
+
+    /// Samples index these gray levels
+    Indexed(Vec<u8>),
+}
+
+fn palette(doc: &Document, space: &Object) -> Option<Palette> {
+    match resolve(doc, space) {
+        Object::Name(name) => match name.as_slice() {
+            b"DeviceGray" | b"CalGray" | b"G" => Some(Palette::Direct(1)),
+            b"DeviceRGB" | b"CalRGB" | b"RGB" => Some(Palette::Direct(3)),
+            b"DeviceCMYK" | b"CMYK" => Some(Palette::Direct(4)),
+            _ => None,
+        },
+        Object::Array(parts) => match parts.first().and_then(|p| p.as_name().ok())? {
+            b"CalGray" => Some(Palette::Direct(1)),
+            b"CalRGB" | b"Lab" => Some(Palette::Direct(3)),
+            b"ICCBased" => {
+                let profile = resolve(doc, parts.get(1)?).as_stream().ok()?;
+                let n = profile.dict.get(b"N").ok()?.as_i64().ok()?;
+                matches!(n, 1 | 3 | 4).then_some(Palette::Direct(n as usize))
+            }
+            b"Indexed" | b"I" => {
+                let Palette::Direct(components) = palette(doc, parts.get(1)?)? else {
+                    return None;
+                };
+                let lookup = match resolve(doc, parts.get(3)?) {
+                    Object::String(bytes, _) => bytes.clone(),
+                    Object::Stream(stream) => stream_data(stream).ok()?,
+                    _ => return None,
+                };
+                Some(Palette::Indexed(
+                    lookup.chunks_exact(components).map(luminance).collect(),
+                ))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Gray level of a gray, RGB or CMYK color
+fn luminance(color: &[u8]) -> u8 {
+    match *color {
+        [gray] => gray,
+        [r, g, b] => ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8,
+        [c, m, y, k] => {
+            let ink = (c as u32 * 299 + m as u32 * 587 + y as u32 * 114) / 1000 + k as u32;
+            255 - ink.min(255) as u8
+        }
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{detectors::uris, document::fixtures};
+    use lopdf::dictionary;
+
+    /// Rendering of `payload` with 4-pixel modules and a 4-module quiet zone
+    fn render(payload: &str) -> (usize, Vec<bool>) {
+        let code = qrcode::QrCode::new(payload.as_bytes()).unwrap();
+        let modules = code.width();
+        let colors = code.to_colors();
+        let side = (modules + 8) * 4;
+        let dark = (0..side * side)
+            .map(|i| {
+                let (x, y) = ((i % side) / 4, (i / side) / 4);
+                (4..modules + 4).contains(&x)
+                    && (4..modules + 4).contains(&y)
+                    && colors[(y - 4) * modules + x - 4] == qrcode::Color::Dark
+            })
+            .collect();
+        (side, dark)
+    }
+
+    #[test]
+    fn test_gray_image_with_url_feeds_uris() {
+        let (side, dark) = render("https://pay.example/invoice");
+        let pixels: Vec<u8> = dark.iter().map(|&d| if d { 0 } else { 255 }).collect();
+        let mut doc = fixtures::document();
+        let image = doc.add_object(Stream::new(
+            dictionary! {
+                "Subtype" => "Image",
+                "Width" => side as i64,
+                "Height" => side as i64,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            pixels,
+        ));
+
+        let codes = detect(&doc);
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0].image, image);
+        assert_eq!(codes[0].payload, "https://pay.example/invoice");
+
+        let report = uris::extract(&doc);
+        let uri = report
+            .uris
+            .iter()
+            .find(|u| u.raw == "https://pay.example/invoice")
+            .unwrap();
+        assert_eq!(uri.sources, vec![(uris::UriOrigin::QrCode, Some(image))]);
+        assert_eq!(report.qr_codes.len(), 1);
+    }
+
+    #[test]
+    fn test_packed_image_mask() {
+        let (side, dark) = render("WIFI:S:guest;;");
+        // Mask samples of 0 are painted; rows are padded to whole bytes
+        let row_bytes = (side + 7) / 8;
+        let mut packed = vec![0u8; row_bytes * side];
+        for (i, &d) in dark.iter().enumerate() {
+            if !d {
+                packed[(i / side) * row_bytes + (i % side) / 8] |= 0x80 >> (i % side % 8);
+            }
+        }
+        let mut doc = fixtures::document();
+        doc.add_object(Stream::new(
+            dictionary! {
+                "Subtype" => "Image",
+                "Width" => side as i64,
+                "Height" => side as i64,
+                "ImageMask" => true,
+            },
+            packed,
+        ));
+
+        let codes = detect(&doc);
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0].payload, "WIFI:S:guest;;");
+        assert!(uris::extract(&doc).uris.is_empty());
+    }
+}
@@ -0,0 +1,364 @@
+//! Reader security-setting dependent feature summary
+//! Created: 2026-10-16 08:39:40 UTC
+//! Author: kartik4091
+//!
+//! Whether a malicious document can do anything depends heavily on the
+//! victim's reader configuration. This pass lists the features whose
+//! behavior is gated by a reader security setting so that responders can
+//! reason about exploitability for a given client.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::document::{collect_references, name_value, walk_dictionaries};
+
+/// Feature whose behavior depends on a reader security setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum ReaderFeature {
+    /// Document-level or action JavaScript
+    JavaScript,
+
+    /// Embedded files and attachment annotations
+    Attachments,
+
+    /// URI actions and remote go-to targets
+    ExternalLinks,
+
+    /// Launch actions starting external applications
+    Launch,
+
+    /// Form submission or data import to external targets
+    FormSubmission,
+
+    /// XFA forms rendered by the XFA engine
+    Xfa,
+
+    /// Rich media, 3D, sound and movie content
+    Multimedia,
+}
+
+impl ReaderFeature {
+    /// Reader setting that controls the feature
+    pub fn setting(&self) -> &'static str {
+        match self {
+            Self::JavaScript => "Enable Acrobat JavaScript",
+            Self::Attachments => "Allow opening of non-PDF file attachments",
+            Self::ExternalLinks => "Internet access / trusted sites",
+            Self::Launch => "Allow launching external applications",
+            Self::FormSubmission => "Internet access / trusted sites (form submission)",
+            Self::Xfa => "XFA forms support",
+            Self::Multimedia => "Multimedia and 3D content trust",
+        }
+    }
+}
+
+/// Occurrences of a single gated feature
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FeatureUsage {
+    /// Feature kind
+    pub feature: ReaderFeature,
+
+    /// Reader setting gating the feature
+    pub setting: String,
+
+    /// Objects using the feature
+    pub objects: Vec<ObjectId>,
+
+    /// Whether the feature runs without user interaction (/OpenAction, or
+    /// the /AA of the document or a page)
+    pub auto_triggered: bool,
+}
+
+/// Per-document summary of setting-dependent features
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ReaderFeatureSummary {
+    /// Features present in the document
+    pub features: Vec<FeatureUsage>,
+}
+
+/// Client configuration used to evaluate exposure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderSettings {
+    /// JavaScript is enabled
+    pub javascript: bool,
+
+    /// Non-PDF attachments may be opened
+    pub attachments: bool,
+
+    /// Links and submissions may reach the network
+    pub network: bool,
+
+    /// External applications may be launched
+    pub launch: bool,
+
+    /// XFA forms are rendered
+    pub xfa: bool,
+
+    /// Multimedia content is played
+    pub multimedia: bool,
+}
+
+impl Default for ReaderSettings {
+    /// Out-of-the-box Acrobat Reader configuration
+    fn default() -> Self {
+        Self {
+            javascript: true,
+            attachments: false,
+            network: true,
+            launch: false,
+            xfa: true,
+            multimedia: false,
+        }
+    }
+}
+
+impl ReaderSettings {
+    /// Whether the given feature is active under these settings
+    pub fn allows(&self, feature: ReaderFeature) -> bool {
+        match feature {
+            ReaderFeature::JavaScript => self.javascript,
+            ReaderFeature::Attachments => self.attachments,
+            ReaderFeature::ExternalLinks | ReaderFeature::FormSubmission => self.network,
+            ReaderFeature::Launch => self.launch,
+            ReaderFeature::Xfa => self.xfa,
+            ReaderFeature::Multimedia => self.multimedia,
+        }
+    }
+}
+
+impl ReaderFeatureSummary {
+    /// Features that would be active for a client with the given settings
+    pub fn exposure(&self, settings: &ReaderSettings) -> Vec<&FeatureUsage> {
+        self.features.iter().filter(|usage| settings.allows(usage.feature)).collect()
+    }
+
+    /// Merges objects written by an incremental update into the summary.
+    ///
+    /// Redefined objects drop their previous usages. Auto-triggering is
+    /// resolved among the appended objects only.
+    pub fn merge_objects(&mut self, objects: &BTreeMap<ObjectId, Object>) {
+        let mut found: Found = BTreeMap::new();
+        for usage in self.features.drain(..) {
+            let entry = found.entry(usage.feature).or_default();
+            entry.0.extend(usage.objects.into_iter().filter(|id| !objects.contains_key(id)));
+            entry.1 |= usage.auto_triggered;
+        }
+
+        let triggers = auto_trigger_targets(objects.values(), |id| objects.get(&id));
+        record(objects.iter(), &triggers, &mut found);
+        found.retain(|_, (ids, _)| !ids.is_empty());
+        *self = into_summary(found);
+    }
+}
+
+/// Builds the feature summary for a document
+pub fn summarize(doc: &Document) -> ReaderFeatureSummary {
+    let triggers = auto_trigger_targets(doc.objects.values(), |id| doc.get_object(id).ok());
+    let mut found = BTreeMap::new();
+    record(doc.objects.iter(), &triggers, &mut found);
+    into_summary(found)
+}
+
+/// Objects using each feature, and whether any of them is auto-triggered
+type Found = BTreeMap<ReaderFeature, (BTreeSet<ObjectId>, bool)>;
+
+fn record<'a>(
+    objects: impl Iterator<Item = (&'a ObjectId, &'a Object)>,
+    triggers: &BTreeSet<ObjectId>,
+    found: &mut Found,
+) {
+    for (&id, object) in objects {
+        // Inline actions under /OpenAction or /AA fire with their container
+        let auto = triggers.contains(&id)
+            || object.as_dict().map_or(false, is_trigger_container);
+        walk_dictionaries(object, &mut |dict| {
+            for feature in classify(dict) {
+                let entry = found.entry(feature).or_default();
+                entry.0.insert(id);
+                entry.1 |= auto;
+            }
+        });
+    }
+}
+
+fn into_summary(found: Found) -> ReaderFeatureSummary {
+    ReaderFeatureSummary {
+        features: found
+            .into_iter()
+            .map(|(feature, (objects, auto_triggered))| FeatureUsage {
+                feature,
+                setting: feature.setting().to_string(),
+                objects: objects.into_iter().collect(),
+                auto_triggered,
+            })
+            .collect(),
+    }
+}
+
+/// Features used directly by a dictionary
+fn classify(dict: &Dictionary) -> Vec<ReaderFeature> {
+    let mut features = Vec::new();
+
+    match name_value(dict, b"S") {
+        Some(b"JavaScript") => features.push(ReaderFeature::JavaScript),
+        Some(b"URI") | Some(b"GoToR") => features.push(ReaderFeature::ExternalLinks),
+        Some(b"Launch") => features.push(ReaderFeature::Launch),
+        Some(b"SubmitForm") | Some(b"ImportData") => features.push(ReaderFeature::FormSubmission),
+        Some(b"GoToE") => features.push(ReaderFeature::Attachments),
+        Some(b"Rendition") | Some(b"Movie") | Some(b"Sound") => {
+            features.push(ReaderFeature::Multimedia)
+        }
+        _ => {}
+    }
+
+    if dict.has(b"JS") && !features.contains(&ReaderFeature::JavaScript) {
+        features.push(ReaderFeature::JavaScript);
+    }
+    if dict.has(b"EF") || name_value(dict, b"Type") == Some(b"EmbeddedFile") {
+        features.push(ReaderFeature::Attachments);
+    }
+    if dict.has(b"XFA") {
+        features.push(ReaderFeature::Xfa);
+    }
+
+    match name_value(dict, b"Subtype") {
+        Some(b"FileAttachment") => features.push(ReaderFeature::Attachments),
+        Some(b"RichMedia") | Some(b"3D") | Some(b"Sound") | Some(b"Movie") | Some(b"Screen") => {
+            features.push(ReaderFeature::Multimedia)
+        }
+        _ => {}
+    }
+
+    features
+}
+
+/// Whether the dictionary fires its actions without user interaction
+fn is_trigger_container(dict: &Dictionary) -> bool {
+    dict.has(b"OpenAction") || (dict.has(b"AA") && fires_additional_actions(dict))
+}
+
+/// Whether the /AA of a dictionary runs by itself: those of the document
+/// and of pages do, while annotation and field actions wait for the user
+fn fires_additional_actions(dict: &Dictionary) -> bool {
+    matches!(name_value(dict, b"Type"), Some(b"Catalog" | b"Page"))
+}
+
+/// Objects referenced from /OpenAction and document and page /AA entries,
+/// following /Next chains
+fn auto_trigger_targets<'a>(
+    objects: impl Iterator<Item = &'a Object>,
+    lookup: impl Fn(ObjectId) -> Option<&'a Object>,
+) -> BTreeSet<ObjectId> {
+    let mut pending = Vec::new();
+    for object in objects {
+        walk_dictionaries(object, &mut |dict| {
+            if let Ok(action) = dict.get(b"OpenAction") {
+                collect_references(action, &mut pending);
+            }
+            if let Some(aa) = dict.get(b"AA").ok().filter(|_| fires_additional_actions(dict)) {
+                collect_references(aa, &mut pending);
+            }
+        });
+    }
+
+    let mut targets = BTreeSet::new();
+    while let Some(id) = pending.pop() {
+        if !targets.insert(id) {
+            continue;
+        }
+        if let Some(Object::Dictionary(dict)) = lookup(id) {
+            if let Ok(next) = dict.get(b"Next") {
+                collect_references(next, &mut pending);
+            }
+        }
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_plain_document_has_no_features() {
+        let doc = fixtures::document();
+        assert!(summarize(&doc).features.is_empty());
+    }
+
+    #[test]
+    fn test_open_action_javascript_is_auto_triggered() {
+        let mut doc = fixtures::document();
+        let js = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.alert(1)"),
+        });
+        let uri = doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::string_literal("https://example.com"),
+        });
+        doc.get_dictionary_mut(fixtures::CATALOG_ID).unwrap().set("OpenAction", js);
+
+        let summary = summarize(&doc);
+        let js_usage = summary.features.iter().find(|f| f.feature == ReaderFeature::JavaScript).unwrap();
+        assert!(js_usage.auto_triggered);
+
+        let link = summary.features.iter().find(|f| f.feature == ReaderFeature::ExternalLinks).unwrap();
+        assert_eq!(link.objects, vec![uri]);
+        assert!(!link.auto_triggered);
+
+        let locked_down = ReaderSettings { javascript: false, network: false, ..Default::default() };
+        assert!(summary.exposure(&locked_down).is_empty());
+        assert_eq!(summary.exposure(&ReaderSettings::default()).len(), 2);
+    }
+
+    #[test]
+    fn test_only_document_and_page_additional_actions_are_auto_triggered() {
+        let mut doc = fixtures::document();
+        let js = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.alert(1)"),
+        });
+        let uri = doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::string_literal("https://example.com"),
+        });
+        doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            "AA" => dictionary! { "E" => uri },
+        });
+        doc.get_dictionary_mut(fixtures::PAGE_ID).unwrap().set("AA", dictionary! { "O" => js });
+
+        let summary = summarize(&doc);
+        let auto: Vec<_> = summary.features.iter().map(|f| (f.feature, f.auto_triggered)).collect();
+        assert_eq!(auto, vec![(ReaderFeature::JavaScript, true), (ReaderFeature::ExternalLinks, false)]);
+    }
+
+    #[test]
+    fn test_merge_objects_replaces_redefined_usages() {
+        let mut doc = fixtures::document();
+        let uri = doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::string_literal("https://example.com"),
+        });
+        let mut summary = summarize(&doc);
+
+        let appended = BTreeMap::from([(
+            uri,
+            Object::Dictionary(dictionary! {
+                "S" => "Launch",
+                "F" => Object::string_literal("cmd.exe"),
+            }),
+        )]);
+        summary.merge_objects(&appended);
+
+        let features: Vec<_> = summary.features.iter().map(|f| f.feature).collect();
+        assert_eq!(features, vec![ReaderFeature::Launch]);
+    }
+}
@@ -0,0 +1,277 @@
+//! Redaction failure detection
+//! Created: 2026-10-16 09:37:06 UTC
+//! Author: kartik4091
+//!
+//! A redaction only removes information when the text under it is deleted
+//! from the content stream. Two failures are common: /Redact annotations
+//! that mark a region but were never applied, and black boxes drawn (or
+//! black annotations placed) over text that is still in the file and can
+//! be copied or extracted. This pass finds both and reports the text that
+//! can be recovered under each region.
+
+use lopdf::{content::Content, Dictionary, Document, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::{
+    document::{
+        content::{coverage, interpret, Color, ContentEvent, IDENTITY},
+        name_value, number, printable_preview, rectangle, resolve,
+    },
+    text::{self, font::PageFonts, TextSpan},
+};
+
+/// Fraction of a character that must lie under a region to count as covered
+pub const MIN_COVERAGE: f64 = 0.5;
+
+/// Kind of suspected redaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum RedactionKind {
+    /// /Redact annotation marking a region that was never applied
+    UnappliedAnnotation,
+
+    /// Dark filled path painted over text in the content stream
+    DrawnOverlay,
+
+    /// Dark-filled Square annotation placed over text
+    AnnotationOverlay,
+}
+
+/// A suspected redaction region
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RedactionFailure {
+    /// Page object
+    pub page: ObjectId,
+
+    /// One-based page number
+    pub page_number: u32,
+
+    /// Kind of redaction
+    pub kind: RedactionKind,
+
+    /// Covered region in default user space
+    pub region: [f64; 4],
+
+    /// Annotation marking the region
+    pub annotation: Option<ObjectId>,
+
+    /// Text still present under the region, one text operator per line
+    pub recoverable_text: String,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Finds unapplied redactions and overlays covering extractable text
+#[instrument(skip(doc))]
+pub fn detect(doc: &Document) -> Vec<RedactionFailure> {
+    let mut found = Vec::new();
+
+    for (page_number, page) in doc.get_pages() {
+        let fonts = PageFonts::load(doc, page);
+        let events = doc
+            .get_page_content(page)
+            .ok()
+            .and_then(|data| Content::decode(&data).ok())
+            .map(|content| interpret(&content.operations, IDENTITY, &fonts))
+            .unwrap_or_default();
+        let spans: Vec<(usize, TextSpan)> = events
+            .iter()
+            .enumerate()
+            .filter_map(|(position, event)| match event {
+                ContentEvent::Text(shown) => Some((position, text::span(&fonts, shown))),
+                _ => None,
+            })
+            .collect();
+        let mut record = |kind, region, annotation, under: Vec<String>| {
+            let recoverable_text = under.join("\n");
+            let severity = match (kind, recoverable_text.is_empty()) {
+                (_, false) => Severity::High,
+                (RedactionKind::UnappliedAnnotation, true) => Severity::Low,
+                _ => return,
+            };
+            found.push(RedactionFailure {
+                page,
+                page_number,
+                kind,
+                region,
+                annotation,
+                description: if recoverable_text.is_empty() {
+                    format!("{:?} on page {} was never applied", kind, page_number)
+                } else {
+                    format!(
+                        "{:?} on page {} covers recoverable text: \"{}\"",
+                        kind,
+                        page_number,
+                        printable_preview(recoverable_text.replace('\n', " ").as_bytes(), 60)
+                    )
+                },
+                recoverable_text,
+                severity,
+            });
+        };
+
+        // Only text painted before a fill is hidden by it
+        for (position, event) in events.iter().enumerate() {
+            if let ContentEvent::Fill { bbox, color } = event {
+                if color.is_dark() {
+                    let under = covered(&spans, bbox, Some(position));
+                    record(RedactionKind::DrawnOverlay, *bbox, None, under);
+                }
+            }
+        }
+
+        let annots = doc
+            .get_dictionary(page)
+            .ok()
+            .and_then(|dict| dict.get(b"Annots").ok())
+            .and_then(|a| resolve(doc, a).as_array().ok())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for entry in annots {
+            let Ok(annot) = resolve(doc, entry).as_dict() else {
+                continue;
+            };
+            let id = entry.as_reference().ok();
+            let kind = match name_value(annot, b"Subtype") {
+                Some(b"Redact") => RedactionKind::UnappliedAnnotation,
+                Some(b"Square") if interior_color(doc, annot).is_dark() => RedactionKind::AnnotationOverlay,
+                _ => continue,
+            };
+            for region in regions(doc, annot) {
+                let under = covered(&spans, &region, None);
+                record(kind, region, id, under);
+            }
+        }
+    }
+
+    debug!("Found {} suspected redaction failures", found.len());
+    found
+}
+
+/// Characters under `region`, per span painted before `before` when given
+fn covered(spans: &[(usize, TextSpan)], region: &[f64; 4], before: Option<usize>) -> Vec<String> {
+    spans
+        .iter()
+        .filter(|(position, _)| before.map_or(true, |before| *position < before))
+        .map(|(_, span)| {
+            span.chars
+                .iter()
+                .filter(|c| coverage(region, &c.bbox) >= MIN_COVERAGE)
+                .map(|c| c.text.as_str())
+                .collect::<String>()
+        })
+        .filter(|text| !text.trim().is_empty())
+        .collect()
+}
+
+/// Regions of a markup annotation: each /QuadPoints quadrilateral, or /Rect
+pub fn regions(doc: &Document, annot: &Dictionary) -> Vec<[f64; 4]> {
+    let quads: Vec<f64> = annot
+        .get(b"QuadPoints")
+        .ok()
+        .and_then(|q| resolve(doc, q).as_array().ok())
+        .map(|points| points.iter().filter_map(number).collect())
+        .unwrap_or_default();
+    let boxes: Vec<[f64; 4]> = quads
+        .chunks_exact(8)
+        .map(|quad| {
+            let xs = [quad[0], quad[2], quad[4], quad[6]];
+            let ys = [quad[1], quad[3], quad[5], quad[7]];
+            [
+                xs.iter().copied().fold(f64::INFINITY, f64::min),
+                ys.iter().copied().fold(f64::INFINITY, f64::min),
+                xs.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                ys.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            ]
+        })
+        .collect();
+    if !boxes.is_empty() {
+        return boxes;
+    }
+    annot
+        .get(b"Rect")
+        .ok()
+        .and_then(|r| rectangle(doc, r))
+        .into_iter()
+        .collect()
+}
+
+/// Interior color (/IC) of an annotation; transparent when absent
+pub fn interior_color(doc: &Document, annot: &Dictionary) -> Color {
+    let components: Vec<f64> = annot
+        .get(b"IC")
+        .ok()
+        .and_then(|c| resolve(doc, c).as_array().ok())
+        .map(|c| c.iter().filter_map(number).collect())
+        .unwrap_or_default();
+    match components[..] {
+        [g] => Color::Gray(g),
+        [r, g, b] => Color::Rgb(r, g, b),
+        [c, m, y, k] => Color::Cmyk(c, m, y, k),
+        _ => Color::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures::{self, with_content};
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_black_box_over_text() {
+        let doc = with_content(
+            b"BT /F1 10 Tf 72 700 Td (SSN 123-45-6789) Tj ET 0 g 70 695 100 15 re f \
+              0 g 70 600 100 15 re f BT /F1 10 Tf 72 605 Td (printed on top) Tj ET",
+        );
+        let found = detect(&doc);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, RedactionKind::DrawnOverlay);
+        assert_eq!(found[0].recoverable_text, "SSN 123-45-6789");
+        assert_eq!(found[0].severity, Severity::High);
+    }
+
+    #[test]
+    fn test_unapplied_redact_annotation() {
+        let mut doc = with_content(b"BT /F1 10 Tf 72 700 Td (secret) Tj ET");
+        let redact = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Redact",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            "QuadPoints" => vec![
+                70.into(), 712.into(), 110.into(), 712.into(),
+                70.into(), 695.into(), 110.into(), 695.into(),
+            ],
+        });
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Annots", vec![redact.into()]);
+
+        let found = detect(&doc);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].annotation, Some(redact));
+        assert_eq!(found[0].region, [70.0, 695.0, 110.0, 712.0]);
+        assert_eq!(found[0].recoverable_text, "secret");
+    }
+
+    #[test]
+    fn test_dark_square_annotation_without_text_is_ignored() {
+        let mut doc = with_content(b"BT /F1 10 Tf 72 700 Td (public) Tj ET");
+        let square = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Square",
+            "IC" => vec![0.into()],
+            "Rect" => vec![300.into(), 300.into(), 400.into(), 400.into()],
+        });
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Annots", vec![square.into()]);
+        assert!(detect(&doc).is_empty());
+    }
+}
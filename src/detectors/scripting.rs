@@ -0,0 +1,315 @@
+//! Rhai scripting hooks for custom analyzers
+//! Created: 2026-10-16 10:08:26 UTC
+//! Author: kartik4091
+//!
+//! Scripts run after the built-in detectors, so they can both inspect the
+//! object model and enrich what the analysis already found. Each script
+//! sees these constants:
+//!
+//! - `objects`: array of `#{ id: [num, gen], type: "Action/JavaScript",
+//!   stream: bool, dict: #{ ... }, value: ... }`, where `dict` holds the
+//!   (stream) dictionary and `value` any other kind of object; names and
+//!   decoded strings are strings and references are `#{ ref: [num, gen] }`
+//! - `metadata`: map of Info entries (and `XMP`)
+//! - `analysis`: the analysis so far, as serialized in JSON reports
+//!
+//! and these functions:
+//!
+//! - `stream_text(num, gen)`: decoded stream content, lossily as text
+//! - `report(severity, description)` and
+//!   `report_object(severity, description, num, gen)`: add a finding
+//!
+//! Scripts run with an operation limit so a runaway loop cannot stall the
+//! analysis. The interpreter is behind the `scripting` feature; without it
+//! the report types exist but no scripts can be loaded.
+
+use lopdf::ObjectId;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::Severity;
+
+/// Script file extension loaded from a directory
+pub const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Most Rhai operations a single script may perform
+pub const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// A finding reported by a script
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScriptFinding {
+    /// Script name (the file stem)
+    pub script: String,
+
+    /// Object the finding is about, if any
+    pub object: Option<ObjectId>,
+
+    /// Severity chosen by the script
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+#[cfg(feature = "scripting")]
+pub use engine::{run, Scripts};
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use std::{
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::{Arc, Mutex},
+    };
+
+    use lopdf::{Document, Object, ObjectId};
+    use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Position, Scope, AST, INT};
+    use serde::Serialize;
+    use tracing::{debug, info, instrument};
+
+    use super::{ScriptFinding, Severity, MAX_OPERATIONS, SCRIPT_EXTENSION};
+    use crate::{
+        document::{decode_text, metadata_text, stream_data, taxonomy::ObjectType},
+        error::{Error, Result},
+    };
+
+    /// Compiled analyzer scripts
+    #[derive(Debug, Clone)]
+    pub struct Scripts {
+        scripts: Vec<(String, AST)>,
+
+        /// Script files the set was compiled from
+        pub sources: Vec<PathBuf>,
+    }
+
+    impl Scripts {
+        /// Compiles script files, and the `.rhai` files of directories
+        pub fn load(paths: &[PathBuf]) -> Result<Self> {
+            let mut sources = Vec::new();
+            for path in paths {
+                if path.is_dir() {
+                    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+                        .filter_map(|entry| entry.ok().map(|e| e.path()))
+                        .filter(|p| p.extension().map_or(false, |e| e == SCRIPT_EXTENSION))
+                        .collect();
+                    files.sort();
+                    sources.extend(files);
+                } else {
+                    sources.push(path.clone());
+                }
+            }
+
+            let engine = Engine::new();
+            let mut scripts = Vec::with_capacity(sources.len());
+            for source in &sources {
+                let text = std::fs::read_to_string(source)?;
+                let ast = engine
+                    .compile(text)
+                    .map_err(|e| Error::Analysis(format!("Script {}: {}", source.display(), e)))?;
+                scripts.push((name(source), ast));
+            }
+            Ok(Self { scripts, sources })
+        }
+    }
+
+    fn name(path: &Path) -> String {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "script".to_string())
+    }
+
+    type Reported = Arc<Mutex<Vec<(Severity, String, Option<ObjectId>)>>>;
+
+    /// Runs every script over the document and the analysis so far.
+    ///
+    /// A failing script does not stop the others; its error is returned
+    /// alongside the findings of the scripts that completed.
+    #[instrument(skip_all)]
+    pub fn run(scripts: &Scripts, doc: &Document, analysis: &impl Serialize) -> (Vec<ScriptFinding>, Vec<String>) {
+        let reported: Reported = Default::default();
+        let engine = engine(Arc::new(doc.clone()), reported.clone());
+
+        let objects: Array = doc
+            .objects
+            .iter()
+            .map(|(&id, object)| object_entry(id, object))
+            .collect();
+        let metadata: Map = metadata_text(doc)
+            .into_iter()
+            .map(|(key, value)| (key.into(), Dynamic::from(value)))
+            .collect();
+        let analysis = rhai::serde::to_dynamic(analysis).unwrap_or(Dynamic::UNIT);
+
+        let mut findings = Vec::new();
+        let mut errors = Vec::new();
+        for (name, ast) in &scripts.scripts {
+            let mut scope = Scope::new();
+            scope.push_constant("objects", objects.clone());
+            scope.push_constant("metadata", metadata.clone());
+            scope.push_constant("analysis", analysis.clone());
+
+            if let Err(e) = engine.run_ast_with_scope(&mut scope, ast) {
+                errors.push(format!("Script {} failed: {}", name, e));
+            }
+            // Findings reported before a failure are kept
+            let drained: Vec<_> = reported.lock().unwrap().drain(..).collect();
+            findings.extend(
+                drained
+                    .into_iter()
+                    .map(|(severity, description, object)| ScriptFinding {
+                        script: name.clone(),
+                        object,
+                        severity,
+                        description,
+                    }),
+            );
+        }
+
+        debug!("{} script findings, {} script errors", findings.len(), errors.len());
+        (findings, errors)
+    }
+
+    fn engine(doc: Arc<Document>, reported: Reported) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.on_print(|text| info!("script: {}", text));
+
+        engine.register_fn("stream_text", move |num: INT, gen: INT| -> String {
+            let id = (num as u32, gen as u16);
+            match doc.get_object(id) {
+                Ok(Object::Stream(stream)) => stream_data(stream)
+                    .map(|data| String::from_utf8_lossy(&data).into_owned())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            }
+        });
+
+        let sink = reported.clone();
+        engine.register_fn(
+            "report",
+            move |severity: &str, description: &str| -> std::result::Result<(), Box<EvalAltResult>> {
+                sink.lock()
+                    .unwrap()
+                    .push((parse_severity(severity)?, description.to_string(), None));
+                Ok(())
+            },
+        );
+        engine.register_fn(
+            "report_object",
+            move |severity: &str,
+                  description: &str,
+                  num: INT,
+                  gen: INT|
+                  -> std::result::Result<(), Box<EvalAltResult>> {
+                let object = Some((num as u32, gen as u16));
+                reported
+                    .lock()
+                    .unwrap()
+                    .push((parse_severity(severity)?, description.to_string(), object));
+                Ok(())
+            },
+        );
+        engine
+    }
+
+    fn parse_severity(name: &str) -> std::result::Result<Severity, Box<EvalAltResult>> {
+        Severity::from_str(name).map_err(|e| EvalAltResult::ErrorRuntime(e.into(), Position::NONE).into())
+    }
+
+    fn object_entry(id: ObjectId, object: &Object) -> Dynamic {
+        let mut entry = Map::new();
+        entry.insert("id".into(), id_value(id));
+        entry.insert("type".into(), Dynamic::from(ObjectType::of(object).to_string()));
+        entry.insert("stream".into(), Dynamic::from_bool(matches!(object, Object::Stream(_))));
+        let (dict, value) = match object {
+            Object::Dictionary(_) | Object::Stream(_) => (to_dynamic(object), Dynamic::UNIT),
+            _ => (Dynamic::from_map(Map::new()), to_dynamic(object)),
+        };
+        entry.insert("dict".into(), dict);
+        entry.insert("value".into(), value);
+        Dynamic::from_map(entry)
+    }
+
+    fn id_value(id: ObjectId) -> Dynamic {
+        Dynamic::from_array(vec![Dynamic::from_int(id.0 as INT), Dynamic::from_int(id.1 as INT)])
+    }
+
+    /// Converts a PDF object to the value scripts see
+    fn to_dynamic(object: &Object) -> Dynamic {
+        match object {
+            Object::Null => Dynamic::UNIT,
+            Object::Boolean(b) => Dynamic::from_bool(*b),
+            Object::Integer(i) => Dynamic::from_int(*i as INT),
+            Object::Real(r) => Dynamic::from_float(*r as rhai::FLOAT),
+            Object::Name(name) => Dynamic::from(String::from_utf8_lossy(name).into_owned()),
+            Object::String(bytes, _) => Dynamic::from(decode_text(bytes)),
+            Object::Array(items) => Dynamic::from_array(items.iter().map(to_dynamic).collect()),
+            Object::Dictionary(dict) | Object::Stream(lopdf::Stream { dict, .. }) => Dynamic::from_map(
+                dict.iter()
+                    .map(|(key, value)| (String::from_utf8_lossy(key).as_ref().into(), to_dynamic(value)))
+                    .collect(),
+            ),
+            Object::Reference(id) => {
+                let mut reference = Map::new();
+                reference.insert("ref".into(), id_value(*id));
+                Dynamic::from_map(reference)
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::document::fixtures;
+        use std::io::Write;
+
+        fn scripts(source: &str) -> Scripts {
+            let mut file = tempfile::Builder::new().suffix(".rhai").tempfile().unwrap();
+            file.write_all(source.as_bytes()).unwrap();
+            Scripts::load(&[file.path().to_path_buf()]).unwrap()
+        }
+
+        #[test]
+        fn test_script_reports_objects() {
+            let scripts = scripts(
+                r#"
+                for object in objects {
+                    if object.type == "Font/Type1" && object.dict.BaseFont == "Helvetica" {
+                        report_object("low", "Standard 14 font", object.id[0], object.id[1]);
+                    }
+                    if object.stream && stream_text(object.id[0], object.id[1]).contains("Hello") {
+                        report("info", "Greeting found");
+                    }
+                }
+                "#,
+            );
+            let (findings, errors) = run(&scripts, &fixtures::document(), &serde_json::json!({}));
+
+            assert!(errors.is_empty(), "{:?}", errors);
+            assert_eq!(findings.len(), 2);
+            assert_eq!(findings[0].object, Some((2, 0)));
+            assert_eq!(findings[0].severity, Severity::Low);
+            assert_eq!(findings[1].object, None);
+        }
+
+        #[test]
+        fn test_script_sees_analysis() {
+            let scripts = scripts(r#"if analysis.uris.len() > 1 { report("medium", "Many links"); }"#);
+            let analysis = serde_json::json!({ "uris": ["https://a.example", "https://b.example"] });
+            let (findings, _) = run(&scripts, &fixtures::document(), &analysis);
+            assert_eq!(findings.len(), 1);
+        }
+
+        #[test]
+        fn test_errors_and_runaway_scripts_are_contained() {
+            let scripts = scripts(r#"report("info", "before"); report("bogus", "after");"#);
+            let (findings, errors) = run(&scripts, &fixtures::document(), &serde_json::json!({}));
+            assert_eq!(findings.len(), 1);
+            assert_eq!(errors.len(), 1);
+
+            let looping = scripts("loop { }");
+            let (_, errors) = run(&looping, &fixtures::document(), &serde_json::json!({}));
+            assert_eq!(errors.len(), 1);
+        }
+    }
+}
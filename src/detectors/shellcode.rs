@@ -0,0 +1,368 @@
+//! Shellcode and heap-spray detection
+//! Created: 2026-10-16 10:22:36 UTC
+//! Author: kartik4091
+//!
+//! Exploit documents carry their payload either in JavaScript, as long
+//! `%uXXXX` or `\xNN` escaped strings fed to `unescape` and sprayed over
+//! the heap, or as raw bytes in a stream the exploit later locates. This
+//! pass decodes escaped script payloads and scans them, along with every
+//! decoded non-image stream, for NOP sleds and for position-independent
+//! x86 idioms (GetPC sequences, PEB access through `fs:[0x30]`), and
+//! flags script loops that grow a string to heap-spray size. Findings
+//! include a short 32-bit disassembly at the match.
+
+use iced_x86::{Decoder, DecoderOptions, Formatter, Instruction, IntelFormatter};
+use lopdf::{Document, Object, ObjectId};
+use regex::{bytes::Regex as BytesRegex, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{javascript, name_value, stream_data};
+
+/// Streams larger than this (decoded) are not scanned
+const MAX_SCANNED_STREAM: usize = 16 * 1024 * 1024;
+
+/// Shortest run of NOPs reported as a sled
+const MIN_SLED: usize = 64;
+
+/// Shortest escaped script payload, in decoded bytes
+const MIN_PAYLOAD: usize = 64;
+
+/// Slide bytes of sprayed payloads: NOP and the `or al, 0Ch` style
+/// slides that double as heap addresses
+const SLED_BYTES: [u8; 3] = [0x90, 0x0C, 0x0D];
+
+/// Instructions shown in a disassembly snippet
+const SNIPPET_INSTRUCTIONS: usize = 8;
+
+/// Byte sequences of x86 shellcode idioms; `None` matches any byte
+const IDIOMS: [(ShellcodeKind, &[Option<u8>]); 4] = [
+    // call $+5; pop reg
+    (
+        ShellcodeKind::GetPc,
+        &[Some(0xE8), Some(0), Some(0), Some(0), Some(0), None],
+    ),
+    // fldz; fnstenv [esp-0xc]
+    (
+        ShellcodeKind::GetPc,
+        &[Some(0xD9), Some(0xEE), Some(0xD9), Some(0x74), Some(0x24), Some(0xF4)],
+    ),
+    // mov eax, fs:[0x30]
+    (
+        ShellcodeKind::PebAccess,
+        &[Some(0x64), Some(0xA1), Some(0x30), Some(0), Some(0), Some(0)],
+    ),
+    // mov reg, fs:[reg+0x30]
+    (ShellcodeKind::PebAccess, &[Some(0x64), Some(0x8B), None, Some(0x30)]),
+];
+
+/// Kind of shellcode indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub enum ShellcodeKind {
+    /// Long run of single-byte no-op instructions
+    NopSled,
+
+    /// Code locating its own address (call/pop, FPU state)
+    GetPc,
+
+    /// Access to the process environment block, the first step of
+    /// resolving API addresses without imports
+    PebAccess,
+
+    /// Long `%uXXXX` or `\xNN` escaped binary string in a script
+    EscapedPayload,
+
+    /// Script loop growing a string to heap-spray size
+    HeapSpray,
+}
+
+/// A shellcode indicator
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ShellcodeFinding {
+    /// Indicator kind
+    pub kind: ShellcodeKind,
+
+    /// Stream or script object
+    pub object: ObjectId,
+
+    /// Whether the bytes come from an escaped script string rather than
+    /// a stream
+    pub in_script: bool,
+
+    /// Offset in the decoded stream, decoded script payload or script text
+    pub offset: usize,
+
+    /// Length of the matched bytes
+    pub length: usize,
+
+    /// 32-bit disassembly from the match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disassembly: Vec<String>,
+
+    /// Finding severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Scans scripts and decoded streams for shellcode indicators
+#[instrument(skip_all)]
+pub fn scan(doc: &Document) -> Vec<ShellcodeFinding> {
+    let mut findings = Vec::new();
+    let idioms: Vec<_> = IDIOMS.iter().map(|&(kind, idiom)| (kind, idiom_regex(idiom))).collect();
+
+    for (&id, object) in &doc.objects {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        // Pixel data has long uniform runs; font programs are code of their own
+        if matches!(name_value(&stream.dict, b"Subtype"), Some(b"Image")) || stream.dict.has(b"Length1") {
+            continue;
+        }
+        if let Ok(data) = stream_data(stream) {
+            if data.len() <= MAX_SCANNED_STREAM {
+                findings.extend(scan_bytes(&data, id, false, &[0x90], &idioms));
+            }
+        }
+    }
+
+    let unicode = Regex::new(r"(?:%u[0-9a-fA-F]{4}){16,}").expect("valid escape pattern");
+    let hex = Regex::new(r"(?:\\x[0-9a-fA-F]{2}){32,}").expect("valid escape pattern");
+    let spray = Regex::new(r"(?s)\b(?:while|for)\s*\([^)]{0,80}\.length\s*<\s*(?:0x[0-9a-fA-F]{5,}|\d{6,})")
+        .expect("valid spray pattern");
+    for (id, script) in javascript(doc) {
+        for m in unicode.find_iter(&script).chain(hex.find_iter(&script)) {
+            let payload = unescape(m.as_str());
+            if payload.len() < MIN_PAYLOAD {
+                continue;
+            }
+            let start = payload.iter().position(|b| !SLED_BYTES.contains(b)).unwrap_or(0);
+            findings.push(ShellcodeFinding {
+                kind: ShellcodeKind::EscapedPayload,
+                object: id,
+                in_script: true,
+                offset: m.start(),
+                length: payload.len(),
+                disassembly: disassemble(&payload, start),
+                severity: Severity::High,
+                description: format!(
+                    "{}-byte escaped binary payload in script object {} {}",
+                    payload.len(),
+                    id.0,
+                    id.1
+                ),
+            });
+            findings.extend(scan_bytes(&payload, id, true, &SLED_BYTES, &idioms));
+        }
+        if let Some(m) = spray.find(&script) {
+            findings.push(ShellcodeFinding {
+                kind: ShellcodeKind::HeapSpray,
+                object: id,
+                in_script: true,
+                offset: m.start(),
+                length: m.len(),
+                disassembly: Vec::new(),
+                severity: Severity::High,
+                description: format!("Heap-spray loop in script object {} {}", id.0, id.1),
+            });
+        }
+    }
+
+    debug!("Found {} shellcode indicators", findings.len());
+    findings
+}
+
+/// Finds sleds and idioms in binary data, at most one finding per kind
+fn scan_bytes(
+    data: &[u8],
+    object: ObjectId,
+    in_script: bool,
+    sled_bytes: &[u8],
+    idioms: &[(ShellcodeKind, BytesRegex)],
+) -> Vec<ShellcodeFinding> {
+    let mut findings = Vec::new();
+    let location = if in_script { "script payload" } else { "stream" };
+
+    if let Some((offset, length)) = longest_sled(data, sled_bytes).filter(|&(_, length)| length >= MIN_SLED) {
+        findings.push(ShellcodeFinding {
+            kind: ShellcodeKind::NopSled,
+            object,
+            in_script,
+            offset,
+            length,
+            disassembly: disassemble(data, offset + length),
+            severity: Severity::High,
+            description: format!(
+                "{}-byte {:#04x} sled at offset {} of {} {} {}",
+                length, data[offset], offset, location, object.0, object.1
+            ),
+        });
+    }
+
+    let mut reported = Vec::new();
+    for (kind, pattern) in idioms {
+        let kind = *kind;
+        if reported.contains(&kind) {
+            continue;
+        }
+        if let Some(m) = pattern.find(data) {
+            reported.push(kind);
+            let name = match kind {
+                ShellcodeKind::GetPc => "GetPC sequence",
+                _ => "PEB access via fs:[0x30]",
+            };
+            findings.push(ShellcodeFinding {
+                kind,
+                object,
+                in_script,
+                offset: m.start(),
+                length: m.len(),
+                disassembly: disassemble(data, m.start()),
+                severity: Severity::High,
+                description: format!(
+                    "x86 {} at offset {} of {} {} {}",
+                    name,
+                    m.start(),
+                    location,
+                    object.0,
+                    object.1
+                ),
+            });
+        }
+    }
+
+    findings
+}
+
+fn idiom_regex(idiom: &[Option<u8>]) -> BytesRegex {
+    let pattern: String = idiom
+        .iter()
+        .map(|byte| match byte {
+            Some(b) => format!(r"\x{:02x}", b),
+            None => "(?s:.)".to_string(),
+        })
+        .collect();
+    BytesRegex::new(&format!("(?-u){}", pattern)).expect("valid idiom pattern")
+}
+
+/// Offset and length of the longest run of one of the sled bytes
+fn longest_sled(data: &[u8], sled_bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    for (start, &byte) in data.iter().enumerate() {
+        if !sled_bytes.contains(&byte) || (start > 0 && data[start - 1] == byte) {
+            continue;
+        }
+        let length = data[start..].iter().take_while(|&&b| b == byte).count();
+        if best.map_or(true, |(_, best)| length > best) {
+            best = Some((start, length));
+        }
+    }
+    best
+}
+
+/// Decodes `%uXXXX` (little-endian code units, as `unescape` lays them
+/// out in memory) and `\xNN` escapes
+fn unescape(escaped: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(escaped.len() / 2);
+    let mut rest = escaped;
+    while !rest.is_empty() {
+        if let Some(unit) = rest.strip_prefix("%u").and_then(|r| r.get(..4)) {
+            if let Ok(unit) = u16::from_str_radix(unit, 16) {
+                out.extend_from_slice(&unit.to_le_bytes());
+                rest = &rest[6..];
+                continue;
+            }
+        }
+        if let Some(byte) = rest.strip_prefix("\\x").and_then(|r| r.get(..2)) {
+            if let Ok(byte) = u8::from_str_radix(byte, 16) {
+                out.push(byte);
+                rest = &rest[4..];
+                continue;
+            }
+        }
+        let skip = rest.chars().next().map_or(1, char::len_utf8);
+        rest = &rest[skip..];
+    }
+    out
+}
+
+/// Disassembles a few 32-bit instructions from `offset`
+fn disassemble(data: &[u8], offset: usize) -> Vec<String> {
+    let Some(code) = data.get(offset..) else {
+        return Vec::new();
+    };
+    let mut decoder = Decoder::with_ip(32, code, offset as u64, DecoderOptions::NONE);
+    let mut formatter = IntelFormatter::new();
+    let mut instruction = Instruction::default();
+    let mut lines = Vec::new();
+    while decoder.can_decode() && lines.len() < SNIPPET_INSTRUCTIONS {
+        decoder.decode_out(&mut instruction);
+        if instruction.is_invalid() {
+            break;
+        }
+        let mut text = String::new();
+        formatter.format(&instruction, &mut text);
+        lines.push(format!("{:08x}  {}", instruction.ip(), text));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_escaped_payload_with_sled() {
+        let mut doc = fixtures::document();
+        let script = format!("var sc = unescape(\"{}%u00e8%u0000%u5d00\");", "%u9090".repeat(40));
+        let id = doc.add_object(dictionary! { "S" => "JavaScript", "JS" => Object::string_literal(script) });
+
+        let findings = scan(&doc);
+        let kinds: Vec<_> = findings.iter().map(|f| f.kind).collect();
+        assert!(kinds.contains(&ShellcodeKind::EscapedPayload));
+        assert!(kinds.contains(&ShellcodeKind::NopSled));
+        assert!(kinds.contains(&ShellcodeKind::GetPc));
+        assert!(findings.iter().all(|f| f.object == id && f.in_script));
+
+        let payload = findings
+            .iter()
+            .find(|f| f.kind == ShellcodeKind::EscapedPayload)
+            .unwrap();
+        assert!(payload.disassembly[0].contains("call"));
+    }
+
+    #[test]
+    fn test_stream_idioms_and_images() {
+        let mut code = vec![0x31, 0xC0, 0x64, 0xA1, 0x30, 0, 0, 0, 0x8B, 0x40, 0x0C];
+        let mut doc = fixtures::document();
+        let stream = doc.add_object(Stream::new(dictionary! {}, code.clone()));
+
+        let findings = scan(&doc);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, ShellcodeKind::PebAccess);
+        assert_eq!((findings[0].object, findings[0].offset), (stream, 2));
+        assert!(findings[0].disassembly[0].contains("fs:"));
+
+        code.extend(vec![0x90; 128]);
+        let mut doc = fixtures::document();
+        doc.add_object(Stream::new(dictionary! { "Subtype" => "Image" }, code));
+        assert!(scan(&doc).is_empty());
+    }
+
+    #[test]
+    fn test_heap_spray_loop() {
+        let mut doc = fixtures::document();
+        let script = "var s = unescape('%u0c0c'); while (s.length < 0x40000) s += s;";
+        doc.add_object(dictionary! { "JS" => Object::string_literal(script) });
+        let findings = scan(&doc);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, ShellcodeKind::HeapSpray);
+
+        assert_eq!(unescape("%u9090\\x41%uzzzz"), vec![0x90, 0x90, 0x41]);
+    }
+}
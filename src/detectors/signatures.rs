@@ -0,0 +1,322 @@
+//! Signature timestamps versus revision append order
+//! Created: 2026-10-16 08:44:25 UTC
+//! Author: kartik4091
+//!
+//! Each signature is appended as part of an incremental update, so the
+//! physical order of revisions is also the order in which the signatures
+//! were applied. A signature in a later revision that claims (or carries a
+//! timestamp-authority token for) an earlier time than a signature before it
+//! is a strong backdating indicator.
+
+use chrono::{DateTime, Utc};
+use lopdf::{Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    name_value, objects::scan_objects, parse_pdf_date, parser::parse_indirect,
+    revisions::{find, split_revisions},
+};
+
+/// DER encoding of the id-ct-TSTInfo content type (1.2.840.113549.1.9.16.1.4)
+const TST_INFO_OID: &[u8] = &[
+    0x06, 0x0B, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x10, 0x01, 0x04,
+];
+
+/// DER encoding of the signingTime attribute (1.2.840.113549.1.9.5)
+const SIGNING_TIME_OID: &[u8] = &[0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x05];
+
+/// Where a signature time was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum TimeSource {
+    /// Signer-supplied time (/M or the signingTime attribute)
+    Claimed,
+
+    /// Time-stamp authority token embedded in the signature
+    Tsa,
+}
+
+/// A signature and the times it asserts
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SignatureRecord {
+    /// Signature dictionary
+    pub id: ObjectId,
+
+    /// Offset of the signature dictionary in the file
+    pub offset: usize,
+
+    /// Revision (in append order) that wrote the signature
+    pub revision: Option<usize>,
+
+    /// /SubFilter of the signature
+    pub sub_filter: Option<String>,
+
+    /// Signer-claimed signing time
+    pub signing_time: Option<DateTime<Utc>>,
+
+    /// Time-stamp authority time
+    pub tsa_time: Option<DateTime<Utc>>,
+}
+
+impl SignatureRecord {
+    fn times(&self) -> impl Iterator<Item = (TimeSource, DateTime<Utc>)> {
+        let claimed = self.signing_time.map(|t| (TimeSource::Claimed, t));
+        let tsa = self.tsa_time.map(|t| (TimeSource::Tsa, t));
+        claimed.into_iter().chain(tsa)
+    }
+}
+
+/// A later-appended signature dated before an earlier one
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BackdatingIndicator {
+    /// Signature in the earlier revision
+    pub earlier: ObjectId,
+
+    /// Signature in the later revision
+    pub later: ObjectId,
+
+    /// Time source of the earlier signature
+    pub earlier_source: TimeSource,
+
+    /// Time source of the later signature
+    pub later_source: TimeSource,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Signatures in append order together with ordering violations
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SignatureTimeline {
+    /// Signatures found in the file, in file order
+    pub signatures: Vec<SignatureRecord>,
+
+    /// Timestamps contradicting the append order
+    pub backdating: Vec<BackdatingIndicator>,
+}
+
+/// Collects every signature in the file and checks its times against the
+/// revision order
+#[instrument(skip(data))]
+pub fn check(data: &[u8]) -> SignatureTimeline {
+    let signatures = collect(data, 0);
+    let backdating = ordering_violations(&signatures);
+    debug!(
+        "Found {} signatures, {} backdating indicators",
+        signatures.len(),
+        backdating.len()
+    );
+    SignatureTimeline { signatures, backdating }
+}
+
+/// Extends a previous timeline with the signatures written after `start`
+#[instrument(skip(data, previous))]
+pub fn check_appended(data: &[u8], start: usize, previous: &SignatureTimeline) -> SignatureTimeline {
+    let mut signatures = previous.signatures.clone();
+    signatures.extend(collect(data, start));
+    let backdating = ordering_violations(&signatures);
+    SignatureTimeline { signatures, backdating }
+}
+
+/// Signature dictionaries whose objects start at or after `start`
+fn collect(data: &[u8], start: usize) -> Vec<SignatureRecord> {
+    let revisions = split_revisions(data);
+    scan_objects(&data[start..])
+        .into_iter()
+        .filter_map(|raw| {
+            let offset = raw.offset + start;
+            let parsed = parse_indirect(data, offset).ok()?;
+            let dict = parsed.object.as_dict().ok()?;
+            if !dict.has(b"ByteRange") {
+                return None;
+            }
+            let contents = match dict.get(b"Contents").ok()? {
+                Object::String(bytes, _) => bytes.as_slice(),
+                _ => return None,
+            };
+
+            let signing_time = dict
+                .get(b"M")
+                .ok()
+                .and_then(|m| m.as_str().ok())
+                .and_then(parse_pdf_date)
+                .or_else(|| signed_attribute_time(contents));
+
+            Some(SignatureRecord {
+                id: raw.id,
+                offset,
+                revision: revisions
+                    .iter()
+                    .find(|rev| (rev.start..rev.end).contains(&offset))
+                    .map(|rev| rev.index),
+                sub_filter: name_value(dict, b"SubFilter")
+                    .map(|name| String::from_utf8_lossy(name).into_owned()),
+                signing_time,
+                tsa_time: tsa_time(contents),
+            })
+        })
+        .collect()
+}
+
+fn ordering_violations(signatures: &[SignatureRecord]) -> Vec<BackdatingIndicator> {
+    let mut indicators = Vec::new();
+
+    for (i, earlier) in signatures.iter().enumerate() {
+        for later in &signatures[i + 1..] {
+            match (earlier.revision, later.revision) {
+                (Some(a), Some(b)) if a < b => {}
+                _ => continue,
+            }
+
+            // Report the strongest contradiction for the pair
+            let worst = earlier
+                .times()
+                .flat_map(|e| later.times().map(move |l| (e, l)))
+                .filter(|((_, e), (_, l))| l < e)
+                .max_by_key(|((source, _), _)| *source == TimeSource::Tsa);
+
+            if let Some(((earlier_source, earlier_time), (later_source, later_time))) = worst {
+                // A trusted anchor on the earlier signature proves the later one backdated
+                let severity = if earlier_source == TimeSource::Tsa {
+                    Severity::High
+                } else {
+                    Severity::Medium
+                };
+                indicators.push(BackdatingIndicator {
+                    earlier: earlier.id,
+                    later: later.id,
+                    earlier_source,
+                    later_source,
+                    severity,
+                    description: format!(
+                        "Signature {} {} appended after signature {} {} is dated {} ({:?}), before {} ({:?})",
+                        later.id.0,
+                        later.id.1,
+                        earlier.id.0,
+                        earlier.id.1,
+                        later_time.to_rfc3339(),
+                        later_source,
+                        earlier_time.to_rfc3339(),
+                        earlier_source
+                    ),
+                });
+            }
+        }
+    }
+
+    indicators
+}
+
+/// genTime of an embedded RFC 3161 token: the first GeneralizedTime in TSTInfo
+fn tsa_time(contents: &[u8]) -> Option<DateTime<Utc>> {
+    let start = find(contents, TST_INFO_OID, 0)? + TST_INFO_OID.len();
+    der_time_after(contents, start, 0x18)
+}
+
+/// Value of the CMS signingTime signed attribute
+fn signed_attribute_time(contents: &[u8]) -> Option<DateTime<Utc>> {
+    let start = find(contents, SIGNING_TIME_OID, 0)? + SIGNING_TIME_OID.len();
+    der_time_after(contents, start, 0x17)
+        .or_else(|| der_time_after(contents, start, 0x18))
+}
+
+/// Finds the next DER time value with the given tag and decodes it
+fn der_time_after(contents: &[u8], from: usize, tag: u8) -> Option<DateTime<Utc>> {
+    let window = &contents[from..contents.len().min(from + 256)];
+    window.windows(2).enumerate().find_map(|(i, pair)| {
+        if pair[0] != tag || !(11..=24).contains(&pair[1]) {
+            return None;
+        }
+        let value = window.get(i + 2..i + 2 + pair[1] as usize)?;
+        if tag == 0x17 {
+            // UTCTime carries a two-digit year (RFC 5280 interpretation)
+            let century: &[u8] = if value.first()? < &b'5' { b"20" } else { b"19" };
+            parse_pdf_date(&[century, value].concat())
+        } else {
+            parse_pdf_date(value)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    fn signature(date: &str, contents: &[u8]) -> String {
+        let hex: String = contents.iter().map(|b| format!("{:02X}", b)).collect();
+        format!(
+            "<< /Type /Sig /SubFilter /adbe.pkcs7.detached /M (D:{}) /ByteRange [0 0 0 0] /Contents <{}> >>",
+            date, hex
+        )
+    }
+
+    fn tsa_token(time: &str) -> Vec<u8> {
+        let mut token = vec![0x30, 0x00];
+        token.extend_from_slice(TST_INFO_OID);
+        token.extend_from_slice(&[0x02, 0x01, 0x01]);
+        token.push(0x18);
+        token.push(time.len() as u8);
+        token.extend_from_slice(time.as_bytes());
+        token
+    }
+
+    #[test]
+    fn test_unsigned_document() {
+        let timeline = check(&fixtures::simple_pdf());
+        assert!(timeline.signatures.is_empty());
+        assert!(timeline.backdating.is_empty());
+    }
+
+    #[test]
+    fn test_ordered_signatures() {
+        let base = fixtures::simple_pdf();
+        let first = fixtures::append_revision(&base, &[(7, &signature("20240101120000Z", &[0]))]);
+        let data = fixtures::append_revision(&first, &[(8, &signature("20240201120000Z", &[0]))]);
+
+        let timeline = check(&data);
+        assert_eq!(timeline.signatures.len(), 2);
+        assert_eq!(timeline.signatures[0].revision, Some(1));
+        assert_eq!(timeline.signatures[1].revision, Some(2));
+        assert!(timeline.backdating.is_empty());
+    }
+
+    #[test]
+    fn test_check_appended() {
+        let base = fixtures::simple_pdf();
+        let first = fixtures::append_revision(&base, &[(7, &signature("20240301120000Z", &[0]))]);
+        let data = fixtures::append_revision(&first, &[(8, &signature("20240101120000Z", &[0]))]);
+
+        let previous = check(&first);
+        let timeline = check_appended(&data, first.len(), &previous);
+        assert_eq!(timeline.signatures.len(), 2);
+        assert_eq!(timeline.backdating.len(), check(&data).backdating.len());
+    }
+
+    #[test]
+    fn test_later_revision_with_earlier_tsa_time() {
+        let base = fixtures::simple_pdf();
+        let first = fixtures::append_revision(
+            &base,
+            &[(7, &signature("20240301120000Z", &tsa_token("20240301120005Z")))],
+        );
+        let data = fixtures::append_revision(&first, &[(8, &signature("20230101000000Z", &[0]))]);
+
+        let timeline = check(&data);
+        assert_eq!(
+            timeline.signatures[0].tsa_time.unwrap().to_rfc3339(),
+            "2024-03-01T12:00:05+00:00"
+        );
+
+        let indicator = &timeline.backdating[0];
+        assert_eq!(indicator.earlier, (7, 0));
+        assert_eq!(indicator.later, (8, 0));
+        assert_eq!(indicator.earlier_source, TimeSource::Tsa);
+        assert_eq!(indicator.severity, Severity::High);
+    }
+}
@@ -0,0 +1,292 @@
+//! Slack-space analysis between file structures
+//! Created: 2026-10-16 08:53:07 UTC
+//! Author: kartik4091
+//!
+//! Builds a byte-coverage map assigning every range of the file to the
+//! structure it belongs to (header, object, xref table, startxref block) and
+//! reports the ranges nobody claims. Readers skip such gaps silently, which
+//! makes them a convenient place to hide data between objects.
+//!
+//! Bytes after the final `%%EOF` are reported by the trailing-data pass and
+//! are left out of the gap list.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    objects::scan_objects,
+    printable_preview,
+    revisions::{find, split_revisions},
+    shannon_entropy,
+    xref::{read_sections, XrefKind},
+};
+
+/// Gaps shorter than this are reported as informational
+const SMALL_GAP: usize = 16;
+
+/// Entropy above which a gap is considered compressed or encrypted
+const HIGH_ENTROPY: f64 = 7.0;
+
+/// Structure a byte range belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum RegionKind {
+    /// `%PDF-` line and the comment lines directly following it
+    Header,
+
+    /// An indirect object, including xref and object streams
+    Object,
+
+    /// Classic xref table and its trailer dictionary
+    XrefTable,
+
+    /// `startxref`, its offset and the `%%EOF` marker
+    StartXref,
+}
+
+/// A covered range of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Region {
+    /// First byte
+    pub start: usize,
+
+    /// Offset just past the last byte
+    pub end: usize,
+
+    /// Owning structure
+    pub kind: RegionKind,
+}
+
+/// Content classification of an unclaimed range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum GapClass {
+    /// Only NUL bytes
+    ZeroPadding,
+
+    /// PDF comment lines
+    Comment,
+
+    /// Printable ASCII text
+    Ascii,
+
+    /// Compressed or encrypted-looking data
+    HighEntropy,
+
+    /// Other binary data
+    Binary,
+}
+
+/// An unclaimed, non-whitespace range
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SlackGap {
+    /// First byte
+    pub start: usize,
+
+    /// Number of bytes
+    pub length: usize,
+
+    /// Content classification
+    pub class: GapClass,
+
+    /// Shannon entropy in bits per byte
+    pub entropy: f64,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Printable excerpt
+    pub preview: String,
+}
+
+/// Coverage map and the gaps found in it
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SlackReport {
+    /// Covered ranges in file order
+    pub regions: Vec<Region>,
+
+    /// Bytes claimed by some structure
+    pub covered: usize,
+
+    /// Bytes up to the end of the final `%%EOF` line
+    pub analyzed: usize,
+
+    /// Unclaimed ranges that are not plain whitespace
+    pub gaps: Vec<SlackGap>,
+}
+
+/// Maps the file's structures and reports the slack space between them
+#[instrument(skip(data))]
+pub fn analyze(data: &[u8]) -> SlackReport {
+    let revisions = split_revisions(data);
+    let analyzed = revisions.last().map_or(data.len(), |rev| rev.end);
+
+    let mut regions = Vec::new();
+    if let Some(end) = header_end(data) {
+        regions.push(Region { start: 0, end, kind: RegionKind::Header });
+    }
+    regions.extend(scan_objects(data).into_iter().map(|raw| Region {
+        start: raw.offset,
+        end: raw.end,
+        kind: RegionKind::Object,
+    }));
+    if let Ok(sections) = read_sections(data) {
+        for section in sections.iter().filter(|s| s.kind == XrefKind::Table) {
+            let start = section.offset as usize;
+            if let Some(end) = find(data, b"startxref", start) {
+                regions.push(Region { start, end, kind: RegionKind::XrefTable });
+            }
+        }
+    }
+    for revision in &revisions {
+        let block = &data[revision.start..revision.end];
+        if let Some(pos) = block.windows(9).rposition(|w| w == b"startxref") {
+            regions.push(Region {
+                start: revision.start + pos,
+                end: revision.end,
+                kind: RegionKind::StartXref,
+            });
+        }
+    }
+    regions.sort_by_key(|r| (r.start, r.end));
+
+    let mut gaps = Vec::new();
+    let mut covered = 0;
+    let mut cursor = 0;
+    for region in &regions {
+        if region.start > cursor {
+            gaps.extend(classify(data, cursor, region.start.min(analyzed)));
+        }
+        if region.end > cursor {
+            covered += region.end - region.start.max(cursor);
+            cursor = region.end;
+        }
+    }
+    if cursor < analyzed {
+        gaps.extend(classify(data, cursor, analyzed));
+    }
+
+    debug!("{} of {} bytes covered, {} gaps", covered, analyzed, gaps.len());
+    SlackReport { regions, covered, analyzed, gaps }
+}
+
+/// End of the header line and any comment lines right after it
+fn header_end(data: &[u8]) -> Option<usize> {
+    let start = find(data, b"%PDF-", 0)?;
+    let mut pos = start;
+    while data.get(pos) == Some(&b'%') {
+        pos = line_end(data, pos);
+    }
+    Some(pos)
+}
+
+fn line_end(data: &[u8], from: usize) -> usize {
+    let mut pos = data[from..]
+        .iter()
+        .position(|&b| b == b'\r' || b == b'\n')
+        .map_or(data.len(), |p| from + p);
+    while pos < data.len() && (data[pos] == b'\r' || data[pos] == b'\n') {
+        pos += 1;
+    }
+    pos
+}
+
+fn classify(data: &[u8], start: usize, end: usize) -> Option<SlackGap> {
+    if start >= end {
+        return None;
+    }
+    let bytes = &data[start..end];
+    // Whitespace between structures is ordinary formatting
+    let first = bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+    let last = bytes.iter().rposition(|b| !b.is_ascii_whitespace())?;
+    let (start, bytes) = (start + first, &bytes[first..=last]);
+
+    let entropy = shannon_entropy(bytes);
+    let class = if bytes.iter().all(|&b| b == 0) {
+        GapClass::ZeroPadding
+    } else if is_comment_block(bytes) {
+        GapClass::Comment
+    } else if bytes.iter().all(|&b| b.is_ascii_graphic() || b.is_ascii_whitespace()) {
+        GapClass::Ascii
+    } else if entropy > HIGH_ENTROPY {
+        GapClass::HighEntropy
+    } else {
+        GapClass::Binary
+    };
+
+    let severity = match class {
+        _ if bytes.len() < SMALL_GAP => Severity::Info,
+        GapClass::Comment | GapClass::ZeroPadding => Severity::Info,
+        GapClass::Ascii => Severity::Low,
+        GapClass::Binary => Severity::Medium,
+        GapClass::HighEntropy => Severity::High,
+    };
+
+    Some(SlackGap {
+        start,
+        length: bytes.len(),
+        class,
+        entropy,
+        severity,
+        preview: printable_preview(bytes, 80),
+    })
+}
+
+/// Whether every non-blank line starts with `%`
+fn is_comment_block(bytes: &[u8]) -> bool {
+    bytes
+        .split(|&b| b == b'\r' || b == b'\n')
+        .filter_map(|line| line.iter().find(|b| !b.is_ascii_whitespace()))
+        .all(|&first| first == b'%')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_clean_file_is_fully_covered() {
+        let report = analyze(&fixtures::simple_pdf());
+        assert!(report.gaps.is_empty(), "{:?}", report.gaps);
+        assert!(report.regions.iter().any(|r| r.kind == RegionKind::XrefTable));
+    }
+
+    /// Inserts bytes before the `index`th object, keeping startxref valid
+    fn insert_before_object(index: usize, bytes: &[u8]) -> (Vec<u8>, usize) {
+        let data = fixtures::simple_pdf();
+        let first = scan_objects(&data)[index].offset;
+        let startxref = crate::document::revisions::find_startxref(&data).unwrap() as usize;
+        let keyword = data.windows(9).rposition(|w| w == b"startxref").unwrap();
+
+        let mut patched = data[..first].to_vec();
+        patched.extend_from_slice(bytes);
+        patched.extend_from_slice(&data[first..keyword]);
+        patched.extend_from_slice(format!("startxref\n{}\n%%EOF\n", startxref + bytes.len()).as_bytes());
+        (patched, first)
+    }
+
+    #[test]
+    fn test_gap_between_objects() {
+        let mut blob: Vec<u8> = (0..=255u8).cycle().take(512).collect();
+        blob.push(b'\n');
+        let (data, first) = insert_before_object(0, &blob);
+
+        let report = analyze(&data);
+        assert_eq!(report.gaps.len(), 1);
+        assert_eq!(report.gaps[0].start, first);
+        assert_eq!(report.gaps[0].length, 512);
+        assert_eq!(report.gaps[0].class, GapClass::HighEntropy);
+        assert_eq!(report.gaps[0].severity, Severity::High);
+    }
+
+    #[test]
+    fn test_comment_gap() {
+        let (data, _) = insert_before_object(1, b"% just a note that is long enough\n");
+
+        let report = analyze(&data);
+        assert_eq!(report.gaps.len(), 1);
+        assert_eq!(report.gaps[0].class, GapClass::Comment);
+        assert_eq!(report.gaps[0].severity, Severity::Info);
+    }
+}
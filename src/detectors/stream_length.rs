@@ -0,0 +1,335 @@
+//! Stream /Length consistency checks
+//! Created: 2026-10-16 09:02:12 UTC
+//! Author: kartik4091
+//!
+//! Readers that trust /Length and readers that scan for `endstream` see
+//! different stream data when the two disagree. This pass measures every
+//! stream both ways, resolves indirect lengths (flagging ones that resolve
+//! to different values depending on which definition is used), and checks
+//! decoded sizes against what the stream dictionary implies.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{Object, ObjectId, Stream};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    name_value,
+    objects::{scan_objects, RawObject},
+    parser::{parse_indirect, parse_indirect_with, LengthPolicy, ParseOptions},
+    revisions::find,
+    xref::{effective_entries, read_sections, XrefState},
+};
+use crate::{error::Error, limits};
+
+/// Decoded-to-encoded ratio above which a stream is reported
+const EXPANSION_RATIO: usize = 100;
+
+/// Kind of stream length finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum StreamLengthKind {
+    /// /Length is absent or not a number
+    MissingLength,
+
+    /// /Length differs from the bytes up to `endstream`
+    LengthMismatch,
+
+    /// No `endstream` keyword follows the data
+    MissingEndstream,
+
+    /// Indirect /Length points at a missing or non-integer object
+    UnresolvedLength,
+
+    /// Indirect /Length is defined several times with different values
+    AmbiguousLength,
+
+    /// /DL or the image dimensions disagree with the decoded size
+    DecodedSizeMismatch,
+
+    /// Decoded data is far larger than the encoded data
+    ExcessiveExpansion,
+}
+
+/// A single stream length finding
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StreamLengthFinding {
+    /// Finding kind
+    pub kind: StreamLengthKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Stream object
+    pub id: ObjectId,
+
+    /// Offset of the stream data
+    pub offset: usize,
+
+    /// Declared length (resolved when indirect)
+    pub declared: Option<i64>,
+
+    /// Bytes between `stream` and `endstream`
+    pub actual: usize,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Checks every stream in the file, including shadowed definitions
+#[instrument(skip(data))]
+pub fn check(data: &[u8]) -> Vec<StreamLengthFinding> {
+    let raw = scan_objects(data);
+    let xref = read_sections(data).map(|s| effective_entries(&s)).unwrap_or_default();
+    let mut definitions: BTreeMap<u32, Vec<&RawObject>> = BTreeMap::new();
+    for object in &raw {
+        definitions.entry(object.id.0).or_default().push(object);
+    }
+
+    let mut findings = Vec::new();
+    for object in &raw {
+        let options = ParseOptions { stream_length: LengthPolicy::Endstream, ..Default::default() };
+        let Ok(parsed) = parse_indirect_with(data, object.offset, options) else {
+            continue;
+        };
+        let (Object::Stream(stream), Some(offset)) = (&parsed.object, parsed.stream_offset) else {
+            continue;
+        };
+
+        let mut finding = |kind, severity, declared, description: String| {
+            findings.push(StreamLengthFinding {
+                kind,
+                severity,
+                id: parsed.id,
+                offset,
+                declared,
+                actual: stream.content.len(),
+                description,
+            })
+        };
+
+        if find(data, b"endstream", offset).is_none() {
+            finding(
+                StreamLengthKind::MissingEndstream,
+                Severity::Medium,
+                None,
+                format!("Stream {} {} has no endstream keyword", parsed.id.0, parsed.id.1),
+            );
+        }
+
+        let declared = match stream.dict.get(b"Length") {
+            Ok(Object::Integer(length)) => Some(*length),
+            Ok(Object::Reference(target)) => {
+                let values = indirect_values(data, definitions.get(&target.0));
+                if values.len() > 1 {
+                    finding(
+                        StreamLengthKind::AmbiguousLength,
+                        Severity::High,
+                        None,
+                        format!(
+                            "/Length {} {} R of stream {} {} has conflicting definitions {:?}",
+                            target.0, target.1, parsed.id.0, parsed.id.1, values
+                        ),
+                    );
+                }
+                let effective = match xref.get(&target.0) {
+                    Some(XrefState::InUse { offset, .. }) => parse_indirect(data, *offset as usize).ok(),
+                    _ => None,
+                };
+                match effective.map(|p| p.object) {
+                    Some(Object::Integer(length)) => Some(length),
+                    _ => {
+                        finding(
+                            StreamLengthKind::UnresolvedLength,
+                            Severity::Medium,
+                            None,
+                            format!(
+                                "/Length {} {} R of stream {} {} does not resolve to an integer",
+                                target.0, target.1, parsed.id.0, parsed.id.1
+                            ),
+                        );
+                        None
+                    }
+                }
+            }
+            _ => {
+                finding(
+                    StreamLengthKind::MissingLength,
+                    Severity::Medium,
+                    None,
+                    format!("Stream {} {} has no usable /Length", parsed.id.0, parsed.id.1),
+                );
+                None
+            }
+        };
+
+        if let Some(declared) = declared {
+            let actual = stream.content.len() as i64;
+            if declared != actual {
+                // Counting the EOL before endstream is a common writer bug
+                let severity = match declared.checked_sub(actual) {
+                    Some(1 | 2) => Severity::Low,
+                    Some(d) if d >= 0 => Severity::Medium,
+                    _ => Severity::High,
+                };
+                finding(
+                    StreamLengthKind::LengthMismatch,
+                    severity,
+                    Some(declared),
+                    format!(
+                        "Stream {} {} declares /Length {} but has {} bytes before endstream",
+                        parsed.id.0, parsed.id.1, declared, actual
+                    ),
+                );
+            }
+        }
+
+        for (kind, severity, description) in decoded_size(stream) {
+            finding(kind, severity, declared, format!("Stream {} {}: {}", parsed.id.0, parsed.id.1, description));
+        }
+    }
+
+    debug!("Found {} stream length findings", findings.len());
+    findings
+}
+
+/// Distinct integer values across every definition of an object number
+fn indirect_values(data: &[u8], definitions: Option<&Vec<&RawObject>>) -> BTreeSet<i64> {
+    definitions
+        .into_iter()
+        .flatten()
+        .filter_map(|raw| match parse_indirect(data, raw.offset).ok()?.object {
+            Object::Integer(value) => Some(value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Compares the decoded size with /DL, image dimensions and the encoded size.
+/// Decoding stops at the resource limits in force, which is itself reported.
+fn decoded_size(stream: &Stream) -> Vec<(StreamLengthKind, Severity, String)> {
+    let mut found = Vec::new();
+    let decoded = if stream.dict.get(b"Filter").is_ok() {
+        limits::decode(stream, &limits::active())
+    } else {
+        Ok(stream.content.clone())
+    };
+    let decoded = match decoded {
+        Ok(decoded) => decoded,
+        Err(Error::Security(reason)) => {
+            found.push((StreamLengthKind::ExcessiveExpansion, Severity::High, reason));
+            return found;
+        }
+        Err(_) => return found,
+    };
+    let dict = &stream.dict;
+
+    if let Ok(Object::Integer(dl)) = dict.get(b"DL") {
+        if *dl != decoded.len() as i64 {
+            found.push((
+                StreamLengthKind::DecodedSizeMismatch,
+                Severity::Low,
+                format!("/DL {} but {} bytes decoded", dl, decoded.len()),
+            ));
+        }
+    }
+
+    if let Some(expected) = expected_image_size(stream) {
+        if expected != decoded.len() {
+            found.push((
+                StreamLengthKind::DecodedSizeMismatch,
+                if decoded.len() > expected { Severity::Medium } else { Severity::Low },
+                format!("image dimensions imply {} bytes but {} decoded", expected, decoded.len()),
+            ));
+        }
+    }
+
+    if !stream.content.is_empty() && decoded.len() / stream.content.len() > EXPANSION_RATIO {
+        found.push((
+            StreamLengthKind::ExcessiveExpansion,
+            Severity::Medium,
+            format!("{} bytes expand to {}", stream.content.len(), decoded.len()),
+        ));
+    }
+    found
+}
+
+/// Raw sample size of an image with a device color space and a lossless filter
+fn expected_image_size(stream: &Stream) -> Option<usize> {
+    let dict = &stream.dict;
+    if name_value(dict, b"Subtype") != Some(b"Image") {
+        return None;
+    }
+    let lossless = match dict.get(b"Filter") {
+        Err(_) => true,
+        Ok(Object::Name(name)) => name == b"FlateDecode" || name == b"LZWDecode",
+        Ok(_) => false,
+    };
+    let colors = match name_value(dict, b"ColorSpace")? {
+        b"DeviceGray" => 1,
+        b"DeviceRGB" => 3,
+        b"DeviceCMYK" => 4,
+        _ => return None,
+    };
+    // Predictors add a tag byte per row; skip rather than model them
+    if !lossless || dict.has(b"DecodeParms") {
+        return None;
+    }
+
+    let dimension = |key: &[u8]| dict.get(key).ok()?.as_i64().ok().filter(|v| *v > 0).map(|v| v as usize);
+    let (width, height, bits) = (dimension(b"Width")?, dimension(b"Height")?, dimension(b"BitsPerComponent")?);
+    let row = (width.checked_mul(colors)?.checked_mul(bits)? + 7) / 8;
+    row.checked_mul(height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_clean_document() {
+        assert!(check(&fixtures::simple_pdf()).is_empty());
+    }
+
+    #[test]
+    fn test_short_length_hides_data() {
+        let data = fixtures::append_revision(
+            &fixtures::simple_pdf(),
+            &[(7, "<< /Length 3 >>\nstream\nabc hidden\nendstream")],
+        );
+        let findings = check(&data);
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, StreamLengthKind::LengthMismatch);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].actual, 10);
+    }
+
+    #[test]
+    fn test_ambiguous_indirect_length() {
+        let base = fixtures::append_revision(&fixtures::simple_pdf(), &[(8, "5"), (7, "<< /Length 8 0 R >>\nstream\nhello\nendstream")]);
+        let data = fixtures::append_revision(&base, &[(8, "3")]);
+        let findings = check(&data);
+
+        let kinds: Vec<_> = findings.iter().map(|f| f.kind).collect();
+        assert!(kinds.contains(&StreamLengthKind::AmbiguousLength));
+        // The effective /Length is the later definition
+        let mismatch = findings.iter().find(|f| f.kind == StreamLengthKind::LengthMismatch).unwrap();
+        assert_eq!(mismatch.declared, Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_decoding_stops_at_limits() {
+        let body = format!("<< /Length 401 /Filter /ASCIIHexDecode >>\nstream\n{}>\nendstream", "00".repeat(200));
+        let data = fixtures::append_revision(&fixtures::simple_pdf(), &[(7, &body)]);
+        let limits = limits::ResourceLimits { max_decoded_size: 100, ..Default::default() };
+        let findings = limits::scope(limits, async { check(&data) }).await;
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, StreamLengthKind::ExcessiveExpansion);
+        assert_eq!(findings[0].severity, Severity::High);
+    }
+}
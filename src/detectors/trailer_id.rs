@@ -0,0 +1,299 @@
+//! Trailer /ID forensics across revisions
+//! Created: 2026-10-16 08:45:55 UTC
+//! Author: kartik4091
+//!
+//! The first element of /ID is meant to be permanent, fixed when the file
+//! is first written; the second is regenerated on every save. Writers
+//! conventionally derive both from an MD5 digest. Rewritten permanent IDs,
+//! a second ID that never changes across saves, or IDs that are not even
+//! digest-shaped all point at a tool editing the file behind its producer's
+//! back.
+
+use lopdf::Object;
+use md5::{Digest, Md5};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    parser::parse_indirect,
+    to_hex,
+    xref::{effective_entries, read_sections, XrefSection, XrefState},
+};
+
+/// How an ID value relates to the MD5 derivation convention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum IdDerivation {
+    /// MD5 of the revision's document information values
+    Md5OfInfo,
+
+    /// 16 varied bytes, consistent with an MD5 digest of unknown input
+    Md5Shaped,
+
+    /// Wrong length, constant bytes or plain text
+    Irregular,
+}
+
+/// /ID as recorded by one xref section
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RevisionId {
+    /// Section index in append order
+    pub revision: usize,
+
+    /// Offset of the xref section carrying the trailer
+    pub section_offset: u64,
+
+    /// Permanent identifier (first element), hex encoded
+    pub permanent: Option<String>,
+
+    /// Changing identifier (second element), hex encoded
+    pub changing: Option<String>,
+
+    /// Derivation of the permanent identifier
+    pub derivation: Option<IdDerivation>,
+}
+
+/// Kind of /ID inconsistency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum IdAnomalyKind {
+    /// A revision dropped the /ID present in an earlier one
+    Missing,
+
+    /// The permanent identifier changed between revisions
+    PermanentChanged,
+
+    /// The changing identifier was not updated by a save
+    ChangingNotUpdated,
+
+    /// An identifier does not follow the MD5 convention
+    Irregular,
+}
+
+/// A single /ID finding
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct IdAnomaly {
+    /// Anomaly kind
+    pub kind: IdAnomalyKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Section index in append order
+    pub revision: usize,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Trailer /ID history of a document
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TrailerIdReport {
+    /// /ID per xref section, oldest first
+    pub revisions: Vec<RevisionId>,
+
+    /// Inconsistencies between revisions
+    pub anomalies: Vec<IdAnomaly>,
+}
+
+/// Reads /ID from every trailer in the xref chain and checks its history
+#[instrument(skip(data))]
+pub fn check(data: &[u8]) -> TrailerIdReport {
+    let Ok(sections) = read_sections(data) else {
+        return TrailerIdReport::default();
+    };
+
+    // Newest first; hybrid /XRefStm streams share their table's trailer
+    let chain: Vec<usize> = (0..sections.len()).filter(|&i| !sections[i].via_xref_stm).collect();
+    let revisions: Vec<RevisionId> = chain
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(revision, &i)| revision_id(data, &sections, i, revision))
+        .collect();
+
+    let anomalies = history_anomalies(&revisions);
+    debug!("Read /ID from {} trailers, {} anomalies", revisions.len(), anomalies.len());
+    TrailerIdReport { revisions, anomalies }
+}
+
+fn revision_id(data: &[u8], sections: &[XrefSection], index: usize, revision: usize) -> RevisionId {
+    let section = &sections[index];
+    let ids: Vec<&[u8]> = match section.trailer.get(b"ID") {
+        Ok(Object::Array(items)) => items
+            .iter()
+            .filter_map(|item| match item {
+                Object::String(bytes, _) => Some(bytes.as_slice()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let permanent = ids.first().copied();
+    let derivation = permanent.map(|id| {
+        // Sections from `index` on describe the file as of this revision
+        let info = info_values(data, &sections[index..]);
+        derive(id, info.as_deref())
+    });
+
+    RevisionId {
+        revision,
+        section_offset: section.offset,
+        permanent: permanent.map(to_hex),
+        changing: ids.get(1).copied().map(to_hex),
+        derivation,
+    }
+}
+
+/// Concatenated string values of the /Info dictionary visible to a revision
+fn info_values(data: &[u8], sections: &[XrefSection]) -> Option<Vec<u8>> {
+    let id = sections.first()?.trailer.get(b"Info").ok()?.as_reference().ok()?;
+    let XrefState::InUse { offset, .. } = *effective_entries(sections).get(&id.0)? else {
+        return None;
+    };
+    let parsed = parse_indirect(data, offset as usize).ok()?;
+    let dict = parsed.object.as_dict().ok()?;
+
+    let mut values = Vec::new();
+    for (_, value) in dict.iter() {
+        if let Object::String(bytes, _) = value {
+            values.extend_from_slice(bytes);
+        }
+    }
+    Some(values)
+}
+
+fn derive(id: &[u8], info: Option<&[u8]>) -> IdDerivation {
+    if info.map_or(false, |info| Md5::digest(info).as_slice() == id) {
+        return IdDerivation::Md5OfInfo;
+    }
+
+    let distinct = {
+        let mut bytes = id.to_vec();
+        bytes.sort_unstable();
+        bytes.dedup();
+        bytes.len()
+    };
+    let textual = id.iter().all(|b| b.is_ascii_alphanumeric());
+    if id.len() == 16 && distinct > 4 && !textual {
+        IdDerivation::Md5Shaped
+    } else {
+        IdDerivation::Irregular
+    }
+}
+
+fn history_anomalies(revisions: &[RevisionId]) -> Vec<IdAnomaly> {
+    let mut anomalies = Vec::new();
+
+    for current in revisions {
+        if current.derivation == Some(IdDerivation::Irregular) {
+            anomalies.push(IdAnomaly {
+                kind: IdAnomalyKind::Irregular,
+                severity: Severity::Low,
+                revision: current.revision,
+                description: format!(
+                    "Permanent ID {} is not MD5-shaped",
+                    current.permanent.as_deref().unwrap_or_default()
+                ),
+            });
+        }
+    }
+
+    for pair in revisions.windows(2) {
+        let (before, after) = (&pair[0], &pair[1]);
+        let mut push = |kind, severity, description: String| {
+            anomalies.push(IdAnomaly { kind, severity, revision: after.revision, description });
+        };
+
+        match (&before.permanent, &after.permanent) {
+            (Some(_), None) => push(
+                IdAnomalyKind::Missing,
+                Severity::Low,
+                "Revision dropped the trailer /ID".into(),
+            ),
+            (Some(old), Some(new)) if old != new => push(
+                IdAnomalyKind::PermanentChanged,
+                Severity::Medium,
+                format!("Permanent ID changed from {} to {}", old, new),
+            ),
+            _ => {}
+        }
+
+        if let (Some(old), Some(new)) = (&before.changing, &after.changing) {
+            if old == new {
+                push(
+                    IdAnomalyKind::ChangingNotUpdated,
+                    Severity::Low,
+                    format!("Changing ID {} was not updated by the save", new),
+                );
+            }
+        }
+    }
+
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    const FIRST: &str = "<0f1e2d3c4b5a69788796a5b4c3d2e1f0>";
+    const SECOND: &str = "<a1b2c3d4e5f60718293a4b5c6d7e8f90>";
+
+    fn base_with_id() -> Vec<u8> {
+        let base = fixtures::simple_pdf();
+        fixtures::append_revision_with_trailer(&base, &[(7, "<< /Type /Annot >>")], &format!("/ID [{} {}]", FIRST, FIRST))
+    }
+
+    #[test]
+    fn test_no_ids() {
+        let report = check(&fixtures::simple_pdf());
+        assert_eq!(report.revisions.len(), 1);
+        assert!(report.revisions[0].permanent.is_none());
+        assert!(report.anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_well_behaved_ids() {
+        let data = fixtures::append_revision_with_trailer(
+            &base_with_id(),
+            &[(8, "<< /Type /Annot >>")],
+            &format!("/ID [{} {}]", FIRST, SECOND),
+        );
+
+        let report = check(&data);
+        assert_eq!(report.revisions.len(), 3);
+        assert_eq!(report.revisions[2].derivation, Some(IdDerivation::Md5Shaped));
+        assert!(report.anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_rewritten_and_stale_ids() {
+        let data = fixtures::append_revision_with_trailer(
+            &base_with_id(),
+            &[(8, "<< /Type /Annot >>")],
+            &format!("/ID [<41414141> {}]", FIRST),
+        );
+
+        let kinds: Vec<_> = check(&data).anomalies.iter().map(|a| a.kind).collect();
+        assert!(kinds.contains(&IdAnomalyKind::Irregular));
+        assert!(kinds.contains(&IdAnomalyKind::PermanentChanged));
+        assert!(kinds.contains(&IdAnomalyKind::ChangingNotUpdated));
+    }
+
+    #[test]
+    fn test_md5_of_info() {
+        let base = fixtures::simple_pdf();
+        let id = to_hex(&Md5::digest(b"Quarterly reportAlice"));
+        let data = fixtures::append_revision_with_trailer(
+            &base,
+            &[(7, "<< /Title (Quarterly report) /Author (Alice) >>")],
+            &format!("/Info 7 0 R /ID [<{}> <{}>]", id, id),
+        );
+
+        let report = check(&data);
+        assert_eq!(report.revisions[1].derivation, Some(IdDerivation::Md5OfInfo));
+    }
+}
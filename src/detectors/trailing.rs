@@ -0,0 +1,162 @@
+//! Data appended after the final %%EOF marker
+//! Created: 2026-10-16 08:49:49 UTC
+//! Author: kartik4091
+//!
+//! Readers stop at the last `%%EOF`, so anything written after it is never
+//! displayed yet travels with the file — a common place to stash payloads
+//! for a dropper that extracts them later. A payload can end in a marker
+//! of its own, so the document is taken to end at the last `%%EOF` whose
+//! `startxref` points at a cross-reference section that parses.
+
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::{
+    document::{
+        magic::{identify, MagicKind},
+        printable_preview,
+        revisions::split_revisions,
+        sha256_hex, shannon_entropy,
+        xref::read_section,
+    },
+    error::Result,
+};
+
+/// Entropy above which trailing data is treated as compressed or encrypted
+const HIGH_ENTROPY: f64 = 7.0;
+
+/// Bytes found after the last %%EOF
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TrailingData {
+    /// Offset of the first trailing byte
+    pub offset: usize,
+
+    /// Number of trailing bytes
+    pub size: usize,
+
+    /// Shannon entropy in bits per byte
+    pub entropy: f64,
+
+    /// MIME type identified from the leading bytes
+    pub mime: Option<String>,
+
+    /// Format family identified from the leading bytes
+    pub kind: Option<MagicKind>,
+
+    /// Human-readable format name
+    pub format: Option<String>,
+
+    /// Hex SHA-256 of the trailing bytes
+    pub sha256: String,
+
+    /// Printable excerpt
+    pub preview: String,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Where the blob was written, when dumping was requested
+    pub dumped_to: Option<String>,
+}
+
+/// Locates and characterizes data after the final `%%EOF` of a revision
+/// with a readable cross-reference section, or after the last marker of a
+/// file with none. Returns `None` when only whitespace follows.
+#[instrument(skip(data))]
+pub fn detect(data: &[u8]) -> Option<TrailingData> {
+    let offset = split_revisions(data)
+        .iter()
+        .rev()
+        .find(|revision| revision.startxref.map_or(false, |xref| read_section(data, xref).is_ok()))
+        .map(|revision| revision.end)
+        .or_else(|| data.windows(5).rposition(|w| w == b"%%EOF").map(|marker| marker + 5))?;
+    let tail = &data[offset..];
+    if tail.iter().all(|b| b.is_ascii_whitespace() || *b == 0) {
+        return None;
+    }
+
+    let entropy = shannon_entropy(tail);
+    let magic = identify(tail);
+    let severity = match magic.map(|m| m.kind) {
+        Some(MagicKind::Executable) | Some(MagicKind::Archive) => Severity::High,
+        Some(MagicKind::Script) | Some(MagicKind::Document) => Severity::Medium,
+        _ if entropy > HIGH_ENTROPY => Severity::Medium,
+        _ => Severity::Low,
+    };
+
+    debug!("{} bytes after final %%EOF", tail.len());
+    Some(TrailingData {
+        offset,
+        size: tail.len(),
+        entropy,
+        mime: magic.map(|m| m.mime.to_string()),
+        kind: magic.map(|m| m.kind),
+        format: magic.map(|m| m.description.to_string()),
+        sha256: sha256_hex(tail),
+        preview: printable_preview(tail, 80),
+        severity,
+        dumped_to: None,
+    })
+}
+
+/// Writes the trailing bytes to `path` and records the destination
+pub async fn dump(data: &[u8], trailing: &mut TrailingData, path: &Path) -> Result<()> {
+    tokio::fs::write(path, &data[trailing.offset..trailing.offset + trailing.size]).await?;
+    trailing.dumped_to = Some(path.to_string_lossy().into_owned());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_clean_file() {
+        let mut data = fixtures::simple_pdf();
+        data.extend_from_slice(b"\r\n\n");
+        assert!(detect(&data).is_none());
+    }
+
+    #[test]
+    fn test_appended_executable() {
+        let mut data = fixtures::simple_pdf();
+        let offset = data.len();
+        data.extend_from_slice(b"MZ\x90\x00\x03\x00\x00\x00This program cannot be run in DOS mode");
+
+        let trailing = detect(&data).unwrap();
+        assert!(trailing.offset <= offset);
+        assert_eq!(trailing.kind, Some(MagicKind::Executable));
+        assert_eq!(trailing.severity, Severity::High);
+    }
+
+    #[test]
+    fn test_payload_ending_in_its_own_marker() {
+        let mut data = fixtures::simple_pdf();
+        let offset = data.len();
+        data.extend_from_slice(b"MZ\x90\x00\x03\x00\x00\x00payload\nstartxref\n999999\n%%EOF\n");
+
+        let trailing = detect(&data).unwrap();
+        assert_eq!(trailing.offset, offset);
+        assert_eq!(trailing.kind, Some(MagicKind::Executable));
+    }
+
+    #[tokio::test]
+    async fn test_dump() {
+        let mut data = fixtures::simple_pdf();
+        data.extend_from_slice(b"hidden payload");
+        let mut trailing = detect(&data).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trailing.bin");
+        dump(&data, &mut trailing, &path).await.unwrap();
+
+        let written = std::fs::read(&path).unwrap();
+        assert!(written.ends_with(b"hidden payload"));
+        assert!(trailing.dumped_to.is_some());
+    }
+}
@@ -0,0 +1,625 @@
+//! URI extraction and lookalike-domain analysis
+//! Created: 2026-10-16 09:56:09 UTC
+//! Author: kartik4091
+//!
+//! Collects every URI a document can send the reader to — URI actions,
+//! remote file specifications, submit targets, URLs inside JavaScript and
+//! URLs printed in the page text — and de-obfuscates them: percent and hex
+//! string encoding is undone, numeric hosts are recognized and IDN labels
+//! are punycode-decoded. Hosts whose skeleton (confusable characters folded
+//! to ASCII) equals a popular domain while the host itself does not are
+//! reported as homoglyph lookalikes. The URL list doubles as an IOC artifact.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Dictionary, Document, Object, ObjectId, StringFormat};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{
+    qr::{self, QrCode},
+    Severity,
+};
+use crate::{
+    document::{decode_text, name_value, resolve, stream_data, walk_dictionaries},
+    integrations::reputation::ReputationVerdict,
+    text,
+};
+
+/// Second-level labels of frequently impersonated domains
+pub const POPULAR_DOMAINS: [&str; 24] = [
+    "adobe",
+    "amazon",
+    "apple",
+    "bankofamerica",
+    "chase",
+    "docusign",
+    "dropbox",
+    "facebook",
+    "github",
+    "google",
+    "icloud",
+    "instagram",
+    "linkedin",
+    "live",
+    "microsoft",
+    "netflix",
+    "office",
+    "onedrive",
+    "outlook",
+    "paypal",
+    "sharepoint",
+    "twitter",
+    "wellsfargo",
+    "yahoo",
+];
+
+/// Schemes that run code or read local files instead of opening a page
+const DANGEROUS_SCHEMES: [&str; 4] = ["javascript:", "vbscript:", "data:", "file:"];
+
+/// Where a URI was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub enum UriOrigin {
+    /// /URI action
+    Action,
+
+    /// URL file specification of a GoToR, Launch or ImportData action
+    RemoteFile,
+
+    /// SubmitForm target
+    SubmitForm,
+
+    /// URL literal inside JavaScript
+    Script,
+
+    /// URL printed in the page text
+    Text,
+
+    /// URL encoded in a QR code image
+    QrCode,
+}
+
+/// Obfuscation applied to a URI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub enum Obfuscation {
+    /// Percent-encoded characters that need no encoding
+    PercentEncoding,
+
+    /// Stored as a PDF hex string
+    HexString,
+
+    /// Host given as a bare, decimal, octal or hexadecimal IP address
+    NumericHost,
+
+    /// Credentials before the host (`https://trusted.example@evil.example/`)
+    UserInfo,
+
+    /// Internationalized (punycode) host
+    Punycode,
+
+    /// Host mixing Latin with Cyrillic or Greek letters
+    MixedScript,
+}
+
+/// An extracted URI
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExtractedUri {
+    /// URI as stored in the document
+    pub raw: String,
+
+    /// URI after percent decoding, with the host in Unicode
+    pub decoded: String,
+
+    /// Host, lowercase
+    pub host: Option<String>,
+
+    /// Host with IDN labels decoded
+    pub unicode_host: Option<String>,
+
+    /// Where the URI was found, with the containing object
+    pub sources: Vec<(UriOrigin, Option<ObjectId>)>,
+
+    /// Obfuscation techniques found
+    pub obfuscation: Vec<Obfuscation>,
+
+    /// Popular domain the host imitates
+    pub lookalike_of: Option<String>,
+
+    /// Verdicts of the configured reputation services
+    #[serde(default)]
+    pub reputation: Vec<ReputationVerdict>,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Every URI in the document
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct UriReport {
+    /// Extracted URIs, de-duplicated by their stored form
+    pub uris: Vec<ExtractedUri>,
+
+    /// QR codes decoded from images, including those without a URL
+    #[serde(default)]
+    pub qr_codes: Vec<QrCode>,
+}
+
+impl UriReport {
+    /// URIs worth reporting on their own
+    pub fn suspicious(&self) -> impl Iterator<Item = &ExtractedUri> {
+        self.uris.iter().filter(|uri| uri.severity > Severity::Info)
+    }
+
+    /// Decoded URIs, one per line, for use as indicators of compromise
+    pub fn ioc_list(&self) -> String {
+        self.uris.iter().map(|uri| format!("{}\n", uri.decoded)).collect()
+    }
+}
+
+/// Extracts and analyzes every URI in the document
+#[instrument(skip(doc))]
+pub fn extract(doc: &Document) -> UriReport {
+    let mut found: BTreeMap<String, (Vec<(UriOrigin, Option<ObjectId>)>, bool)> = BTreeMap::new();
+    let mut record = |raw: String, origin, id, hex| {
+        let raw = raw.trim().to_string();
+        if raw.is_empty() {
+            return;
+        }
+        let entry = found.entry(raw).or_default();
+        if !entry.0.contains(&(origin, id)) {
+            entry.0.push((origin, id));
+        }
+        entry.1 |= hex;
+    };
+
+    let urls = url_pattern();
+    for (&id, object) in &doc.objects {
+        walk_dictionaries(object, &mut |dict| {
+            for (origin, value) in targets(doc, dict) {
+                match value {
+                    Object::String(bytes, format) if origin != UriOrigin::Script => record(
+                        decode_text(bytes),
+                        origin,
+                        Some(id),
+                        *format == StringFormat::Hexadecimal,
+                    ),
+                    Object::String(..) | Object::Stream(_) if origin == UriOrigin::Script => {
+                        let script = match value {
+                            Object::Stream(stream) => stream_data(stream).unwrap_or_default(),
+                            _ => value.as_str().map(<[u8]>::to_vec).unwrap_or_default(),
+                        };
+                        for url in urls.find_iter(&decode_text(&script)) {
+                            record(trim_url(url.as_str()), origin, Some(id), false);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+    for page in text::extract(doc) {
+        for url in urls.find_iter(&page.text()) {
+            record(trim_url(url.as_str()), UriOrigin::Text, Some(page.page), false);
+        }
+    }
+    let qr_codes = qr::detect(doc);
+    for code in &qr_codes {
+        for url in urls.find_iter(&code.payload) {
+            record(trim_url(url.as_str()), UriOrigin::QrCode, Some(code.image), false);
+        }
+    }
+
+    let uris: Vec<ExtractedUri> = found
+        .into_iter()
+        .map(|(raw, (sources, hex))| analyze(raw, sources, hex))
+        .collect();
+    debug!("Extracted {} URIs", uris.len());
+    UriReport { uris, qr_codes }
+}
+
+/// URI-bearing values of a dictionary; JavaScript is scanned for URLs
+fn targets<'a>(doc: &'a Document, dict: &'a Dictionary) -> Vec<(UriOrigin, &'a Object)> {
+    let mut targets = Vec::new();
+    let origin = match name_value(dict, b"S") {
+        Some(b"URI") => {
+            targets.extend(dict.get(b"URI").ok().map(|uri| (UriOrigin::Action, resolve(doc, uri))));
+            return targets;
+        }
+        Some(b"JavaScript") => {
+            targets.extend(dict.get(b"JS").ok().map(|js| (UriOrigin::Script, resolve(doc, js))));
+            return targets;
+        }
+        Some(b"SubmitForm") => UriOrigin::SubmitForm,
+        Some(b"GoToR") | Some(b"Launch") | Some(b"ImportData") => UriOrigin::RemoteFile,
+        _ => return targets,
+    };
+
+    // Only URL file specifications (/FS /URL) or URL-looking strings count
+    match dict.get(b"F").map(|f| resolve(doc, f)) {
+        Ok(Object::Dictionary(spec)) if name_value(spec, b"FS") == Some(b"URL") => {
+            targets.extend(spec.get(b"F").ok().map(|f| (origin, resolve(doc, f))));
+        }
+        Ok(string)
+            if string
+                .as_str()
+                .map_or(false, |bytes| decode_text(bytes).contains("://")) =>
+        {
+            targets.push((origin, string));
+        }
+        _ => {}
+    }
+    targets
+}
+
+fn url_pattern() -> Regex {
+    Regex::new(r#"(?i)\b(?:(?:https?|ftp)://|www\.)[^\s<>"'()\[\]{}\\]+"#).expect("valid URL pattern")
+}
+
+/// Strips sentence punctuation following a URL in running text
+fn trim_url(url: &str) -> String {
+    url.trim_end_matches(['.', ',', ';', ':', '!', '?']).to_string()
+}
+
+fn analyze(raw: String, sources: Vec<(UriOrigin, Option<ObjectId>)>, hex: bool) -> ExtractedUri {
+    let mut obfuscation = Vec::new();
+    if hex {
+        obfuscation.push(Obfuscation::HexString);
+    }
+    if needless_percent_encoding(&raw) {
+        obfuscation.push(Obfuscation::PercentEncoding);
+    }
+    let decoded = percent_decode(&raw);
+
+    let authority = authority(&decoded);
+    let host = authority.map(|authority| {
+        if authority.contains('@') {
+            obfuscation.push(Obfuscation::UserInfo);
+        }
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        let host = host
+            .rsplit_once(':')
+            .filter(|(_, port)| port.parse::<u16>().is_ok())
+            .map_or(host, |(h, _)| h);
+        host.trim_end_matches('.').to_lowercase()
+    });
+
+    let unicode_host = host.as_deref().map(|host| {
+        host.split('.')
+            .map(|label| match label.strip_prefix("xn--").and_then(punycode_decode) {
+                Some(decoded) => decoded,
+                None => label.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    });
+    if let (Some(host), Some(unicode)) = (&host, &unicode_host) {
+        if host != unicode {
+            obfuscation.push(Obfuscation::Punycode);
+        }
+        if is_numeric_host(host) {
+            obfuscation.push(Obfuscation::NumericHost);
+        }
+        if unicode.split('.').any(mixes_scripts) {
+            obfuscation.push(Obfuscation::MixedScript);
+        }
+    }
+    let lookalike_of = unicode_host.as_deref().and_then(lookalike);
+
+    let decoded = match (&host, &unicode_host) {
+        (Some(host), Some(unicode)) if host != unicode => decoded.replacen(host.as_str(), unicode, 1),
+        _ => decoded,
+    };
+    let lower = decoded.to_ascii_lowercase();
+    let dangerous_scheme = DANGEROUS_SCHEMES.iter().any(|scheme| lower.starts_with(scheme));
+
+    // QR codes are a common way to keep phishing links away from scanners
+    let quishing = sources.iter().any(|(origin, _)| *origin == UriOrigin::QrCode);
+    // Many writers store every string in hex, so on its own that is only noted
+    let disguised = obfuscation
+        .iter()
+        .any(|o| !matches!(o, Obfuscation::Punycode | Obfuscation::HexString));
+    let severity = if lookalike_of.is_some() {
+        Severity::High
+    } else if dangerous_scheme || disguised {
+        Severity::Medium
+    } else if quishing || hex {
+        Severity::Low
+    } else {
+        Severity::Info
+    };
+    let description = match &lookalike_of {
+        Some(domain) => format!("URI {} imitates {}", decoded, domain),
+        None if dangerous_scheme => format!("URI uses a script or local scheme: {}", decoded),
+        None if obfuscation.is_empty() && quishing => format!("URI {} delivered by QR code", decoded),
+        None if obfuscation.is_empty() => format!("URI {}", decoded),
+        None => format!("Obfuscated URI {} ({:?})", decoded, obfuscation),
+    };
+
+    ExtractedUri {
+        raw,
+        decoded,
+        host,
+        unicode_host,
+        sources,
+        obfuscation,
+        lookalike_of,
+        reputation: Vec::new(),
+        severity,
+        description,
+    }
+}
+
+/// Authority part of a URL (`www.` links have an implied scheme)
+fn authority(url: &str) -> Option<&str> {
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest,
+        None if url.to_ascii_lowercase().starts_with("www.") => url,
+        None => return None,
+    };
+    let end = rest.find(['/', '?', '#', '\\']).unwrap_or(rest.len());
+    Some(&rest[..end]).filter(|authority| !authority.is_empty())
+}
+
+/// Whether a percent escape encodes a character that never needs it
+fn needless_percent_encoding(url: &str) -> bool {
+    percent_escapes(url.as_bytes()).any(|(_, byte)| byte.is_ascii_alphanumeric() || b"-._~".contains(&byte))
+}
+
+/// Offsets and values of `%XX` escapes
+fn percent_escapes(bytes: &[u8]) -> impl Iterator<Item = (usize, u8)> + '_ {
+    bytes.windows(3).enumerate().filter_map(|(i, w)| {
+        if w[0] != b'%' {
+            return None;
+        }
+        let hex = std::str::from_utf8(&w[1..]).ok()?;
+        u8::from_str_radix(hex, 16).ok().map(|byte| (i, byte))
+    })
+}
+
+/// Decodes `%XX` escapes, keeping malformed ones
+pub fn percent_decode(url: &str) -> String {
+    let bytes = url.as_bytes();
+    let escapes: BTreeMap<usize, u8> = percent_escapes(bytes).collect();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match escapes.get(&i) {
+            Some(&byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Bare IPv4, or a single decimal, octal or hexadecimal number
+fn is_numeric_host(host: &str) -> bool {
+    let number = |part: &str| {
+        let part = part.to_ascii_lowercase();
+        match part.strip_prefix("0x") {
+            Some(hex) => !hex.is_empty() && hex.bytes().all(|b| b.is_ascii_hexdigit()),
+            None => !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()),
+        }
+    };
+    host.starts_with('[') || (!host.is_empty() && host.split('.').all(number))
+}
+
+/// Whether a label mixes Latin letters with Cyrillic or Greek ones
+fn mixes_scripts(label: &str) -> bool {
+    let latin = label.chars().any(|c| c.is_ascii_alphabetic());
+    let other = label
+        .chars()
+        .any(|c| matches!(c, '\u{370}'..='\u{3FF}' | '\u{400}'..='\u{52F}'));
+    latin && other
+}
+
+/// Popular domain whose second-level label the host's skeleton equals
+fn lookalike(host: &str) -> Option<String> {
+    let labels: Vec<&str> = host.split('.').collect();
+    let [.., label, tld] = labels[..] else {
+        return None;
+    };
+    let folded = skeleton(label);
+    POPULAR_DOMAINS
+        .iter()
+        .find(|&&domain| folded == domain && label != domain)
+        .map(|domain| format!("{}.{}", domain, tld))
+}
+
+/// Folds confusable characters to the ASCII letters they resemble
+pub fn skeleton(label: &str) -> String {
+    let folded: String = label
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'а' | 'α' => 'a',
+            'с' | 'ϲ' => 'c',
+            'ԁ' => 'd',
+            'е' | 'ε' => 'e',
+            'ɡ' => 'g',
+            'һ' => 'h',
+            'і' | 'ι' | 'ı' => 'i',
+            '1' | 'ӏ' => 'l',
+            'ј' => 'j',
+            'κ' => 'k',
+            'о' | 'ο' | '0' => 'o',
+            'р' | 'ρ' => 'p',
+            'ѕ' => 's',
+            'τ' => 't',
+            'ν' => 'v',
+            'ԝ' => 'w',
+            'х' => 'x',
+            'у' => 'y',
+            other => other,
+        })
+        .collect();
+    folded.replace("rn", "m").replace("vv", "w")
+}
+
+/// Decodes an RFC 3492 punycode label (without the `xn--` prefix)
+pub fn punycode_decode(input: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const TMIN: u32 = 1;
+    const TMAX: u32 = 26;
+
+    let (basic, extended) = match input.rfind('-') {
+        Some(i) => (&input[..i], &input[i + 1..]),
+        None => ("", input),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+    let (mut n, mut i, mut bias) = (128u32, 0u32, 72u32);
+    let mut digits = extended.bytes().peekable();
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w = 1u32;
+        let mut k = BASE;
+        loop {
+            let digit = match digits.next()? {
+                byte @ b'a'..=b'z' => byte - b'a',
+                byte @ b'A'..=b'Z' => byte - b'A',
+                byte @ b'0'..=b'9' => byte - b'0' + 26,
+                _ => return None,
+            } as u32;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = if k <= bias {
+                TMIN
+            } else if k >= bias + TMAX {
+                TMAX
+            } else {
+                k - bias
+            };
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output.into_iter().collect())
+}
+
+fn adapt(delta: u32, points: u32, first: bool) -> u32 {
+    const BASE: u32 = 36;
+    let mut delta = if first { delta / 700 } else { delta / 2 };
+    delta += delta / points;
+    let mut k = 0;
+    while delta > ((BASE - 1) * 26) / 2 {
+        delta /= BASE - 1;
+        k += BASE;
+    }
+    k + (BASE * delta) / (delta + 38)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_punycode_and_skeleton() {
+        assert_eq!(punycode_decode("pypal-4ve").as_deref(), Some("pаypal"));
+        assert_eq!(punycode_decode("mnchen-3ya").as_deref(), Some("münchen"));
+        assert_eq!(skeleton("pаypa1"), "paypal");
+        assert_eq!(skeleton("rnicrosoft"), "microsoft");
+        assert_eq!(percent_decode("https://ex%61mple.com/%zz"), "https://example.com/%zz");
+    }
+
+    #[test]
+    fn test_homoglyph_and_obfuscation() {
+        let mut doc = fixtures::document();
+        let idn = doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::string_literal("https://xn--pypal-4ve.com/login"),
+        });
+        doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::String(b"http://trusted.example@3232235777/".to_vec(), StringFormat::Hexadecimal),
+        });
+        doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.launchURL('https://example.org/a', true);"),
+        });
+
+        let report = extract(&doc);
+        assert_eq!(report.uris.len(), 3);
+
+        let lookalike = report
+            .uris
+            .iter()
+            .find(|u| u.sources == vec![(UriOrigin::Action, Some(idn))])
+            .unwrap();
+        assert_eq!(lookalike.lookalike_of.as_deref(), Some("paypal.com"));
+        assert_eq!(lookalike.decoded, "https://pаypal.com/login");
+        assert_eq!(lookalike.severity, Severity::High);
+
+        let numeric = report.uris.iter().find(|u| u.raw.contains("3232235777")).unwrap();
+        assert_eq!(
+            numeric.obfuscation,
+            vec![Obfuscation::HexString, Obfuscation::UserInfo, Obfuscation::NumericHost]
+        );
+
+        let script = report
+            .uris
+            .iter()
+            .find(|u| u.sources[0].0 == UriOrigin::Script)
+            .unwrap();
+        assert_eq!(script.raw, "https://example.org/a");
+        assert_eq!(script.severity, Severity::Info);
+    }
+
+    #[test]
+    fn test_hex_string_alone_is_low() {
+        let mut doc = fixtures::document();
+        doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::String(b"https://example.com/".to_vec(), StringFormat::Hexadecimal),
+        });
+        doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::String(b"https://%65xample.org/".to_vec(), StringFormat::Hexadecimal),
+        });
+
+        let report = extract(&doc);
+        let severities: Vec<_> = report.uris.iter().map(|u| (u.decoded.as_str(), u.severity)).collect();
+        assert!(severities.contains(&("https://example.com/", Severity::Low)));
+        assert!(severities.contains(&("https://example.org/", Severity::Medium)));
+    }
+
+    #[test]
+    fn test_urls_in_page_text() {
+        let mut doc = fixtures::document();
+        let content = doc.add_object(lopdf::Stream::new(
+            dictionary! {},
+            b"BT /F1 10 Tf 72 700 Td (Visit www.example.com/pay.) Tj ET".to_vec(),
+        ));
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Contents", content);
+
+        let report = extract(&doc);
+        assert_eq!(report.uris.len(), 1);
+        assert_eq!(report.uris[0].raw, "www.example.com/pay");
+        assert_eq!(report.uris[0].host.as_deref(), Some("www.example.com"));
+        assert_eq!(report.ioc_list(), "www.example.com/pay\n");
+    }
+}
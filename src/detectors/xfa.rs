@@ -0,0 +1,457 @@
+//! XFA form extraction and inspection
+//! Created: 2026-10-16 09:33:08 UTC
+//! Author: kartik4091
+//!
+//! An XFA form is an XML document stored under /AcroForm /XFA, either as a
+//! single stream or as an array of named packets (template, datasets,
+//! config, ...). The template can carry JavaScript and FormCalc scripts
+//! bound to events such as `initialize` and `docReady`, or placed in
+//! `<calculate>` and `<validate>` elements, which run as soon as an
+//! XFA-capable viewer opens the file. Most documents also ship an
+//! AcroForm rendition of the same form for other viewers, so the two can
+//! be made to show different content depending on the application.
+
+use lopdf::{Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{decode_text, printable_preview, resolve, stream_data};
+
+/// Length of script excerpts
+const SCRIPT_PREVIEW: usize = 80;
+
+/// Indentation step of the pretty-printed XML
+const INDENT: &str = "  ";
+
+/// Events that fire without user interaction
+const AUTOMATIC_EVENTS: [&str; 7] =
+    ["initialize", "docready", "ready", "layoutready", "calculate", "validate", "preopen"];
+
+/// Elements a script can be bound through: an `<event>` naming its activity,
+/// or a `<calculate>` or `<validate>` that is its own activity
+const SCRIPT_CONTAINERS: [&str; 3] = ["event", "calculate", "validate"];
+
+/// One XFA packet
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct XfaPacket {
+    /// Packet name (`xdp` for a single-stream form)
+    pub name: String,
+
+    /// Stream holding the packet
+    pub id: Option<ObjectId>,
+
+    /// Decoded size in bytes
+    pub size: usize,
+
+    /// Pretty-printed XML
+    pub xml: String,
+}
+
+/// Scripting language of an XFA script
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ScriptLanguage {
+    /// `application/x-javascript`
+    JavaScript,
+
+    /// FormCalc, the XFA default when no content type is given
+    FormCalc,
+}
+
+/// A `<script>` element
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct XfaScript {
+    /// Packet containing the script
+    pub packet: String,
+
+    /// Language
+    pub language: ScriptLanguage,
+
+    /// Activity of the enclosing `<event>`, or `calculate` or `validate`
+    /// for scripts of those elements
+    pub activity: Option<String>,
+
+    /// Whether the event fires without user interaction
+    pub automatic: bool,
+
+    /// Script excerpt
+    pub preview: String,
+}
+
+/// Suspicious XFA pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum XfaIssue {
+    /// Embedded script
+    Script,
+
+    /// XFA and AcroForm fields describe the same form twice
+    DualRepresentation,
+}
+
+/// A flagged XFA pattern
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct XfaFinding {
+    /// Pattern found
+    pub issue: XfaIssue,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Extracted XFA form
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct XfaReport {
+    /// Packets in document order
+    pub packets: Vec<XfaPacket>,
+
+    /// Scripts found in the packets
+    pub scripts: Vec<XfaScript>,
+
+    /// Catalog /NeedsRendering: a dynamic form with no static rendition
+    pub dynamic: bool,
+
+    /// Number of AcroForm /Fields entries
+    pub acroform_fields: usize,
+
+    /// Flagged patterns
+    pub findings: Vec<XfaFinding>,
+}
+
+/// Extracts and inspects the XFA form, if the document has one
+#[instrument(skip(doc))]
+pub fn analyze(doc: &Document) -> Option<XfaReport> {
+    let catalog = doc
+        .trailer
+        .get(b"Root")
+        .ok()
+        .and_then(|root| resolve(doc, root).as_dict().ok())?;
+    let form = resolve(doc, catalog.get(b"AcroForm").ok()?).as_dict().ok()?;
+    let xfa = form.get(b"XFA").ok()?;
+
+    let mut raw = Vec::new();
+    match resolve(doc, xfa) {
+        Object::Array(items) => {
+            for pair in items.chunks(2) {
+                let [name, packet] = pair else {
+                    continue;
+                };
+                let name = match resolve(doc, name) {
+                    Object::String(bytes, _) => decode_text(bytes),
+                    _ => continue,
+                };
+                raw.push((name, packet.as_reference().ok(), packet_text(doc, packet)));
+            }
+        }
+        Object::Stream(_) => raw.push(("xdp".to_string(), xfa.as_reference().ok(), packet_text(doc, xfa))),
+        _ => return None,
+    }
+
+    let mut report = XfaReport {
+        dynamic: matches!(catalog.get(b"NeedsRendering"), Ok(Object::Boolean(true))),
+        acroform_fields: form
+            .get(b"Fields")
+            .map(|fields| resolve(doc, fields))
+            .and_then(Object::as_array)
+            .map_or(0, Vec::len),
+        ..Default::default()
+    };
+
+    for (name, id, text) in raw {
+        report.scripts.extend(scripts(&name, &text));
+        report.packets.push(XfaPacket {
+            size: text.len(),
+            xml: pretty_print(&text),
+            name,
+            id,
+        });
+    }
+
+    for script in &report.scripts {
+        let severity = match (script.language, script.automatic) {
+            (ScriptLanguage::JavaScript, true) => Severity::High,
+            (ScriptLanguage::JavaScript, false) | (ScriptLanguage::FormCalc, true) => Severity::Medium,
+            (ScriptLanguage::FormCalc, false) => Severity::Low,
+        };
+        report.findings.push(XfaFinding {
+            issue: XfaIssue::Script,
+            severity,
+            description: format!(
+                "XFA {:?} in {} packet{}",
+                script.language,
+                script.packet,
+                script
+                    .activity
+                    .as_ref()
+                    .map(|a| format!(" runs on {}", a))
+                    .unwrap_or_default()
+            ),
+        });
+    }
+
+    // Most XFA forms ship an AcroForm fallback; on its own that is normal
+    if report.acroform_fields > 0 {
+        report.findings.push(XfaFinding {
+            issue: XfaIssue::DualRepresentation,
+            severity: Severity::Low,
+            description: format!(
+                "XFA form alongside {} AcroForm fields; XFA and non-XFA viewers render different forms",
+                report.acroform_fields
+            ),
+        });
+    }
+
+    debug!(
+        "Extracted {} XFA packets, {} scripts",
+        report.packets.len(),
+        report.scripts.len()
+    );
+    Some(report)
+}
+
+/// Decoded text of a packet stream
+fn packet_text(doc: &Document, packet: &Object) -> String {
+    match resolve(doc, packet) {
+        Object::Stream(stream) => String::from_utf8_lossy(&stream_data(stream).unwrap_or_default()).into_owned(),
+        Object::String(bytes, _) => String::from_utf8_lossy(bytes).into_owned(),
+        _ => String::new(),
+    }
+}
+
+/// Finds `<script>` elements and the events they are bound to
+fn scripts(packet: &str, xml: &str) -> Vec<XfaScript> {
+    let lower = xml.to_ascii_lowercase();
+    let mut found = Vec::new();
+    let mut cursor = 0;
+
+    while let Some(start) = lower[cursor..].find("<script").map(|pos| cursor + pos) {
+        let Some(tag_end) = lower[start..].find('>').map(|pos| start + pos + 1) else {
+            break;
+        };
+        let tag = &lower[start..tag_end];
+        cursor = tag_end;
+        if !tag[7..].starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+            continue;
+        }
+
+        let body = if tag.ends_with("/>") {
+            ""
+        } else {
+            let end = lower[tag_end..].find("</script").map_or(xml.len(), |pos| tag_end + pos);
+            cursor = end;
+            &xml[tag_end..end]
+        };
+        let body = body
+            .trim()
+            .trim_start_matches("<![CDATA[")
+            .trim_end_matches("]]>")
+            .trim();
+        if body.is_empty() {
+            continue;
+        }
+
+        let language = match attribute(tag, "contenttype") {
+            Some(content_type) if content_type.contains("javascript") => ScriptLanguage::JavaScript,
+            _ => ScriptLanguage::FormCalc,
+        };
+
+        // The enclosing container is the last one opened and not yet closed
+        let container = SCRIPT_CONTAINERS
+            .iter()
+            .filter_map(|&element| {
+                let open = lower[..start].rfind(&format!("<{}", element))?;
+                (!lower[open..start].contains(&format!("</{}", element))).then_some((open, element))
+            })
+            .max();
+        let activity = match container {
+            Some((event, "event")) => {
+                let event_end = lower[event..].find('>').map_or(start, |pos| event + pos);
+                attribute(&xml[event..event_end], "activity")
+            }
+            Some((_, element)) => Some(element.to_string()),
+            None => None,
+        };
+
+        found.push(XfaScript {
+            packet: packet.to_string(),
+            language,
+            automatic: activity
+                .as_ref()
+                .map_or(false, |a| AUTOMATIC_EVENTS.contains(&a.to_ascii_lowercase().as_str())),
+            activity,
+            preview: printable_preview(body.as_bytes(), SCRIPT_PREVIEW),
+        });
+    }
+
+    found
+}
+
+/// Value of `name="..."` (or single-quoted) inside a tag
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut cursor = 0;
+    while let Some(pos) = tag[cursor..].find(name).map(|pos| cursor + pos) {
+        cursor = pos + name.len();
+        let preceded = tag[..pos].ends_with(|c: char| c.is_whitespace());
+        let rest = tag[cursor..].trim_start();
+        let Some(rest) = rest.strip_prefix('=').filter(|_| preceded) else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        return rest[1..].split(quote).next().map(str::to_string);
+    }
+    None
+}
+
+/// Re-indents XML one element per line; text-only elements stay on one line
+pub fn pretty_print(xml: &str) -> String {
+    let tokens = tokenize(xml);
+    let mut out = String::new();
+    let mut depth: usize = 0;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+
+        if token.starts_with("</") {
+            depth = depth.saturating_sub(1);
+            out.push_str(&INDENT.repeat(depth));
+            out.push_str(token);
+        } else if is_open_tag(token) {
+            // <a>text</a> on a single line
+            if let (Some(text), Some(close)) = (tokens.get(i + 1), tokens.get(i + 2)) {
+                if !text.starts_with('<') && close.starts_with("</") {
+                    out.push_str(&INDENT.repeat(depth));
+                    out.push_str(token);
+                    out.push_str(text);
+                    out.push_str(close);
+                    out.push('\n');
+                    i += 3;
+                    continue;
+                }
+            }
+            out.push_str(&INDENT.repeat(depth));
+            out.push_str(token);
+            depth += 1;
+        } else {
+            out.push_str(&INDENT.repeat(depth));
+            out.push_str(token);
+        }
+        out.push('\n');
+        i += 1;
+    }
+
+    out
+}
+
+/// Whether a token opens an element that has content
+fn is_open_tag(token: &str) -> bool {
+    token.starts_with('<') && !token.starts_with("<?") && !token.starts_with("<!") && !token.ends_with("/>")
+}
+
+/// Splits XML into tags, comments, CDATA sections and trimmed text runs
+fn tokenize(xml: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut cursor = 0;
+
+    while cursor < xml.len() {
+        let rest = &xml[cursor..];
+        if rest.starts_with('<') {
+            let terminator = if rest.starts_with("<!--") {
+                "-->"
+            } else if rest.starts_with("<![CDATA[") {
+                "]]>"
+            } else {
+                ">"
+            };
+            let len = rest.find(terminator).map_or(rest.len(), |pos| pos + terminator.len());
+            tokens.push(&rest[..len]);
+            cursor += len;
+        } else {
+            let len = rest.find('<').unwrap_or(rest.len());
+            let text = rest[..len].trim();
+            if !text.is_empty() {
+                tokens.push(text);
+            }
+            cursor += len;
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    const TEMPLATE: &str = r#"<template xmlns="http://www.xfa.org/schema/xfa-template/3.3/"><subform name="form1"><field name="a"><event activity="initialize"><script contentType="application/x-javascript">app.alert(1);</script></event></field><field name="b"><calculate><script>Sum(a)</script></calculate></field><field name="c"><validate><script>c > 0</script></validate><script>c</script></field></subform></template>"#;
+
+    #[test]
+    fn test_document_without_xfa() {
+        let mut doc = fixtures::document();
+        fixtures::with_form(&mut doc, dictionary! { "Fields" => Vec::<Object>::new() });
+        assert!(analyze(&doc).is_none());
+    }
+
+    #[test]
+    fn test_packets_and_scripts() {
+        let mut doc = fixtures::document();
+        let template = doc.add_object(Stream::new(dictionary! {}, TEMPLATE.as_bytes().to_vec()));
+        let datasets = doc.add_object(Stream::new(dictionary! {}, b"<xfa:datasets/>".to_vec()));
+        fixtures::with_form(
+            &mut doc,
+            dictionary! {
+                "Fields" => Vec::<Object>::new(),
+                "XFA" => vec![
+                    Object::string_literal("template"), template.into(),
+                    Object::string_literal("datasets"), datasets.into(),
+                ],
+            },
+        );
+
+        let report = analyze(&doc).unwrap();
+        let names: Vec<_> = report.packets.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["template", "datasets"]);
+        assert_eq!(report.packets[0].id, Some(template));
+
+        assert_eq!(report.scripts.len(), 4);
+        assert_eq!(report.scripts[0].language, ScriptLanguage::JavaScript);
+        assert_eq!(report.scripts[0].activity.as_deref(), Some("initialize"));
+        assert!(report.scripts[0].automatic);
+        assert_eq!(report.scripts[1].language, ScriptLanguage::FormCalc);
+        assert_eq!(report.scripts[1].activity.as_deref(), Some("calculate"));
+        assert_eq!(report.scripts[2].activity.as_deref(), Some("validate"));
+        assert!(report.scripts[1].automatic && report.scripts[2].automatic);
+        // A script outside any container only runs when called
+        assert_eq!(report.scripts[3].activity, None);
+        let severities: Vec<_> = report.findings.iter().map(|f| f.severity).collect();
+        assert_eq!(severities, vec![Severity::High, Severity::Medium, Severity::Medium, Severity::Low]);
+        assert!(report.findings.iter().all(|f| f.issue == XfaIssue::Script));
+    }
+
+    #[test]
+    fn test_dual_representation_and_pretty_print() {
+        let mut doc = fixtures::document();
+        let field = doc.add_object(dictionary! { "T" => Object::string_literal("a"), "FT" => "Tx" });
+        let xdp = doc.add_object(Stream::new(
+            dictionary! {},
+            b"<xdp:xdp><template><field>x</field></template></xdp:xdp>".to_vec(),
+        ));
+        fixtures::with_form(&mut doc, dictionary! { "Fields" => vec![field.into()], "XFA" => xdp });
+
+        let report = analyze(&doc).unwrap();
+        assert_eq!(report.packets[0].name, "xdp");
+        assert_eq!(
+            report.packets[0].xml,
+            "<xdp:xdp>\n  <template>\n    <field>x</field>\n  </template>\n</xdp:xdp>\n"
+        );
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].issue, XfaIssue::DualRepresentation);
+        assert_eq!(report.findings[0].severity, Severity::Low);
+    }
+}
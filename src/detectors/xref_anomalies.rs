@@ -0,0 +1,334 @@
+//! Cross-reference table anomaly detection
+//! Created: 2026-10-16 08:38:36 UTC
+//! Author: kartik4091
+
+use std::collections::{BTreeMap, HashSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::Severity;
+use crate::document::{
+    objects::{scan_objects, RawObject},
+    parser::{skip_whitespace, Parser},
+    xref::{read_sections, XrefKind, XrefSection, XrefState},
+};
+
+/// Kind of cross-reference anomaly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum XrefAnomalyKind {
+    /// The xref chain could not be read at all
+    Unreadable,
+
+    /// Free entries do not form a proper linked list headed by object 0
+    MalformedFreeList,
+
+    /// Several in-use entries share the same offset
+    OverlappingEntries,
+
+    /// An entry points inside the body of another object
+    OffsetIntoObject,
+
+    /// Subsections of one section cover the same object numbers
+    DuplicateSubsection,
+
+    /// An entry does not point at the matching `N G obj` header
+    PositionMismatch,
+
+    /// An entry points past the end of the file
+    OffsetBeyondEof,
+}
+
+/// A single cross-reference anomaly
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct XrefAnomaly {
+    /// Anomaly kind
+    pub kind: XrefAnomalyKind,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Offset of the xref section the anomaly was found in
+    pub section_offset: Option<u64>,
+
+    /// Affected object number
+    pub object: Option<u32>,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Checks every cross-reference section of the file
+#[instrument(skip(data))]
+pub fn detect(data: &[u8]) -> Vec<XrefAnomaly> {
+    let sections = match read_sections(data) {
+        Ok(sections) => sections,
+        Err(e) => {
+            return vec![XrefAnomaly {
+                kind: XrefAnomalyKind::Unreadable,
+                severity: Severity::High,
+                section_offset: None,
+                object: None,
+                description: e.to_string(),
+            }]
+        }
+    };
+
+    let objects = scan_objects(data);
+    let mut anomalies = Vec::new();
+    for section in &sections {
+        check_free_list(section, &mut anomalies);
+        check_subsections(section, &mut anomalies);
+        check_offsets(data, section, &objects, &mut anomalies);
+    }
+
+    debug!("Found {} xref anomalies", anomalies.len());
+    anomalies
+}
+
+fn check_free_list(section: &XrefSection, anomalies: &mut Vec<XrefAnomaly>) {
+    let free: BTreeMap<u32, (u64, u64)> = section
+        .entries
+        .iter()
+        .filter_map(|e| match e.state {
+            XrefState::Free { next, generation } => Some((e.number, (next, generation))),
+            _ => None,
+        })
+        .collect();
+
+    let mut report = |object: Option<u32>, description: String, severity: Severity| {
+        anomalies.push(XrefAnomaly {
+            kind: XrefAnomalyKind::MalformedFreeList,
+            severity,
+            section_offset: Some(section.offset),
+            object,
+            description,
+        });
+    };
+
+    // Only a section that starts at object 0 carries the list head
+    let covers_head = section.subsections.iter().any(|s| s.start == 0 && s.count > 0);
+    if covers_head && section.kind == XrefKind::Table {
+        match free.get(&0) {
+            None => report(Some(0), "Object 0 is not a free entry".into(), Severity::Medium),
+            Some((_, generation)) if *generation != 65535 => report(
+                Some(0),
+                format!("Free list head has generation {} instead of 65535", generation),
+                Severity::Low,
+            ),
+            _ => {}
+        }
+    }
+
+    let Some(&(mut next, _)) = free.get(&0) else { return };
+    let mut seen = HashSet::new();
+    while next != 0 {
+        let number = next as u32;
+        if !seen.insert(number) {
+            report(Some(number), "Free list contains a cycle".into(), Severity::High);
+            return;
+        }
+        match free.get(&number) {
+            Some(&(following, _)) => next = following,
+            None => {
+                report(
+                    Some(number),
+                    format!("Free list points at object {} which is not free in this section", number),
+                    Severity::Medium,
+                );
+                return;
+            }
+        }
+    }
+
+    let unlinked = free.keys().filter(|&&n| n != 0 && !seen.contains(&n)).count();
+    if unlinked > 0 && section.kind == XrefKind::Table {
+        report(
+            None,
+            format!("{} free entries are not linked from the free list head", unlinked),
+            Severity::Low,
+        );
+    }
+}
+
+fn check_subsections(section: &XrefSection, anomalies: &mut Vec<XrefAnomaly>) {
+    let mut covered: Vec<(u32, u32)> = Vec::new();
+    for subsection in &section.subsections {
+        let range = (subsection.start, subsection.start.saturating_add(subsection.count));
+        if let Some(existing) = covered.iter().find(|(s, e)| range.0 < *e && *s < range.1) {
+            anomalies.push(XrefAnomaly {
+                kind: XrefAnomalyKind::DuplicateSubsection,
+                severity: Severity::Medium,
+                section_offset: Some(section.offset),
+                object: Some(range.0.max(existing.0)),
+                description: format!(
+                    "Subsection {}..{} overlaps subsection {}..{}",
+                    range.0, range.1, existing.0, existing.1
+                ),
+            });
+        }
+        covered.push(range);
+    }
+}
+
+fn check_offsets(
+    data: &[u8],
+    section: &XrefSection,
+    objects: &[RawObject],
+    anomalies: &mut Vec<XrefAnomaly>,
+) {
+    let mut by_offset: BTreeMap<u64, Vec<u32>> = BTreeMap::new();
+
+    for entry in &section.entries {
+        let XrefState::InUse { offset, generation } = entry.state else { continue };
+        // Object 0 and zero offsets are written by some producers for unused slots
+        if offset == 0 {
+            continue;
+        }
+        by_offset.entry(offset).or_default().push(entry.number);
+
+        let mut push = |kind, severity, description| {
+            anomalies.push(XrefAnomaly {
+                kind,
+                severity,
+                section_offset: Some(section.offset),
+                object: Some(entry.number),
+                description,
+            });
+        };
+
+        if offset as usize >= data.len() {
+            push(
+                XrefAnomalyKind::OffsetBeyondEof,
+                Severity::High,
+                format!("Object {} offset {} is beyond end of file", entry.number, offset),
+            );
+            continue;
+        }
+
+        let pos = skip_whitespace(data, offset as usize);
+        if let Some(inside) = objects.iter().find(|o| o.offset < pos && pos < o.end) {
+            push(
+                XrefAnomalyKind::OffsetIntoObject,
+                Severity::High,
+                format!(
+                    "Object {} offset {} points inside object {} {}",
+                    entry.number, offset, inside.id.0, inside.id.1
+                ),
+            );
+            continue;
+        }
+
+        if header_at(data, pos) != Some((entry.number, generation)) {
+            let actual = objects
+                .iter()
+                .filter(|o| o.id == (entry.number, generation as u16))
+                .map(|o| o.offset.to_string())
+                .collect::<Vec<_>>();
+            let hint = if actual.is_empty() {
+                String::from("no matching object header exists")
+            } else {
+                format!("object found at {}", actual.join(", "))
+            };
+            push(
+                XrefAnomalyKind::PositionMismatch,
+                Severity::Medium,
+                format!("Object {} offset {} does not hold its header; {}", entry.number, offset, hint),
+            );
+        }
+    }
+
+    for (offset, numbers) in by_offset.into_iter().filter(|(_, n)| n.len() > 1) {
+        anomalies.push(XrefAnomaly {
+            kind: XrefAnomalyKind::OverlappingEntries,
+            severity: Severity::High,
+            section_offset: Some(section.offset),
+            object: numbers.first().copied(),
+            description: format!("Objects {:?} share offset {}", numbers, offset),
+        });
+    }
+}
+
+fn header_at(data: &[u8], pos: usize) -> Option<(u32, u64)> {
+    let mut parser = Parser::new(data, pos);
+    let number = parser.parse_unsigned()?;
+    let generation = parser.parse_unsigned()?;
+    parser.eat_keyword(b"obj").then_some((number as u32, generation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    fn kinds(anomalies: &[XrefAnomaly]) -> Vec<XrefAnomalyKind> {
+        anomalies.iter().map(|a| a.kind).collect()
+    }
+
+    #[test]
+    fn test_clean_document() {
+        let data = fixtures::simple_pdf();
+        let anomalies = detect(&data);
+        assert!(
+            anomalies.iter().all(|a| a.severity <= Severity::Low),
+            "unexpected anomalies: {:?}",
+            anomalies
+        );
+    }
+
+    /// Two objects, with entry 2 sharing object 1's offset and entry 3
+    /// pointing into the middle of object 2
+    fn crafted_pdf() -> Vec<u8> {
+        let mut data = b"%PDF-1.4\n".to_vec();
+        let first = data.len();
+        data.extend_from_slice(b"1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n");
+        let second = data.len();
+        data.extend_from_slice(b"2 0 obj\n<< /Type /Pages /Kids [] /Count 0 >>\nendobj\n");
+        let xref = data.len();
+        data.extend_from_slice(
+            format!(
+                "xref\n0 4\n0000000000 65535 f \n{:010} 00000 n \n{:010} 00000 n \n{:010} 00000 n \n\
+                 trailer\n<< /Size 4 /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                first,
+                first,
+                second + 12,
+                xref
+            )
+            .as_bytes(),
+        );
+        data
+    }
+
+    #[test]
+    fn test_crafted_offsets() {
+        let anomalies = detect(&crafted_pdf());
+        let kinds = kinds(&anomalies);
+
+        assert!(kinds.contains(&XrefAnomalyKind::OverlappingEntries));
+        assert!(kinds.contains(&XrefAnomalyKind::PositionMismatch));
+        assert!(kinds.contains(&XrefAnomalyKind::OffsetIntoObject));
+    }
+
+    #[test]
+    fn test_duplicate_subsection() {
+        let data = fixtures::simple_pdf();
+        let xref = data.windows(4).rposition(|w| w == b"xref").unwrap();
+        let mut patched = data[..xref].to_vec();
+        let table = String::from_utf8_lossy(&data[xref..]).replacen(
+            "trailer",
+            "0 1\n0000000000 65535 f \ntrailer",
+            1,
+        );
+        patched.extend_from_slice(table.as_bytes());
+
+        let kinds = kinds(&detect(&patched));
+        assert!(kinds.contains(&XrefAnomalyKind::DuplicateSubsection));
+    }
+
+    #[test]
+    fn test_missing_startxref_is_unreadable() {
+        let anomalies = detect(b"%PDF-1.4\n1 0 obj\n<<>>\nendobj\n");
+        assert_eq!(kinds(&anomalies), vec![XrefAnomalyKind::Unreadable]);
+    }
+}
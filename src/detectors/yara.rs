@@ -0,0 +1,272 @@
+//! YARA rule scanning over the raw file and decoded streams
+//! Created: 2026-10-16 10:04:36 UTC
+//! Author: kartik4091
+//!
+//! Runs user-supplied YARA rule sets (compiled with yara-x) against the
+//! file as stored and against every stream after decoding, so rules
+//! written for deflated JavaScript or shellcode match no matter how the
+//! stream is filtered. Matches in the raw file are attributed to the
+//! indirect object whose bytes contain them.
+//!
+//! The engine is behind the `yara` feature; without it the report types
+//! exist but no rules can be loaded.
+
+use lopdf::ObjectId;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::Severity;
+
+/// Rule file extensions loaded from a directory
+pub const RULE_EXTENSIONS: [&str; 2] = ["yar", "yara"];
+
+/// What a rule matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub enum YaraTarget {
+    /// The file as stored
+    File,
+
+    /// Decoded data of a stream object
+    DecodedStream,
+}
+
+/// A matching rule
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct YaraMatch {
+    /// Rule identifier
+    pub rule: String,
+
+    /// Rule namespace (the rule file name)
+    pub namespace: String,
+
+    /// Rule tags
+    pub tags: Vec<String>,
+
+    /// Data the rule matched against
+    pub target: YaraTarget,
+
+    /// Owning object: the stream for decoded data, the enclosing object
+    /// for raw file matches (if any)
+    pub object: Option<ObjectId>,
+
+    /// Pattern match offsets within the target (file offsets for raw matches)
+    pub offsets: Vec<usize>,
+
+    /// Severity from the rule's `severity` meta, medium by default
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+#[cfg(feature = "yara")]
+pub use engine::{scan, YaraRules};
+
+#[cfg(feature = "yara")]
+mod engine {
+    use std::{
+        fmt,
+        path::{Path, PathBuf},
+        str::FromStr,
+    };
+
+    use lopdf::{Document, Object, ObjectId};
+    use tracing::{debug, instrument, warn};
+
+    use super::{Severity, YaraMatch, YaraTarget, RULE_EXTENSIONS};
+    use crate::{
+        document::{objects::scan_objects, stream_data},
+        error::{Error, Result},
+    };
+
+    /// Compiled rule set
+    pub struct YaraRules {
+        rules: yara_x::Rules,
+
+        /// Rule files the set was compiled from
+        pub sources: Vec<PathBuf>,
+    }
+
+    impl fmt::Debug for YaraRules {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("YaraRules").field("sources", &self.sources).finish()
+        }
+    }
+
+    impl YaraRules {
+        /// Compiles rule files, and the `.yar`/`.yara` files of directories,
+        /// each into a namespace named after the file
+        pub fn load(paths: &[PathBuf]) -> Result<Self> {
+            let mut sources = Vec::new();
+            for path in paths {
+                if path.is_dir() {
+                    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+                        .filter_map(|entry| entry.ok().map(|e| e.path()))
+                        .filter(|p| {
+                            p.extension()
+                                .and_then(|e| e.to_str())
+                                .map_or(false, |e| RULE_EXTENSIONS.contains(&e))
+                        })
+                        .collect();
+                    files.sort();
+                    sources.extend(files);
+                } else {
+                    sources.push(path.clone());
+                }
+            }
+
+            let mut compiler = yara_x::Compiler::new();
+            for source in &sources {
+                let text = std::fs::read_to_string(source)?;
+                compiler.new_namespace(&namespace(source));
+                compiler
+                    .add_source(text.as_str())
+                    .map_err(|e| Error::Analysis(format!("YARA rule {}: {}", source.display(), e)))?;
+            }
+            Ok(Self {
+                rules: compiler.build(),
+                sources,
+            })
+        }
+    }
+
+    fn namespace(path: &Path) -> String {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "default".to_string())
+    }
+
+    /// Scans the raw file and every decodable stream
+    #[instrument(skip(rules, data, doc))]
+    pub fn scan(rules: &YaraRules, data: &[u8], doc: Option<&Document>) -> Result<Vec<YaraMatch>> {
+        let mut scanner = yara_x::Scanner::new(&rules.rules);
+        let objects = scan_objects(data);
+        let mut matches = Vec::new();
+
+        let results = scanner
+            .scan(data)
+            .map_err(|e| Error::Analysis(format!("YARA scan failed: {}", e)))?;
+        for rule in results.matching_rules() {
+            let offsets = offsets(&rule);
+            // Attribute to the object holding the first match
+            let object = offsets
+                .first()
+                .and_then(|&offset| objects.iter().find(|o| o.offset <= offset && offset < o.end))
+                .map(|o| o.id);
+            matches.push(to_match(&rule, YaraTarget::File, object, offsets));
+        }
+
+        for (&id, object) in doc.map(|doc| &doc.objects).into_iter().flatten() {
+            let Object::Stream(stream) = object else {
+                continue;
+            };
+            // Unfiltered streams were covered by the raw scan
+            if stream.dict.get(b"Filter").is_err() {
+                continue;
+            }
+            let decoded = match stream_data(stream) {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    debug!("Stream {} {} not scanned: {}", id.0, id.1, e);
+                    continue;
+                }
+            };
+            match scanner.scan(&decoded) {
+                Ok(results) => {
+                    for rule in results.matching_rules() {
+                        let offsets = offsets(&rule);
+                        matches.push(to_match(&rule, YaraTarget::DecodedStream, Some(id), offsets));
+                    }
+                }
+                Err(e) => warn!("YARA scan of stream {} {} failed: {}", id.0, id.1, e),
+            }
+        }
+
+        debug!("{} YARA matches", matches.len());
+        Ok(matches)
+    }
+
+    fn offsets(rule: &yara_x::Rule) -> Vec<usize> {
+        let mut offsets: Vec<usize> = rule
+            .patterns()
+            .flat_map(|pattern| pattern.matches().map(|m| m.range().start))
+            .collect();
+        offsets.sort_unstable();
+        offsets.dedup();
+        offsets
+    }
+
+    fn to_match(rule: &yara_x::Rule, target: YaraTarget, object: Option<ObjectId>, offsets: Vec<usize>) -> YaraMatch {
+        let severity = rule
+            .metadata()
+            .find(|(key, _)| *key == "severity")
+            .and_then(|(_, value)| match value {
+                yara_x::MetaValue::String(s) => Severity::from_str(s).ok(),
+                _ => None,
+            })
+            .unwrap_or(Severity::Medium);
+        let location = match (target, object) {
+            (YaraTarget::DecodedStream, Some(id)) => format!("decoded stream {} {}", id.0, id.1),
+            (_, Some(id)) => format!("object {} {}", id.0, id.1),
+            (_, None) => "file".to_string(),
+        };
+        YaraMatch {
+            rule: rule.identifier().to_string(),
+            namespace: rule.namespace().to_string(),
+            tags: rule.tags().map(|tag| tag.identifier().to_string()).collect(),
+            target,
+            object,
+            description: format!(
+                "YARA rule {}:{} matched {}",
+                rule.namespace(),
+                rule.identifier(),
+                location
+            ),
+            offsets,
+            severity,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::document::fixtures;
+        use std::io::Write;
+
+        fn rules(source: &str) -> YaraRules {
+            let mut file = tempfile::Builder::new().suffix(".yar").tempfile().unwrap();
+            file.write_all(source.as_bytes()).unwrap();
+            YaraRules::load(&[file.path().to_path_buf()]).unwrap()
+        }
+
+        #[test]
+        fn test_raw_match_is_attributed_to_object() {
+            let rules =
+                rules(r#"rule hello : demo { meta: severity = "high" strings: $a = "Hello World" condition: $a }"#);
+            let data = fixtures::simple_pdf();
+            let doc = Document::load_mem(&data).unwrap();
+
+            let matches = scan(&rules, &data, Some(&doc)).unwrap();
+            assert_eq!(matches.len(), 1);
+            assert_eq!(matches[0].target, YaraTarget::File);
+            assert_eq!(matches[0].object, Some((4, 0)));
+            assert_eq!(matches[0].tags, vec!["demo".to_string()]);
+            assert_eq!(matches[0].severity, Severity::High);
+        }
+
+        #[test]
+        fn test_decoded_stream_match() {
+            let rules = rules(r#"rule launch { strings: $a = "app.launchURL" condition: $a }"#);
+            let mut doc = fixtures::document();
+            let mut stream = lopdf::Stream::new(lopdf::Dictionary::new(), b"app.launchURL('x')".to_vec());
+            stream.compress().unwrap();
+            let id = doc.add_object(stream);
+            let data = fixtures::save(doc);
+
+            let matches = scan(&rules, &data, Some(&Document::load_mem(&data).unwrap())).unwrap();
+            assert_eq!(matches.len(), 1);
+            assert_eq!(matches[0].target, YaraTarget::DecodedStream);
+            assert_eq!(matches[0].object, Some(id));
+        }
+    }
+}
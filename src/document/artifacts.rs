@@ -0,0 +1,465 @@
+//! Artifact extraction into a structured directory
+//! Created: 2026-10-16 10:51:29 UTC
+//! Author: kartik4091
+//!
+//! Pulls every artifact class an analyst usually wants out of a document
+//! in one pass: JavaScript, images, attachments, embedded font programs,
+//! XMP packets and ICC profiles. Each class gets its own subdirectory and a
+//! `manifest.json` at the top records where every file came from and its
+//! SHA-256. Images keep their own encoding (JPEG, JPEG 2000, JBIG2) when
+//! they have one; everything else is written decoded.
+
+use std::{collections::BTreeSet, fmt, path::Path, str::FromStr};
+
+use lopdf::{Document, Object, ObjectId, Stream};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+
+use super::{attachments, javascript, name_value, sha256_hex, stream_data, taxonomy::ObjectType, walk_dictionaries};
+use crate::error::Result;
+
+/// Name of the manifest written at the top of the output directory
+pub const MANIFEST: &str = "manifest.json";
+
+/// Longest file name kept from an attachment
+const MAX_NAME: usize = 100;
+
+/// Kind of artifact, each extracted into its own subdirectory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ArtifactClass {
+    /// Scripts of `/JS` entries
+    JavaScript,
+
+    /// Image XObjects
+    Images,
+
+    /// Embedded files
+    Attachments,
+
+    /// Embedded font programs
+    Fonts,
+
+    /// XMP metadata packets
+    Xmp,
+
+    /// ICC colour profiles
+    Icc,
+}
+
+impl ArtifactClass {
+    /// Every class, in extraction order
+    pub const ALL: [Self; 6] = [
+        Self::JavaScript,
+        Self::Images,
+        Self::Attachments,
+        Self::Fonts,
+        Self::Xmp,
+        Self::Icc,
+    ];
+
+    /// Subdirectory the class is written to
+    pub fn dir(&self) -> &'static str {
+        match self {
+            Self::JavaScript => "js",
+            Self::Images => "images",
+            Self::Attachments => "attachments",
+            Self::Fonts => "fonts",
+            Self::Xmp => "xmp",
+            Self::Icc => "icc",
+        }
+    }
+}
+
+impl fmt::Display for ArtifactClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.dir())
+    }
+}
+
+impl FromStr for ArtifactClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "js" | "javascript" => Ok(Self::JavaScript),
+            "images" | "image" => Ok(Self::Images),
+            "attachments" | "attachment" | "files" => Ok(Self::Attachments),
+            "fonts" | "font" => Ok(Self::Fonts),
+            "xmp" | "metadata" => Ok(Self::Xmp),
+            "icc" | "profiles" => Ok(Self::Icc),
+            other => Err(format!("Unknown artifact class: {}", other)),
+        }
+    }
+}
+
+/// One extracted file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Artifact class
+    pub class: ArtifactClass,
+
+    /// Object holding the artifact
+    pub id: ObjectId,
+
+    /// Path relative to the output directory
+    pub file: String,
+
+    /// Name the document gives it (attachment file name, font name)
+    pub name: Option<String>,
+
+    /// Bytes written
+    pub size: usize,
+
+    /// SHA-256 of the bytes written
+    pub sha256: String,
+}
+
+/// Everything written by an extraction
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    /// Classes that were extracted
+    pub classes: Vec<ArtifactClass>,
+
+    /// Extracted files, grouped by class
+    pub artifacts: Vec<Artifact>,
+}
+
+impl ArtifactManifest {
+    /// Number of artifacts of a class
+    pub fn count(&self, class: ArtifactClass) -> usize {
+        self.artifacts.iter().filter(|a| a.class == class).count()
+    }
+}
+
+/// Artifact found in the document, before it is written
+struct Found {
+    id: ObjectId,
+    name: Option<String>,
+    stem: String,
+    extension: &'static str,
+    data: Vec<u8>,
+}
+
+/// Extracts the given classes (all of them when empty) into `dir` and
+/// writes the manifest beside them
+#[instrument(skip(doc))]
+pub async fn extract(doc: &Document, dir: &Path, classes: &[ArtifactClass]) -> Result<ArtifactManifest> {
+    let classes: BTreeSet<ArtifactClass> = if classes.is_empty() {
+        ArtifactClass::ALL.into_iter().collect()
+    } else {
+        classes.iter().copied().collect()
+    };
+    tokio::fs::create_dir_all(dir).await?;
+
+    let mut manifest = ArtifactManifest {
+        classes: classes.iter().copied().collect(),
+        ..Default::default()
+    };
+    for &class in &classes {
+        let found = find(doc, class);
+        if found.is_empty() {
+            continue;
+        }
+        tokio::fs::create_dir_all(dir.join(class.dir())).await?;
+        for artifact in found {
+            let file = format!("{}/{}.{}", class.dir(), artifact.stem, artifact.extension);
+            tokio::fs::write(dir.join(&file), &artifact.data).await?;
+            manifest.artifacts.push(Artifact {
+                class,
+                id: artifact.id,
+                file,
+                name: artifact.name,
+                size: artifact.data.len(),
+                sha256: sha256_hex(&artifact.data),
+            });
+        }
+    }
+    tokio::fs::write(dir.join(MANIFEST), serde_json::to_vec_pretty(&manifest)?).await?;
+    debug!("Extracted {} artifacts to {}", manifest.artifacts.len(), dir.display());
+    Ok(manifest)
+}
+
+fn find(doc: &Document, class: ArtifactClass) -> Vec<Found> {
+    let stem = |id: ObjectId| format!("{}_{}", id.0, id.1);
+    match class {
+        ArtifactClass::JavaScript => {
+            let mut found: Vec<Found> = Vec::new();
+            for (id, script) in javascript(doc) {
+                // An object can hold several scripts (nested actions)
+                let nth = found.iter().filter(|f| f.id == id).count();
+                found.push(Found {
+                    id,
+                    name: None,
+                    stem: match nth {
+                        0 => stem(id),
+                        nth => format!("{}_{}", stem(id), nth),
+                    },
+                    extension: "js",
+                    data: script.into_bytes(),
+                });
+            }
+            found
+        }
+        ArtifactClass::Images => streams(
+            doc,
+            |object| matches!(ObjectType::of(object), ObjectType::XObject { subtype: Some(s) } if s == "Image"),
+        )
+        .filter_map(|(id, stream)| {
+            let (data, extension) = image(stream)?;
+            Some(Found {
+                id,
+                name: None,
+                stem: stem(id),
+                extension,
+                data,
+            })
+        })
+        .collect(),
+        ArtifactClass::Attachments => attachments::extract(doc)
+            .into_iter()
+            .map(|file| Found {
+                id: file.id,
+                stem: format!("{}_{}", stem(file.id), safe_name(&file.name)),
+                name: Some(file.name),
+                extension: "bin",
+                data: file.data,
+            })
+            .collect(),
+        ArtifactClass::Fonts => fonts(doc)
+            .into_iter()
+            .filter_map(|(id, name, extension)| {
+                let stream = doc.get_object(id).ok()?.as_stream().ok()?;
+                Some(Found {
+                    id,
+                    name,
+                    stem: stem(id),
+                    extension,
+                    data: decoded(id, stream)?,
+                })
+            })
+            .collect(),
+        ArtifactClass::Xmp => streams(doc, |object| ObjectType::of(object) == ObjectType::Metadata)
+            .filter_map(|(id, stream)| {
+                Some(Found {
+                    id,
+                    name: None,
+                    stem: stem(id),
+                    extension: "xml",
+                    data: decoded(id, stream)?,
+                })
+            })
+            .collect(),
+        ArtifactClass::Icc => icc_profiles(doc)
+            .into_iter()
+            .filter_map(|id| {
+                let stream = doc.get_object(id).ok()?.as_stream().ok()?;
+                Some(Found {
+                    id,
+                    name: None,
+                    stem: stem(id),
+                    extension: "icc",
+                    data: decoded(id, stream)?,
+                })
+            })
+            .collect(),
+    }
+}
+
+fn streams<'a>(
+    doc: &'a Document,
+    wanted: impl Fn(&Object) -> bool + 'a,
+) -> impl Iterator<Item = (ObjectId, &'a Stream)> + 'a {
+    doc.objects.iter().filter_map(move |(&id, object)| match object {
+        Object::Stream(stream) if wanted(object) => Some((id, stream)),
+        _ => None,
+    })
+}
+
+fn decoded(id: ObjectId, stream: &Stream) -> Option<Vec<u8>> {
+    stream_data(stream)
+        .map_err(|e| warn!("Failed to decode stream {} {}: {}", id.0, id.1, e))
+        .ok()
+}
+
+/// Image bytes in their own format when the last filter is an image codec,
+/// decoded samples otherwise
+fn image(stream: &Stream) -> Option<(Vec<u8>, &'static str)> {
+    let filters = stream.filters().unwrap_or_default();
+    let codec = match filters.last().map(String::as_str) {
+        Some("DCTDecode") => Some("jpg"),
+        Some("JPXDecode") => Some("jp2"),
+        Some("JBIG2Decode") => Some("jb2"),
+        Some("CCITTFaxDecode") => Some("g4"),
+        _ => None,
+    };
+    match codec {
+        // The codec is the only filter, so the stored bytes are the image file
+        Some(extension) if filters.len() == 1 => Some((stream.content.clone(), extension)),
+        // Undo the filters applied on top of the codec and keep its output
+        Some(extension) => {
+            let outer = filters.len() - 1;
+            let mut wrapped = stream.clone();
+            let names = filters[..outer].iter().map(|f| Object::Name(f.as_bytes().to_vec()));
+            wrapped.dict.set("Filter", Object::Array(names.collect()));
+            match stream.dict.get(b"DecodeParms") {
+                Ok(Object::Array(params)) => {
+                    let params = params.iter().take(outer).cloned().collect();
+                    wrapped.dict.set("DecodeParms", Object::Array(params));
+                }
+                _ => {
+                    wrapped.dict.remove(b"DecodeParms");
+                }
+            }
+            Some((stream_data(&wrapped).ok()?, extension))
+        }
+        None => Some((stream_data(stream).ok()?, "raw")),
+    }
+}
+
+/// Font program streams with the font name and the extension of their format
+fn fonts(doc: &Document) -> Vec<(ObjectId, Option<String>, &'static str)> {
+    let mut found = Vec::new();
+    for object in doc.objects.values() {
+        walk_dictionaries(object, &mut |dict| {
+            if name_value(dict, b"Type") != Some(b"FontDescriptor") {
+                return;
+            }
+            let name = name_value(dict, b"FontName").map(|n| String::from_utf8_lossy(n).into_owned());
+            for key in [&b"FontFile"[..], b"FontFile2", b"FontFile3"] {
+                let Ok(Object::Reference(id)) = dict.get(key) else {
+                    continue;
+                };
+                let extension = match key {
+                    b"FontFile" => "pfb",
+                    b"FontFile2" => "ttf",
+                    _ => match doc.get_object(*id).ok().and_then(|o| o.as_stream().ok()) {
+                        Some(stream) if name_value(&stream.dict, b"Subtype") == Some(b"OpenType") => "otf",
+                        _ => "cff",
+                    },
+                };
+                if !found.iter().any(|(other, _, _)| other == id) {
+                    found.push((*id, name.clone(), extension));
+                }
+            }
+        });
+    }
+    found
+}
+
+/// Streams named by `[/ICCBased N G R]` colour spaces
+fn icc_profiles(doc: &Document) -> BTreeSet<ObjectId> {
+    fn visit(object: &Object, found: &mut BTreeSet<ObjectId>) {
+        match object {
+            Object::Array(items) => {
+                if let [Object::Name(name), Object::Reference(id), ..] = &items[..] {
+                    if name == b"ICCBased" {
+                        found.insert(*id);
+                    }
+                }
+                items.iter().for_each(|item| visit(item, found));
+            }
+            Object::Dictionary(dict) => dict.iter().for_each(|(_, value)| visit(value, found)),
+            Object::Stream(stream) => stream.dict.iter().for_each(|(_, value)| visit(value, found)),
+            _ => {}
+        }
+    }
+    let mut found = BTreeSet::new();
+    doc.objects.values().for_each(|object| visit(object, &mut found));
+    found
+}
+
+/// A document-supplied file name reduced to characters safe on any file
+/// system
+fn safe_name(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "._-".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_NAME)
+        .collect();
+    safe.trim_start_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    fn document() -> Document {
+        let mut doc = fixtures::document();
+        doc.add_object(
+            dictionary! { "Type" => "Action", "S" => "JavaScript", "JS" => Object::string_literal("app.alert(1)") },
+        );
+        let jpeg = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Image", "Filter" => "DCTDecode" },
+            b"\xff\xd8\xff\xe0 jpeg".to_vec(),
+        ));
+        let profile = doc.add_object(Stream::new(dictionary! { "N" => 3 }, b"icc profile".to_vec()));
+        doc.add_object(dictionary! { "ColorSpace" => dictionary! { "CS0" => vec![Object::Name(b"ICCBased".to_vec()), profile.into()] }, "XObject" => dictionary! { "Im0" => jpeg } });
+        let payload = doc.add_object(Stream::new(
+            dictionary! { "Type" => "EmbeddedFile" },
+            b"MZ payload".to_vec(),
+        ));
+        doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("../../evil name.exe"),
+            "EF" => dictionary! { "F" => payload },
+        });
+        doc
+    }
+
+    #[test]
+    fn test_classes_and_names() {
+        assert_eq!("JS".parse(), Ok(ArtifactClass::JavaScript));
+        assert_eq!("profiles".parse(), Ok(ArtifactClass::Icc));
+        assert!("sounds".parse::<ArtifactClass>().is_err());
+        assert_eq!(safe_name("../../evil name.exe"), "_.._evil_name.exe");
+        assert_eq!(safe_name(&"a".repeat(300)).len(), MAX_NAME);
+    }
+
+    #[tokio::test]
+    async fn test_extract_all() {
+        let doc = document();
+        let dir = tempfile::tempdir().unwrap();
+
+        let manifest = extract(&doc, dir.path(), &[]).await.unwrap();
+        assert_eq!(manifest.classes, ArtifactClass::ALL.to_vec());
+        for class in [
+            ArtifactClass::JavaScript,
+            ArtifactClass::Images,
+            ArtifactClass::Attachments,
+            ArtifactClass::Icc,
+        ] {
+            assert_eq!(manifest.count(class), 1, "{}", class);
+        }
+        for artifact in &manifest.artifacts {
+            let written = std::fs::read(dir.path().join(&artifact.file)).unwrap();
+            assert_eq!(sha256_hex(&written), artifact.sha256);
+        }
+        let image = manifest
+            .artifacts
+            .iter()
+            .find(|a| a.class == ArtifactClass::Images)
+            .unwrap();
+        assert!(image.file.starts_with("images/") && image.file.ends_with(".jpg"));
+        let attachment = manifest
+            .artifacts
+            .iter()
+            .find(|a| a.class == ArtifactClass::Attachments)
+            .unwrap();
+        assert!(attachment.file.starts_with("attachments/") && !attachment.file.contains("/.."));
+        assert_eq!(attachment.name.as_deref(), Some("../../evil name.exe"));
+        assert!(dir.path().join(MANIFEST).exists());
+
+        let only = tempfile::tempdir().unwrap();
+        let manifest = extract(&doc, only.path(), &[ArtifactClass::JavaScript]).await.unwrap();
+        assert_eq!(manifest.artifacts.len(), 1);
+        assert!(!only.path().join("images").exists());
+    }
+}
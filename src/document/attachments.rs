@@ -0,0 +1,229 @@
+//! Embedded file extraction
+//! Created: 2026-10-16 09:25:03 UTC
+//! Author: kartik4091
+//!
+//! Attachments are reached through file specifications (/EF entries in
+//! the /EmbeddedFiles name tree or in FileAttachment annotations), but a
+//! dropper does not have to reference its payload at all. Every stream
+//! typed /EmbeddedFile is therefore extracted, named from whichever file
+//! specification points at it.
+
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read},
+};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use tracing::{debug, warn};
+
+use super::{
+    collect_references, decode_text,
+    magic::{identify, MagicKind},
+    name_value,
+    revisions::find,
+    stream_data, walk_dictionaries,
+};
+use crate::{
+    error::{Error, Result},
+    limits::ResourceLimits,
+};
+
+/// Most entries extracted from one ZIP archive
+const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+
+/// An embedded file
+#[derive(Debug, Clone)]
+pub struct EmbeddedFile {
+    /// Stream holding the file
+    pub id: ObjectId,
+
+    /// File name from the file specification, or a placeholder
+    pub name: String,
+
+    /// Decoded content
+    pub data: Vec<u8>,
+}
+
+/// A file extracted from an archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    /// Path inside the archive
+    pub path: String,
+
+    /// Decompressed content
+    pub data: Vec<u8>,
+}
+
+/// Readers accept a header anywhere in the first 1024 bytes
+const HEADER_WINDOW: usize = 1024;
+
+/// Whether data is a PDF document, as a reader would accept it
+pub fn is_pdf(data: &[u8]) -> bool {
+    find(&data[..data.len().min(HEADER_WINDOW)], b"%PDF-", 0).is_some()
+}
+
+/// Whether data is a ZIP archive
+pub fn is_zip(data: &[u8]) -> bool {
+    identify(data).map_or(false, |magic| magic.kind == MagicKind::Archive && magic.mime == "application/zip")
+}
+
+/// Extracts every embedded file stream of the document
+pub fn extract(doc: &Document) -> Vec<EmbeddedFile> {
+    let names = file_names(doc);
+
+    doc.objects
+        .iter()
+        .filter_map(|(&id, object)| {
+            let stream = object.as_stream().ok()?;
+            if name_value(&stream.dict, b"Type") != Some(b"EmbeddedFile") && !names.contains_key(&id) {
+                return None;
+            }
+            let name = names.get(&id).cloned().unwrap_or_else(|| format!("<object {} {}>", id.0, id.1));
+            match stream_data(stream) {
+                Ok(data) => Some(EmbeddedFile { id, name, data }),
+                Err(e) => {
+                    warn!("Failed to decode embedded file {}: {}", name, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// File names of embedded file streams, from the specifications that
+/// reference them
+pub(crate) fn file_names(doc: &Document) -> BTreeMap<ObjectId, String> {
+    let mut names = BTreeMap::new();
+    for object in doc.objects.values() {
+        walk_dictionaries(object, &mut |dict| {
+            let Ok(ef) = dict.get(b"EF") else {
+                return;
+            };
+            let mut targets = Vec::new();
+            collect_references(ef, &mut targets);
+            if let Some(name) = spec_name(dict) {
+                for id in targets {
+                    names.entry(id).or_insert_with(|| name.clone());
+                }
+            }
+        });
+    }
+    names
+}
+
+/// Preferred name of a file specification (/UF, then /F)
+fn spec_name(spec: &Dictionary) -> Option<String> {
+    [&b"UF"[..], b"F"].iter().find_map(|key| match spec.get(key).ok()? {
+        Object::String(bytes, _) => Some(decode_text(bytes)),
+        _ => None,
+    })
+}
+
+/// Extracts the files of a ZIP archive.
+///
+/// Entries that would take the extracted total past the decoded size limit
+/// are skipped, as are directories and entries that fail to decompress.
+/// Archives with more than [`MAX_ARCHIVE_ENTRIES`] entries are refused.
+pub fn unzip(data: &[u8], limits: &ResourceLimits) -> Result<Vec<ArchiveEntry>> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(data)).map_err(|e| Error::Analysis(format!("Unreadable ZIP archive: {}", e)))?;
+    if archive.len() > MAX_ARCHIVE_ENTRIES {
+        return Err(Error::Security(format!(
+            "ZIP archive has {} entries, more than {}",
+            archive.len(),
+            MAX_ARCHIVE_ENTRIES
+        )));
+    }
+
+    let mut entries = Vec::new();
+    let mut remaining = limits.max_decoded_size;
+    for index in 0..archive.len() {
+        let Ok(file) = archive.by_index(index) else {
+            continue;
+        };
+        if file.is_dir() {
+            continue;
+        }
+        let path = file.name().to_string();
+        let mut content = Vec::new();
+        // The declared size is attacker-controlled, so the read is capped too
+        let read = file.take(remaining.saturating_add(1)).read_to_end(&mut content);
+        if read.is_err() || content.len() as u64 > remaining {
+            warn!("Skipping ZIP entry {}: unreadable or past the {} byte limit", path, limits.max_decoded_size);
+            continue;
+        }
+        remaining -= content.len() as u64;
+        entries.push(ArchiveEntry { path, data: content });
+    }
+
+    debug!("Extracted {} ZIP entries", entries.len());
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+    use std::io::Write;
+
+    #[test]
+    fn test_extract_named_and_unreferenced_files() {
+        let mut doc = fixtures::document();
+        let named = doc.add_object(Stream::new(dictionary! { "Type" => "EmbeddedFile" }, b"invoice".to_vec()));
+        doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("invoice.pdf"),
+            "EF" => dictionary! { "F" => named },
+        });
+        let hidden = doc.add_object(Stream::new(dictionary! { "Type" => "EmbeddedFile" }, b"payload".to_vec()));
+
+        let files = extract(&doc);
+        let found: Vec<_> = files.iter().map(|f| (f.id, f.name.as_str(), f.data.as_slice())).collect();
+        assert_eq!(
+            found,
+            vec![(named, "invoice.pdf", &b"invoice"[..]), (hidden, "<object 9 0>", &b"payload"[..])]
+        );
+    }
+
+    #[test]
+    fn test_unzip_respects_size_limit() {
+        let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        archive.start_file("small.pdf", options).unwrap();
+        archive.write_all(b"%PDF-1.7").unwrap();
+        archive.start_file("large.bin", options).unwrap();
+        archive.write_all(&[0u8; 4096]).unwrap();
+        let data = archive.finish().unwrap().into_inner();
+
+        let limits = ResourceLimits { max_decoded_size: 1024, ..Default::default() };
+        let entries = unzip(&data, &limits).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "small.pdf");
+        assert!(unzip(b"not a zip", &limits).is_err());
+        assert!(is_zip(&data));
+        assert!(is_pdf(&entries[0].data));
+    }
+
+    #[test]
+    fn test_unzip_limits_total_size_and_entries() {
+        let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        for name in ["a.bin", "b.bin", "c.bin"] {
+            archive.start_file(name, options).unwrap();
+            archive.write_all(&[0u8; 400]).unwrap();
+        }
+        let data = archive.finish().unwrap().into_inner();
+
+        let limits = ResourceLimits { max_decoded_size: 1000, ..Default::default() };
+        let entries = unzip(&data, &limits).unwrap();
+        assert_eq!(entries.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(), ["a.bin", "b.bin"]);
+
+        let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for index in 0..=MAX_ARCHIVE_ENTRIES {
+            archive.start_file(index.to_string(), options).unwrap();
+        }
+        let data = archive.finish().unwrap().into_inner();
+        assert!(unzip(&data, &limits).is_err());
+    }
+}
@@ -0,0 +1,473 @@
+//! Document history reconstructed from every dated source
+//! Created: 2026-10-16 10:57:43 UTC
+//! Author: kartik4091
+//!
+//! Merges the dates a file carries into one chronological timeline: the
+//! information dictionary of every revision, XMP create/modify dates and
+//! `xmpMM:History` events, signing times, and time-stamp authority tokens
+//! (including document timestamps). Each event keeps the revision that
+//! wrote it, so dates can be checked against the physical append order.
+//! Contradictions are reported as conflicts and the events involved are
+//! flagged: modification before creation, information dictionary and XMP
+//! disagreeing, a later revision dated before an earlier one, backdated
+//! signatures, and dates in the future.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use lopdf::{Document, Object, ObjectId};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{
+    decode_text, info_dictionary, metadata_text, parse_pdf_date,
+    revisions::{materialize, split_revisions},
+};
+use crate::detectors::{signatures, Severity};
+
+/// Differences up to this many seconds between the information dictionary
+/// and XMP are rounding, not a conflict
+const TOLERANCE_SECONDS: i64 = 60;
+
+/// Sub-filter of document timestamp signatures
+const DOCUMENT_TIMESTAMP: &str = "ETSI.RFC3161";
+
+/// Where a date was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventSource {
+    /// Information dictionary /CreationDate
+    InfoCreated,
+
+    /// Information dictionary /ModDate
+    InfoModified,
+
+    /// XMP `xmp:CreateDate`
+    XmpCreated,
+
+    /// XMP `xmp:ModifyDate`
+    XmpModified,
+
+    /// XMP `xmp:MetadataDate`
+    XmpMetadata,
+
+    /// XMP `xmpMM:History` event
+    XmpHistory,
+
+    /// Signer-claimed signing time
+    SignatureClaimed,
+
+    /// Time-stamp authority token of a signature
+    SignatureTsa,
+
+    /// Document timestamp (RFC 3161 signature)
+    DocumentTimestamp,
+}
+
+/// A dated event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    /// When the event claims to have happened
+    pub time: DateTime<Utc>,
+
+    /// Where the date was read from
+    pub source: EventSource,
+
+    /// Revision that first wrote the date
+    pub revision: Option<usize>,
+
+    /// Signature dictionary, for signature events
+    pub object: Option<ObjectId>,
+
+    /// Human-readable description
+    pub description: String,
+
+    /// Whether the event takes part in a conflict
+    pub conflict: bool,
+}
+
+/// Events contradicting each other
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineConflict {
+    /// Severity
+    pub severity: Severity,
+
+    /// Indices of the events involved
+    pub events: Vec<usize>,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Chronological history of a file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocumentTimeline {
+    /// Revisions found in the file
+    pub revisions: usize,
+
+    /// Events ordered by time, then by revision
+    pub events: Vec<TimelineEvent>,
+
+    /// Contradictions between events
+    pub conflicts: Vec<TimelineConflict>,
+}
+
+/// Reconstructs the timeline of a file
+#[instrument(skip(data))]
+pub fn reconstruct(data: &[u8]) -> DocumentTimeline {
+    let revisions = split_revisions(data);
+    let mut events: Vec<TimelineEvent> = Vec::new();
+    for revision in &revisions {
+        let Ok(doc) = materialize(data, revision) else {
+            continue;
+        };
+        for (source, time, description) in document_dates(&doc) {
+            // Dates carried over unchanged belong to the revision that wrote them
+            let known = events
+                .iter()
+                .any(|e| e.source == source && e.time == time && e.description == description);
+            if !known {
+                events.push(TimelineEvent {
+                    time,
+                    source,
+                    revision: Some(revision.index),
+                    object: None,
+                    description,
+                    conflict: false,
+                });
+            }
+        }
+    }
+
+    let signed = signatures::check(data);
+    for record in &signed.signatures {
+        let timestamp = record.sub_filter.as_deref() == Some(DOCUMENT_TIMESTAMP);
+        let (number, generation) = record.id;
+        let (tsa_source, tsa_description) = if timestamp {
+            (
+                EventSource::DocumentTimestamp,
+                format!("Document timestamp {} {}", number, generation),
+            )
+        } else {
+            (
+                EventSource::SignatureTsa,
+                format!("Time-stamp authority token of signature {} {}", number, generation),
+            )
+        };
+        let dated = [
+            (
+                record.signing_time,
+                EventSource::SignatureClaimed,
+                format!("Signature {} {} claims signing", number, generation),
+            ),
+            (record.tsa_time, tsa_source, tsa_description),
+        ];
+        for (time, source, description) in dated {
+            if let Some(time) = time {
+                events.push(TimelineEvent {
+                    time,
+                    source,
+                    revision: record.revision,
+                    object: Some(record.id),
+                    description,
+                    conflict: false,
+                });
+            }
+        }
+    }
+    events.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.revision.cmp(&b.revision)));
+
+    let mut timeline = DocumentTimeline {
+        revisions: revisions.len(),
+        events,
+        conflicts: Vec::new(),
+    };
+    find_conflicts(&mut timeline, &signed.backdating);
+    debug!(
+        "Timeline with {} events, {} conflicts",
+        timeline.events.len(),
+        timeline.conflicts.len()
+    );
+    timeline
+}
+
+/// Dates of the information dictionary and the XMP packet
+fn document_dates(doc: &Document) -> Vec<(EventSource, DateTime<Utc>, String)> {
+    let mut dates = Vec::new();
+    if let Some(info) = info_dictionary(doc) {
+        for (key, source) in [
+            (&b"CreationDate"[..], EventSource::InfoCreated),
+            (b"ModDate", EventSource::InfoModified),
+        ] {
+            if let Ok(Object::String(bytes, _)) = info.get(key) {
+                if let Some(time) = parse_pdf_date(decode_text(bytes).as_bytes()) {
+                    dates.push((source, time, format!("Info /{}", String::from_utf8_lossy(key))));
+                }
+            }
+        }
+    }
+    if let Some((_, xmp)) = metadata_text(doc).into_iter().find(|(key, _)| key == "XMP") {
+        dates.extend(xmp_dates(&xmp));
+    }
+    dates
+}
+
+/// Date properties and history events of an XMP packet
+fn xmp_dates(xmp: &str) -> Vec<(EventSource, DateTime<Utc>, String)> {
+    let property = Regex::new(r#"xmp:(CreateDate|ModifyDate|MetadataDate)(?:\s*=\s*["']([^"']*)["']|>([^<]*)<)"#)
+        .expect("valid XMP pattern");
+    let event = Regex::new(r#"stEvt:(action|when|softwareAgent)(?:\s*=\s*["']([^"']*)["']|>([^<]*)<)"#)
+        .expect("valid XMP pattern");
+    let value = |captures: &regex::Captures| {
+        captures
+            .get(2)
+            .or_else(|| captures.get(3))
+            .map_or("", |m| m.as_str())
+            .trim()
+            .to_string()
+    };
+
+    let mut dates = Vec::new();
+    for captures in property.captures_iter(xmp) {
+        let source = match &captures[1] {
+            "CreateDate" => EventSource::XmpCreated,
+            "ModifyDate" => EventSource::XmpModified,
+            _ => EventSource::XmpMetadata,
+        };
+        if let Some(time) = parse_xmp_date(&value(&captures)) {
+            dates.push((source, time, format!("XMP xmp:{}", &captures[1])));
+        }
+    }
+
+    let history = xmp
+        .find("<xmpMM:History")
+        .and_then(|start| Some(&xmp[start..start + xmp[start..].find("</xmpMM:History>")?]));
+    for item in history.into_iter().flat_map(|h| h.split("<rdf:li").skip(1)) {
+        let (mut action, mut when, mut agent) = (None, None, None);
+        for captures in event.captures_iter(item) {
+            // Closing tags match too, with nothing captured
+            let text = value(&captures);
+            if text.is_empty() {
+                continue;
+            }
+            match &captures[1] {
+                "action" => action = Some(text),
+                "when" => when = Some(text),
+                _ => agent = Some(text),
+            }
+        }
+        if let Some(time) = when.as_deref().and_then(parse_xmp_date) {
+            let mut description = format!("XMP history: {}", action.as_deref().unwrap_or("event"));
+            if let Some(agent) = agent {
+                description.push_str(&format!(" by {}", agent));
+            }
+            dates.push((EventSource::XmpHistory, time, description));
+        }
+    }
+    dates
+}
+
+/// Parses an XMP (ISO 8601) date; trailing fields may be left out and a
+/// missing time zone is taken as UTC
+pub fn parse_xmp_date(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%dT%H:%M%:z", "%Y-%m-%dT%H:%M:%S%.f%:z"] {
+        if let Ok(time) = DateTime::parse_from_str(text, format) {
+            return Some(time.with_timezone(&Utc));
+        }
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(time.and_utc());
+        }
+    }
+    let date = match text.len() {
+        4 => NaiveDate::from_ymd_opt(text.parse().ok()?, 1, 1)?,
+        7 => NaiveDate::parse_from_str(&format!("{}-01", text), "%Y-%m-%d").ok()?,
+        _ => NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?,
+    };
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+fn find_conflicts(timeline: &mut DocumentTimeline, backdating: &[signatures::BackdatingIndicator]) {
+    let events = &timeline.events;
+    let mut conflicts = Vec::new();
+    let of = |source: EventSource| events.iter().enumerate().filter(move |(_, e)| e.source == source);
+
+    for (created, modified) in [
+        (EventSource::InfoCreated, EventSource::InfoModified),
+        (EventSource::XmpCreated, EventSource::XmpModified),
+    ] {
+        for (m, modification) in of(modified) {
+            // The creation date in force when the modification was written
+            let Some((c, creation)) = of(created)
+                .filter(|(_, c)| c.revision <= modification.revision)
+                .max_by_key(|(_, c)| c.revision)
+            else {
+                continue;
+            };
+            if modification.time < creation.time {
+                conflicts.push(TimelineConflict {
+                    severity: Severity::Medium,
+                    events: vec![c, m],
+                    description: format!(
+                        "{} ({}) is earlier than {} ({})",
+                        modification.description, modification.time, creation.description, creation.time
+                    ),
+                });
+            }
+        }
+    }
+
+    for (info, xmp) in [
+        (EventSource::InfoCreated, EventSource::XmpCreated),
+        (EventSource::InfoModified, EventSource::XmpModified),
+    ] {
+        // Compare what the final revision says; older pairs were superseded
+        let (Some((i, a)), Some((x, b))) = (latest(events, info), latest(events, xmp)) else {
+            continue;
+        };
+        if (a.time - b.time).num_seconds().abs() > TOLERANCE_SECONDS {
+            conflicts.push(TimelineConflict {
+                severity: Severity::Medium,
+                events: vec![i, x],
+                description: format!(
+                    "{} ({}) disagrees with {} ({})",
+                    a.description, a.time, b.description, b.time
+                ),
+            });
+        }
+    }
+
+    for (i, earlier) in of(EventSource::InfoModified) {
+        for (j, later) in of(EventSource::InfoModified) {
+            if later.revision > earlier.revision && later.time < earlier.time {
+                conflicts.push(TimelineConflict {
+                    severity: Severity::High,
+                    events: vec![i, j],
+                    description: format!(
+                        "Revision {} is dated {}, before revision {} ({})",
+                        later.revision.unwrap_or_default(),
+                        later.time,
+                        earlier.revision.unwrap_or_default(),
+                        earlier.time
+                    ),
+                });
+            }
+        }
+    }
+
+    for indicator in backdating {
+        let involved: Vec<usize> = events
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.object == Some(indicator.earlier) || e.object == Some(indicator.later))
+            .map(|(index, _)| index)
+            .collect();
+        conflicts.push(TimelineConflict {
+            severity: indicator.severity,
+            events: involved,
+            description: indicator.description.clone(),
+        });
+    }
+
+    let horizon = Utc::now() + Duration::days(1);
+    for (index, event) in events.iter().enumerate().filter(|(_, e)| e.time > horizon) {
+        conflicts.push(TimelineConflict {
+            severity: Severity::Medium,
+            events: vec![index],
+            description: format!("{} is in the future ({})", event.description, event.time),
+        });
+    }
+
+    for conflict in &conflicts {
+        for &index in &conflict.events {
+            timeline.events[index].conflict = true;
+        }
+    }
+    timeline.conflicts = conflicts;
+}
+
+/// Latest-revision event of a source
+fn latest(events: &[TimelineEvent], source: EventSource) -> Option<(usize, &TimelineEvent)> {
+    events
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.source == source)
+        .max_by_key(|(_, e)| e.revision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_parse_xmp_date() {
+        let expected = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap()
+            .and_utc();
+        assert_eq!(parse_xmp_date("2024-03-01T10:30:00+01:00"), Some(expected));
+        assert_eq!(parse_xmp_date("2024-03-01T10:30+01:00"), Some(expected));
+        assert_eq!(parse_xmp_date("2024-03-01T09:30:00Z"), Some(expected));
+        assert_eq!(parse_xmp_date("2024-03-01T09:30"), Some(expected));
+        assert_eq!(
+            parse_xmp_date("2024-03").unwrap().to_rfc3339(),
+            "2024-03-01T00:00:00+00:00"
+        );
+        assert!(parse_xmp_date("yesterday").is_none());
+
+        let xmp = r#"<x:xmpmeta><rdf:Description xmp:CreateDate="2024-03-01T09:30:00Z">
+            <xmp:ModifyDate>2024-03-02T09:30:00Z</xmp:ModifyDate>
+            <xmpMM:History><rdf:Seq>
+              <rdf:li stEvt:action="created" stEvt:when="2024-03-01T09:30:00Z" stEvt:softwareAgent="Word"/>
+              <rdf:li><stEvt:action>saved</stEvt:action><stEvt:when>2024-03-02T09:30:00Z</stEvt:when></rdf:li>
+            </rdf:Seq></xmpMM:History></rdf:Description></x:xmpmeta>"#;
+        let dates = xmp_dates(xmp);
+        assert_eq!(dates.len(), 4);
+        assert_eq!(dates[0].0, EventSource::XmpCreated);
+        assert_eq!(dates[2].2, "XMP history: created by Word");
+        assert_eq!(dates[3].2, "XMP history: saved");
+    }
+
+    #[test]
+    fn test_backdated_revision_conflicts() {
+        let mut doc = fixtures::document();
+        let info = doc.add_object(lopdf::dictionary! {
+            "CreationDate" => Object::string_literal("D:20240301093000Z"),
+            "ModDate" => Object::string_literal("D:20240302093000Z"),
+        });
+        doc.trailer.set("Info", info);
+        let base = fixtures::save(doc);
+        // The update rewrites the information dictionary with an older /ModDate
+        let data = fixtures::append_revision_with_trailer(
+            &base,
+            &[(
+                info.0,
+                "<< /CreationDate (D:20240301093000Z) /ModDate (D:20240201093000Z) >>",
+            )],
+            &format!("/Info {} 0 R", info.0),
+        );
+
+        let timeline = reconstruct(&data);
+        assert_eq!(timeline.revisions, 2);
+        let created: Vec<_> = timeline
+            .events
+            .iter()
+            .filter(|e| e.source == EventSource::InfoCreated)
+            .collect();
+        assert_eq!(created.len(), 1, "unchanged dates are not repeated");
+        assert_eq!(created[0].revision, Some(0));
+        assert!(timeline.events.windows(2).all(|pair| pair[0].time <= pair[1].time));
+
+        assert_eq!(timeline.conflicts.len(), 2, "{:?}", timeline.conflicts);
+        assert!(timeline
+            .conflicts
+            .iter()
+            .any(|c| c.severity == Severity::High && c.description.starts_with("Revision 1 is dated")));
+        assert!(timeline.events.iter().filter(|e| e.conflict).count() >= 3);
+    }
+}
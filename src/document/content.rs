@@ -0,0 +1,621 @@
+//! Content stream interpretation
+//! Created: 2026-10-16 09:33:37 UTC
+//! Author: kartik4091
+//!
+//! A small interpreter for page content streams that tracks the parts of
+//! the graphics state that decide what a reader actually sees: the current
+//! transformation matrix, fill and stroke colors, and the text state. It
+//! reports every text-showing operator with its device-space box, every
+//! filled path, every painted XObject, inline image and shading, in
+//! painting order. Colors set with `sc`/`scn` in anything but a device
+//! color space are not interpreted.
+//!
+//! Character codes and glyph advances come from a [`TextMetrics`]
+//! implementation, so callers that do not load fonts can fall back to
+//! single-byte codes of an average width.
+
+use lopdf::content::Operation;
+use lopdf::Object;
+use serde::{Deserialize, Serialize};
+
+use super::number;
+
+/// Affine transformation `[a b c d e f]`
+pub type Matrix = [f64; 6];
+
+/// The identity transformation
+pub const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// Average glyph advance in text space units per em, for unknown fonts
+pub const DEFAULT_ADVANCE: f64 = 0.5;
+
+/// Glyph extent below and above the baseline, in ems
+const DESCENT: f64 = 0.2;
+const ASCENT: f64 = 0.8;
+
+/// Deepest q/Q nesting tracked
+const MAX_STACK: usize = 256;
+
+/// Color tolerance when comparing components
+const COLOR_TOLERANCE: f64 = 0.02;
+
+/// `m × n`
+pub fn multiply(m: &Matrix, n: &Matrix) -> Matrix {
+    [
+        m[0] * n[0] + m[1] * n[2],
+        m[0] * n[1] + m[1] * n[3],
+        m[2] * n[0] + m[3] * n[2],
+        m[2] * n[1] + m[3] * n[3],
+        m[4] * n[0] + m[5] * n[2] + n[4],
+        m[4] * n[1] + m[5] * n[3] + n[5],
+    ]
+}
+
+/// Applies a transformation to a point
+pub fn transform(m: &Matrix, x: f64, y: f64) -> (f64, f64) {
+    (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5])
+}
+
+/// Bounding box of a rectangle after transformation
+pub fn transform_box(m: &Matrix, [x0, y0, x1, y1]: [f64; 4]) -> [f64; 4] {
+    let corners = [
+        transform(m, x0, y0),
+        transform(m, x1, y0),
+        transform(m, x0, y1),
+        transform(m, x1, y1),
+    ];
+    corners.iter().fold(
+        [f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY],
+        |[bx0, by0, bx1, by1], &(x, y)| [bx0.min(x), by0.min(y), bx1.max(x), by1.max(y)],
+    )
+}
+
+/// Fill or stroke color
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Color {
+    /// DeviceGray level
+    Gray(f64),
+
+    /// DeviceRGB components
+    Rgb(f64, f64, f64),
+
+    /// DeviceCMYK components
+    Cmyk(f64, f64, f64, f64),
+
+    /// Pattern or a color space that is not interpreted
+    Other,
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::Gray(0.0)
+    }
+}
+
+impl Color {
+    /// Approximate RGB rendering of the color
+    pub fn rgb(&self) -> Option<[f64; 3]> {
+        match *self {
+            Self::Gray(g) => Some([g, g, g]),
+            Self::Rgb(r, g, b) => Some([r, g, b]),
+            Self::Cmyk(c, m, y, k) => Some([(1.0 - c) * (1.0 - k), (1.0 - m) * (1.0 - k), (1.0 - y) * (1.0 - k)]),
+            Self::Other => None,
+        }
+    }
+
+    /// Whether two colors render the same
+    pub fn matches(&self, other: &Color) -> bool {
+        match (self.rgb(), other.rgb()) {
+            (Some(a), Some(b)) => a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() <= COLOR_TOLERANCE),
+            _ => false,
+        }
+    }
+
+    /// Whether the color is white
+    pub fn is_white(&self) -> bool {
+        self.matches(&Color::Gray(1.0))
+    }
+
+    /// Whether the color is black or close to it
+    pub fn is_dark(&self) -> bool {
+        self.rgb().map_or(false, |c| c.iter().all(|&v| v <= 0.1))
+    }
+
+    fn from_operands(operands: &[Object]) -> Self {
+        if matches!(operands.last(), Some(Object::Name(_))) {
+            return Self::Other;
+        }
+        let values: Vec<f64> = operands.iter().filter_map(number).collect();
+        match values[..] {
+            [g] => Self::Gray(g),
+            [r, g, b] => Self::Rgb(r, g, b),
+            [c, m, y, k] => Self::Cmyk(c, m, y, k),
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Character code boundaries and glyph widths, per font resource
+pub trait TextMetrics {
+    /// Splits shown bytes into character codes
+    fn codes<'b>(&self, font: &[u8], bytes: &'b [u8]) -> Vec<&'b [u8]>;
+
+    /// Advance of a character code, in ems (glyph space units / 1000)
+    fn width(&self, font: &[u8], code: &[u8]) -> f64;
+}
+
+/// Single-byte codes of [`DEFAULT_ADVANCE`] width
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AverageWidths;
+
+impl TextMetrics for AverageWidths {
+    fn codes<'b>(&self, _font: &[u8], bytes: &'b [u8]) -> Vec<&'b [u8]> {
+        bytes.chunks(1).collect()
+    }
+
+    fn width(&self, _font: &[u8], _code: &[u8]) -> f64 {
+        DEFAULT_ADVANCE
+    }
+}
+
+/// One character code shown by a text operator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Glyph {
+    /// Character code bytes
+    pub code: Vec<u8>,
+
+    /// Device-space box of the glyph
+    pub bbox: [f64; 4],
+
+    /// Horizontal displacement including spacing, in unscaled text space units
+    pub advance: f64,
+}
+
+/// A text-showing operator (Tj, TJ, ' or ")
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextShow {
+    /// Index of the operator in the content stream
+    pub index: usize,
+
+    /// Font resource name
+    pub font: Vec<u8>,
+
+    /// Shown string bytes (TJ strings concatenated)
+    pub bytes: Vec<u8>,
+
+    /// Font size operand of Tf
+    pub font_size: f64,
+
+    /// Text render mode (Tr)
+    pub render_mode: i64,
+
+    /// Fill color
+    pub fill: Color,
+
+    /// Stroke color
+    pub stroke: Color,
+
+    /// Device-space box covered by the glyphs
+    pub bbox: [f64; 4],
+
+    /// Shown character codes in order
+    pub glyphs: Vec<Glyph>,
+
+    /// Smaller of the device-space glyph height and em width
+    pub apparent_size: f64,
+}
+
+impl TextShow {
+    /// Whether the render mode paints nothing (3, or 7 which only clips)
+    pub fn is_invisible_mode(&self) -> bool {
+        matches!(self.render_mode, 3 | 7)
+    }
+
+    /// Color glyphs are painted in
+    pub fn paint_color(&self) -> Color {
+        match self.render_mode {
+            1 | 5 => self.stroke,
+            _ => self.fill,
+        }
+    }
+}
+
+/// Something painted by a content stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ContentEvent {
+    /// Text was shown
+    Text(TextShow),
+
+    /// A path was filled
+    Fill {
+        /// Device-space bounding box of the path
+        bbox: [f64; 4],
+
+        /// Fill color
+        color: Color,
+    },
+
+    /// An XObject was painted with `Do`
+    XObject {
+        /// Index of the operator in the content stream
+        index: usize,
+
+        /// Resource name
+        name: Vec<u8>,
+
+        /// Transformation the XObject was painted with
+        ctm: Matrix,
+
+        /// Device-space box of the unit square
+        bbox: [f64; 4],
+    },
+
+    /// An inline image was painted (`BI` … `ID` … `EI`)
+    InlineImage {
+        /// Index of the `BI` operator in the content stream
+        index: usize,
+
+        /// Device-space box of the unit square
+        bbox: [f64; 4],
+    },
+
+    /// A shading was painted with `sh`, over the whole clipping region
+    Shading {
+        /// Index of the operator in the content stream
+        index: usize,
+    },
+}
+
+#[derive(Clone)]
+struct GraphicsState {
+    ctm: Matrix,
+    fill: Color,
+    stroke: Color,
+    // Whether sc/scn set device color components
+    fill_device: bool,
+    stroke_device: bool,
+    font: Vec<u8>,
+    font_size: f64,
+    char_spacing: f64,
+    word_spacing: f64,
+    horizontal_scale: f64,
+    leading: f64,
+    rise: f64,
+    render_mode: i64,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        Self {
+            ctm: IDENTITY,
+            fill: Color::default(),
+            stroke: Color::default(),
+            fill_device: true,
+            stroke_device: true,
+            font: Vec::new(),
+            font_size: 0.0,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scale: 1.0,
+            leading: 0.0,
+            rise: 0.0,
+            render_mode: 0,
+        }
+    }
+}
+
+/// Interprets `operations`, starting from `ctm`
+pub fn interpret(operations: &[Operation], ctm: Matrix, metrics: &impl TextMetrics) -> Vec<ContentEvent> {
+    let mut events = Vec::new();
+    let mut state = GraphicsState {
+        ctm,
+        ..Default::default()
+    };
+    let mut stack = Vec::new();
+    let mut text_matrix = IDENTITY;
+    let mut line_matrix = IDENTITY;
+    let mut path: Option<[f64; 4]> = None;
+
+    let numbers = |operands: &[Object]| -> Vec<f64> { operands.iter().filter_map(number).collect() };
+
+    for (index, operation) in operations.iter().enumerate() {
+        let operands = operation.operands.as_slice();
+        match operation.operator.as_str() {
+            "q" if stack.len() < MAX_STACK => stack.push(state.clone()),
+            "Q" => state = stack.pop().unwrap_or(state),
+            "cm" => {
+                if let [a, b, c, d, e, f] = numbers(operands)[..] {
+                    state.ctm = multiply(&[a, b, c, d, e, f], &state.ctm);
+                }
+            }
+
+            "g" | "rg" | "k" => {
+                state.fill = Color::from_operands(operands);
+                state.fill_device = true;
+            }
+            "G" | "RG" | "K" => {
+                state.stroke = Color::from_operands(operands);
+                state.stroke_device = true;
+            }
+            "sc" | "scn" => state.fill = device_color(state.fill_device, operands),
+            "SC" | "SCN" => state.stroke = device_color(state.stroke_device, operands),
+            "cs" => {
+                state.fill = initial_color(operands);
+                state.fill_device = state.fill != Color::Other;
+            }
+            "CS" => {
+                state.stroke = initial_color(operands);
+                state.stroke_device = state.stroke != Color::Other;
+            }
+
+            "m" | "l" | "c" | "v" | "y" => {
+                for pair in numbers(operands).chunks_exact(2) {
+                    let (x, y) = transform(&state.ctm, pair[0], pair[1]);
+                    path = Some(union(path, [x, y, x, y]));
+                }
+            }
+            "re" => {
+                if let [x, y, w, h] = numbers(operands)[..] {
+                    path = Some(union(path, transform_box(&state.ctm, [x, y, x + w, y + h])));
+                }
+            }
+            "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" => {
+                if let Some(bbox) = path.take() {
+                    events.push(ContentEvent::Fill {
+                        bbox,
+                        color: state.fill,
+                    });
+                }
+            }
+            "S" | "s" | "n" => path = None,
+
+            "Do" => {
+                if let Some(Object::Name(name)) = operands.first() {
+                    let bbox = transform_box(&state.ctm, [0.0, 0.0, 1.0, 1.0]);
+                    events.push(ContentEvent::XObject {
+                        index,
+                        name: name.clone(),
+                        ctm: state.ctm,
+                        bbox,
+                    });
+                }
+            }
+
+            "BI" => events.push(ContentEvent::InlineImage {
+                index,
+                bbox: transform_box(&state.ctm, [0.0, 0.0, 1.0, 1.0]),
+            }),
+            "sh" => events.push(ContentEvent::Shading { index }),
+
+            "BT" => {
+                text_matrix = IDENTITY;
+                line_matrix = IDENTITY;
+            }
+            "Tf" => {
+                if let [Object::Name(font), size] = operands {
+                    state.font = font.clone();
+                    state.font_size = number(size).unwrap_or(0.0);
+                }
+            }
+            "Tc" => state.char_spacing = numbers(operands).first().copied().unwrap_or(0.0),
+            "Tw" => state.word_spacing = numbers(operands).first().copied().unwrap_or(0.0),
+            "Tz" => state.horizontal_scale = numbers(operands).first().copied().unwrap_or(100.0) / 100.0,
+            "TL" => state.leading = numbers(operands).first().copied().unwrap_or(0.0),
+            "Ts" => state.rise = numbers(operands).first().copied().unwrap_or(0.0),
+            "Tr" => state.render_mode = operands.first().and_then(|o| o.as_i64().ok()).unwrap_or(0),
+            "Td" | "TD" => {
+                if let [tx, ty] = numbers(operands)[..] {
+                    if operation.operator == "TD" {
+                        state.leading = -ty;
+                    }
+                    line_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, tx, ty], &line_matrix);
+                    text_matrix = line_matrix;
+                }
+            }
+            "Tm" => {
+                if let [a, b, c, d, e, f] = numbers(operands)[..] {
+                    line_matrix = [a, b, c, d, e, f];
+                    text_matrix = line_matrix;
+                }
+            }
+            "T*" => {
+                line_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, 0.0, -state.leading], &line_matrix);
+                text_matrix = line_matrix;
+            }
+
+            "Tj" | "'" | "\"" | "TJ" => {
+                if operation.operator != "Tj" && operation.operator != "TJ" {
+                    line_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, 0.0, -state.leading], &line_matrix);
+                    text_matrix = line_matrix;
+                }
+                if let ("\"", [aw, ac, _]) = (operation.operator.as_str(), operands) {
+                    state.word_spacing = number(aw).unwrap_or(0.0);
+                    state.char_spacing = number(ac).unwrap_or(0.0);
+                }
+
+                let parts: Vec<&Object> = match operands.last() {
+                    Some(Object::Array(items)) if operation.operator == "TJ" => items.iter().collect(),
+                    Some(string @ Object::String(..)) => vec![string],
+                    _ => continue,
+                };
+                let mut bytes = Vec::new();
+                let mut glyphs = Vec::new();
+                for part in parts {
+                    let Object::String(shown, _) = part else {
+                        let tx = -number(part).unwrap_or(0.0) / 1000.0 * state.font_size * state.horizontal_scale;
+                        text_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, tx, 0.0], &text_matrix);
+                        continue;
+                    };
+                    for code in metrics.codes(&state.font, shown) {
+                        let advance = metrics.width(&state.font, code) * state.font_size;
+                        // Word spacing applies to the single-byte code 32 only
+                        let spacing = state.char_spacing + if code == b" " { state.word_spacing } else { 0.0 };
+                        let extent = [
+                            0.0,
+                            state.rise - DESCENT * state.font_size,
+                            advance * state.horizontal_scale,
+                            state.rise + ASCENT * state.font_size,
+                        ];
+                        let device = multiply(&text_matrix, &state.ctm);
+                        glyphs.push(Glyph {
+                            code: code.to_vec(),
+                            bbox: transform_box(&device, extent),
+                            advance: advance + spacing,
+                        });
+
+                        let tx = (advance + spacing) * state.horizontal_scale;
+                        text_matrix = multiply(&[1.0, 0.0, 0.0, 1.0, tx, 0.0], &text_matrix);
+                    }
+                    bytes.extend_from_slice(shown);
+                }
+
+                let Some(bbox) = glyphs.iter().map(|g| g.bbox).reduce(|a, b| union(Some(a), b)) else {
+                    continue;
+                };
+                let device = multiply(&text_matrix, &state.ctm);
+                let height = length(&device, 0.0, state.font_size);
+                let width = length(&device, state.font_size * state.horizontal_scale, 0.0);
+                events.push(ContentEvent::Text(TextShow {
+                    index,
+                    font: state.font.clone(),
+                    bytes,
+                    font_size: state.font_size,
+                    render_mode: state.render_mode,
+                    fill: state.fill,
+                    stroke: state.stroke,
+                    bbox,
+                    glyphs,
+                    apparent_size: height.min(width),
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// Color set by `sc`/`scn` (or `SC`/`SCN`): components of a device color
+/// space, while a Separation tint or an ICC-based color is not interpreted
+fn device_color(device: bool, operands: &[Object]) -> Color {
+    if device {
+        Color::from_operands(operands)
+    } else {
+        Color::Other
+    }
+}
+
+/// Color set by `cs`/`CS`: black, or a pattern
+fn initial_color(operands: &[Object]) -> Color {
+    match operands.first() {
+        Some(Object::Name(name)) if name == b"DeviceRGB" => Color::Rgb(0.0, 0.0, 0.0),
+        Some(Object::Name(name)) if name == b"DeviceCMYK" => Color::Cmyk(0.0, 0.0, 0.0, 1.0),
+        Some(Object::Name(name)) if name == b"DeviceGray" => Color::Gray(0.0),
+        _ => Color::Other,
+    }
+}
+
+/// Device-space length of a text-space vector
+fn length(m: &Matrix, x: f64, y: f64) -> f64 {
+    let (dx, dy) = (m[0] * x + m[2] * y, m[1] * x + m[3] * y);
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Smallest box covering both
+pub fn union(a: Option<[f64; 4]>, b: [f64; 4]) -> [f64; 4] {
+    match a {
+        Some(a) => [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])],
+        None => b,
+    }
+}
+
+/// Whether two boxes overlap
+pub fn intersects(a: &[f64; 4], b: &[f64; 4]) -> bool {
+    a[0] < b[2] && b[0] < a[2] && a[1] < b[3] && b[1] < a[3]
+}
+
+/// Fraction of `inner`'s area covered by `outer`
+pub fn coverage(outer: &[f64; 4], inner: &[f64; 4]) -> f64 {
+    let width = outer[2].min(inner[2]) - outer[0].max(inner[0]);
+    let height = outer[3].min(inner[3]) - outer[1].max(inner[1]);
+    let area = (inner[2] - inner[0]) * (inner[3] - inner[1]);
+    if width <= 0.0 || height <= 0.0 || area <= 0.0 {
+        return 0.0;
+    }
+    width * height / area
+}
+
+/// Whether `outer` covers `inner` entirely
+pub fn contains(outer: &[f64; 4], inner: &[f64; 4]) -> bool {
+    outer[0] <= inner[0] && outer[1] <= inner[1] && outer[2] >= inner[2] && outer[3] >= inner[3]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::Content;
+
+    fn run(source: &[u8]) -> Vec<ContentEvent> {
+        let content = Content::decode(source).unwrap();
+        interpret(&content.operations, IDENTITY, &AverageWidths)
+    }
+
+    #[test]
+    fn test_text_position_and_state() {
+        let events = run(b"q 2 0 0 2 0 0 cm 1 0 0 rg BT /F1 10 Tf 50 100 Td 3 Tr (abcd) Tj ET Q");
+        let [ContentEvent::Text(text)] = &events[..] else {
+            panic!("expected one text event: {:?}", events);
+        };
+        assert_eq!(text.font, b"F1");
+        assert_eq!(text.fill, Color::Rgb(1.0, 0.0, 0.0));
+        assert!(text.is_invisible_mode());
+        // 4 glyphs * 0.5 em * 10pt, scaled by 2
+        assert_eq!(text.bbox, [100.0, 196.0, 140.0, 216.0]);
+        assert_eq!(text.glyphs[1].bbox, [110.0, 196.0, 120.0, 216.0]);
+        assert_eq!(text.apparent_size, 20.0);
+    }
+
+    #[test]
+    fn test_fills_and_state_restore() {
+        let events = run(b"q 1 g 0 0 100 50 re f Q 10 10 m 20 20 l S BT /F1 12 Tf (x) Tj ET");
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0],
+            ContentEvent::Fill {
+                bbox: [0.0, 0.0, 100.0, 50.0],
+                color: Color::Gray(1.0)
+            }
+        );
+        let ContentEvent::Text(text) = &events[1] else {
+            panic!("expected text");
+        };
+        assert_eq!(text.fill, Color::Gray(0.0));
+    }
+
+    #[test]
+    fn test_color_spaces_and_shadings() {
+        let events = run(b"/CS0 cs 1 sc 0 0 10 10 re f /DeviceRGB cs 1 1 1 sc 0 0 10 10 re f /Sh0 sh");
+        assert_eq!(
+            events[..2],
+            [
+                ContentEvent::Fill {
+                    bbox: [0.0, 0.0, 10.0, 10.0],
+                    color: Color::Other
+                },
+                ContentEvent::Fill {
+                    bbox: [0.0, 0.0, 10.0, 10.0],
+                    color: Color::Rgb(1.0, 1.0, 1.0)
+                }
+            ]
+        );
+        assert_eq!(events[2], ContentEvent::Shading { index: 8 });
+    }
+
+    #[test]
+    fn test_colors() {
+        assert!(Color::Cmyk(0.0, 0.0, 0.0, 0.0).is_white());
+        assert!(Color::Rgb(0.0, 0.0, 0.0).matches(&Color::Gray(0.0)));
+        assert!(Color::Cmyk(0.0, 0.0, 0.0, 1.0).is_dark());
+        assert!(!Color::Other.matches(&Color::Other));
+    }
+}
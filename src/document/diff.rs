@@ -0,0 +1,440 @@
+//! Object-level comparison of documents and revisions
+//! Created: 2026-10-16 08:37:31 UTC
+//! Author: kartik4091
+
+use std::collections::BTreeSet;
+
+use lopdf::{Document, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+
+use super::{
+    display_value, info_dictionary, metadata_text, object_digest,
+    revisions::{materialize, split_revisions, Revision},
+    taxonomy::ObjectType,
+    xmp,
+};
+use crate::{
+    detectors::signatures::{self, SignatureRecord},
+    error::{Error, Result},
+};
+
+/// Kind of change between two versions of a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ChangeKind {
+    /// Present only in the newer version
+    Added,
+
+    /// Present only in the older version
+    Removed,
+
+    /// Present in both with different content
+    Modified,
+}
+
+/// Change to a single indirect object
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ObjectChange {
+    /// Object number and generation
+    pub id: ObjectId,
+
+    /// Change kind
+    pub kind: ChangeKind,
+
+    /// Object type (from the newer version when available)
+    pub object_type: ObjectType,
+}
+
+/// Change to a document information entry or its XMP counterpart
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MetadataChange {
+    /// Info dictionary key, or `XMP <property>`
+    pub key: String,
+
+    /// Value in the older version
+    pub before: Option<String>,
+
+    /// Value in the newer version
+    pub after: Option<String>,
+}
+
+/// Change to a page's content
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PageChange {
+    /// One-based page number
+    pub page: u32,
+
+    /// Change kind
+    pub kind: ChangeKind,
+}
+
+/// Differences between two documents
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentDiff {
+    /// Changed indirect objects
+    pub objects: Vec<ObjectChange>,
+
+    /// Changed document information entries
+    pub metadata: Vec<MetadataChange>,
+
+    /// Pages whose content was added, removed or rewritten
+    pub pages: Vec<PageChange>,
+}
+
+impl DocumentDiff {
+    /// Number of object changes of the given kind
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.objects.iter().filter(|c| c.kind == kind).count()
+    }
+
+    /// Whether the two documents are identical at object level
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty() && self.metadata.is_empty() && self.pages.is_empty()
+    }
+}
+
+/// Signature present in only one file, or rewritten
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureChange {
+    /// Signature dictionary
+    pub id: ObjectId,
+
+    /// Change kind; modified when the signature dictionary differs
+    pub kind: ChangeKind,
+
+    /// Signature in the older file
+    pub before: Option<SignatureRecord>,
+
+    /// Signature in the newer file
+    pub after: Option<SignatureRecord>,
+}
+
+/// Differences between two separate files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// Size of the older file
+    pub before_size: usize,
+
+    /// Size of the newer file
+    pub after_size: usize,
+
+    /// Object, metadata and page differences
+    pub diff: DocumentDiff,
+
+    /// Signature differences
+    pub signatures: Vec<SignatureChange>,
+}
+
+impl FileDiff {
+    /// Whether the files are identical at object level
+    pub fn is_empty(&self) -> bool {
+        self.diff.is_empty() && self.signatures.is_empty()
+    }
+}
+
+/// Transition from one revision to the next
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RevisionStep {
+    /// Index of the older revision
+    pub from: usize,
+
+    /// Index of the newer revision
+    pub to: usize,
+
+    /// File offset at which the newer revision starts
+    pub offset: usize,
+
+    /// Number of bytes appended by the newer revision
+    pub size: usize,
+
+    /// Object-level differences
+    pub diff: DocumentDiff,
+}
+
+/// Change timeline across all revisions of a file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct RevisionTimeline {
+    /// Number of revisions found in the file
+    pub revision_count: usize,
+
+    /// Consecutive revision transitions, oldest first
+    pub steps: Vec<RevisionStep>,
+
+    /// Revisions that could not be materialized
+    pub errors: Vec<String>,
+}
+
+/// Compares two documents object by object
+pub fn diff_documents(before: &Document, after: &Document) -> DocumentDiff {
+    let mut diff = DocumentDiff::default();
+
+    let ids: BTreeSet<ObjectId> = before.objects.keys().chain(after.objects.keys()).copied().collect();
+    for id in ids {
+        let change = match (before.objects.get(&id), after.objects.get(&id)) {
+            (None, Some(new)) => Some((ChangeKind::Added, ObjectType::of(new))),
+            (Some(old), None) => Some((ChangeKind::Removed, ObjectType::of(old))),
+            (Some(old), Some(new)) if object_digest(old) != object_digest(new) => {
+                Some((ChangeKind::Modified, ObjectType::of(new)))
+            }
+            _ => None,
+        };
+        if let Some((kind, object_type)) = change {
+            diff.objects.push(ObjectChange { id, kind, object_type });
+        }
+    }
+
+    diff.metadata = diff_metadata(before, after);
+    diff.pages = diff_pages(before, after);
+    diff
+}
+
+/// Compares two files: their effective documents object by object, and
+/// the signatures each carries
+#[instrument(skip(before, after))]
+pub fn diff_files(before: &[u8], after: &[u8]) -> Result<FileDiff> {
+    let load = |data: &[u8], which: &str| {
+        Document::load_mem(data).map_err(|e| Error::Pdf(format!("Failed to load {} file: {}", which, e)))
+    };
+    let diff = diff_documents(&load(before, "older")?, &load(after, "newer")?);
+
+    let old = signatures::check(before).signatures;
+    let new = signatures::check(after).signatures;
+    let ids: BTreeSet<ObjectId> = old.iter().chain(new.iter()).map(|s| s.id).collect();
+    let signatures = ids
+        .into_iter()
+        .filter_map(|id| {
+            let before = old.iter().find(|s| s.id == id).cloned();
+            let after = new.iter().find(|s| s.id == id).cloned();
+            let kind = match (&before, &after) {
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) => ChangeKind::Removed,
+                _ if diff.objects.iter().any(|c| c.id == id) => ChangeKind::Modified,
+                _ => return None,
+            };
+            Some(SignatureChange { id, kind, before, after })
+        })
+        .collect();
+
+    let diff = FileDiff {
+        before_size: before.len(),
+        after_size: after.len(),
+        diff,
+        signatures,
+    };
+    debug!(
+        "{} object, {} metadata, {} page and {} signature differences",
+        diff.diff.objects.len(),
+        diff.diff.metadata.len(),
+        diff.diff.pages.len(),
+        diff.signatures.len()
+    );
+    Ok(diff)
+}
+
+fn diff_metadata(before: &Document, after: &Document) -> Vec<MetadataChange> {
+    let collect = |doc: &Document| -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = info_dictionary(doc)
+            .map(|dict| {
+                dict.iter()
+                    .map(|(k, v)| (String::from_utf8_lossy(k).into_owned(), display_value(v)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some((_, packet)) = metadata_text(doc).into_iter().find(|(key, _)| key == "XMP") {
+            entries.extend(
+                xmp::info_properties(&packet)
+                    .into_iter()
+                    .map(|(name, value)| (format!("XMP {}", name), value)),
+            );
+        }
+        entries
+    };
+    let old = collect(before);
+    let new = collect(after);
+
+    let keys: BTreeSet<&String> = old.iter().chain(new.iter()).map(|(k, _)| k).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let lookup = |entries: &[(String, String)]| {
+                entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+            };
+            let before = lookup(&old);
+            let after = lookup(&new);
+            (before != after).then(|| MetadataChange { key: key.clone(), before, after })
+        })
+        .collect()
+}
+
+fn diff_pages(before: &Document, after: &Document) -> Vec<PageChange> {
+    let old_pages = before.get_pages();
+    let new_pages = after.get_pages();
+    let numbers: BTreeSet<u32> = old_pages.keys().chain(new_pages.keys()).copied().collect();
+
+    numbers
+        .into_iter()
+        .filter_map(|page| {
+            let kind = match (old_pages.get(&page), new_pages.get(&page)) {
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) => ChangeKind::Removed,
+                (Some(&old_id), Some(&new_id)) => {
+                    let old = before.get_page_content(old_id).unwrap_or_default();
+                    let new = after.get_page_content(new_id).unwrap_or_default();
+                    if old == new {
+                        return None;
+                    }
+                    ChangeKind::Modified
+                }
+                (None, None) => return None,
+            };
+            Some(PageChange { page, kind })
+        })
+        .collect()
+}
+
+/// Diffs every pair of consecutive revisions in a file
+#[instrument(skip(data))]
+pub fn revision_timeline(data: &[u8]) -> Result<RevisionTimeline> {
+    let revisions = split_revisions(data);
+    let mut timeline = RevisionTimeline {
+        revision_count: revisions.len(),
+        ..Default::default()
+    };
+
+    push_steps(data, &revisions, &mut timeline);
+    debug!("Built timeline with {} steps", timeline.steps.len());
+    Ok(timeline)
+}
+
+/// Extends a timeline with steps for the revisions appended since it was
+/// built. Only the last known revision and the new ones are materialized.
+#[instrument(skip(data, previous))]
+pub fn extend_timeline(data: &[u8], previous: &RevisionTimeline) -> RevisionTimeline {
+    let revisions = split_revisions(data);
+    let mut timeline = previous.clone();
+    timeline.revision_count = revisions.len();
+
+    let first = previous.revision_count.saturating_sub(1).min(revisions.len());
+    push_steps(data, &revisions[first..], &mut timeline);
+    debug!("Extended timeline to {} steps", timeline.steps.len());
+    timeline
+}
+
+fn push_steps(data: &[u8], revisions: &[Revision], timeline: &mut RevisionTimeline) {
+    let mut previous: Option<(usize, Document)> = None;
+    for revision in revisions {
+        let doc = match materialize(data, revision) {
+            Ok(doc) => doc,
+            Err(e) => {
+                warn!("Skipping revision {}: {}", revision.index, e);
+                timeline.errors.push(e.to_string());
+                continue;
+            }
+        };
+
+        if let Some((from, old)) = &previous {
+            timeline.steps.push(RevisionStep {
+                from: *from,
+                to: revision.index,
+                offset: revision.start,
+                size: revision.len(),
+                diff: diff_documents(old, &doc),
+            });
+        }
+        previous = Some((revision.index, doc));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_identical_documents() {
+        let doc = fixtures::document();
+        assert!(diff_documents(&doc, &doc).is_empty());
+    }
+
+    #[test]
+    fn test_revision_timeline() {
+        let base = fixtures::simple_pdf();
+        let data = fixtures::append_revision(
+            &base,
+            &[
+                (5, "<< /Type /Page /Parent 1 0 R /Contents 4 0 R /Annots [7 0 R] >>"),
+                (7, "<< /Type /Annot /Subtype /Text >>"),
+            ],
+        );
+
+        let timeline = revision_timeline(&data).unwrap();
+        assert_eq!(timeline.revision_count, 2);
+        assert_eq!(timeline.steps.len(), 1);
+
+        let diff = &timeline.steps[0].diff;
+        assert_eq!(diff.count(ChangeKind::Added), 1);
+        assert_eq!(diff.count(ChangeKind::Modified), 1);
+        assert_eq!(diff.count(ChangeKind::Removed), 0);
+        let annotation = ObjectType::Annotation { subtype: Some("Text".into()) };
+        assert!(diff.objects.iter().any(|c| c.id == (7, 0) && c.object_type == annotation));
+    }
+
+    #[test]
+    fn test_extend_timeline() {
+        let base = fixtures::simple_pdf();
+        let first = fixtures::append_revision(&base, &[(7, "<< /Type /Annot /Subtype /Text >>")]);
+        let data = fixtures::append_revision(&first, &[(8, "<< /Type /Annot /Subtype /Link >>")]);
+
+        let previous = revision_timeline(&first).unwrap();
+        let extended = extend_timeline(&data, &previous);
+        let full = revision_timeline(&data).unwrap();
+
+        assert_eq!(extended.revision_count, 3);
+        assert_eq!(extended.steps.len(), full.steps.len());
+        assert_eq!(extended.steps[1].from, 1);
+        assert_eq!(extended.steps[1].to, 2);
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let base = fixtures::simple_pdf();
+        let changed = fixtures::append_revision(
+            &base,
+            &[
+                (5, "<< /Type /Page /Parent 1 0 R /Contents 7 0 R >>"),
+                (7, "<< /Length 18 >>\nstream\nBT (Changed) Tj ET\nendstream"),
+            ],
+        );
+
+        let diff = diff_files(&base, &changed).unwrap();
+        assert!(!diff.is_empty());
+        assert_eq!(diff.after_size, changed.len());
+        assert_eq!(diff.diff.count(ChangeKind::Added), 1);
+        assert_eq!(diff.diff.count(ChangeKind::Modified), 1);
+        assert_eq!(diff.diff.pages[0].kind, ChangeKind::Modified);
+        assert!(diff.signatures.is_empty());
+        assert!(diff_files(&base, &base).unwrap().is_empty());
+        assert!(diff_files(&base, b"not a pdf").is_err());
+    }
+
+    #[test]
+    fn test_metadata_change() {
+        let base = fixtures::simple_pdf();
+        let first = fixtures::append_revision_with_trailer(
+            &base,
+            &[(8, "<< /Producer (Writer A) >>")],
+            "/Info 8 0 R ",
+        );
+        let second = fixtures::append_revision_with_trailer(
+            &first,
+            &[(8, "<< /Producer (Writer B) >>")],
+            "/Info 8 0 R ",
+        );
+
+        let timeline = revision_timeline(&second).unwrap();
+        let change = &timeline.steps[1].diff.metadata[0];
+        assert_eq!(change.key, "Producer");
+        assert_eq!(change.before.as_deref(), Some("Writer A"));
+        assert_eq!(change.after.as_deref(), Some("Writer B"));
+    }
+}
@@ -0,0 +1,174 @@
+//! Stream dumping for external tools
+//! Created: 2026-10-16 10:51:37 UTC
+//! Author: kartik4091
+//!
+//! Writes every stream of a document to a directory, one file per object,
+//! so strings, disassemblers and antivirus engines can be run over the
+//! payloads. Streams are decoded on request; a stream whose filters fail is
+//! written as stored and the failure recorded rather than skipped. A
+//! `manifest.json` beside the files maps each one back to its object.
+
+use std::path::Path;
+
+use lopdf::{Document, Object, ObjectId};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{magic, sha256_hex, stream_data, taxonomy::ObjectType};
+use crate::error::Result;
+
+/// Name of the manifest written beside the streams
+pub const MANIFEST: &str = "manifest.json";
+
+/// One stream written to disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DumpedStream {
+    /// Object number and generation
+    pub id: ObjectId,
+
+    /// Object type
+    pub object_type: ObjectType,
+
+    /// Stream filters in application order
+    pub filters: Vec<String>,
+
+    /// File name inside the output directory
+    pub file: String,
+
+    /// Whether the file holds decoded bytes
+    pub decoded: bool,
+
+    /// Bytes written
+    pub size: usize,
+
+    /// SHA-256 of the bytes written
+    pub sha256: String,
+
+    /// Format recognized from the leading bytes
+    pub detected: Option<String>,
+
+    /// Why decoding failed, when the stored bytes were written instead
+    pub error: Option<String>,
+}
+
+/// Streams written by a dump
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamManifest {
+    /// Whether decoding was requested
+    pub decode: bool,
+
+    /// Streams in object order
+    pub streams: Vec<DumpedStream>,
+}
+
+/// Writes every stream to `dir` as `<num>_<gen>.bin` (`.raw` when the
+/// bytes are stored rather than decoded) and the manifest beside them
+#[instrument(skip(doc))]
+pub async fn dump_streams(doc: &Document, dir: &Path, decode: bool) -> Result<StreamManifest> {
+    tokio::fs::create_dir_all(dir).await?;
+    let mut manifest = StreamManifest {
+        decode,
+        ..Default::default()
+    };
+    for (&id, object) in &doc.objects {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        let (data, error) = if decode {
+            match stream_data(stream) {
+                Ok(data) => (data, None),
+                Err(e) => (stream.content.clone(), Some(e.to_string())),
+            }
+        } else {
+            (stream.content.clone(), None)
+        };
+        let decoded = decode && error.is_none();
+        let file = format!("{}_{}.{}", id.0, id.1, if decoded { "bin" } else { "raw" });
+        tokio::fs::write(dir.join(&file), &data).await?;
+        manifest.streams.push(DumpedStream {
+            id,
+            object_type: ObjectType::of(object),
+            filters: stream.filters().unwrap_or_default(),
+            file,
+            decoded,
+            size: data.len(),
+            sha256: sha256_hex(&data),
+            detected: magic::identify(&data).map(|m| m.description.to_string()),
+            error,
+        });
+    }
+    tokio::fs::write(dir.join(MANIFEST), serde_json::to_vec_pretty(&manifest)?).await?;
+    debug!("Dumped {} streams to {}", manifest.streams.len(), dir.display());
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    use flate2::{write::ZlibEncoder, Compression};
+    use lopdf::{dictionary, Stream};
+
+    fn payload() -> Vec<u8> {
+        let mut payload = b"MZ\x90\x00".to_vec();
+        payload.resize(512, 0);
+        payload
+    }
+
+    fn document() -> (Document, ObjectId, ObjectId) {
+        let mut doc = crate::document::fixtures::document();
+        let mut flate = Stream::new(dictionary! {}, payload());
+        flate.compress().unwrap();
+        let flate = doc.add_object(flate);
+        // A nested bomb the decoding limits refuse
+        let deflate = |data: &[u8]| {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        };
+        let bomb = deflate(&deflate(&vec![0u8; 8 * 1024 * 1024]));
+        let broken = doc.add_object(Stream::new(
+            dictionary! { "Filter" => vec![Object::Name(b"FlateDecode".to_vec()), Object::Name(b"FlateDecode".to_vec())] },
+            bomb,
+        ));
+        (doc, flate, broken)
+    }
+
+    #[tokio::test]
+    async fn test_dump_decoded() {
+        let (doc, flate, broken) = document();
+        let dir = tempfile::tempdir().unwrap();
+
+        let manifest = dump_streams(&doc, dir.path(), true).await.unwrap();
+        assert_eq!(manifest.streams.len(), 3);
+        let dumped = manifest.streams.iter().find(|s| s.id == flate).unwrap();
+        assert!(dumped.decoded && dumped.error.is_none());
+        assert_eq!(dumped.filters, vec!["FlateDecode".to_string()]);
+        assert_eq!(dumped.detected.as_deref(), Some("Windows PE executable"));
+        let written = std::fs::read(dir.path().join(&dumped.file)).unwrap();
+        assert_eq!(written, payload());
+        assert_eq!(dumped.sha256, sha256_hex(&written));
+
+        let failed = manifest.streams.iter().find(|s| s.id == broken).unwrap();
+        assert!(!failed.decoded && failed.error.is_some());
+        assert_eq!(failed.file, format!("{}_{}.raw", broken.0, broken.1));
+
+        let on_disk: StreamManifest =
+            serde_json::from_slice(&std::fs::read(dir.path().join(MANIFEST)).unwrap()).unwrap();
+        assert_eq!(on_disk, manifest);
+    }
+
+    #[tokio::test]
+    async fn test_dump_stored() {
+        let (doc, flate, _) = document();
+        let dir = tempfile::tempdir().unwrap();
+
+        let manifest = dump_streams(&doc, &dir.path().join("streams"), false).await.unwrap();
+        let dumped = manifest.streams.iter().find(|s| s.id == flate).unwrap();
+        assert!(!dumped.decoded && dumped.error.is_none());
+        let written = std::fs::read(dir.path().join("streams").join(&dumped.file)).unwrap();
+        assert!(written.len() < payload().len());
+        assert!(dumped.detected.is_none());
+    }
+}
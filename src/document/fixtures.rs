@@ -0,0 +1,205 @@
+//! Synthetic PDF fixtures shared by unit tests
+//! Created: 2026-10-16 08:38:51 UTC
+//! Author: kartik4091
+
+use super::revisions::find_startxref;
+use lopdf::{
+    content::{Content, Operation},
+    dictionary, Dictionary, Document, Object, ObjectId, Stream,
+};
+
+/// Object number of the single page in [`document`]
+pub const PAGE_ID: ObjectId = (5, 0);
+
+/// Object number of the catalog in [`document`]
+pub const CATALOG_ID: ObjectId = (6, 0);
+
+/// Builds a minimal one-page document with a Helvetica "Hello World!" line.
+///
+/// Object layout: 1 Pages, 2 Font, 3 Resources, 4 Contents, 5 Page, 6 Catalog.
+pub fn document() -> Document {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+    let content = Content {
+        operations: vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 48.into()]),
+            Operation::new("Td", vec![100.into(), 600.into()]),
+            Operation::new("Tj", vec![Object::string_literal("Hello World!")]),
+            Operation::new("ET", vec![]),
+        ],
+    };
+    let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+    });
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc
+}
+
+/// Adds an interactive form dictionary and links it from the catalog
+pub fn with_form(doc: &mut Document, form: Dictionary) {
+    let form = doc.add_object(form);
+    doc.get_dictionary_mut(CATALOG_ID).unwrap().set("AcroForm", form);
+}
+
+/// Builds [`document`] with `content` as its page's content stream
+pub fn with_content(content: &[u8]) -> Document {
+    let mut doc = document();
+    let contents = doc.add_object(Stream::new(dictionary! {}, content.to_vec()));
+    doc.get_dictionary_mut(PAGE_ID).unwrap().set("Contents", contents);
+    doc
+}
+
+/// Serializes a document with lopdf's writer (classic xref table)
+pub fn save(mut doc: Document) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    buffer
+}
+
+/// Serialized form of [`document`]
+pub fn simple_pdf() -> Vec<u8> {
+    save(document())
+}
+
+/// Appends an incremental update containing the given `(object number, body)`
+/// pairs, with a classic xref section chained to the previous one via /Prev.
+pub fn append_revision(base: &[u8], objects: &[(u32, &str)]) -> Vec<u8> {
+    append_revision_with_trailer(base, objects, "")
+}
+
+/// Like [`append_revision`] but with extra raw trailer entries
+pub fn append_revision_with_trailer(base: &[u8], objects: &[(u32, &str)], extra: &str) -> Vec<u8> {
+    let previous = Document::load_mem(base).unwrap();
+    let prev_xref = find_startxref(base).expect("fixture has startxref");
+    let root = previous.trailer.get(b"Root").unwrap().as_reference().unwrap();
+    let size = objects
+        .iter()
+        .map(|(num, _)| num + 1)
+        .chain(std::iter::once(previous.max_id + 1))
+        .max()
+        .unwrap();
+
+    let mut out = base.to_vec();
+    if !out.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+
+    let mut offsets = Vec::new();
+    for (num, body) in objects {
+        offsets.push((*num, out.len()));
+        out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", num, body).as_bytes());
+    }
+
+    let xref_offset = out.len();
+    out.extend_from_slice(b"xref\n");
+    for (num, offset) in &offsets {
+        out.extend_from_slice(format!("{} 1\n{:010} 00000 n \n", num, offset).as_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root {} {} R /Prev {} {}>>\nstartxref\n{}\n%%EOF\n",
+            size, root.0, root.1, prev_xref, extra, xref_offset
+        )
+        .as_bytes(),
+    );
+    out
+}
+
+/// Simple TrueType glyph with one closed contour of on-curve points
+pub fn glyph(points: &[(i16, i16)], instructions: &[u8]) -> Vec<u8> {
+    let xs = points.iter().map(|p| p.0);
+    let ys = points.iter().map(|p| p.1);
+    let mut out = Vec::new();
+    out.extend_from_slice(&1i16.to_be_bytes());
+    for bound in [xs.clone().min(), ys.clone().min(), xs.max(), ys.max()] {
+        out.extend_from_slice(&bound.unwrap_or(0).to_be_bytes());
+    }
+    out.extend_from_slice(&(points.len() as u16 - 1).to_be_bytes());
+    out.extend_from_slice(&(instructions.len() as u16).to_be_bytes());
+    out.extend_from_slice(instructions);
+    out.extend(std::iter::repeat(0x01).take(points.len()));
+    for axis in [0, 1] {
+        let mut previous = 0;
+        for point in points {
+            let value = if axis == 0 { point.0 } else { point.1 };
+            out.extend_from_slice(&(value - previous).to_be_bytes());
+            previous = value;
+        }
+    }
+    if out.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+/// TrueType program with an empty .notdef followed by `glyphs`, which a
+/// (3,1) cmap maps to the characters from 'A' on
+pub fn truetype(glyphs: &[Vec<u8>]) -> Vec<u8> {
+    let mut glyf = Vec::new();
+    let mut loca = vec![0u32];
+    for glyph in glyphs {
+        glyf.extend_from_slice(glyph);
+        loca.push(glyf.len() as u32);
+    }
+    let loca: Vec<u8> = std::iter::once(0u32).chain(loca).flat_map(u32::to_be_bytes).collect();
+
+    let mut head = vec![0u8; 54];
+    head[50..52].copy_from_slice(&1u16.to_be_bytes());
+
+    let last = b'A' as u16 + glyphs.len() as u16 - 1;
+    let delta = 1u16.wrapping_sub(b'A' as u16);
+    let mut subtable = Vec::new();
+    for value in [4u16, 32, 0, 4, 4, 1, 0, last, 0xFFFF, 0, b'A' as u16, 0xFFFF, delta, 1, 0, 0] {
+        subtable.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut cmap = Vec::new();
+    for value in [0u16, 1, 3, 1] {
+        cmap.extend_from_slice(&value.to_be_bytes());
+    }
+    cmap.extend_from_slice(&12u32.to_be_bytes());
+    cmap.extend_from_slice(&subtable);
+
+    let tables: [(&[u8; 4], Vec<u8>); 4] = [(b"cmap", cmap), (b"glyf", glyf), (b"head", head), (b"loca", loca)];
+    let mut out = Vec::new();
+    out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    out.extend_from_slice(&(tables.len() as u16).to_be_bytes());
+    out.extend_from_slice(&[0; 6]);
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, table) in &tables {
+        out.extend_from_slice(&tag[..]);
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len();
+    }
+    for (_, table) in tables {
+        out.extend_from_slice(&table);
+    }
+    out
+}
@@ -0,0 +1,312 @@
+//! Indirect-reference graph of a document
+//! Created: 2026-10-16 10:45:03 UTC
+//! Author: kartik4091
+//!
+//! Every object in the effective cross-reference becomes a node and every
+//! indirect reference an edge labelled with the entry holding it, so the
+//! whole structure can be handed to graph tooling. Nodes record whether the
+//! trailer reaches them, and findings can be attached afterwards so the
+//! objects behind them stand out. References to objects the file does not
+//! contain are kept as dangling edges. The revision history can be added
+//! from the file bytes: which incremental update wrote each object, as
+//! recorded by the cross-reference sections.
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{
+    collect_references,
+    revisions::split_revisions,
+    shannon_entropy, stream_data,
+    taxonomy::ObjectType,
+    xref::{read_sections, XrefState},
+};
+use crate::detectors::{orphans::reachable, Finding, Severity};
+
+/// An object of the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Object number and generation
+    pub id: ObjectId,
+
+    /// Object type
+    pub kind: ObjectType,
+
+    /// Stored length for streams
+    pub length: Option<usize>,
+
+    /// Shannon entropy of stream data, decoded when the filters allow
+    pub entropy: Option<f64>,
+
+    /// Revision that last wrote the object, once history is added
+    pub revision: Option<usize>,
+
+    /// Whether the trailer leads to the object
+    pub reachable: bool,
+
+    /// Findings naming the object
+    pub findings: usize,
+
+    /// Highest severity among those findings
+    pub severity: Option<Severity>,
+}
+
+/// A reference from one object to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    /// Referencing object
+    pub from: ObjectId,
+
+    /// Referenced object
+    pub to: ObjectId,
+
+    /// Entry holding the reference, such as `/Pages` or `[2]`
+    pub key: String,
+
+    /// Whether the referenced object is missing from the file
+    pub dangling: bool,
+}
+
+/// An incremental update of the file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphRevision {
+    /// Zero-based revision index in append order
+    pub index: usize,
+
+    /// Offset of the first byte written by the revision
+    pub start: usize,
+
+    /// Bytes appended by the revision
+    pub size: usize,
+
+    /// Objects its cross-reference sections record as written
+    pub written: Vec<ObjectId>,
+}
+
+/// Objects and references of a document
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ObjectGraph {
+    /// Objects, ordered by number
+    pub nodes: Vec<GraphNode>,
+
+    /// References between objects
+    pub edges: Vec<GraphEdge>,
+
+    /// References held by the trailer
+    pub roots: Vec<(String, ObjectId)>,
+
+    /// Revision history, oldest first; empty until added
+    pub revisions: Vec<GraphRevision>,
+}
+
+impl ObjectGraph {
+    /// Counts the findings naming each object and keeps their highest
+    /// severity
+    pub fn annotate(&mut self, findings: &[Finding]) {
+        for finding in findings {
+            for id in &finding.objects {
+                if let Ok(index) = self.nodes.binary_search_by_key(id, |node| node.id) {
+                    let node = &mut self.nodes[index];
+                    node.findings += 1;
+                    node.severity = node.severity.max(Some(finding.severity));
+                }
+            }
+        }
+    }
+
+    /// Adds the revision history of the file the graph was built from
+    pub fn add_history(&mut self, data: &[u8]) {
+        self.revisions = history(data);
+        for revision in &self.revisions {
+            for id in &revision.written {
+                if let Ok(index) = self.nodes.binary_search_by_key(id, |node| node.id) {
+                    let node = &mut self.nodes[index];
+                    node.revision = node.revision.max(Some(revision.index));
+                }
+            }
+        }
+    }
+
+    /// Node of an object
+    pub fn node(&self, id: ObjectId) -> Option<&GraphNode> {
+        self.nodes
+            .binary_search_by_key(&id, |node| node.id)
+            .ok()
+            .map(|index| &self.nodes[index])
+    }
+}
+
+/// Builds the reference graph of a document
+#[instrument(skip(doc))]
+pub fn build(doc: &Document) -> ObjectGraph {
+    let reachable = reachable(doc);
+    let mut graph = ObjectGraph {
+        roots: references(&doc.trailer),
+        ..Default::default()
+    };
+    for (&id, object) in &doc.objects {
+        graph.nodes.push(GraphNode {
+            id,
+            kind: ObjectType::of(object),
+            length: match object {
+                Object::Stream(stream) => Some(stream.content.len()),
+                _ => None,
+            },
+            entropy: match object {
+                Object::Stream(stream) => Some(shannon_entropy(
+                    &stream_data(stream).unwrap_or_else(|_| stream.content.clone()),
+                )),
+                _ => None,
+            },
+            revision: None,
+            reachable: reachable.contains(&id),
+            findings: 0,
+            severity: None,
+        });
+        graph
+            .edges
+            .extend(outgoing(object).into_iter().map(|(key, to)| GraphEdge {
+                from: id,
+                to,
+                key,
+                dangling: !doc.objects.contains_key(&to),
+            }));
+    }
+    debug!(
+        "Reference graph with {} nodes and {} edges",
+        graph.nodes.len(),
+        graph.edges.len()
+    );
+    graph
+}
+
+/// Revisions of a file with the objects each one wrote. Files whose
+/// cross-reference cannot be read get revisions without written objects.
+pub fn history(data: &[u8]) -> Vec<GraphRevision> {
+    let mut revisions: Vec<GraphRevision> = split_revisions(data)
+        .iter()
+        .map(|revision| GraphRevision {
+            index: revision.index,
+            start: revision.start,
+            size: revision.len(),
+            written: Vec::new(),
+        })
+        .collect();
+    let sections = read_sections(data).unwrap_or_default();
+    for section in &sections {
+        let Some(revision) = revisions
+            .iter_mut()
+            .find(|r| (section.offset as usize) < r.start + r.size)
+        else {
+            continue;
+        };
+        for record in &section.entries {
+            let id = match record.state {
+                XrefState::InUse { generation, .. } => (record.number, generation as u16),
+                XrefState::Compressed { .. } => (record.number, 0),
+                XrefState::Free { .. } => continue,
+            };
+            if !revision.written.contains(&id) {
+                revision.written.push(id);
+            }
+        }
+    }
+    for revision in &mut revisions {
+        revision.written.sort_unstable();
+    }
+    revisions
+}
+
+/// References held by an object, labelled with the entry or array index
+/// holding them
+pub fn outgoing(object: &Object) -> Vec<(String, ObjectId)> {
+    match object {
+        Object::Dictionary(dict) => references(dict),
+        Object::Stream(stream) => references(&stream.dict),
+        Object::Array(items) => items
+            .iter()
+            .enumerate()
+            .flat_map(|(index, item)| {
+                let mut ids = Vec::new();
+                collect_references(item, &mut ids);
+                ids.into_iter().map(move |id| (format!("[{}]", index), id))
+            })
+            .collect(),
+        Object::Reference(id) => vec![(String::new(), *id)],
+        _ => Vec::new(),
+    }
+}
+
+/// References in a dictionary, labelled with the top-level key holding them
+pub fn references(dict: &Dictionary) -> Vec<(String, ObjectId)> {
+    let mut found = Vec::new();
+    for (key, value) in dict.iter() {
+        let mut ids = Vec::new();
+        collect_references(value, &mut ids);
+        let key = String::from_utf8_lossy(key);
+        found.extend(ids.into_iter().map(|id| (format!("/{}", key), id)));
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        detectors::RiskCategory,
+        document::fixtures::{self, CATALOG_ID, PAGE_ID},
+    };
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_nodes_and_edges() {
+        let mut doc = fixtures::document();
+        let orphan = doc.add_object(dictionary! { "Type" => "Action", "S" => "JavaScript", "Next" => (99, 0) });
+
+        let graph = build(&doc);
+        assert_eq!(graph.nodes.len(), doc.objects.len());
+        assert_eq!(graph.roots, vec![("/Root".to_string(), CATALOG_ID)]);
+        assert!(graph.node(PAGE_ID).unwrap().reachable);
+        assert!(!graph.node(orphan).unwrap().reachable);
+        assert!(graph
+            .edges
+            .iter()
+            .any(|e| e.from == CATALOG_ID && e.key == "/Pages" && !e.dangling));
+        let dangling: Vec<_> = graph.edges.iter().filter(|e| e.dangling).collect();
+        assert_eq!(dangling.len(), 1);
+        assert_eq!((dangling[0].from, dangling[0].to), (orphan, (99, 0)));
+    }
+
+    #[test]
+    fn test_annotate_with_findings() {
+        let mut graph = build(&fixtures::document());
+        let finding = |severity| {
+            let mut finding = Finding::new("test", severity, RiskCategory::Structure, "Test");
+            finding.objects.push(PAGE_ID);
+            finding
+        };
+        graph.annotate(&[finding(Severity::Low), finding(Severity::High)]);
+
+        let page = graph.node(PAGE_ID).unwrap();
+        assert_eq!((page.findings, page.severity), (2, Some(Severity::High)));
+        assert_eq!(graph.node(CATALOG_ID).unwrap().findings, 0);
+    }
+
+    #[test]
+    fn test_revision_history() {
+        let data = fixtures::append_revision(
+            &fixtures::simple_pdf(),
+            &[(7, "<< /Type /Action /S /JavaScript /JS (app.alert(1)) >>")],
+        );
+        let mut graph = build(&Document::load_mem(&data).unwrap());
+        graph.add_history(&data);
+
+        assert_eq!(graph.revisions.len(), 2);
+        assert!(graph.revisions[0].written.contains(&CATALOG_ID));
+        assert_eq!(graph.revisions[1].written, vec![(7, 0)]);
+        assert_eq!(graph.node((7, 0)).unwrap().revision, Some(1));
+        assert_eq!(graph.node(PAGE_ID).unwrap().revision, Some(0));
+        assert!(graph.node((4, 0)).unwrap().entropy.unwrap() > 0.0);
+    }
+}
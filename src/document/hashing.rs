@@ -0,0 +1,226 @@
+//! Selectable hash backends
+//! Created: 2026-10-16 09:15:26 UTC
+//! Author: kartik4091
+//!
+//! SHA-256 identifies files throughout the reports, but intelligence feeds
+//! still key samples by SHA-1 or MD5 and bulk triage benefits from BLAKE3's
+//! speed. The algorithm for the reported file digest is chosen with
+//! `AnalysisOptions::hash` (`--hash` on the command line).
+//!
+//! Every backend hashes incrementally, so large inputs never need to be
+//! held in memory in full just to be hashed: readers are consumed chunk by
+//! chunk, and Flate streams are inflated straight into the hasher, within
+//! the resource limits in force.
+
+use std::{fmt, io::Read, str::FromStr};
+
+use flate2::read::ZlibDecoder;
+use lopdf::Stream;
+use md5::Md5;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use super::{name_value, stream_data, to_hex};
+use crate::{
+    error::{Error, Result},
+    limits,
+};
+
+/// Read size used when hashing readers and decoded streams
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Supported hash algorithms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum HashAlgorithm {
+    /// SHA-256, used for file identity
+    #[default]
+    Sha256,
+
+    /// BLAKE3, for fast bulk hashing
+    Blake3,
+
+    /// SHA-1, for matching legacy intelligence
+    Sha1,
+
+    /// MD5, for matching legacy intelligence
+    Md5,
+}
+
+/// Incremental hash computation
+pub trait HashBackend: Send {
+    /// Feeds more input
+    fn update(&mut self, data: &[u8]);
+
+    /// Lowercase hexadecimal digest of everything fed so far
+    fn finalize_hex(self: Box<Self>) -> String;
+}
+
+macro_rules! digest_backend {
+    ($ty:ty) => {
+        impl HashBackend for $ty {
+            fn update(&mut self, data: &[u8]) {
+                Digest::update(self, data);
+            }
+
+            fn finalize_hex(self: Box<Self>) -> String {
+                to_hex(&Digest::finalize(*self))
+            }
+        }
+    };
+}
+
+digest_backend!(Sha256);
+digest_backend!(Sha1);
+digest_backend!(Md5);
+
+impl HashBackend for blake3::Hasher {
+    fn update(&mut self, data: &[u8]) {
+        blake3::Hasher::update(self, data);
+    }
+
+    fn finalize_hex(self: Box<Self>) -> String {
+        self.finalize().to_hex().to_string()
+    }
+}
+
+impl HashAlgorithm {
+    /// Fresh incremental hasher
+    pub fn hasher(self) -> Box<dyn HashBackend> {
+        match self {
+            Self::Sha256 => Box::new(Sha256::new()),
+            Self::Blake3 => Box::new(blake3::Hasher::new()),
+            Self::Sha1 => Box::new(Sha1::new()),
+            Self::Md5 => Box::new(Md5::new()),
+        }
+    }
+
+    /// Hex digest of a buffer
+    pub fn digest_hex(self, data: &[u8]) -> String {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize_hex()
+    }
+
+    /// Hex digest of everything a reader yields, read in chunks
+    pub fn digest_reader(self, reader: impl Read) -> Result<String> {
+        Ok(self.digest_chunks(reader)?.0)
+    }
+
+    /// Hex digest of a stream's decoded data.
+    ///
+    /// Plain Flate streams are inflated chunk by chunk into the hasher, so
+    /// the decoded data is never buffered; other filter chains are decoded
+    /// in memory first. Either way decoding stops at the limits in force
+    /// (see [`limits::scope`]).
+    pub fn stream_digest(self, stream: &Stream) -> Result<String> {
+        let plain_flate = matches!(name_value(&stream.dict, b"Filter"), Some(b"FlateDecode" | b"Fl"))
+            && stream.dict.get(b"DecodeParms").is_err();
+        if !plain_flate {
+            return Ok(self.digest_hex(&stream_data(stream)?));
+        }
+
+        let limits = limits::active();
+        let stored = stream.content.len().max(1) as u64;
+        let cap = limits.max_decoded_size.min(stored.saturating_mul(limits.max_expansion_ratio));
+        let inflated = ZlibDecoder::new(stream.content.as_slice()).take(cap.saturating_add(1));
+        let (digest, size) = self
+            .digest_chunks(inflated)
+            .map_err(|e| Error::Pdf(format!("Failed to decode stream: {}", e)))?;
+        if size > cap {
+            return Err(Error::Security(format!(
+                "Stream decodes to more than the limit of {} bytes, likely a decompression bomb",
+                cap
+            )));
+        }
+        Ok(digest)
+    }
+
+    /// Digest and length of everything a reader yields
+    fn digest_chunks(self, mut reader: impl Read) -> std::io::Result<(String, u64)> {
+        let mut hasher = self.hasher();
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let mut total = 0;
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                return Ok((hasher.finalize_hex(), total));
+            }
+            hasher.update(&buffer[..read]);
+            total += read as u64;
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Sha256 => "sha256",
+            Self::Blake3 => "blake3",
+            Self::Sha1 => "sha1",
+            Self::Md5 => "md5",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "blake3" => Ok(Self::Blake3),
+            "sha1" => Ok(Self::Sha1),
+            "md5" => Ok(Self::Md5),
+            other => Err(format!("Unknown hash algorithm: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::ResourceLimits;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(
+            HashAlgorithm::Sha256.digest_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(HashAlgorithm::Sha1.digest_hex(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(HashAlgorithm::Md5.digest_hex(b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            HashAlgorithm::Blake3.digest_hex(b"abc"),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert_eq!("SHA-256".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha256);
+    }
+
+    #[test]
+    fn test_streaming_matches_buffered() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        for algorithm in [HashAlgorithm::Sha256, HashAlgorithm::Blake3, HashAlgorithm::Md5] {
+            assert_eq!(algorithm.digest_reader(data.as_slice()).unwrap(), algorithm.digest_hex(&data));
+        }
+
+        let mut stream = Stream::new(dictionary! {}, data.clone());
+        stream.compress().unwrap();
+        assert_eq!(name_value(&stream.dict, b"Filter"), Some(&b"FlateDecode"[..]));
+        assert_eq!(HashAlgorithm::Blake3.stream_digest(&stream).unwrap(), HashAlgorithm::Blake3.digest_hex(&data));
+    }
+
+    #[tokio::test]
+    async fn test_stream_digest_stops_at_limits() {
+        let mut stream = Stream::new(dictionary! {}, vec![0u8; 1024 * 1024]);
+        stream.compress().unwrap();
+
+        let strict = ResourceLimits { max_decoded_size: 64 * 1024, ..Default::default() };
+        let digest = limits::scope(strict, async { HashAlgorithm::Sha256.stream_digest(&stream) }).await;
+        assert!(matches!(digest, Err(Error::Security(_))));
+        assert!(HashAlgorithm::Sha256.stream_digest(&stream).is_ok());
+    }
+}
@@ -0,0 +1,309 @@
+//! Single-object inspection
+//! Created: 2026-10-16 10:48:21 UTC
+//! Author: kartik4091
+//!
+//! Everything needed to look at one object by hand: its body
+//! pretty-printed, raw and decoded stream bytes, the references it holds
+//! and the objects referencing it, where the cross-reference places it, and
+//! the revisions that wrote it. Stream bytes are kept up to a caller-chosen
+//! limit so a large stream does not flood the terminal.
+
+use std::fmt::Write;
+
+use base64::Engine;
+use lopdf::{Document, Object, ObjectId};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    graph::{history, outgoing},
+    stream_data,
+    taxonomy::ObjectType,
+    xref::{effective_entries, read_sections, XrefState},
+};
+use crate::error::{Error, Result};
+
+/// Arrays with more items than this are printed one item per line
+const INLINE_ITEMS: usize = 8;
+
+/// One object, laid out for manual analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectInspection {
+    /// Object number and generation
+    pub id: ObjectId,
+
+    /// Object type
+    pub object_type: ObjectType,
+
+    /// Entry the effective cross-reference holds for the object
+    pub location: Option<XrefState>,
+
+    /// Revisions whose cross-reference wrote the object, oldest first; the
+    /// first one introduced it
+    pub revisions: Vec<usize>,
+
+    /// Object body in PDF syntax, with stream data left out
+    pub body: String,
+
+    /// Stream filters in application order
+    pub filters: Vec<String>,
+
+    /// Stored stream length
+    pub raw_length: Option<usize>,
+
+    /// Base64 of the stored stream bytes, up to the limit
+    pub raw: Option<String>,
+
+    /// Decoded stream length
+    pub decoded_length: Option<usize>,
+
+    /// Base64 of the decoded stream bytes, up to the limit
+    pub decoded: Option<String>,
+
+    /// Why the stream could not be decoded
+    pub decode_error: Option<String>,
+
+    /// References held by the object, with the entry holding each
+    pub references: Vec<(String, ObjectId)>,
+
+    /// Objects referencing this one, with the entry holding the reference
+    pub referenced_by: Vec<(ObjectId, String)>,
+}
+
+/// Parses an object reference written `N`, `N:G` or `N G`
+pub fn parse_object_id(s: &str) -> std::result::Result<(u32, Option<u16>), String> {
+    let s = s.trim().trim_end_matches(" R");
+    let (number, generation) = match s.split_once([':', ' ']) {
+        Some((number, generation)) => (number, Some(generation.trim())),
+        None => (s, None),
+    };
+    let number = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid object number: {}", number))?;
+    let generation = generation
+        .map(|g| g.parse().map_err(|_| format!("Invalid generation: {}", g)))
+        .transpose()?;
+    Ok((number, generation))
+}
+
+/// Inspects an object; without a generation, the lowest present one is
+/// taken. `data` is the file the document was loaded from.
+pub fn inspect(
+    doc: &Document,
+    data: &[u8],
+    number: u32,
+    generation: Option<u16>,
+    limit: usize,
+) -> Result<ObjectInspection> {
+    let (&id, object) = doc
+        .objects
+        .range((number, generation.unwrap_or(0))..=(number, generation.unwrap_or(u16::MAX)))
+        .next()
+        .ok_or_else(|| match generation {
+            Some(generation) => Error::Analysis(format!("Object {} {} not found", number, generation)),
+            None => Error::Analysis(format!("Object {} not found", number)),
+        })?;
+
+    let location = read_sections(data)
+        .ok()
+        .and_then(|sections| effective_entries(&sections).remove(&id.0));
+    let revisions = history(data)
+        .into_iter()
+        .filter(|revision| revision.written.contains(&id))
+        .map(|revision| revision.index)
+        .collect();
+    let referenced_by = doc
+        .objects
+        .iter()
+        .flat_map(|(&from, other)| {
+            outgoing(other)
+                .into_iter()
+                .filter(|(_, to)| *to == id)
+                .map(move |(key, _)| (from, key))
+        })
+        .collect();
+
+    let mut inspection = ObjectInspection {
+        id,
+        object_type: ObjectType::of(object),
+        location,
+        revisions,
+        body: pretty(object, 0),
+        filters: Vec::new(),
+        raw_length: None,
+        raw: None,
+        decoded_length: None,
+        decoded: None,
+        decode_error: None,
+        references: outgoing(object),
+        referenced_by,
+    };
+    if let Object::Stream(stream) = object {
+        let head = |bytes: &[u8]| base64::engine::general_purpose::STANDARD.encode(&bytes[..bytes.len().min(limit)]);
+        inspection.filters = stream.filters().unwrap_or_default();
+        inspection.raw_length = Some(stream.content.len());
+        inspection.raw = Some(head(&stream.content));
+        match stream_data(stream) {
+            Ok(decoded) => {
+                inspection.decoded_length = Some(decoded.len());
+                inspection.decoded = Some(head(&decoded));
+            }
+            Err(e) => inspection.decode_error = Some(e.to_string()),
+        }
+    }
+    Ok(inspection)
+}
+
+/// Object in PDF syntax, dictionaries one entry per line
+pub fn pretty(object: &Object, indent: usize) -> String {
+    let pad = "  ".repeat(indent);
+    match object {
+        Object::Null => "null".into(),
+        Object::Boolean(b) => b.to_string(),
+        Object::Integer(i) => i.to_string(),
+        Object::Real(r) => r.to_string(),
+        Object::Name(name) => name_token(name),
+        Object::String(bytes, _) => string_token(bytes),
+        Object::Reference((number, generation)) => format!("{} {} R", number, generation),
+        Object::Array(items) if items.len() <= INLINE_ITEMS && items.iter().all(is_scalar) => {
+            let items: Vec<String> = items.iter().map(|item| pretty(item, indent)).collect();
+            format!("[{}]", items.join(" "))
+        }
+        Object::Array(items) => {
+            let mut out = "[\n".to_string();
+            for item in items {
+                let _ = writeln!(out, "{}  {}", pad, pretty(item, indent + 1));
+            }
+            out + &pad + "]"
+        }
+        Object::Dictionary(dict) => {
+            let mut out = "<<\n".to_string();
+            for (key, value) in dict.iter() {
+                let _ = writeln!(out, "{}  {} {}", pad, name_token(key), pretty(value, indent + 1));
+            }
+            out + &pad + ">>"
+        }
+        Object::Stream(stream) => format!(
+            "{}\nstream ({} bytes)",
+            pretty(&Object::Dictionary(stream.dict.clone()), indent),
+            stream.content.len()
+        ),
+    }
+}
+
+/// Classic hex dump: offset, sixteen bytes, printable ASCII. Offsets start
+/// at `base`, the position of `data` in its file.
+pub fn hex_dump(data: &[u8], base: usize) -> String {
+    let mut out = String::new();
+    for (line, chunk) in data.chunks(16).enumerate() {
+        let _ = write!(out, "{:08x} ", base + line * 16);
+        for index in 0..16 {
+            if index % 8 == 0 {
+                out.push(' ');
+            }
+            match chunk.get(index) {
+                Some(byte) => {
+                    let _ = write!(out, "{:02x} ", byte);
+                }
+                None => out.push_str("   "),
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        let _ = writeln!(out, " |{}|", ascii);
+    }
+    out
+}
+
+fn is_scalar(object: &Object) -> bool {
+    !matches!(object, Object::Array(_) | Object::Dictionary(_) | Object::Stream(_))
+}
+
+fn name_token(name: &[u8]) -> String {
+    let mut token = "/".to_string();
+    for &b in name {
+        if b.is_ascii_graphic() && !b"()<>[]{}/%#".contains(&b) {
+            token.push(b as char);
+        } else {
+            let _ = write!(token, "#{:02X}", b);
+        }
+    }
+    token
+}
+
+/// Literal string when printable, hex string otherwise
+fn string_token(bytes: &[u8]) -> String {
+    if bytes.iter().all(|&b| b.is_ascii_graphic() || b == b' ') {
+        let escaped = String::from_utf8_lossy(bytes)
+            .replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)");
+        format!("({})", escaped)
+    } else {
+        let hex: String = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        format!("<{}>", hex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures::{self, CATALOG_ID, PAGE_ID};
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!(parse_object_id("12"), Ok((12, None)));
+        assert_eq!(parse_object_id("12:3"), Ok((12, Some(3))));
+        assert_eq!(parse_object_id("12 0 R"), Ok((12, Some(0))));
+        assert!(parse_object_id("x").is_err());
+
+        assert_eq!(name_token(b"A B#"), "/A#20B#23");
+        assert_eq!(string_token(b"a(b)"), "(a\\(b\\))");
+        assert_eq!(string_token(&[0xfe, 0xff]), "<FEFF>");
+        let dump = hex_dump(b"%PDF-1.7\n\x00abcdefghijk", 0);
+        assert_eq!(
+            dump.lines().next().unwrap(),
+            "00000000  25 50 44 46 2d 31 2e 37  0a 00 61 62 63 64 65 66  |%PDF-1.7..abcdef|"
+        );
+        assert_eq!(dump.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_inspect_page_and_stream() {
+        let data = fixtures::simple_pdf();
+        let doc = Document::load_mem(&data).unwrap();
+
+        let page = inspect(&doc, &data, PAGE_ID.0, None, 1024).unwrap();
+        assert_eq!(page.object_type, ObjectType::Page);
+        assert!(page.body.starts_with("<<\n") && page.body.contains("  /Type /Page\n"));
+        assert!(matches!(page.location, Some(XrefState::InUse { .. })));
+        assert_eq!(page.revisions, vec![0]);
+        assert!(page
+            .references
+            .iter()
+            .any(|(key, id)| key == "/Contents" && *id == (4, 0)));
+        assert!(page
+            .referenced_by
+            .iter()
+            .any(|(id, key)| *id == (1, 0) && key == "/Kids"));
+        assert!(page.raw.is_none());
+
+        let content = inspect(&doc, &data, 4, Some(0), 4).unwrap();
+        assert_eq!(content.raw_length, content.decoded_length);
+        let raw = base64::engine::general_purpose::STANDARD
+            .decode(content.raw.unwrap())
+            .unwrap();
+        assert_eq!(raw.len(), 4);
+
+        assert!(inspect(&doc, &data, CATALOG_ID.0, Some(3), 16).is_err());
+        assert!(inspect(&doc, &data, 99, None, 16).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! JBIG2 segment headers
+//! Created: 2026-10-16 10:18:56 UTC
+//! Author: kartik4091
+//!
+//! JBIG2Decode streams use the embedded organisation of ITU-T T.88
+//! (Annex D.3): a bare sequence of segments, each a header followed by
+//! its data, with no file header and no end-of-file segment. Shared
+//! segments live in the `/JBIG2Globals` stream. Only the headers are
+//! parsed here; the region data is never decoded.
+
+use std::ops::Range;
+
+use lopdf::{Document, Object, Stream};
+
+use super::{resolve, stream_data};
+use crate::limits;
+
+/// Symbol dictionary segment type
+pub const SYMBOL_DICTIONARY: u8 = 0;
+
+/// Immediate generic region segment type, the only one allowed an
+/// unknown data length
+pub const IMMEDIATE_GENERIC_REGION: u8 = 38;
+
+/// Page information segment type
+pub const PAGE_INFORMATION: u8 = 48;
+
+/// Data length marking "unknown, scan for the end"
+pub const UNKNOWN_LENGTH: u32 = 0xFFFF_FFFF;
+
+/// A parsed segment header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// Segment number
+    pub number: u32,
+
+    /// Segment type (T.88 7.3)
+    pub kind: u8,
+
+    /// Numbers of the segments this one refers to
+    pub referred: Vec<u32>,
+
+    /// Page the segment belongs to, 0 for global segments
+    pub page: u32,
+
+    /// Declared data length
+    pub data_length: u32,
+
+    /// Offset of the header within the stream data
+    pub offset: usize,
+
+    /// Segment data, clipped to the stream
+    pub data: Range<usize>,
+}
+
+/// Segments of a stream, and where parsing had to stop
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Segments {
+    /// Segments in stream order
+    pub segments: Vec<Segment>,
+
+    /// Offset of a header or data block running past the end of the
+    /// stream, if any
+    pub truncated_at: Option<usize>,
+}
+
+/// Parses the segment headers of embedded JBIG2 data
+pub fn parse(data: &[u8]) -> Segments {
+    let mut parsed = Segments::default();
+    let mut offset = 0;
+    while offset < data.len() {
+        let Some((segment, next)) = header(data, offset) else {
+            parsed.truncated_at = Some(offset);
+            break;
+        };
+        let unknown = segment.data_length == UNKNOWN_LENGTH;
+        let overrun = segment.data.end < next;
+        parsed.segments.push(segment);
+        if unknown {
+            // The end can only be found by decoding the region
+            break;
+        }
+        if overrun {
+            parsed.truncated_at = Some(offset);
+            break;
+        }
+        offset = next;
+    }
+    parsed
+}
+
+fn header(data: &[u8], start: usize) -> Option<(Segment, usize)> {
+    let u32_at = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let number = u32_at(start)?;
+    let flags = *data.get(start + 4)?;
+    let mut at = start + 5;
+
+    // Referred-to segment count and retention flags (7.2.4)
+    let short = *data.get(at)?;
+    let count = if short >> 5 == 7 {
+        let count = (u32_at(at)? & 0x1FFF_FFFF) as usize;
+        at += 4 + (count + 1).div_ceil(8);
+        count
+    } else {
+        at += 1;
+        (short >> 5) as usize
+    };
+
+    let width = match number {
+        0..=256 => 1,
+        257..=65536 => 2,
+        _ => 4,
+    };
+    if data.len().saturating_sub(at) / width < count {
+        return None;
+    }
+    let mut referred = Vec::with_capacity(count);
+    for _ in 0..count {
+        let value = match width {
+            1 => *data.get(at)? as u32,
+            2 => u16::from_be_bytes([*data.get(at)?, *data.get(at + 1)?]) as u32,
+            _ => u32_at(at)?,
+        };
+        referred.push(value);
+        at += width;
+    }
+
+    let page = if flags & 0x40 != 0 {
+        let page = u32_at(at)?;
+        at += 4;
+        page
+    } else {
+        let page = *data.get(at)? as u32;
+        at += 1;
+        page
+    };
+    let data_length = u32_at(at)?;
+    at += 4;
+
+    let end = if data_length == UNKNOWN_LENGTH {
+        data.len()
+    } else {
+        at.saturating_add(data_length as usize)
+    };
+    let segment = Segment {
+        number,
+        kind: flags & 0x3F,
+        referred,
+        page,
+        data_length,
+        offset: start,
+        data: at..end.min(data.len()),
+    };
+    Some((segment, end))
+}
+
+/// Whether the stream's last filter is JBIG2Decode
+pub fn is_jbig2(stream: &Stream) -> bool {
+    limits::filters(&stream.dict)
+        .last()
+        .map_or(false, |f| f.as_slice() == b"JBIG2Decode")
+}
+
+/// JBIG2 data of a JBIG2Decode stream, with any filters in front of it
+/// undone
+pub fn encoded_data(stream: &Stream) -> Option<Vec<u8>> {
+    let mut filters = limits::filters(&stream.dict);
+    if filters.pop()?.as_slice() != b"JBIG2Decode" {
+        return None;
+    }
+    if filters.is_empty() {
+        return Some(stream.content.clone());
+    }
+    let mut outer = stream.clone();
+    outer
+        .dict
+        .set("Filter", Object::Array(filters.into_iter().map(Object::Name).collect()));
+    outer.dict.remove(b"DecodeParms");
+    stream_data(&outer).ok()
+}
+
+/// Parsed `/JBIG2Globals` of a JBIG2Decode stream, if it has any
+pub fn globals(doc: &Document, stream: &Stream) -> Option<Segments> {
+    globals_data(doc, stream).map(|data| parse(&data))
+}
+
+/// Decoded `/JBIG2Globals` data of a JBIG2Decode stream
+pub fn globals_data(doc: &Document, stream: &Stream) -> Option<Vec<u8>> {
+    let parms = stream.dict.get(b"DecodeParms").ok().map(|p| resolve(doc, p))?;
+    let parms = match parms {
+        Object::Array(items) => items.last().map(|p| resolve(doc, p))?,
+        other => other,
+    };
+    let globals = parms
+        .as_dict()
+        .ok()?
+        .get(b"JBIG2Globals")
+        .ok()
+        .map(|g| resolve(doc, g))?;
+    stream_data(globals.as_stream().ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a segment header with short-form referred-to segments and
+    /// a one-byte page association
+    fn segment(number: u32, kind: u8, referred: &[u8], page: u8, data: &[u8]) -> Vec<u8> {
+        let mut out = number.to_be_bytes().to_vec();
+        out.push(kind);
+        out.push((referred.len() as u8) << 5);
+        out.extend_from_slice(referred);
+        out.push(page);
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        let mut data = segment(0, SYMBOL_DICTIONARY, &[], 1, &[0; 10]);
+        data.extend(segment(1, PAGE_INFORMATION, &[], 1, &[0; 19]));
+        data.extend(segment(2, 6, &[0], 1, &[0; 4]));
+
+        let parsed = parse(&data);
+        assert_eq!(parsed.truncated_at, None);
+        assert_eq!(parsed.segments.len(), 3);
+        assert_eq!(parsed.segments[2].referred, vec![0]);
+        assert_eq!(parsed.segments[2].data.len(), 4);
+    }
+
+    #[test]
+    fn test_truncated_segment() {
+        let mut data = segment(0, SYMBOL_DICTIONARY, &[], 1, &[0; 10]);
+        data.truncate(data.len() - 4);
+        let parsed = parse(&data);
+        assert_eq!(parsed.truncated_at, Some(0));
+        assert_eq!(parsed.segments[0].data_length, 10);
+    }
+}
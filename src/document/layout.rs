@@ -0,0 +1,248 @@
+//! Byte layout of a file for annotated hex dumps
+//! Created: 2026-10-16 10:53:09 UTC
+//! Author: kartik4091
+//!
+//! Assigns every byte of the file to the structure owning it, using the
+//! coverage map of the slack-space pass: the header, each indirect object by
+//! number, xref tables and their trailers, startxref blocks, unclaimed slack
+//! and anything after the final `%%EOF`. Whitespace between structures is
+//! folded into the structure before it so a dump is not cut into one-byte
+//! pieces.
+
+use std::{fmt, str::FromStr};
+
+use lopdf::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use super::{objects::scan_objects, revisions::find};
+use crate::detectors::slack::{self, RegionKind};
+
+/// Structure owning a range of bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum SpanOwner {
+    /// `%PDF-` line and the comment lines after it
+    Header,
+
+    /// Indirect object, from `N G obj` to `endobj`
+    Object { id: ObjectId },
+
+    /// Classic xref table up to its trailer
+    XrefTable,
+
+    /// Trailer dictionary of a classic xref table
+    Trailer,
+
+    /// `startxref`, its offset and `%%EOF`
+    StartXref,
+
+    /// Bytes no structure claims
+    Slack,
+
+    /// Bytes after the final `%%EOF`
+    Trailing,
+}
+
+impl fmt::Display for SpanOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Header => f.write_str("header"),
+            Self::Object { id } => write!(f, "object {} {}", id.0, id.1),
+            Self::XrefTable => f.write_str("xref"),
+            Self::Trailer => f.write_str("trailer"),
+            Self::StartXref => f.write_str("startxref"),
+            Self::Slack => f.write_str("slack"),
+            Self::Trailing => f.write_str("after %%EOF"),
+        }
+    }
+}
+
+/// A range of bytes and its owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteSpan {
+    /// First byte
+    pub start: usize,
+
+    /// Offset just past the last byte
+    pub end: usize,
+
+    /// Owning structure
+    pub owner: SpanOwner,
+}
+
+/// Part of a file, written `START:END`, `START:`, `START+LENGTH` or
+/// `START`, with decimal or `0x` hexadecimal offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    /// First byte
+    pub start: usize,
+
+    /// Offset just past the last byte; the end of the file when absent
+    pub end: Option<usize>,
+}
+
+impl FromStr for ByteRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let offset = |text: &str| {
+            let text = text.trim();
+            match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                Some(hex) => usize::from_str_radix(hex, 16),
+                None => text.parse(),
+            }
+            .map_err(|_| format!("Invalid offset: {}", text))
+        };
+        let (start, end) = if let Some((start, length)) = s.split_once('+') {
+            let start = offset(start)?;
+            (start, Some(start.saturating_add(offset(length)?)))
+        } else if let Some((start, end)) = s.split_once(':') {
+            let end = if end.trim().is_empty() {
+                None
+            } else {
+                Some(offset(end)?)
+            };
+            (offset(start)?, end)
+        } else {
+            (offset(s)?, None)
+        };
+        if end.is_some_and(|end| end < start) {
+            return Err(format!("Range ends before it starts: {}", s));
+        }
+        Ok(Self { start, end })
+    }
+}
+
+/// Owners of every byte of the file, in file order without overlaps
+pub fn layout(data: &[u8]) -> Vec<ByteSpan> {
+    let report = slack::analyze(data);
+    let objects = scan_objects(data);
+
+    let mut spans = Vec::new();
+    let mut cursor = 0;
+    for region in &report.regions {
+        let start = region.start.max(cursor);
+        if start >= region.end {
+            continue;
+        }
+        unclaimed(&mut spans, data, cursor, start, report.analyzed);
+        match region.kind {
+            RegionKind::Header => push(&mut spans, start, region.end, SpanOwner::Header),
+            RegionKind::Object => {
+                let owner = match objects.iter().find(|raw| raw.offset == region.start) {
+                    Some(raw) => SpanOwner::Object { id: raw.id },
+                    None => SpanOwner::Slack,
+                };
+                push(&mut spans, start, region.end, owner);
+            }
+            RegionKind::XrefTable => match find(data, b"trailer", start).filter(|&pos| pos < region.end) {
+                Some(trailer) => {
+                    push(&mut spans, start, trailer, SpanOwner::XrefTable);
+                    push(&mut spans, trailer, region.end, SpanOwner::Trailer);
+                }
+                None => push(&mut spans, start, region.end, SpanOwner::XrefTable),
+            },
+            RegionKind::StartXref => push(&mut spans, start, region.end, SpanOwner::StartXref),
+        }
+        cursor = region.end;
+    }
+    unclaimed(&mut spans, data, cursor, data.len(), report.analyzed);
+    spans
+}
+
+/// Spans cut to a range of the file
+pub fn select(spans: &[ByteSpan], range: ByteRange) -> Vec<ByteSpan> {
+    let end = range.end.unwrap_or(usize::MAX);
+    spans
+        .iter()
+        .filter(|span| span.end > range.start && span.start < end)
+        .map(|span| ByteSpan {
+            start: span.start.max(range.start),
+            end: span.end.min(end),
+            owner: span.owner,
+        })
+        .collect()
+}
+
+fn push(spans: &mut Vec<ByteSpan>, start: usize, end: usize, owner: SpanOwner) {
+    if start < end {
+        spans.push(ByteSpan { start, end, owner });
+    }
+}
+
+/// Records a gap: whitespace joins the span before it, anything else is
+/// slack up to the final `%%EOF` and trailing data after it
+fn unclaimed(spans: &mut Vec<ByteSpan>, data: &[u8], start: usize, end: usize, analyzed: usize) {
+    if start >= end {
+        return;
+    }
+    if data[start..end].iter().all(u8::is_ascii_whitespace) {
+        if let Some(last) = spans.last_mut().filter(|last| last.end == start) {
+            last.end = end;
+            return;
+        }
+    }
+    push(spans, start, end.min(analyzed), SpanOwner::Slack);
+    push(spans, start.max(analyzed), end, SpanOwner::Trailing);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures::{self, PAGE_ID};
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(
+            "16:32".parse(),
+            Ok(ByteRange {
+                start: 16,
+                end: Some(32)
+            })
+        );
+        assert_eq!(
+            "0x10+0x10".parse(),
+            Ok(ByteRange {
+                start: 16,
+                end: Some(32)
+            })
+        );
+        assert_eq!("100:".parse(), Ok(ByteRange { start: 100, end: None }));
+        assert_eq!("100".parse(), Ok(ByteRange { start: 100, end: None }));
+        assert!("32:16".parse::<ByteRange>().is_err());
+        assert!("zz".parse::<ByteRange>().is_err());
+    }
+
+    #[test]
+    fn test_layout_covers_file() {
+        let mut data = fixtures::simple_pdf();
+        data.extend_from_slice(b"\nhidden payload");
+        let spans = layout(&data);
+
+        assert_eq!(spans[0].start, 0);
+        assert_eq!(spans[0].owner, SpanOwner::Header);
+        assert!(spans.windows(2).all(|pair| pair[0].end == pair[1].start));
+        assert_eq!(spans.last().unwrap().end, data.len());
+        assert_eq!(spans.last().unwrap().owner, SpanOwner::Trailing);
+        for owner in [SpanOwner::XrefTable, SpanOwner::Trailer, SpanOwner::StartXref] {
+            assert!(spans.iter().any(|span| span.owner == owner), "{}", owner);
+        }
+
+        let page = spans
+            .iter()
+            .find(|span| span.owner == SpanOwner::Object { id: PAGE_ID })
+            .unwrap();
+        assert!(data[page.start..].starts_with(b"5 0 obj"));
+        let middle = page.start + 4;
+        let cut = select(&spans, format!("{}+8", middle).parse().unwrap());
+        assert_eq!(
+            cut[0],
+            ByteSpan {
+                start: middle,
+                end: middle + 8,
+                owner: page.owner
+            }
+        );
+        assert_eq!(cut.len(), 1);
+    }
+}
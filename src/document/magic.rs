@@ -0,0 +1,112 @@
+//! Magic-byte file type identification
+//! Created: 2026-10-16 08:52:09 UTC
+//! Author: kartik4091
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Broad family of an identified format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum MagicKind {
+    /// Native executables and bytecode
+    Executable,
+
+    /// Compressed archives and containers
+    Archive,
+
+    /// Office and document formats
+    Document,
+
+    /// Raster images
+    Image,
+
+    /// Markup and script text
+    Script,
+}
+
+/// A recognized format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Magic {
+    /// MIME type
+    pub mime: &'static str,
+
+    /// Human-readable name
+    pub description: &'static str,
+
+    /// Format family
+    pub kind: MagicKind,
+}
+
+const fn magic(mime: &'static str, description: &'static str, kind: MagicKind) -> Magic {
+    Magic { mime, description, kind }
+}
+
+/// Signatures checked at the start of the data, most specific first
+const SIGNATURES: &[(&[u8], Magic)] = &[
+    (b"MZ", magic("application/vnd.microsoft.portable-executable", "Windows PE executable", MagicKind::Executable)),
+    (b"\x7fELF", magic("application/x-elf", "ELF executable", MagicKind::Executable)),
+    (b"\xcf\xfa\xed\xfe", magic("application/x-mach-binary", "Mach-O executable", MagicKind::Executable)),
+    (b"\xce\xfa\xed\xfe", magic("application/x-mach-binary", "Mach-O executable", MagicKind::Executable)),
+    (b"\xca\xfe\xba\xbe", magic("application/java-vm", "Java class or Mach-O fat binary", MagicKind::Executable)),
+    (b"\x4c\x00\x00\x00\x01\x14\x02\x00", magic("application/x-ms-shortcut", "Windows shortcut", MagicKind::Executable)),
+    (b"FWS", magic("application/x-shockwave-flash", "Flash (SWF)", MagicKind::Executable)),
+    (b"CWS", magic("application/x-shockwave-flash", "Flash (SWF, zlib)", MagicKind::Executable)),
+    (b"ZWS", magic("application/x-shockwave-flash", "Flash (SWF, LZMA)", MagicKind::Executable)),
+    (b"PK\x03\x04", magic("application/zip", "ZIP archive", MagicKind::Archive)),
+    (b"PK\x05\x06", magic("application/zip", "ZIP archive (empty)", MagicKind::Archive)),
+    (b"Rar!\x1a\x07", magic("application/vnd.rar", "RAR archive", MagicKind::Archive)),
+    (b"7z\xbc\xaf\x27\x1c", magic("application/x-7z-compressed", "7-Zip archive", MagicKind::Archive)),
+    (b"\x1f\x8b", magic("application/gzip", "gzip data", MagicKind::Archive)),
+    (b"BZh", magic("application/x-bzip2", "bzip2 data", MagicKind::Archive)),
+    (b"\xfd7zXZ\x00", magic("application/x-xz", "xz data", MagicKind::Archive)),
+    (b"MSCF", magic("application/vnd.ms-cab-compressed", "Cabinet archive", MagicKind::Archive)),
+    (b"%PDF-", magic("application/pdf", "PDF document", MagicKind::Document)),
+    (b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1", magic("application/x-ole-storage", "OLE compound document", MagicKind::Document)),
+    (b"{\\rtf", magic("application/rtf", "RTF document", MagicKind::Document)),
+    (b"\x89PNG\r\n\x1a\n", magic("image/png", "PNG image", MagicKind::Image)),
+    (b"\xff\xd8\xff", magic("image/jpeg", "JPEG image", MagicKind::Image)),
+    (b"GIF87a", magic("image/gif", "GIF image", MagicKind::Image)),
+    (b"GIF89a", magic("image/gif", "GIF image", MagicKind::Image)),
+    (b"II*\x00", magic("image/tiff", "TIFF image", MagicKind::Image)),
+    (b"MM\x00*", magic("image/tiff", "TIFF image", MagicKind::Image)),
+    (b"<?xml", magic("application/xml", "XML document", MagicKind::Script)),
+    (b"#!", magic("text/x-shellscript", "Script with interpreter line", MagicKind::Script)),
+];
+
+/// Case-insensitive markup prefixes
+const MARKUP: &[(&[u8], Magic)] = &[
+    (b"<!doctype html", magic("text/html", "HTML document", MagicKind::Script)),
+    (b"<html", magic("text/html", "HTML document", MagicKind::Script)),
+    (b"<script", magic("text/html", "HTML script", MagicKind::Script)),
+    (b"<svg", magic("image/svg+xml", "SVG image", MagicKind::Script)),
+];
+
+/// Identifies data by its leading bytes; leading whitespace is ignored
+pub fn identify(data: &[u8]) -> Option<Magic> {
+    let start = data.iter().position(|b| !b.is_ascii_whitespace())?;
+    let data = &data[start..];
+
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| data.starts_with(signature))
+        .or_else(|| {
+            MARKUP.iter().find(|(prefix, _)| {
+                data.len() >= prefix.len() && data[..prefix.len()].eq_ignore_ascii_case(prefix)
+            })
+        })
+        .map(|(_, magic)| *magic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify() {
+        assert_eq!(identify(b"MZ\x90\x00\x03").unwrap().kind, MagicKind::Executable);
+        assert_eq!(identify(b"\r\nPK\x03\x04rest").unwrap().mime, "application/zip");
+        assert_eq!(identify(b"<!DOCTYPE HTML>").unwrap().mime, "text/html");
+        assert!(identify(b"just some text").is_none());
+        assert!(identify(b"").is_none());
+    }
+}
@@ -0,0 +1,469 @@
+//! Raw document model shared by the forensic passes
+//! Created: 2026-10-16 08:38:03 UTC
+//! Author: kartik4091
+//!
+//! Helpers in this module work directly on lopdf objects and on the raw
+//! byte stream, so that passes which care about file history (revisions,
+//! deleted objects, slack space) see the file exactly as it was written.
+
+pub mod revisions;
+pub mod diff;
+pub mod parser;
+pub mod objects;
+pub mod xref;
+pub mod magic;
+pub mod objstm;
+pub mod taxonomy;
+pub mod hashing;
+pub mod attachments;
+pub mod content;
+pub mod jbig2;
+pub mod graph;
+pub mod preview;
+pub mod inspect;
+pub mod listing;
+pub mod dump;
+pub mod layout;
+pub mod artifacts;
+pub mod chronology;
+pub mod xmp;
+
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use sha2::{Digest, Sha256};
+
+use crate::{error::Result, limits};
+
+/// Returns the document information dictionary, if the trailer carries one
+pub fn info_dictionary(doc: &Document) -> Option<&Dictionary> {
+    match doc.trailer.get(b"Info").ok()? {
+        Object::Reference(id) => doc.get_dictionary(*id).ok(),
+        Object::Dictionary(dict) => Some(dict),
+        _ => None,
+    }
+}
+
+/// Text-valued metadata: information dictionary strings by key, then the
+/// catalog's XMP packet under the key `XMP`
+pub fn metadata_text(doc: &Document) -> Vec<(String, String)> {
+    let mut metadata: Vec<(String, String)> = info_dictionary(doc)
+        .map(|info| {
+            info.iter()
+                .filter_map(|(key, value)| match value {
+                    Object::String(bytes, _) => Some((String::from_utf8_lossy(key).into_owned(), decode_text(bytes))),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let xmp = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"Metadata").ok())
+        .and_then(|m| m.as_reference().ok())
+        .and_then(|id| doc.get_object(id).ok())
+        .and_then(|m| m.as_stream().ok())
+        .and_then(|stream| stream_data(stream).ok());
+    if let Some(xmp) = xmp {
+        metadata.push(("XMP".to_string(), String::from_utf8_lossy(&xmp).into_owned()));
+    }
+    metadata
+}
+
+/// Visits every dictionary nested in an object (including stream
+/// dictionaries), without following indirect references
+pub fn walk_dictionaries<'a>(obj: &'a Object, visit: &mut impl FnMut(&'a Dictionary)) {
+    match obj {
+        Object::Dictionary(dict) => {
+            visit(dict);
+            for (_, value) in dict.iter() {
+                walk_dictionaries(value, visit);
+            }
+        }
+        Object::Stream(stream) => {
+            visit(&stream.dict);
+            for (_, value) in stream.dict.iter() {
+                walk_dictionaries(value, visit);
+            }
+        }
+        Object::Array(items) => {
+            for item in items {
+                walk_dictionaries(item, visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Appends every indirect reference nested in an object, including those
+/// in stream dictionaries
+pub fn collect_references(obj: &Object, out: &mut Vec<ObjectId>) {
+    match obj {
+        Object::Reference(id) => out.push(*id),
+        Object::Array(items) => items.iter().for_each(|item| collect_references(item, out)),
+        Object::Dictionary(dict) => dict.iter().for_each(|(_, v)| collect_references(v, out)),
+        Object::Stream(stream) => stream.dict.iter().for_each(|(_, v)| collect_references(v, out)),
+        _ => {}
+    }
+}
+
+/// Decoded JavaScript of every `/JS` entry, with the object holding it
+pub fn javascript(doc: &Document) -> Vec<(ObjectId, String)> {
+    let mut scripts = Vec::new();
+    for (&id, object) in &doc.objects {
+        walk_dictionaries(object, &mut |dict| {
+            let script = match dict.get(b"JS").map(|js| resolve(doc, js)) {
+                Ok(Object::String(bytes, _)) => decode_text(bytes),
+                Ok(Object::Stream(stream)) => decode_text(&stream_data(stream).unwrap_or_default()),
+                _ => return,
+            };
+            scripts.push((id, script));
+        });
+    }
+    scripts
+}
+
+/// Returns a dictionary entry as a name, if it is one
+pub fn name_value<'a>(dict: &'a Dictionary, key: &[u8]) -> Option<&'a [u8]> {
+    match dict.get(key).ok()? {
+        Object::Name(name) => Some(name),
+        _ => None,
+    }
+}
+
+/// Follows a reference, returning the object itself when it is not one or
+/// when the target is missing
+pub fn resolve<'a>(doc: &'a Document, obj: &'a Object) -> &'a Object {
+    match obj {
+        Object::Reference(id) => doc.get_object(*id).unwrap_or(obj),
+        _ => obj,
+    }
+}
+
+/// Object references listed in an array entry, resolving the array itself
+/// when it is indirect
+pub fn array_references(doc: &Document, array: Option<&Object>) -> Vec<ObjectId> {
+    array
+        .and_then(|a| resolve(doc, a).as_array().ok())
+        .map(|items| items.iter().filter_map(|item| item.as_reference().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Numeric value of an integer or real
+pub fn number(obj: &Object) -> Option<f64> {
+    match obj {
+        Object::Integer(i) => Some(*i as f64),
+        Object::Real(r) => Some(*r as f64),
+        _ => None,
+    }
+}
+
+/// Normalized `[x0, y0, x1, y1]` rectangle, with x0 <= x1 and y0 <= y1
+pub fn rectangle(doc: &Document, obj: &Object) -> Option<[f64; 4]> {
+    let coords: Vec<f64> = resolve(doc, obj)
+        .as_array()
+        .ok()?
+        .iter()
+        .filter_map(|c| number(resolve(doc, c)))
+        .collect();
+    let [x0, y0, x1, y1] = coords[..] else {
+        return None;
+    };
+    Some([x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)])
+}
+
+/// Effective crop box (or media box) of a page, inherited through /Parent
+pub fn page_box(doc: &Document, page: &Dictionary) -> Option<[f64; 4]> {
+    let mut node = page;
+    // Bounded to guard against /Parent loops
+    for _ in 0..64 {
+        for key in [&b"CropBox"[..], b"MediaBox"] {
+            if let Some(rect) = node.get(key).ok().and_then(|b| rectangle(doc, b)) {
+                return Some(rect);
+            }
+        }
+        node = resolve(doc, node.get(b"Parent").ok()?).as_dict().ok()?;
+    }
+    None
+}
+
+/// Returns the decoded stream data, or the raw bytes for unfiltered streams.
+///
+/// Decoding stops at the [`ResourceLimits`](limits::ResourceLimits) of the running analysis (see
+/// [`limits::scope`]), or at the defaults outside of one, failing with
+/// [`Error::Security`](crate::error::Error::Security), so that no pass can be made to inflate a bomb.
+pub fn stream_data(stream: &Stream) -> Result<Vec<u8>> {
+    if stream.dict.get(b"Filter").is_err() {
+        return Ok(stream.content.clone());
+    }
+    limits::decode(stream, &limits::active())
+}
+
+/// Lowercase hexadecimal rendering of a byte string
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hex SHA-256 of a buffer, used to recognize previously analyzed content
+pub fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+/// Shannon entropy of a buffer in bits per byte (0.0 to 8.0)
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let mut frequencies = [0u64; 256];
+    for &byte in data {
+        frequencies[byte as usize] += 1;
+    }
+
+    let len = data.len() as f64;
+    frequencies
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Printable excerpt of binary data, with other bytes shown as '.'
+pub fn printable_preview(data: &[u8], max: usize) -> String {
+    data.iter()
+        .take(max)
+        .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+        .collect()
+}
+
+/// Escapes text for XML and HTML element content and quoted attribute
+/// values; characters XML 1.0 cannot hold are dropped
+pub fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if (c as u32) < 0x20 || c == '\u{fffe}' || c == '\u{ffff}' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Decodes a PDF text string (UTF-16BE with BOM, otherwise PDFDocEncoding)
+pub fn decode_text(bytes: &[u8]) -> String {
+    if bytes.len() >= 2 && bytes[0] == 0xFE && bytes[1] == 0xFF {
+        let units: Vec<u16> = bytes[2..]
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        return String::from_utf16_lossy(&units);
+    }
+
+    // PDFDocEncoding matches Latin-1 for every printable code we report on
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Encodes a PDF text string: ASCII as is, anything else as UTF-16BE with BOM
+pub fn encode_text(text: &str) -> Vec<u8> {
+    if text.is_ascii() {
+        return text.as_bytes().to_vec();
+    }
+    let mut bytes = vec![0xFE, 0xFF];
+    bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+    bytes
+}
+
+/// Parses a PDF date string (`D:YYYYMMDDHHmmSSOHH'mm'`).
+///
+/// Trailing fields may be omitted as allowed by the specification; a
+/// missing time zone is treated as UTC. ASN.1 GeneralizedTime values use
+/// the same layout and parse as well.
+pub fn parse_pdf_date(bytes: &[u8]) -> Option<DateTime<Utc>> {
+    let text = std::str::from_utf8(bytes).ok()?.trim();
+    let text = text.strip_prefix("D:").unwrap_or(text);
+
+    let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+    if digits < 4 {
+        return None;
+    }
+    let field = |start: usize, default: u32| {
+        text.get(start..start + 2)
+            .filter(|_| start + 2 <= digits)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default)
+    };
+
+    let year = text[..4].parse().ok()?;
+    let naive = NaiveDate::from_ymd_opt(year, field(4, 1), field(6, 1))?
+        .and_hms_opt(field(8, 0), field(10, 0), field(12, 0))?;
+
+    let rest = &text[digits..];
+    let offset = match rest.as_bytes().first() {
+        Some(sign @ (b'+' | b'-')) => {
+            let tz: Vec<i64> = rest[1..]
+                .split(|c: char| !c.is_ascii_digit())
+                .filter(|part| !part.is_empty())
+                .filter_map(|part| part.parse().ok())
+                .collect();
+            let seconds = tz.first().copied().unwrap_or(0) * 3600 + tz.get(1).copied().unwrap_or(0) * 60;
+            if *sign == b'-' {
+                -seconds
+            } else {
+                seconds
+            }
+        }
+        _ => 0,
+    };
+
+    Some(Utc.from_utc_datetime(&naive) - Duration::seconds(offset))
+}
+
+/// Renders a short, single-line representation of a value for reports
+pub fn display_value(obj: &Object) -> String {
+    match obj {
+        Object::Null => "null".into(),
+        Object::Boolean(b) => b.to_string(),
+        Object::Integer(i) => i.to_string(),
+        Object::Real(r) => r.to_string(),
+        Object::Name(name) => format!("/{}", String::from_utf8_lossy(name)),
+        Object::String(bytes, _) => decode_text(bytes),
+        Object::Array(items) => format!("[{} items]", items.len()),
+        Object::Dictionary(dict) => format!("<<{} keys>>", dict.len()),
+        Object::Stream(stream) => format!("stream ({} bytes)", stream.content.len()),
+        Object::Reference((num, gen)) => format!("{} {} R", num, gen),
+    }
+}
+
+/// Stable digest of an object's content, including raw stream bytes.
+///
+/// Dictionary keys are hashed in sorted order so that two objects which
+/// only differ in key ordering compare equal.
+pub fn object_digest(obj: &Object) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    let mut buffer = Vec::new();
+    write_canonical(obj, &mut buffer);
+    hasher.update(&buffer);
+    hasher.finalize().into()
+}
+
+fn write_canonical(obj: &Object, out: &mut Vec<u8>) {
+    match obj {
+        Object::Null => out.extend_from_slice(b"null"),
+        Object::Boolean(b) => out.extend_from_slice(if *b { b"true" } else { b"false" }),
+        Object::Integer(i) => out.extend_from_slice(i.to_string().as_bytes()),
+        Object::Real(r) => out.extend_from_slice(r.to_string().as_bytes()),
+        Object::Name(name) => {
+            out.push(b'/');
+            out.extend_from_slice(name);
+        }
+        Object::String(bytes, _) => {
+            out.push(b'(');
+            out.extend_from_slice(bytes.len().to_string().as_bytes());
+            out.push(b':');
+            out.extend_from_slice(bytes);
+            out.push(b')');
+        }
+        Object::Array(items) => {
+            out.push(b'[');
+            for item in items {
+                write_canonical(item, out);
+                out.push(b' ');
+            }
+            out.push(b']');
+        }
+        Object::Dictionary(dict) => write_dictionary(dict, out),
+        Object::Stream(stream) => {
+            write_dictionary(&stream.dict, out);
+            out.extend_from_slice(b"stream");
+            out.extend_from_slice(stream.content.len().to_string().as_bytes());
+            out.push(b':');
+            out.extend_from_slice(&stream.content);
+        }
+        Object::Reference((num, gen)) => {
+            out.extend_from_slice(format!("{} {} R", num, gen).as_bytes());
+        }
+    }
+}
+
+fn write_dictionary(dict: &Dictionary, out: &mut Vec<u8>) {
+    let mut entries: Vec<_> = dict.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    out.extend_from_slice(b"<<");
+    for (key, value) in entries {
+        out.push(b'/');
+        out.extend_from_slice(key);
+        out.push(b' ');
+        write_canonical(value, out);
+        out.push(b' ');
+    }
+    out.extend_from_slice(b">>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_decode_text_utf16() {
+        let bytes = [0xFE, 0xFF, 0x00, b'H', 0x00, b'i'];
+        assert_eq!(decode_text(&bytes), "Hi");
+        assert_eq!(decode_text(b"plain"), "plain");
+        assert_eq!(encode_text("plain"), b"plain");
+        assert_eq!(decode_text(&encode_text("Zoë")), "Zoë");
+    }
+
+    #[test]
+    fn test_escape_markup() {
+        assert_eq!(
+            escape_markup("<script>alert(\"x\" & 'y')</script>\u{1}"),
+            "&lt;script&gt;alert(&quot;x&quot; &amp; &#39;y&#39;)&lt;/script&gt;"
+        );
+    }
+
+    #[test]
+    fn test_parse_pdf_date() {
+        let utc = parse_pdf_date(b"D:20240301120000Z").unwrap();
+        assert_eq!(utc.to_rfc3339(), "2024-03-01T12:00:00+00:00");
+
+        let offset = parse_pdf_date(b"D:20240301120000+02'00'").unwrap();
+        assert_eq!(offset.to_rfc3339(), "2024-03-01T10:00:00+00:00");
+
+        let partial = parse_pdf_date(b"D:2024").unwrap();
+        assert_eq!(partial.to_rfc3339(), "2024-01-01T00:00:00+00:00");
+
+        assert!(parse_pdf_date(b"yesterday").is_none());
+    }
+
+    #[test]
+    fn test_object_digest_ignores_key_order() {
+        let a = Object::Dictionary(dictionary! { "A" => 1, "B" => 2 });
+        let b = Object::Dictionary(dictionary! { "B" => 2, "A" => 1 });
+        let c = Object::Dictionary(dictionary! { "A" => 1, "B" => 3 });
+
+        assert_eq!(object_digest(&a), object_digest(&b));
+        assert_ne!(object_digest(&a), object_digest(&c));
+    }
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy(b""), 0.0);
+        assert_eq!(shannon_entropy(b"aaaa"), 0.0);
+        let all: Vec<u8> = (0..=255).collect();
+        assert!((shannon_entropy(&all) - 8.0).abs() < 1e-9);
+    }
+
+}
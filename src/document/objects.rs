@@ -0,0 +1,149 @@
+//! Raw scan for indirect object headers in the byte stream
+//! Created: 2026-10-16 08:35:42 UTC
+//! Author: kartik4091
+
+use lopdf::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    parser::{is_delimiter, is_whitespace},
+    revisions::find,
+};
+
+/// Location of an `N G obj ... endobj` block in the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawObject {
+    /// Object number and generation from the header
+    pub id: ObjectId,
+
+    /// Offset of the first digit of the object number
+    pub offset: usize,
+
+    /// Offset just past `endobj` (or the end of the file if it is missing)
+    pub end: usize,
+}
+
+impl RawObject {
+    /// Number of bytes covered by the object
+    pub fn len(&self) -> usize {
+        self.end - self.offset
+    }
+
+    /// Whether the object covers no bytes
+    pub fn is_empty(&self) -> bool {
+        self.end == self.offset
+    }
+}
+
+/// Finds every indirect object header in the file, in file order.
+///
+/// Scanning resumes after each object's `endobj`, so object-like text
+/// inside stream data is not reported as a separate object.
+pub fn scan_objects(data: &[u8]) -> Vec<RawObject> {
+    let mut objects = Vec::new();
+    let mut cursor = 0;
+
+    while let Some(pos) = find(data, b"obj", cursor) {
+        cursor = pos + 3;
+
+        let followed_ok = data.get(pos + 3).map_or(true, |&b| is_whitespace(b) || is_delimiter(b));
+        let Some((id, offset)) = header_before(data, pos).filter(|_| followed_ok) else {
+            continue;
+        };
+
+        let end = object_end(data, pos + 3);
+        objects.push(RawObject { id, offset, end });
+        cursor = end;
+    }
+
+    objects
+}
+
+/// Parses `N G` backwards from the `obj` keyword at `pos`
+fn header_before(data: &[u8], pos: usize) -> Option<(ObjectId, usize)> {
+    let mut i = pos;
+
+    let ws_end = i;
+    while i > 0 && is_whitespace(data[i - 1]) {
+        i -= 1;
+    }
+    if i == ws_end {
+        return None;
+    }
+
+    let gen_end = i;
+    while i > 0 && data[i - 1].is_ascii_digit() {
+        i -= 1;
+    }
+    let gen_start = i;
+    if gen_start == gen_end {
+        return None;
+    }
+
+    let ws_end = i;
+    while i > 0 && is_whitespace(data[i - 1]) {
+        i -= 1;
+    }
+    if i == ws_end {
+        return None;
+    }
+
+    let num_end = i;
+    while i > 0 && data[i - 1].is_ascii_digit() {
+        i -= 1;
+    }
+    let num_start = i;
+    if num_start == num_end {
+        return None;
+    }
+    if num_start > 0 && !is_whitespace(data[num_start - 1]) && !is_delimiter(data[num_start - 1]) {
+        return None;
+    }
+
+    let num = std::str::from_utf8(&data[num_start..num_end]).ok()?.parse().ok()?;
+    let gen = std::str::from_utf8(&data[gen_start..gen_end]).ok()?.parse().ok()?;
+    Some(((num, gen), num_start))
+}
+
+/// Finds the end of an object body, stepping over stream data
+fn object_end(data: &[u8], body_start: usize) -> usize {
+    let endobj = find(data, b"endobj", body_start);
+    let stream = find(data, b"stream", body_start);
+
+    let search_from = match (stream, endobj) {
+        (Some(s), Some(e)) if s < e => find(data, b"endstream", s + 6).unwrap_or(s),
+        (Some(s), None) => s,
+        _ => body_start,
+    };
+
+    find(data, b"endobj", search_from)
+        .map(|pos| pos + 6)
+        .unwrap_or(data.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_scan_simple_document() {
+        let data = fixtures::simple_pdf();
+        let objects = scan_objects(&data);
+
+        assert_eq!(objects.len(), 6);
+        for object in &objects {
+            assert!(data[object.offset..].starts_with(format!("{} ", object.id.0).as_bytes()));
+            assert!(data[..object.end].ends_with(b"endobj"));
+        }
+    }
+
+    #[test]
+    fn test_stream_content_is_skipped() {
+        let data = b"1 0 obj\n<< /Length 15 >>\nstream\n9 0 obj endobj\nendstream\nendobj\n2 0 obj\n5\nendobj";
+        let objects = scan_objects(data);
+
+        let ids: Vec<_> = objects.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![(1, 0), (2, 0)]);
+    }
+}
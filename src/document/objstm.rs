@@ -0,0 +1,135 @@
+//! Object stream (/Type /ObjStm) decoding
+//! Created: 2026-10-16 08:59:21 UTC
+//! Author: kartik4091
+
+use lopdf::{Object, Stream};
+
+use super::{name_value, parser::Parser, stream_data};
+use crate::error::{Error, Result};
+
+/// Upper bound on /N, to keep hostile headers from driving allocation
+const MAX_OBJECTS: usize = 1 << 20;
+
+/// An object stored inside an object stream
+#[derive(Debug, Clone)]
+pub struct CompressedObject {
+    /// Object number (the generation is implicitly 0)
+    pub number: u32,
+
+    /// Position within the stream, as referenced by xref stream entries
+    pub index: usize,
+
+    /// Offset of the object relative to /First in the decoded data
+    pub offset: usize,
+
+    /// Parsed object, or the parse failure
+    pub object: std::result::Result<Object, String>,
+}
+
+/// Decoded contents of an object stream
+#[derive(Debug, Clone)]
+pub struct ObjectStream {
+    /// Value of /N
+    pub declared: usize,
+
+    /// Object stream this one extends (/Extends)
+    pub extends: Option<(u32, u16)>,
+
+    /// Objects in header order
+    pub objects: Vec<CompressedObject>,
+}
+
+/// Whether a stream is an object stream
+pub fn is_object_stream(stream: &Stream) -> bool {
+    name_value(&stream.dict, b"Type") == Some(b"ObjStm")
+}
+
+/// Decodes an object stream and parses every object it declares
+pub fn decode(stream: &Stream) -> Result<ObjectStream> {
+    let count = stream
+        .dict
+        .get(b"N")
+        .and_then(Object::as_i64)
+        .map_err(|_| Error::Pdf("Object stream without /N".into()))?;
+    let first = stream
+        .dict
+        .get(b"First")
+        .and_then(Object::as_i64)
+        .map_err(|_| Error::Pdf("Object stream without /First".into()))?;
+    let declared = usize::try_from(count).map_err(|_| Error::Pdf(format!("Invalid /N {}", count)))?;
+    let first = usize::try_from(first).map_err(|_| Error::Pdf(format!("Invalid /First {}", first)))?;
+    if declared > MAX_OBJECTS {
+        return Err(Error::Pdf(format!("Object stream declares {} objects", declared)));
+    }
+
+    let data = stream_data(stream)?;
+    if first > data.len() {
+        return Err(Error::Pdf(format!("/First {} beyond decoded length {}", first, data.len())));
+    }
+
+    let mut header = Parser::new(&data[..first], 0);
+    let mut objects = Vec::with_capacity(declared);
+    for index in 0..declared {
+        let (Some(number), Some(offset)) = (header.parse_unsigned(), header.parse_unsigned()) else {
+            break;
+        };
+        let offset = usize::try_from(offset).unwrap_or(usize::MAX);
+        let object = match first.checked_add(offset) {
+            Some(start) if start < data.len() => Parser::new(&data, start).parse_object().map_err(|e| e.to_string()),
+            _ => Err(format!("Offset {} beyond decoded data", offset)),
+        };
+        objects.push(CompressedObject { number: number as u32, index, offset, object });
+    }
+
+    let extends = match stream.dict.get(b"Extends") {
+        Ok(Object::Reference(id)) => Some(*id),
+        _ => None,
+    };
+
+    Ok(ObjectStream { declared, extends, objects })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    /// Builds an uncompressed object stream holding `(number, body)` pairs
+    pub(crate) fn object_stream(objects: &[(u32, &str)]) -> Stream {
+        let mut header = String::new();
+        let mut body = String::new();
+        for (number, text) in objects {
+            header.push_str(&format!("{} {} ", number, body.len()));
+            body.push_str(text);
+            body.push(' ');
+        }
+        let first = header.len();
+        Stream::new(
+            dictionary! {
+                "Type" => "ObjStm",
+                "N" => objects.len() as i64,
+                "First" => first as i64,
+            },
+            format!("{}{}", header, body).into_bytes(),
+        )
+    }
+
+    #[test]
+    fn test_decode() {
+        let stream = object_stream(&[(10, "<< /Type /Annot >>"), (11, "(text)")]);
+        assert!(is_object_stream(&stream));
+
+        let decoded = decode(&stream).unwrap();
+        assert_eq!(decoded.declared, 2);
+        assert_eq!(decoded.objects[0].number, 10);
+        assert_eq!(decoded.objects[1].index, 1);
+        assert!(matches!(decoded.objects[1].object, Ok(Object::String(..))));
+    }
+
+    #[test]
+    fn test_first_out_of_range() {
+        let mut stream = object_stream(&[(10, "null")]);
+        stream.dict.set("First", 1000);
+        assert!(decode(&stream).is_err());
+    }
+}
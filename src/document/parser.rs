@@ -0,0 +1,532 @@
+//! Tolerant parser for raw PDF object syntax
+//! Created: 2026-10-16 08:40:32 UTC
+//! Author: kartik4091
+//!
+//! lopdf only exposes objects reachable through the cross-reference table.
+//! The forensic passes also need to read bytes the xref no longer points
+//! at, so this parser works from an arbitrary offset and produces lopdf
+//! objects that the rest of the crate already knows how to handle.
+
+use lopdf::{Dictionary, Object, ObjectId, Stream, StringFormat};
+
+use crate::error::{Error, Result};
+
+/// Which value wins when a dictionary repeats a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyPolicy {
+    /// The first occurrence is kept
+    First,
+
+    /// Later occurrences overwrite earlier ones
+    #[default]
+    Last,
+}
+
+/// How the extent of stream data is determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LengthPolicy {
+    /// Trust a direct /Length when it lands on `endstream`, else scan
+    #[default]
+    Declared,
+
+    /// Always scan for the `endstream` keyword
+    Endstream,
+}
+
+/// Resolution choices for constructs the specification leaves ambiguous
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// Duplicate dictionary key handling
+    pub duplicate_keys: KeyPolicy,
+
+    /// Stream extent handling
+    pub stream_length: LengthPolicy,
+}
+
+/// Cursor-based parser over a byte buffer
+pub struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    options: ParseOptions,
+    duplicate_keys: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Creates a parser positioned at `pos`
+    pub fn new(data: &'a [u8], pos: usize) -> Self {
+        Self::with_options(data, pos, ParseOptions::default())
+    }
+
+    /// Creates a parser with explicit ambiguity resolution options
+    pub fn with_options(data: &'a [u8], pos: usize, options: ParseOptions) -> Self {
+        Self { data, pos, options, duplicate_keys: 0 }
+    }
+
+    /// Number of repeated dictionary keys seen so far
+    pub fn duplicate_keys(&self) -> usize {
+        self.duplicate_keys
+    }
+
+    /// Current offset into the buffer
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Skips whitespace and comments
+    pub fn skip_whitespace(&mut self) {
+        while let Some(&b) = self.data.get(self.pos) {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while let Some(&c) = self.data.get(self.pos) {
+                    if c == b'\r' || c == b'\n' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Consumes `keyword` if it appears at the current position
+    pub fn eat_keyword(&mut self, keyword: &[u8]) -> bool {
+        self.skip_whitespace();
+        let end = self.pos + keyword.len();
+        if self.data.get(self.pos..end) == Some(keyword)
+            && self.data.get(end).map_or(true, |&b| is_delimiter(b) || is_whitespace(b))
+        {
+            self.pos = end;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Parses an unsigned integer at the current position
+    pub fn parse_unsigned(&mut self) -> Option<u64> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.data.get(self.pos).map_or(false, u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return None;
+        }
+        std::str::from_utf8(&self.data[start..self.pos]).ok()?.parse().ok()
+    }
+
+    /// Parses a direct object (a stream is only recognised by [`parse_indirect`])
+    pub fn parse_object(&mut self) -> Result<Object> {
+        self.parse_object_at_depth(0)
+    }
+
+    fn parse_object_at_depth(&mut self, depth: usize) -> Result<Object> {
+        if depth > MAX_NESTING {
+            return Err(self.error("nesting too deep"));
+        }
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(b'<') if self.peek_at(1) == Some(b'<') => {
+                Ok(Object::Dictionary(self.parse_dictionary(depth)?))
+            }
+            Some(b'<') => self.parse_hex_string(),
+            Some(b'[') => self.parse_array(depth),
+            Some(b'(') => self.parse_literal_string(),
+            Some(b'/') => Ok(Object::Name(self.parse_name())),
+            Some(b) if b.is_ascii_digit() || b == b'-' || b == b'+' || b == b'.' => {
+                self.parse_number_or_reference()
+            }
+            Some(_) => {
+                if self.eat_keyword(b"true") {
+                    Ok(Object::Boolean(true))
+                } else if self.eat_keyword(b"false") {
+                    Ok(Object::Boolean(false))
+                } else if self.eat_keyword(b"null") {
+                    Ok(Object::Null)
+                } else {
+                    Err(self.error("unexpected token"))
+                }
+            }
+            None => Err(self.error("unexpected end of data")),
+        }
+    }
+
+    fn parse_dictionary(&mut self, depth: usize) -> Result<Dictionary> {
+        self.pos += 2;
+        let mut dict = Dictionary::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'>') if self.peek_at(1) == Some(b'>') => {
+                    self.pos += 2;
+                    return Ok(dict);
+                }
+                Some(b'/') => {
+                    let key = self.parse_name();
+                    let value = self.parse_object_at_depth(depth + 1)?;
+                    if dict.has(&key) {
+                        self.duplicate_keys += 1;
+                        if self.options.duplicate_keys == KeyPolicy::First {
+                            continue;
+                        }
+                    }
+                    dict.set(key, value);
+                }
+                _ => return Err(self.error("malformed dictionary")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Result<Object> {
+        self.pos += 1;
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Object::Array(items));
+                }
+                Some(_) => items.push(self.parse_object_at_depth(depth + 1)?),
+                None => return Err(self.error("unterminated array")),
+            }
+        }
+    }
+
+    /// Parses a name, decoding `#xx` escapes
+    fn parse_name(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut name = Vec::new();
+
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) || is_delimiter(b) {
+                break;
+            }
+            if b == b'#' {
+                let hex = self.data.get(self.pos + 1..self.pos + 3);
+                if let Some(value) = hex.and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                {
+                    name.push(value);
+                    self.pos += 3;
+                    continue;
+                }
+            }
+            name.push(b);
+            self.pos += 1;
+        }
+
+        name
+    }
+
+    fn parse_literal_string(&mut self) -> Result<Object> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        let mut nesting = 0usize;
+
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'\\' => {
+                    let Some(next) = self.peek() else { break };
+                    self.pos += 1;
+                    match next {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0C),
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        b'0'..=b'7' => {
+                            let mut value = u32::from(next - b'0');
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + u32::from(d - b'0');
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            out.push(value as u8);
+                        }
+                        other => out.push(other),
+                    }
+                }
+                b'(' => {
+                    nesting += 1;
+                    out.push(b);
+                }
+                b')' if nesting == 0 => return Ok(Object::String(out, StringFormat::Literal)),
+                b')' => {
+                    nesting -= 1;
+                    out.push(b);
+                }
+                _ => out.push(b),
+            }
+        }
+
+        Err(self.error("unterminated string"))
+    }
+
+    fn parse_hex_string(&mut self) -> Result<Object> {
+        self.pos += 1;
+        let mut digits = Vec::new();
+
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            if b == b'>' {
+                if digits.len() % 2 == 1 {
+                    digits.push(b'0');
+                }
+                let bytes = digits
+                    .chunks(2)
+                    .filter_map(|pair| std::str::from_utf8(pair).ok())
+                    .filter_map(|pair| u8::from_str_radix(pair, 16).ok())
+                    .collect();
+                return Ok(Object::String(bytes, StringFormat::Hexadecimal));
+            }
+            if b.is_ascii_hexdigit() {
+                digits.push(b);
+            } else if !is_whitespace(b) {
+                return Err(self.error("invalid hex string"));
+            }
+        }
+
+        Err(self.error("unterminated hex string"))
+    }
+
+    fn parse_number_or_reference(&mut self) -> Result<Object> {
+        let first = self.parse_number()?;
+
+        // "N G R" is only a reference when both parts are plain integers
+        if let Object::Integer(num) = first {
+            let checkpoint = self.pos;
+            if let Some(gen) = self.parse_unsigned() {
+                if self.eat_keyword(b"R") && num >= 0 {
+                    return Ok(Object::Reference((num as u32, gen as u16)));
+                }
+            }
+            self.pos = checkpoint;
+        }
+
+        Ok(first)
+    }
+
+    fn parse_number(&mut self) -> Result<Object> {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            if b.is_ascii_digit() || b == b'-' || b == b'+' || b == b'.' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        let text = std::str::from_utf8(&self.data[start..self.pos]).unwrap_or_default();
+        if text.contains('.') {
+            text.parse::<f32>().map(Object::Real).map_err(|_| self.error("invalid real"))
+        } else {
+            text.parse::<i64>().map(Object::Integer).map_err(|_| self.error("invalid integer"))
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.data.get(self.pos + offset).copied()
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::Pdf(format!("{} at offset {}", message, self.pos))
+    }
+}
+
+/// Maximum nesting of arrays and dictionaries accepted by the parser
+pub const MAX_NESTING: usize = 64;
+
+/// An indirect object parsed from raw bytes
+#[derive(Debug, Clone)]
+pub struct IndirectObject {
+    /// Object number and generation from the `N G obj` header
+    pub id: ObjectId,
+
+    /// Parsed object; streams carry their raw (still encoded) content
+    pub object: Object,
+
+    /// Offset of the stream data, if the object is a stream
+    pub stream_offset: Option<usize>,
+
+    /// Offset just past the object (after `endobj` when present)
+    pub end: usize,
+
+    /// Number of repeated dictionary keys encountered
+    pub duplicate_keys: usize,
+}
+
+/// Parses an indirect object whose `N G obj` header starts at `offset`
+pub fn parse_indirect(data: &[u8], offset: usize) -> Result<IndirectObject> {
+    parse_indirect_with(data, offset, ParseOptions::default())
+}
+
+/// Parses an indirect object using explicit ambiguity resolution options
+pub fn parse_indirect_with(data: &[u8], offset: usize, options: ParseOptions) -> Result<IndirectObject> {
+    let mut parser = Parser::with_options(data, offset, options);
+    let num = parser.parse_unsigned().ok_or_else(|| parser.error("missing object number"))?;
+    let gen = parser.parse_unsigned().ok_or_else(|| parser.error("missing generation"))?;
+    if !parser.eat_keyword(b"obj") {
+        return Err(parser.error("missing obj keyword"));
+    }
+
+    let mut object = parser.parse_object()?;
+    let mut stream_offset = None;
+
+    if let Object::Dictionary(dict) = &object {
+        if parser.eat_keyword(b"stream") {
+            let start = skip_eol(data, parser.pos);
+            let end = stream_end(data, start, dict, options.stream_length);
+            let mut stream = Stream::new(dict.clone(), data[start..end].to_vec());
+            // Keep the declared /Length rather than the one Stream::new computes
+            stream.dict = dict.clone();
+            stream.start_position = Some(start);
+            stream_offset = Some(start);
+            object = Object::Stream(stream);
+
+            parser.pos = end;
+            parser.eat_keyword(b"endstream");
+        }
+    }
+
+    parser.eat_keyword(b"endobj");
+    Ok(IndirectObject {
+        id: (num as u32, gen as u16),
+        object,
+        stream_offset,
+        end: parser.position(),
+        duplicate_keys: parser.duplicate_keys(),
+    })
+}
+
+/// Locates the end of stream data, trusting a direct /Length only when it
+/// lands on `endstream`
+fn stream_end(data: &[u8], start: usize, dict: &Dictionary, policy: LengthPolicy) -> usize {
+    if let (LengthPolicy::Declared, Ok(Object::Integer(length))) = (policy, dict.get(b"Length")) {
+        if *length >= 0 {
+            let end = start.saturating_add(*length as usize);
+            if end <= data.len() {
+                let mut probe = Parser::new(data, end);
+                if probe.eat_keyword(b"endstream") {
+                    return end;
+                }
+            }
+        }
+    }
+
+    match super::revisions::find(data, b"endstream", start) {
+        Some(mut end) => {
+            // The EOL before endstream is not part of the data
+            if end > start && data[end - 1] == b'\n' {
+                end -= 1;
+            }
+            if end > start && data[end - 1] == b'\r' {
+                end -= 1;
+            }
+            end
+        }
+        None => data.len(),
+    }
+}
+
+fn skip_eol(data: &[u8], mut pos: usize) -> usize {
+    if data.get(pos) == Some(&b'\r') {
+        pos += 1;
+    }
+    if data.get(pos) == Some(&b'\n') {
+        pos += 1;
+    }
+    pos
+}
+
+/// Position of the first non-whitespace byte at or after `pos`
+pub fn skip_whitespace(data: &[u8], mut pos: usize) -> usize {
+    while data.get(pos).map_or(false, |&b| is_whitespace(b)) {
+        pos += 1;
+    }
+    pos
+}
+
+/// PDF whitespace characters (ISO 32000-1, Table 1)
+pub fn is_whitespace(b: u8) -> bool {
+    matches!(b, b'\0' | b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+/// PDF delimiter characters (ISO 32000-1, Table 2)
+pub fn is_delimiter(b: u8) -> bool {
+    matches!(b, b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dictionary() {
+        let data = b"<< /Type /Page /Kids [1 0 R 2 0 R] /N 3.5 /S (a\\(b\\)) /H <414>>>";
+        let obj = Parser::new(data, 0).parse_object().unwrap();
+        let dict = obj.as_dict().unwrap();
+
+        assert_eq!(dict.get(b"Type").unwrap().as_name().unwrap(), b"Page");
+        assert_eq!(dict.get(b"Kids").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(dict.get(b"S").unwrap().as_str().unwrap(), b"a(b)");
+        assert_eq!(dict.get(b"H").unwrap().as_str().unwrap(), b"A@");
+    }
+
+    #[test]
+    fn test_name_escapes() {
+        let obj = Parser::new(b"/J#61vaScript", 0).parse_object().unwrap();
+        assert_eq!(obj.as_name().unwrap(), b"JavaScript");
+    }
+
+    #[test]
+    fn test_parse_indirect_stream() {
+        let data = b"12 0 obj\n<< /Length 5 >>\nstream\nhello\nendstream\nendobj\n";
+        let parsed = parse_indirect(data, 0).unwrap();
+
+        assert_eq!(parsed.id, (12, 0));
+        assert_eq!(parsed.object.as_stream().unwrap().content, b"hello");
+        assert_eq!(&data[parsed.end - 6..parsed.end], b"endobj");
+    }
+
+    #[test]
+    fn test_duplicate_key_policies() {
+        let data = b"<< /S /First /S /Second >>";
+        let first = ParseOptions { duplicate_keys: KeyPolicy::First, ..Default::default() };
+
+        let mut parser = Parser::with_options(data, 0, first);
+        let obj = parser.parse_object().unwrap();
+        assert_eq!(obj.as_dict().unwrap().get(b"S").unwrap().as_name().unwrap(), b"First");
+        assert_eq!(parser.duplicate_keys(), 1);
+
+        let obj = Parser::new(data, 0).parse_object().unwrap();
+        assert_eq!(obj.as_dict().unwrap().get(b"S").unwrap().as_name().unwrap(), b"Second");
+    }
+
+    #[test]
+    fn test_wrong_stream_length_falls_back_to_endstream() {
+        let data = b"1 0 obj\n<< /Length 99 >>\nstream\nabc\nendstream\nendobj";
+        let parsed = parse_indirect(data, 0).unwrap();
+        assert_eq!(parsed.object.as_stream().unwrap().content, b"abc");
+    }
+}
@@ -0,0 +1,287 @@
+//! Object tree and image thumbnails for visual reports
+//! Created: 2026-10-16 10:37:46 UTC
+//! Author: kartik4091
+//!
+//! Captures what the HTML report shows beyond the analysis model: the
+//! object graph as a tree, laid out breadth-first from the trailer so each
+//! object appears once at its shallowest position (unreferenced objects
+//! follow as extra roots), and small grayscale PNG thumbnails of page
+//! thumbnails and image XObjects. Both are capped so a hostile file
+//! cannot inflate the report.
+
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    io::Cursor,
+};
+
+use base64::Engine;
+use image::{imageops, GrayImage, ImageOutputFormat};
+use lopdf::{Document, Object, ObjectId, Stream};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{
+    graph::{outgoing, references},
+    name_value,
+    taxonomy::ObjectType,
+};
+use crate::detectors::qr;
+
+/// Most objects placed in the tree
+const MAX_NODES: usize = 5000;
+
+/// Deepest level of the tree; deeper objects become extra roots
+const MAX_DEPTH: usize = 64;
+
+/// Most thumbnails captured
+const MAX_THUMBNAILS: usize = 48;
+
+/// Longest side of a thumbnail in pixels
+const THUMBNAIL_SIZE: u32 = 160;
+
+/// An object and the objects first reached through it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ObjectNode {
+    /// Object number and generation
+    pub id: ObjectId,
+
+    /// Entry of the parent through which the object is referenced, `None`
+    /// for roots
+    pub key: Option<String>,
+
+    /// Object type, with subtype detail
+    pub kind: String,
+
+    /// Stored length for streams
+    pub length: Option<usize>,
+
+    /// Objects first reached from this one
+    pub children: Vec<ObjectNode>,
+}
+
+/// Downscaled grayscale rendering of an image
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Thumbnail {
+    /// Image stream
+    pub object: ObjectId,
+
+    /// Page the thumbnail was stored for, when it is a page /Thumb
+    pub page: Option<u32>,
+
+    /// Width of the source image
+    pub width: usize,
+
+    /// Height of the source image
+    pub height: usize,
+
+    /// Base64 of the PNG thumbnail
+    pub png: String,
+}
+
+/// What the HTML report shows of the document itself
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentPreview {
+    /// Trees rooted at the trailer entries, then at unreferenced objects
+    pub objects: Vec<ObjectNode>,
+
+    /// Whether objects were left out of the tree
+    pub truncated: bool,
+
+    /// Page thumbnails first, then image XObjects
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+/// Builds the object tree and thumbnails of a document
+#[instrument(skip(doc))]
+pub fn build(doc: &Document) -> DocumentPreview {
+    let (objects, truncated) = object_tree(doc);
+    let thumbnails = thumbnails(doc);
+    debug!(
+        "Preview with {} root objects and {} thumbnails",
+        objects.len(),
+        thumbnails.len()
+    );
+    DocumentPreview {
+        objects,
+        truncated,
+        thumbnails,
+    }
+}
+
+fn object_tree(doc: &Document) -> (Vec<ObjectNode>, bool) {
+    let mut seen = HashSet::new();
+    let mut children: BTreeMap<ObjectId, Vec<(String, ObjectId)>> = BTreeMap::new();
+    let mut roots = Vec::new();
+
+    let trailer = references(&doc.trailer);
+    let others = doc.objects.keys().map(|&id| (None, id));
+    for (key, root) in trailer.into_iter().map(|(key, id)| (Some(key), id)).chain(others) {
+        if seen.len() >= MAX_NODES {
+            break;
+        }
+        if !doc.objects.contains_key(&root) || !seen.insert(root) {
+            continue;
+        }
+        roots.push((key, root));
+
+        let mut queue = VecDeque::from([(root, 0)]);
+        while let Some((id, depth)) = queue.pop_front() {
+            if depth >= MAX_DEPTH {
+                continue;
+            }
+            let entries = doc.objects.get(&id).map(outgoing).unwrap_or_default();
+            for (key, child) in entries {
+                if seen.len() >= MAX_NODES {
+                    break;
+                }
+                if doc.objects.contains_key(&child) && seen.insert(child) {
+                    children.entry(id).or_default().push((key, child));
+                    queue.push_back((child, depth + 1));
+                }
+            }
+        }
+    }
+
+    let truncated = seen.len() < doc.objects.len();
+    let tree = roots
+        .into_iter()
+        .map(|(key, id)| node(doc, id, key, &mut children))
+        .collect();
+    (tree, truncated)
+}
+
+fn node(
+    doc: &Document,
+    id: ObjectId,
+    key: Option<String>,
+    children: &mut BTreeMap<ObjectId, Vec<(String, ObjectId)>>,
+) -> ObjectNode {
+    let object = doc.objects.get(&id);
+    let nested = children.remove(&id).unwrap_or_default();
+    ObjectNode {
+        id,
+        key,
+        kind: object.map(|o| ObjectType::of(o).to_string()).unwrap_or_default(),
+        length: match object {
+            Some(Object::Stream(stream)) => Some(stream.content.len()),
+            _ => None,
+        },
+        children: nested
+            .into_iter()
+            .map(|(key, child)| node(doc, child, Some(key), children))
+            .collect(),
+    }
+}
+
+fn thumbnails(doc: &Document) -> Vec<Thumbnail> {
+    let mut found = Vec::new();
+    let mut done = HashSet::new();
+
+    for (number, page_id) in doc.get_pages() {
+        if found.len() >= MAX_THUMBNAILS {
+            break;
+        }
+        let thumb = doc
+            .get_dictionary(page_id)
+            .ok()
+            .and_then(|page| page.get(b"Thumb").ok())
+            .and_then(|thumb| thumb.as_reference().ok());
+        if let Some(id) = thumb {
+            if let Ok(Object::Stream(stream)) = doc.get_object(id) {
+                done.insert(id);
+                found.extend(thumbnail(doc, id, stream, Some(number)));
+            }
+        }
+    }
+    for (&id, object) in &doc.objects {
+        if found.len() >= MAX_THUMBNAILS {
+            break;
+        }
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        if name_value(&stream.dict, b"Subtype") == Some(b"Image") && !done.contains(&id) {
+            found.extend(thumbnail(doc, id, stream, None));
+        }
+    }
+    found.truncate(MAX_THUMBNAILS);
+    found
+}
+
+fn thumbnail(doc: &Document, id: ObjectId, image: &Stream, page: Option<u32>) -> Option<Thumbnail> {
+    let (width, height, pixels) = qr::grayscale(doc, image)?;
+    let source = GrayImage::from_raw(width as u32, height as u32, pixels)?;
+    let scale = (THUMBNAIL_SIZE as f64 / width.max(height) as f64).min(1.0);
+    let small = imageops::thumbnail(
+        &source,
+        ((width as f64 * scale) as u32).max(1),
+        ((height as f64 * scale) as u32).max(1),
+    );
+
+    let mut png = Cursor::new(Vec::new());
+    small.write_to(&mut png, ImageOutputFormat::Png).ok()?;
+    Some(Thumbnail {
+        object: id,
+        page,
+        width,
+        height,
+        png: base64::engine::general_purpose::STANDARD.encode(png.into_inner()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures::{self, CATALOG_ID, PAGE_ID};
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_tree_from_trailer() {
+        let mut doc = fixtures::document();
+        let orphan = doc.add_object(dictionary! { "Type" => "Action", "S" => "JavaScript" });
+
+        let (roots, truncated) = object_tree(&doc);
+        assert!(!truncated);
+        assert_eq!(roots.len(), 2);
+        assert_eq!((roots[0].id, roots[0].key.as_deref()), (CATALOG_ID, Some("/Root")));
+        assert_eq!(
+            (roots[1].id, roots[1].key.as_deref(), roots[1].kind.as_str()),
+            (orphan, None, "Action/JavaScript")
+        );
+
+        let pages = &roots[0].children[0];
+        assert_eq!(pages.key.as_deref(), Some("/Pages"));
+        let page = pages.children.iter().find(|n| n.id == PAGE_ID).unwrap();
+        assert_eq!(page.key.as_deref(), Some("/Kids"));
+        // /Parent points back at the page tree, which is already placed
+        assert!(page.children.iter().all(|n| n.key.as_deref() == Some("/Contents")));
+    }
+
+    #[test]
+    fn test_image_thumbnail() {
+        let mut doc = fixtures::document();
+        let gradient: Vec<u8> = (0..320 * 64).map(|i| (i % 256) as u8).collect();
+        let image = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 320,
+                "Height" => 64,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            gradient,
+        ));
+
+        let preview = build(&doc);
+        assert_eq!(preview.thumbnails.len(), 1);
+        let thumbnail = &preview.thumbnails[0];
+        assert_eq!((thumbnail.object, thumbnail.page, thumbnail.width), (image, None, 320));
+        let png = base64::engine::general_purpose::STANDARD
+            .decode(&thumbnail.png)
+            .unwrap();
+        let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (THUMBNAIL_SIZE, 32));
+    }
+}
@@ -0,0 +1,152 @@
+//! Incremental update (revision) boundaries
+//! Created: 2026-10-16 08:33:17 UTC
+//! Author: kartik4091
+//!
+//! Every incremental save appends a body, an xref section, a trailer and a
+//! closing `%%EOF` marker. Splitting the byte stream on those markers gives
+//! the physical revision history of the file.
+
+use lopdf::Document;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// A single revision as laid out in the file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Revision {
+    /// Zero-based revision index in append order
+    pub index: usize,
+
+    /// Offset of the first byte written by this revision
+    pub start: usize,
+
+    /// Offset just past this revision's `%%EOF` line
+    pub end: usize,
+
+    /// Value of the `startxref` keyword closing this revision
+    pub startxref: Option<u64>,
+}
+
+impl Revision {
+    /// Number of bytes appended by this revision
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the revision appended no bytes
+    pub fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
+}
+
+/// Splits the byte stream into revisions on `%%EOF` markers.
+///
+/// Bytes following the last marker are not treated as a revision. A file
+/// without any marker yields a single revision spanning the whole buffer so
+/// that damaged files can still be inspected.
+pub fn split_revisions(data: &[u8]) -> Vec<Revision> {
+    let mut revisions = Vec::new();
+    let mut start = 0;
+    let mut cursor = 0;
+
+    while let Some(pos) = find(data, b"%%EOF", cursor) {
+        let mut end = pos + 5;
+        // Consume the end-of-line sequence belonging to the marker
+        if data.get(end) == Some(&b'\r') {
+            end += 1;
+        }
+        if data.get(end) == Some(&b'\n') {
+            end += 1;
+        }
+
+        revisions.push(Revision {
+            index: revisions.len(),
+            start,
+            end,
+            startxref: find_startxref(&data[start..pos]),
+        });
+
+        start = end;
+        cursor = end;
+    }
+
+    if revisions.is_empty() && !data.is_empty() {
+        revisions.push(Revision {
+            index: 0,
+            start: 0,
+            end: data.len(),
+            startxref: find_startxref(data),
+        });
+    }
+
+    revisions
+}
+
+/// Loads the document as it existed when the given revision was written
+pub fn materialize(data: &[u8], revision: &Revision) -> Result<Document> {
+    Document::load_mem(&data[..revision.end]).map_err(|e| {
+        Error::Pdf(format!("Failed to load revision {}: {}", revision.index, e))
+    })
+}
+
+/// Finds the last `startxref` value within a slice
+pub fn find_startxref(data: &[u8]) -> Option<u64> {
+    let pos = data.windows(9).rposition(|w| w == b"startxref")?;
+    let digits: String = data[pos + 9..]
+        .iter()
+        .skip_while(|b| b.is_ascii_whitespace())
+        .take_while(|b| b.is_ascii_digit())
+        .map(|&b| b as char)
+        .collect();
+    digits.parse().ok()
+}
+
+/// Finds the first occurrence of `needle` at or after `from`
+pub fn find(data: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from >= data.len() {
+        return None;
+    }
+    data[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|pos| pos + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_single_revision() {
+        let data = fixtures::simple_pdf();
+        let revisions = split_revisions(&data);
+
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].start, 0);
+        assert!(revisions[0].startxref.is_some());
+    }
+
+    #[test]
+    fn test_incremental_revisions() {
+        let base = fixtures::simple_pdf();
+        let data = fixtures::append_revision(&base, &[(7, "<< /Type /Annot >>")]);
+        let revisions = split_revisions(&data);
+
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[1].start, revisions[0].end);
+        assert_eq!(revisions[1].end, data.len());
+
+        let first = materialize(&data, &revisions[0]).unwrap();
+        let second = materialize(&data, &revisions[1]).unwrap();
+        assert!(!first.objects.contains_key(&(7, 0)));
+        assert!(second.objects.contains_key(&(7, 0)));
+    }
+
+    #[test]
+    fn test_missing_eof_marker() {
+        let revisions = split_revisions(b"%PDF-1.4\n1 0 obj\n<<>>\nendobj\n");
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].startxref, None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Structured object type taxonomy
+//! Created: 2026-10-16 09:08:04 UTC
+//! Author: kartik4091
+//!
+//! Reports identify objects by [`ObjectType`] rather than by lopdf's type
+//! names or raw /Type values, so consumers can match on stable variants.
+//! Open-ended PDF names (font and annotation subtypes, action kinds) are
+//! kept as strings inside the variant; unmodelled /Type values fall back to
+//! [`ObjectType::Other`].
+
+use std::fmt;
+
+use lopdf::{Dictionary, Object};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::name_value;
+
+/// Annotation subtypes, recognized even when /Type is omitted
+const ANNOTATION_SUBTYPES: [&[u8]; 26] = [
+    b"Text", b"Link", b"FreeText", b"Line", b"Square", b"Circle", b"Polygon", b"PolyLine",
+    b"Highlight", b"Underline", b"Squiggly", b"StrikeOut", b"Stamp", b"Caret", b"Ink", b"Popup",
+    b"FileAttachment", b"Sound", b"Movie", b"Widget", b"Screen", b"PrinterMark", b"TrapNet",
+    b"Watermark", b"3D", b"Redact",
+];
+
+/// What an object is, independent of how it is written
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind")]
+pub enum ObjectType {
+    /// Document catalog
+    Catalog,
+
+    /// Page tree node
+    Pages,
+
+    /// Page
+    Page,
+
+    /// Outline (bookmark) tree
+    Outlines,
+
+    /// Font, with its /Subtype
+    Font { subtype: Option<String> },
+
+    /// Font descriptor
+    FontDescriptor,
+
+    /// Annotation, with its /Subtype
+    Annotation { subtype: Option<String> },
+
+    /// Action, with its /S kind
+    Action { kind: Option<String> },
+
+    /// External object, with its /Subtype (Image, Form, PS)
+    XObject { subtype: Option<String> },
+
+    /// Embedded file stream
+    EmbeddedFile,
+
+    /// File specification
+    FileSpec,
+
+    /// Signature dictionary
+    Signature,
+
+    /// XMP metadata stream
+    Metadata,
+
+    /// Object stream
+    ObjectStream,
+
+    /// Cross-reference stream
+    XrefStream,
+
+    /// Other stream (content streams, font programs), with any /Subtype
+    Stream { subtype: Option<String> },
+
+    /// Dictionary without a recognized type
+    Dictionary,
+
+    /// Array
+    Array,
+
+    /// Name
+    Name,
+
+    /// String
+    String,
+
+    /// Integer or real number
+    Number,
+
+    /// Boolean
+    Boolean,
+
+    /// Null
+    Null,
+
+    /// Indirect reference
+    Reference,
+
+    /// Dictionary or stream with an unmodelled /Type
+    Other { type_name: String },
+}
+
+impl ObjectType {
+    /// Classifies an object from its dictionary entries
+    pub fn of(obj: &Object) -> Self {
+        match obj {
+            Object::Dictionary(dict) => classify(dict, false),
+            Object::Stream(stream) => classify(&stream.dict, true),
+            Object::Array(_) => Self::Array,
+            Object::Name(_) => Self::Name,
+            Object::String(..) => Self::String,
+            Object::Integer(_) | Object::Real(_) => Self::Number,
+            Object::Boolean(_) => Self::Boolean,
+            Object::Null => Self::Null,
+            Object::Reference(_) => Self::Reference,
+        }
+    }
+
+    /// Variant name, without subtype detail (the `kind` tag in serialized form)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Catalog => "Catalog",
+            Self::Pages => "Pages",
+            Self::Page => "Page",
+            Self::Outlines => "Outlines",
+            Self::Font { .. } => "Font",
+            Self::FontDescriptor => "FontDescriptor",
+            Self::Annotation { .. } => "Annotation",
+            Self::Action { .. } => "Action",
+            Self::XObject { .. } => "XObject",
+            Self::EmbeddedFile => "EmbeddedFile",
+            Self::FileSpec => "FileSpec",
+            Self::Signature => "Signature",
+            Self::Metadata => "Metadata",
+            Self::ObjectStream => "ObjectStream",
+            Self::XrefStream => "XrefStream",
+            Self::Stream { .. } => "Stream",
+            Self::Dictionary => "Dictionary",
+            Self::Array => "Array",
+            Self::Name => "Name",
+            Self::String => "String",
+            Self::Number => "Number",
+            Self::Boolean => "Boolean",
+            Self::Null => "Null",
+            Self::Reference => "Reference",
+            Self::Other { .. } => "Other",
+        }
+    }
+}
+
+fn classify(dict: &Dictionary, is_stream: bool) -> ObjectType {
+    let name = |key: &[u8]| name_value(dict, key).map(|n| String::from_utf8_lossy(n).into_owned());
+    let subtype = name(b"Subtype");
+
+    match name_value(dict, b"Type") {
+        Some(b"Catalog") => ObjectType::Catalog,
+        Some(b"Pages") => ObjectType::Pages,
+        Some(b"Page") => ObjectType::Page,
+        Some(b"Outlines") => ObjectType::Outlines,
+        Some(b"Font") => ObjectType::Font { subtype },
+        Some(b"FontDescriptor") => ObjectType::FontDescriptor,
+        Some(b"Annot") => ObjectType::Annotation { subtype },
+        Some(b"Action") => ObjectType::Action { kind: name(b"S") },
+        Some(b"XObject") => ObjectType::XObject { subtype },
+        Some(b"EmbeddedFile") => ObjectType::EmbeddedFile,
+        Some(b"Filespec") => ObjectType::FileSpec,
+        Some(b"Sig") | Some(b"DocTimeStamp") => ObjectType::Signature,
+        Some(b"Metadata") => ObjectType::Metadata,
+        Some(b"ObjStm") => ObjectType::ObjectStream,
+        Some(b"XRef") => ObjectType::XrefStream,
+        Some(other) => ObjectType::Other { type_name: String::from_utf8_lossy(other).into_owned() },
+        // /Type is optional for most objects; fall back to their required keys
+        None => match name_value(dict, b"Subtype") {
+            Some(b"Image") | Some(b"Form") | Some(b"PS") if is_stream => ObjectType::XObject { subtype },
+            Some(s) if dict.has(b"Rect") && ANNOTATION_SUBTYPES.contains(&s) => ObjectType::Annotation { subtype },
+            _ if dict.has(b"S") && !is_stream => ObjectType::Action { kind: name(b"S") },
+            _ if is_stream => ObjectType::Stream { subtype },
+            _ => ObjectType::Dictionary,
+        },
+    }
+}
+
+impl fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let detail = match self {
+            Self::Font { subtype }
+            | Self::Annotation { subtype }
+            | Self::XObject { subtype }
+            | Self::Stream { subtype } => subtype.as_deref(),
+            Self::Action { kind } => kind.as_deref(),
+            Self::Other { type_name } => return f.pad(&format!("/{}", type_name)),
+            _ => None,
+        };
+        // Padded so the label lines up in tabular report output
+        match detail {
+            Some(detail) => f.pad(&format!("{}/{}", self.kind(), detail)),
+            None => f.pad(self.kind()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_classification() {
+        let font = Object::Dictionary(dictionary! { "Type" => "Font", "Subtype" => "Type1" });
+        assert_eq!(ObjectType::of(&font), ObjectType::Font { subtype: Some("Type1".into()) });
+        assert_eq!(ObjectType::of(&font).to_string(), "Font/Type1");
+
+        let link = Object::Dictionary(dictionary! { "Subtype" => "Link", "Rect" => vec![0.into(); 4] });
+        assert_eq!(ObjectType::of(&link), ObjectType::Annotation { subtype: Some("Link".into()) });
+
+        let action = Object::Dictionary(dictionary! { "S" => "JavaScript" });
+        assert_eq!(ObjectType::of(&action), ObjectType::Action { kind: Some("JavaScript".into()) });
+
+        let image = Object::Stream(Stream::new(dictionary! { "Subtype" => "Image" }, vec![]));
+        assert_eq!(ObjectType::of(&image), ObjectType::XObject { subtype: Some("Image".into()) });
+
+        let custom = Object::Dictionary(dictionary! { "Type" => "Vendor" });
+        assert_eq!(ObjectType::of(&custom).to_string(), "/Vendor");
+        assert_eq!(ObjectType::of(&Object::Integer(3)), ObjectType::Number);
+    }
+
+    #[test]
+    fn test_serialization_is_tagged() {
+        let json = serde_json::to_string(&ObjectType::Annotation { subtype: Some("Widget".into()) }).unwrap();
+        assert_eq!(json, r#"{"kind":"Annotation","subtype":"Widget"}"#);
+        assert_eq!(serde_json::to_string(&ObjectType::Page).unwrap(), r#"{"kind":"Page"}"#);
+    }
+}
@@ -0,0 +1,128 @@
+//! XMP properties mirroring the document information dictionary
+//! Created: 2026-10-16 11:05:10 UTC
+//! Author: kartik4091
+//!
+//! Reads the XMP properties that carry the same facts as the information
+//! dictionary — title, author, subject, keywords, creator tool, producer
+//! and the creation and modification dates — in either the attribute or
+//! the element form, taking the first item of `rdf:Alt`/`rdf:Seq` arrays.
+//! A minimal packet holding those properties can be written back.
+
+use std::fmt::Write;
+
+use regex::Regex;
+
+use super::escape_markup;
+
+/// Information dictionary key and the XMP property mirroring it
+pub const INFO_PROPERTIES: [(&str, &str); 8] = [
+    ("Title", "dc:title"),
+    ("Author", "dc:creator"),
+    ("Subject", "dc:description"),
+    ("Keywords", "pdf:Keywords"),
+    ("Creator", "xmp:CreatorTool"),
+    ("Producer", "pdf:Producer"),
+    ("CreationDate", "xmp:CreateDate"),
+    ("ModDate", "xmp:ModifyDate"),
+];
+
+/// Value of a property, or the first item of an array property
+pub fn property(xmp: &str, name: &str) -> Option<String> {
+    let name = regex::escape(name);
+    let attribute = Regex::new(&format!(r#"\s{}\s*=\s*(?:"([^"]*)"|'([^']*)')"#, name)).expect("valid XMP pattern");
+    let element = Regex::new(&format!(r"(?s)<{0}(?:\s[^>]*)?>(.*?)</{0}>", name)).expect("valid XMP pattern");
+    let item = Regex::new(r"(?s)<rdf:li(?:\s[^>]*)?>(.*?)</rdf:li>").expect("valid XMP pattern");
+
+    let value = if let Some(captures) = attribute.captures(xmp) {
+        captures.get(1).or_else(|| captures.get(2))?.as_str().to_string()
+    } else {
+        let inner = element.captures(xmp)?.get(1)?.as_str();
+        match item.captures(inner) {
+            Some(captures) => captures[1].to_string(),
+            None if inner.contains('<') => return None,
+            None => inner.to_string(),
+        }
+    };
+    let value = unescape(value.trim());
+    (!value.is_empty()).then_some(value)
+}
+
+/// Properties of [`INFO_PROPERTIES`] present in a packet, by property name
+pub fn info_properties(xmp: &str) -> Vec<(&'static str, String)> {
+    INFO_PROPERTIES
+        .iter()
+        .filter_map(|&(_, name)| property(xmp, name).map(|value| (name, value)))
+        .collect()
+}
+
+/// Packet holding the given properties; `dc:` properties are written as
+/// the arrays the schema requires
+pub fn packet(properties: &[(&str, String)]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n");
+    out.push_str("<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n");
+    out.push_str(" <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n");
+    out.push_str("  <rdf:Description rdf:about=\"\"");
+    out.push_str(" xmlns:dc=\"http://purl.org/dc/elements/1.1/\"");
+    out.push_str(" xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"");
+    out.push_str(" xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\">\n");
+    for (name, value) in properties {
+        let value = escape_markup(value);
+        let _ = match *name {
+            "dc:creator" => writeln!(
+                out,
+                "   <{0}><rdf:Seq><rdf:li>{1}</rdf:li></rdf:Seq></{0}>",
+                name, value
+            ),
+            name if name.starts_with("dc:") => writeln!(
+                out,
+                "   <{0}><rdf:Alt><rdf:li xml:lang=\"x-default\">{1}</rdf:li></rdf:Alt></{0}>",
+                name, value
+            ),
+            name => writeln!(out, "   <{0}>{1}</{0}>", name, value),
+        };
+    }
+    out.push_str("  </rdf:Description>\n </rdf:RDF>\n</x:xmpmeta>\n<?xpacket end=\"w\"?>");
+    out
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_property_forms() {
+        let xmp = r#"<x:xmpmeta><rdf:Description xmp:CreatorTool="Word &amp; Co" pdf:Producer=''>
+            <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Report</rdf:li></rdf:Alt></dc:title>
+            <xmp:CreateDate>2024-03-01T09:30:00Z</xmp:CreateDate>
+            <dc:subject><rdf:Bag/></dc:subject>
+        </rdf:Description></x:xmpmeta>"#;
+
+        assert_eq!(property(xmp, "xmp:CreatorTool").as_deref(), Some("Word & Co"));
+        assert_eq!(property(xmp, "dc:title").as_deref(), Some("Report"));
+        assert_eq!(property(xmp, "xmp:CreateDate").as_deref(), Some("2024-03-01T09:30:00Z"));
+        assert!(property(xmp, "pdf:Producer").is_none());
+        assert!(property(xmp, "dc:subject").is_none());
+        assert!(property(xmp, "dc:creator").is_none());
+    }
+
+    #[test]
+    fn test_packet_round_trip() {
+        let properties = vec![
+            ("dc:title", "Q3 <draft>".to_string()),
+            ("dc:creator", "Jane".to_string()),
+            ("xmp:CreateDate", "2024-03-01T09:30:00Z".to_string()),
+        ];
+        let xmp = packet(&properties);
+        assert!(xmp.contains("<rdf:Seq><rdf:li>Jane</rdf:li></rdf:Seq>"));
+        assert_eq!(info_properties(&xmp), properties);
+    }
+}
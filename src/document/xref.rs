@@ -0,0 +1,357 @@
+//! Raw cross-reference section parsing
+//! Created: 2026-10-16 08:42:56 UTC
+//! Author: kartik4091
+//!
+//! lopdf merges every xref section into one table while loading, which
+//! hides exactly the inconsistencies forensic analysis is interested in.
+//! This module reads each section on its own, following the /Prev chain
+//! from the last `startxref`.
+
+use std::collections::{btree_map::Entry, BTreeMap, HashSet};
+
+use lopdf::{Dictionary, Object};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    parser::{parse_indirect, Parser},
+    revisions::find_startxref,
+    stream_data,
+};
+use crate::error::{Error, Result};
+
+/// Storage format of a cross-reference section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum XrefKind {
+    /// Classic `xref` keyword table
+    Table,
+
+    /// Cross-reference stream (`/Type /XRef`)
+    Stream,
+}
+
+/// State recorded for one object number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum XrefState {
+    /// Free entry pointing at the next free object number
+    Free { next: u64, generation: u64 },
+
+    /// In-use entry at a byte offset
+    InUse { offset: u64, generation: u64 },
+
+    /// Object stored at `index` inside object stream `stream`
+    Compressed { stream: u64, index: u64 },
+}
+
+/// A single cross-reference entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XrefRecord {
+    /// Object number
+    pub number: u32,
+
+    /// Recorded state
+    pub state: XrefState,
+}
+
+/// A subsection header (`start count`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subsection {
+    /// First object number
+    pub start: u32,
+
+    /// Number of entries
+    pub count: u32,
+}
+
+/// One cross-reference section as written in the file
+#[derive(Debug, Clone)]
+pub struct XrefSection {
+    /// Offset of the `xref` keyword or of the xref stream object
+    pub offset: u64,
+
+    /// Storage format
+    pub kind: XrefKind,
+
+    /// Subsection headers in file order
+    pub subsections: Vec<Subsection>,
+
+    /// Entries in file order
+    pub entries: Vec<XrefRecord>,
+
+    /// Trailer dictionary (the stream dictionary for xref streams)
+    pub trailer: Dictionary,
+
+    /// Whether this section was reached through a hybrid /XRefStm entry
+    pub via_xref_stm: bool,
+}
+
+impl XrefSection {
+    /// Value of /Prev in the trailer
+    pub fn prev(&self) -> Option<u64> {
+        trailer_offset(&self.trailer, b"Prev")
+    }
+
+    /// Value of /XRefStm in the trailer (hybrid-reference files)
+    pub fn xref_stm(&self) -> Option<u64> {
+        trailer_offset(&self.trailer, b"XRefStm")
+    }
+}
+
+/// Reads every cross-reference section reachable from the last `startxref`,
+/// newest first. Hybrid /XRefStm streams directly follow their table.
+pub fn read_sections(data: &[u8]) -> Result<Vec<XrefSection>> {
+    let start = find_startxref(data).ok_or_else(|| Error::Pdf("No startxref found".into()))?;
+    let mut sections = Vec::new();
+    let mut visited = HashSet::new();
+    let mut next = Some(start);
+
+    while let Some(offset) = next.take() {
+        if !visited.insert(offset) {
+            // A /Prev loop; stop rather than spin
+            break;
+        }
+
+        let section = read_section(data, offset)?;
+        next = section.prev();
+        let hybrid = section.xref_stm();
+        sections.push(section);
+
+        if let Some(stm_offset) = hybrid {
+            if visited.insert(stm_offset) {
+                if let Ok(mut stream_section) = read_section(data, stm_offset) {
+                    stream_section.via_xref_stm = true;
+                    sections.push(stream_section);
+                }
+            }
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Merges sections (newest first, as returned by [`read_sections`]) into the
+/// entry a reader resolves for each object number.
+///
+/// The newest entry wins, except that a hybrid /XRefStm stream fills in
+/// numbers its table marks as free.
+pub fn effective_entries(sections: &[XrefSection]) -> BTreeMap<u32, XrefState> {
+    let mut effective = BTreeMap::new();
+    let mut from_table = HashSet::new();
+
+    for section in sections {
+        if !section.via_xref_stm {
+            from_table.clear();
+        }
+        for record in &section.entries {
+            match effective.entry(record.number) {
+                Entry::Vacant(slot) => {
+                    slot.insert(record.state);
+                    if !section.via_xref_stm {
+                        from_table.insert(record.number);
+                    }
+                }
+                Entry::Occupied(mut slot)
+                    if section.via_xref_stm
+                        && from_table.contains(&record.number)
+                        && matches!(slot.get(), XrefState::Free { .. }) =>
+                {
+                    slot.insert(record.state);
+                }
+                Entry::Occupied(_) => {}
+            }
+        }
+    }
+
+    effective
+}
+
+/// Reads the section at a byte offset, detecting its format
+pub fn read_section(data: &[u8], offset: u64) -> Result<XrefSection> {
+    let pos = usize::try_from(offset).map_err(|_| Error::Pdf("Offset out of range".into()))?;
+    if pos >= data.len() {
+        return Err(Error::Pdf(format!("xref offset {} beyond end of file", offset)));
+    }
+
+    let mut parser = Parser::new(data, pos);
+    if parser.eat_keyword(b"xref") {
+        read_table(parser, offset)
+    } else {
+        read_stream(data, offset)
+    }
+}
+
+fn read_table(mut parser: Parser<'_>, offset: u64) -> Result<XrefSection> {
+    let mut subsections = Vec::new();
+    let mut entries = Vec::new();
+
+    loop {
+        if parser.eat_keyword(b"trailer") {
+            break;
+        }
+        let (Some(start), Some(count)) = (parser.parse_unsigned(), parser.parse_unsigned()) else {
+            return Err(Error::Pdf(format!("Malformed xref table at {}", offset)));
+        };
+        subsections.push(Subsection { start: start as u32, count: count as u32 });
+
+        for i in 0..count {
+            let (Some(field1), Some(field2)) = (parser.parse_unsigned(), parser.parse_unsigned()) else {
+                return Err(Error::Pdf(format!("Truncated xref subsection at {}", offset)));
+            };
+            let state = if parser.eat_keyword(b"n") {
+                XrefState::InUse { offset: field1, generation: field2 }
+            } else if parser.eat_keyword(b"f") {
+                XrefState::Free { next: field1, generation: field2 }
+            } else {
+                return Err(Error::Pdf(format!("Invalid xref entry type at {}", parser.position())));
+            };
+            entries.push(XrefRecord { number: (start + i) as u32, state });
+        }
+    }
+
+    let trailer = match parser.parse_object()? {
+        Object::Dictionary(dict) => dict,
+        _ => return Err(Error::Pdf(format!("Trailer at {} is not a dictionary", offset))),
+    };
+
+    Ok(XrefSection {
+        offset,
+        kind: XrefKind::Table,
+        subsections,
+        entries,
+        trailer,
+        via_xref_stm: false,
+    })
+}
+
+fn read_stream(data: &[u8], offset: u64) -> Result<XrefSection> {
+    let parsed = parse_indirect(data, offset as usize)?;
+    let stream = parsed
+        .object
+        .as_stream()
+        .map_err(|_| Error::Pdf(format!("No xref table or stream at {}", offset)))?;
+    let dict = &stream.dict;
+
+    let widths: Vec<usize> = dict
+        .get(b"W")
+        .and_then(Object::as_array)
+        .map_err(|_| Error::Pdf("Xref stream without /W".into()))?
+        .iter()
+        .map(|w| w.as_i64().unwrap_or(0).max(0) as usize)
+        .collect();
+    if widths.len() != 3 || widths.iter().any(|&w| w > 8) || widths.iter().sum::<usize>() == 0 {
+        return Err(Error::Pdf(format!("Invalid /W in xref stream at {}", offset)));
+    }
+
+    let size = dict.get(b"Size").and_then(Object::as_i64).unwrap_or(0).max(0) as u64;
+    let index: Vec<u64> = match dict.get(b"Index").and_then(Object::as_array) {
+        Ok(items) => items.iter().map(|i| i.as_i64().unwrap_or(0).max(0) as u64).collect(),
+        Err(_) => vec![0, size],
+    };
+
+    let content = stream_data(stream)?;
+    let row = widths.iter().sum::<usize>();
+    let mut subsections = Vec::new();
+    let mut entries = Vec::new();
+    let mut rows = content.chunks(row);
+
+    for pair in index.chunks(2) {
+        let [start, count] = [pair[0], *pair.get(1).unwrap_or(&0)];
+        subsections.push(Subsection { start: start as u32, count: count as u32 });
+
+        for i in 0..count {
+            let Some(bytes) = rows.next().filter(|r| r.len() == row) else { break };
+            let (f1, rest) = bytes.split_at(widths[0]);
+            let (f2, f3) = rest.split_at(widths[1]);
+            let kind = if widths[0] == 0 { 1 } else { be_uint(f1) };
+            let (a, b) = (be_uint(f2), be_uint(f3));
+
+            let state = match kind {
+                0 => XrefState::Free { next: a, generation: b },
+                2 => XrefState::Compressed { stream: a, index: b },
+                _ => XrefState::InUse { offset: a, generation: b },
+            };
+            entries.push(XrefRecord { number: (start + i) as u32, state });
+        }
+    }
+
+    Ok(XrefSection {
+        offset,
+        kind: XrefKind::Stream,
+        subsections,
+        entries,
+        trailer: dict.clone(),
+        via_xref_stm: false,
+    })
+}
+
+fn be_uint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0u64, |acc, &b| (acc << 8) | u64::from(b))
+}
+
+fn trailer_offset(trailer: &Dictionary, key: &[u8]) -> Option<u64> {
+    trailer.get(key).ok()?.as_i64().ok().filter(|&v| v >= 0).map(|v| v as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_read_single_table() {
+        let data = fixtures::simple_pdf();
+        let sections = read_sections(&data).unwrap();
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].kind, XrefKind::Table);
+        assert_eq!(sections[0].entries.len(), 7);
+        assert!(matches!(sections[0].entries[0].state, XrefState::Free { .. }));
+    }
+
+    #[test]
+    fn test_prev_chain() {
+        let base = fixtures::simple_pdf();
+        let data = fixtures::append_revision(&base, &[(7, "<< /Type /Annot >>")]);
+        let sections = read_sections(&data).unwrap();
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].entries.len(), 1);
+        assert_eq!(sections[0].entries[0].number, 7);
+        assert_eq!(sections[0].prev(), Some(sections[1].offset));
+    }
+
+    #[test]
+    fn test_effective_entries_prefer_newest() {
+        let base = fixtures::simple_pdf();
+        let data = fixtures::append_revision(&base, &[(4, "<< /Length 0 >>\nstream\n\nendstream")]);
+        let sections = read_sections(&data).unwrap();
+        let effective = effective_entries(&sections);
+
+        let XrefState::InUse { offset, .. } = effective[&4] else {
+            panic!("object 4 should be in use");
+        };
+        assert!(offset as usize >= base.len());
+        assert_eq!(effective.len(), 7);
+    }
+
+    #[test]
+    fn test_xref_stream() {
+        let mut data = b"%PDF-1.5\n".to_vec();
+        let offset = data.len();
+        // Rows: type(1) offset(2) gen(1)
+        let rows = [0u8, 0, 0, 255, 1, 0, 9, 0];
+        data.extend_from_slice(
+            format!("2 0 obj\n<< /Type /XRef /Size 2 /W [1 2 1] /Length {} >>\nstream\n", rows.len())
+                .as_bytes(),
+        );
+        data.extend_from_slice(&rows);
+        data.extend_from_slice(format!("\nendstream\nendobj\nstartxref\n{}\n%%EOF\n", offset).as_bytes());
+
+        let sections = read_sections(&data).unwrap();
+        assert_eq!(sections[0].kind, XrefKind::Stream);
+        assert_eq!(
+            sections[0].entries[1].state,
+            XrefState::InUse { offset: 9, generation: 0 }
+        );
+    }
+}
@@ -20,6 +20,18 @@ pub enum Error {
     
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    
+    #[error("YAML serialization error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    
+    #[error("Binary encoding error: {0}")]
+    Encoding(String),
+    
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+    
+    #[error("Security limit exceeded: {0}")]
+    Security(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
\ No newline at end of file
@@ -0,0 +1,595 @@
+//! Unified findings
+//! Created: 2026-10-16 10:13:52 UTC
+//! Author: kartik4091
+//!
+//! Every detection pass keeps its own report type, rich enough for the
+//! detailed text sections. This module flattens them into [`Finding`]s
+//! with a stable id, severity, confidence, category, evidence, object
+//! references, a remediation hint and ATT&CK techniques, which is what
+//! the policy, the risk score and machine-readable output work from.
+//!
+//! Ids are `<analyzer>.<kind>`, the analyzer being the pass name used by
+//! the [registry](crate::registry) and the kind the snake-case name of the
+//! detector's kind enum where it has one.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    attack,
+    detectors::{reader_features::ReaderFeature, Confidence, Finding, RiskCategory, Severity},
+    PdfAnalysis,
+};
+
+const REMOVE_ACTIVE_CONTENT: &str = "Strip scripts and actions, or open the file with JavaScript disabled";
+const REBUILD: &str = "Rewrite the file with a conforming writer to rebuild its structure";
+const DROP_HIDDEN: &str = "Remove the hidden content before sharing the file";
+const VERIFY_LINKS: &str = "Do not follow the links; verify the sender through another channel";
+const APPLY_REDACTIONS: &str = "Apply the redactions with `pdx redact apply` and review the result";
+
+/// Every finding of an analysis, including those of embedded documents
+pub fn collect(analysis: &PdfAnalysis) -> Vec<Finding> {
+    let mut found = Vec::new();
+    structure(analysis, &mut found);
+    concealment(analysis, &mut found);
+    active_content(analysis, &mut found);
+    phishing(analysis, &mut found);
+    data_exposure(analysis, &mut found);
+    custom(analysis, &mut found);
+    attack::tag(&mut found);
+
+    // Object ids of embedded documents are their own, so attachments are
+    // matched before those findings are added
+    let known: Vec<_> = analysis.known_good.iter().filter_map(|m| m.object).collect();
+    for finding in &mut found {
+        if !finding.objects.is_empty() && finding.objects.iter().all(|id| known.contains(id)) {
+            known_good(finding);
+        }
+    }
+
+    // A dropper is as dangerous as what it drops
+    for embedded in &analysis.embedded {
+        found.extend(collect(&embedded.analysis).into_iter().map(|mut finding| {
+            finding.title = format!("{}: {}", embedded.name, finding.title);
+            finding.evidence.push(format!("Embedded document {}", embedded.name));
+            finding
+        }));
+    }
+
+    if analysis.known_good.iter().any(|m| m.object.is_none()) {
+        found.iter_mut().for_each(known_good);
+    }
+
+    found
+}
+
+/// Keeps a finding on known-good content but takes it out of the verdict
+fn known_good(finding: &mut Finding) {
+    if finding.severity != Severity::Info {
+        finding.severity = Severity::Info;
+        finding.evidence.push("Content matches a known-good hash".to_string());
+    }
+}
+
+/// Snake-case name of a kind enum's variant, as used in finding ids
+fn kind(kind: &impl Serialize) -> String {
+    let name = match serde_json::to_value(kind) {
+        Ok(Value::String(name)) => name,
+        Ok(Value::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+        _ => String::new(),
+    };
+    snake_case(&name)
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && previous_lower {
+            out.push('_');
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+fn finding(
+    analyzer: &str,
+    kind: &str,
+    category: RiskCategory,
+    severity: Severity,
+    confidence: Confidence,
+    title: impl Into<String>,
+) -> Finding {
+    let id = if kind.is_empty() {
+        analyzer.to_string()
+    } else {
+        format!("{}.{}", analyzer, kind)
+    };
+    Finding::new(id, severity, category, title).confidence(confidence)
+}
+
+/// File structure findings
+fn structure(analysis: &PdfAnalysis, found: &mut Vec<Finding>) {
+    use Confidence::*;
+    let new = |analyzer: &str, kind: &str, severity, confidence, title: &str| {
+        finding(analyzer, kind, RiskCategory::Structure, severity, confidence, title).remediation(REBUILD)
+    };
+
+    found.extend(
+        analysis
+            .header
+            .anomalies
+            .iter()
+            .map(|a| new("header", &kind(&a.kind), a.severity, High, &a.description)),
+    );
+    found.extend(analysis.xref_anomalies.iter().map(|a| {
+        let mut f = new("xref_anomalies", &kind(&a.kind), a.severity, High, &a.description);
+        if let Some(offset) = a.section_offset {
+            f = f.evidence(format!("Section at offset {}", offset));
+        }
+        f.objects(a.object.map(|number| (number, 0)))
+    }));
+    if let Some(hybrid) = &analysis.hybrid_xref {
+        for section in &hybrid.sections {
+            found.extend(section.divergences.iter().map(|d| {
+                new("hybrid_xref", "divergence", d.severity, High, &d.description)
+                    .evidence(format!("Table: {:?}, stream: {:?}", d.table, d.stream))
+                    .objects([(d.number, 0)])
+            }));
+        }
+    }
+    found.extend(analysis.signatures.backdating.iter().map(|b| {
+        finding(
+            "signatures",
+            "backdating",
+            RiskCategory::Structure,
+            b.severity,
+            Medium,
+            &b.description,
+        )
+        .objects([b.earlier, b.later])
+    }));
+    found.extend(analysis.trailer_ids.anomalies.iter().map(|a| {
+        new("trailer_ids", &kind(&a.kind), a.severity, High, &a.description)
+            .evidence(format!("Revision {}", a.revision))
+    }));
+    found.extend(analysis.slack.gaps.iter().map(|g| {
+        new(
+            "slack",
+            &kind(&g.class),
+            g.severity,
+            Medium,
+            &format!("{} unclaimed bytes ({:?}) at offset {}", g.length, g.class, g.start),
+        )
+        .evidence(format!("Entropy {:.2}", g.entropy))
+        .evidence(g.preview.clone())
+    }));
+    found.extend(
+        analysis
+            .object_streams
+            .findings
+            .iter()
+            .map(|f| new("object_streams", &kind(&f.kind), f.severity, High, &f.description).objects([f.stream])),
+    );
+    found.extend(analysis.orphan_objects.iter().map(|o| {
+        finding(
+            "orphan_objects",
+            &kind(&o.category),
+            RiskCategory::Structure,
+            o.severity,
+            Medium,
+            &o.description,
+        )
+        .objects([o.id])
+    }));
+    found.extend(
+        analysis
+            .page_tree
+            .iter()
+            .map(|p| new("page_tree", &kind(&p.kind), p.severity, High, &p.description).objects(p.id)),
+    );
+    if let Some(linearization) = &analysis.linearization {
+        found.extend(
+            linearization
+                .anomalies
+                .iter()
+                .map(|a| new("linearization", &kind(&a.kind), a.severity, High, &a.description)),
+        );
+    }
+    found.extend(analysis.dangling_references.iter().map(|d| {
+        new("dangling_references", &kind(&d.kind), d.severity, High, &d.description)
+            .objects([d.target])
+            .objects(d.sites.iter().filter_map(|site| site.object))
+    }));
+    found.extend(analysis.stream_lengths.iter().map(|s| {
+        new("stream_lengths", &kind(&s.kind), s.severity, High, &s.description)
+            .evidence(format!(
+                "Declared {:?}, actual {} bytes at offset {}",
+                s.declared, s.actual, s.offset
+            ))
+            .objects([s.id])
+    }));
+    found.extend(analysis.decoder_anomalies.iter().map(|a| {
+        new("decoder_anomalies", &kind(&a.kind), a.severity, Medium, &a.description).objects([a.id])
+    }));
+    found.extend(analysis.limit_violations.iter().map(|v| {
+        finding(
+            "limits",
+            &kind(&v.kind),
+            RiskCategory::Structure,
+            v.severity,
+            High,
+            &v.description,
+        )
+        .evidence(format!("Limit {}, actual {}", v.limit, v.actual))
+        .objects(v.id)
+    }));
+    if let Some(divergences) = &analysis.parser_divergences {
+        found.extend(divergences.iter().map(|d| {
+            new(
+                "parser_divergences",
+                "",
+                Severity::High,
+                High,
+                &format!("Object {} {} renders differently across viewers", d.id.0, d.id.1),
+            )
+            .objects([d.id])
+        }));
+    }
+}
+
+/// Hidden or disguised content findings
+fn concealment(analysis: &PdfAnalysis, found: &mut Vec<Finding>) {
+    use Confidence::*;
+    let new = |analyzer: &str, kind: &str, severity, confidence, title: &str| {
+        finding(analyzer, kind, RiskCategory::Concealment, severity, confidence, title)
+    };
+
+    if let Some(trailing) = &analysis.trailing_data {
+        let mut f = new(
+            "trailing_data",
+            "",
+            trailing.severity,
+            High,
+            &format!("{} bytes after final %%EOF", trailing.size),
+        )
+        .evidence(format!("Offset {}, SHA-256 {}", trailing.offset, trailing.sha256))
+        .remediation("Truncate the file after the final %%EOF");
+        if let Some(format) = &trailing.format {
+            f = f.evidence(format!("Detected format: {}", format));
+        }
+        found.push(f);
+    }
+    found.extend(analysis.polyglots.iter().map(|p| {
+        new("polyglots", &snake_case(&p.format), p.severity, High, &p.description)
+            .evidence(format!("{} at offset {}", p.mime, p.offset))
+    }));
+    if let Some(antivirus) = &analysis.antivirus {
+        found.extend(antivirus.detections.iter().map(|d| {
+            new(
+                "clamav",
+                &d.kind.to_string(),
+                Severity::Critical,
+                High,
+                &format!("ClamAV detected {} in {} {}", d.signature, d.kind, d.name),
+            )
+            .evidence(format!("SHA-256 {}", d.sha256))
+            .objects(d.object)
+            .remediation("Quarantine the file; do not open it or extract its attachments")
+        }));
+    }
+    if let Some(virustotal) = &analysis.virustotal {
+        found.extend(virustotal.samples.iter().filter_map(|s| {
+            let f = new(
+                "virustotal",
+                &s.kind.to_string(),
+                virustotal.severity(s)?,
+                High,
+                &format!("VirusTotal: {}/{} engines flag {} {}", s.malicious, s.engines, s.kind, s.name),
+            )
+            .evidence(format!("{} suspicious verdicts", s.suspicious))
+            .evidence(s.permalink.clone())
+            .objects(s.object)
+            .remediation("Quarantine the file; do not open it or extract its attachments");
+            Some(f)
+        }));
+    }
+    found.extend(analysis.filter_anomalies.iter().map(|f| {
+        new("filter_anomalies", &kind(&f.kind), f.severity, High, &f.description)
+            .evidence(format!("Filters: {}", f.filters.join(" ")))
+            .objects([f.id])
+    }));
+    found.extend(analysis.obfuscated_tokens.iter().map(|t| {
+        new("obfuscated_tokens", &kind(&t.kind), t.severity, High, &t.description)
+            .evidence(format!("{} decodes to {} at offset {}", t.raw, t.decoded, t.offset))
+            .objects(t.object)
+    }));
+    found.extend(analysis.stream_entropy.packed.iter().map(|p| {
+        new("stream_entropy", "packed", p.severity, Medium, &p.description)
+            .evidence(format!("Entropy {:.2}", p.entropy))
+            .objects([p.id])
+    }));
+    found.extend(analysis.attachments.mismatches.iter().map(|m| {
+        new("attachments", "type_mismatch", m.severity, High, &m.description)
+            .evidence(format!("Claimed type: {}", m.claim))
+            .objects([m.id])
+            .remediation("Do not open the attachment with the application its name suggests")
+    }));
+    found.extend(
+        analysis.fonts.findings.iter().map(|f| {
+            new("fonts", &kind(&f.issue), f.severity, Medium, &f.description).objects(f.fonts.iter().copied())
+        }),
+    );
+    found.extend(analysis.invisible_text.iter().map(|t| {
+        new(
+            "invisible_text",
+            &kind(&t.technique),
+            t.severity,
+            Medium,
+            &t.description,
+        )
+        .evidence(t.text.clone())
+        .objects([t.page])
+        .remediation(DROP_HIDDEN)
+    }));
+    found.extend(analysis.layers.hidden_content.iter().map(|c| {
+        new("layers", &kind(&c.kind), c.severity, Medium, &c.description)
+            .evidence(c.excerpt.clone())
+            .objects(c.object.or(c.page))
+            .remediation(DROP_HIDDEN)
+    }));
+    found.extend(analysis.layers.layers.iter().filter(|l| l.print_only).map(|l| {
+        new(
+            "layers",
+            "print_only",
+            Severity::Medium,
+            High,
+            &format!("Layer \"{}\" is hidden on screen but printed", l.name),
+        )
+        .objects([l.id])
+        .remediation(DROP_HIDDEN)
+    }));
+}
+
+/// Interactive content findings
+fn active_content(analysis: &PdfAnalysis, found: &mut Vec<Finding>) {
+    use Confidence::*;
+    let new = |analyzer: &str, kind: &str, severity, title: &str| {
+        finding(analyzer, kind, RiskCategory::ActiveContent, severity, High, title).remediation(REMOVE_ACTIVE_CONTENT)
+    };
+
+    for usage in &analysis.reader_features.features {
+        let dangerous = matches!(usage.feature, ReaderFeature::JavaScript | ReaderFeature::Launch);
+        let severity = match (dangerous, usage.auto_triggered) {
+            (true, true) => Severity::High,
+            (true, false) | (false, true) => Severity::Medium,
+            (false, false) => Severity::Low,
+        };
+        let mut f = new(
+            "reader_features",
+            &kind(&usage.feature),
+            severity,
+            &format!("{:?} used by {} objects", usage.feature, usage.objects.len()),
+        )
+        .evidence(format!("Reader setting: {}", usage.setting))
+        .objects(usage.objects.iter().copied());
+        if usage.auto_triggered {
+            f = f.evidence("Triggered without user interaction");
+        }
+        found.push(f);
+    }
+    found.extend(
+        analysis
+            .annotations
+            .suspicious
+            .iter()
+            .map(|s| new("annotations", &kind(&s.issue), s.severity, &s.description).objects(s.id.or(s.page))),
+    );
+    if let Some(forms) = &analysis.forms {
+        found.extend(
+            forms
+                .findings
+                .iter()
+                .map(|f| new("forms", &kind(&f.issue), f.severity, &f.description).objects([f.id])),
+        );
+    }
+    found.extend(analysis.media.annotations.iter().map(|a| {
+        new("media", &snake_case(&a.subtype), a.severity, &a.description)
+            .confidence(if a.auto_activated { High } else { Medium })
+            .objects([a.id])
+    }));
+    found.extend(analysis.media.payloads.iter().map(|p| {
+        new(
+            "media",
+            &format!("{}_payload", kind(&p.format)),
+            p.severity,
+            &format!(
+                "{:?} payload in object {} {} ({} bytes)",
+                p.format, p.id.0, p.id.1, p.size
+            ),
+        )
+        .evidence(format!("SHA-256 {}", p.sha256))
+        .objects([p.id])
+    }));
+    if let Some(xfa) = &analysis.xfa {
+        found.extend(
+            xfa.findings
+                .iter()
+                .map(|f| new("xfa", &kind(&f.issue), f.severity, &f.description)),
+        );
+    }
+    found.extend(analysis.actions.dangerous().map(|a| {
+        let mut f = new("actions", &snake_case(&a.kind), a.severity, &a.description)
+            .evidence(format!("Trigger: {}", a.trigger))
+            .objects(a.id.or(a.owner));
+        if let Some(target) = &a.target {
+            f = f.evidence(format!("Target: {}", target));
+        }
+        f
+    }));
+    found.extend(analysis.exploits.iter().map(|m| {
+        new("exploits", &m.cve.to_ascii_lowercase(), m.severity, &m.description)
+            .evidence(format!("Signature: {}", m.signature))
+            .objects(m.object)
+            .remediation("Update the PDF reader and do not open the file on unpatched systems")
+    }));
+    found.extend(analysis.shellcode.iter().map(|s| {
+        let f = new("shellcode", &kind(&s.kind), s.severity, &s.description)
+            .evidence(format!("Offset {}, {} bytes", s.offset, s.length))
+            .objects([s.object]);
+        s.disassembly.iter().fold(f, |f, line| f.evidence(line.clone()))
+    }));
+    found.extend(analysis.actions.chain_loops.iter().map(|&(num, gen)| {
+        new(
+            "actions",
+            "chain_loop",
+            Severity::Low,
+            &format!("Action chain loops back to {} {}", num, gen),
+        )
+        .objects([(num, gen)])
+    }));
+}
+
+/// Phishing findings
+fn phishing(analysis: &PdfAnalysis, found: &mut Vec<Finding>) {
+    let new = |analyzer: &str, kind: &str, severity, confidence, title: &str| {
+        finding(analyzer, kind, RiskCategory::Phishing, severity, confidence, title).remediation(VERIFY_LINKS)
+    };
+
+    found.extend(analysis.uris.suspicious().map(|u| {
+        new("uris", "suspicious", u.severity, Confidence::Medium, &u.description)
+            .evidence(u.decoded.clone())
+            .objects(u.sources.iter().filter_map(|(_, id)| *id))
+    }));
+    found.extend(analysis.language.mismatch.iter().map(|m| {
+        new("language", "mismatch", m.severity, Confidence::Low, &m.description).evidence(format!(
+            "Claimed {}, detected {}",
+            m.claimed.join(", "),
+            m.detected
+        ))
+    }));
+    if let Some(severity) = analysis.phishing.severity() {
+        found.push(new(
+            "phishing",
+            "assessment",
+            severity,
+            Confidence::Medium,
+            &analysis.phishing.description(),
+        ));
+    }
+}
+
+/// Data exposure findings
+fn data_exposure(analysis: &PdfAnalysis, found: &mut Vec<Finding>) {
+    found.extend(analysis.redaction_failures.iter().map(|r| {
+        finding(
+            "redaction_failures",
+            &kind(&r.kind),
+            RiskCategory::DataExposure,
+            r.severity,
+            Confidence::High,
+            &r.description,
+        )
+        .evidence(format!("Page {}", r.page_number))
+        .objects([r.page])
+        .objects(r.annotation)
+        .remediation(APPLY_REDACTIONS)
+    }));
+    found.extend(analysis.secrets.iter().map(|s| {
+        finding(
+            "secrets",
+            &kind(&s.kind),
+            RiskCategory::DataExposure,
+            s.severity,
+            Confidence::Medium,
+            &s.description,
+        )
+        .evidence(s.masked.clone())
+        .remediation("Revoke the credential and remove it from the document")
+    }));
+}
+
+/// Findings of user-supplied detection content
+fn custom(analysis: &PdfAnalysis, found: &mut Vec<Finding>) {
+    let new = |analyzer: &str, kind: &str, severity, confidence, title: &str| {
+        finding(analyzer, kind, RiskCategory::Custom, severity, confidence, title)
+    };
+
+    found.extend(analysis.yara_matches.iter().map(|m| {
+        new("yara", &m.rule, m.severity, Confidence::High, &m.description)
+            .evidence(format!("Offsets: {:?}", m.offsets))
+            .objects(m.object)
+            .techniques(m.tags.iter().filter_map(|t| attack::technique_tag(t)))
+    }));
+    found.extend(analysis.rule_matches.iter().map(|m| {
+        new("rules", &m.rule, m.severity, Confidence::High, &m.description)
+            .objects(m.object)
+            .techniques(m.tags.iter().filter_map(|t| attack::technique_tag(t)))
+    }));
+    found.extend(
+        analysis
+            .script_findings
+            .iter()
+            .map(|f| new("scripts", &f.script, f.severity, Confidence::Medium, &f.description).objects(f.object)),
+    );
+    found.extend(
+        analysis
+            .plugin_findings
+            .iter()
+            .map(|f| new("plugins", &f.plugin, f.severity, Confidence::Medium, &f.description).objects(f.object)),
+    );
+    found.extend(analysis.detector_findings.iter().cloned());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        detectors::{
+            actions::ActionReport,
+            xref_anomalies::{XrefAnomaly, XrefAnomalyKind},
+        },
+        Analyzer, PdfAnalyzer,
+    };
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_kind_names() {
+        assert_eq!(snake_case("JavaScript"), "java_script");
+        assert_eq!(snake_case("URI"), "uri");
+        assert_eq!(kind(&Confidence::High), "high");
+    }
+
+    #[tokio::test]
+    async fn test_findings_carry_ids_and_objects() {
+        let temp = NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let mut analysis = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+        assert!(analysis.findings.is_empty());
+
+        analysis.actions = ActionReport {
+            actions: Vec::new(),
+            chain_loops: vec![(7, 0)],
+        };
+        analysis.xref_anomalies.push(XrefAnomaly {
+            kind: XrefAnomalyKind::OverlappingEntries,
+            severity: Severity::High,
+            section_offset: Some(120),
+            object: Some(3),
+            description: "Objects 3 and 4 share an offset".into(),
+        });
+
+        let findings = collect(&analysis);
+        let xref = findings.iter().find(|f| f.detector() == "xref_anomalies").unwrap();
+        assert_eq!(xref.id, "xref_anomalies.overlapping_entries");
+        assert_eq!(xref.category, RiskCategory::Structure);
+        assert_eq!(xref.objects, vec![(3, 0)]);
+        assert!(xref.remediation.is_some());
+
+        let chain = findings.iter().find(|f| f.id == "actions.chain_loop").unwrap();
+        assert_eq!(chain.objects, vec![(7, 0)]);
+        assert_eq!(chain.category, RiskCategory::ActiveContent);
+    }
+}
@@ -0,0 +1,430 @@
+//! Opt-in ClamAV scanning of extracted payloads
+//! Created: 2026-10-16 10:25:07 UTC
+//! Author: kartik4091
+//!
+//! Streams the file itself, every embedded file, every image and every
+//! other decoded stream to a local clamd over its `INSTREAM` command and
+//! records the signatures it reports. Payloads are decoded one at a time
+//! as they are sent, and identical payloads are scanned once.
+//! Nothing leaves the machine, but clamd has to be running, so scanning
+//! only happens when a socket is configured.
+
+use std::{collections::HashSet, fmt, path::PathBuf, str::FromStr, time::Duration};
+
+use lopdf::{Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    document::{attachments, name_value, sha256_hex},
+    error::{Error, Result},
+    limits::{self, ResourceLimits},
+};
+
+/// Socket of the Debian and Ubuntu clamav-daemon packages
+pub const DEFAULT_SOCKET: &str = "/var/run/clamav/clamd.ctl";
+
+/// clamd's default `StreamMaxLength` (25 MiB); larger payloads are skipped
+pub const DEFAULT_MAX_SIZE: usize = 25 * 1024 * 1024;
+
+/// Size of the chunks sent to clamd
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Where clamd listens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClamdAddress {
+    /// Local (Unix domain) socket
+    Unix(PathBuf),
+
+    /// TCP `host:port`
+    Tcp(String),
+}
+
+impl Default for ClamdAddress {
+    fn default() -> Self {
+        Self::Unix(PathBuf::from(DEFAULT_SOCKET))
+    }
+}
+
+impl fmt::Display for ClamdAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "{}", path.display()),
+            Self::Tcp(address) => write!(f, "tcp://{}", address),
+        }
+    }
+}
+
+impl FromStr for ClamdAddress {
+    type Err = String;
+
+    /// Parses `tcp://host:port`, or a socket path
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.strip_prefix("tcp://") {
+            Some(address) if address.contains(':') => Ok(Self::Tcp(address.to_string())),
+            Some(address) => Err(format!("clamd address needs a port: {}", address)),
+            None if s.is_empty() => Err("Empty clamd socket path".to_string()),
+            None => Ok(Self::Unix(PathBuf::from(s))),
+        }
+    }
+}
+
+/// Where clamd listens and how much to send it
+#[derive(Debug, Clone)]
+pub struct ClamAvConfig {
+    /// clamd socket
+    pub address: ClamdAddress,
+
+    /// Per-payload timeout, including the connection
+    pub timeout: Duration,
+
+    /// Largest payload sent
+    pub max_size: usize,
+}
+
+impl Default for ClamAvConfig {
+    fn default() -> Self {
+        Self {
+            address: ClamdAddress::default(),
+            timeout: Duration::from_secs(30),
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+}
+
+/// What a scanned payload is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadKind {
+    /// The PDF file itself
+    Document,
+
+    /// Embedded file
+    Attachment,
+
+    /// Image XObject
+    Image,
+
+    /// Any other decoded stream
+    Stream,
+}
+
+impl fmt::Display for PayloadKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Document => "document",
+            Self::Attachment => "attachment",
+            Self::Image => "image",
+            Self::Stream => "stream",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Data handed to clamd
+#[derive(Debug, Clone)]
+pub struct Payload {
+    /// Payload kind
+    pub kind: PayloadKind,
+
+    /// Object holding the payload, `None` for the file itself
+    pub object: Option<ObjectId>,
+
+    /// Attachment name or object label
+    pub name: String,
+
+    /// Decoded content
+    pub data: Vec<u8>,
+}
+
+/// A signature clamd reported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AvDetection {
+    /// Payload kind
+    pub kind: PayloadKind,
+
+    /// Object holding the payload, `None` for the file itself
+    pub object: Option<ObjectId>,
+
+    /// Attachment name or object label
+    pub name: String,
+
+    /// SHA-256 of the payload
+    pub sha256: String,
+
+    /// ClamAV signature name
+    pub signature: String,
+}
+
+/// Outcome of scanning a document's payloads
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ClamAvReport {
+    /// Payloads clamd scanned
+    pub scanned: usize,
+
+    /// Payloads larger than the configured maximum
+    pub skipped: usize,
+
+    /// Payloads clamd flagged
+    pub detections: Vec<AvDetection>,
+
+    /// Payloads that could not be scanned, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// clamd client
+#[derive(Debug, Clone)]
+pub struct ClamAvScanner {
+    config: ClamAvConfig,
+}
+
+impl ClamAvScanner {
+    /// Creates a scanner; nothing is connected until the first scan
+    pub fn new(config: ClamAvConfig) -> Self {
+        Self { config }
+    }
+
+    /// Scans one buffer, returning the signature name if clamd flags it
+    pub async fn scan(&self, data: &[u8]) -> Result<Option<String>> {
+        let reply = tokio::time::timeout(self.config.timeout, async {
+            match &self.config.address {
+                #[cfg(unix)]
+                ClamdAddress::Unix(path) => instream(tokio::net::UnixStream::connect(path).await?, data).await,
+                #[cfg(not(unix))]
+                ClamdAddress::Unix(_) => Err(Error::Protocol("Unix sockets are not available here".to_string())),
+                ClamdAddress::Tcp(address) => instream(tokio::net::TcpStream::connect(address).await?, data).await,
+            }
+        })
+        .await
+        .map_err(|_| Error::Protocol(format!("clamd at {} timed out", self.config.address)))??;
+        parse_reply(&reply)
+    }
+
+    /// Scans every payload, recording the ones clamd fails on. Detections
+    /// made before clamd becomes unreachable are kept.
+    #[instrument(skip_all)]
+    pub async fn scan_payloads(&self, payloads: impl IntoIterator<Item = Payload>) -> ClamAvReport {
+        let mut report = ClamAvReport::default();
+        let mut seen = HashSet::new();
+        for payload in payloads {
+            let sha256 = sha256_hex(&payload.data);
+            if !seen.insert(sha256.clone()) {
+                continue;
+            }
+            if payload.data.len() > self.config.max_size {
+                debug!("Skipping {} ({} bytes)", payload.name, payload.data.len());
+                report.skipped += 1;
+                continue;
+            }
+            let signature = match self.scan(&payload.data).await {
+                Ok(signature) => signature,
+                Err(e) => {
+                    report.errors.push(format!("{} {}: {}", payload.kind, payload.name, e));
+                    // Every further payload would wait for the connection too
+                    if matches!(e, Error::Io(_)) {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            report.scanned += 1;
+            if let Some(signature) = signature {
+                warn!("ClamAV flagged {} {}: {}", payload.kind, payload.name, signature);
+                report.detections.push(AvDetection {
+                    kind: payload.kind,
+                    object: payload.object,
+                    name: payload.name,
+                    sha256,
+                    signature,
+                });
+            }
+        }
+        debug!(
+            "ClamAV scanned {} payloads, {} detections",
+            report.scanned,
+            report.detections.len()
+        );
+        report
+    }
+}
+
+/// The file, its embedded files, images and other decoded streams, each
+/// decoded only when the iterator reaches it. Streams that fail to decode
+/// within the limits are left out.
+pub fn payloads<'a>(
+    data: &'a [u8],
+    doc: Option<&'a Document>,
+    limits: &'a ResourceLimits,
+) -> impl Iterator<Item = Payload> + 'a {
+    let document = Payload {
+        kind: PayloadKind::Document,
+        object: None,
+        name: "<document>".to_string(),
+        data: data.to_vec(),
+    };
+    let names = doc.map(attachments::file_names).unwrap_or_default();
+    let streams = doc
+        .into_iter()
+        .flat_map(|doc| &doc.objects)
+        .filter_map(move |(&id, object)| {
+            let Object::Stream(stream) = object else {
+                return None;
+            };
+            let data = limits::decode(stream, limits).ok()?;
+            let (kind, name) = if name_value(&stream.dict, b"Type") == Some(b"EmbeddedFile") || names.contains_key(&id)
+            {
+                let name = names.get(&id).cloned();
+                (PayloadKind::Attachment, name)
+            } else if name_value(&stream.dict, b"Subtype") == Some(b"Image") {
+                (PayloadKind::Image, None)
+            } else {
+                (PayloadKind::Stream, None)
+            };
+            Some(Payload {
+                kind,
+                object: Some(id),
+                name: name.unwrap_or_else(|| format!("<object {} {}>", id.0, id.1)),
+                data,
+            })
+        });
+    std::iter::once(document).chain(streams)
+}
+
+/// Sends `data` with the `INSTREAM` command and reads clamd's reply
+async fn instream<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, data: &[u8]) -> Result<String> {
+    stream.write_all(b"zINSTREAM\0").await?;
+    for chunk in data.chunks(CHUNK_SIZE) {
+        stream.write_all(&(chunk.len() as u32).to_be_bytes()).await?;
+        stream.write_all(chunk).await?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await?;
+    stream.flush().await?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    Ok(String::from_utf8_lossy(&reply)
+        .trim_end_matches(['\0', '\n'])
+        .to_string())
+}
+
+/// Interprets `stream: OK`, `stream: <name> FOUND` and `... ERROR` replies
+fn parse_reply(reply: &str) -> Result<Option<String>> {
+    let status = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if status == "OK" {
+        Ok(None)
+    } else if let Some(signature) = status.strip_suffix(" FOUND") {
+        Ok(Some(signature.to_string()))
+    } else {
+        Err(Error::Protocol(format!("clamd: {}", status)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_replies_and_addresses() {
+        assert_eq!(parse_reply("stream: OK").unwrap(), None);
+        assert_eq!(
+            parse_reply("stream: Eicar-Test-Signature FOUND").unwrap().as_deref(),
+            Some("Eicar-Test-Signature")
+        );
+        assert!(parse_reply("INSTREAM size limit exceeded. ERROR").is_err());
+
+        assert_eq!(
+            "tcp://127.0.0.1:3310".parse::<ClamdAddress>(),
+            Ok(ClamdAddress::Tcp("127.0.0.1:3310".to_string()))
+        );
+        assert!("tcp://localhost".parse::<ClamdAddress>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_instream_framing() {
+        let (client, mut server) = tokio::io::duplex(1 << 16);
+        let clamd = async move {
+            let mut command = [0; 10];
+            server.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+            let mut received = Vec::new();
+            loop {
+                let mut length = [0; 4];
+                server.read_exact(&mut length).await.unwrap();
+                let length = u32::from_be_bytes(length) as usize;
+                if length == 0 {
+                    break;
+                }
+                let mut chunk = vec![0; length];
+                server.read_exact(&mut chunk).await.unwrap();
+                received.extend(chunk);
+            }
+            server.write_all(b"stream: Test.Signature FOUND\0").await.unwrap();
+            received
+        };
+        let (reply, received) = tokio::join!(instream(client, b"payload"), clamd);
+        assert_eq!(received, b"payload");
+        assert_eq!(reply.unwrap(), "stream: Test.Signature FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_failed_payload_keeps_detections() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            for reply in [
+                &b"stream: Test.Signature FOUND\0"[..],
+                b"stream: Size limit exceeded. ERROR\0",
+            ] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut command = [0; 10];
+                socket.read_exact(&mut command).await.unwrap();
+                loop {
+                    let mut length = [0; 4];
+                    socket.read_exact(&mut length).await.unwrap();
+                    let length = u32::from_be_bytes(length) as usize;
+                    if length == 0 {
+                        break;
+                    }
+                    socket.read_exact(&mut vec![0; length]).await.unwrap();
+                }
+                socket.write_all(reply).await.unwrap();
+            }
+        });
+
+        let scanner = ClamAvScanner::new(ClamAvConfig {
+            address: ClamdAddress::Tcp(address),
+            ..Default::default()
+        });
+        let payload = |name: &str| Payload {
+            kind: PayloadKind::Stream,
+            object: None,
+            name: name.to_string(),
+            data: name.as_bytes().to_vec(),
+        };
+        let report = scanner.scan_payloads([payload("first"), payload("second")]).await;
+        assert_eq!(report.scanned, 1);
+        assert_eq!(report.detections[0].signature, "Test.Signature");
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].contains("second"));
+    }
+
+    #[test]
+    fn test_payload_kinds() {
+        let mut doc = fixtures::document();
+        let image = doc.add_object(Stream::new(dictionary! { "Subtype" => "Image" }, vec![0xFF; 8]));
+        let file = doc.add_object(Stream::new(dictionary! { "Type" => "EmbeddedFile" }, b"MZ".to_vec()));
+
+        let limits = ResourceLimits::default();
+        let payloads: Vec<Payload> = payloads(b"%PDF-1.7", Some(&doc), &limits).collect();
+        let kind = |id| payloads.iter().find(|p| p.object == Some(id)).map(|p| p.kind);
+        assert_eq!(payloads[0].kind, PayloadKind::Document);
+        assert_eq!(kind(image), Some(PayloadKind::Image));
+        assert_eq!(kind(file), Some(PayloadKind::Attachment));
+        assert_eq!(payloads.iter().filter(|p| p.object == Some(file)).count(), 1);
+    }
+}
@@ -0,0 +1,691 @@
+//! ICAP responder for web proxies and DLP gateways
+//! Created: 2026-10-16 09:02:13 UTC
+//! Author: kartik4091
+//!
+//! Implements the RFC 3507 REQMOD and RESPMOD services, reachable as
+//! `icap://host:1344/reqmod` and `icap://host:1344/respmod`. Uploads and
+//! downloads carrying a PDF are scanned: rejected ones are replaced with a
+//! `403` block page, quarantined ones pass with `X-PDx-*` headers added to
+//! the encapsulated HTTP message, and everything else is answered with
+//! `204 No Content`. A preview is requested so that non-PDF traffic can be
+//! waved through without transferring the whole body.
+
+use std::{fmt::Write as _, sync::Arc, time::Duration};
+
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+use tracing::{debug, info, warn};
+
+use super::{
+    mail::{self, Attachment},
+    policy::{Decision, Policy, Verdict},
+};
+use crate::{
+    detectors::header::HEADER_WINDOW,
+    document::revisions::find,
+    error::{Error, Result},
+    AnalysisOptions,
+};
+
+/// Default upper bound on buffered message bodies (32 MiB)
+pub const DEFAULT_MAX_BODY: usize = 32 * 1024 * 1024;
+
+/// Preview size requested from clients: enough to see a PDF header
+pub const PREVIEW_SIZE: usize = HEADER_WINDOW;
+
+/// Default time allowed for one connection, from accept to close
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Longest ICAP header block or encapsulated HTTP header accepted
+const MAX_HEAD: usize = 64 * 1024;
+
+/// Pause after a failed accept (e.g. out of file descriptors) before retrying
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// ICAP modification service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Request modification: uploads on their way to the origin server
+    Reqmod,
+
+    /// Response modification: downloads on their way to the client
+    Respmod,
+}
+
+impl Mode {
+    /// Service selected by the last path segment of the ICAP URI
+    fn from_uri(uri: &str) -> Option<Mode> {
+        let path = uri.split('?').next().unwrap_or(uri);
+        match path.rsplit('/').next()?.to_ascii_lowercase().as_str() {
+            "reqmod" => Some(Mode::Reqmod),
+            "respmod" => Some(Mode::Respmod),
+            _ => None,
+        }
+    }
+
+    fn method(self) -> &'static str {
+        match self {
+            Mode::Reqmod => "REQMOD",
+            Mode::Respmod => "RESPMOD",
+        }
+    }
+
+    /// Encapsulated header and body section names
+    fn sections(self) -> (&'static str, &'static str) {
+        match self {
+            Mode::Reqmod => ("req-hdr", "req-body"),
+            Mode::Respmod => ("res-hdr", "res-body"),
+        }
+    }
+}
+
+/// ICAP service scanning encapsulated PDFs
+#[derive(Debug, Clone)]
+pub struct IcapService {
+    policy: Policy,
+    options: AnalysisOptions,
+    max_body: usize,
+    timeout: Duration,
+}
+
+/// A parsed ICAP request header
+#[derive(Debug)]
+struct IcapRequest {
+    method: String,
+    uri: String,
+    headers: Vec<(String, String)>,
+}
+
+/// Response bytes, and whether the connection must be closed afterwards
+struct Reply {
+    message: Vec<u8>,
+    close: bool,
+}
+
+impl IcapService {
+    /// Creates a service enforcing `policy` with default analysis options
+    pub fn new(policy: Policy) -> Self {
+        Self { policy, options: AnalysisOptions::default(), max_body: DEFAULT_MAX_BODY, timeout: DEFAULT_TIMEOUT }
+    }
+
+    /// Sets the analysis options used for each document
+    pub fn options(mut self, options: AnalysisOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the largest message body that will be buffered for scanning
+    pub fn max_body(mut self, max_body: usize) -> Self {
+        self.max_body = max_body;
+        self
+    }
+
+    /// Sets how long one connection may stay open; slow or stalled clients
+    /// are dropped when it elapses
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Service tag; changes with the policy so that clients drop cached verdicts
+    fn istag(&self) -> String {
+        format!(
+            "\"pdx-{}-{}-{}\"",
+            env!("CARGO_PKG_VERSION"),
+            self.policy.quarantine_at,
+            self.policy.reject_at
+        )
+    }
+
+    /// Accepts connections until the task is cancelled; failed accepts are
+    /// logged and retried
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        let service = Arc::new(self);
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept ICAP connection: {}", e);
+                    tokio::time::sleep(ACCEPT_BACKOFF).await;
+                    continue;
+                }
+            };
+            debug!("ICAP connection from {}", peer);
+            let service = service.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(service.timeout, service.handle(stream)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => warn!("ICAP connection from {} failed: {}", peer, e),
+                    Err(_) => warn!("ICAP connection from {} timed out after {:?}", peer, service.timeout),
+                }
+            });
+        }
+    }
+
+    /// Serves ICAP requests on one connection until the client closes it
+    pub async fn handle<S: AsyncRead + AsyncWrite + Unpin>(&self, stream: S) -> Result<()> {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut reader = BufReader::new(reader);
+
+        while let Some(head) = read_head(&mut reader).await? {
+            let reply = match IcapRequest::parse(&head) {
+                Ok(request) => match self.respond(&request, &mut reader, &mut writer).await {
+                    Ok(reply) => reply,
+                    Err(Error::Protocol(e)) => {
+                        warn!("Malformed {} request: {}", request.method, e);
+                        self.status(400, "Bad Request")
+                    }
+                    Err(e) => return Err(e),
+                },
+                Err(e) => {
+                    warn!("Malformed ICAP request: {}", e);
+                    self.status(400, "Bad Request")
+                }
+            };
+
+            writer.write_all(&reply.message).await?;
+            writer.flush().await?;
+            if reply.close {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    async fn respond<R, W>(&self, request: &IcapRequest, reader: &mut R, writer: &mut W) -> Result<Reply>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let Some(mode) = Mode::from_uri(&request.uri) else {
+            return Ok(self.status(404, "ICAP Service Not Found"));
+        };
+        if request.method == "OPTIONS" {
+            return Ok(self.options_response(mode));
+        }
+        if request.method != mode.method() {
+            return Ok(self.status(405, "Method Not Allowed For Service"));
+        }
+
+        let encapsulated = parse_encapsulated(request.header("Encapsulated").unwrap_or_default())?;
+        let (head_name, body_name) = mode.sections();
+        let (last_name, body_offset) = encapsulated.last().cloned().unwrap_or_default();
+        if body_offset > MAX_HEAD {
+            return Err(protocol("encapsulated headers too large"));
+        }
+
+        // Every header section precedes the body
+        let mut sections = vec![0; body_offset];
+        reader.read_exact(&mut sections).await?;
+        let http_head = section(&encapsulated, &sections, head_name)
+            .ok_or_else(|| protocol(format!("{} without {}", mode.method(), head_name)))?;
+        let content_type = http_header(http_head, "Content-Type");
+        let name = document_name(section(&encapsulated, &sections, "req-hdr"));
+
+        let preview = request.header("Preview").is_some();
+        let allow_204 = preview
+            || request
+                .header("Allow")
+                .map_or(false, |allow| allow.split(',').any(|v| v.trim() == "204"));
+
+        let mut body = Vec::new();
+        let mut overflow = false;
+        if last_name == body_name {
+            let ieof = read_chunks(reader, &mut body, self.max_body, &mut overflow).await?;
+            if preview && !ieof {
+                if !is_candidate(content_type.as_deref(), &body) {
+                    return Ok(self.status(204, "No Content"));
+                }
+                writer.write_all(b"ICAP/1.0 100 Continue\r\n\r\n").await?;
+                writer.flush().await?;
+                read_chunks(reader, &mut body, self.max_body, &mut overflow).await?;
+            }
+        }
+        let body = (last_name == body_name).then_some(&body[..]);
+
+        let decision = match body {
+            Some(data) if is_candidate(content_type.as_deref(), data) => {
+                if overflow {
+                    // An unscanned document is never passed on, whatever the error policy says
+                    let mut decision = self.policy.error(&format!("body exceeds {} bytes", self.max_body));
+                    decision.verdict = Verdict::Reject;
+                    Some(decision)
+                } else {
+                    match documents(content_type.as_deref(), data, &name) {
+                        Ok(documents) if documents.is_empty() => None,
+                        Ok(documents) => Some(mail::scan_attachments(&documents, &self.policy, &self.options).await),
+                        // An upload that cannot be split may still carry a PDF
+                        Err(e) => Some(self.policy.error(&e)),
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let Some(decision) = decision else {
+            return Ok(self.unmodified(mode, allow_204, http_head, body, overflow));
+        };
+        info!("{} {}: {} ({} reasons)", mode.method(), name, decision.verdict, decision.reasons.len());
+
+        Ok(match decision.verdict {
+            Verdict::Accept => self.unmodified(mode, allow_204, http_head, body, overflow),
+            Verdict::Quarantine if !overflow => {
+                let head = with_headers(http_head, &mail::verdict_headers(&decision));
+                self.modified(mode.sections(), &head, body, &decision)
+            }
+            Verdict::Quarantine | Verdict::Reject => {
+                let (head, page) = block_page(&decision);
+                self.modified(("res-hdr", "res-body"), &head, Some(&page), &decision)
+            }
+        })
+    }
+
+    fn options_response(&self, mode: Mode) -> Reply {
+        let message = format!(
+            "ICAP/1.0 200 OK\r\nMethods: {}\r\nService: PDx PDF scanner {}\r\nISTag: {}\r\n\
+             Options-TTL: 3600\r\nAllow: 204\r\nPreview: {}\r\nTransfer-Preview: *\r\n\
+             Encapsulated: null-body=0\r\n\r\n",
+            mode.method(),
+            env!("CARGO_PKG_VERSION"),
+            self.istag(),
+            PREVIEW_SIZE
+        );
+        Reply { message: message.into_bytes(), close: false }
+    }
+
+    /// Header-only response; errors close the connection since the
+    /// request body may not have been consumed
+    fn status(&self, code: u16, reason: &str) -> Reply {
+        let message = format!(
+            "ICAP/1.0 {} {}\r\nISTag: {}\r\nEncapsulated: null-body=0\r\n\r\n",
+            code,
+            reason,
+            self.istag()
+        );
+        Reply { message: message.into_bytes(), close: code >= 400 }
+    }
+
+    /// Passes the message on unchanged: `204` when the client allows it,
+    /// otherwise an echo of what it sent
+    fn unmodified(&self, mode: Mode, allow_204: bool, head: &[u8], body: Option<&[u8]>, overflow: bool) -> Reply {
+        if allow_204 {
+            return self.status(204, "No Content");
+        }
+        if overflow {
+            // The dropped tail cannot be echoed
+            return self.status(500, "Server Error");
+        }
+        self.modified(mode.sections(), head, body, &Decision::default())
+    }
+
+    /// `200 OK` carrying the given HTTP header and body
+    fn modified(&self, (head_name, body_name): (&str, &str), head: &[u8], body: Option<&[u8]>, decision: &Decision) -> Reply {
+        let body_section = if body.is_some() { body_name } else { "null-body" };
+        let mut message = format!("ICAP/1.0 200 OK\r\nISTag: {}\r\n", self.istag());
+        if decision.verdict != Verdict::Accept {
+            let _ = write!(message, "{}: {}\r\n", mail::VERDICT_HEADER, decision.verdict);
+        }
+        let _ = write!(message, "Encapsulated: {}=0, {}={}\r\n\r\n", head_name, body_section, head.len());
+
+        let mut message = message.into_bytes();
+        message.extend_from_slice(head);
+        if let Some(body) = body {
+            if !body.is_empty() {
+                message.extend_from_slice(format!("{:x}\r\n", body.len()).as_bytes());
+                message.extend_from_slice(body);
+                message.extend_from_slice(b"\r\n");
+            }
+            message.extend_from_slice(b"0\r\n\r\n");
+        }
+        Reply { message, close: false }
+    }
+}
+
+impl IcapRequest {
+    fn parse(head: &[u8]) -> Result<Self> {
+        let text = std::str::from_utf8(head).map_err(|_| protocol("ICAP header is not UTF-8"))?;
+        let mut lines = text.lines();
+
+        let request_line = lines.next().unwrap_or_default();
+        let mut parts = request_line.split_whitespace();
+        let (Some(method), Some(uri), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(protocol(format!("invalid request line {:?}", request_line)));
+        };
+        if !version.starts_with("ICAP/") {
+            return Err(protocol(format!("unsupported version {}", version)));
+        }
+
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Ok(Self { method: method.to_string(), uri: uri.to_string(), headers })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn protocol(message: impl Into<String>) -> Error {
+    Error::Protocol(message.into())
+}
+
+/// Reads a header block up to and including its blank line; `None` when
+/// the client closed the connection between requests
+async fn read_head<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Vec<u8>>> {
+    let mut head = Vec::new();
+    loop {
+        let mut line = Vec::new();
+        let limit = (MAX_HEAD + 2).saturating_sub(head.len()) as u64;
+        if (&mut *reader).take(limit).read_until(b'\n', &mut line).await? == 0 {
+            if head.is_empty() {
+                return Ok(None);
+            }
+            return Err(protocol("header block truncated"));
+        }
+
+        let blank = line == b"\r\n" || line == b"\n";
+        if blank && head.is_empty() {
+            continue;
+        }
+        head.extend_from_slice(&line);
+        if blank {
+            return Ok(Some(head));
+        }
+        if head.len() > MAX_HEAD {
+            return Err(protocol("header block too large"));
+        }
+    }
+}
+
+/// Appends chunked data to `body` up to the terminating chunk and returns
+/// whether it carried the `ieof` extension. Data beyond `max` is discarded
+/// and reported through `overflow`.
+async fn read_chunks<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    body: &mut Vec<u8>,
+    max: usize,
+    overflow: &mut bool,
+) -> Result<bool> {
+    loop {
+        let mut line = Vec::new();
+        (&mut *reader).take(1024).read_until(b'\n', &mut line).await?;
+        let line = String::from_utf8_lossy(&line);
+        let (size, extension) = line.trim().split_once(';').unwrap_or((line.trim(), ""));
+        let size = usize::from_str_radix(size.trim(), 16)
+            .map_err(|_| protocol(format!("invalid chunk size {:?}", line.trim())))?;
+
+        if size == 0 {
+            // Skip the trailer, normally just the closing blank line
+            loop {
+                let mut trailer = Vec::new();
+                let read = (&mut *reader).take(1024).read_until(b'\n', &mut trailer).await?;
+                if read == 0 || trailer == b"\r\n" || trailer == b"\n" {
+                    break;
+                }
+            }
+            return Ok(extension.trim() == "ieof");
+        }
+
+        let keep = size.min(max.saturating_sub(body.len()));
+        let start = body.len();
+        body.resize(start + keep, 0);
+        reader.read_exact(&mut body[start..]).await?;
+        if keep < size {
+            *overflow = true;
+            let skipped = tokio::io::copy(&mut (&mut *reader).take((size - keep) as u64), &mut tokio::io::sink()).await?;
+            if skipped < (size - keep) as u64 {
+                return Err(protocol("chunk truncated"));
+            }
+        }
+
+        let mut crlf = Vec::new();
+        (&mut *reader).take(2).read_until(b'\n', &mut crlf).await?;
+    }
+}
+
+/// Parses `req-hdr=0, res-hdr=137, res-body=296`; the last entry names the
+/// body section (or `null-body`)
+fn parse_encapsulated(value: &str) -> Result<Vec<(String, usize)>> {
+    let mut entries = Vec::new();
+    for part in value.split(',') {
+        let (name, offset) = part
+            .trim()
+            .split_once('=')
+            .ok_or_else(|| protocol(format!("invalid Encapsulated entry {:?}", part)))?;
+        let offset: usize = offset
+            .trim()
+            .parse()
+            .map_err(|_| protocol(format!("invalid Encapsulated offset {:?}", part)))?;
+        if entries.last().map_or(false, |(_, previous)| offset < *previous) {
+            return Err(protocol("Encapsulated offsets out of order"));
+        }
+        entries.push((name.trim().to_ascii_lowercase(), offset));
+    }
+
+    match entries.last() {
+        Some((name, _)) if name.ends_with("-body") => Ok(entries),
+        _ => Err(protocol("Encapsulated header missing body entry")),
+    }
+}
+
+/// Bytes of a named header section
+fn section<'a>(encapsulated: &[(String, usize)], sections: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    let index = encapsulated.iter().position(|(n, _)| n == name)?;
+    let start = encapsulated[index].1;
+    let end = encapsulated.get(index + 1).map_or(sections.len(), |(_, offset)| *offset);
+    sections.get(start..end)
+}
+
+/// Value of a header in an encapsulated HTTP header block
+fn http_header(head: &[u8], name: &str) -> Option<String> {
+    String::from_utf8_lossy(head)
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim().to_string())
+}
+
+/// Last path segment of the encapsulated request target, used as the
+/// document name in reasons
+fn document_name(request_head: Option<&[u8]>) -> String {
+    request_head
+        .and_then(|head| {
+            let head = String::from_utf8_lossy(head);
+            let target = head.lines().next()?.split_whitespace().nth(1)?.to_string();
+            let path = target.split(['?', '#']).next().unwrap_or_default();
+            path.rsplit('/').next().filter(|s| !s.is_empty()).map(str::to_string)
+        })
+        .unwrap_or_else(|| "<icap>".to_string())
+}
+
+/// Whether a body may hold a PDF: declared as one, a form upload, or
+/// starting with a PDF header
+fn is_candidate(content_type: Option<&str>, body: &[u8]) -> bool {
+    let essence = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|ct| ct.trim().to_ascii_lowercase());
+    matches!(essence.as_deref(), Some("application/pdf") | Some("multipart/form-data"))
+        || find(&body[..body.len().min(PREVIEW_SIZE)], b"%PDF-", 0).is_some()
+}
+
+/// PDFs carried by a body
+fn documents(content_type: Option<&str>, body: &[u8], name: &str) -> Result<Vec<Attachment>> {
+    if let Some(ct) = content_type.filter(|ct| ct.trim().to_ascii_lowercase().starts_with("multipart/")) {
+        // Form uploads share the MIME multipart syntax, so the mail extractor applies
+        let mut message = format!("Content-Type: {}\r\n\r\n", ct).into_bytes();
+        message.extend_from_slice(body);
+        return mail::pdf_attachments(&message);
+    }
+    Ok(vec![Attachment { name: name.to_string(), data: body.to_vec() }])
+}
+
+/// Inserts header lines before the blank line closing an HTTP header block
+fn with_headers(head: &[u8], headers: &str) -> Vec<u8> {
+    let end = if head.ends_with(b"\r\n\r\n") { head.len() - 2 } else { head.len() };
+    let mut out = head[..end].to_vec();
+    out.extend_from_slice(headers.as_bytes());
+    out.extend_from_slice(&head[end..]);
+    if end == head.len() {
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
+
+/// HTTP `403` response header and HTML body replacing a rejected message
+fn block_page(decision: &Decision) -> (Vec<u8>, Vec<u8>) {
+    let mut page = String::from(
+        "<!DOCTYPE html>\n<html><head><title>Document blocked</title></head><body>\n\
+         <h1>Document blocked</h1>\n<p>This PDF was rejected by the document security policy.</p>\n<ul>\n",
+    );
+    for reason in &decision.reasons {
+        let _ = writeln!(page, "<li>{}</li>", escape_html(reason));
+    }
+    page.push_str("</ul>\n</body></html>\n");
+
+    let head = format!(
+        "HTTP/1.1 403 Forbidden\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\n{}\r\n",
+        page.len(),
+        mail::verdict_headers(decision)
+    );
+    (head.into_bytes(), page.into_bytes())
+}
+
+fn escape_html(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '&' => "&amp;".to_string(),
+            '"' => "&quot;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{detectors::Severity, document::fixtures};
+
+    /// Sends raw requests on a fresh connection and returns everything the
+    /// service wrote back
+    async fn exchange(service: &IcapService, request: &[u8]) -> String {
+        let (mut client, server) = tokio::io::duplex(1 << 20);
+        let (result, response) = tokio::join!(service.handle(server), async move {
+            client.write_all(request).await.unwrap();
+            client.shutdown().await.unwrap();
+            let mut response = Vec::new();
+            client.read_to_end(&mut response).await.unwrap();
+            response
+        });
+        result.unwrap();
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    fn respmod(body: &[u8], extra: &str) -> Vec<u8> {
+        let req = "GET http://example.com/files/report.pdf HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let res = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/pdf\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        );
+        let mut request = format!(
+            "RESPMOD icap://localhost/respmod ICAP/1.0\r\nHost: localhost\r\n{}\
+             Encapsulated: req-hdr=0, res-hdr={}, res-body={}\r\n\r\n{}{}{:x}\r\n",
+            extra,
+            req.len(),
+            req.len() + res.len(),
+            req,
+            res,
+            body.len()
+        )
+        .into_bytes();
+        request.extend_from_slice(body);
+        request.extend_from_slice(b"\r\n0\r\n\r\n");
+        request
+    }
+
+    #[tokio::test]
+    async fn test_options() {
+        let service = IcapService::new(Policy::default());
+        let response = exchange(&service, b"OPTIONS icap://localhost/respmod ICAP/1.0\r\nHost: localhost\r\n\r\n").await;
+
+        assert!(response.starts_with("ICAP/1.0 200 OK\r\n"));
+        assert!(response.contains("Methods: RESPMOD\r\n"));
+        assert!(response.contains(&format!("Preview: {}\r\n", PREVIEW_SIZE)));
+    }
+
+    #[tokio::test]
+    async fn test_clean_pdf_passes() {
+        let service = IcapService::new(Policy::default());
+        let response = exchange(&service, &respmod(&fixtures::simple_pdf(), "Allow: 204\r\n")).await;
+        assert!(response.starts_with("ICAP/1.0 204 No Content\r\n"));
+
+        // Without Allow: 204 the message is echoed
+        let response = exchange(&service, &respmod(&fixtures::simple_pdf(), "")).await;
+        assert!(response.starts_with("ICAP/1.0 200 OK\r\n"));
+        assert!(response.contains("Encapsulated: res-hdr=0, res-body="));
+        assert!(response.contains("%PDF-"));
+    }
+
+    #[tokio::test]
+    async fn test_flagged_pdf_is_blocked() {
+        let mut pdf = fixtures::simple_pdf();
+        pdf.extend_from_slice(b"MZ\x90\x00payload");
+
+        let policy = Policy { reject_at: Severity::High, ..Default::default() };
+        let response = exchange(&IcapService::new(policy), &respmod(&pdf, "Allow: 204\r\n")).await;
+
+        assert!(response.starts_with("ICAP/1.0 200 OK\r\n"));
+        assert!(response.contains("X-PDx-Verdict: reject\r\n"));
+        assert!(response.contains("HTTP/1.1 403 Forbidden\r\n"));
+        assert!(response.contains("report.pdf: "));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_pdf_is_blocked() {
+        let service = IcapService::new(Policy::default()).max_body(16);
+        let response = exchange(&service, &respmod(&fixtures::simple_pdf(), "Allow: 204\r\n")).await;
+
+        assert!(response.starts_with("ICAP/1.0 200 OK\r\n"));
+        assert!(response.contains("X-PDx-Verdict: reject\r\n"));
+        assert!(response.contains("HTTP/1.1 403 Forbidden\r\n"));
+        assert!(!response.contains("%PDF-"));
+    }
+
+    #[tokio::test]
+    async fn test_non_pdf_preview_is_waved_through() {
+        let req = "POST /comment HTTP/1.1\r\nHost: example.com\r\nContent-Type: text/plain\r\n\r\n";
+        let request = format!(
+            "REQMOD icap://localhost/reqmod ICAP/1.0\r\nHost: localhost\r\nPreview: 5\r\n\
+             Encapsulated: req-hdr=0, req-body={}\r\n\r\n{}5\r\nhello\r\n0\r\n\r\n",
+            req.len(),
+            req
+        );
+
+        let response = exchange(&IcapService::new(Policy::default()), request.as_bytes()).await;
+        assert!(response.starts_with("ICAP/1.0 204 No Content\r\n"));
+    }
+
+    #[test]
+    fn test_parse_encapsulated() {
+        let entries = parse_encapsulated("req-hdr=0, res-hdr=137, res-body=296").unwrap();
+        assert_eq!(entries[2], ("res-body".to_string(), 296));
+        assert!(parse_encapsulated("req-hdr=0").is_err());
+        assert!(parse_encapsulated("res-hdr=10, res-body=5").is_err());
+    }
+
+    #[test]
+    fn test_with_headers() {
+        let head = with_headers(b"HTTP/1.1 200 OK\r\nA: b\r\n\r\n", "X-PDx-Verdict: quarantine\r\n");
+        assert_eq!(head, b"HTTP/1.1 200 OK\r\nA: b\r\nX-PDx-Verdict: quarantine\r\n\r\n");
+    }
+}
@@ -0,0 +1,240 @@
+//! Mail content-filter integration
+//! Created: 2026-10-16 08:54:01 UTC
+//! Author: kartik4091
+//!
+//! Implements the Postfix after-queue content-filter contract: the message
+//! arrives on stdin, is scanned, and is either re-injected through
+//! `sendmail` (tagged with `X-PDx-*` headers) or refused with the exit
+//! status Postfix maps to a bounce. Quarantine is expressed as a header so
+//! the MTA can act on it, e.g. with a `header_checks` HOLD rule.
+
+use std::{path::Path, process::Stdio};
+
+use mail_parser::{Message, MessageParser, MimeHeaders};
+use tokio::{io::AsyncWriteExt, process::Command};
+use tracing::{debug, info};
+
+use super::policy::{Decision, Policy, Verdict};
+use crate::{
+    error::{Error, Result},
+    AnalysisOptions, PdfAnalyzer,
+};
+
+/// Exit status for delivered (or quarantined) messages
+pub const EX_OK: i32 = 0;
+
+/// Exit status Postfix turns into a permanent bounce (sysexits EX_UNAVAILABLE)
+pub const EX_UNAVAILABLE: i32 = 69;
+
+/// Exit status Postfix turns into a deferral (sysexits EX_TEMPFAIL)
+pub const EX_TEMPFAIL: i32 = 75;
+
+/// Header carrying the verdict
+pub const VERDICT_HEADER: &str = "X-PDx-Verdict";
+
+/// Header carrying one reason per line
+pub const REASON_HEADER: &str = "X-PDx-Reason";
+
+/// A PDF found in a message
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    /// File name, or a placeholder when the part has none
+    pub name: String,
+
+    /// Decoded content
+    pub data: Vec<u8>,
+}
+
+/// Extracts every PDF part of a message, including those in forwarded
+/// (message/rfc822) attachments. A message that cannot be parsed is an
+/// error, since its attachments cannot be ruled out.
+pub fn pdf_attachments(raw: &[u8]) -> Result<Vec<Attachment>> {
+    let message = MessageParser::default()
+        .parse(raw)
+        .ok_or_else(|| Error::Analysis("Unparseable MIME message".into()))?;
+    let mut found = Vec::new();
+    collect(&message, &mut found);
+    Ok(found)
+}
+
+fn collect(message: &Message<'_>, found: &mut Vec<Attachment>) {
+    for part in message.attachments() {
+        if let Some(nested) = part.message() {
+            collect(nested, found);
+            continue;
+        }
+
+        let name = part.attachment_name().unwrap_or("<unnamed>").to_string();
+        let declared = part
+            .content_type()
+            .map_or(false, |ct| ct.ctype() == "application" && ct.subtype() == Some("pdf"))
+            || name.to_ascii_lowercase().ends_with(".pdf");
+        let data = part.contents();
+        if declared || data.starts_with(b"%PDF-") {
+            found.push(Attachment { name, data: data.to_vec() });
+        }
+    }
+}
+
+/// Scans every PDF attachment and combines the per-attachment decisions;
+/// a message that cannot be parsed gets the policy's error verdict
+pub async fn scan_message(raw: &[u8], policy: &Policy, options: &AnalysisOptions) -> Decision {
+    match pdf_attachments(raw) {
+        Ok(attachments) => scan_attachments(&attachments, policy, options).await,
+        Err(e) => policy.error(&e),
+    }
+}
+
+/// Scans extracted PDFs, prefixing each reason with the document name
+pub async fn scan_attachments(attachments: &[Attachment], policy: &Policy, options: &AnalysisOptions) -> Decision {
+    let mut decision = Decision::default();
+    for attachment in attachments {
+        debug!("Scanning attachment {} ({} bytes)", attachment.name, attachment.data.len());
+        let result = match PdfAnalyzer::with_options(&attachment.name, options.clone()) {
+            Ok(analyzer) => analyzer.analyze_bytes(&attachment.data).await,
+            Err(e) => Err(e),
+        };
+        let verdict = match result {
+            Ok(analysis) => policy.evaluate(&analysis),
+            Err(e) => policy.error(&e),
+        };
+        decision = decision.merge(Decision {
+            reasons: verdict.reasons.iter().map(|r| format!("{}: {}", attachment.name, r)).collect(),
+            ..verdict
+        });
+    }
+    decision
+}
+
+/// Verdict and reason header lines, each terminated by CRLF
+pub fn verdict_headers(decision: &Decision) -> String {
+    let mut headers = format!("{}: {}\r\n", VERDICT_HEADER, decision.verdict);
+    for reason in &decision.reasons {
+        // Header values must stay on one line
+        let reason: String = reason.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+        headers.push_str(&format!("{}: {}\r\n", REASON_HEADER, reason));
+    }
+    headers
+}
+
+/// Prepends the verdict headers to a message
+pub fn tag_message(raw: &[u8], decision: &Decision) -> Vec<u8> {
+    let mut tagged = verdict_headers(decision).into_bytes();
+    tagged.extend_from_slice(raw);
+    tagged
+}
+
+/// Where accepted messages go
+#[derive(Debug, Clone)]
+pub enum Delivery<'a> {
+    /// Write the tagged message to stdout
+    Stdout,
+
+    /// Re-inject through sendmail with the envelope Postfix passed in
+    Sendmail {
+        /// Path of the sendmail binary
+        program: &'a Path,
+
+        /// Envelope sender
+        sender: &'a str,
+
+        /// Envelope recipients
+        recipients: &'a [String],
+    },
+}
+
+/// Runs the content filter on one message and returns the process exit status
+pub async fn filter(raw: &[u8], policy: &Policy, options: &AnalysisOptions, delivery: Delivery<'_>) -> Result<i32> {
+    let decision = scan_message(raw, policy, options).await;
+    info!("Message verdict: {} ({} reasons)", decision.verdict, decision.reasons.len());
+
+    if decision.verdict == Verdict::Reject {
+        // Postfix includes stderr in the bounce
+        eprintln!("5.7.1 Message rejected by PDF policy: {}", decision.reasons.join("; "));
+        return Ok(EX_UNAVAILABLE);
+    }
+
+    let tagged = tag_message(raw, &decision);
+    match delivery {
+        Delivery::Stdout => {
+            let mut stdout = tokio::io::stdout();
+            stdout.write_all(&tagged).await?;
+            stdout.flush().await?;
+        }
+        Delivery::Sendmail { program, sender, recipients } => {
+            let mut child = Command::new(program)
+                .args(["-G", "-i", "-f", sender, "--"])
+                .args(recipients)
+                .stdin(Stdio::piped())
+                .spawn()?;
+            let mut stdin = child.stdin.take().ok_or_else(|| Error::Analysis("sendmail stdin unavailable".into()))?;
+            stdin.write_all(&tagged).await?;
+            drop(stdin);
+
+            let status = child.wait().await?;
+            if !status.success() {
+                // Let Postfix retry rather than lose the message
+                eprintln!("4.3.0 Re-injection failed: {}", status);
+                return Ok(EX_TEMPFAIL);
+            }
+        }
+    }
+
+    Ok(EX_OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use base64::Engine;
+
+    fn message(pdf: &[u8]) -> Vec<u8> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(pdf);
+        format!(
+            "From: a@example.com\r\nTo: b@example.com\r\nSubject: invoice\r\nMIME-Version: 1.0\r\n\
+             Content-Type: multipart/mixed; boundary=\"B\"\r\n\r\n\
+             --B\r\nContent-Type: text/plain\r\n\r\nSee attached.\r\n\
+             --B\r\nContent-Type: application/octet-stream\r\n\
+             Content-Disposition: attachment; filename=\"invoice.bin\"\r\n\
+             Content-Transfer-Encoding: base64\r\n\r\n{}\r\n--B--\r\n",
+            encoded
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_pdf_attachments_are_sniffed() {
+        let attachments = pdf_attachments(&message(&fixtures::simple_pdf())).unwrap();
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].name, "invoice.bin");
+        assert!(attachments[0].data.starts_with(b"%PDF-"));
+    }
+
+    #[tokio::test]
+    async fn test_scan_message() {
+        let clean = scan_message(&message(&fixtures::simple_pdf()), &Policy::default(), &Default::default()).await;
+        assert_eq!(clean.verdict, Verdict::Accept);
+
+        let mut pdf = fixtures::simple_pdf();
+        pdf.extend_from_slice(b"PK\x03\x04payload");
+        let flagged = scan_message(&message(&pdf), &Policy::default(), &Default::default()).await;
+        assert_eq!(flagged.verdict, Verdict::Reject);
+        assert!(flagged.reasons[0].starts_with("invoice.bin: "));
+
+        let unparseable = scan_message(b"", &Policy::default(), &Default::default()).await;
+        assert_eq!(unparseable.verdict, Policy::default().on_error);
+        assert!(unparseable.reasons[0].contains("Unparseable MIME message"));
+    }
+
+    #[test]
+    fn test_tag_message() {
+        let decision = Decision {
+            verdict: Verdict::Quarantine,
+            severity: None,
+            reasons: vec!["line\nbreak".into()],
+        };
+        let tagged = String::from_utf8(tag_message(b"Subject: x\r\n\r\nbody", &decision)).unwrap();
+        assert!(tagged.starts_with("X-PDx-Verdict: quarantine\r\nX-PDx-Reason: line break\r\nSubject"));
+    }
+}
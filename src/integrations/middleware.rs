@@ -0,0 +1,293 @@
+//! Tower middleware scanning PDF uploads
+//! Created: 2026-10-16 08:55:30 UTC
+//! Author: kartik4091
+//!
+//! Wrap a service with [`PdxScanLayer`] to have every `multipart/form-data`
+//! request scanned before it reaches the handler:
+//!
+//! ```ignore
+//! let app = Router::new()
+//!     .route("/upload", post(upload))
+//!     .layer(PdxScanLayer::new(Policy::default()));
+//! ```
+//!
+//! Rejected uploads are answered with `422 Unprocessable Entity`, bodies
+//! larger than the buffering limit with `413 Payload Too Large` and
+//! unreadable ones with `400 Bad Request`; the wrapped service's error type
+//! is left alone, so infallible services such as axum routes fit. Accepted
+//! and quarantined ones are forwarded with an `x-pdx-verdict` header and the
+//! [`ScanOutcome`] in the request extensions, so the handler can decide what
+//! quarantine means for it.
+
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http::{header, HeaderValue, Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use tower::{Layer, Service};
+use tracing::{debug, warn};
+
+use super::policy::{Decision, Policy, Verdict};
+use crate::{document::attachments, AnalysisOptions, PdfAnalyzer};
+
+/// Header carrying the verdict on forwarded requests and rejections
+pub const VERDICT_HEADER: &str = "x-pdx-verdict";
+
+/// Default upper bound on buffered request bodies (32 MiB)
+pub const DEFAULT_MAX_BODY: usize = 32 * 1024 * 1024;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Scan results attached to forwarded requests
+#[derive(Debug, Clone)]
+pub struct ScanOutcome {
+    /// Combined decision over every scanned part
+    pub decision: Decision,
+
+    /// File names (or field names) of the scanned PDF parts
+    pub scanned: Vec<String>,
+}
+
+/// Layer adding [`PdxScan`] to a service
+#[derive(Debug, Clone)]
+pub struct PdxScanLayer {
+    policy: Policy,
+    options: AnalysisOptions,
+    max_body: usize,
+}
+
+impl PdxScanLayer {
+    /// Creates a layer enforcing `policy` with default analysis options
+    pub fn new(policy: Policy) -> Self {
+        Self { policy, options: AnalysisOptions::default(), max_body: DEFAULT_MAX_BODY }
+    }
+
+    /// Sets the analysis options used for each upload
+    pub fn options(mut self, options: AnalysisOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the largest request body that will be buffered for scanning
+    pub fn max_body(mut self, max_body: usize) -> Self {
+        self.max_body = max_body;
+        self
+    }
+}
+
+impl<S> Layer<S> for PdxScanLayer {
+    type Service = PdxScan<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PdxScan { inner, layer: self.clone() }
+    }
+}
+
+/// Middleware service produced by [`PdxScanLayer`]
+#[derive(Debug, Clone)]
+pub struct PdxScan<S> {
+    inner: S,
+    layer: PdxScanLayer,
+}
+
+impl<S, B, ResBody> Service<Request<B>> for PdxScan<S>
+where
+    S: Service<Request<Full<Bytes>>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    ResBody: Default + Send,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        // Drive the instance that was polled ready; leave the clone for the next call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let body = match Limited::new(body, layer.max_body).collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(e) if e.is::<LengthLimitError>() => {
+                    warn!("Upload exceeds {} bytes", layer.max_body);
+                    return Ok(status(StatusCode::PAYLOAD_TOO_LARGE, None));
+                }
+                Err(e) => {
+                    warn!("Unreadable upload: {}", e);
+                    return Ok(status(StatusCode::BAD_REQUEST, None));
+                }
+            };
+
+            let boundary = parts
+                .headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|ct| multer::parse_boundary(ct).ok());
+
+            if let Some(boundary) = boundary {
+                let outcome = scan_multipart(&layer, body.clone(), boundary).await;
+                if !outcome.scanned.is_empty() {
+                    debug!("Scanned {:?}: {}", outcome.scanned, outcome.decision.verdict);
+                    let verdict = HeaderValue::from_static(verdict_name(outcome.decision.verdict));
+                    if outcome.decision.verdict == Verdict::Reject {
+                        return Ok(status(StatusCode::UNPROCESSABLE_ENTITY, Some(verdict)));
+                    }
+                    parts.headers.insert(VERDICT_HEADER, verdict);
+                    parts.extensions.insert(outcome);
+                }
+            }
+
+            inner.call(Request::from_parts(parts, Full::new(body))).await
+        })
+    }
+}
+
+/// Empty response answered in place of the wrapped service
+fn status<ResBody: Default>(code: StatusCode, verdict: Option<HeaderValue>) -> Response<ResBody> {
+    let mut response = Response::new(ResBody::default());
+    *response.status_mut() = code;
+    if let Some(verdict) = verdict {
+        response.headers_mut().insert(VERDICT_HEADER, verdict);
+    }
+    response
+}
+
+fn verdict_name(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::Accept => "accept",
+        Verdict::Quarantine => "quarantine",
+        Verdict::Reject => "reject",
+    }
+}
+
+/// Scans every PDF part of a multipart body
+async fn scan_multipart(layer: &PdxScanLayer, body: Bytes, boundary: String) -> ScanOutcome {
+    let stream = futures::stream::once(async move { Ok::<_, Infallible>(body) });
+    let mut multipart = multer::Multipart::new(stream, boundary);
+    let mut outcome = ScanOutcome { decision: Decision::default(), scanned: Vec::new() };
+
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Malformed multipart body: {}", e);
+                outcome.decision = outcome.decision.merge(layer.policy.error(&e));
+                outcome.scanned.push("<multipart>".into());
+                break;
+            }
+        };
+
+        let name = field
+            .file_name()
+            .or_else(|| field.name())
+            .unwrap_or("<unnamed>")
+            .to_string();
+        let declared_pdf = field.content_type().map_or(false, |m| m.essence_str() == "application/pdf")
+            || name.to_ascii_lowercase().ends_with(".pdf");
+
+        let data = match field.bytes().await {
+            Ok(data) => data,
+            Err(e) => {
+                outcome.decision = outcome.decision.merge(layer.policy.error(&e));
+                outcome.scanned.push(name);
+                continue;
+            }
+        };
+        // Content sniffing catches PDFs uploaded under a misleading name
+        if !declared_pdf && !attachments::is_pdf(&data) {
+            continue;
+        }
+
+        let decision = match PdfAnalyzer::with_options(&name, layer.options.clone()) {
+            Ok(analyzer) => match analyzer.analyze_bytes(&data).await {
+                Ok(analysis) => layer.policy.evaluate(&analysis),
+                Err(e) => layer.policy.error(&e),
+            },
+            Err(e) => layer.policy.error(&e),
+        };
+        outcome.decision = outcome.decision.merge(decision);
+        outcome.scanned.push(name);
+    }
+
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{detectors::Severity, document::fixtures};
+    use tower::{service_fn, ServiceExt};
+
+    fn upload(pdf: &[u8]) -> Request<Full<Bytes>> {
+        let mut body = b"--XYZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"doc.pdf\"\r\nContent-Type: application/pdf\r\n\r\n".to_vec();
+        body.extend_from_slice(pdf);
+        body.extend_from_slice(b"\r\n--XYZ--\r\n");
+
+        Request::builder()
+            .uri("/upload")
+            .header(header::CONTENT_TYPE, "multipart/form-data; boundary=XYZ")
+            .body(Full::new(Bytes::from(body)))
+            .unwrap()
+    }
+
+    fn echo_verdict() -> impl Service<
+        Request<Full<Bytes>>,
+        Response = Response<String>,
+        Error = Infallible,
+        Future = impl Send,
+    > + Clone {
+        service_fn(|request: Request<Full<Bytes>>| async move {
+            let verdict = request
+                .extensions()
+                .get::<ScanOutcome>()
+                .map(|o| o.decision.verdict.to_string())
+                .unwrap_or_default();
+            Ok::<_, Infallible>(Response::new(verdict))
+        })
+    }
+
+    #[tokio::test]
+    async fn test_clean_upload_is_forwarded() {
+        let service = PdxScanLayer::new(Policy::default()).layer(echo_verdict());
+        let response = service.oneshot(upload(&fixtures::simple_pdf())).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), "accept");
+    }
+
+    #[tokio::test]
+    async fn test_flagged_upload_is_rejected() {
+        let mut pdf = fixtures::simple_pdf();
+        pdf.extend_from_slice(b"MZ\x90\x00payload");
+
+        let policy = Policy { reject_at: Severity::High, ..Default::default() };
+        let service = PdxScanLayer::new(policy).layer(echo_verdict());
+        let response = service.oneshot(upload(&pdf)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.headers()[VERDICT_HEADER], "reject");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_upload_is_refused() {
+        let service = PdxScanLayer::new(Policy::default()).max_body(64).layer(echo_verdict());
+        let response = service.oneshot(upload(&fixtures::simple_pdf())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
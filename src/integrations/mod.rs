@@ -0,0 +1,21 @@
+//! Integration points for embedding PDx in other services
+//! Created: 2026-10-16 08:51:08 UTC
+//! Author: kartik4091
+//!
+//! Gateways (web services, mail servers, proxies) all need the same thing:
+//! scan a PDF they are handling and get an accept/quarantine/reject answer.
+//! [`policy`] turns an analysis into that answer; the transport adapters in
+//! the sibling modules only deal with their wire format.
+
+pub mod policy;
+pub mod mail;
+pub mod icap;
+pub mod reputation;
+pub mod clamav;
+pub mod virustotal;
+pub mod watch;
+
+#[cfg(feature = "tower")]
+pub mod middleware;
+
+pub use policy::{Decision, Policy, RiskCategory, Verdict};
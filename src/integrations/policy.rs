@@ -0,0 +1,180 @@
+//! Accept/quarantine/reject policy over analysis results
+//! Created: 2026-10-16 08:55:16 UTC
+//! Author: kartik4091
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{detectors::Severity, findings, PdfAnalysis};
+
+pub use crate::detectors::RiskCategory;
+
+/// Action a gateway should take for a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Verdict {
+    /// Deliver unchanged
+    Accept,
+
+    /// Deliver to a holding area for review
+    Quarantine,
+
+    /// Refuse the document
+    Reject,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Accept => "accept",
+            Self::Quarantine => "quarantine",
+            Self::Reject => "reject",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Severity thresholds mapping findings to a verdict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Policy {
+    /// Lowest severity that quarantines the document
+    pub quarantine_at: Severity,
+
+    /// Lowest severity that rejects the document
+    pub reject_at: Severity,
+
+    /// Verdict for documents that could not be analyzed
+    pub on_error: Verdict,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            quarantine_at: Severity::Medium,
+            reject_at: Severity::High,
+            on_error: Verdict::Quarantine,
+        }
+    }
+}
+
+/// Verdict with the findings that caused it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decision {
+    /// Action to take
+    pub verdict: Verdict,
+
+    /// Highest finding severity, if any finding was made
+    pub severity: Option<Severity>,
+
+    /// Findings at or above the quarantine threshold
+    pub reasons: Vec<String>,
+}
+
+impl Decision {
+    /// Combines decisions for several documents (e.g. all attachments of a
+    /// message), keeping the strictest verdict
+    pub fn merge(mut self, other: Decision) -> Decision {
+        self.verdict = self.verdict.max(other.verdict);
+        self.severity = self.severity.max(other.severity);
+        self.reasons.extend(other.reasons);
+        self
+    }
+}
+
+impl Default for Decision {
+    fn default() -> Self {
+        Self { verdict: Verdict::Accept, severity: None, reasons: Vec::new() }
+    }
+}
+
+impl Policy {
+    /// Decides what to do with an analyzed document
+    pub fn evaluate(&self, analysis: &PdfAnalysis) -> Decision {
+        let indicators = indicators(analysis);
+        let severity = indicators.iter().map(|(severity, _)| *severity).max();
+        let verdict = match severity {
+            Some(s) if s >= self.reject_at => Verdict::Reject,
+            Some(s) if s >= self.quarantine_at => Verdict::Quarantine,
+            _ => Verdict::Accept,
+        };
+
+        Decision {
+            verdict,
+            severity,
+            reasons: indicators
+                .into_iter()
+                .filter(|(severity, _)| *severity >= self.quarantine_at)
+                .map(|(severity, reason)| format!("[{}] {}", severity, reason))
+                .collect(),
+        }
+    }
+
+    /// Decision for a document whose analysis failed
+    pub fn error(&self, error: &dyn fmt::Display) -> Decision {
+        Decision {
+            verdict: self.on_error,
+            severity: None,
+            reasons: vec![format!("Analysis failed: {}", error)],
+        }
+    }
+}
+
+/// Every severity-bearing finding of an analysis
+pub fn indicators(analysis: &PdfAnalysis) -> Vec<(Severity, String)> {
+    findings::collect(analysis)
+        .into_iter()
+        .map(|finding| (finding.severity, finding.title))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        detectors::reader_features::{FeatureUsage, ReaderFeature, ReaderFeatureSummary},
+        Analyzer, PdfAnalyzer,
+    };
+    use tempfile::NamedTempFile;
+
+    async fn analysis() -> PdfAnalysis {
+        let temp = NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_clean_document_is_accepted() {
+        let decision = Policy::default().evaluate(&analysis().await);
+        assert_eq!(decision.verdict, Verdict::Accept);
+        assert!(decision.reasons.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_auto_javascript_is_rejected() {
+        let mut analysis = analysis().await;
+        analysis.reader_features = ReaderFeatureSummary {
+            features: vec![FeatureUsage {
+                feature: ReaderFeature::JavaScript,
+                setting: ReaderFeature::JavaScript.setting().into(),
+                objects: vec![(7, 0)],
+                auto_triggered: true,
+            }],
+        };
+
+        let decision = Policy::default().evaluate(&analysis);
+        assert_eq!(decision.verdict, Verdict::Reject);
+        assert_eq!(decision.severity, Some(Severity::High));
+
+        let lenient = Policy { reject_at: Severity::Critical, ..Default::default() };
+        assert_eq!(lenient.evaluate(&analysis).verdict, Verdict::Quarantine);
+    }
+
+    #[test]
+    fn test_merge_keeps_strictest() {
+        let accept = Decision::default();
+        let reject = Decision { verdict: Verdict::Reject, severity: Some(Severity::High), reasons: vec!["x".into()] };
+        let merged = accept.merge(reject);
+        assert_eq!(merged.verdict, Verdict::Reject);
+        assert_eq!(merged.reasons.len(), 1);
+    }
+}
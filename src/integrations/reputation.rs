@@ -0,0 +1,491 @@
+//! Opt-in URL reputation lookups
+//! Created: 2026-10-16 09:53:18 UTC
+//! Author: kartik4091
+//!
+//! Checks the URIs extracted from a document against reputation services
+//! (Google Safe Browsing, PhishTank or a custom HTTP endpoint) and attaches
+//! their verdicts to the URI findings. Lookups leave the machine, so they
+//! only run when services are configured. Verdicts are cached for
+//! [`ReputationConfig::cache_ttl`] and requests to each service are spaced
+//! by at least [`ReputationConfig::min_interval`].
+//!
+//! A custom endpoint receives `POST {"url": ..., "host": ...}` and answers
+//! `{"verdict": "clean" | "unknown" | "suspicious" | "malicious", "detail": ...}`.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    detectors::{
+        uris::{ExtractedUri, UriReport},
+        Severity,
+    },
+    error::Result,
+};
+
+/// Environment variable holding the Google Safe Browsing API key
+pub const SAFE_BROWSING_KEY_VAR: &str = "PDX_SAFE_BROWSING_KEY";
+
+/// Environment variable holding the optional PhishTank application key
+pub const PHISHTANK_KEY_VAR: &str = "PDX_PHISHTANK_KEY";
+
+const SAFE_BROWSING_URL: &str = "https://safebrowsing.googleapis.com/v4/threatMatches:find";
+const PHISHTANK_URL: &str = "https://checkurl.phishtank.com/checkurl/";
+
+/// Safe Browsing accepts at most this many URLs per request
+const SAFE_BROWSING_BATCH: usize = 500;
+
+/// Reputation service to query
+#[derive(Clone, PartialEq, Eq)]
+pub enum ReputationService {
+    /// Google Safe Browsing v4 Lookup API
+    SafeBrowsing {
+        /// API key
+        api_key: String,
+    },
+
+    /// PhishTank URL check
+    PhishTank {
+        /// Application key; anonymous lookups are rate limited harder
+        app_key: Option<String>,
+    },
+
+    /// Custom HTTP endpoint (see the module documentation for the contract)
+    Custom {
+        /// Endpoint URL
+        endpoint: String,
+    },
+}
+
+impl ReputationService {
+    /// Service name used in verdicts
+    pub fn name(&self) -> &str {
+        match self {
+            Self::SafeBrowsing { .. } => "safe-browsing",
+            Self::PhishTank { .. } => "phishtank",
+            Self::Custom { endpoint } => endpoint,
+        }
+    }
+}
+
+// API keys stay out of logs and debug output
+impl fmt::Debug for ReputationService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ReputationService {
+    type Err = String;
+
+    /// Parses `safe-browsing`, `phishtank` or an endpoint URL; keys are
+    /// read from [`SAFE_BROWSING_KEY_VAR`] and [`PHISHTANK_KEY_VAR`]
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "safebrowsing" => std::env::var(SAFE_BROWSING_KEY_VAR)
+                .map(|api_key| Self::SafeBrowsing { api_key })
+                .map_err(|_| format!("Safe Browsing needs an API key in {}", SAFE_BROWSING_KEY_VAR)),
+            "phishtank" => Ok(Self::PhishTank {
+                app_key: std::env::var(PHISHTANK_KEY_VAR).ok(),
+            }),
+            _ if s.starts_with("http://") || s.starts_with("https://") => Ok(Self::Custom {
+                endpoint: s.to_string(),
+            }),
+            other => Err(format!("Unknown reputation service: {}", other)),
+        }
+    }
+}
+
+/// Verdict of a reputation service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Reputation {
+    /// Known good
+    Clean,
+
+    /// Not known to the service
+    Unknown,
+
+    /// Listed with low confidence
+    Suspicious,
+
+    /// Listed as phishing or malware
+    Malicious,
+}
+
+impl Reputation {
+    /// Lowest severity a URI with this verdict is reported at
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Clean | Self::Unknown => Severity::Info,
+            // Unverified listings (PhishTank submissions) are only leads
+            Self::Suspicious => Severity::Medium,
+            Self::Malicious => Severity::Critical,
+        }
+    }
+}
+
+/// A service's verdict on a URI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ReputationVerdict {
+    /// Service name
+    pub service: String,
+
+    /// Verdict
+    pub reputation: Reputation,
+
+    /// Threat type or listing reference
+    pub detail: Option<String>,
+}
+
+/// Services to query and how often
+#[derive(Debug, Clone)]
+pub struct ReputationConfig {
+    /// Services queried for every URL
+    pub services: Vec<ReputationService>,
+
+    /// How long a verdict is reused
+    pub cache_ttl: Duration,
+
+    /// Minimum delay between two requests to the same service
+    pub min_interval: Duration,
+
+    /// Per-request timeout
+    pub timeout: Duration,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        Self {
+            services: Vec::new(),
+            cache_ttl: Duration::from_secs(3600),
+            min_interval: Duration::from_secs(1),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Reputation client shared by all analyses, so the cache and rate limits
+/// span documents
+#[derive(Debug)]
+pub struct ReputationChecker {
+    config: ReputationConfig,
+    client: reqwest::Client,
+    cache: Mutex<HashMap<(String, String), (Instant, ReputationVerdict)>>,
+    last_request: Vec<tokio::sync::Mutex<Option<Instant>>>,
+}
+
+impl ReputationChecker {
+    /// Creates a checker with an empty cache
+    pub fn new(config: ReputationConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .user_agent(concat!("pdx/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        let last_request = config.services.iter().map(|_| tokio::sync::Mutex::new(None)).collect();
+        Ok(Self {
+            config,
+            client,
+            cache: Mutex::new(HashMap::new()),
+            last_request,
+        })
+    }
+
+    /// Looks up every web URL in the report and attaches the verdicts,
+    /// raising the severity of listed URIs. Failed lookups are logged and
+    /// leave the URI without a verdict from that service.
+    #[instrument(skip(self, report))]
+    pub async fn annotate(&self, report: &mut UriReport) {
+        let urls: Vec<String> = report.uris.iter().filter_map(lookup_url).collect();
+        if urls.is_empty() {
+            return;
+        }
+
+        for (index, service) in self.config.services.iter().enumerate() {
+            let pending: Vec<String> = urls
+                .iter()
+                .filter(|url| self.cached(service, url).is_none())
+                .cloned()
+                .collect();
+            if !pending.is_empty() {
+                match self.query(index, service, &pending).await {
+                    Ok(verdicts) => self.store(service, verdicts),
+                    // Request URLs can carry credentials
+                    Err(e) => warn!("Reputation lookup with {} failed: {}", service.name(), e.without_url()),
+                }
+            }
+
+            for uri in &mut report.uris {
+                let Some(url) = lookup_url(uri) else {
+                    continue;
+                };
+                if let Some(verdict) = self.cached(service, &url) {
+                    apply(uri, verdict);
+                }
+            }
+        }
+        debug!(
+            "Checked {} URLs with {} services",
+            urls.len(),
+            self.config.services.len()
+        );
+    }
+
+    fn cached(&self, service: &ReputationService, url: &str) -> Option<ReputationVerdict> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .get(&(service.name().to_string(), url.to_string()))
+            .filter(|(stored, _)| stored.elapsed() < self.config.cache_ttl)
+            .map(|(_, verdict)| verdict.clone())
+    }
+
+    fn store(&self, service: &ReputationService, verdicts: Vec<(String, ReputationVerdict)>) {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        cache.retain(|_, (stored, _)| now.duration_since(*stored) < self.config.cache_ttl);
+        for (url, verdict) in verdicts {
+            cache.insert((service.name().to_string(), url), (now, verdict));
+        }
+    }
+
+    /// Waits until the service's rate limit allows another request
+    async fn throttle(&self, index: usize) {
+        let mut last = self.last_request[index].lock().await;
+        if let Some(previous) = *last {
+            let ready = previous + self.config.min_interval;
+            let now = Instant::now();
+            if ready > now {
+                tokio::time::sleep(ready - now).await;
+            }
+        }
+        *last = Some(Instant::now());
+    }
+
+    async fn query(
+        &self,
+        index: usize,
+        service: &ReputationService,
+        urls: &[String],
+    ) -> reqwest::Result<Vec<(String, ReputationVerdict)>> {
+        let mut verdicts = Vec::new();
+        match service {
+            ReputationService::SafeBrowsing { api_key } => {
+                for batch in urls.chunks(SAFE_BROWSING_BATCH) {
+                    self.throttle(index).await;
+                    let response: Value = self
+                        .client
+                        .post(SAFE_BROWSING_URL)
+                        .header("x-goog-api-key", api_key)
+                        .json(&safe_browsing_request(batch))
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .json()
+                        .await?;
+                    verdicts.extend(safe_browsing_verdicts(batch, &response));
+                }
+            }
+            ReputationService::PhishTank { app_key } => {
+                for url in urls {
+                    self.throttle(index).await;
+                    let mut form = vec![("url", url.as_str()), ("format", "json")];
+                    if let Some(key) = app_key {
+                        form.push(("app_key", key.as_str()));
+                    }
+                    let response: Value = self
+                        .client
+                        .post(PHISHTANK_URL)
+                        .form(&form)
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .json()
+                        .await?;
+                    verdicts.push((url.clone(), phishtank_verdict(&response)));
+                }
+            }
+            ReputationService::Custom { endpoint } => {
+                for url in urls {
+                    self.throttle(index).await;
+                    let host = reqwest::Url::parse(url)
+                        .ok()
+                        .and_then(|u| u.host_str().map(str::to_string));
+                    let response: Value = self
+                        .client
+                        .post(endpoint)
+                        .json(&json!({ "url": url, "host": host }))
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .json()
+                        .await?;
+                    verdicts.push((url.clone(), custom_verdict(endpoint, &response)));
+                }
+            }
+        }
+        Ok(verdicts)
+    }
+}
+
+/// URL sent to the services: web URLs only, scheme added to `www.` links
+fn lookup_url(uri: &ExtractedUri) -> Option<String> {
+    let lower = uri.decoded.to_ascii_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        Some(uri.decoded.clone())
+    } else if lower.starts_with("www.") {
+        Some(format!("http://{}", uri.decoded))
+    } else {
+        None
+    }
+}
+
+fn apply(uri: &mut ExtractedUri, verdict: ReputationVerdict) {
+    if verdict.reputation.severity() > uri.severity {
+        uri.severity = verdict.reputation.severity();
+    }
+    if verdict.reputation >= Reputation::Suspicious {
+        uri.description = format!(
+            "{} (listed as {:?} by {})",
+            uri.description, verdict.reputation, verdict.service
+        );
+    }
+    uri.reputation.retain(|existing| existing.service != verdict.service);
+    uri.reputation.push(verdict);
+}
+
+fn safe_browsing_request(urls: &[String]) -> Value {
+    json!({
+        "client": { "clientId": "pdx", "clientVersion": env!("CARGO_PKG_VERSION") },
+        "threatInfo": {
+            "threatTypes": ["MALWARE", "SOCIAL_ENGINEERING", "UNWANTED_SOFTWARE", "POTENTIALLY_HARMFUL_APPLICATION"],
+            "platformTypes": ["ANY_PLATFORM"],
+            "threatEntryTypes": ["URL"],
+            "threatEntries": urls.iter().map(|url| json!({ "url": url })).collect::<Vec<_>>(),
+        },
+    })
+}
+
+/// Safe Browsing only returns matches; every other URL is unknown to it
+fn safe_browsing_verdicts(urls: &[String], response: &Value) -> Vec<(String, ReputationVerdict)> {
+    let matches = response["matches"].as_array().map(Vec::as_slice).unwrap_or_default();
+    urls.iter()
+        .map(|url| {
+            let threat = matches
+                .iter()
+                .find(|m| m["threat"]["url"].as_str() == Some(url))
+                .and_then(|m| m["threatType"].as_str());
+            let verdict = ReputationVerdict {
+                service: "safe-browsing".to_string(),
+                reputation: if threat.is_some() {
+                    Reputation::Malicious
+                } else {
+                    Reputation::Unknown
+                },
+                detail: threat.map(str::to_string),
+            };
+            (url.clone(), verdict)
+        })
+        .collect()
+}
+
+fn phishtank_verdict(response: &Value) -> ReputationVerdict {
+    let results = &response["results"];
+    let flag = |key: &str| {
+        results[key]
+            .as_bool()
+            .or_else(|| results[key].as_str().map(|s| s == "true"))
+            == Some(true)
+    };
+    let reputation = match (flag("in_database"), flag("verified"), flag("valid")) {
+        (true, true, true) => Reputation::Malicious,
+        (true, false, _) => Reputation::Suspicious,
+        _ => Reputation::Unknown,
+    };
+    ReputationVerdict {
+        service: "phishtank".to_string(),
+        reputation,
+        detail: results["phish_detail_page"].as_str().map(str::to_string),
+    }
+}
+
+fn custom_verdict(endpoint: &str, response: &Value) -> ReputationVerdict {
+    ReputationVerdict {
+        service: endpoint.to_string(),
+        reputation: serde_json::from_value(response["verdict"].clone()).unwrap_or(Reputation::Unknown),
+        detail: response["detail"].as_str().map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Object};
+
+    #[test]
+    fn test_service_responses() {
+        let urls = vec!["https://bad.example/".to_string(), "https://good.example/".to_string()];
+        let response =
+            json!({ "matches": [{ "threatType": "SOCIAL_ENGINEERING", "threat": { "url": "https://bad.example/" } }] });
+        let verdicts = safe_browsing_verdicts(&urls, &response);
+        assert_eq!(verdicts[0].1.reputation, Reputation::Malicious);
+        assert_eq!(verdicts[0].1.detail.as_deref(), Some("SOCIAL_ENGINEERING"));
+        assert_eq!(verdicts[1].1.reputation, Reputation::Unknown);
+        assert!(safe_browsing_verdicts(&urls, &json!({}))
+            .iter()
+            .all(|(_, v)| v.reputation == Reputation::Unknown));
+
+        let listed = json!({ "results": { "in_database": true, "verified": "true", "valid": "true" } });
+        assert_eq!(phishtank_verdict(&listed).reputation, Reputation::Malicious);
+        assert_eq!(
+            phishtank_verdict(&json!({ "results": { "in_database": false } })).reputation,
+            Reputation::Unknown
+        );
+
+        let custom = custom_verdict("https://rep.example/check", &json!({ "verdict": "suspicious" }));
+        assert_eq!(custom.reputation, Reputation::Suspicious);
+        assert_eq!(custom.reputation.severity(), Severity::Medium);
+    }
+
+    #[tokio::test]
+    async fn test_cached_verdicts_are_attached() {
+        let mut doc = fixtures::document();
+        doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::string_literal("https://login.example/"),
+        });
+        let mut report = crate::detectors::uris::extract(&doc);
+        assert_eq!(report.uris[0].severity, Severity::Info);
+
+        // Unroutable endpoint: a cache miss would fail and attach nothing
+        let service = ReputationService::Custom {
+            endpoint: "http://127.0.0.1:9/".to_string(),
+        };
+        let checker = ReputationChecker::new(ReputationConfig {
+            services: vec![service.clone()],
+            timeout: Duration::from_millis(200),
+            ..Default::default()
+        })
+        .unwrap();
+        let verdict = ReputationVerdict {
+            service: service.name().to_string(),
+            reputation: Reputation::Malicious,
+            detail: Some("phishing kit".to_string()),
+        };
+        checker.store(&service, vec![("https://login.example/".to_string(), verdict.clone())]);
+
+        checker.annotate(&mut report).await;
+        assert_eq!(report.uris[0].reputation, vec![verdict]);
+        assert_eq!(report.uris[0].severity, Severity::Critical);
+    }
+}
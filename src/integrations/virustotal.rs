@@ -0,0 +1,410 @@
+//! Opt-in VirusTotal hash lookups
+//! Created: 2026-10-16 10:30:33 UTC
+//! Author: kartik4091
+//!
+//! Looks up the SHA-256 of the document and of each embedded file with
+//! the VirusTotal v3 API and records the detection ratios. Only hashes are
+//! sent unless submission is enabled, in which case samples VirusTotal has
+//! never seen are uploaded, and become visible to its subscribers. Nothing
+//! is sent unless a client is configured, and the client needs an API key
+//! from the config file or [`API_KEY_VAR`].
+//!
+//! ```toml
+//! api_key = "..."
+//! submit = false
+//! min_interval_secs = 15
+//! min_detections = 3
+//! ```
+//!
+//! Single engines misfire often enough that fewer than `min_detections`
+//! malicious verdicts only make a sample suspicious (Medium).
+
+use std::{
+    fmt,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use lopdf::{Document, ObjectId};
+use reqwest::{multipart, StatusCode};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, instrument, warn};
+
+use super::clamav::{Payload, PayloadKind};
+use crate::{
+    detectors::Severity,
+    document::{attachments, sha256_hex},
+    error::{Error, Result},
+};
+
+/// Environment variable holding the API key when the config has none
+pub const API_KEY_VAR: &str = "PDX_VIRUSTOTAL_KEY";
+
+const API_URL: &str = "https://www.virustotal.com/api/v3";
+
+/// Largest file the plain upload endpoint accepts (32 MB)
+const MAX_UPLOAD: usize = 32 * 1000 * 1000;
+
+/// Engines flagging a sample at which it is reported as critical
+const CRITICAL_DETECTIONS: u32 = 5;
+
+/// Default of [`VirusTotalConfig::min_detections`]
+pub const DEFAULT_MIN_DETECTIONS: u32 = 3;
+
+/// Lookup settings, read from a TOML file
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VirusTotalConfig {
+    /// API key; [`API_KEY_VAR`] is used when absent
+    pub api_key: Option<String>,
+
+    /// Upload samples VirusTotal does not know
+    pub submit: bool,
+
+    /// Per-request timeout in seconds
+    pub timeout_secs: u64,
+
+    /// Minimum delay between requests; the public API allows four a minute
+    pub min_interval_secs: u64,
+
+    /// Malicious verdicts from which a sample is reported as High rather
+    /// than Medium
+    pub min_detections: u32,
+}
+
+impl Default for VirusTotalConfig {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            submit: false,
+            timeout_secs: 30,
+            min_interval_secs: 15,
+            min_detections: DEFAULT_MIN_DETECTIONS,
+        }
+    }
+}
+
+// The API key stays out of logs and debug output
+impl fmt::Debug for VirusTotalConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirusTotalConfig")
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("submit", &self.submit)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("min_interval_secs", &self.min_interval_secs)
+            .field("min_detections", &self.min_detections)
+            .finish()
+    }
+}
+
+impl VirusTotalConfig {
+    /// Reads the settings from a TOML file; omitted settings keep their
+    /// defaults
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| Error::Analysis(format!("VirusTotal config {}: {}", path.display(), e)))
+    }
+}
+
+/// What VirusTotal knows about a sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SampleStatus {
+    /// Analyzed before; the ratio is from the last analysis
+    Known,
+
+    /// Never seen
+    Unknown,
+
+    /// Never seen, uploaded now; results follow on the permalink
+    Submitted,
+}
+
+/// Verdict on one sample
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SampleVerdict {
+    /// Sample kind (the document or an attachment)
+    pub kind: PayloadKind,
+
+    /// Embedded file stream, `None` for the document
+    pub object: Option<ObjectId>,
+
+    /// Attachment name or `<document>`
+    pub name: String,
+
+    /// SHA-256 of the sample
+    pub sha256: String,
+
+    /// Lookup outcome
+    pub status: SampleStatus,
+
+    /// Engines reporting the sample as malicious
+    pub malicious: u32,
+
+    /// Engines reporting it as suspicious
+    pub suspicious: u32,
+
+    /// Engines that returned a result
+    pub engines: u32,
+
+    /// Report page
+    pub permalink: String,
+}
+
+impl SampleVerdict {
+    /// Severity of the detection ratio, `None` when nothing flags the
+    /// sample; fewer than `min_detections` malicious verdicts are Medium
+    pub fn severity(&self, min_detections: u32) -> Option<Severity> {
+        match (self.malicious, self.suspicious) {
+            (m, _) if m >= CRITICAL_DETECTIONS.max(min_detections) => Some(Severity::Critical),
+            (m, _) if m >= min_detections.max(1) => Some(Severity::High),
+            (m, s) if m > 0 || s > 0 => Some(Severity::Medium),
+            _ => None,
+        }
+    }
+}
+
+/// Verdicts on the document and its attachments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct VirusTotalReport {
+    /// One verdict per distinct sample
+    pub samples: Vec<SampleVerdict>,
+
+    /// Malicious verdicts from which a sample counts as High
+    #[serde(default = "default_min_detections")]
+    pub min_detections: u32,
+}
+
+fn default_min_detections() -> u32 {
+    DEFAULT_MIN_DETECTIONS
+}
+
+impl Default for VirusTotalReport {
+    fn default() -> Self {
+        Self {
+            samples: Vec::new(),
+            min_detections: DEFAULT_MIN_DETECTIONS,
+        }
+    }
+}
+
+impl VirusTotalReport {
+    /// Severity of a sample of this report
+    pub fn severity(&self, sample: &SampleVerdict) -> Option<Severity> {
+        sample.severity(self.min_detections)
+    }
+}
+
+/// VirusTotal client, shared by all analyses so the rate limit spans
+/// documents
+#[derive(Debug)]
+pub struct VirusTotalClient {
+    config: VirusTotalConfig,
+    api_key: String,
+    client: reqwest::Client,
+    last_request: tokio::sync::Mutex<Option<Instant>>,
+}
+
+impl VirusTotalClient {
+    /// Creates a client; fails without an API key
+    pub fn new(config: VirusTotalConfig) -> Result<Self> {
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var(API_KEY_VAR).ok())
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| {
+                Error::Analysis(format!(
+                    "VirusTotal needs an API key in its config file or in {}",
+                    API_KEY_VAR
+                ))
+            })?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent(concat!("pdx/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+        Ok(Self {
+            config,
+            api_key,
+            client,
+            last_request: tokio::sync::Mutex::new(None),
+        })
+    }
+
+    /// Looks up the document and attachment samples, submitting unknown
+    /// ones when configured. Returns the verdicts and a warning for every
+    /// sample whose lookup failed.
+    #[instrument(skip_all)]
+    pub async fn check(&self, samples: Vec<Payload>) -> (VirusTotalReport, Vec<String>) {
+        let mut report = VirusTotalReport {
+            samples: Vec::new(),
+            min_detections: self.config.min_detections,
+        };
+        let mut warnings = Vec::new();
+        for sample in samples {
+            let sha256 = sha256_hex(&sample.data);
+            if report.samples.iter().any(|s| s.sha256 == sha256) {
+                continue;
+            }
+            match self.verdict(&sample, &sha256).await {
+                Ok(verdict) => report.samples.push(verdict),
+                Err(e) => {
+                    warn!("VirusTotal lookup of {} failed: {}", sample.name, e);
+                    warnings.push(format!("VirusTotal lookup of {} failed: {}", sample.name, e));
+                }
+            }
+        }
+        debug!("Looked up {} samples on VirusTotal", report.samples.len());
+        (report, warnings)
+    }
+
+    async fn verdict(&self, sample: &Payload, sha256: &str) -> Result<SampleVerdict> {
+        let mut verdict = SampleVerdict {
+            kind: sample.kind,
+            object: sample.object,
+            name: sample.name.clone(),
+            sha256: sha256.to_string(),
+            status: SampleStatus::Unknown,
+            malicious: 0,
+            suspicious: 0,
+            engines: 0,
+            permalink: format!("https://www.virustotal.com/gui/file/{}", sha256),
+        };
+
+        self.throttle().await;
+        let response = self
+            .client
+            .get(format!("{}/files/{}", API_URL, sha256))
+            .header("x-apikey", &self.api_key)
+            .send()
+            .await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            let body: Value = response.error_for_status()?.json().await?;
+            apply_stats(&mut verdict, &body);
+            return Ok(verdict);
+        }
+
+        if self.config.submit {
+            if sample.data.len() > MAX_UPLOAD {
+                return Err(Error::Analysis(format!("{} is too large to submit", sample.name)));
+            }
+            self.throttle().await;
+            let form = multipart::Form::new().part(
+                "file",
+                multipart::Part::bytes(sample.data.clone()).file_name(sample.name.clone()),
+            );
+            self.client
+                .post(format!("{}/files", API_URL))
+                .header("x-apikey", &self.api_key)
+                .multipart(form)
+                .send()
+                .await?
+                .error_for_status()?;
+            verdict.status = SampleStatus::Submitted;
+        }
+        Ok(verdict)
+    }
+
+    /// Waits until the rate limit allows another request
+    async fn throttle(&self) {
+        let mut last = self.last_request.lock().await;
+        if let Some(previous) = *last {
+            let ready = previous + Duration::from_secs(self.config.min_interval_secs);
+            let now = Instant::now();
+            if ready > now {
+                tokio::time::sleep(ready - now).await;
+            }
+        }
+        *last = Some(Instant::now());
+    }
+}
+
+/// Samples looked up: the document and its embedded files
+pub fn samples(data: &[u8], doc: Option<&Document>) -> Vec<Payload> {
+    let mut samples = vec![Payload {
+        kind: PayloadKind::Document,
+        object: None,
+        name: "<document>".to_string(),
+        data: data.to_vec(),
+    }];
+    samples.extend(
+        doc.map(attachments::extract)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|file| Payload {
+                kind: PayloadKind::Attachment,
+                object: Some(file.id),
+                name: file.name,
+                data: file.data,
+            }),
+    );
+    samples
+}
+
+/// Fills in the last analysis stats of a `GET /files/{id}` response
+fn apply_stats(verdict: &mut SampleVerdict, body: &Value) {
+    let stats = &body["data"]["attributes"]["last_analysis_stats"];
+    let count = |key: &str| stats[key].as_u64().unwrap_or(0) as u32;
+    verdict.status = SampleStatus::Known;
+    verdict.malicious = count("malicious");
+    verdict.suspicious = count("suspicious");
+    // Engines that timed out or cannot handle the type gave no answer
+    verdict.engines = ["malicious", "suspicious", "undetected", "harmless"]
+        .iter()
+        .map(|key| count(key))
+        .sum();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn verdict() -> SampleVerdict {
+        SampleVerdict {
+            kind: PayloadKind::Document,
+            object: None,
+            name: "<document>".to_string(),
+            sha256: "00".repeat(32),
+            status: SampleStatus::Unknown,
+            malicious: 0,
+            suspicious: 0,
+            engines: 0,
+            permalink: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_detection_ratio() {
+        let mut flagged = verdict();
+        let body = json!({ "data": { "attributes": { "last_analysis_stats": {
+            "malicious": 12, "suspicious": 1, "undetected": 50, "harmless": 0, "timeout": 3, "type-unsupported": 8
+        } } } });
+        apply_stats(&mut flagged, &body);
+        assert_eq!(flagged.status, SampleStatus::Known);
+        assert_eq!((flagged.malicious, flagged.engines), (12, 63));
+        assert_eq!(flagged.severity(DEFAULT_MIN_DETECTIONS), Some(Severity::Critical));
+        flagged.malicious = 1;
+        assert_eq!(flagged.severity(DEFAULT_MIN_DETECTIONS), Some(Severity::Medium));
+        assert_eq!(flagged.severity(1), Some(Severity::High));
+
+        let mut clean = verdict();
+        apply_stats(
+            &mut clean,
+            &json!({ "data": { "attributes": { "last_analysis_stats": { "undetected": 60 } } } }),
+        );
+        assert_eq!(clean.severity(DEFAULT_MIN_DETECTIONS), None);
+    }
+
+    #[test]
+    fn test_config_keeps_key_out_of_debug() {
+        let config: VirusTotalConfig = toml::from_str("api_key = \"secret\"\nsubmit = true").unwrap();
+        assert!(config.submit);
+        assert_eq!(config.min_interval_secs, 15);
+        assert!(!format!("{:?}", config).contains("secret"));
+        assert!(!VirusTotalConfig::default().submit);
+    }
+}
@@ -0,0 +1,408 @@
+//! Drop-directory monitoring
+//! Created: 2026-10-16 11:18:40 UTC
+//! Author: kartik4091
+//!
+//! Watches a directory with the platform's filesystem notifications and
+//! scans every PDF written or moved into it once the file has stopped
+//! changing for a settle period. A file whose risk score reaches the
+//! threshold, that the policy rejects or that cannot be analyzed is moved
+//! to the quarantine directory, any other to the clean directory (each only
+//! when configured), and the outcome can be posted as JSON to a webhook.
+//! Webhooks are posted in the background with a timeout, so a slow
+//! receiver does not hold up the watch. Files inside the destination
+//! directories are never picked up again, so they may live below the
+//! watched directory.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use notify::{event::EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use super::policy::{Policy, Verdict};
+use crate::{
+    batch,
+    error::{Error, Result},
+    report::ScanReport,
+    scoring::ScoringModel,
+    AnalysisOptions, PdfAnalyzer,
+};
+
+/// Default quiet period before a changed file is scanned
+pub const DEFAULT_SETTLE: Duration = Duration::from_secs(2);
+
+/// Default risk score at which a file is quarantined
+pub const DEFAULT_THRESHOLD: f64 = 0.5;
+
+/// How often pending files are checked for having settled
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long a webhook receiver gets to accept an outcome
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where a scanned file went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Disposition {
+    /// Moved to the quarantine directory
+    Quarantined,
+
+    /// Moved to the clean directory
+    Cleaned,
+
+    /// Left in place
+    Kept,
+}
+
+/// Result of scanning one file of the drop directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchOutcome {
+    /// Scan of the file, with the threshold applied
+    pub scan: ScanReport,
+
+    /// Whether the file is quarantined: its risk score reached the
+    /// threshold, the policy rejects it or it could not be analyzed
+    pub risky: bool,
+
+    /// Why the analysis failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// What happened to the file
+    pub disposition: Disposition,
+
+    /// New location of a moved file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moved_to: Option<PathBuf>,
+}
+
+/// Drop-directory watcher
+#[derive(Debug, Clone)]
+pub struct DropWatcher {
+    dir: PathBuf,
+    recursive: bool,
+    settle: Duration,
+    threshold: f64,
+    quarantine: Option<PathBuf>,
+    clean: Option<PathBuf>,
+    webhook: Option<String>,
+    webhook_all: bool,
+    policy: Policy,
+    options: AnalysisOptions,
+    client: reqwest::Client,
+}
+
+impl DropWatcher {
+    /// Watcher of `dir` that scans with the default policy and scoring and moves nothing
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            recursive: false,
+            settle: DEFAULT_SETTLE,
+            threshold: DEFAULT_THRESHOLD,
+            quarantine: None,
+            clean: None,
+            webhook: None,
+            webhook_all: false,
+            policy: Policy::default(),
+            options: AnalysisOptions::default(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Also watches the subdirectories of the directory
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Sets how long a file must stay unchanged before it is scanned
+    pub fn settle(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+
+    /// Sets the risk score (0.0-1.0) at which a file counts as risky
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Moves risky files to `dir`
+    pub fn quarantine(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.quarantine = Some(dir.into());
+        self
+    }
+
+    /// Moves files below the threshold to `dir`
+    pub fn clean(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.clean = Some(dir.into());
+        self
+    }
+
+    /// Posts the outcome of risky files, or of every file with `all`, to `url`
+    pub fn webhook(mut self, url: impl Into<String>, all: bool) -> Self {
+        self.webhook = Some(url.into());
+        self.webhook_all = all;
+        self
+    }
+
+    /// Sets the policy behind the verdict of each scan
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets the risk scoring model
+    pub fn scoring(mut self, model: ScoringModel) -> Self {
+        self.options.scoring = model;
+        self
+    }
+
+    /// Sets the analysis options used for each file, including their
+    /// scoring model
+    pub fn options(mut self, options: AnalysisOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Watches until the notification channel closes, passing each outcome
+    /// to `report`; with `existing`, PDFs already in the directory are
+    /// scanned first
+    pub async fn run(&self, existing: bool, mut report: impl FnMut(&WatchOutcome)) -> Result<()> {
+        for dir in self.quarantine.iter().chain(&self.clean) {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut watcher: RecommendedWatcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let _ = tx.send(event);
+            })
+            .map_err(|e| Error::Analysis(format!("Cannot watch {}: {}", self.dir.display(), e)))?;
+        let mode = if self.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher
+            .watch(&self.dir, mode)
+            .map_err(|e| Error::Analysis(format!("Cannot watch {}: {}", self.dir.display(), e)))?;
+        info!("Watching {}", self.dir.display());
+
+        // Paths waiting to settle, with the time of their last change
+        let mut pending: BTreeMap<PathBuf, Instant> = BTreeMap::new();
+        if existing {
+            let selection = batch::FileSelection {
+                recursive: self.recursive,
+                ..Default::default()
+            };
+            for path in selection.expand(&[self.dir.clone()]).await.files {
+                if self.watches(&path) {
+                    let due = Instant::now().checked_sub(self.settle).unwrap_or_else(Instant::now);
+                    pending.insert(path, due);
+                }
+            }
+        }
+
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Some(Ok(event)) => {
+                        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                            for path in event.paths.into_iter().filter(|path| self.watches(path)) {
+                                pending.insert(path, Instant::now());
+                            }
+                        }
+                    }
+                    Some(Err(e)) => warn!("Watch error on {}: {}", self.dir.display(), e),
+                    None => return Ok(()),
+                },
+                _ = ticker.tick() => {
+                    let settled: Vec<PathBuf> = pending
+                        .iter()
+                        .filter(|(_, changed)| changed.elapsed() >= self.settle)
+                        .map(|(path, _)| path.clone())
+                        .collect();
+                    for path in settled {
+                        pending.remove(&path);
+                        // Gone again (moved away or a temporary file)
+                        if !path.is_file() {
+                            continue;
+                        }
+                        match self.handle(&path).await {
+                            Ok(outcome) => report(&outcome),
+                            Err(e) => warn!("Handling of {} failed: {}", path.display(), e),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Scans one file and applies the configured actions. Files that fail
+    /// to analyze, or panic the analyzer, are quarantined.
+    pub async fn handle(&self, path: &Path) -> Result<WatchOutcome> {
+        let analyzed =
+            batch::isolated(async { PdfAnalyzer::with_options(path, self.options.clone())?.analyze().await }).await;
+        let (scan, error) = match analyzed {
+            Ok(analysis) => (ScanReport::new(&analysis, &self.policy), None),
+            Err(e) => {
+                warn!("Scan of {} failed: {}", path.display(), e);
+                let decision = self.policy.error(&e);
+                (
+                    ScanReport::failed(path.display().to_string(), decision),
+                    Some(e.to_string()),
+                )
+            }
+        };
+        let scan = scan.threshold(self.threshold);
+        let risky = error.is_some() || scan.exceeds_threshold() || scan.decision.verdict == Verdict::Reject;
+
+        let (disposition, target) = if risky {
+            (Disposition::Quarantined, self.quarantine.as_deref())
+        } else {
+            (Disposition::Cleaned, self.clean.as_deref())
+        };
+        let mut outcome = WatchOutcome {
+            scan,
+            risky,
+            error,
+            disposition: Disposition::Kept,
+            moved_to: None,
+        };
+        if let Some(dir) = target {
+            let destination = move_into(path, dir).await?;
+            info!("Moved {} to {}", path.display(), destination.display());
+            outcome.disposition = disposition;
+            outcome.moved_to = Some(destination);
+        }
+
+        if let Some(url) = self.webhook.clone().filter(|_| risky || self.webhook_all) {
+            let request = self.client.post(&url).timeout(WEBHOOK_TIMEOUT).json(&outcome);
+            let path = path.to_path_buf();
+            tokio::spawn(async move {
+                match request.send().await.and_then(|r| r.error_for_status()) {
+                    Ok(_) => debug!("Posted the outcome of {} to {}", path.display(), url),
+                    Err(e) => warn!("Webhook {} failed for {}: {}", url, path.display(), e.without_url()),
+                }
+            });
+        }
+        Ok(outcome)
+    }
+
+    /// Whether a changed path is a PDF to scan
+    fn watches(&self, path: &Path) -> bool {
+        path.extension().map_or(false, |e| e.eq_ignore_ascii_case("pdf"))
+            && !self.quarantine.iter().chain(&self.clean).any(|dir| inside(path, dir))
+    }
+}
+
+/// Whether `path` is inside `dir`, comparing canonical paths where they exist
+fn inside(path: &Path, dir: &Path) -> bool {
+    let canonical = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    let dir = canonical(dir);
+    path.parent()
+        .map_or(false, |parent| canonical(parent).starts_with(&dir))
+}
+
+/// Moves a file into `dir` under a name not taken yet, copying across filesystems
+async fn move_into(path: &Path, dir: &Path) -> Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| Error::Analysis(format!("{} has no file name", path.display())))?;
+    let mut destination = dir.join(name);
+    let stem = Path::new(name)
+        .file_stem()
+        .unwrap_or(name)
+        .to_string_lossy()
+        .into_owned();
+    let extension = Path::new(name).extension().map(|e| e.to_string_lossy().into_owned());
+    let mut copy = 1;
+    while tokio::fs::try_exists(&destination).await? {
+        let numbered = match &extension {
+            Some(extension) => format!("{}.{}.{}", stem, copy, extension),
+            None => format!("{}.{}", stem, copy),
+        };
+        destination = dir.join(numbered);
+        copy += 1;
+    }
+
+    if tokio::fs::rename(path, &destination).await.is_err() {
+        tokio::fs::copy(path, &destination).await?;
+        tokio::fs::remove_file(path).await?;
+    }
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_handle_moves_by_risk() {
+        let dir = tempdir().unwrap();
+        let (quarantine, clean) = (dir.path().join("quarantine"), dir.path().join("clean"));
+        let file = dir.path().join("invoice.pdf");
+
+        std::fs::write(&file, fixtures::simple_pdf()).unwrap();
+        let watcher = DropWatcher::new(dir.path()).quarantine(&quarantine).clean(&clean);
+        let outcome = watcher.handle(&file).await.unwrap();
+        assert!(!outcome.risky);
+        assert_eq!(outcome.disposition, Disposition::Cleaned);
+        assert_eq!(outcome.moved_to.as_deref(), Some(clean.join("invoice.pdf").as_path()));
+        assert!(!file.exists());
+
+        // Everything reaches a zero threshold
+        std::fs::write(&file, fixtures::simple_pdf()).unwrap();
+        let outcome = watcher.threshold(0.0).handle(&file).await.unwrap();
+        assert!(outcome.risky);
+        assert_eq!(outcome.disposition, Disposition::Quarantined);
+        assert!(quarantine.join("invoice.pdf").exists());
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_file_is_quarantined() {
+        let dir = tempdir().unwrap();
+        let quarantine = dir.path().join("quarantine");
+        // A directory named like a PDF cannot be read as one
+        let file = dir.path().join("broken.pdf");
+        std::fs::create_dir(&file).unwrap();
+
+        let watcher = DropWatcher::new(dir.path())
+            .quarantine(&quarantine)
+            .clean(dir.path().join("clean"));
+        let outcome = watcher.handle(&file).await.unwrap();
+        assert!(outcome.risky);
+        assert!(outcome.error.is_some());
+        assert_eq!(outcome.disposition, Disposition::Quarantined);
+        assert!(quarantine.join("broken.pdf").exists());
+    }
+
+    #[tokio::test]
+    async fn test_destinations_are_not_watched_or_overwritten() {
+        let dir = tempdir().unwrap();
+        let quarantine = dir.path().join("quarantine");
+        std::fs::create_dir(&quarantine).unwrap();
+        let watcher = DropWatcher::new(dir.path()).quarantine(&quarantine);
+        assert!(watcher.watches(&dir.path().join("new.PDF")));
+        assert!(!watcher.watches(&dir.path().join("notes.txt")));
+        assert!(!watcher.watches(&quarantine.join("old.pdf")));
+
+        std::fs::write(quarantine.join("a.pdf"), b"first").unwrap();
+        let incoming = dir.path().join("a.pdf");
+        std::fs::write(&incoming, b"second").unwrap();
+        let moved = move_into(&incoming, &quarantine).await.unwrap();
+        assert_eq!(moved, quarantine.join("a.1.pdf"));
+        assert_eq!(std::fs::read(quarantine.join("a.pdf")).unwrap(), b"first");
+        assert_eq!(std::fs::read(moved).unwrap(), b"second");
+    }
+}
@@ -0,0 +1,207 @@
+//! Known-good hash sets
+//! Created: 2026-10-16 10:26:48 UTC
+//! Author: kartik4091
+//!
+//! Loads allowlists of file hashes, either NSRL RDS exports (CSV with a
+//! header naming `SHA-1`, `MD5` and/or `SHA-256` columns) or plain lists
+//! with one hash per line, and matches documents and their attachments
+//! against them. A match does not stop the analysis: findings on
+//! known-good content are kept but lowered to informational, so batch
+//! triage de-prioritizes them and the policy accepts a known-good file.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use lopdf::{Document, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::{
+    document::{attachments, hashing::HashAlgorithm},
+    error::{Error, Result},
+};
+
+/// Algorithms hash sets are keyed by, in lookup order
+const ALGORITHMS: [HashAlgorithm; 3] = [HashAlgorithm::Sha256, HashAlgorithm::Sha1, HashAlgorithm::Md5];
+
+/// Hashes of files known to be benign
+#[derive(Debug, Clone, Default)]
+pub struct KnownGoodSet {
+    hashes: BTreeMap<HashAlgorithm, HashSet<Vec<u8>>>,
+}
+
+/// Content that matched a known-good hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct KnownGoodMatch {
+    /// Embedded file stream, `None` for the document itself
+    pub object: Option<ObjectId>,
+
+    /// Attachment name, or `<document>`
+    pub name: String,
+
+    /// Algorithm of the matching hash
+    pub algorithm: HashAlgorithm,
+
+    /// Matching hash, lowercase hexadecimal
+    pub hash: String,
+}
+
+impl KnownGoodSet {
+    /// Loads and merges hash set files
+    pub fn load(paths: &[impl AsRef<Path>]) -> Result<Self> {
+        let mut set = Self::default();
+        for path in paths {
+            let path = path.as_ref();
+            let file = File::open(path)?;
+            let added = set.read(BufReader::new(file)).map_err(|e| match e {
+                Error::Analysis(message) => Error::Analysis(format!("Hash set {}: {}", path.display(), message)),
+                other => other,
+            })?;
+            info!("Loaded {} known-good hashes from {}", added, path.display());
+        }
+        Ok(set)
+    }
+
+    /// Adds the hashes of an NSRL export or plain list, returning how many
+    /// were read
+    pub fn read(&mut self, reader: impl BufRead) -> Result<usize> {
+        let mut columns: Option<Vec<Option<HashAlgorithm>>> = None;
+        let mut added = 0;
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if number == 0 && line.contains(',') {
+                columns = Some(fields(line).map(|name| name.parse().ok()).collect());
+                continue;
+            }
+
+            match &columns {
+                Some(columns) => {
+                    for (algorithm, value) in columns.iter().zip(fields(line)) {
+                        if let Some(algorithm) = algorithm {
+                            added += self.insert(*algorithm, value) as usize;
+                        }
+                    }
+                }
+                None => {
+                    let value = line.split_whitespace().next().unwrap_or_default();
+                    let algorithm = match value.len() {
+                        64 => HashAlgorithm::Sha256,
+                        40 => HashAlgorithm::Sha1,
+                        32 => HashAlgorithm::Md5,
+                        _ => return Err(Error::Analysis(format!("line {}: not a hash: {}", number + 1, value))),
+                    };
+                    added += self.insert(algorithm, value) as usize;
+                }
+            }
+        }
+        Ok(added)
+    }
+
+    fn insert(&mut self, algorithm: HashAlgorithm, hex: &str) -> bool {
+        match from_hex(hex) {
+            Some(bytes) => self.hashes.entry(algorithm).or_default().insert(bytes),
+            None => false,
+        }
+    }
+
+    /// Number of hashes in the set
+    pub fn len(&self) -> usize {
+        self.hashes.values().map(HashSet::len).sum()
+    }
+
+    /// Whether the set holds no hashes
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Matching algorithm and hash of `data`, if the set contains it
+    pub fn lookup(&self, data: &[u8]) -> Option<(HashAlgorithm, String)> {
+        ALGORITHMS.iter().find_map(|&algorithm| {
+            let set = self.hashes.get(&algorithm).filter(|set| !set.is_empty())?;
+            let hash = algorithm.digest_hex(data);
+            set.contains(&from_hex(&hash)?).then_some((algorithm, hash))
+        })
+    }
+
+    /// Matches the document and each of its embedded files
+    pub fn check(&self, data: &[u8], doc: Option<&Document>) -> Vec<KnownGoodMatch> {
+        let mut found = Vec::new();
+        if let Some((algorithm, hash)) = self.lookup(data) {
+            found.push(KnownGoodMatch {
+                object: None,
+                name: "<document>".to_string(),
+                algorithm,
+                hash,
+            });
+        }
+        for file in doc.map(attachments::extract).unwrap_or_default() {
+            if let Some((algorithm, hash)) = self.lookup(&file.data) {
+                found.push(KnownGoodMatch {
+                    object: Some(file.id),
+                    name: file.name,
+                    algorithm,
+                    hash,
+                });
+            }
+        }
+        debug!("{} known-good matches", found.len());
+        found
+    }
+}
+
+/// Unquoted CSV fields; NSRL quotes every field and never embeds commas
+/// in the hash columns
+fn fields(line: &str) -> impl Iterator<Item = &str> {
+    line.split(',').map(|field| field.trim().trim_matches('"'))
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_list() {
+        let mut set = KnownGoodSet::default();
+        let list = "# vendor installers\n\
+                    ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  abc.txt\n\
+                    900150983CD24FB0D6963F7D28E17F72\n";
+        assert_eq!(set.read(list.as_bytes()).unwrap(), 2);
+        assert_eq!(set.lookup(b"abc").map(|(a, _)| a), Some(HashAlgorithm::Sha256));
+        assert_eq!(set.lookup(b"abd"), None);
+        assert!(set.read("not-a-hash\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_nsrl_export() {
+        let mut set = KnownGoodSet::default();
+        let rds = "\"SHA-1\",\"MD5\",\"CRC32\",\"FileName\",\"FileSize\",\"ProductCode\",\"OpSystemCode\",\"SpecialCode\"\n\
+                   \"A9993E364706816ABA3E25717850C26C9CD0D89D\",\"900150983CD24FB0D6963F7D28E17F72\",\"352441C2\",\"abc.txt\",3,1,\"358\",\"\"\n";
+        assert_eq!(set.read(rds.as_bytes()).unwrap(), 2);
+        assert_eq!(
+            set.lookup(b"abc"),
+            Some((
+                HashAlgorithm::Sha1,
+                "a9993e364706816aba3e25717850c26c9cd0d89d".to_string()
+            ))
+        );
+    }
+}
@@ -2,13 +2,63 @@
 //! Author: kartik4091
 //! Created: 2025-06-03 19:56:29 UTC
 
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use anyhow::Result;
 use thiserror::Error;
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
-use tracing::{info, warn, error};
+use tracing::{debug, info, warn, error};
 use async_trait::async_trait;
+use futures::future::BoxFuture;
+
+pub mod error;
+pub mod document;
+pub mod report;
+pub mod detectors;
+pub mod budget;
+pub mod integrations;
+pub mod remediation;
+pub mod summary;
+pub mod testing;
+pub mod sandbox;
+pub mod limits;
+pub mod text;
+pub mod registry;
+pub mod findings;
+pub mod attack;
+pub mod scoring;
+pub mod known_good;
+pub mod batch;
+
+use budget::{AnalysisBudget, Depth, Plan};
+use limits::LimitViolation;
+use scoring::ScoringModel;
+use document::{
+    diff::RevisionTimeline,
+    hashing::HashAlgorithm,
+    objects::scan_objects,
+    parser::parse_indirect,
+    xref::{effective_entries, read_sections},
+};
+use detectors::{
+    carving::CarvedObject, differential::ParserDivergence, reader_features::ReaderFeatureSummary,
+    xref_anomalies::XrefAnomaly, hybrid_xref::HybridReport, signatures::SignatureTimeline,
+    trailer_id::TrailerIdReport, header::HeaderReport, trailing::TrailingData, polyglot::Polyglot,
+    slack::SlackReport, object_streams::ObjStmReport, orphans::OrphanObject,
+    dangling::DanglingReference, stream_length::StreamLengthFinding,
+    filters::FilterAnomaly, obfuscation::ObfuscatedToken, page_tree::PageTreeAnomaly,
+    linearization::LinearizationReport, entropy::EntropyReport, attachments::AttachmentReport,
+    actions::ActionReport, annotations::AnnotationReport, forms::FormReport,
+    xfa::XfaReport, multimedia::MediaReport, layers::LayerReport, invisible_text::InvisibleText,
+    redaction::RedactionFailure, fonts::FontReport, uris::UriReport, pii::{PiiConfig, PiiReport},
+    secrets::SecretFinding, language::LanguageReport, phishing::PhishingAssessment,
+    yara::YaraMatch, Finding, custom_rules::{RuleMatch, RuleSet}, scripting::ScriptFinding,
+    plugins::PluginFinding, exploits::ExploitMatch, shellcode::ShellcodeFinding,
+    decoders::DecoderAnomaly,
+};
 
 #[derive(Error, Debug)]
 pub enum PdxError {
@@ -23,32 +73,320 @@ pub enum PdxError {
     
     #[error("Network error: {0}")]
     Network(#[from] reqwest::Error),
+    
+    #[error("Security limit exceeded: {0}")]
+    Security(String),
+}
+
+impl From<&limits::LimitViolation> for PdxError {
+    fn from(violation: &limits::LimitViolation) -> Self {
+        PdxError::Security(violation.description.clone())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PdfAnalysis {
+    /// Version of the output model, see [`report::schema::SCHEMA_VERSION`];
+    /// empty in output of releases before versioning
+    #[serde(default)]
+    pub schema_version: String,
     pub path: String,
     pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<usize>,
     pub metadata: PdfMetadata,
     pub security: SecurityInfo,
+    #[serde(default)]
+    pub summary: summary::DocumentSummary,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision_timeline: Option<RevisionTimeline>,
+    #[serde(default)]
+    pub carved_objects: Vec<CarvedObject>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parser_divergences: Option<Vec<ParserDivergence>>,
+    #[serde(default)]
+    pub reader_features: ReaderFeatureSummary,
+    #[serde(default)]
+    pub actions: ActionReport,
+    #[serde(default)]
+    pub annotations: AnnotationReport,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forms: Option<FormReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xfa: Option<XfaReport>,
+    #[serde(default)]
+    pub media: MediaReport,
+    #[serde(default)]
+    pub layers: LayerReport,
+    #[serde(default)]
+    pub invisible_text: Vec<InvisibleText>,
+    #[serde(default)]
+    pub redaction_failures: Vec<RedactionFailure>,
+    #[serde(default)]
+    pub fonts: FontReport,
+    #[serde(default)]
+    pub uris: UriReport,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pii: Option<PiiReport>,
+    #[serde(default)]
+    pub secrets: Vec<SecretFinding>,
+    #[serde(default)]
+    pub language: LanguageReport,
+    #[serde(default)]
+    pub phishing: PhishingAssessment,
+    #[serde(default)]
+    pub exploits: Vec<ExploitMatch>,
+    #[serde(default)]
+    pub shellcode: Vec<ShellcodeFinding>,
+    #[serde(default)]
+    pub decoder_anomalies: Vec<DecoderAnomaly>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub antivirus: Option<integrations::clamav::ClamAvReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virustotal: Option<integrations::virustotal::VirusTotalReport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_good: Vec<known_good::KnownGoodMatch>,
+    #[serde(default)]
+    pub yara_matches: Vec<YaraMatch>,
+    #[serde(default)]
+    pub rule_matches: Vec<RuleMatch>,
+    #[serde(default)]
+    pub script_findings: Vec<ScriptFinding>,
+    #[serde(default)]
+    pub plugin_findings: Vec<PluginFinding>,
+    #[serde(default, alias = "analyzer_findings")]
+    pub detector_findings: Vec<Finding>,
+    /// Every finding above in unified form, including embedded documents
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+    #[serde(default)]
+    pub xref_anomalies: Vec<XrefAnomaly>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hybrid_xref: Option<HybridReport>,
+    #[serde(default)]
+    pub signatures: SignatureTimeline,
+    #[serde(default)]
+    pub trailer_ids: TrailerIdReport,
+    #[serde(default)]
+    pub header: HeaderReport,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailing_data: Option<TrailingData>,
+    #[serde(default)]
+    pub polyglots: Vec<Polyglot>,
+    #[serde(default)]
+    pub slack: SlackReport,
+    #[serde(default)]
+    pub object_streams: ObjStmReport,
+    #[serde(default)]
+    pub orphan_objects: Vec<OrphanObject>,
+    #[serde(default)]
+    pub page_tree: Vec<PageTreeAnomaly>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linearization: Option<LinearizationReport>,
+    #[serde(default)]
+    pub dangling_references: Vec<DanglingReference>,
+    #[serde(default)]
+    pub stream_lengths: Vec<StreamLengthFinding>,
+    #[serde(default)]
+    pub filter_anomalies: Vec<FilterAnomaly>,
+    #[serde(default)]
+    pub obfuscated_tokens: Vec<ObfuscatedToken>,
+    #[serde(default)]
+    pub stream_entropy: EntropyReport,
+    #[serde(default)]
+    pub limit_violations: Vec<LimitViolation>,
+    #[serde(default)]
+    pub attachments: AttachmentReport,
+    #[serde(default)]
+    pub embedded: Vec<EmbeddedAnalysis>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<document::preview::DocumentPreview>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl PdfAnalysis {
+    /// Collects the unified findings once every pass has reported and scores
+    /// the summary from them
+    fn collect_findings(&mut self, model: &ScoringModel) {
+        self.findings = findings::collect(self);
+        self.summary.score(&self.findings, model);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PdfMetadata {
     pub size: u64,
     pub created: Option<DateTime<Utc>>,
     pub modified: Option<DateTime<Utc>>,
     pub author: Option<String>,
     pub title: Option<String>,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<FileDigest>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Analysis of a PDF carried inside the analyzed document
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EmbeddedAnalysis {
+    /// Attachment name; files unpacked from a ZIP add their archive path
+    pub name: String,
+
+    /// Embedded file stream holding the document or its archive
+    pub object: lopdf::ObjectId,
+
+    /// Analysis of the embedded document
+    pub analysis: PdfAnalysis,
+}
+
+/// File digest computed with the configured algorithm
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FileDigest {
+    pub algorithm: HashAlgorithm,
+    pub value: String,
+}
+
+impl FileDigest {
+    /// Digest of `data`, or `None` for SHA-256, which `sha256` already holds
+    fn compute(algorithm: HashAlgorithm, data: &[u8]) -> Option<Self> {
+        (algorithm != HashAlgorithm::Sha256).then(|| Self { algorithm, value: algorithm.digest_hex(data) })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SecurityInfo {
     pub encrypted: bool,
     pub permissions: Vec<String>,
     pub risks: Vec<String>,
 }
 
+/// Levels of embedded PDFs analyzed by default
+pub const DEFAULT_EMBEDDED_DEPTH: usize = 3;
+
+/// Built-in passes that [`PdfAnalyzer::reanalyze_incremental`] runs over
+/// the appended bytes only
+pub const INCREMENTAL_PASSES: [&str; 4] = ["carved_objects", "reader_features", "signatures", "header"];
+
+/// Optional analysis passes, all disabled by default except the analysis
+/// of embedded documents
+#[derive(Debug, Clone)]
+pub struct AnalysisOptions {
+    /// Diff consecutive revisions and attach a change timeline
+    pub diff_revisions: bool,
+
+    /// Resolve ambiguous constructs per viewer model and report divergences
+    pub simulate_viewers: bool,
+
+    /// Capture the object tree and image thumbnails shown by the HTML
+    /// report (not available in sandbox mode)
+    pub preview: bool,
+
+    /// Degrade analysis depth for files whose estimated cost exceeds this
+    pub budget: Option<AnalysisBudget>,
+
+    /// Write any data found after the final %%EOF to this path
+    pub dump_trailing: Option<PathBuf>,
+
+    /// Extract Flash, 3D and other multimedia payloads into this directory
+    /// (not available in sandbox mode, where the host never parses the file)
+    pub extract_media: Option<PathBuf>,
+
+    /// Analyze the file as it was when this revision was written
+    /// (zero-based, as numbered in the revision timeline)
+    pub revision: Option<usize>,
+
+    /// Algorithm for the reported file digest (SHA-256 is always computed
+    /// for file identity)
+    pub hash: HashAlgorithm,
+
+    /// Run the analysis in a locked-down child process
+    pub sandbox: Option<sandbox::SandboxConfig>,
+
+    /// Decoding and parsing limits; violations are reported as findings
+    pub limits: limits::ResourceLimits,
+
+    /// Analyze embedded PDFs, including those in ZIP attachments, up to
+    /// this many levels deep (0 disables; [`DEFAULT_EMBEDDED_DEPTH`] by default)
+    pub embedded_depth: usize,
+
+    /// Check extracted URIs with these reputation services (sends the
+    /// URLs to third parties)
+    pub reputation: Option<Arc<integrations::reputation::ReputationChecker>>,
+
+    /// Scan the file, its attachments, images and decoded streams with a
+    /// local clamd (only the file itself in sandbox mode)
+    pub clamav: Option<Arc<integrations::clamav::ClamAvScanner>>,
+
+    /// Look up the file and attachment hashes on VirusTotal (sends the
+    /// hashes, and unknown samples if the client submits, to a third party)
+    pub virustotal: Option<Arc<integrations::virustotal::VirusTotalClient>>,
+
+    /// Match the file and its attachments against these known-good
+    /// hashes; findings on matching content are lowered to informational
+    pub known_good: Option<Arc<known_good::KnownGoodSet>>,
+
+    /// Scan text, metadata and attachment names for personal data
+    pub pii: Option<PiiConfig>,
+
+    /// Run these YARA rules over the raw file and decoded streams (not
+    /// available in sandbox mode)
+    #[cfg(feature = "yara")]
+    pub yara: Option<Arc<detectors::yara::YaraRules>>,
+
+    /// Evaluate these user-defined detection rules
+    pub rules: Option<Arc<RuleSet>>,
+
+    /// Run these Rhai analyzer scripts after the built-in detectors (not
+    /// available in sandbox mode)
+    #[cfg(feature = "scripting")]
+    pub scripts: Option<Arc<detectors::scripting::Scripts>>,
+
+    /// Run these WebAssembly analyzer plugins (not available in sandbox
+    /// mode, though plugins are sandboxed themselves)
+    #[cfg(feature = "plugins")]
+    pub plugins: Option<Arc<detectors::plugins::Plugins>>,
+
+    /// Built-in passes to skip and detectors to add (registered detectors
+    /// are not run in sandbox mode)
+    pub detectors: registry::DetectorRegistry,
+
+    /// Weights behind the risk score in the summary
+    pub scoring: ScoringModel,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            diff_revisions: false,
+            simulate_viewers: false,
+            preview: false,
+            budget: None,
+            dump_trailing: None,
+            extract_media: None,
+            revision: None,
+            hash: HashAlgorithm::default(),
+            sandbox: None,
+            limits: limits::ResourceLimits::default(),
+            embedded_depth: DEFAULT_EMBEDDED_DEPTH,
+            reputation: None,
+            clamav: None,
+            virustotal: None,
+            known_good: None,
+            pii: None,
+            #[cfg(feature = "yara")]
+            yara: None,
+            rules: None,
+            #[cfg(feature = "scripting")]
+            scripts: None,
+            #[cfg(feature = "plugins")]
+            plugins: None,
+            detectors: registry::DetectorRegistry::default(),
+            scoring: ScoringModel::default(),
+        }
+    }
+}
+
 #[async_trait]
 pub trait Analyzer {
     async fn analyze(&self) -> Result<PdfAnalysis>;
@@ -59,38 +397,395 @@ pub struct PdfAnalyzer {
     path: String,
     client: reqwest::Client,
     created: DateTime<Utc>,
+    options: AnalysisOptions,
 }
 
 impl PdfAnalyzer {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_options(path, AnalysisOptions::default())
+    }
+
+    pub fn with_options<P: AsRef<Path>>(path: P, options: AnalysisOptions) -> Result<Self> {
         Ok(Self {
             path: path.as_ref().to_string_lossy().into_owned(),
             client: reqwest::Client::new(),
             created: Utc::now(),
+            options,
         })
     }
 }
 
-#[async_trait]
-impl Analyzer for PdfAnalyzer {
-    async fn analyze(&self) -> Result<PdfAnalysis> {
-        info!("Starting analysis of: {}", self.path);
-        
-        let metadata = tokio::fs::metadata(&self.path).await?;
-        let security = self.scan_security().await?;
+impl PdfAnalyzer {
+    /// Detects data after the final %%EOF, dumping it when requested
+    async fn trailing_data(&self, data: &[u8]) -> Result<Option<TrailingData>> {
+        let mut trailing = detectors::trailing::detect(data);
+        self.dump_trailing(data, trailing.as_mut()).await?;
+        Ok(trailing)
+    }
+
+    /// Writes detected trailing data to the requested dump file
+    async fn dump_trailing(&self, data: &[u8], trailing: Option<&mut TrailingData>) -> Result<()> {
+        if let (Some(trailing), Some(path)) = (trailing, &self.options.dump_trailing) {
+            detectors::trailing::dump(data, trailing, path).await?;
+            info!("Wrote {} trailing bytes to {}", trailing.size, path.display());
+        }
+        Ok(())
+    }
+
+    /// Runs the configured YARA rules, recording a failed scan as a warning
+    #[cfg(feature = "yara")]
+    fn yara_matches(&self, data: &[u8], doc: Option<&lopdf::Document>, warnings: &mut Vec<String>) -> Vec<YaraMatch> {
+        let Some(rules) = &self.options.yara else {
+            return Vec::new();
+        };
+        detectors::yara::scan(rules, data, doc).unwrap_or_else(|e| {
+            warnings.push(e.to_string());
+            Vec::new()
+        })
+    }
+
+    #[cfg(not(feature = "yara"))]
+    fn yara_matches(&self, _data: &[u8], _doc: Option<&lopdf::Document>, _warnings: &mut Vec<String>) -> Vec<YaraMatch> {
+        Vec::new()
+    }
+
+    /// Runs the configured analyzer scripts, recording script errors as
+    /// warnings
+    #[cfg(feature = "scripting")]
+    fn run_scripts(&self, doc: Option<&lopdf::Document>, analysis: &mut PdfAnalysis) {
+        let (Some(scripts), Some(doc)) = (&self.options.scripts, doc) else {
+            return;
+        };
+        let (findings, errors) = detectors::scripting::run(scripts, doc, &*analysis);
+        analysis.script_findings = findings;
+        analysis.warnings.extend(errors);
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn run_scripts(&self, _doc: Option<&lopdf::Document>, _analysis: &mut PdfAnalysis) {}
+
+    /// Runs the configured analyzer plugins, recording plugin errors as
+    /// warnings
+    #[cfg(feature = "plugins")]
+    fn run_plugins(&self, doc: Option<&lopdf::Document>, analysis: &mut PdfAnalysis) {
+        let (Some(plugins), Some(doc)) = (&self.options.plugins, doc) else {
+            return;
+        };
+        let (findings, errors) = detectors::plugins::run(plugins, doc);
+        analysis.plugin_findings = findings;
+        analysis.warnings.extend(errors);
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    fn run_plugins(&self, _doc: Option<&lopdf::Document>, _analysis: &mut PdfAnalysis) {}
+
+    /// Attaches reputation verdicts to the URIs when services are configured
+    async fn check_reputation(&self, analysis: &mut PdfAnalysis) {
+        if let Some(checker) = &self.options.reputation {
+            checker.annotate(&mut analysis.uris).await;
+            analysis.phishing.record_reputation(&analysis.uris);
+        }
+    }
+
+    /// Scans the file and its payloads when a clamd socket is configured;
+    /// payloads clamd could not scan become warnings
+    async fn scan_payloads(&self, data: &[u8], doc: Option<&lopdf::Document>, analysis: &mut PdfAnalysis) {
+        if let Some(scanner) = &self.options.clamav {
+            let payloads = integrations::clamav::payloads(data, doc, &self.options.limits);
+            let report = scanner.scan_payloads(payloads).await;
+            analysis
+                .warnings
+                .extend(report.errors.iter().map(|e| format!("ClamAV scan failed: {}", e)));
+            analysis.antivirus = Some(report);
+        }
+    }
+
+    /// Attaches VirusTotal detection ratios when a client is configured
+    async fn check_virustotal(&self, data: &[u8], doc: Option<&lopdf::Document>, analysis: &mut PdfAnalysis) {
+        if let Some(client) = &self.options.virustotal {
+            let (report, warnings) = client.check(integrations::virustotal::samples(data, doc)).await;
+            analysis.virustotal = Some(report);
+            analysis.warnings.extend(warnings);
+        }
+    }
+
+    /// Known-good hash matches of the file and its attachments
+    fn known_good(&self, data: &[u8], doc: Option<&lopdf::Document>) -> Vec<known_good::KnownGoodMatch> {
+        self.options.known_good.as_ref().map(|set| set.check(data, doc)).unwrap_or_default()
+    }
+
+    /// Extracts the listed multimedia payloads to the requested directory
+    async fn extract_media(&self, doc: &lopdf::Document, report: &mut MediaReport) -> Result<()> {
+        if let Some(dir) = &self.options.extract_media {
+            detectors::multimedia::extract(doc, report, dir).await?;
+            info!("Extracted {} multimedia payloads to {}", report.payloads.len(), dir.display());
+        }
+        Ok(())
+    }
+
+    /// Analyzes PDFs embedded in the document, directly or inside ZIP
+    /// attachments, with one level less of recursion left.
+    ///
+    /// Returns the nested analyses and a warning for every embedded PDF
+    /// that could not be analyzed.
+    fn analyze_embedded<'a>(
+        &'a self,
+        doc: &'a lopdf::Document,
+    ) -> BoxFuture<'a, (Vec<EmbeddedAnalysis>, Vec<String>)> {
+        // Boxed because the nested analyses recurse through analyze_data
+        Box::pin(async move {
+            let mut embedded = Vec::new();
+            let mut warnings = Vec::new();
+            if self.options.embedded_depth == 0 {
+                return (embedded, warnings);
+            }
+
+            let mut documents = Vec::new();
+            for file in document::attachments::extract(doc) {
+                if self.options.known_good.as_ref().map_or(false, |set| set.lookup(&file.data).is_some()) {
+                    debug!("Skipping known-good attachment {}", file.name);
+                    continue;
+                }
+                if document::attachments::is_pdf(&file.data) {
+                    documents.push((file.name, file.id, file.data));
+                } else if document::attachments::is_zip(&file.data) {
+                    match document::attachments::unzip(&file.data, &self.options.limits) {
+                        Ok(entries) => documents.extend(
+                            entries
+                                .into_iter()
+                                .filter(|entry| document::attachments::is_pdf(&entry.data))
+                                .map(|entry| (format!("{}/{}", file.name, entry.path), file.id, entry.data)),
+                        ),
+                        Err(e) => warnings.push(format!("Embedded archive {} not unpacked: {}", file.name, e)),
+                    }
+                }
+            }
+
+            let options = AnalysisOptions {
+                embedded_depth: self.options.embedded_depth - 1,
+                dump_trailing: None,
+                extract_media: None,
+                revision: None,
+                sandbox: None,
+                ..self.options.clone()
+            };
+            for (name, object, data) in documents {
+                info!("Analyzing embedded document {} ({} bytes)", name, data.len());
+                let label = format!("{}#{}", self.path, name);
+                let result = match PdfAnalyzer::with_options(&label, options.clone()) {
+                    Ok(analyzer) => analyzer.analyze_bytes(&data).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(analysis) => embedded.push(EmbeddedAnalysis { name, object, analysis }),
+                    Err(e) => warnings.push(format!("Embedded document {} not analyzed: {}", name, e)),
+                }
+            }
+            (embedded, warnings)
+        })
+    }
+
+    /// Re-analyzes a file that grew by incremental updates since `previous`
+    /// was produced, parsing only the appended revisions.
+    ///
+    /// The enabled detectors run as in a full analysis, except the
+    /// [`INCREMENTAL_PASSES`], which cover the appended bytes and are merged
+    /// into the previous results. Falls back to a full analysis when the
+    /// file was rewritten rather than appended to, when the previous
+    /// analysis was degraded by the budget or the grown file no longer fits
+    /// it, when viewer simulation (which needs the whole document) is
+    /// enabled, or in sandbox mode (incremental parsing would run outside
+    /// the sandbox).
+    pub async fn reanalyze_incremental(&self, previous: &PdfAnalysis) -> Result<PdfAnalysis> {
+        limits::scope(self.options.limits, self.reanalyze_appended(previous)).await
+    }
+
+    async fn reanalyze_appended(&self, previous: &PdfAnalysis) -> Result<PdfAnalysis> {
+        let data = tokio::fs::read(&self.path).await?;
+        let start = previous.metadata.size as usize;
+
+        let appended = data.len() > start
+            && previous.metadata.sha256.as_deref() == Some(document::sha256_hex(&data[..start]).as_str());
+        // An update that takes the file over budget gets the degraded full analysis
+        let over_budget = self
+            .options
+            .budget
+            .map_or(false, |budget| budget.plan(&budget::estimate(&data), &self.options).depth != Depth::Full);
+        // A degraded previous analysis has nothing complete to merge into
+        if !appended
+            || over_budget
+            || self.options.simulate_viewers
+            || self.options.revision.is_some()
+            || self.options.sandbox.is_some()
+            || !previous.warnings.is_empty()
+        {
+            info!("Running full analysis of: {}", self.path);
+            return self.analyze().await;
+        }
+
+        let (before, after) = match (read_sections(&data[..start]), read_sections(&data)) {
+            (Ok(before), Ok(after)) => (effective_entries(&before), effective_entries(&after)),
+            _ => {
+                warn!("Unreadable xref chain in {}, running full analysis", self.path);
+                return self.analyze().await;
+            }
+        };
+        info!("Analyzing {} appended bytes of: {}", data.len() - start, self.path);
 
-        Ok(PdfAnalysis {
+        let revision_timeline = match (&previous.revision_timeline, self.options.diff_revisions) {
+            (Some(timeline), true) => Some(document::diff::extend_timeline(&data, timeline)),
+            (None, true) => Some(document::diff::revision_timeline(&data)?),
+            _ => None,
+        };
+
+        // Any update can detach or break references, so the graph passes
+        // always see the whole document
+        let limit_violations = limits::check(&data, &self.options.limits);
+        let mut warnings = Vec::new();
+        let document = match limits::loading_blocker(&limit_violations) {
+            Some(violation) => {
+                warnings.push(format!("Document not loaded: {}", PdxError::from(violation)));
+                None
+            }
+            None => lopdf::Document::load_mem(&data).ok(),
+        };
+        let (embedded, embedded_warnings) = match &document {
+            Some(doc) => self.analyze_embedded(doc).await,
+            None => Default::default(),
+        };
+        warnings.extend(embedded_warnings);
+
+        let yara_matches = self.yara_matches(&data, document.as_ref(), &mut warnings);
+
+        let metadata = tokio::fs::metadata(&self.path).await?;
+        let mut analysis = PdfAnalysis {
+            schema_version: report::schema::SCHEMA_VERSION.to_string(),
             path: self.path.clone(),
             timestamp: Utc::now(),
+            revision: None,
             metadata: PdfMetadata {
-                size: metadata.len(),
+                size: data.len() as u64,
                 created: metadata.created().ok().map(|t| t.into()),
                 modified: metadata.modified().ok().map(|t| t.into()),
-                author: None,
-                title: None,
+                author: previous.metadata.author.clone(),
+                title: previous.metadata.title.clone(),
+                sha256: Some(document::sha256_hex(&data)),
+                digest: FileDigest::compute(self.options.hash, &data),
             },
-            security,
-        })
+            security: self.scan_security().await?,
+            summary: summary::summarize(&data, document.as_ref()),
+            revision_timeline,
+            carved_objects: Default::default(),
+            parser_divergences: None,
+            reader_features: Default::default(),
+            actions: Default::default(),
+            annotations: Default::default(),
+            forms: Default::default(),
+            xfa: Default::default(),
+            media: Default::default(),
+            layers: Default::default(),
+            invisible_text: Default::default(),
+            redaction_failures: Default::default(),
+            fonts: Default::default(),
+            uris: Default::default(),
+            pii: document
+                .as_ref()
+                .zip(self.options.pii.as_ref())
+                .map(|(doc, config)| detectors::pii::scan(doc, config)),
+            secrets: Default::default(),
+            language: Default::default(),
+            phishing: Default::default(),
+            exploits: Default::default(),
+            shellcode: Default::default(),
+            decoder_anomalies: Default::default(),
+            antivirus: None,
+            virustotal: None,
+            known_good: self.known_good(&data, document.as_ref()),
+            yara_matches,
+            rule_matches: document
+                .as_ref()
+                .zip(self.options.rules.as_ref())
+                .map(|(doc, rules)| detectors::custom_rules::evaluate(rules, doc))
+                .unwrap_or_default(),
+            script_findings: Vec::new(),
+            plugin_findings: Vec::new(),
+            detector_findings: Vec::new(),
+            findings: Vec::new(),
+            xref_anomalies: Default::default(),
+            hybrid_xref: Default::default(),
+            signatures: Default::default(),
+            trailer_ids: Default::default(),
+            header: Default::default(),
+            trailing_data: Default::default(),
+            polyglots: Default::default(),
+            slack: Default::default(),
+            object_streams: Default::default(),
+            orphan_objects: Default::default(),
+            page_tree: Default::default(),
+            linearization: Default::default(),
+            dangling_references: Default::default(),
+            stream_lengths: Default::default(),
+            filter_anomalies: Default::default(),
+            obfuscated_tokens: Default::default(),
+            stream_entropy: Default::default(),
+            limit_violations,
+            attachments: Default::default(),
+            embedded,
+            preview: document
+                .as_ref()
+                .filter(|_| self.options.preview)
+                .map(document::preview::build),
+            warnings,
+        };
+        let context = registry::DocumentContext { path: &self.path, data: &data, document: document.as_ref() };
+        let passes = &self.options.detectors;
+        passes.analyze(&context, true, &INCREMENTAL_PASSES, &mut analysis);
+        // The sections the incremental passes fill extend the previous ones
+        if passes.is_enabled("carved_objects") {
+            analysis.carved_objects = previous.carved_objects.clone();
+            analysis.carved_objects.extend(detectors::carving::carve_appended(&data, start, &before, &after));
+        }
+        if passes.is_enabled("reader_features") {
+            let appended_objects: BTreeMap<_, _> = scan_objects(&data[start..])
+                .into_iter()
+                .filter_map(|raw| {
+                    let parsed = parse_indirect(&data, raw.offset + start).ok()?;
+                    Some((parsed.id, parsed.object))
+                })
+                .collect();
+            analysis.reader_features = previous.reader_features.clone();
+            analysis.reader_features.merge_objects(&appended_objects);
+        }
+        if passes.is_enabled("signatures") {
+            analysis.signatures = detectors::signatures::check_appended(&data, start, &previous.signatures);
+        }
+        if passes.is_enabled("header") {
+            analysis.header = previous.header.clone();
+        }
+        // The passes only detect; writing their payloads out is done here
+        self.dump_trailing(&data, analysis.trailing_data.as_mut()).await?;
+        if let Some(doc) = &document {
+            self.extract_media(doc, &mut analysis.media).await?;
+        }
+
+        self.check_reputation(&mut analysis).await;
+        self.scan_payloads(&data, document.as_ref(), &mut analysis).await;
+        self.check_virustotal(&data, document.as_ref(), &mut analysis).await;
+        self.run_scripts(document.as_ref(), &mut analysis);
+        self.run_plugins(document.as_ref(), &mut analysis);
+        analysis.collect_findings(&self.options.scoring);
+        Ok(analysis)
+    }
+}
+
+#[async_trait]
+impl Analyzer for PdfAnalyzer {
+    async fn analyze(&self) -> Result<PdfAnalysis> {
+        info!("Starting analysis of: {}", self.path);
+        
+        let metadata = tokio::fs::metadata(&self.path).await?;
+        let data = tokio::fs::read(&self.path).await?;
+        limits::scope(self.options.limits, self.analyze_data(&data, Some(&metadata))).await
     }
 
     async fn scan_security(&self) -> Result<SecurityInfo> {
@@ -104,6 +799,225 @@ impl Analyzer for PdfAnalyzer {
     }
 }
 
+impl PdfAnalyzer {
+    /// Analyzes an in-memory document, e.g. an upload or mail attachment.
+    /// The analyzer's path is only used as a label in the report.
+    pub async fn analyze_bytes(&self, data: &[u8]) -> Result<PdfAnalysis> {
+        info!("Starting analysis of {} bytes: {}", data.len(), self.path);
+        limits::scope(self.options.limits, self.analyze_data(data, None)).await
+    }
+
+    async fn analyze_data(&self, data: &[u8], file: Option<&std::fs::Metadata>) -> Result<PdfAnalysis> {
+        if let Some(config) = &self.options.sandbox {
+            let mut analysis = sandbox::analyze(&self.path, data, &self.options, config).await?;
+            analysis.metadata.created = file.and_then(|m| m.created().ok()).map(|t| t.into());
+            analysis.metadata.modified = file.and_then(|m| m.modified().ok()).map(|t| t.into());
+            // The worker cannot write files, so trailing data is dumped here
+            if self.options.dump_trailing.is_some() {
+                analysis.trailing_data = self.trailing_data(data).await?;
+            }
+            #[cfg(feature = "yara")]
+            if self.options.yara.is_some() {
+                analysis.warnings.push("YARA rules are not run in sandbox mode".to_string());
+            }
+            #[cfg(feature = "scripting")]
+            if self.options.scripts.is_some() {
+                analysis.warnings.push("Analyzer scripts are not run in sandbox mode".to_string());
+            }
+            #[cfg(feature = "plugins")]
+            if self.options.plugins.is_some() {
+                analysis.warnings.push("Analyzer plugins are not run in sandbox mode".to_string());
+            }
+            if self.options.detectors.registered().next().is_some() {
+                analysis.warnings.push("Registered detectors are not run in sandbox mode".to_string());
+            }
+            if self.options.preview {
+                analysis.warnings.push("The object tree and thumbnails are not captured in sandbox mode".to_string());
+            }
+            if self.options.extract_media.is_some() {
+                analysis.warnings.push("Multimedia payloads are not extracted in sandbox mode".to_string());
+            }
+            // Nor can it reach the network
+            let reputation = self.options.reputation.is_some();
+            let payloads =
+                self.options.clamav.is_some() || self.options.virustotal.is_some() || self.options.known_good.is_some();
+            if reputation {
+                self.check_reputation(&mut analysis).await;
+            }
+            if payloads {
+                analysis.known_good = self.known_good(data, None);
+                self.scan_payloads(data, None, &mut analysis).await;
+                self.check_virustotal(data, None, &mut analysis).await;
+            }
+            // The worker scored with the default model
+            analysis.collect_findings(&self.options.scoring);
+            return Ok(analysis);
+        }
+
+        let security = self.scan_security().await?;
+
+        // A pinned revision is analyzed exactly as the bytes stood back then
+        let data = match self.options.revision {
+            Some(index) => {
+                let revisions = document::revisions::split_revisions(data);
+                let revision = revisions.get(index).ok_or_else(|| {
+                    PdxError::Analysis(format!(
+                        "Revision {} not found: {} has {} revisions",
+                        index,
+                        self.path,
+                        revisions.len()
+                    ))
+                })?;
+                info!("Analyzing revision {} ({} bytes) of: {}", index, revision.end, self.path);
+                &data[..revision.end]
+            }
+            None => data,
+        };
+
+        let mut plan = match &self.options.budget {
+            Some(budget) => budget.plan(&budget::estimate(data), &self.options),
+            None => Plan::full(&self.options),
+        };
+        let limit_violations = limits::check(data, &self.options.limits);
+        if let Some(violation) = limits::loading_blocker(&limit_violations) {
+            plan.warnings.push(format!("Document not loaded: {}", PdxError::from(violation)));
+        }
+        for warning in &plan.warnings {
+            warn!("{}: {}", self.path, warning);
+        }
+        let object_passes = plan.depth > Depth::Minimal;
+        let loadable = object_passes && limits::loading_blocker(&limit_violations).is_none();
+
+        let document = match loadable.then(|| lopdf::Document::load_mem(data)) {
+            Some(Ok(doc)) => Some(doc),
+            Some(Err(e)) => {
+                warn!("Failed to parse {}: {}", self.path, e);
+                None
+            }
+            None => None,
+        };
+
+        let revision_timeline = if plan.options.diff_revisions {
+            Some(document::diff::revision_timeline(data)?)
+        } else {
+            None
+        };
+
+        let yara_matches = self.yara_matches(data, document.as_ref(), &mut plan.warnings);
+        let rule_matches = document
+            .as_ref()
+            .zip(plan.options.rules.as_ref())
+            .map(|(doc, rules)| detectors::custom_rules::evaluate(rules, doc))
+            .unwrap_or_default();
+        let pii = document
+            .as_ref()
+            .zip(plan.options.pii.as_ref())
+            .map(|(doc, config)| detectors::pii::scan(doc, config));
+
+        let embedded = match &document {
+            Some(doc) => {
+                let (embedded, warnings) = self.analyze_embedded(doc).await;
+                plan.warnings.extend(warnings);
+                embedded
+            }
+            None => Vec::new(),
+        };
+
+        let parser_divergences = match (&document, plan.options.simulate_viewers) {
+            (Some(doc), true) => Some(detectors::differential::simulate_viewers(data, doc)),
+            _ => None,
+        };
+
+        let mut analysis = PdfAnalysis {
+            schema_version: report::schema::SCHEMA_VERSION.to_string(),
+            path: self.path.clone(),
+            timestamp: Utc::now(),
+            revision: self.options.revision,
+            metadata: PdfMetadata {
+                size: data.len() as u64,
+                created: file.and_then(|m| m.created().ok()).map(|t| t.into()),
+                modified: file.and_then(|m| m.modified().ok()).map(|t| t.into()),
+                author: None,
+                title: None,
+                sha256: Some(document::sha256_hex(data)),
+                digest: FileDigest::compute(self.options.hash, data),
+            },
+            security,
+            summary: summary::summarize(data, document.as_ref()),
+            revision_timeline,
+            carved_objects: Default::default(),
+            parser_divergences,
+            reader_features: Default::default(),
+            actions: Default::default(),
+            annotations: Default::default(),
+            forms: Default::default(),
+            xfa: Default::default(),
+            media: Default::default(),
+            layers: Default::default(),
+            invisible_text: Default::default(),
+            redaction_failures: Default::default(),
+            fonts: Default::default(),
+            uris: Default::default(),
+            pii,
+            secrets: Default::default(),
+            language: Default::default(),
+            phishing: Default::default(),
+            exploits: Default::default(),
+            shellcode: Default::default(),
+            decoder_anomalies: Default::default(),
+            antivirus: None,
+            virustotal: None,
+            known_good: self.known_good(&data, document.as_ref()),
+            yara_matches,
+            rule_matches,
+            script_findings: Vec::new(),
+            plugin_findings: Vec::new(),
+            detector_findings: Vec::new(),
+            findings: Vec::new(),
+            xref_anomalies: Default::default(),
+            hybrid_xref: Default::default(),
+            signatures: Default::default(),
+            trailer_ids: Default::default(),
+            header: Default::default(),
+            trailing_data: Default::default(),
+            polyglots: Default::default(),
+            slack: Default::default(),
+            object_streams: Default::default(),
+            orphan_objects: Default::default(),
+            page_tree: Default::default(),
+            linearization: Default::default(),
+            dangling_references: Default::default(),
+            stream_lengths: Default::default(),
+            filter_anomalies: Default::default(),
+            obfuscated_tokens: Default::default(),
+            stream_entropy: Default::default(),
+            limit_violations,
+            attachments: Default::default(),
+            embedded,
+            preview: document
+                .as_ref()
+                .filter(|_| self.options.preview)
+                .map(document::preview::build),
+            warnings: plan.warnings,
+        };
+        let context = registry::DocumentContext { path: &self.path, data, document: document.as_ref() };
+        plan.options.detectors.analyze(&context, object_passes, &[], &mut analysis);
+        // The passes only detect; writing their payloads out is done here
+        self.dump_trailing(data, analysis.trailing_data.as_mut()).await?;
+        if let Some(doc) = &document {
+            self.extract_media(doc, &mut analysis.media).await?;
+        }
+
+        self.check_reputation(&mut analysis).await;
+        self.scan_payloads(data, document.as_ref(), &mut analysis).await;
+        self.check_virustotal(data, document.as_ref(), &mut analysis).await;
+        self.run_scripts(document.as_ref(), &mut analysis);
+        self.run_plugins(document.as_ref(), &mut analysis);
+        analysis.collect_findings(&self.options.scoring);
+        Ok(analysis)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,4 +1030,137 @@ mod tests {
         let analysis = analyzer.analyze().await.unwrap();
         assert_eq!(analysis.path, temp.path().to_string_lossy());
     }
+
+    #[tokio::test]
+    async fn test_revision_diff_option() {
+        let temp = NamedTempFile::new().unwrap();
+        let base = document::fixtures::simple_pdf();
+        let data = document::fixtures::append_revision(&base, &[(7, "<< /Type /Annot >>")]);
+        std::fs::write(temp.path(), data).unwrap();
+
+        let options = AnalysisOptions { diff_revisions: true, ..Default::default() };
+        let analyzer = PdfAnalyzer::with_options(temp.path(), options).unwrap();
+        let analysis = analyzer.analyze().await.unwrap();
+
+        let timeline = analysis.revision_timeline.unwrap();
+        assert_eq!(timeline.revision_count, 2);
+        assert_eq!(timeline.steps.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_revision() {
+        let temp = NamedTempFile::new().unwrap();
+        let base = document::fixtures::simple_pdf();
+        let data = document::fixtures::append_revision(&base, &[(7, "<< /S /JavaScript /JS (app.alert(1)) >>")]);
+        std::fs::write(temp.path(), &data).unwrap();
+
+        let options = AnalysisOptions { revision: Some(0), ..Default::default() };
+        let analysis = PdfAnalyzer::with_options(temp.path(), options).unwrap().analyze().await.unwrap();
+        let original = &document::revisions::split_revisions(&data)[0];
+        assert_eq!(analysis.revision, Some(0));
+        assert_eq!(analysis.metadata.size, original.end as u64);
+        assert!(analysis.reader_features.features.is_empty());
+
+        let options = AnalysisOptions { revision: Some(5), ..Default::default() };
+        let analyzer = PdfAnalyzer::with_options(temp.path(), options).unwrap();
+        assert!(analyzer.analyze().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reanalyze_incremental() {
+        let temp = NamedTempFile::new().unwrap();
+        let base = document::fixtures::simple_pdf();
+        std::fs::write(temp.path(), &base).unwrap();
+
+        let options = AnalysisOptions { diff_revisions: true, ..Default::default() };
+        let analyzer = PdfAnalyzer::with_options(temp.path(), options).unwrap();
+        let previous = analyzer.analyze().await.unwrap();
+
+        let data = document::fixtures::append_revision(
+            &base,
+            &[
+                (4, "<< /Length 3 >>\nstream\nnew\nendstream"),
+                (7, "<< /S /JavaScript /JS (app.alert(1)) >>"),
+            ],
+        );
+        std::fs::write(temp.path(), &data).unwrap();
+
+        let incremental = analyzer.reanalyze_incremental(&previous).await.unwrap();
+        let full = analyzer.analyze().await.unwrap();
+
+        assert_eq!(incremental.metadata.sha256, full.metadata.sha256);
+        assert_eq!(incremental.carved_objects.len(), full.carved_objects.len());
+        assert_eq!(
+            incremental.reader_features.features.len(),
+            full.reader_features.features.len()
+        );
+        let timeline = incremental.revision_timeline.unwrap();
+        assert_eq!(timeline.steps.len(), 1);
+
+        let registry = registry::DetectorRegistry::default();
+        assert!(INCREMENTAL_PASSES.iter().all(|pass| registry.names().any(|name| name == *pass)));
+        let mut detectors = registry;
+        detectors.disable("reader_features").unwrap();
+        let options = AnalysisOptions { detectors, ..Default::default() };
+        let analyzer = PdfAnalyzer::with_options(temp.path(), options).unwrap();
+        let partial = analyzer.reanalyze_incremental(&previous).await.unwrap();
+        assert!(partial.reader_features.features.is_empty());
+        assert!(partial.warnings.is_empty());
+
+        let options = AnalysisOptions { budget: Some(AnalysisBudget::new(1)), ..Default::default() };
+        let analyzer = PdfAnalyzer::with_options(temp.path(), options).unwrap();
+        let degraded = analyzer.reanalyze_incremental(&previous).await.unwrap();
+        assert!(degraded.warnings.iter().any(|w| w.starts_with("Analysis degraded")));
+    }
+
+    #[tokio::test]
+    async fn test_embedded_documents_are_analyzed() {
+        let inner = document::fixtures::append_revision(
+            &document::fixtures::simple_pdf(),
+            &[(7, "<< /S /JavaScript /JS (app.alert(1)) >>")],
+        );
+        let mut doc = document::fixtures::document();
+        let file = doc.add_object(lopdf::Stream::new(lopdf::dictionary! { "Type" => "EmbeddedFile" }, inner));
+        doc.add_object(lopdf::dictionary! {
+            "Type" => "Filespec",
+            "UF" => lopdf::Object::string_literal("dropper.pdf"),
+            "EF" => lopdf::dictionary! { "F" => file },
+        });
+        let mut data = Vec::new();
+        doc.save_to(&mut data).unwrap();
+
+        let options = AnalysisOptions { embedded_depth: 1, ..Default::default() };
+        let analysis = PdfAnalyzer::with_options("outer.pdf", options).unwrap().analyze_bytes(&data).await.unwrap();
+        assert_eq!(analysis.embedded.len(), 1);
+        assert_eq!(analysis.embedded[0].name, "dropper.pdf");
+        assert_eq!(analysis.embedded[0].analysis.path, "outer.pdf#dropper.pdf");
+        assert!(!analysis.embedded[0].analysis.reader_features.features.is_empty());
+
+        let default = PdfAnalyzer::new("outer.pdf").unwrap().analyze_bytes(&data).await.unwrap();
+        assert_eq!(default.embedded.len(), 1);
+
+        let options = AnalysisOptions { embedded_depth: 0, ..Default::default() };
+        let flat = PdfAnalyzer::with_options("outer.pdf", options).unwrap().analyze_bytes(&data).await.unwrap();
+        assert!(flat.embedded.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_budget_degrades_analysis() {
+        let temp = NamedTempFile::new().unwrap();
+        let base = document::fixtures::simple_pdf();
+        let data = document::fixtures::append_revision(&base, &[(4, "<< /Length 0 >>\nstream\n\nendstream")]);
+        std::fs::write(temp.path(), data).unwrap();
+
+        let options = AnalysisOptions {
+            diff_revisions: true,
+            budget: Some(AnalysisBudget::new(0)),
+            ..Default::default()
+        };
+        let analyzer = PdfAnalyzer::with_options(temp.path(), options).unwrap();
+        let analysis = analyzer.analyze().await.unwrap();
+
+        assert!(analysis.revision_timeline.is_none());
+        assert!(analysis.carved_objects.is_empty());
+        assert!(analysis.warnings[0].contains("Minimal"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,578 @@
+//! Resource limits against decompression bombs and parser exhaustion
+//! Created: 2026-10-16 09:22:43 UTC
+//! Author: kartik4091
+//!
+//! A few hundred bytes of nested Flate data can inflate to gigabytes, and
+//! a few kilobytes of brackets can exhaust the stack of a recursive
+//! parser. Before the document is loaded, every object is checked against
+//! configurable limits: streams are inflated with a hard cap instead of
+//! being decoded blindly, and objects are measured for nesting depth.
+//! Violations are reported as findings, and a document whose loading
+//! would itself be unsafe is not loaded at all.
+//!
+//! The limits of the analysis in progress are kept for the running task
+//! (see [`scope`]), so that every stream decoded on its behalf, however
+//! deep in a pass, is held to the configured values.
+
+use std::{future::Future, io::Read};
+
+use flate2::read::ZlibDecoder;
+use lopdf::{Dictionary, Object, ObjectId, Stream};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    detectors::Severity,
+    document::{
+        name_value,
+        objects::scan_objects,
+        parser::{is_whitespace, parse_indirect, MAX_NESTING},
+    },
+    error::{Error, Result},
+};
+
+/// Default cap on the decoded size of a single stream (256 MiB)
+pub const DEFAULT_MAX_DECODED_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Default cap on decoded-to-stored size. A single Flate stage cannot
+/// exceed about 1032:1, so a higher ratio means nested compression.
+pub const DEFAULT_MAX_EXPANSION_RATIO: u64 = 1500;
+
+/// Default cap on the number of indirect objects
+pub const DEFAULT_MAX_OBJECTS: usize = 1_000_000;
+
+/// Default cap on array and dictionary nesting
+pub const DEFAULT_MAX_DEPTH: usize = MAX_NESTING;
+
+/// Limits applied before and while decoding a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Largest decoded stream, in bytes
+    pub max_decoded_size: u64,
+
+    /// Largest decoded-to-stored size ratio of a stream
+    pub max_expansion_ratio: u64,
+
+    /// Most indirect objects in the file
+    pub max_objects: usize,
+
+    /// Deepest nesting of arrays and dictionaries in an object
+    pub max_depth: usize,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_decoded_size: DEFAULT_MAX_DECODED_SIZE,
+            max_expansion_ratio: DEFAULT_MAX_EXPANSION_RATIO,
+            max_objects: DEFAULT_MAX_OBJECTS,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
+/// Limit that was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum LimitKind {
+    /// A stream decodes to more than the size limit
+    DecodedSize,
+
+    /// A stream expands by more than the ratio limit
+    ExpansionRatio,
+
+    /// The file holds more objects than the object limit
+    ObjectCount,
+
+    /// An object nests deeper than the depth limit
+    NestingDepth,
+}
+
+/// A resource limit exceeded by the document
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LimitViolation {
+    /// Limit that was exceeded
+    pub kind: LimitKind,
+
+    /// Offending object, `None` for file-wide limits
+    pub id: Option<ObjectId>,
+
+    /// Configured limit
+    pub limit: u64,
+
+    /// Measured value; for streams, the point at which decoding stopped
+    pub actual: u64,
+
+    /// Whether loading the document was skipped because of the violation
+    pub prevents_loading: bool,
+
+    /// Severity
+    pub severity: Severity,
+
+    /// Human-readable description
+    pub description: String,
+}
+
+/// Stream limit hit while inflating
+struct Exceeded {
+    kind: LimitKind,
+    limit: u64,
+    actual: u64,
+}
+
+/// Why a stream was not decoded
+enum Failure {
+    /// A stage would decode beyond the limits
+    Exceeded(Exceeded),
+
+    /// The data is corrupt or a filter cannot be decoded within the limits
+    Undecodable(String),
+}
+
+impl From<Exceeded> for Failure {
+    fn from(exceeded: Exceeded) -> Self {
+        Failure::Exceeded(exceeded)
+    }
+}
+
+tokio::task_local! {
+    /// Limits of the analysis running on the current task
+    static ACTIVE: ResourceLimits;
+}
+
+/// Runs `work` with `limits` in force for every stream it decodes
+pub async fn scope<F: Future>(limits: ResourceLimits, work: F) -> F::Output {
+    ACTIVE.scope(limits, work).await
+}
+
+/// Limits in force on the current task: those of the enclosing [`scope`],
+/// or the defaults outside of one
+pub fn active() -> ResourceLimits {
+    ACTIVE.try_with(|limits| *limits).unwrap_or_default()
+}
+
+/// Checks every object of the file against the limits, without loading it
+#[instrument(skip(data))]
+pub fn check(data: &[u8], limits: &ResourceLimits) -> Vec<LimitViolation> {
+    let objects = scan_objects(data);
+    if objects.len() > limits.max_objects {
+        warn!("{} objects exceed the limit of {}", objects.len(), limits.max_objects);
+        return vec![LimitViolation {
+            kind: LimitKind::ObjectCount,
+            id: None,
+            limit: limits.max_objects as u64,
+            actual: objects.len() as u64,
+            prevents_loading: true,
+            severity: Severity::Medium,
+            description: format!(
+                "File has {} objects, more than the limit of {}",
+                objects.len(),
+                limits.max_objects
+            ),
+        }];
+    }
+
+    let mut violations = Vec::new();
+    for raw in &objects {
+        let depth = nesting_depth(&data[raw.offset..raw.end]);
+        if depth > limits.max_depth {
+            violations.push(LimitViolation {
+                kind: LimitKind::NestingDepth,
+                id: Some(raw.id),
+                limit: limits.max_depth as u64,
+                actual: depth as u64,
+                prevents_loading: true,
+                severity: Severity::High,
+                description: format!(
+                    "Object {} {} nests {} levels deep, more than the limit of {}",
+                    raw.id.0, raw.id.1, depth, limits.max_depth
+                ),
+            });
+            continue;
+        }
+
+        let Ok(parsed) = parse_indirect(data, raw.offset) else {
+            continue;
+        };
+        let Object::Stream(stream) = &parsed.object else {
+            continue;
+        };
+        // Corrupt data is not a limit violation; decoding reports it later
+        if let Err(Failure::Exceeded(exceeded)) = decode_stages(stream, limits) {
+            // Object streams are decoded while the document is loaded
+            let object_stream = name_value(&stream.dict, b"Type") == Some(b"ObjStm");
+            violations.push(stream_violation(parsed.id, stream, exceeded, object_stream));
+        }
+    }
+
+    debug!("Found {} resource limit violations", violations.len());
+    violations
+}
+
+/// The violation that makes loading the document unsafe, if any
+pub fn loading_blocker(violations: &[LimitViolation]) -> Option<&LimitViolation> {
+    violations.iter().find(|v| v.prevents_loading)
+}
+
+/// Decodes a stream, refusing to inflate it beyond the limits.
+///
+/// Every stage of the filter chain is held to the limits: Flate and LZW
+/// data are inflated with a hard cap, and the ASCII and run-length stages
+/// are measured before they are decoded. Chains with a filter whose output
+/// cannot be bounded this way (image codecs, encryption) are not decoded.
+pub fn decode(stream: &Stream, limits: &ResourceLimits) -> Result<Vec<u8>> {
+    decode_stages(stream, limits).map_err(|failure| match failure {
+        Failure::Exceeded(exceeded) => Error::Security(describe(&exceeded)),
+        Failure::Undecodable(reason) => Error::Pdf(format!("Failed to decode stream: {}", reason)),
+    })
+}
+
+fn decode_stages(stream: &Stream, limits: &ResourceLimits) -> std::result::Result<Vec<u8>, Failure> {
+    let stored = stream.content.len() as u64;
+    let ratio_cap = stored.max(1).saturating_mul(limits.max_expansion_ratio);
+    let cap = limits.max_decoded_size.min(ratio_cap);
+    let exceeded = |actual: u64| {
+        if cap == limits.max_decoded_size {
+            Exceeded {
+                kind: LimitKind::DecodedSize,
+                limit: cap,
+                actual,
+            }
+        } else {
+            Exceeded {
+                kind: LimitKind::ExpansionRatio,
+                limit: limits.max_expansion_ratio,
+                actual: actual / stored.max(1),
+            }
+        }
+    };
+
+    let mut data = stream.content.clone();
+    for (index, filter) in filters(&stream.dict).iter().enumerate() {
+        let params = decode_params(&stream.dict, index);
+        let decoded = match filter.as_slice() {
+            b"FlateDecode" | b"Fl" => capped(ZlibDecoder::new(data.as_slice()), cap)?,
+            b"LZWDecode" | b"LZW" => unlzw(&data, early_change(params), cap)?,
+            b"ASCIIHexDecode" | b"AHx" | b"ASCII85Decode" | b"A85" | b"RunLengthDecode" | b"RL" => {
+                let size = decoded_size(filter, &data);
+                if size > cap {
+                    return Err(exceeded(size).into());
+                }
+                single_stage(filter, params, &data)?
+            }
+            other => {
+                return Err(Failure::Undecodable(format!(
+                    "{} cannot be decoded within the resource limits",
+                    String::from_utf8_lossy(other)
+                )))
+            }
+        };
+        if decoded.len() as u64 > cap {
+            return Err(exceeded(decoded.len() as u64).into());
+        }
+        // Predictors never lengthen the data, so the stage stays within the cap
+        data = if has_predictor(params) {
+            single_stage(filter, params, &data)?
+        } else {
+            decoded
+        };
+    }
+
+    Ok(data)
+}
+
+/// Reads at most one byte more than `cap` from a decoder. Truncated or
+/// damaged data decodes as far as it goes, as readers do.
+fn capped(decoder: impl Read, cap: u64) -> std::result::Result<Vec<u8>, Failure> {
+    let mut out = Vec::new();
+    if let Err(e) = decoder.take(cap.saturating_add(1)).read_to_end(&mut out) {
+        if out.is_empty() {
+            return Err(Failure::Undecodable(e.to_string()));
+        }
+    }
+    Ok(out)
+}
+
+/// LZW-decodes at most one buffer more than `cap`
+fn unlzw(data: &[u8], early_change: bool, cap: u64) -> std::result::Result<Vec<u8>, Failure> {
+    let mut decoder = if early_change {
+        weezl::decode::Decoder::with_tiff_size_switch(weezl::BitOrder::Msb, 8)
+    } else {
+        weezl::decode::Decoder::new(weezl::BitOrder::Msb, 8)
+    };
+    let (mut out, mut buffer, mut input) = (Vec::new(), [0u8; 8192], data);
+    while out.len() as u64 <= cap {
+        let result = decoder.decode_bytes(input, &mut buffer);
+        input = &input[result.consumed_in..];
+        out.extend_from_slice(&buffer[..result.consumed_out]);
+        match result.status {
+            Ok(weezl::LzwStatus::Ok) => {}
+            Ok(weezl::LzwStatus::NoProgress | weezl::LzwStatus::Done) => break,
+            Err(e) => return Err(Failure::Undecodable(e.to_string())),
+        }
+    }
+    Ok(out)
+}
+
+/// Decoded size of an ASCII or run-length stage, computed without decoding
+fn decoded_size(filter: &[u8], data: &[u8]) -> u64 {
+    let data = data.strip_prefix(b"<~").unwrap_or(data);
+    let significant = data
+        .iter()
+        .take_while(|&&b| b != b'>' && b != b'~')
+        .filter(|b| !is_whitespace(**b));
+    match filter {
+        b"ASCIIHexDecode" | b"AHx" => (significant.count() as u64 + 1) / 2,
+        b"ASCII85Decode" | b"A85" => significant.map(|&b| if b == b'z' { 5 } else { 1 }).sum::<u64>() * 4 / 5,
+        _ => {
+            let (mut size, mut i) = (0u64, 0);
+            while let Some(&length) = data.get(i) {
+                match length {
+                    0..=127 => {
+                        size += u64::from(length) + 1;
+                        i += usize::from(length) + 2;
+                    }
+                    128 => break,
+                    _ => {
+                        size += 257 - u64::from(length);
+                        i += 2;
+                    }
+                }
+            }
+            size
+        }
+    }
+}
+
+/// Decodes one stage with lopdf, for stages already known to stay within the limits
+fn single_stage(filter: &[u8], params: Option<&Object>, data: &[u8]) -> std::result::Result<Vec<u8>, Failure> {
+    let mut dict = Dictionary::new();
+    dict.set("Filter", Object::Name(filter.to_vec()));
+    if let Some(params) = params {
+        dict.set("DecodeParms", params.clone());
+    }
+    Stream::new(dict, data.to_vec())
+        .decompressed_content()
+        .map_err(|e| Failure::Undecodable(e.to_string()))
+}
+
+fn stream_violation(id: ObjectId, stream: &Stream, exceeded: Exceeded, object_stream: bool) -> LimitViolation {
+    let container = if object_stream { "Object stream" } else { "Stream" };
+    LimitViolation {
+        kind: exceeded.kind,
+        id: Some(id),
+        limit: exceeded.limit,
+        actual: exceeded.actual,
+        prevents_loading: object_stream,
+        severity: Severity::High,
+        description: format!(
+            "{} {} {} ({} bytes stored): {}",
+            container,
+            id.0,
+            id.1,
+            stream.content.len(),
+            describe(&exceeded)
+        ),
+    }
+}
+
+fn describe(exceeded: &Exceeded) -> String {
+    match exceeded.kind {
+        LimitKind::DecodedSize => {
+            format!(
+                "decodes to more than the limit of {} bytes, likely a decompression bomb",
+                exceeded.limit
+            )
+        }
+        _ => format!(
+            "expands more than {}:1 (limit {}:1), likely a decompression bomb",
+            exceeded.actual, exceeded.limit
+        ),
+    }
+}
+
+/// Filter chain of a stream dictionary, outermost first
+pub(crate) fn filters(dict: &Dictionary) -> Vec<Vec<u8>> {
+    match dict.get(b"Filter") {
+        Ok(Object::Name(name)) => vec![name.clone()],
+        Ok(Object::Array(items)) => items
+            .iter()
+            .filter_map(|f| f.as_name().ok())
+            .map(<[u8]>::to_vec)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Decode parameters of stage `index` of the filter chain
+fn decode_params(dict: &Dictionary, index: usize) -> Option<&Object> {
+    match dict.get(b"DecodeParms") {
+        Ok(Object::Array(items)) => items.get(index),
+        Ok(params) if index == 0 => Some(params),
+        _ => None,
+    }
+    .filter(|params| params.as_dict().is_ok())
+}
+
+fn integer_param(params: Option<&Object>, key: &[u8]) -> Option<i64> {
+    params?.as_dict().ok()?.get(key).ok()?.as_i64().ok()
+}
+
+/// Whether the decode parameters of a stage apply a predictor
+fn has_predictor(params: Option<&Object>) -> bool {
+    integer_param(params, b"Predictor").map_or(false, |predictor| predictor > 1)
+}
+
+/// Whether LZW code widths grow one code early, as they do by default
+fn early_change(params: Option<&Object>) -> bool {
+    integer_param(params, b"EarlyChange") != Some(0)
+}
+
+/// Deepest array and dictionary nesting in an object's bytes, up to its
+/// stream data
+fn nesting_depth(bytes: &[u8]) -> usize {
+    let (mut depth, mut deepest, mut i) = (0usize, 0usize, 0);
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                while i < bytes.len() && !matches!(bytes[i], b'\r' | b'\n') {
+                    i += 1;
+                }
+            }
+            b'(' => {
+                let mut open = 0;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'\\' => i += 1,
+                        b'(' => open += 1,
+                        b')' => {
+                            open -= 1;
+                            if open == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+            }
+            b'<' if bytes.get(i + 1) == Some(&b'<') => {
+                depth += 1;
+                i += 1;
+            }
+            b'<' => {
+                while i < bytes.len() && bytes[i] != b'>' {
+                    i += 1;
+                }
+            }
+            b'>' if bytes.get(i + 1) == Some(&b'>') => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            b'[' => depth += 1,
+            b']' => depth = depth.saturating_sub(1),
+            b's' if bytes[i..].starts_with(b"stream")
+                && (i == 0 || is_whitespace(bytes[i - 1]) || bytes[i - 1] == b'>') =>
+            {
+                break;
+            }
+            _ => {}
+        }
+        deepest = deepest.max(depth);
+        i += 1;
+    }
+
+    deepest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use flate2::{write::ZlibEncoder, Compression};
+    use lopdf::dictionary;
+    use std::io::Write;
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_clean_document_is_within_limits() {
+        assert!(check(&fixtures::simple_pdf(), &ResourceLimits::default()).is_empty());
+    }
+
+    #[test]
+    fn test_nested_flate_bomb_is_refused() {
+        let bomb = deflate(&deflate(&vec![0u8; 8 * 1024 * 1024]));
+        let stream = Stream::new(
+            dictionary! { "Filter" => vec![Object::Name(b"FlateDecode".to_vec()), Object::Name(b"FlateDecode".to_vec())] },
+            bomb,
+        );
+
+        let limits = ResourceLimits {
+            max_decoded_size: 1024 * 1024,
+            ..Default::default()
+        };
+        let err = decode(&stream, &limits).unwrap_err();
+        assert!(matches!(err, Error::Security(_)));
+
+        let relaxed = ResourceLimits {
+            max_expansion_ratio: u64::MAX,
+            max_decoded_size: 16 * 1024 * 1024,
+            ..Default::default()
+        };
+        assert_eq!(decode(&stream, &relaxed).unwrap().len(), 8 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_every_stage_is_capped() {
+        // Chains not made of Flate stages alone used to skip the guard
+        let mut bomb = Vec::new();
+        for _ in 0..1024 {
+            bomb.extend_from_slice(&[129, b'A']);
+        }
+        bomb.push(128);
+        let stream = Stream::new(dictionary! { "Filter" => "RunLengthDecode" }, bomb);
+        let limits = ResourceLimits {
+            max_decoded_size: 64 * 1024,
+            ..Default::default()
+        };
+        assert!(matches!(decode(&stream, &limits), Err(Error::Security(_))));
+
+        let stream = Stream::new(dictionary! { "Filter" => "DCTDecode" }, vec![0xff, 0xd8]);
+        assert!(matches!(decode(&stream, &limits), Err(Error::Pdf(_))));
+    }
+
+    #[tokio::test]
+    async fn test_scoped_limits() {
+        assert_eq!(active(), ResourceLimits::default());
+        let strict = ResourceLimits {
+            max_decoded_size: 10,
+            ..Default::default()
+        };
+        assert_eq!(scope(strict, async { active() }).await, strict);
+    }
+
+    #[test]
+    fn test_deep_nesting_prevents_loading() {
+        let base = fixtures::simple_pdf();
+        let body = format!("{}1{}", "[".repeat(500), "]".repeat(500));
+        let data = fixtures::append_revision(&base, &[(7, body.as_str())]);
+
+        let violations = check(&data, &ResourceLimits::default());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, LimitKind::NestingDepth);
+        assert_eq!(violations[0].actual, 500);
+        assert!(loading_blocker(&violations).is_some());
+
+        let few = ResourceLimits {
+            max_objects: 3,
+            ..Default::default()
+        };
+        assert_eq!(check(&data, &few)[0].kind, LimitKind::ObjectCount);
+    }
+}
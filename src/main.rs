@@ -1,54 +1,1370 @@
-use std::path::PathBuf;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use anyhow::Result;
-use clap::Parser;
-use tracing::{info, error};
+use tokio::io::AsyncReadExt;
+use clap::{Parser, Subcommand};
+use tracing::{info, error, warn};
 use tracing_subscriber::FmtSubscriber;
 
+use pdx::{
+    batch::{self, FileSelection, InputConfig, Selection},
+    budget::AnalysisBudget,
+    detectors::{
+        custom_rules::RuleSet,
+        pii::{PiiConfig, PiiKind},
+        Severity,
+    },
+    document::{
+        artifacts::{self, ArtifactClass},
+        chronology,
+        diff::{self, FileDiff},
+        dump::{self, StreamManifest},
+        graph,
+        hashing::HashAlgorithm,
+        inspect::{self, ObjectInspection},
+        layout::{self, ByteRange},
+        listing::{self, ObjectEntry, ObjectQuery, SortKey},
+    },
+    limits::{self, ResourceLimits},
+    integrations::{
+        clamav::{self, ClamAvConfig, ClamAvScanner, ClamdAddress},
+        icap::{self, IcapService},
+        mail,
+        reputation::{ReputationChecker, ReputationConfig, ReputationService},
+        virustotal::{VirusTotalClient, VirusTotalConfig},
+        watch::{self, DropWatcher},
+        Policy,
+    },
+    known_good::KnownGoodSet,
+    registry::DetectorRegistry,
+    remediation::{
+        self,
+        disarm::Disarm,
+        flatten::FlattenForms,
+        normalize::Normalize,
+        redact::ApplyRedactions,
+        repair,
+        sanitize::{Sanitize, SanitizePolicy},
+        scrub::{MetadataPolicy, ScrubMetadata},
+        strip::StripRevisions,
+    },
+    scoring::ScoringModel,
+    report::{self, table::Rows, template::ReportTemplate, GraphFormat, OutputFormat},
+    sandbox::{self, SandboxConfig},
+    testing::Corpus,
+    AnalysisOptions, Analyzer, PdfAnalysis, PdfAnalyzer, PdxError,
+};
+
 #[derive(Parser)]
-#[command(name = "pdx", about = "PDF Anti-Forensics Analysis Tool")]
+#[command(
+    name = "pdx",
+    about = "PDF Anti-Forensics Analysis Tool",
+    args_conflicts_with_subcommands = true,
+    arg_required_else_help = true
+)]
 struct Cli {
-    /// PDF file to analyze
+    /// PDF file to summarize in the log, as releases before subcommands did
+    /// (deprecated: use `pdx analyze FILE`)
+    file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Analyze PDF files and print a report for each
+    Analyze {
+        #[command(flatten)]
+        inputs: Inputs,
+
+        /// Output format (text, json, jsonl, yaml, csv, tsv, html, markdown, pdf, stix, cbor,
+        /// msgpack, detailed); jsonl writes one line per file as soon as it is analyzed
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+
+        /// What a CSV/TSV row stands for: files (one row of key indicators) or findings
+        #[arg(long, default_value = "files")]
+        rows: Rows,
+
+        /// Leave out the CSV/TSV header, to append rows to an existing table
+        #[arg(long)]
+        no_header: bool,
+
+        /// Render each analysis with a Tera template instead of --format; templates named
+        /// *.html, *.htm or *.xml escape every value
+        #[arg(long, value_name = "FILE")]
+        template: Option<PathBuf>,
+
+        /// Diff consecutive revisions and include a change timeline
+        #[arg(long)]
+        diff_revisions: bool,
+
+        /// Simulate how Acrobat, PDFium and MuPDF resolve ambiguous constructs
+        #[arg(long)]
+        simulate_viewers: bool,
+
+        /// Maximum estimated analysis cost; costlier files are analyzed less deeply
+        #[arg(long)]
+        budget: Option<u64>,
+
+        /// Write data found after the final %%EOF to this file
+        #[arg(long, value_name = "PATH")]
+        dump_trailing: Option<PathBuf>,
+
+        /// Extract Flash, U3D, PRC and other multimedia payloads into this directory
+        #[arg(long, value_name = "DIR")]
+        extract_media: Option<PathBuf>,
+
+        /// Analyze the file as it was after revision N (0 = original, as numbered in the timeline)
+        #[arg(long, value_name = "N")]
+        revision: Option<usize>,
+
+        /// Additional file digest to report (sha256, blake3, sha1, md5)
+        #[arg(long, default_value = "sha256")]
+        hash: HashAlgorithm,
+
+        /// Parse the file in a resource-limited, syscall-filtered child process (Linux only)
+        #[arg(long)]
+        sandbox: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Analyze embedded PDFs, also inside ZIP attachments, up to N levels deep (0 disables)
+        #[arg(long, value_name = "N", default_value_t = pdx::DEFAULT_EMBEDDED_DEPTH)]
+        embedded_depth: usize,
+
+        /// Check extracted URLs with a reputation service: safe-browsing
+        /// (key in PDX_SAFE_BROWSING_KEY), phishtank or an endpoint URL; repeatable
+        #[arg(long, value_name = "SERVICE")]
+        reputation: Vec<ReputationService>,
+
+        /// Scan the file, attachments, images and decoded streams with clamd: a socket path
+        /// (default /var/run/clamav/clamd.ctl) or tcp://host:port
+        #[arg(long, value_name = "SOCKET", num_args = 0..=1, default_missing_value = clamav::DEFAULT_SOCKET)]
+        clamav: Option<ClamdAddress>,
+
+        /// Look up the file and attachment hashes on VirusTotal; optional TOML config
+        /// (key in the config or PDX_VIRUSTOTAL_KEY)
+        #[arg(long, value_name = "CONFIG", num_args = 0..=1)]
+        virustotal: Option<Option<PathBuf>>,
+
+        /// Upload files VirusTotal has never seen (they become available to its subscribers)
+        #[arg(long, requires = "virustotal")]
+        virustotal_submit: bool,
+
+        /// Known-good hash set (NSRL RDS export or one hash per line); matching content is
+        /// reported as informational; repeatable
+        #[arg(long, value_name = "PATH")]
+        known_good: Vec<PathBuf>,
+
+        /// Scan for personal data: email, phone, ssn, iban, credit-card (all when no kinds are given)
+        #[arg(long, value_name = "KINDS", num_args = 0.., value_delimiter = ',')]
+        pii: Option<Vec<PiiKind>>,
+
+        /// YARA rule file, or directory of .yar/.yara files, to run over the file and decoded streams; repeatable
+        #[arg(long, value_name = "PATH")]
+        yara: Vec<PathBuf>,
+
+        /// Directory of YAML/TOML detection rules to evaluate
+        #[arg(long, value_name = "DIR")]
+        rules: Option<PathBuf>,
+
+        /// Rhai analyzer script, or directory of .rhai files, run after the built-in detectors; repeatable
+        #[arg(long, value_name = "PATH")]
+        script: Vec<PathBuf>,
+
+        /// Directory of WebAssembly analyzer plugins (.wasm components)
+        #[arg(long, value_name = "DIR")]
+        plugins: Option<PathBuf>,
+
+        /// Skip a built-in detector, named after its report section (e.g. fonts); repeatable
+        #[arg(long, value_name = "DETECTOR")]
+        disable: Vec<String>,
+    },
+
+    /// Triage PDF files: policy verdict and phishing likelihood
+    Scan {
+        #[command(flatten)]
+        inputs: Inputs,
+
+        /// Lowest finding severity that quarantines the file
+        #[arg(long, default_value = "medium")]
+        quarantine_at: Severity,
+
+        /// Lowest finding severity that rejects the file
+        #[arg(long, default_value = "high")]
+        reject_at: Severity,
+
+        /// Risk score (0.0-1.0) at which the file is flagged with exit status 3, which takes
+        /// precedence over status 1 for files that failed to scan
+        #[arg(long, value_name = "SCORE", value_parser = parse_threshold)]
+        threshold: Option<f64>,
+
+        /// TOML file overriding the risk scoring weights
+        #[arg(long, value_name = "PATH")]
+        scoring: Option<PathBuf>,
+
+        /// Known-good hash set; a matching file is accepted and matching attachments are
+        /// de-prioritized; repeatable
+        #[arg(long, value_name = "PATH")]
+        known_good: Vec<PathBuf>,
+
+        /// Output format (text, json, jsonl, yaml, csv, tsv, detailed); json and yaml print
+        /// a list when several files are scanned
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+
+        /// Leave out the CSV/TSV header, to append rows to an existing table
+        #[arg(long)]
+        no_header: bool,
+    },
+
+    /// Postfix content filter: scan PDF attachments of the message on stdin
+    Milter {
+        /// Envelope sender; with recipients, the message is re-injected via sendmail
+        #[arg(short = 'f', long)]
+        sender: Option<String>,
+
+        /// sendmail binary used for re-injection
+        #[arg(long, default_value = "/usr/sbin/sendmail")]
+        sendmail: PathBuf,
+
+        /// Lowest finding severity that tags the message for quarantine
+        #[arg(long, default_value = "medium")]
+        quarantine_at: Severity,
+
+        /// Lowest finding severity that rejects the message
+        #[arg(long, default_value = "high")]
+        reject_at: Severity,
+
+        /// Envelope recipients (after --); without them the message goes to stdout
+        #[arg(last = true)]
+        recipients: Vec<String>,
+    },
+
+    /// ICAP server: scan PDFs sent by web proxies via REQMOD/RESPMOD
+    Icap {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:1344")]
+        listen: SocketAddr,
+
+        /// Lowest finding severity that tags the message for quarantine
+        #[arg(long, default_value = "medium")]
+        quarantine_at: Severity,
+
+        /// Lowest finding severity that replaces the message with a block page
+        #[arg(long, default_value = "high")]
+        reject_at: Severity,
+
+        /// Largest message body buffered for scanning, in bytes
+        #[arg(long, default_value_t = icap::DEFAULT_MAX_BODY)]
+        max_body: usize,
+    },
+
+    /// Scan PDF files as they are written to a drop directory
+    Watch {
+        /// Directory to watch
+        dir: PathBuf,
+
+        /// Also watch subdirectories
+        #[arg(short, long)]
+        recursive: bool,
+
+        /// Scan the PDF files already in the directory on startup
+        #[arg(long)]
+        existing: bool,
+
+        /// Risk score (0.0-1.0) at which a file is quarantined
+        #[arg(long, value_name = "SCORE", value_parser = parse_threshold, default_value_t = watch::DEFAULT_THRESHOLD)]
+        threshold: f64,
+
+        /// Move files reaching the threshold to this directory
+        #[arg(long, value_name = "DIR")]
+        quarantine: Option<PathBuf>,
+
+        /// Move files below the threshold to this directory
+        #[arg(long, value_name = "DIR")]
+        clean: Option<PathBuf>,
+
+        /// POST the outcome of files reaching the threshold as JSON to this URL
+        #[arg(long, value_name = "URL")]
+        webhook: Option<String>,
+
+        /// POST the outcome of every file, not only of those reaching the threshold
+        #[arg(long, requires = "webhook")]
+        webhook_all: bool,
+
+        /// Seconds a file must stay unchanged before it is scanned
+        #[arg(long, value_name = "SECS", default_value_t = watch::DEFAULT_SETTLE.as_secs())]
+        settle: u64,
+
+        /// TOML file overriding the risk scoring weights
+        #[arg(long, value_name = "PATH")]
+        scoring: Option<PathBuf>,
+
+        /// Output format for each scanned file (text, json, jsonl, yaml, csv, tsv, detailed)
+        #[arg(short, long, default_value = "jsonl")]
+        format: OutputFormat,
+    },
+
+    /// Extract content from a PDF file
+    Extract {
+        /// PDF file to read
+        file: PathBuf,
+
+        /// Extract page text with positions
+        #[arg(long)]
+        text: bool,
+
+        /// Extract every URI as an IOC list, with obfuscation and lookalike analysis
+        #[arg(long)]
+        uris: bool,
+
+        /// Extract JavaScript, images, attachments, fonts, XMP and ICC profiles into this
+        /// directory, with a manifest of their hashes
+        #[arg(long, value_name = "DIR")]
+        out: Option<PathBuf>,
+
+        /// Artifact classes to extract with --out: js, images, attachments, fonts, xmp, icc
+        /// (all when omitted)
+        #[arg(long, value_name = "CLASSES", value_delimiter = ',', requires = "out")]
+        only: Vec<ArtifactClass>,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, csv, tsv, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Compare two PDF files object by object; exits 1 when they differ, 2 on errors
+    Diff {
+        /// Older file
+        before: PathBuf,
+
+        /// Newer file
+        after: PathBuf,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Export the indirect-reference graph as Graphviz DOT or GraphML
+    Graph {
+        /// PDF file to read
+        file: PathBuf,
+
+        /// Where to write the graph; standard output when omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Graph format (dot, graphml); graphml adds object attributes and the revision history
+        #[arg(short, long, default_value = "dot")]
+        format: GraphFormat,
+    },
+
+    /// Dump one object: its dictionary, raw and decoded stream, references and revision
+    Inspect {
+        /// PDF file to read
+        file: PathBuf,
+
+        /// Object to dump, as N or N:GEN
+        #[arg(long, value_parser = inspect::parse_object_id)]
+        object: (u32, Option<u16>),
+
+        /// Stream bytes to dump, raw and decoded each
+        #[arg(long, default_value_t = 1024)]
+        max_bytes: usize,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// List objects with type, size, entropy, filters and revision
+    Objects {
+        /// PDF file to read
+        file: PathBuf,
+
+        /// Only objects of this type, such as Stream or XObject/Image
+        #[arg(long = "type")]
+        object_type: Option<String>,
+
+        /// Only streams using this filter, such as JBIG2Decode
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Only streams with at least this entropy
+        #[arg(long)]
+        min_entropy: Option<f64>,
+
+        /// Only objects taking at least this many bytes
+        #[arg(long)]
+        min_size: Option<usize>,
+
+        /// Only objects last written by this revision
+        #[arg(long)]
+        revision: Option<usize>,
+
+        /// Column to sort by (id, type, size, entropy, revision)
+        #[arg(long, default_value = "id")]
+        sort: SortKey,
+
+        /// Sort largest first
+        #[arg(long)]
+        reverse: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, csv, tsv)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Write every stream to a directory with a manifest, for external tools
+    Streams {
+        /// PDF file to read
+        file: PathBuf,
+
+        /// Write decoded bytes instead of the stored ones
+        #[arg(long)]
+        decode: bool,
+
+        /// Directory to write the streams and manifest.json into
+        #[arg(short, long)]
+        out: PathBuf,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format for the summary (text, json, jsonl, yaml)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Hex dump with every byte range labelled by the structure owning it
+    Hexdump {
+        /// PDF file to read
+        file: PathBuf,
+
+        /// Bytes to dump, as START:END, START:, START+LENGTH or START (decimal or 0x hex)
+        #[arg(long)]
+        range: Option<ByteRange>,
+
+        /// Output format (text; json, jsonl and yaml give the byte layout only)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Reconstruct the document's history from every dated source, highlighting conflicts
+    Timeline {
+        /// PDF file to read
+        file: PathBuf,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, csv, tsv)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Apply redactions to a copy of a PDF file
+    Redact {
+        #[command(subcommand)]
+        command: RedactCommand,
+    },
+
+    /// Rebuild a damaged PDF file from its objects
+    Repair {
+        /// PDF file to repair; it is never modified
+        file: PathBuf,
+
+        /// Where to write the rebuilt file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Report what would be rebuilt without writing the output
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Remove active content and metadata from a copy of a PDF file
+    Sanitize {
+        /// PDF file to sanitize; it is never modified
+        file: PathBuf,
+
+        /// Where to write the sanitized copy
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Report what would be removed without writing the output
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Keep JavaScript
+        #[arg(long)]
+        keep_javascript: bool,
+
+        /// Keep embedded files and attachments
+        #[arg(long)]
+        keep_attachments: bool,
+
+        /// Keep automatic, launch, form and media actions
+        #[arg(long)]
+        keep_actions: bool,
+
+        /// Keep URI and remote go-to links
+        #[arg(long)]
+        keep_links: bool,
+
+        /// Keep the document information dictionary and XMP metadata
+        #[arg(long)]
+        keep_metadata: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Remove or rewrite metadata fields of a copy of a PDF file
+    ScrubMetadata {
+        /// PDF file to scrub; it is never modified
+        file: PathBuf,
+
+        /// Where to write the scrubbed copy
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Policy file (YAML, or TOML with a .toml extension)
+        #[arg(short, long)]
+        policy: PathBuf,
+
+        /// Report the metadata changes without writing the output
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Rewrite a copy of a PDF file as a single revision, purging earlier versions
+    StripRevisions {
+        /// PDF file to flatten; it is never modified
+        file: PathBuf,
+
+        /// Where to write the flattened copy
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Zero-based revision to keep (default: the latest)
+        #[arg(long)]
+        revision: Option<usize>,
+
+        /// Report what would be purged without writing the output
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Remove only executable behavior from a copy of a PDF file, keeping layout and form data
+    Disarm {
+        /// PDF file to disarm; it is never modified
+        file: PathBuf,
+
+        /// Where to write the disarmed copy
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Report what would be removed without writing the output
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Burn form fields into the page content of a copy of a PDF file
+    FlattenForms {
+        /// PDF file to flatten; it is never modified
+        file: PathBuf,
+
+        /// Where to write the non-interactive copy
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Report what would be flattened without writing the output
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Rewrite a copy of a PDF file in canonical form, so equivalent files hash identically
+    Normalize {
+        /// PDF file to normalize; it is never modified
+        file: PathBuf,
+
+        /// Where to write the canonical copy
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Report the canonical hash without writing the output
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Maintain detection content
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+
+    /// Print the JSON Schema of the analysis output
+    Schema,
+}
+
+#[derive(Subcommand)]
+enum RedactCommand {
+    /// Remove the content under pending /Redact annotations and drawn-over text
+    Apply {
+        /// PDF file to redact; it is never modified
+        file: PathBuf,
+
+        /// Where to write the redacted copy
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Report what would be removed without writing the output
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        limit_args: LimitArgs,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum RulesCommand {
+    /// Run every detector over a fixture corpus and compare with expected-findings snapshots
+    Test {
+        /// Directory of fixture PDFs, each with a NAME.expected.json snapshot
+        #[arg(long, value_name = "DIR")]
+        corpus: PathBuf,
+
+        /// Rewrite the snapshots from the current findings
+        #[arg(long)]
+        update: bool,
+
+        /// Output format (text, json, jsonl, yaml, detailed)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Validate a directory of YAML/TOML detection rules and list them
+    Check {
+        /// Rules directory
+        dir: PathBuf,
+    },
+}
+
+/// Resource limits of the commands that load documents
+#[derive(clap::Args)]
+struct LimitArgs {
+    /// Largest decoded stream in bytes; larger streams are reported, not decoded
+    #[arg(long, value_name = "BYTES", default_value_t = limits::DEFAULT_MAX_DECODED_SIZE)]
+    max_decoded_size: u64,
+
+    /// Largest decoded-to-stored size ratio of a stream
+    #[arg(long, value_name = "RATIO", default_value_t = limits::DEFAULT_MAX_EXPANSION_RATIO)]
+    max_expansion_ratio: u64,
+
+    /// Most indirect objects; larger files are not loaded
+    #[arg(long, value_name = "N", default_value_t = limits::DEFAULT_MAX_OBJECTS)]
+    max_objects: usize,
+
+    /// Deepest array and dictionary nesting; deeper files are not loaded
+    #[arg(long, value_name = "N", default_value_t = limits::DEFAULT_MAX_DEPTH)]
+    max_depth: usize,
+}
+
+impl LimitArgs {
+    fn resource_limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            max_decoded_size: self.max_decoded_size,
+            max_expansion_ratio: self.max_expansion_ratio,
+            max_objects: self.max_objects,
+            max_depth: self.max_depth,
+        }
+    }
+}
+
+/// Files processed by a batch command
+#[derive(clap::Args)]
+struct Inputs {
+    /// PDF files, directories holding PDF files, glob patterns such as 'corpus/**/*.pdf',
+    /// or - for standard input. Only the files directly inside a directory are taken
+    /// unless --recursive is given
     #[arg(required = true)]
-    file: PathBuf,
+    files: Vec<PathBuf>,
+
+    /// Also process the files in subdirectories of the given directories (earlier releases
+    /// always did)
+    #[arg(short, long)]
+    recursive: bool,
+
+    /// Glob pattern selecting the files taken from directories (default *.pdf); repeatable
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Glob pattern of files to skip, e.g. '*/drafts/*'; repeatable
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Standard input larger than this many bytes is spooled to a temporary file in
+    /// --temp-dir
+    #[arg(long, value_name = "BYTES", default_value_t = batch::DEFAULT_STDIN_MEMORY)]
+    stdin_memory: usize,
+
+    /// Directory standard input is spooled to (default: general.temp_dir of --config, else
+    /// the system temporary directory, TMPDIR)
+    #[arg(long, value_name = "DIR")]
+    temp_dir: Option<PathBuf>,
+
+    /// Files and standard input larger than this many bytes are refused (default:
+    /// general.max_file_size of --config, else 512 MiB)
+    #[arg(long, value_name = "BYTES")]
+    max_input_size: Option<u64>,
+
+    /// YAML configuration file whose general section sets temp_dir and max_file_size
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Setup logging
-    let subscriber = FmtSubscriber::builder()
+impl Inputs {
+    /// Files to process; unreadable inputs are logged and returned as failures
+    async fn select(&self) -> Result<Selection> {
+        // A broken configuration file fails the run rather than every file
+        self.config()?;
+        let selection = FileSelection::new(self.recursive, &self.include, &self.exclude)?
+            .expand(&self.files)
+            .await;
+        for (path, reason) in &selection.failures {
+            error!("Cannot read {}: {}", path.display(), reason);
+        }
+        Ok(selection)
+    }
+
+    /// Settings of the configuration file, overridden by the options given
+    fn config(&self) -> Result<InputConfig> {
+        let config = match &self.config {
+            Some(path) => InputConfig::load(path)?,
+            None => InputConfig::default(),
+        };
+        Ok(InputConfig {
+            temp_dir: self.temp_dir.clone().or(config.temp_dir),
+            max_file_size: self.max_input_size.or(config.max_file_size),
+        })
+    }
+
+    /// Analyzes a selected file, reading standard input for -
+    async fn analyze(&self, path: &Path, options: AnalysisOptions) -> Result<PdfAnalysis> {
+        let config = self.config()?;
+        let max_size = config.max_file_size.unwrap_or(batch::DEFAULT_MAX_INPUT_SIZE);
+        if !batch::is_stdin(path) {
+            batch::check_size(path, max_size).await?;
+            return PdfAnalyzer::with_options(path, options)?.analyze().await;
+        }
+        let temp_dir = config.temp_dir.unwrap_or_else(std::env::temp_dir);
+        let data = batch::spool(tokio::io::stdin(), self.stdin_memory, max_size, &temp_dir).await?;
+        PdfAnalyzer::with_options("<stdin>", options)?.analyze_bytes(&data).await
+    }
+}
+
+fn main() -> Result<()> {
+    // Setup logging to stderr so reports on stdout stay machine-readable
+    FmtSubscriber::builder()
         .with_max_level(tracing::Level::INFO)
+        .with_writer(std::io::stderr)
         .init();
 
+    // Sandbox workers lock themselves down before any runtime threads exist
+    if std::env::args_os().nth(1).map_or(false, |arg| arg == sandbox::WORKER_ARG) {
+        std::process::exit(sandbox::worker_main());
+    }
+
+    run()
+}
+
+#[tokio::main]
+async fn run() -> Result<()> {
     let cli = Cli::parse();
-    let file_path = cli.file;
 
     info!("PDx Anti-Forensics Tool");
-    info!("Author: kartik4091");
-    info!("Timestamp: 2025-06-03 19:58:30");
 
-    if !file_path.exists() {
-        error!("File not found: {}", file_path.display());
-        std::process::exit(1);
-    }
+    let command = match (cli.command, cli.file) {
+        (Some(command), _) => command,
+        (None, file) => {
+            let file = file.unwrap_or_default();
+            warn!("`pdx FILE` is deprecated and will be removed; use `pdx analyze FILE` for the full report");
+            if !file.exists() {
+                error!("File not found: {}", file.display());
+                std::process::exit(1);
+            }
+            match summarize_pdf(&file) {
+                Ok(_) => info!("Analysis complete"),
+                Err(e) => error!("Analysis failed: {}", e),
+            }
+            return Ok(());
+        }
+    };
+
+    match command {
+        Command::Analyze {
+            inputs,
+            format,
+            rows,
+            no_header,
+            template,
+            diff_revisions,
+            simulate_viewers,
+            budget,
+            dump_trailing,
+            extract_media,
+            revision,
+            hash,
+            sandbox,
+            limit_args,
+            embedded_depth,
+            reputation,
+            clamav,
+            virustotal,
+            virustotal_submit,
+            known_good,
+            pii,
+            yara,
+            rules,
+            script,
+            plugins,
+            disable,
+        } => {
+            let Selection { files, failures } = inputs.select().await?;
 
-    match analyze_pdf(&file_path).await {
-        Ok(_) => info!("Analysis complete"),
-        Err(e) => error!("Analysis failed: {}", e),
+            let mut detectors = DetectorRegistry::default();
+            for name in &disable {
+                detectors.disable(name)?;
+            }
+            let options = AnalysisOptions {
+                diff_revisions,
+                simulate_viewers,
+                preview: format == OutputFormat::Html,
+                budget: budget.map(AnalysisBudget::new),
+                dump_trailing,
+                extract_media,
+                revision,
+                hash,
+                sandbox: sandbox.then(SandboxConfig::default),
+                limits: limit_args.resource_limits(),
+                embedded_depth,
+                reputation: (!reputation.is_empty())
+                    .then(|| ReputationChecker::new(ReputationConfig { services: reputation, ..Default::default() }))
+                    .transpose()?
+                    .map(Arc::new),
+                clamav: clamav
+                    .map(|address| Arc::new(ClamAvScanner::new(ClamAvConfig { address, ..Default::default() }))),
+                virustotal: match virustotal {
+                    Some(config) => {
+                        let mut config = config.as_deref().map(VirusTotalConfig::load).transpose()?.unwrap_or_default();
+                        config.submit |= virustotal_submit;
+                        Some(Arc::new(VirusTotalClient::new(config)?))
+                    }
+                    None => None,
+                },
+                known_good: load_known_good(&known_good)?,
+                pii: pii.map(|kinds| PiiConfig { kinds }),
+                #[cfg(feature = "yara")]
+                yara: match yara.as_slice() {
+                    [] => None,
+                    paths => Some(Arc::new(pdx::detectors::yara::YaraRules::load(paths)?)),
+                },
+                rules: rules.as_deref().map(RuleSet::load).transpose()?.map(Arc::new),
+                #[cfg(feature = "scripting")]
+                scripts: match script.as_slice() {
+                    [] => None,
+                    paths => Some(Arc::new(pdx::detectors::scripting::Scripts::load(paths)?)),
+                },
+                #[cfg(feature = "plugins")]
+                plugins: plugins
+                    .as_deref()
+                    .map(pdx::detectors::plugins::Plugins::load)
+                    .transpose()?
+                    .map(Arc::new),
+                detectors,
+            };
+            #[cfg(not(feature = "yara"))]
+            if !yara.is_empty() {
+                error!("This build of pdx has no YARA support (enable the `yara` feature)");
+                std::process::exit(2);
+            }
+            #[cfg(not(feature = "scripting"))]
+            if !script.is_empty() {
+                error!("This build of pdx has no scripting support (enable the `scripting` feature)");
+                std::process::exit(2);
+            }
+            #[cfg(not(feature = "plugins"))]
+            if plugins.is_some() {
+                error!("This build of pdx has no plugin support (enable the `plugins` feature)");
+                std::process::exit(2);
+            }
+            if sandbox && !sandbox::SUPPORTED {
+                error!("--sandbox needs syscall filtering, which is only available on Linux (x86_64 and aarch64)");
+                std::process::exit(2);
+            }
+            if format == OutputFormat::Pdf && files.len() > 1 {
+                error!("A PDF report holds one analysis; analyze {} files one at a time", files.len());
+                std::process::exit(2);
+            }
+            let template = template.as_deref().map(ReportTemplate::load).transpose()?;
+            let mut header = !no_header;
+            let mut failed = failures.len();
+            if format == OutputFormat::Jsonl {
+                for (path, reason) in &failures {
+                    println!("{}", serde_json::json!({ "path": path, "error": reason }));
+                }
+            }
+            for file in &files {
+                let analyzed = analyze_pdf(&inputs, file, options.clone(), format, rows, header, template.as_ref());
+                match batch::isolated(analyzed).await {
+                    Ok(_) => {
+                        info!("Analysis of {} complete", file.display());
+                        header = false;
+                    }
+                    Err(e) => {
+                        error!("Analysis of {} failed: {}", file.display(), e);
+                        // Keep one line per input so consumers can account for every file
+                        if format == OutputFormat::Jsonl {
+                            println!("{}", serde_json::json!({ "path": file, "error": e.to_string() }));
+                        }
+                        failed += 1;
+                    }
+                }
+            }
+            if failed > 0 {
+                error!("{} of {} files failed", failed, files.len() + failures.len());
+                std::process::exit(1);
+            }
+        }
+        Command::Scan { inputs, quarantine_at, reject_at, threshold, scoring, known_good, format, no_header } => {
+            let policy = Policy { quarantine_at, reject_at, ..Default::default() };
+            let scoring = match &scoring {
+                Some(path) => ScoringModel::load(path)?,
+                None => ScoringModel::default(),
+            };
+            let options = AnalysisOptions { known_good: load_known_good(&known_good)?, scoring, ..Default::default() };
+            let Selection { files, failures } = inputs.select().await?;
+            let mut header = !no_header;
+            let mut failed = failures.len();
+            let mut flagged = false;
+            // Concatenated JSON or YAML documents would not parse as one
+            let list = files.len() > 1 && matches!(format, OutputFormat::Json | OutputFormat::Yaml);
+            let mut scans = Vec::new();
+            for file in &files {
+                let analysis = match batch::isolated(inputs.analyze(file, options.clone())).await {
+                    Ok(analysis) => analysis,
+                    Err(e) => {
+                        error!("Scan of {} failed: {}", file.display(), e);
+                        failed += 1;
+                        continue;
+                    }
+                };
+                let mut scan = report::ScanReport::new(&analysis, &policy);
+                if let Some(threshold) = threshold {
+                    scan = scan.threshold(threshold);
+                }
+                flagged |= scan.exceeds_threshold();
+                if list {
+                    scans.push(scan);
+                } else {
+                    println!("{}", report::render_scan_rows(&scan, format, header)?);
+                    header = false;
+                }
+            }
+            if list {
+                println!("{}", report::render_scans(&scans, format)?);
+            }
+            if failed > 0 {
+                error!("{} of {} files failed", failed, files.len() + failures.len());
+            }
+            // A flagged file needs attention even when others could not be scanned
+            if flagged {
+                std::process::exit(3);
+            }
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::Milter { sender, sendmail, quarantine_at, reject_at, recipients } => {
+            let policy = Policy { quarantine_at, reject_at, ..Default::default() };
+            let code = match run_content_filter(policy, sender, &sendmail, &recipients).await {
+                Ok(code) => code,
+                Err(e) => {
+                    error!("Content filter failed: {}", e);
+                    mail::EX_TEMPFAIL
+                }
+            };
+            std::process::exit(code);
+        }
+        Command::Icap { listen, quarantine_at, reject_at, max_body } => {
+            let policy = Policy { quarantine_at, reject_at, ..Default::default() };
+            let listener = tokio::net::TcpListener::bind(listen).await?;
+            info!("ICAP services at icap://{}/reqmod and icap://{}/respmod", listen, listen);
+            IcapService::new(policy).max_body(max_body).serve(listener).await?;
+        }
+        Command::Watch {
+            dir,
+            recursive,
+            existing,
+            threshold,
+            quarantine,
+            clean,
+            webhook,
+            webhook_all,
+            settle,
+            scoring,
+            format,
+        } => {
+            let mut watcher = DropWatcher::new(dir)
+                .recursive(recursive)
+                .threshold(threshold)
+                .settle(std::time::Duration::from_secs(settle));
+            if let Some(path) = &scoring {
+                watcher = watcher.scoring(ScoringModel::load(path)?);
+            }
+            if let Some(quarantine) = quarantine {
+                watcher = watcher.quarantine(quarantine);
+            }
+            if let Some(clean) = clean {
+                watcher = watcher.clean(clean);
+            }
+            if let Some(url) = webhook {
+                watcher = watcher.webhook(url, webhook_all);
+            }
+            let mut header = true;
+            watcher
+                .run(existing, |outcome| match report::render_scan_rows(&outcome.scan, format, header) {
+                    Ok(rendered) => {
+                        println!("{}", rendered);
+                        header = false;
+                    }
+                    Err(e) => error!("Cannot render the scan of {}: {}", outcome.scan.path, e),
+                })
+                .await?;
+        }
+        Command::Extract { file, text, uris, out, only, limit_args, format } => {
+            if !text && !uris && out.is_none() {
+                error!("Nothing to extract; pass --text, --uris or --out");
+                std::process::exit(2);
+            }
+            let resource_limits = limit_args.resource_limits();
+            let extracted = extract_content(&file, text, uris, out.as_deref(), &only, resource_limits, format);
+            match limits::scope(resource_limits, extracted).await {
+                Ok(_) => info!("Extraction complete"),
+                Err(e) => {
+                    error!("Extraction failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Diff { before, after, limit_args, format } => {
+            let resource_limits = limit_args.resource_limits();
+            let diff = match limits::scope(resource_limits, diff_files(&before, &after, resource_limits)).await {
+                Ok(diff) => diff,
+                Err(e) => {
+                    // Like diff(1), trouble is 2 so that 1 keeps meaning "different"
+                    error!("Comparison failed: {}", e);
+                    std::process::exit(2);
+                }
+            };
+            println!("{}", report::render_diff(&diff, format)?);
+            if !diff.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Command::Graph { file, output, limit_args, format } => {
+            let resource_limits = limit_args.resource_limits();
+            let exported = export_graph(&file, output.as_deref(), resource_limits, format);
+            if let Err(e) = limits::scope(resource_limits, exported).await {
+                error!("Graph export failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::Inspect { file, object: (number, generation), max_bytes, limit_args, format } => {
+            let resource_limits = limit_args.resource_limits();
+            let inspected = inspect_object(&file, number, generation, max_bytes, resource_limits);
+            let inspection = match limits::scope(resource_limits, inspected).await {
+                Ok(inspection) => inspection,
+                Err(e) => {
+                    error!("Inspection failed: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            println!("{}", report::render_inspection(&inspection, format)?);
+        }
+        Command::Objects {
+            file,
+            object_type,
+            filter,
+            min_entropy,
+            min_size,
+            revision,
+            sort,
+            reverse,
+            limit_args,
+            format,
+        } => {
+            let query = ObjectQuery { object_type, filter, min_entropy, min_size, revision, sort, reverse };
+            let resource_limits = limit_args.resource_limits();
+            let entries = match limits::scope(resource_limits, list_objects(&file, &query, resource_limits)).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    error!("Listing failed: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            println!("{}", report::render_objects(&entries, format)?);
+        }
+        Command::Streams { file, decode, out, limit_args, format } => {
+            let resource_limits = limit_args.resource_limits();
+            let dumped = dump_streams(&file, &out, decode, resource_limits);
+            let manifest = match limits::scope(resource_limits, dumped).await {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    error!("Stream dump failed: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            println!("{}", report::render_manifest(&manifest, format)?);
+        }
+        Command::Hexdump { file, range, format } => {
+            let data = tokio::fs::read(&file).await?;
+            let spans = layout::layout(&data);
+            let spans = match range {
+                Some(range) => layout::select(&spans, range),
+                None => spans,
+            };
+            println!("{}", report::render_hexdump(&data, &spans, format)?);
+        }
+        Command::Timeline { file, limit_args, format } => {
+            info!("Loading PDF: {}", file.display());
+            let data = tokio::fs::read(&file).await?;
+            let resource_limits = limit_args.resource_limits();
+            let violations = limits::check(&data, &resource_limits);
+            if let Some(violation) = limits::loading_blocker(&violations) {
+                error!("Timeline failed: {}", PdxError::from(violation));
+                std::process::exit(1);
+            }
+            let timeline = limits::scope(resource_limits, async { chronology::reconstruct(&data) }).await;
+            println!("{}", report::render_timeline(&timeline, format)?);
+        }
+        Command::Redact { command: RedactCommand::Apply { file, output, dry_run, limit_args, format } } => {
+            let report =
+                remediation::run(&ApplyRedactions, &file, &output, dry_run, limit_args.resource_limits()).await?;
+            println!("{}", report::render_remediation(&report, format)?);
+            if !report.verification.passed {
+                std::process::exit(1);
+            }
+        }
+        Command::Repair { file, output, dry_run, limit_args, format } => {
+            let report = repair::run(&file, &output, dry_run, limit_args.resource_limits()).await?;
+            println!("{}", report::render_repair(&report, format)?);
+            if !report.verification.passed {
+                std::process::exit(1);
+            }
+        }
+        Command::Sanitize {
+            file,
+            output,
+            dry_run,
+            keep_javascript,
+            keep_attachments,
+            keep_actions,
+            keep_links,
+            keep_metadata,
+            limit_args,
+            format,
+        } => {
+            let policy = SanitizePolicy {
+                javascript: !keep_javascript,
+                embedded_files: !keep_attachments,
+                actions: !keep_actions,
+                external_links: !keep_links,
+                metadata: !keep_metadata,
+            };
+            let report =
+                remediation::run(&Sanitize { policy }, &file, &output, dry_run, limit_args.resource_limits()).await?;
+            println!("{}", report::render_remediation(&report, format)?);
+            if !report.verification.passed {
+                std::process::exit(1);
+            }
+        }
+        Command::ScrubMetadata { file, output, policy, dry_run, limit_args, format } => {
+            let policy = MetadataPolicy::load(&policy)?;
+            let operation = ScrubMetadata { policy };
+            let report = remediation::run(&operation, &file, &output, dry_run, limit_args.resource_limits()).await?;
+            println!("{}", report::render_remediation(&report, format)?);
+            if !report.verification.passed {
+                std::process::exit(1);
+            }
+        }
+        Command::StripRevisions { file, output, revision, dry_run, limit_args, format } => {
+            let operation = StripRevisions { revision };
+            let report = remediation::run(&operation, &file, &output, dry_run, limit_args.resource_limits()).await?;
+            println!("{}", report::render_remediation(&report, format)?);
+            if !report.verification.passed {
+                std::process::exit(1);
+            }
+        }
+        Command::Disarm { file, output, dry_run, limit_args, format } => {
+            let report = remediation::run(&Disarm, &file, &output, dry_run, limit_args.resource_limits()).await?;
+            println!("{}", report::render_remediation(&report, format)?);
+            if !report.verification.passed {
+                std::process::exit(1);
+            }
+        }
+        Command::FlattenForms { file, output, dry_run, limit_args, format } => {
+            let report = remediation::run(&FlattenForms, &file, &output, dry_run, limit_args.resource_limits()).await?;
+            println!("{}", report::render_remediation(&report, format)?);
+            if !report.verification.passed {
+                std::process::exit(1);
+            }
+        }
+        Command::Normalize { file, output, dry_run, limit_args, format } => {
+            let report = remediation::run(&Normalize, &file, &output, dry_run, limit_args.resource_limits()).await?;
+            println!("{}", report::render_remediation(&report, format)?);
+            if !report.verification.passed {
+                std::process::exit(1);
+            }
+        }
+        Command::Rules { command: RulesCommand::Test { corpus, update, format } } => {
+            let report = Corpus::new(&corpus).update(update).run().await?;
+            println!("{}", report::render_corpus(&report, format)?);
+            if !report.passed() {
+                std::process::exit(1);
+            }
+        }
+        Command::Rules { command: RulesCommand::Check { dir } } => {
+            let rules = match RuleSet::load(&dir) {
+                Ok(rules) => rules,
+                Err(e) => {
+                    error!("Invalid rules: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            for rule in rules.definitions() {
+                println!("{} [{}] {}", rule.id, rule.severity, rule.description.as_deref().unwrap_or(""));
+            }
+            info!("{} rules in {} files", rules.len(), rules.sources.len());
+        }
+        Command::Schema => {
+            println!("{}", serde_json::to_string_pretty(&report::schema::analysis()?)?);
+        }
     }
 
     Ok(())
 }
 
-async fn analyze_pdf(path: &PathBuf) -> Result<()> {
-    use lopdf::Document;
-    
+async fn analyze_pdf(
+    inputs: &Inputs,
+    path: &Path,
+    options: AnalysisOptions,
+    format: OutputFormat,
+    rows: Rows,
+    header: bool,
+    template: Option<&ReportTemplate>,
+) -> Result<()> {
+    info!("Loading PDF: {}", path.display());
+
+    let analysis = inputs.analyze(path, options).await?;
+
+    let mut stdout = std::io::stdout().lock();
+    if let Some(template) = template {
+        writeln!(stdout, "{}", template.render(&analysis)?)?;
+    } else if format.is_binary() {
+        // Concatenated items form a CBOR sequence or MessagePack stream
+        stdout.write_all(&report::encode(&analysis, format)?)?;
+    } else {
+        writeln!(stdout, "{}", report::render_rows(&analysis, format, rows, header)?)?;
+    }
+    // Streaming consumers see each result as soon as it is complete
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Logs the version, page count, metadata, JavaScript, images and
+/// encryption of a file: the summary of `pdx FILE`
+fn summarize_pdf(path: &Path) -> Result<()> {
     info!("Loading PDF: {}", path.display());
-    let doc = Document::load(path)?;
-    
-    info!("PDF Version: {}.{}", doc.version.0, doc.version.1);
+    let doc = lopdf::Document::load(path)?;
+
+    info!("PDF Version: {}", doc.version);
     info!("Total pages: {}", doc.get_pages().len());
-    
-    // Start real analysis
+
     analyze_metadata(&doc)?;
     analyze_javascript(&doc)?;
     analyze_images(&doc)?;
@@ -58,11 +1374,11 @@ async fn analyze_pdf(path: &PathBuf) -> Result<()> {
 }
 
 fn analyze_metadata(doc: &lopdf::Document) -> Result<()> {
-    if let Some(info) = doc.get_info() {
+    if let Some(info) = pdx::document::info_dictionary(doc) {
         info!("Analyzing metadata...");
         for (key, value) in info.iter() {
-            if let Ok(text) = value.as_text_string() {
-                info!("{}: {}", String::from_utf8_lossy(key), text);
+            if let lopdf::Object::String(bytes, _) = value {
+                info!("{}: {}", String::from_utf8_lossy(key), pdx::document::decode_text(bytes));
             }
         }
     }
@@ -73,7 +1389,7 @@ fn analyze_javascript(doc: &lopdf::Document) -> Result<()> {
     info!("Scanning for JavaScript...");
     for (_, object) in doc.objects.iter() {
         if let lopdf::Object::Stream(ref stream) = object {
-            if let Ok(data) = stream.decompressed_content() {
+            if let Ok(data) = pdx::document::stream_data(stream) {
                 if data.windows(3).any(|w| w == b"JS ") {
                     info!("JavaScript content found!");
                 }
@@ -88,7 +1404,7 @@ fn analyze_images(doc: &lopdf::Document) -> Result<()> {
     let mut image_count = 0;
     for (_, object) in doc.objects.iter() {
         if let lopdf::Object::Stream(ref stream) = object {
-            if stream.dict.get(b"Subtype") == Some(&lopdf::Object::Name(b"Image".to_vec())) {
+            if pdx::document::name_value(&stream.dict, b"Subtype") == Some(b"Image") {
                 image_count += 1;
             }
         }
@@ -99,8 +1415,173 @@ fn analyze_images(doc: &lopdf::Document) -> Result<()> {
 
 fn analyze_security(doc: &lopdf::Document) -> Result<()> {
     info!("Analyzing security...");
-    if doc.trailer.get(b"Encrypt").is_some() {
+    if doc.trailer.has(b"Encrypt") {
         info!("Document is encrypted");
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn extract_content(
+    path: &Path,
+    text: bool,
+    uris: bool,
+    out: Option<&Path>,
+    only: &[ArtifactClass],
+    resource_limits: ResourceLimits,
+    format: OutputFormat,
+) -> Result<()> {
+    info!("Loading PDF: {}", path.display());
+
+    let data = tokio::fs::read(path).await?;
+    let violations = limits::check(&data, &resource_limits);
+    if let Some(violation) = limits::loading_blocker(&violations) {
+        return Err(PdxError::from(violation).into());
+    }
+    let doc = lopdf::Document::load_mem(&data)?;
+
+    if text {
+        println!("{}", report::render_text(&pdx::text::extract(&doc), format)?);
+    }
+    if uris {
+        println!("{}", report::render_uris(&pdx::detectors::uris::extract(&doc), format)?);
+    }
+    if let Some(out) = out {
+        let manifest = artifacts::extract(&doc, out, only).await?;
+        println!("{}", report::render_artifacts(&manifest, format)?);
+    }
+    Ok(())
+}
+
+async fn diff_files(before: &Path, after: &Path, resource_limits: ResourceLimits) -> Result<FileDiff> {
+    let mut files = Vec::new();
+    for path in [before, after] {
+        info!("Loading PDF: {}", path.display());
+        let data = tokio::fs::read(path).await?;
+        let violations = limits::check(&data, &resource_limits);
+        if let Some(violation) = limits::loading_blocker(&violations) {
+            return Err(PdxError::from(violation).into());
+        }
+        files.push(data);
+    }
+    Ok(diff::diff_files(&files[0], &files[1])?)
+}
+
+async fn export_graph(
+    path: &Path,
+    output: Option<&Path>,
+    resource_limits: ResourceLimits,
+    format: GraphFormat,
+) -> Result<()> {
+    info!("Loading PDF: {}", path.display());
+
+    let data = tokio::fs::read(path).await?;
+    let violations = limits::check(&data, &resource_limits);
+    if let Some(violation) = limits::loading_blocker(&violations) {
+        return Err(PdxError::from(violation).into());
+    }
+    let doc = lopdf::Document::load_mem(&data)?;
+    let mut graph = graph::build(&doc);
+    graph.add_history(&data);
+    let options = AnalysisOptions { limits: resource_limits, ..Default::default() };
+    let analysis = PdfAnalyzer::with_options(path, options)?.analyze().await?;
+    graph.annotate(&analysis.findings);
+
+    let rendered = report::render_graph(&graph, format);
+    match output {
+        Some(output) => {
+            tokio::fs::write(output, rendered).await?;
+            info!(
+                "Wrote {} objects and {} references to {}",
+                graph.nodes.len(),
+                graph.edges.len(),
+                output.display()
+            );
+        }
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
+
+async fn inspect_object(
+    path: &Path,
+    number: u32,
+    generation: Option<u16>,
+    max_bytes: usize,
+    resource_limits: ResourceLimits,
+) -> Result<ObjectInspection> {
+    info!("Loading PDF: {}", path.display());
+
+    let data = tokio::fs::read(path).await?;
+    let violations = limits::check(&data, &resource_limits);
+    if let Some(violation) = limits::loading_blocker(&violations) {
+        return Err(PdxError::from(violation).into());
+    }
+    let doc = lopdf::Document::load_mem(&data)?;
+    Ok(inspect::inspect(&doc, &data, number, generation, max_bytes)?)
+}
+
+async fn list_objects(path: &Path, query: &ObjectQuery, resource_limits: ResourceLimits) -> Result<Vec<ObjectEntry>> {
+    info!("Loading PDF: {}", path.display());
+
+    let data = tokio::fs::read(path).await?;
+    let violations = limits::check(&data, &resource_limits);
+    if let Some(violation) = limits::loading_blocker(&violations) {
+        return Err(PdxError::from(violation).into());
+    }
+    let doc = lopdf::Document::load_mem(&data)?;
+    Ok(listing::list(&doc, &data, query))
+}
+
+async fn dump_streams(
+    path: &Path,
+    out: &Path,
+    decode: bool,
+    resource_limits: ResourceLimits,
+) -> Result<StreamManifest> {
+    info!("Loading PDF: {}", path.display());
+
+    let data = tokio::fs::read(path).await?;
+    let violations = limits::check(&data, &resource_limits);
+    if let Some(violation) = limits::loading_blocker(&violations) {
+        return Err(PdxError::from(violation).into());
+    }
+    let doc = lopdf::Document::load_mem(&data)?;
+    Ok(dump::dump_streams(&doc, out, decode).await?)
+}
+
+async fn run_content_filter(
+    policy: Policy,
+    sender: Option<String>,
+    sendmail: &Path,
+    recipients: &[String],
+) -> Result<i32> {
+    let mut message = Vec::new();
+    tokio::io::stdin().read_to_end(&mut message).await?;
+
+    let delivery = if recipients.is_empty() {
+        mail::Delivery::Stdout
+    } else {
+        mail::Delivery::Sendmail {
+            program: sendmail,
+            sender: sender.as_deref().unwrap_or(""),
+            recipients,
+        }
+    };
+    Ok(mail::filter(&message, &policy, &AnalysisOptions::default(), delivery).await?)
+}
+
+fn parse_threshold(value: &str) -> std::result::Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(score) if (0.0..=1.0).contains(&score) => Ok(score),
+        Ok(_) => Err("threshold must be between 0.0 and 1.0".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Loads the known-good hash sets given on the command line, if any
+fn load_known_good(paths: &[PathBuf]) -> Result<Option<Arc<KnownGoodSet>>> {
+    if paths.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Arc::new(KnownGoodSet::load(paths)?)))
+}
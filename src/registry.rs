@@ -0,0 +1,382 @@
+//! Detector registry
+//! Created: 2026-10-16 10:12:30 UTC
+//! Author: kartik4091
+//!
+//! The analysis is a sequence of named [`Detector`] passes. The built-in
+//! passes fill the typed sections of [`PdfAnalysis`] and are named after
+//! them (`fonts`, `xref_anomalies`, ...); library users can switch any of
+//! them off, and register their own detectors, whose findings are
+//! collected in `PdfAnalysis::detector_findings`. Their finding ids should
+//! start with the detector name (`<detector>.<kind>`).
+//!
+//! ```no_run
+//! use pdx::{
+//!     detectors::{Finding, Severity},
+//!     registry::{DetectorRegistry, Detector, DocumentContext},
+//! };
+//!
+//! struct Producer;
+//!
+//! impl Detector for Producer {
+//!     fn name(&self) -> &str {
+//!         "producer"
+//!     }
+//!
+//!     fn run(&self, _context: &DocumentContext<'_>) -> Vec<Finding> {
+//!         Vec::new()
+//!     }
+//! }
+//!
+//! let mut registry = DetectorRegistry::default();
+//! registry.disable("language").unwrap();
+//! registry.register(Producer).unwrap();
+//! ```
+
+use std::{collections::BTreeSet, fmt, sync::Arc};
+
+use lopdf::Document;
+use tracing::debug;
+
+use crate::{
+    detectors::{self, Finding},
+    error::{Error, Result},
+    PdfAnalysis,
+};
+
+/// A built-in pass filling one typed section of the analysis
+struct Builtin {
+    name: &'static str,
+    pass: Pass,
+}
+
+/// What a built-in pass reads, and so when it can run
+enum Pass {
+    /// Raw bytes; runs even when the document does not load
+    Bytes(fn(&[u8], Option<&Document>, &mut PdfAnalysis)),
+
+    /// Raw objects; skipped when the budget allows minimal depth only
+    Objects(fn(&[u8], Option<&Document>, &mut PdfAnalysis)),
+
+    /// The loaded document
+    Document(fn(&[u8], &Document, &mut PdfAnalysis)),
+}
+
+impl Detector for Builtin {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn analyze(&self, context: &DocumentContext<'_>, analysis: &mut PdfAnalysis) {
+        match (&self.pass, context.document) {
+            (Pass::Bytes(fill) | Pass::Objects(fill), document) => fill(context.data, document, analysis),
+            (Pass::Document(fill), Some(doc)) => fill(context.data, doc, analysis),
+            (Pass::Document(_), None) => {}
+        }
+    }
+}
+
+/// Built-in passes in run order, named after the report sections they
+/// fill; later passes may read the sections of earlier ones
+const BUILTINS: [Builtin; 36] = [
+    Builtin {
+        name: "carved_objects",
+        pass: Pass::Document(|data, doc, a| a.carved_objects = detectors::carving::carve_objects(data, doc)),
+    },
+    Builtin {
+        name: "xref_anomalies",
+        pass: Pass::Bytes(|data, _, a| a.xref_anomalies = detectors::xref_anomalies::detect(data)),
+    },
+    Builtin {
+        name: "hybrid_xref",
+        pass: Pass::Bytes(|data, _, a| a.hybrid_xref = detectors::hybrid_xref::check(data)),
+    },
+    Builtin { name: "signatures", pass: Pass::Objects(|data, _, a| a.signatures = detectors::signatures::check(data)) },
+    Builtin { name: "trailer_ids", pass: Pass::Bytes(|data, _, a| a.trailer_ids = detectors::trailer_id::check(data)) },
+    Builtin { name: "header", pass: Pass::Bytes(|data, doc, a| a.header = detectors::header::check(data, doc)) },
+    Builtin {
+        name: "trailing_data",
+        pass: Pass::Bytes(|data, _, a| a.trailing_data = detectors::trailing::detect(data)),
+    },
+    Builtin { name: "polyglots", pass: Pass::Bytes(|data, _, a| a.polyglots = detectors::polyglot::detect(data)) },
+    Builtin { name: "slack", pass: Pass::Bytes(|data, _, a| a.slack = detectors::slack::analyze(data)) },
+    Builtin {
+        name: "object_streams",
+        pass: Pass::Objects(|data, _, a| a.object_streams = detectors::object_streams::inspect(data)),
+    },
+    Builtin {
+        name: "stream_lengths",
+        pass: Pass::Objects(|data, _, a| a.stream_lengths = detectors::stream_length::check(data)),
+    },
+    Builtin {
+        name: "filter_anomalies",
+        pass: Pass::Objects(|data, _, a| a.filter_anomalies = detectors::filters::check(data)),
+    },
+    Builtin {
+        name: "obfuscated_tokens",
+        pass: Pass::Objects(|data, _, a| a.obfuscated_tokens = detectors::obfuscation::detect(data)),
+    },
+    Builtin {
+        name: "reader_features",
+        pass: Pass::Document(|_, doc, a| a.reader_features = detectors::reader_features::summarize(doc)),
+    },
+    Builtin { name: "actions", pass: Pass::Document(|_, doc, a| a.actions = detectors::actions::analyze(doc)) },
+    Builtin {
+        name: "annotations",
+        pass: Pass::Document(|_, doc, a| a.annotations = detectors::annotations::analyze(doc)),
+    },
+    Builtin { name: "forms", pass: Pass::Document(|_, doc, a| a.forms = detectors::forms::analyze(doc)) },
+    Builtin { name: "xfa", pass: Pass::Document(|_, doc, a| a.xfa = detectors::xfa::analyze(doc)) },
+    Builtin { name: "media", pass: Pass::Document(|_, doc, a| a.media = detectors::multimedia::analyze(doc)) },
+    Builtin { name: "layers", pass: Pass::Document(|_, doc, a| a.layers = detectors::layers::analyze(doc)) },
+    Builtin {
+        name: "invisible_text",
+        pass: Pass::Document(|_, doc, a| a.invisible_text = detectors::invisible_text::detect(doc)),
+    },
+    Builtin {
+        name: "redaction_failures",
+        pass: Pass::Document(|_, doc, a| a.redaction_failures = detectors::redaction::detect(doc)),
+    },
+    Builtin { name: "fonts", pass: Pass::Document(|data, doc, a| a.fonts = detectors::fonts::analyze(data, doc)) },
+    Builtin { name: "uris", pass: Pass::Document(|_, doc, a| a.uris = detectors::uris::extract(doc)) },
+    Builtin {
+        name: "phishing",
+        pass: Pass::Document(|_, doc, a| a.phishing = detectors::phishing::assess(doc, &a.uris, a.forms.as_ref())),
+    },
+    Builtin {
+        name: "exploits",
+        pass: Pass::Document(|_, doc, a| a.exploits = detectors::exploits::scan(doc, a.xfa.as_ref())),
+    },
+    Builtin { name: "shellcode", pass: Pass::Document(|_, doc, a| a.shellcode = detectors::shellcode::scan(doc)) },
+    Builtin {
+        name: "decoder_anomalies",
+        pass: Pass::Document(|_, doc, a| a.decoder_anomalies = detectors::decoders::check(doc)),
+    },
+    Builtin { name: "secrets", pass: Pass::Document(|_, doc, a| a.secrets = detectors::secrets::scan(doc)) },
+    Builtin { name: "language", pass: Pass::Document(|_, doc, a| a.language = detectors::language::analyze(doc)) },
+    Builtin {
+        name: "orphan_objects",
+        pass: Pass::Document(|_, doc, a| a.orphan_objects = detectors::orphans::detect(doc)),
+    },
+    Builtin { name: "page_tree", pass: Pass::Document(|_, doc, a| a.page_tree = detectors::page_tree::check(doc)) },
+    Builtin {
+        name: "stream_entropy",
+        pass: Pass::Document(|_, doc, a| a.stream_entropy = detectors::entropy::analyze(doc)),
+    },
+    Builtin {
+        name: "attachments",
+        pass: Pass::Document(|_, doc, a| a.attachments = detectors::attachments::inspect(doc)),
+    },
+    Builtin {
+        name: "linearization",
+        pass: Pass::Objects(|data, doc, a| a.linearization = detectors::linearization::check(data, doc)),
+    },
+    Builtin {
+        name: "dangling_references",
+        pass: Pass::Document(|data, doc, a| a.dangling_references = detectors::dangling::detect(data, doc)),
+    },
+];
+
+/// What a detector gets to look at
+#[derive(Debug, Clone, Copy)]
+pub struct DocumentContext<'a> {
+    /// Report label of the document
+    pub path: &'a str,
+
+    /// Raw file bytes
+    pub data: &'a [u8],
+
+    /// Loaded document, `None` if it could not (or may not) be loaded
+    pub document: Option<&'a Document>,
+}
+
+/// A named detection pass, built in or contributed by a library user
+pub trait Detector: Send + Sync {
+    /// Unique detector name, used in findings and to disable it
+    fn name(&self) -> &str;
+
+    /// Examines the document and returns its findings
+    fn run(&self, _context: &DocumentContext<'_>) -> Vec<Finding> {
+        Vec::new()
+    }
+
+    /// Records the pass in the analysis: by default its findings go to
+    /// `detector_findings`, while the built-in passes fill their sections
+    fn analyze(&self, context: &DocumentContext<'_>, analysis: &mut PdfAnalysis) {
+        analysis.detector_findings.extend(self.run(context));
+    }
+}
+
+/// Enabled built-in passes and registered detectors
+#[derive(Clone, Default)]
+pub struct DetectorRegistry {
+    detectors: Vec<Arc<dyn Detector>>,
+    disabled: BTreeSet<String>,
+}
+
+impl fmt::Debug for DetectorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let detectors: Vec<&str> = self.detectors.iter().map(|d| d.name()).collect();
+        f.debug_struct("DetectorRegistry")
+            .field("detectors", &detectors)
+            .field("disabled", &self.disabled)
+            .finish()
+    }
+}
+
+impl DetectorRegistry {
+    /// Adds a detector; names must not clash with any other detector
+    pub fn register(&mut self, detector: impl Detector + 'static) -> Result<()> {
+        let name = detector.name();
+        if self.is_known(name) {
+            return Err(Error::Analysis(format!("Detector {} is already registered", name)));
+        }
+        self.detectors.push(Arc::new(detector));
+        Ok(())
+    }
+
+    /// Switches off a built-in or registered detector
+    pub fn disable(&mut self, name: &str) -> Result<()> {
+        if !self.is_known(name) {
+            return Err(Error::Analysis(format!("Unknown detector: {}", name)));
+        }
+        self.disabled.insert(name.to_string());
+        Ok(())
+    }
+
+    /// Switches a detector back on
+    pub fn enable(&mut self, name: &str) {
+        self.disabled.remove(name);
+    }
+
+    /// Whether the named detector runs
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.contains(name)
+    }
+
+    /// Disabled detectors, by name
+    pub fn disabled(&self) -> impl Iterator<Item = &str> {
+        self.disabled.iter().map(String::as_str)
+    }
+
+    /// Names of the registered detectors
+    pub fn registered(&self) -> impl Iterator<Item = &str> {
+        self.detectors.iter().map(|d| d.name())
+    }
+
+    /// Every detector name, built-in first
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        BUILTINS.iter().map(|builtin| builtin.name).chain(self.registered())
+    }
+
+    fn is_known(&self, name: &str) -> bool {
+        self.names().any(|known| known == name)
+    }
+
+    /// Runs the enabled built-in passes but those in `skip`, then the
+    /// registered detectors. Passes over raw objects are skipped unless
+    /// `object_passes` is set.
+    pub(crate) fn analyze(
+        &self,
+        context: &DocumentContext<'_>,
+        object_passes: bool,
+        skip: &[&str],
+        analysis: &mut PdfAnalysis,
+    ) {
+        let builtins = BUILTINS
+            .iter()
+            .filter(|builtin| object_passes || !matches!(builtin.pass, Pass::Objects(_)))
+            .filter(|builtin| !skip.contains(&builtin.name))
+            .map(|builtin| builtin as &dyn Detector);
+        let registered = self.detectors.iter().map(|detector| detector.as_ref());
+        for detector in builtins.chain(registered).filter(|detector| self.is_enabled(detector.name())) {
+            detector.analyze(context, analysis);
+        }
+        debug!("{} findings from registered detectors", analysis.detector_findings.len());
+    }
+
+    /// Runs the enabled registered detectors
+    pub fn run(&self, context: &DocumentContext<'_>) -> Vec<Finding> {
+        let findings: Vec<Finding> = self
+            .detectors
+            .iter()
+            .filter(|detector| self.is_enabled(detector.name()))
+            .flat_map(|detector| detector.run(context))
+            .collect();
+        debug!("{} findings from registered detectors", findings.len());
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detectors::{RiskCategory, Severity};
+    use crate::document::fixtures;
+    use crate::{AnalysisOptions, Analyzer, PdfAnalyzer};
+    use tempfile::NamedTempFile;
+
+    struct PageCount;
+
+    impl Detector for PageCount {
+        fn name(&self) -> &str {
+            "page_count"
+        }
+
+        fn run(&self, context: &DocumentContext<'_>) -> Vec<Finding> {
+            let pages = context.document.map_or(0, |doc| doc.get_pages().len());
+            vec![Finding::new("page_count.total", Severity::Info, RiskCategory::Custom, format!("{} pages", pages))]
+        }
+    }
+
+    #[test]
+    fn test_registered_detector_runs_until_disabled() {
+        let doc = fixtures::document();
+        let context = DocumentContext { path: "test.pdf", data: &[], document: Some(&doc) };
+
+        let mut registry = DetectorRegistry::default();
+        registry.register(PageCount).unwrap();
+        let findings = registry.run(&context);
+        assert_eq!(findings[0].title, "1 pages");
+        assert_eq!(findings[0].detector(), "page_count");
+
+        registry.disable("page_count").unwrap();
+        assert!(registry.run(&context).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_builtin_passes_can_be_disabled() {
+        let temp = NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), fixtures::simple_pdf()).unwrap();
+        let path = temp.path();
+        let analyze = |detectors| {
+            let options = AnalysisOptions { detectors, ..Default::default() };
+            async move { PdfAnalyzer::with_options(path, options).unwrap().analyze().await.unwrap() }
+        };
+
+        let mut registry = DetectorRegistry::default();
+        assert!(analyze(registry.clone()).await.header.version.is_some());
+
+        registry.disable("header").unwrap();
+        registry.register(PageCount).unwrap();
+        let analysis = analyze(registry.clone()).await;
+        assert!(analysis.header.version.is_none());
+        assert_eq!(analysis.detector_findings[0].title, "1 pages");
+        assert!(registry.disable("no_such_pass").is_err());
+    }
+
+    #[test]
+    fn test_names_must_be_unique() {
+        struct Shadow;
+        impl Detector for Shadow {
+            fn name(&self) -> &str {
+                "fonts"
+            }
+            fn run(&self, _context: &DocumentContext<'_>) -> Vec<Finding> {
+                Vec::new()
+            }
+        }
+
+        let mut registry = DetectorRegistry::default();
+        assert!(registry.register(Shadow).is_err());
+    }
+}
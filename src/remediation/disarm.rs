@@ -0,0 +1,322 @@
+//! Content disarm of executable behavior
+//! Created: 2026-10-16 11:09:58 UTC
+//! Author: kartik4091
+//!
+//! A narrower cut than [`super::sanitize`], meant for mail gateways that
+//! must deliver a usable document: only what runs code or acts on its own
+//! is removed — JavaScript, launch, form submission, data import and rich
+//! media execution actions wherever they are attached, the document-level
+//! script tree, and the `<script>` elements of XFA templates. Harmless
+//! actions such as internal navigation or links stay, as do pages,
+//! annotations, form fields with their values and appearances, attachments
+//! and metadata. Object numbers are kept; only the removed actions and
+//! what nothing else references disappear.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use regex::bytes::Regex;
+use tracing::{debug, instrument};
+
+use super::{redact::flate_stream, write, Remediation, Rewrite};
+use crate::{
+    detectors::reader_features::ReaderFeature,
+    document::{name_value, resolve, stream_data},
+    error::{Error, Result},
+    PdfAnalysis,
+};
+
+/// Action types that run code or reach outside the reader, and how they are reported
+const EXECUTABLE: [(&[u8], &str); 5] = [
+    (b"JavaScript", "JavaScript action"),
+    (b"Launch", "launch action"),
+    (b"SubmitForm", "form submission"),
+    (b"ImportData", "data import"),
+    (b"RichMediaExecute", "rich media command"),
+];
+
+/// Removes executable behavior and keeps everything else
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Disarm;
+
+impl Remediation for Disarm {
+    fn name(&self) -> &'static str {
+        "disarm"
+    }
+
+    #[instrument(skip(self, data))]
+    fn apply(&self, data: &[u8]) -> Result<Rewrite> {
+        let mut doc = Document::load_mem(data).map_err(|e| Error::Pdf(format!("Failed to load input: {}", e)))?;
+        let actions: BTreeMap<ObjectId, &'static str> = doc
+            .objects
+            .iter()
+            .filter_map(|(&id, object)| executable(object.as_dict().ok()?).map(|reason| (id, reason)))
+            .collect();
+
+        let mut reasons = BTreeMap::new();
+        let ids: Vec<ObjectId> = doc
+            .objects
+            .keys()
+            .filter(|id| !actions.contains_key(id))
+            .copied()
+            .collect();
+        for id in ids {
+            let mut object = doc.objects[&id].clone();
+            let mut removed = BTreeSet::new();
+            disarm(&actions, &mut object, b"", &mut removed);
+            if !removed.is_empty() {
+                doc.objects.insert(id, object);
+                reasons.insert(
+                    id,
+                    format!("Removed {}", removed.into_iter().collect::<Vec<_>>().join(", ")),
+                );
+            }
+        }
+        strip_xfa_scripts(&mut doc, &mut reasons)?;
+
+        if reasons.is_empty() {
+            return Ok(Rewrite {
+                output: data.to_vec(),
+                reasons,
+            });
+        }
+        for id in doc.prune_objects() {
+            let reason = actions.get(&id).copied().unwrap_or("unreachable object");
+            reasons.insert(id, format!("Removed {}", reason));
+        }
+
+        let output = write(&mut doc)?;
+        debug!("Disarming touched {} objects", reasons.len());
+        Ok(Rewrite { output, reasons })
+    }
+
+    fn targets(&self, analysis: &PdfAnalysis) -> Vec<String> {
+        analysis
+            .reader_features
+            .features
+            .iter()
+            .filter(|usage| {
+                matches!(
+                    usage.feature,
+                    ReaderFeature::JavaScript | ReaderFeature::Launch | ReaderFeature::FormSubmission
+                )
+            })
+            .map(|usage| {
+                if usage.auto_triggered {
+                    format!("Auto-triggered {:?}", usage.feature)
+                } else {
+                    format!("{:?} in use", usage.feature)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Why an action dictionary is executable
+fn executable(dict: &Dictionary) -> Option<&'static str> {
+    let kind = name_value(dict, b"S");
+    if let Some(&(_, reason)) = EXECUTABLE.iter().find(|(name, _)| kind == Some(*name)) {
+        return Some(reason);
+    }
+    // Renditions and other actions may carry a script of their own
+    dict.has(b"JS").then_some("JavaScript action")
+}
+
+/// Removes executable actions below `object`, which is stored under `key`,
+/// noting each removal as `/Key (reason)`
+fn disarm(actions: &BTreeMap<ObjectId, &'static str>, object: &mut Object, key: &[u8], removed: &mut BTreeSet<String>) {
+    let unwanted = |value: &Object| match value {
+        Object::Reference(id) => actions.get(id).copied(),
+        Object::Dictionary(dict) => executable(dict),
+        _ => None,
+    };
+    match object {
+        Object::Dictionary(dict) => disarm_dictionary(actions, dict, removed),
+        Object::Stream(stream) => disarm_dictionary(actions, &mut stream.dict, removed),
+        Object::Array(items) => {
+            items.retain(|item| match unwanted(item) {
+                Some(reason) => {
+                    removed.insert(format!("{} entry ({})", label(key), reason));
+                    false
+                }
+                None => true,
+            });
+            for item in items.iter_mut() {
+                disarm(actions, item, key, removed);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn disarm_dictionary(
+    actions: &BTreeMap<ObjectId, &'static str>,
+    dict: &mut Dictionary,
+    removed: &mut BTreeSet<String>,
+) {
+    let keys: Vec<Vec<u8>> = dict.iter().map(|(key, _)| key.clone()).collect();
+    for key in keys {
+        let reason = match (key.as_slice(), dict.get(&key)) {
+            (b"JavaScript", _) => Some("document-level JavaScript"),
+            (_, Ok(Object::Reference(id))) => actions.get(id).copied(),
+            (_, Ok(Object::Dictionary(value))) => executable(value),
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            dict.remove(&key);
+            removed.insert(format!("{} ({})", label(&key), reason));
+            continue;
+        }
+        if let Ok(value) = dict.get_mut(&key) {
+            disarm(actions, value, &key, removed);
+        }
+        // Additional-action dictionaries emptied by the pass go as well
+        if key == b"AA" && matches!(dict.get(&key), Ok(Object::Dictionary(aa)) if aa.is_empty()) {
+            dict.remove(&key);
+        }
+    }
+}
+
+/// Removes `<script>` elements from the XFA packets of the form, which
+/// keeps the template layout and the datasets holding the field values
+pub(super) fn strip_xfa_scripts(doc: &mut Document, reasons: &mut BTreeMap<ObjectId, String>) -> Result<()> {
+    let script = Regex::new(r"(?is)<(?:\w+:)?script\b[^>]*/>|<(?:\w+:)?script\b.*?</(?:\w+:)?script\s*>")
+        .expect("valid XFA script pattern");
+    let packets: Vec<ObjectId> = match doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"AcroForm").ok())
+        .and_then(|form| resolve(doc, form).as_dict().ok())
+        .and_then(|form| form.get(b"XFA").ok())
+    {
+        Some(Object::Reference(id)) => vec![*id],
+        Some(Object::Array(items)) => items.iter().filter_map(|item| item.as_reference().ok()).collect(),
+        _ => Vec::new(),
+    };
+
+    for id in packets {
+        let Ok(stream) = doc.get_object(id).and_then(Object::as_stream) else {
+            continue;
+        };
+        let data = stream_data(stream)?;
+        let count = script.find_iter(&data).count();
+        if count == 0 {
+            continue;
+        }
+        let mut dict = stream.dict.clone();
+        dict.remove(b"DecodeParms");
+        let cleaned = flate_stream(dict, &script.replace_all(&data, &b""[..]))?;
+        doc.objects.insert(id, Object::Stream(cleaned));
+        reasons.insert(id, format!("Removed {} XFA scripts", count));
+    }
+    Ok(())
+}
+
+fn label(key: &[u8]) -> String {
+    if key.is_empty() {
+        "array".to_string()
+    } else {
+        format!("/{}", String::from_utf8_lossy(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures::{self, CATALOG_ID, PAGE_ID};
+    use lopdf::{dictionary, Stream};
+
+    /// Fixture with a form field carrying a value, a validation script and
+    /// a submit button, an auto-run script, a go-to open action and an XFA template
+    fn armed() -> (Document, ObjectId, ObjectId, ObjectId) {
+        let mut doc = fixtures::document();
+        let script = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.launchURL('https://example.com')"),
+        });
+        let submit = doc.add_object(dictionary! {
+            "S" => "SubmitForm",
+            "F" => Object::string_literal("https://example.com/collect"),
+        });
+        let field = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "FT" => "Tx",
+            "T" => Object::string_literal("amount"),
+            "V" => Object::string_literal("100"),
+            "Rect" => vec![0.into(), 0.into(), 100.into(), 20.into()],
+            "AA" => dictionary! {
+                "V" => dictionary! { "S" => "JavaScript", "JS" => Object::string_literal("event.rc = true") },
+            },
+        });
+        let button = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "FT" => "Btn",
+            "T" => Object::string_literal("send"),
+            "Rect" => vec![0.into(), 30.into(), 100.into(), 50.into()],
+            "A" => submit,
+        });
+        let template = doc.add_object(Stream::new(
+            dictionary! {},
+            b"<template><field name=\"amount\"><event><script>xfa.host.gotoURL('x')</script></event></field>\
+              <xfa:script src=\"x.js\"/></template>"
+                .to_vec(),
+        ));
+        doc.get_dictionary_mut(PAGE_ID)
+            .unwrap()
+            .set("Annots", vec![field.into(), button.into()]);
+        let catalog = doc.get_dictionary_mut(CATALOG_ID).unwrap();
+        catalog.set("OpenAction", vec![PAGE_ID.into(), "Fit".into()]);
+        catalog.set(
+            "AA",
+            dictionary! { "WC" => script, "DP" => dictionary! { "S" => "Named", "N" => "Print" } },
+        );
+        catalog.set(
+            "AcroForm",
+            dictionary! {
+                "Fields" => vec![field.into(), button.into()],
+                "XFA" => vec![Object::string_literal("template"), template.into()],
+            },
+        );
+        (doc, field, submit, template)
+    }
+
+    #[test]
+    fn test_disarm_keeps_form_data() {
+        let (doc, field, submit, template) = armed();
+        let rewrite = Disarm.apply(&fixtures::save(doc)).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        let catalog = doc.get_dictionary(CATALOG_ID).unwrap();
+        assert!(catalog.has(b"OpenAction"));
+        let aa = catalog.get(b"AA").unwrap().as_dict().unwrap();
+        assert!(!aa.has(b"WC") && aa.has(b"DP"));
+        let widget = doc.get_dictionary(field).unwrap();
+        assert!(!widget.has(b"AA"));
+        assert_eq!(widget.get(b"V").unwrap().as_str().unwrap(), b"100");
+        assert!(!doc.objects.contains_key(&submit));
+        assert_eq!(rewrite.reasons[&submit], "Removed form submission");
+        assert_eq!(rewrite.reasons[&field], "Removed /V (JavaScript action)");
+
+        let xfa = stream_data(doc.get_object(template).unwrap().as_stream().unwrap()).unwrap();
+        assert_eq!(
+            String::from_utf8(xfa).unwrap(),
+            "<template><field name=\"amount\"><event></event></field></template>"
+        );
+        assert_eq!(rewrite.reasons[&template], "Removed 2 XFA scripts");
+    }
+
+    #[test]
+    fn test_harmless_document_is_untouched() {
+        let mut doc = fixtures::document();
+        doc.get_dictionary_mut(CATALOG_ID).unwrap().set(
+            "OpenAction",
+            dictionary! { "S" => "GoTo", "D" => vec![PAGE_ID.into(), "Fit".into()] },
+        );
+        let data = fixtures::save(doc);
+        let rewrite = Disarm.apply(&data).unwrap();
+        assert_eq!(rewrite.output, data);
+        assert!(rewrite.reasons.is_empty());
+    }
+}
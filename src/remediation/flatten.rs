@@ -0,0 +1,491 @@
+//! Flattening of interactive forms into page content
+//! Created: 2026-10-16 11:07:28 UTC
+//! Author: kartik4091
+//!
+//! Paints every visible widget annotation into the content of its page and
+//! removes the form: the widget's normal appearance (the one selected by
+//! /AS for checkboxes and radio buttons) is placed as a form XObject over
+//! the annotation rectangle, and text and choice fields without an
+//! appearance get one generated from their current value in Helvetica.
+//! The widgets leave /Annots, /AcroForm leaves the catalog, and the field
+//! dictionaries are dropped with everything only they referenced — field
+//! scripts and submit actions included. Other annotations stay.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{
+    content::{Content, Operation},
+    dictionary, Dictionary, Document, Object, ObjectId, Stream,
+};
+use tracing::{debug, instrument};
+
+use super::{
+    redact::{flate_stream, own_resources, real},
+    write, Remediation, Rewrite,
+};
+use crate::{
+    document::{
+        array_references,
+        content::{transform_box, Matrix, IDENTITY},
+        decode_text, name_value, number, rectangle, resolve,
+    },
+    error::{Error, Result},
+    PdfAnalysis,
+};
+
+/// Deepest field hierarchy followed
+const MAX_DEPTH: usize = 32;
+
+/// Annotation flags hiding a widget from display (PDF 32000-1, 12.5.3)
+const FLAG_HIDDEN: i64 = 1 << 1;
+const FLAG_NO_VIEW: i64 = 1 << 5;
+
+/// Font size used when /DA asks for auto-sizing or cannot be read
+const MAX_FONT_SIZE: f64 = 12.0;
+
+/// Inset of generated text from the field border
+const PADDING: f64 = 2.0;
+
+/// Burns form fields into page content and removes the form
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlattenForms;
+
+impl Remediation for FlattenForms {
+    fn name(&self) -> &'static str {
+        "flatten-forms"
+    }
+
+    #[instrument(skip(self, data))]
+    fn apply(&self, data: &[u8]) -> Result<Rewrite> {
+        let mut doc = Document::load_mem(data).map_err(|e| Error::Pdf(format!("Failed to load input: {}", e)))?;
+        let root = doc
+            .trailer
+            .get(b"Root")
+            .and_then(Object::as_reference)
+            .map_err(|e| Error::Pdf(format!("Document has no catalog: {}", e)))?;
+
+        let mut fields = BTreeSet::new();
+        if let Some(form) = doc
+            .get_dictionary(root)
+            .ok()
+            .and_then(|catalog| catalog.get(b"AcroForm").ok())
+            .and_then(|form| resolve(&doc, form).as_dict().ok())
+        {
+            let mut pending: Vec<(ObjectId, usize)> = array_references(&doc, form.get(b"Fields").ok())
+                .into_iter()
+                .map(|id| (id, 0))
+                .collect();
+            while let Some((id, depth)) = pending.pop() {
+                if depth > MAX_DEPTH || !fields.insert(id) {
+                    continue;
+                }
+                let kids = doc.get_dictionary(id).ok().and_then(|field| field.get(b"Kids").ok());
+                pending.extend(array_references(&doc, kids).into_iter().map(|kid| (kid, depth + 1)));
+            }
+        }
+
+        let mut reasons = BTreeMap::new();
+        let mut widgets = BTreeSet::new();
+        for (page_number, page) in doc.get_pages() {
+            flatten_page(&mut doc, page_number, page, &mut widgets, &mut reasons)?;
+        }
+
+        let had_form = doc
+            .get_dictionary_mut(root)
+            .map(|catalog| catalog.remove(b"AcroForm").is_some())
+            .unwrap_or(false);
+        if !had_form && widgets.is_empty() {
+            return Ok(Rewrite {
+                output: data.to_vec(),
+                reasons,
+            });
+        }
+        if had_form {
+            reasons.insert(root, "Removed /AcroForm".to_string());
+        }
+        for id in doc.prune_objects() {
+            let reason = if widgets.contains(&id) {
+                "Flattened widget annotation"
+            } else if fields.contains(&id) {
+                "Removed form field"
+            } else {
+                "Removed object only the form referenced"
+            };
+            reasons.insert(id, reason.to_string());
+        }
+
+        let output = write(&mut doc)?;
+        debug!("Flattened {} widgets", widgets.len());
+        Ok(Rewrite { output, reasons })
+    }
+
+    fn targets(&self, analysis: &PdfAnalysis) -> Vec<String> {
+        analysis
+            .forms
+            .iter()
+            .flat_map(|form| &form.fields)
+            .map(|field| format!("Form field {}", field.name))
+            .collect()
+    }
+}
+
+/// Paints the visible widgets of a page and removes them from /Annots
+fn flatten_page(
+    doc: &mut Document,
+    page_number: u32,
+    page: ObjectId,
+    widgets: &mut BTreeSet<ObjectId>,
+    reasons: &mut BTreeMap<ObjectId, String>,
+) -> Result<()> {
+    let annots: Vec<Object> = doc
+        .get_dictionary(page)
+        .ok()
+        .and_then(|dict| dict.get(b"Annots").ok())
+        .and_then(|a| resolve(doc, a).as_array().ok())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut kept = Vec::new();
+    let mut placed = Vec::new();
+    let mut flattened = 0;
+    for entry in annots {
+        let Ok(annot) = resolve(doc, &entry).as_dict() else {
+            kept.push(entry);
+            continue;
+        };
+        if name_value(annot, b"Subtype") != Some(b"Widget") {
+            kept.push(entry);
+            continue;
+        }
+        flattened += 1;
+        if let Ok(id) = entry.as_reference() {
+            widgets.insert(id);
+        }
+
+        let flags = annot.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+        let Some(rect) = annot.get(b"Rect").ok().and_then(|r| rectangle(doc, r)) else {
+            continue;
+        };
+        if flags & (FLAG_HIDDEN | FLAG_NO_VIEW) != 0 || rect[2] <= rect[0] || rect[3] <= rect[1] {
+            continue;
+        }
+        let appearance = match appearance(doc, annot) {
+            Some(id) => id,
+            None => match generated(doc, annot, rect)? {
+                Some(stream) => {
+                    let id = doc.add_object(stream);
+                    reasons.insert(id, format!("Generated field appearance on page {}", page_number));
+                    id
+                }
+                None => continue,
+            },
+        };
+        placed.push((appearance, rect));
+    }
+    if flattened == 0 {
+        return Ok(());
+    }
+
+    let mut operations = Vec::new();
+    let mut xobjects = Vec::new();
+    for (index, (id, rect)) in placed.into_iter().enumerate() {
+        let Ok(stream) = doc.get_object_mut(id).and_then(Object::as_stream_mut) else {
+            continue;
+        };
+        // Appearance streams may leave out the XObject keys
+        stream.dict.set("Type", "XObject");
+        stream.dict.set("Subtype", "Form");
+        let bbox = match stream.dict.get(b"BBox") {
+            Ok(Object::Array(coords)) => match coords.iter().filter_map(number).collect::<Vec<_>>()[..] {
+                [x0, y0, x1, y1] => [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)],
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let matrix = match stream.dict.get(b"Matrix") {
+            Ok(Object::Array(values)) => {
+                Matrix::try_from(values.iter().filter_map(number).collect::<Vec<_>>().as_slice()).unwrap_or(IDENTITY)
+            }
+            _ => IDENTITY,
+        };
+        let name = format!("PdxFlat{}", index);
+        operations.extend([
+            Operation::new("q", vec![]),
+            Operation::new("cm", fit(&matrix, bbox, rect).into_iter().map(real).collect()),
+            Operation::new("Do", vec![Object::Name(name.clone().into_bytes())]),
+            Operation::new("Q", vec![]),
+        ]);
+        xobjects.push((name, id));
+    }
+
+    let mut resources = own_resources(doc, page);
+    let mut entries = resources
+        .get(b"XObject")
+        .and_then(Object::as_dict)
+        .cloned()
+        .unwrap_or_else(|_| Dictionary::new());
+    for (name, id) in &xobjects {
+        entries.set(name.as_str(), *id);
+    }
+    resources.set("XObject", entries);
+
+    // The original content runs in its own graphics state so that the
+    // appearances are placed in default user space
+    let encoded = Content { operations }
+        .encode()
+        .map_err(|e| Error::Pdf(format!("Failed to encode content of page {}: {}", page_number, e)))?;
+    let save = doc.add_object(Stream::new(dictionary! {}, b"q\n".to_vec()));
+    let mut restore = b"Q\n".to_vec();
+    restore.extend_from_slice(&encoded);
+    let fields = doc.add_object(flate_stream(dictionary! {}, &restore)?);
+    reasons.insert(fields, format!("Form fields of page {}", page_number));
+    let mut contents = vec![Object::Reference(save)];
+    contents.extend(doc.get_page_contents(page).into_iter().map(Object::Reference));
+    contents.push(Object::Reference(fields));
+
+    let dict = doc
+        .get_dictionary_mut(page)
+        .map_err(|e| Error::Pdf(format!("Page {} is not a dictionary: {}", page_number, e)))?;
+    dict.set("Contents", contents);
+    dict.set("Resources", resources);
+    if kept.is_empty() {
+        dict.remove(b"Annots");
+    } else {
+        dict.set("Annots", kept);
+    }
+    reasons.insert(
+        page,
+        format!(
+            "Flattened {} widgets on page {} ({} painted)",
+            flattened,
+            page_number,
+            xobjects.len()
+        ),
+    );
+    Ok(())
+}
+
+/// Normal appearance stream of a widget, in its current state for
+/// checkboxes and radio buttons
+fn appearance(doc: &Document, annot: &Dictionary) -> Option<ObjectId> {
+    let normal = resolve(doc, annot.get(b"AP").ok()?).as_dict().ok()?.get(b"N").ok()?;
+    match resolve(doc, normal) {
+        Object::Stream(_) => normal.as_reference().ok(),
+        Object::Dictionary(states) => {
+            let state = name_value(annot, b"AS")?;
+            states.get(state).ok()?.as_reference().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Appearance painting the current value of a text or choice field that has none
+fn generated(doc: &Document, annot: &Dictionary, [x0, y0, x1, y1]: [f64; 4]) -> Result<Option<Stream>> {
+    if !matches!(
+        inherited(doc, annot, b"FT").and_then(|t| t.as_name().ok()),
+        Some(b"Tx") | Some(b"Ch")
+    ) {
+        return Ok(None);
+    }
+    let value = match inherited(doc, annot, b"V") {
+        Some(Object::String(bytes, _)) => decode_text(bytes),
+        Some(Object::Array(items)) => match items.first().map(|item| resolve(doc, item)) {
+            Some(Object::String(bytes, _)) => decode_text(bytes),
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let (width, height) = (x1 - x0, y1 - y0);
+    let size = font_size(doc, annot)
+        .filter(|&size| size > 0.0)
+        .unwrap_or(MAX_FONT_SIZE)
+        .min(height - PADDING);
+    // Glyphs outside WinAnsiEncoding cannot be shown with the standard font
+    let text: Vec<u8> = value
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+        .collect();
+    let operations = vec![
+        Operation::new("BMC", vec![Object::Name(b"Tx".to_vec())]),
+        Operation::new("q", vec![]),
+        Operation::new("re", vec![real(0.0), real(0.0), real(width), real(height)]),
+        Operation::new("W", vec![]),
+        Operation::new("n", vec![]),
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec![Object::Name(b"Helv".to_vec()), real(size)]),
+        Operation::new("g", vec![real(0.0)]),
+        Operation::new("Td", vec![real(PADDING), real((height - size) / 2.0 + size * 0.2)]),
+        Operation::new("Tj", vec![Object::string_literal(text)]),
+        Operation::new("ET", vec![]),
+        Operation::new("Q", vec![]),
+        Operation::new("EMC", vec![]),
+    ];
+    let content = Content { operations }
+        .encode()
+        .map_err(|e| Error::Pdf(format!("Failed to encode field appearance: {}", e)))?;
+    let dict = dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Form",
+        "BBox" => vec![real(0.0), real(0.0), real(width), real(height)],
+        "Resources" => dictionary! {
+            "Font" => dictionary! {
+                "Helv" => dictionary! {
+                    "Type" => "Font",
+                    "Subtype" => "Type1",
+                    "BaseFont" => "Helvetica",
+                    "Encoding" => "WinAnsiEncoding",
+                },
+            },
+        },
+    };
+    Ok(Some(flate_stream(dict, &content)?))
+}
+
+/// Font size of the default appearance (/DA) of a field, its ancestors or the form
+fn font_size(doc: &Document, annot: &Dictionary) -> Option<f64> {
+    let da = inherited(doc, annot, b"DA").or_else(|| {
+        let catalog = doc.catalog().ok()?;
+        let form = resolve(doc, catalog.get(b"AcroForm").ok()?).as_dict().ok()?;
+        form.get(b"DA").ok()
+    })?;
+    let Object::String(bytes, _) = resolve(doc, da) else {
+        return None;
+    };
+    let text = String::from_utf8_lossy(bytes);
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let tf = tokens.iter().position(|&token| token == "Tf")?;
+    tokens.get(tf.checked_sub(1)?)?.parse().ok()
+}
+
+/// Value of a field attribute on a widget or the nearest field above it
+fn inherited<'a>(doc: &'a Document, annot: &'a Dictionary, key: &[u8]) -> Option<&'a Object> {
+    let mut node = annot;
+    for _ in 0..MAX_DEPTH {
+        if let Ok(value) = node.get(key) {
+            return Some(resolve(doc, value));
+        }
+        node = resolve(doc, node.get(b"Parent").ok()?).as_dict().ok()?;
+    }
+    None
+}
+
+/// Transformation placing an appearance's bounding box, as mapped by its
+/// /Matrix, onto the annotation rectangle (PDF 32000-1, 12.5.5)
+pub(super) fn fit(matrix: &Matrix, bbox: [f64; 4], [x0, y0, x1, y1]: [f64; 4]) -> Matrix {
+    let [bx0, by0, bx1, by1] = transform_box(matrix, bbox);
+    let sx = if bx1 > bx0 { (x1 - x0) / (bx1 - bx0) } else { 1.0 };
+    let sy = if by1 > by0 { (y1 - y0) / (by1 - by0) } else { 1.0 };
+    [sx, 0.0, 0.0, sy, x0 - sx * bx0, y0 - sy * by0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{
+        fixtures::{self, CATALOG_ID, PAGE_ID},
+        stream_data,
+    };
+
+    /// Fixture with a checked checkbox carrying appearances, a text field
+    /// without one, a hidden text field and a link
+    fn with_form() -> (Document, ObjectId, ObjectId) {
+        let mut doc = fixtures::document();
+        let on = doc.add_object(Stream::new(
+            dictionary! { "BBox" => vec![0.into(), 0.into(), 10.into(), 10.into()] },
+            b"0 0 10 10 re f".to_vec(),
+        ));
+        let off = doc.add_object(Stream::new(
+            dictionary! { "BBox" => vec![0.into(), 0.into(), 10.into(), 10.into()] },
+            Vec::new(),
+        ));
+        let script = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.alert(1)"),
+        });
+        let checkbox = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "FT" => "Btn",
+            "T" => Object::string_literal("approved"),
+            "V" => "Yes",
+            "AS" => "Yes",
+            "Rect" => vec![100.into(), 100.into(), 120.into(), 120.into()],
+            "AP" => dictionary! { "N" => dictionary! { "Yes" => on, "Off" => off } },
+            "AA" => dictionary! { "K" => script },
+        });
+        let name = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "FT" => "Tx",
+            "T" => Object::string_literal("name"),
+            "V" => Object::string_literal("Jane Doe"),
+            "DA" => Object::string_literal("/Helv 0 Tf 0 g"),
+            "Rect" => vec![100.into(), 200.into(), 300.into(), 220.into()],
+        });
+        let hidden = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "FT" => "Tx",
+            "T" => Object::string_literal("secret"),
+            "V" => Object::string_literal("do not print"),
+            "F" => FLAG_HIDDEN,
+            "Rect" => vec![100.into(), 300.into(), 300.into(), 320.into()],
+        });
+        let link = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+        });
+        doc.get_dictionary_mut(PAGE_ID)
+            .unwrap()
+            .set("Annots", vec![checkbox.into(), name.into(), hidden.into(), link.into()]);
+        doc.get_dictionary_mut(CATALOG_ID).unwrap().set(
+            "AcroForm",
+            dictionary! { "Fields" => vec![checkbox.into(), name.into(), hidden.into()] },
+        );
+        (doc, on, script)
+    }
+
+    #[test]
+    fn test_flatten_paints_values() {
+        let (doc, on, script) = with_form();
+        let rewrite = FlattenForms.apply(&fixtures::save(doc)).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        assert!(!doc.get_dictionary(CATALOG_ID).unwrap().has(b"AcroForm"));
+        let page = doc.get_dictionary(PAGE_ID).unwrap();
+        assert_eq!(page.get(b"Annots").unwrap().as_array().unwrap().len(), 1);
+        let resources = page.get(b"Resources").unwrap().as_dict().unwrap();
+        let xobjects = resources.get(b"XObject").unwrap().as_dict().unwrap();
+        assert_eq!(xobjects.len(), 2);
+        assert_eq!(xobjects.get(b"PdxFlat0").unwrap().as_reference().unwrap(), on);
+
+        let generated = xobjects.get(b"PdxFlat1").unwrap().as_reference().unwrap();
+        let stream = doc.get_object(generated).unwrap().as_stream().unwrap();
+        let content = Content::decode(&stream_data(stream).unwrap()).unwrap();
+        let operand = |operator: &str| {
+            let operation = content.operations.iter().find(|op| op.operator == operator).unwrap();
+            operation.operands.last().cloned().unwrap()
+        };
+        assert_eq!(operand("Tj").as_str().unwrap(), b"Jane Doe");
+        assert_eq!(number(&operand("Tf")), Some(MAX_FONT_SIZE));
+        assert!(!doc.objects.contains_key(&script));
+        assert_eq!(rewrite.reasons[&script], "Removed object only the form referenced");
+        assert_eq!(rewrite.reasons[&PAGE_ID], "Flattened 3 widgets on page 1 (2 painted)");
+    }
+
+    #[test]
+    fn test_document_without_form_is_untouched() {
+        let data = fixtures::simple_pdf();
+        let rewrite = FlattenForms.apply(&data).unwrap();
+        assert_eq!(rewrite.output, data);
+        assert!(rewrite.reasons.is_empty());
+    }
+}
@@ -0,0 +1,567 @@
+//! Document remediation and dry-run impact preview
+//! Created: 2026-10-16 09:01:28 UTC
+//! Author: kartik4091
+//!
+//! Every mutating operation (sanitize, disarm, scrub, redact, flatten) implements
+//! [`Remediation`]: it rewrites the document in memory and records why each
+//! object was touched. [`run`] is the only place output is written, so a dry
+//! run performs the very same rewrite — yielding the exact object changes
+//! and output hash — and simply stops before touching disk.
+//!
+//! Every run is verified by re-analyzing the output: the findings the
+//! operation targets must be gone, and no new findings may appear. The
+//! output is analyzed from a temporary file next to its destination and
+//! only renamed into place once it passes, so a failed run leaves nothing
+//! behind.
+//!
+//! Input and output are held to the same resource limits as an analysis:
+//! a run happens within one [`limits::scope`], and neither file is loaded
+//! when [`limits::check`] finds that loading it would be unsafe.
+
+pub mod disarm;
+pub mod flatten;
+pub mod normalize;
+pub mod redact;
+pub mod repair;
+pub mod sanitize;
+pub mod scrub;
+pub mod strip;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use lopdf::{Document, ObjectId};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    detectors::{
+        carving::{carve_objects, CarveReason},
+        orphans,
+        Severity,
+    },
+    document::{
+        diff::{diff_documents, ChangeKind, MetadataChange},
+        objects::scan_objects,
+        sha256_hex,
+        taxonomy::ObjectType,
+    },
+    error::{Error, Result},
+    limits::{self, ResourceLimits},
+    AnalysisOptions, Analyzer, PdfAnalysis, PdfAnalyzer,
+};
+
+/// In-memory result of a remediation
+#[derive(Debug, Clone, Default)]
+pub struct Rewrite {
+    /// Complete output file
+    pub output: Vec<u8>,
+
+    /// Finding that motivated the change of each touched object
+    pub reasons: BTreeMap<ObjectId, String>,
+}
+
+/// A document-mutating operation
+pub trait Remediation: Send + Sync {
+    /// Operation name used in reports
+    fn name(&self) -> &'static str;
+
+    /// Rewrites the document; must be deterministic for a given input
+    fn apply(&self, data: &[u8]) -> Result<Rewrite>;
+
+    /// Findings in `analysis` that the operation is meant to eliminate
+    fn targets(&self, _analysis: &PdfAnalysis) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Effect of a remediation on one object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectImpact {
+    /// Object number and generation
+    pub id: ObjectId,
+
+    /// Change kind
+    pub kind: ChangeKind,
+
+    /// Object type
+    pub object_type: ObjectType,
+
+    /// Serialized size in the input, if present there
+    pub bytes_before: Option<usize>,
+
+    /// Serialized size in the output, if present there
+    pub bytes_after: Option<usize>,
+
+    /// Finding that motivated the change
+    pub reason: Option<String>,
+}
+
+/// Hidden object definition of the input that the output no longer carries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgedObject {
+    /// Object number and generation from the raw header
+    pub id: ObjectId,
+
+    /// File offset of the definition in the input
+    pub offset: usize,
+
+    /// Number of bytes spanned by the definition
+    pub length: usize,
+
+    /// Why no reader displayed the definition
+    pub reason: PurgeReason,
+}
+
+/// Why no reader displayed a purged definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PurgeReason {
+    /// A later revision redefined the object number
+    Superseded,
+
+    /// A later xref section marked the object number as free
+    Freed,
+
+    /// The object number is not indexed by any xref section
+    Unindexed,
+
+    /// Indexed, but not referenced from anything the trailer leads to
+    Unreachable,
+}
+
+impl From<CarveReason> for PurgeReason {
+    fn from(reason: CarveReason) -> Self {
+        match reason {
+            CarveReason::Superseded => Self::Superseded,
+            CarveReason::Freed => Self::Freed,
+            CarveReason::Unindexed => Self::Unindexed,
+        }
+    }
+}
+
+/// Outcome of re-analyzing the output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Verification {
+    /// Whether every targeted finding is gone and none was introduced
+    pub passed: bool,
+
+    /// Targeted findings absent from the output
+    pub resolved: Vec<String>,
+
+    /// Targeted findings still present in the output
+    pub remaining: Vec<String>,
+
+    /// Findings present in the output but not in the input
+    pub introduced: Vec<String>,
+}
+
+/// What a remediation changed, or would change in a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationReport {
+    /// Operation name
+    pub operation: String,
+
+    /// Whether output writing was skipped
+    pub dry_run: bool,
+
+    /// SHA-256 of the input
+    pub input_sha256: String,
+
+    /// Input size in bytes
+    pub input_size: usize,
+
+    /// SHA-256 of the output (predicted in a dry run)
+    pub output_sha256: String,
+
+    /// Output size in bytes
+    pub output_size: usize,
+
+    /// Objects added, removed or modified
+    pub objects: Vec<ObjectImpact>,
+
+    /// Document information changes
+    pub metadata: Vec<MetadataChange>,
+
+    /// Superseded, freed and unindexed definitions dropped by the rewrite
+    #[serde(default)]
+    pub purged: Vec<PurgedObject>,
+
+    /// Path the output was written to
+    pub written_to: Option<PathBuf>,
+
+    /// Re-analysis of the output
+    #[serde(default)]
+    pub verification: Verification,
+}
+
+impl RemediationReport {
+    /// Whether the operation left the document untouched
+    pub fn is_noop(&self) -> bool {
+        self.input_sha256 == self.output_sha256
+    }
+}
+
+/// Applies a remediation in memory and describes its impact. Input and
+/// output are checked against the limits in force before either is loaded.
+#[instrument(skip(operation, data), fields(operation = operation.name()))]
+pub fn preview(operation: &dyn Remediation, data: &[u8]) -> Result<(RemediationReport, Vec<u8>)> {
+    check_limits(data)?;
+    let rewrite = operation.apply(data)?;
+    check_limits(&rewrite.output)?;
+
+    let before = Document::load_mem(data).map_err(|e| Error::Pdf(format!("Failed to load input: {}", e)))?;
+    let after = Document::load_mem(&rewrite.output)
+        .map_err(|e| Error::Pdf(format!("{} produced an unloadable file: {}", operation.name(), e)))?;
+    let diff = diff_documents(&before, &after);
+
+    let sizes_before = object_sizes(data);
+    let sizes_after = object_sizes(&rewrite.output);
+    let objects = diff
+        .objects
+        .into_iter()
+        .map(|change| ObjectImpact {
+            id: change.id,
+            kind: change.kind,
+            object_type: change.object_type,
+            bytes_before: sizes_before.get(&change.id).copied().filter(|_| change.kind != ChangeKind::Added),
+            bytes_after: sizes_after.get(&change.id).copied().filter(|_| change.kind != ChangeKind::Removed),
+            reason: rewrite.reasons.get(&change.id).cloned(),
+        })
+        .collect();
+
+    // A full rewrite leaves no hidden definitions; an output that still has
+    // some was appended to the input and purged nothing
+    let mut purged: Vec<PurgedObject> = if carve_objects(&rewrite.output, &after).is_empty() {
+        carve_objects(data, &before)
+            .into_iter()
+            .map(|carved| PurgedObject {
+                id: carved.id,
+                offset: carved.offset,
+                length: carved.length,
+                reason: carved.reason.into(),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    // Orphans of the input that the output dropped were hidden too
+    let reachable = orphans::reachable(&before);
+    let definitions: BTreeMap<ObjectId, (usize, usize)> =
+        scan_objects(data).into_iter().map(|raw| (raw.id, (raw.offset, raw.len()))).collect();
+    for id in before.objects.keys().filter(|id| !reachable.contains(id) && !after.objects.contains_key(id)) {
+        if let Some(&(offset, length)) = definitions.get(id) {
+            purged.push(PurgedObject { id: *id, offset, length, reason: PurgeReason::Unreachable });
+        }
+    }
+
+    let report = RemediationReport {
+        operation: operation.name().to_string(),
+        dry_run: true,
+        input_sha256: sha256_hex(data),
+        input_size: data.len(),
+        output_sha256: sha256_hex(&rewrite.output),
+        output_size: rewrite.output.len(),
+        objects,
+        metadata: diff.metadata,
+        purged,
+        written_to: None,
+        verification: Verification::default(),
+    };
+    Ok((report, rewrite.output))
+}
+
+/// Runs a remediation on `input`, writing `output` unless `dry_run` is set.
+///
+/// The input is never overwritten: remediation works on evidence copies.
+/// The output (in memory for a dry run, re-read from disk otherwise) is
+/// re-analyzed and only written when `verification.passed` holds; commands
+/// must fail when it does not. Loading, rewriting and verification all run
+/// under `resource_limits`.
+pub async fn run(
+    operation: &dyn Remediation,
+    input: &Path,
+    output: &Path,
+    dry_run: bool,
+    resource_limits: ResourceLimits,
+) -> Result<RemediationReport> {
+    limits::scope(resource_limits, remediate(operation, input, output, dry_run)).await
+}
+
+async fn remediate(
+    operation: &dyn Remediation,
+    input: &Path,
+    output: &Path,
+    dry_run: bool,
+) -> Result<RemediationReport> {
+    let data = tokio::fs::read(input).await?;
+    let (mut report, rewritten) = preview(operation, &data)?;
+    let before = verifier(input)?.analyze_bytes(&data).await.map_err(verification_error)?;
+    if dry_run {
+        let after = verifier(output)?.analyze_bytes(&rewritten).await.map_err(verification_error)?;
+        report.verification = verify(operation, &before, &after);
+        info!("Dry run of {}: {} objects would change", report.operation, report.objects.len());
+        return Ok(report);
+    }
+
+    if let (Ok(a), Ok(b)) = (tokio::fs::canonicalize(input).await, tokio::fs::canonicalize(output).await) {
+        if a == b {
+            return Err(Error::Analysis(format!("Refusing to overwrite the input {}", input.display())));
+        }
+    }
+
+    report.dry_run = false;
+    report.verification = write_verified(output, &rewritten, |after| verify(operation, &before, after)).await?;
+    if report.verification.passed {
+        info!("{} wrote {} ({} objects changed)", report.operation, output.display(), report.objects.len());
+        report.written_to = Some(output.to_path_buf());
+    } else {
+        warn!(
+            "Verification of {} failed, nothing written: {} targeted findings remain, {} introduced",
+            output.display(),
+            report.verification.remaining.len(),
+            report.verification.introduced.len()
+        );
+    }
+    Ok(report)
+}
+
+/// Writes `data` to a temporary file beside `output`, analyzes it there and
+/// renames it to `output` only if `check` passes; otherwise it is deleted
+async fn write_verified(
+    output: &Path,
+    data: &[u8],
+    check: impl FnOnce(&PdfAnalysis) -> Verification,
+) -> Result<Verification> {
+    let dir = output.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let temp = tempfile::Builder::new().prefix(".pdx-").suffix(".pdf").tempfile_in(dir)?;
+    tokio::fs::write(temp.path(), data).await?;
+
+    let after = verifier(temp.path())?.analyze().await.map_err(verification_error)?;
+    let verification = check(&after);
+    if verification.passed {
+        temp.persist(output).map_err(|e| Error::Io(e.error))?;
+    }
+    Ok(verification)
+}
+
+/// Analyzer used on both sides of a remediation, under the limits in
+/// force; viewer simulation is on so that parser-level regressions count
+/// as introduced findings
+fn verifier(path: &Path) -> Result<PdfAnalyzer> {
+    let options = AnalysisOptions { simulate_viewers: true, limits: limits::active(), ..Default::default() };
+    PdfAnalyzer::with_options(path, options).map_err(verification_error)
+}
+
+/// Refuses data that the limits in force do not allow to be loaded
+fn check_limits(data: &[u8]) -> Result<()> {
+    let violations = limits::check(data, &limits::active());
+    match limits::loading_blocker(&violations) {
+        Some(violation) => Err(Error::Security(violation.description.clone())),
+        None => Ok(()),
+    }
+}
+
+fn verification_error(e: anyhow::Error) -> Error {
+    Error::Analysis(format!("Verification analysis failed: {}", e))
+}
+
+/// Compares the analyses of input and output
+pub fn verify(operation: &dyn Remediation, before: &PdfAnalysis, after: &PdfAnalysis) -> Verification {
+    let remaining = operation.targets(after);
+    let resolved = operation.targets(before).into_iter().filter(|finding| !remaining.contains(finding)).collect();
+
+    let introduced = introduced(before, after);
+    Verification { passed: remaining.is_empty() && introduced.is_empty(), resolved, remaining, introduced }
+}
+
+/// Findings of `after` above Info in excess of those with the same id in
+/// `before`. Ids rather than titles are compared: a rewrite moves objects,
+/// and many titles carry offsets or object numbers.
+fn introduced(before: &PdfAnalysis, after: &PdfAnalysis) -> Vec<String> {
+    let mut existing: BTreeMap<&str, usize> = BTreeMap::new();
+    for finding in &before.findings {
+        *existing.entry(finding.id.as_str()).or_default() += 1;
+    }
+    after
+        .findings
+        .iter()
+        .filter(|finding| finding.severity > Severity::Info)
+        .filter(|finding| match existing.get_mut(finding.id.as_str()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .map(|finding| format!("[{}] {}", finding.severity, finding.title))
+        .collect()
+}
+
+/// Trailer keys tying a loaded trailer to the xref sections of the input
+const STALE_TRAILER_KEYS: [&[u8]; 8] =
+    [b"Prev", b"XRefStm", b"Type", b"W", b"Index", b"Filter", b"DecodeParms", b"Length"];
+
+/// Serializes a rewritten document as a single revision with a fresh xref
+/// table, so no earlier version of any object survives in the output
+fn write(doc: &mut Document) -> Result<Vec<u8>> {
+    for key in STALE_TRAILER_KEYS {
+        doc.trailer.remove(key);
+    }
+    let mut output = Vec::new();
+    doc.save_to(&mut output).map_err(|e| Error::Pdf(format!("Failed to write output: {}", e)))?;
+    Ok(output)
+}
+
+/// Serialized size of the last definition of each object
+fn object_sizes(data: &[u8]) -> BTreeMap<ObjectId, usize> {
+    scan_objects(data).into_iter().map(|raw| (raw.id, raw.len())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        document::fixtures,
+        remediation::sanitize::{Sanitize, SanitizePolicy},
+    };
+    use flate2::{write::ZlibEncoder, Compression};
+    use lopdf::{dictionary, Object, Stream};
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    /// Drops the catalog's /OpenAction together with the action object
+    struct DropOpenAction;
+
+    impl Remediation for DropOpenAction {
+        fn name(&self) -> &'static str {
+            "drop-open-action"
+        }
+
+        fn apply(&self, data: &[u8]) -> Result<Rewrite> {
+            let mut doc = Document::load_mem(data).map_err(|e| Error::Pdf(e.to_string()))?;
+            let mut reasons = BTreeMap::new();
+            let catalog = doc.get_dictionary_mut(fixtures::CATALOG_ID).map_err(|e| Error::Pdf(e.to_string()))?;
+            if let Some(Object::Reference(action)) = catalog.remove(b"OpenAction") {
+                reasons.insert(fixtures::CATALOG_ID, "Auto-triggered action".to_string());
+                doc.objects.remove(&action);
+                reasons.insert(action, "Auto-triggered action".to_string());
+            }
+            Ok(Rewrite { output: fixtures::save(doc), reasons })
+        }
+
+        fn targets(&self, analysis: &PdfAnalysis) -> Vec<String> {
+            analysis
+                .reader_features
+                .features
+                .iter()
+                .filter(|usage| usage.auto_triggered)
+                .map(|usage| format!("Auto-triggered {:?}", usage.feature))
+                .collect()
+        }
+    }
+
+    /// Leaves the document intact but appends an executable
+    struct AppendPayload;
+
+    impl Remediation for AppendPayload {
+        fn name(&self) -> &'static str {
+            "append-payload"
+        }
+
+        fn apply(&self, data: &[u8]) -> Result<Rewrite> {
+            let mut output = data.to_vec();
+            output.extend_from_slice(b"MZ\x90\x00payload");
+            Ok(Rewrite { output, reasons: BTreeMap::new() })
+        }
+    }
+
+    fn with_open_action() -> Vec<u8> {
+        let mut doc = fixtures::document();
+        let js = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.alert(1)"),
+        });
+        doc.get_dictionary_mut(fixtures::CATALOG_ID).unwrap().set("OpenAction", js);
+        fixtures::save(doc)
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_predicts_output() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("in.pdf");
+        let output = dir.path().join("out.pdf");
+        std::fs::write(&input, with_open_action()).unwrap();
+
+        let dry = run(&DropOpenAction, &input, &output, true, ResourceLimits::default()).await.unwrap();
+        assert!(dry.dry_run);
+        assert!(!output.exists());
+        assert_eq!(dry.objects.len(), 2);
+        let removed = dry.objects.iter().find(|o| o.kind == ChangeKind::Removed).unwrap();
+        assert!(removed.bytes_before.is_some());
+        assert_eq!(removed.reason.as_deref(), Some("Auto-triggered action"));
+
+        let real = run(&DropOpenAction, &input, &output, false, ResourceLimits::default()).await.unwrap();
+        assert_eq!(real.written_to.as_deref(), Some(output.as_path()));
+        assert_eq!(sha256_hex(&std::fs::read(&output).unwrap()), dry.output_sha256);
+        assert!(real.verification.passed);
+        assert_eq!(real.verification.resolved, vec!["Auto-triggered JavaScript".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_verification_catches_introduced_findings() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("in.pdf");
+        std::fs::write(&input, fixtures::simple_pdf()).unwrap();
+
+        let output = dir.path().join("out.pdf");
+        let report = run(&AppendPayload, &input, &output, true, ResourceLimits::default()).await.unwrap();
+        assert!(!report.verification.passed);
+        assert!(report.verification.introduced.iter().any(|f| f.contains("after final %%EOF")));
+    }
+
+    #[tokio::test]
+    async fn test_failed_verification_writes_nothing() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("in.pdf");
+        let output = dir.path().join("out.pdf");
+        std::fs::write(&input, fixtures::simple_pdf()).unwrap();
+
+        let report = run(&AppendPayload, &input, &output, false, ResourceLimits::default()).await.unwrap();
+        assert!(!report.verification.passed);
+        assert!(report.written_to.is_none());
+        assert!(!output.exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_input_is_never_overwritten() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("evidence.pdf");
+        std::fs::write(&input, with_open_action()).unwrap();
+
+        assert!(run(&DropOpenAction, &input, &input, false, ResourceLimits::default()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bomb_input_is_refused() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![0u8; 8 * 1024 * 1024]).unwrap();
+        let mut doc = fixtures::document();
+        doc.add_object(Stream::new(
+            dictionary! { "Type" => "ObjStm", "N" => 1, "First" => 4, "Filter" => "FlateDecode" },
+            encoder.finish().unwrap(),
+        ));
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("bomb.pdf");
+        let output = dir.path().join("out.pdf");
+        std::fs::write(&input, fixtures::save(doc)).unwrap();
+
+        let sanitize = Sanitize { policy: SanitizePolicy::default() };
+        let strict = ResourceLimits { max_decoded_size: 1024 * 1024, ..Default::default() };
+        let err = run(&sanitize, &input, &output, false, strict).await.unwrap_err();
+        assert!(matches!(err, Error::Security(_)));
+        assert!(!output.exists());
+    }
+}
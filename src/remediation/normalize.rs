@@ -0,0 +1,255 @@
+//! Canonical re-serialization for diffing and hashing
+//! Created: 2026-10-16 11:09:33 UTC
+//! Author: kartik4091
+//!
+//! Writes the document so that its bytes depend only on its object graph:
+//! objects are renumbered 1, 2, 3… (generation 0) in depth-first order from
+//! the catalog and then the information dictionary, dictionary keys are
+//! sorted, strings use the literal form, and every stream that can be
+//! decoded is stored Flate-compressed at a fixed level. Streams whose
+//! filters cannot be decoded (DCT, JBIG2, JPX…) keep their encoding.
+//! Unreachable objects, the file identifier and all xref and revision
+//! structure are dropped, so two files differing only in those respects
+//! normalize to identical output, and normalizing twice changes nothing.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream, StringFormat};
+use tracing::{debug, instrument};
+
+use super::{redact::flate_stream, write, Remediation, Rewrite};
+use crate::{
+    document::stream_data,
+    error::{Error, Result},
+};
+
+/// Trailer entries naming the roots of the object graph, in numbering order
+const ROOTS: [&[u8]; 2] = [b"Root", b"Info"];
+
+/// Canonical re-serialization
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Normalize;
+
+impl Remediation for Normalize {
+    fn name(&self) -> &'static str {
+        "normalize"
+    }
+
+    #[instrument(skip(self, data))]
+    fn apply(&self, data: &[u8]) -> Result<Rewrite> {
+        let doc = Document::load_mem(data).map_err(|e| Error::Pdf(format!("Failed to load input: {}", e)))?;
+        if doc.trailer.has(b"Encrypt") {
+            return Err(Error::Analysis("Encrypted documents cannot be normalized".to_string()));
+        }
+
+        let numbers = renumber(&doc);
+        let mut objects = BTreeMap::new();
+        for (old, new) in &numbers {
+            if let Some(object) = doc.objects.get(old) {
+                objects.insert(*new, canonical(object, &numbers)?);
+            }
+        }
+        let mut trailer = Dictionary::new();
+        for key in ROOTS {
+            if let Ok(value) = doc.trailer.get(key) {
+                trailer.set(key, canonical(value, &numbers)?);
+            }
+        }
+
+        let mut reasons = BTreeMap::new();
+        for (old, new) in &numbers {
+            if old != new {
+                reasons.insert(*new, format!("Renumbered from {} {}", old.0, old.1));
+            }
+        }
+        for (old, new) in &numbers {
+            if old != new {
+                reasons
+                    .entry(*old)
+                    .or_insert_with(|| format!("Renumbered as {} {}", new.0, new.1));
+            }
+        }
+        for id in doc.objects.keys().filter(|id| !numbers.contains_key(id)) {
+            reasons
+                .entry(*id)
+                .or_insert_with(|| "Removed unreachable object".to_string());
+        }
+
+        let mut out = Document::with_version(doc.version.clone());
+        out.max_id = objects.len() as u32;
+        out.objects = objects;
+        out.trailer = trailer;
+        let output = write(&mut out)?;
+        debug!("Normalized {} objects", numbers.len());
+        Ok(Rewrite { output, reasons })
+    }
+}
+
+/// New number of every object reachable from the trailer roots, in
+/// depth-first order over sorted dictionary keys
+fn renumber(doc: &Document) -> BTreeMap<ObjectId, ObjectId> {
+    let mut numbers = BTreeMap::new();
+    let mut pending: Vec<ObjectId> = Vec::new();
+    for key in ROOTS.iter().rev() {
+        if let Ok(Object::Reference(id)) = doc.trailer.get(key) {
+            pending.push(*id);
+        }
+    }
+    while let Some(id) = pending.pop() {
+        if numbers.contains_key(&id) {
+            continue;
+        }
+        let Some(object) = doc.objects.get(&id) else {
+            continue;
+        };
+        numbers.insert(id, (numbers.len() as u32 + 1, 0));
+        let mut references = Vec::new();
+        ordered_references(object, &mut references);
+        pending.extend(references.into_iter().rev());
+    }
+    numbers
+}
+
+/// References below an object, visiting dictionary entries in key order
+fn ordered_references(object: &Object, references: &mut Vec<ObjectId>) {
+    match object {
+        Object::Reference(id) => references.push(*id),
+        Object::Array(items) => items.iter().for_each(|item| ordered_references(item, references)),
+        Object::Dictionary(dict) => sorted_entries(dict)
+            .into_iter()
+            .for_each(|(_, value)| ordered_references(value, references)),
+        Object::Stream(stream) => sorted_entries(&stream.dict)
+            .into_iter()
+            .for_each(|(_, value)| ordered_references(value, references)),
+        _ => {}
+    }
+}
+
+fn sorted_entries(dict: &Dictionary) -> Vec<(&Vec<u8>, &Object)> {
+    let mut entries: Vec<(&Vec<u8>, &Object)> = dict.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Canonical form of an object under the new numbering; references to
+/// missing objects become null, as readers treat them
+fn canonical(object: &Object, numbers: &BTreeMap<ObjectId, ObjectId>) -> Result<Object> {
+    Ok(match object {
+        Object::Reference(id) => numbers.get(id).map_or(Object::Null, |&new| Object::Reference(new)),
+        Object::String(bytes, _) => Object::String(bytes.clone(), StringFormat::Literal),
+        Object::Array(items) => Object::Array(
+            items
+                .iter()
+                .map(|item| canonical(item, numbers))
+                .collect::<Result<_>>()?,
+        ),
+        Object::Dictionary(dict) => Object::Dictionary(canonical_dictionary(dict, numbers)?),
+        Object::Stream(stream) => Object::Stream(canonical_stream(stream, numbers)?),
+        other => other.clone(),
+    })
+}
+
+fn canonical_dictionary(dict: &Dictionary, numbers: &BTreeMap<ObjectId, ObjectId>) -> Result<Dictionary> {
+    let mut out = Dictionary::new();
+    for (key, value) in sorted_entries(dict) {
+        out.set(key.clone(), canonical(value, numbers)?);
+    }
+    Ok(out)
+}
+
+/// Stream recompressed with Flate when its filters can be decoded, and
+/// stored as found otherwise
+fn canonical_stream(stream: &Stream, numbers: &BTreeMap<ObjectId, ObjectId>) -> Result<Stream> {
+    let mut dict = stream.dict.clone();
+    let content = match stream_data(stream) {
+        Ok(decoded) => {
+            dict.remove(b"DecodeParms");
+            dict.set("Filter", "FlateDecode");
+            flate_stream(Dictionary::new(), &decoded)?.content
+        }
+        Err(_) => stream.content.clone(),
+    };
+    dict.set("Length", content.len() as i64);
+    Ok(Stream::new(canonical_dictionary(&dict, numbers)?, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::dictionary;
+
+    /// The same one-page document numbered, ordered and encoded differently
+    fn variant(reversed: bool) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let base = if reversed { 20 } else { 1 };
+        let (pages, page, content, catalog) = if reversed {
+            ((base + 3, 0), (base + 2, 0), (base + 1, 0), (base, 0))
+        } else {
+            ((base, 0), (base + 1, 0), (base + 2, 0), (base + 3, 0))
+        };
+        let text = if reversed {
+            Object::String(b"Hello".to_vec(), StringFormat::Hexadecimal)
+        } else {
+            Object::string_literal("Hello")
+        };
+        let mut stream = Stream::new(dictionary! {}, b"BT /F1 12 Tf (Hello) Tj ET".to_vec());
+        if reversed {
+            stream.compress().unwrap();
+        }
+        doc.objects.insert(content, Object::Stream(stream));
+        let page_dict = if reversed {
+            dictionary! { "Contents" => content, "Parent" => pages, "Type" => "Page" }
+        } else {
+            dictionary! { "Type" => "Page", "Parent" => pages, "Contents" => content }
+        };
+        doc.objects.insert(page, Object::Dictionary(page_dict));
+        doc.objects.insert(
+            pages,
+            Object::Dictionary(dictionary! { "Type" => "Pages", "Kids" => vec![page.into()], "Count" => 1 }),
+        );
+        doc.objects.insert(
+            catalog,
+            Object::Dictionary(dictionary! { "Type" => "Catalog", "Pages" => pages, "Lang" => text }),
+        );
+        if reversed {
+            doc.objects.insert((base + 4, 0), Object::string_literal("orphan"));
+            doc.trailer
+                .set("ID", vec![Object::string_literal("abc"), Object::string_literal("abc")]);
+        }
+        doc.max_id = doc.objects.keys().map(|id| id.0).max().unwrap_or(0);
+        doc.trailer.set("Root", catalog);
+        fixtures::save(doc)
+    }
+
+    #[test]
+    fn test_equivalent_files_normalize_identically() {
+        let (first, second) = (variant(false), variant(true));
+        assert_ne!(first, second);
+        let one = Normalize.apply(&first).unwrap();
+        let two = Normalize.apply(&second).unwrap();
+        assert_eq!(one.output, two.output);
+
+        let doc = Document::load_mem(&one.output).unwrap();
+        assert_eq!(doc.objects.len(), 4);
+        assert!(!doc.trailer.has(b"ID"));
+        let catalog = doc.catalog().unwrap();
+        let keys: Vec<&[u8]> = catalog.iter().map(|(key, _)| key.as_slice()).collect();
+        assert_eq!(keys, [&b"Lang"[..], b"Pages", b"Type"]);
+        assert_eq!(doc.trailer.get(b"Root").unwrap().as_reference().unwrap(), (1, 0));
+        assert_eq!(two.reasons[&(24, 0)], "Removed unreachable object");
+    }
+
+    #[test]
+    fn test_normalizing_is_idempotent() {
+        let once = Normalize.apply(&variant(false)).unwrap().output;
+        let twice = Normalize.apply(&once).unwrap();
+        assert_eq!(twice.output, once);
+        assert!(twice.reasons.is_empty());
+
+        let doc = Document::load_mem(&once).unwrap();
+        let stream = doc.objects.values().find_map(|object| object.as_stream().ok()).unwrap();
+        assert_eq!(stream.dict.get(b"Filter").unwrap().as_name().unwrap(), b"FlateDecode");
+        assert_eq!(stream_data(stream).unwrap(), b"BT /F1 12 Tf (Hello) Tj ET");
+    }
+}
@@ -0,0 +1,980 @@
+//! Application of pending and visual-only redactions
+//! Created: 2026-10-16 09:49:28 UTC
+//! Author: kartik4091
+//!
+//! Removes the content under every region the redaction detector looks
+//! at — /Redact annotations, dark boxes drawn over text and dark Square
+//! annotations — instead of merely covering it. Glyphs under a region are
+//! cut from their text operators and replaced by an equal displacement, so
+//! the remaining text keeps its position. Images are blanked pixel by pixel
+//! where they can be decoded and removed otherwise, and inline images under
+//! a region are removed. Form XObjects and annotation appearance streams
+//! that overlap a region are clipped the same way, and marked-content
+//! properties around cut glyphs lose the /ActualText and /Alt that would
+//! spell them out. /Redact annotations are then burned in as an opaque box
+//! in their interior color.
+//!
+//! The output is a full rewrite, so earlier revisions of the redacted
+//! content do not survive in the file.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    ops::Range,
+};
+
+use flate2::{write::ZlibEncoder, Compression};
+use lopdf::{
+    content::{Content, Operation},
+    dictionary, Dictionary, Document, Object, ObjectId, Stream,
+};
+use tracing::{debug, instrument};
+
+use super::{flatten::fit, Remediation, Rewrite};
+use crate::{
+    detectors::redaction::{interior_color, regions, MIN_COVERAGE},
+    document::{
+        content::{
+            contains, coverage, interpret, intersects, multiply, transform_box, Color, ContentEvent, Matrix,
+            TextMetrics, TextShow, IDENTITY,
+        },
+        name_value, number, rectangle, resolve, stream_data,
+    },
+    error::{Error, Result},
+    text::font::PageFonts,
+    PdfAnalysis,
+};
+
+/// Removes the content under every redaction region and burns in /Redact annotations
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApplyRedactions;
+
+impl Remediation for ApplyRedactions {
+    fn name(&self) -> &'static str {
+        "redact-apply"
+    }
+
+    #[instrument(skip(self, data))]
+    fn apply(&self, data: &[u8]) -> Result<Rewrite> {
+        let mut doc = Document::load_mem(data).map_err(|e| Error::Pdf(format!("Failed to load input: {}", e)))?;
+        let mut reasons = BTreeMap::new();
+        for (page_number, page) in doc.get_pages() {
+            redact_page(&mut doc, page_number, page, &mut reasons)?;
+        }
+
+        if reasons.is_empty() {
+            return Ok(Rewrite {
+                output: data.to_vec(),
+                reasons,
+            });
+        }
+        let mut output = Vec::new();
+        doc.save_to(&mut output)
+            .map_err(|e| Error::Pdf(format!("Failed to write output: {}", e)))?;
+        debug!("Redaction touched {} objects", reasons.len());
+        Ok(Rewrite { output, reasons })
+    }
+
+    fn targets(&self, analysis: &PdfAnalysis) -> Vec<String> {
+        analysis
+            .redaction_failures
+            .iter()
+            .map(|failure| failure.description.clone())
+            .collect()
+    }
+}
+
+/// Deepest form XObject nesting redacted; deeper forms under a region are removed
+const MAX_DEPTH: usize = 16;
+
+/// Marked-content properties that spell out the content they mark
+const REPLACEMENT_KEYS: [&[u8]; 3] = [b"ActualText", b"Alt", b"E"];
+
+/// Region whose content is removed
+#[derive(Debug, Clone, Copy)]
+struct Region {
+    /// Covered area in default user space
+    bbox: [f64; 4],
+
+    /// Only content painted before this event is removed
+    before: Option<usize>,
+}
+
+impl Region {
+    fn applies(&self, position: usize) -> bool {
+        self.before.map_or(true, |before| position < before)
+    }
+}
+
+/// Content stream rewritten without the content under its regions
+struct Rewritten {
+    operations: Vec<Operation>,
+
+    /// Resources added for the rewritten operators: category, name and object
+    resources: Vec<(&'static str, Vec<u8>, ObjectId)>,
+}
+
+/// Outcome of redacting an XObject that overlaps a region
+enum Clipped {
+    /// Nothing it paints is under the region
+    Unchanged,
+
+    /// Copy without the content under the region
+    Redacted(Stream),
+
+    /// It cannot be clipped and is removed as a whole
+    Removed,
+}
+
+/// Redaction of the content of one page, its forms and annotation appearances
+struct PageRedaction<'r> {
+    page_number: u32,
+    regions: usize,
+    glyphs: usize,
+    images: usize,
+    xobjects: usize,
+    reasons: &'r mut BTreeMap<ObjectId, String>,
+}
+
+fn redact_page(
+    doc: &mut Document,
+    page_number: u32,
+    page: ObjectId,
+    reasons: &mut BTreeMap<ObjectId, String>,
+) -> Result<()> {
+    let annots: Vec<Object> = doc
+        .get_dictionary(page)
+        .ok()
+        .and_then(|dict| dict.get(b"Annots").ok())
+        .and_then(|a| resolve(doc, a).as_array().ok())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut found = Vec::new();
+    let mut overlays = Vec::new();
+    let mut applied = BTreeSet::new();
+    let mut kept_annots = Vec::new();
+    // Annotations whose appearance may show content under a region
+    let mut painted = Vec::new();
+    for entry in annots {
+        let Ok(annot) = resolve(doc, &entry).as_dict() else {
+            kept_annots.push(entry.clone());
+            continue;
+        };
+        match name_value(annot, b"Subtype") {
+            Some(b"Redact") => {
+                let color = match interior_color(doc, annot) {
+                    Color::Other => Color::Gray(0.0),
+                    color => color,
+                };
+                for bbox in regions(doc, annot) {
+                    found.push(Region { bbox, before: None });
+                    overlays.push((bbox, color));
+                }
+                if let Ok(Object::Reference(popup)) = annot.get(b"Popup") {
+                    applied.insert(*popup);
+                }
+                if let Ok(id) = entry.as_reference() {
+                    applied.insert(id);
+                }
+                continue;
+            }
+            Some(b"Square") if interior_color(doc, annot).is_dark() => {
+                found.extend(
+                    regions(doc, annot)
+                        .into_iter()
+                        .map(|bbox| Region { bbox, before: None }),
+                );
+            }
+            _ => painted.push(entry.clone()),
+        }
+        kept_annots.push(entry);
+    }
+    kept_annots.retain(|entry| entry.as_reference().map_or(true, |id| !applied.contains(&id)));
+    painted.retain(|entry| entry.as_reference().map_or(true, |id| !applied.contains(&id)));
+
+    let content_ids = doc.get_page_contents(page);
+    let Some(content) = doc
+        .get_page_content(page)
+        .ok()
+        .and_then(|data| Content::decode(&data).ok())
+    else {
+        if found.is_empty() && overlays.is_empty() {
+            return Ok(());
+        }
+        return Err(Error::Pdf(format!(
+            "Content of page {} cannot be parsed; its redactions cannot be applied",
+            page_number
+        )));
+    };
+
+    let resources = own_resources(doc, page);
+    let mut redaction = PageRedaction {
+        page_number,
+        regions: found.len(),
+        glyphs: 0,
+        images: 0,
+        xobjects: 0,
+        reasons,
+    };
+    let rewritten = redaction.content(doc, &content.operations, &resources, IDENTITY, &found, 0)?;
+    // Annotations are painted above the page, so only annotation regions cover them
+    redaction.appearances(doc, &painted, &resources, &found)?;
+    if rewritten.is_none() && overlays.is_empty() && applied.is_empty() {
+        return Ok(());
+    }
+    let Rewritten {
+        operations: redacted,
+        resources: added,
+    } = rewritten.unwrap_or(Rewritten {
+        operations: content.operations,
+        resources: Vec::new(),
+    });
+
+    // The original content runs in its own graphics state so that the
+    // burned-in boxes are painted in default user space
+    let mut operations = vec![Operation::new("q", vec![])];
+    operations.extend(redacted);
+    operations.push(Operation::new("Q", vec![]));
+    for (bbox, color) in &overlays {
+        operations.extend(overlay(bbox, color));
+    }
+    let encoded = Content { operations }
+        .encode()
+        .map_err(|e| Error::Pdf(format!("Failed to encode content of page {}: {}", page_number, e)))?;
+    let contents = doc.add_object(flate_stream(dictionary! {}, &encoded)?);
+    redaction
+        .reasons
+        .insert(contents, format!("Redacted content of page {}", page_number));
+
+    // Streams shared with other pages stay; the rest only hold the redacted text
+    let shared: BTreeSet<ObjectId> = doc
+        .get_pages()
+        .into_values()
+        .filter(|&other| other != page)
+        .flat_map(|other| doc.get_page_contents(other))
+        .collect();
+    for old in content_ids.into_iter().filter(|id| !shared.contains(id)) {
+        doc.objects.remove(&old);
+        redaction
+            .reasons
+            .insert(old, format!("Unredacted content of page {}", page_number));
+    }
+    for id in &applied {
+        doc.objects.remove(id);
+        redaction
+            .reasons
+            .insert(*id, format!("Applied /Redact annotation on page {}", page_number));
+    }
+
+    let resources = (!added.is_empty()).then(|| with_resources(doc, resources, &added));
+    let dict = doc
+        .get_dictionary_mut(page)
+        .map_err(|e| Error::Pdf(format!("Page {} is not a dictionary: {}", page_number, e)))?;
+    dict.set("Contents", contents);
+    if let Some(resources) = resources {
+        dict.set("Resources", resources);
+    }
+    if !applied.is_empty() {
+        if kept_annots.is_empty() {
+            dict.remove(b"Annots");
+        } else {
+            dict.set("Annots", kept_annots);
+        }
+    }
+    redaction.reasons.insert(
+        page,
+        format!(
+            "Applied {} redaction regions on page {}: {} glyphs, {} inline images and {} XObjects removed",
+            redaction.regions, page_number, redaction.glyphs, redaction.images, redaction.xobjects
+        ),
+    );
+    Ok(())
+}
+
+impl PageRedaction<'_> {
+    /// Redacts content painted with `ctm` in default user space.
+    ///
+    /// `None` means nothing the content paints is under a region.
+    fn content(
+        &mut self,
+        doc: &mut Document,
+        operations: &[Operation],
+        resources: &Dictionary,
+        ctm: Matrix,
+        regions: &[Region],
+        depth: usize,
+    ) -> Result<Option<Rewritten>> {
+        let fonts = PageFonts::from_resources(doc, &[resources]);
+        let events = interpret(operations, ctm, &fonts);
+
+        // Dark boxes hide the content painted before them
+        let mut found = regions.to_vec();
+        for (position, event) in events.iter().enumerate() {
+            if let ContentEvent::Fill { bbox, color } = event {
+                if color.is_dark() {
+                    found.push(Region {
+                        bbox: *bbox,
+                        before: Some(position),
+                    });
+                }
+            }
+        }
+        self.regions += found.len() - regions.len();
+        if found.is_empty() {
+            return Ok(None);
+        }
+        let under = |position: usize, bbox: &[f64; 4]| -> Vec<[f64; 4]> {
+            found
+                .iter()
+                .filter(|r| r.applies(position) && intersects(&r.bbox, bbox))
+                .map(|r| r.bbox)
+                .collect()
+        };
+
+        let mut replacements: BTreeMap<usize, Vec<Operation>> = BTreeMap::new();
+        let mut added = Vec::new();
+        let mut cut = BTreeSet::new();
+        for (position, event) in events.iter().enumerate() {
+            match event {
+                ContentEvent::Text(shown) => {
+                    let removed: Vec<bool> = shown
+                        .glyphs
+                        .iter()
+                        .map(|glyph| {
+                            found
+                                .iter()
+                                .any(|r| r.applies(position) && coverage(&r.bbox, &glyph.bbox) >= MIN_COVERAGE)
+                        })
+                        .collect();
+                    let count = removed.iter().filter(|&&r| r).count();
+                    if count > 0 {
+                        self.glyphs += count;
+                        cut.insert(shown.index);
+                        replacements.insert(
+                            shown.index,
+                            cut_glyphs(&operations[shown.index], shown, &removed, &fonts),
+                        );
+                    }
+                }
+                ContentEvent::InlineImage { index, bbox } => {
+                    if under(position, bbox).is_empty() {
+                        continue;
+                    }
+                    self.images += 1;
+                    for index in *index..=inline_image_end(operations, *index) {
+                        replacements.insert(index, Vec::new());
+                    }
+                }
+                ContentEvent::XObject {
+                    index,
+                    name,
+                    ctm: painted,
+                    bbox,
+                } => {
+                    let stream = xobject(doc, resources, name);
+                    let form = stream
+                        .as_ref()
+                        .filter(|s| name_value(&s.dict, b"Subtype") == Some(b"Form"))
+                        .and_then(|s| form_geometry(doc, s));
+                    let bbox = match form {
+                        Some((form_box, matrix)) => transform_box(&multiply(&matrix, painted), form_box),
+                        None => *bbox,
+                    };
+                    let hits = under(position, &bbox);
+                    if hits.is_empty() {
+                        continue;
+                    }
+                    let clipped = match (stream, form) {
+                        _ if hits.iter().any(|hit| contains(hit, &bbox)) => Clipped::Removed,
+                        (Some(stream), Some((_, matrix))) => {
+                            self.form(doc, &stream, resources, &multiply(&matrix, painted), &hits, depth)?
+                        }
+                        (Some(stream), None) => {
+                            blank_image(doc, &stream, painted, &hits).map_or(Clipped::Removed, Clipped::Redacted)
+                        }
+                        (None, _) => Clipped::Removed,
+                    };
+                    match clipped {
+                        Clipped::Unchanged => {}
+                        Clipped::Removed => {
+                            self.xobjects += 1;
+                            replacements.insert(*index, Vec::new());
+                        }
+                        Clipped::Redacted(stream) => {
+                            let (kind, action) = match form {
+                                Some(_) => ("Form XObject", "clipped"),
+                                None => ("Image", "blanked"),
+                            };
+                            let id = doc.add_object(stream);
+                            let renamed = format!("PdxRedacted{}", id.0).into_bytes();
+                            self.reasons.insert(
+                                id,
+                                format!(
+                                    "{} /{} {} under a redaction on page {}",
+                                    kind,
+                                    String::from_utf8_lossy(name),
+                                    action,
+                                    self.page_number
+                                ),
+                            );
+                            replacements
+                                .insert(*index, vec![Operation::new("Do", vec![Object::Name(renamed.clone())])]);
+                            added.push(("XObject", renamed, id));
+                        }
+                    }
+                }
+                ContentEvent::Fill { .. } | ContentEvent::Shading { .. } => {}
+            }
+        }
+
+        // Marked-content properties around cut glyphs can spell them out
+        let mut open = Vec::new();
+        let mut spans = BTreeSet::new();
+        for (index, operation) in operations.iter().enumerate() {
+            match operation.operator.as_str() {
+                "BDC" | "BMC" => open.push(index),
+                "EMC" => {
+                    open.pop();
+                }
+                _ if cut.contains(&index) => spans.extend(open.iter().copied()),
+                _ => {}
+            }
+        }
+        for index in spans {
+            let operation = &operations[index];
+            let Some(properties) = stripped(doc, operation, resources) else {
+                continue;
+            };
+            let properties = match operation.operands.get(1) {
+                Some(Object::Name(_)) => {
+                    let id = doc.add_object(properties);
+                    let renamed = format!("PdxRedacted{}", id.0).into_bytes();
+                    self.reasons.insert(
+                        id,
+                        format!(
+                            "Marked-content properties without replacement text on page {}",
+                            self.page_number
+                        ),
+                    );
+                    added.push(("Properties", renamed.clone(), id));
+                    Object::Name(renamed)
+                }
+                _ => Object::Dictionary(properties),
+            };
+            replacements.insert(
+                index,
+                vec![Operation::new("BDC", vec![operation.operands[0].clone(), properties])],
+            );
+        }
+
+        if replacements.is_empty() {
+            return Ok(None);
+        }
+        let mut rewritten = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.iter().enumerate() {
+            match replacements.remove(&index) {
+                Some(replacement) => rewritten.extend(replacement),
+                None => rewritten.push(operation.clone()),
+            }
+        }
+        Ok(Some(Rewritten {
+            operations: rewritten,
+            resources: added,
+        }))
+    }
+
+    /// Copy of a form XObject without the content under `hits`, painted
+    /// with `ctm` (its /Matrix included)
+    fn form(
+        &mut self,
+        doc: &mut Document,
+        form: &Stream,
+        parent: &Dictionary,
+        ctm: &Matrix,
+        hits: &[[f64; 4]],
+        depth: usize,
+    ) -> Result<Clipped> {
+        if depth >= MAX_DEPTH {
+            return Ok(Clipped::Removed);
+        }
+        let Some(content) = stream_data(form).ok().and_then(|data| Content::decode(&data).ok()) else {
+            return Ok(Clipped::Removed);
+        };
+        // Forms without resources use those of the content painting them
+        let resources = form
+            .dict
+            .get(b"Resources")
+            .ok()
+            .and_then(|r| resolve(doc, r).as_dict().ok())
+            .cloned()
+            .unwrap_or_else(|| parent.clone());
+        let regions: Vec<Region> = hits.iter().map(|&bbox| Region { bbox, before: None }).collect();
+        let Some(rewritten) = self.content(doc, &content.operations, &resources, *ctm, &regions, depth + 1)? else {
+            return Ok(Clipped::Unchanged);
+        };
+
+        let encoded = Content {
+            operations: rewritten.operations,
+        }
+        .encode()
+        .map_err(|e| Error::Pdf(format!("Failed to encode form on page {}: {}", self.page_number, e)))?;
+        let mut dict = form.dict.clone();
+        dict.remove(b"DecodeParms");
+        if !rewritten.resources.is_empty() {
+            dict.set("Resources", with_resources(doc, resources, &rewritten.resources));
+        }
+        Ok(Clipped::Redacted(flate_stream(dict, &encoded)?))
+    }
+
+    /// Redacts the appearance streams of annotations under a region, in place
+    fn appearances(
+        &mut self,
+        doc: &mut Document,
+        annots: &[Object],
+        resources: &Dictionary,
+        regions: &[Region],
+    ) -> Result<()> {
+        let mut streams = BTreeMap::new();
+        for entry in annots {
+            let Ok(annot) = resolve(doc, entry).as_dict() else {
+                continue;
+            };
+            let Some(rect) = annot.get(b"Rect").ok().and_then(|r| rectangle(doc, r)) else {
+                continue;
+            };
+            if !regions.iter().any(|r| intersects(&r.bbox, &rect)) {
+                continue;
+            }
+            let Some(appearances) = annot.get(b"AP").ok().and_then(|ap| resolve(doc, ap).as_dict().ok()) else {
+                continue;
+            };
+            // Normal, rollover and down appearances, each a stream or one per state
+            for (_, appearance) in appearances.iter() {
+                let ids: Vec<ObjectId> = match resolve(doc, appearance) {
+                    Object::Dictionary(states) => states.iter().filter_map(|(_, s)| s.as_reference().ok()).collect(),
+                    _ => appearance.as_reference().into_iter().collect(),
+                };
+                for id in ids {
+                    streams.entry(id).or_insert(rect);
+                }
+            }
+        }
+
+        for (id, rect) in streams {
+            let Ok(stream) = doc.get_object(id).and_then(Object::as_stream).cloned() else {
+                continue;
+            };
+            let hits: Vec<[f64; 4]> = regions
+                .iter()
+                .filter(|r| intersects(&r.bbox, &rect))
+                .map(|r| r.bbox)
+                .collect();
+            let clipped = match form_geometry(doc, &stream) {
+                Some((bbox, matrix)) => {
+                    let ctm = multiply(&matrix, &fit(&matrix, bbox, rect));
+                    self.form(doc, &stream, resources, &ctm, &hits, 0)?
+                }
+                None => Clipped::Removed,
+            };
+            let redacted = match clipped {
+                Clipped::Unchanged => continue,
+                Clipped::Redacted(redacted) => redacted,
+                Clipped::Removed => {
+                    let mut dict = stream.dict.clone();
+                    dict.remove(b"Filter");
+                    dict.remove(b"DecodeParms");
+                    Stream::new(dict, Vec::new())
+                }
+            };
+            doc.objects.insert(id, Object::Stream(redacted));
+            self.reasons.insert(
+                id,
+                format!("Redacted annotation appearance on page {}", self.page_number),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites a text operator without the removed glyphs.
+///
+/// Each removed glyph becomes a TJ adjustment of its own advance, so the
+/// glyphs that remain are painted exactly where they were.
+fn cut_glyphs(operation: &Operation, shown: &TextShow, removed: &[bool], fonts: &PageFonts) -> Vec<Operation> {
+    let parts: Vec<&Object> = match operation.operands.last() {
+        Some(Object::Array(items)) if operation.operator == "TJ" => items.iter().collect(),
+        Some(string) => vec![string],
+        None => return Vec::new(),
+    };
+
+    let mut glyphs = shown.glyphs.iter().zip(removed);
+    let mut array = Vec::new();
+    for part in parts {
+        let Object::String(bytes, format) = part else {
+            array.push(part.clone());
+            continue;
+        };
+        let mut kept = Vec::new();
+        for code in fonts.codes(&shown.font, bytes) {
+            match glyphs.next() {
+                Some((glyph, true)) => {
+                    if !kept.is_empty() {
+                        array.push(Object::String(std::mem::take(&mut kept), *format));
+                    }
+                    if shown.font_size != 0.0 {
+                        array.push(real(-glyph.advance * 1000.0 / shown.font_size));
+                    }
+                }
+                _ => kept.extend_from_slice(code),
+            }
+        }
+        if !kept.is_empty() {
+            array.push(Object::String(kept, *format));
+        }
+    }
+
+    // ' and " move to the next line (and set spacing) before showing text
+    let mut operations = Vec::new();
+    match (operation.operator.as_str(), operation.operands.as_slice()) {
+        ("\"", [aw, ac, _]) => {
+            operations.push(Operation::new("Tw", vec![aw.clone()]));
+            operations.push(Operation::new("Tc", vec![ac.clone()]));
+            operations.push(Operation::new("T*", vec![]));
+        }
+        ("'", _) => operations.push(Operation::new("T*", vec![])),
+        _ => {}
+    }
+    operations.push(Operation::new("TJ", vec![Object::Array(array)]));
+    operations
+}
+
+/// Copy of an image with the pixels under `hits` set to zero.
+///
+/// Only 8-bit DeviceGray, DeviceRGB and DeviceCMYK images painted without
+/// rotation are handled; `None` means the image has to be removed.
+fn blank_image(doc: &Document, image: &Stream, ctm: &Matrix, hits: &[[f64; 4]]) -> Option<Stream> {
+    if ctm[1] != 0.0 || ctm[2] != 0.0 || ctm[0] == 0.0 || ctm[3] == 0.0 {
+        return None;
+    }
+    if name_value(&image.dict, b"Subtype") != Some(b"Image") {
+        return None;
+    }
+    let integer = |key: &[u8]| image.dict.get(key).ok().and_then(|v| v.as_i64().ok());
+    let (width, height) = (integer(b"Width")? as usize, integer(b"Height")? as usize);
+    if integer(b"BitsPerComponent") != Some(8) {
+        return None;
+    }
+    let components = match image.dict.get(b"ColorSpace").map(|c| resolve(doc, c)) {
+        Ok(Object::Name(space)) if space == b"DeviceGray" => 1,
+        Ok(Object::Name(space)) if space == b"DeviceRGB" => 3,
+        Ok(Object::Name(space)) if space == b"DeviceCMYK" => 4,
+        _ => return None,
+    };
+    let mut pixels = stream_data(image).ok()?;
+    if pixels.len() != width * height * components {
+        return None;
+    }
+
+    for hit in hits {
+        // Region corners in the image's unit square
+        let (x0, y0) = ((hit[0] - ctm[4]) / ctm[0], (hit[1] - ctm[5]) / ctm[3]);
+        let (x1, y1) = ((hit[2] - ctm[4]) / ctm[0], (hit[3] - ctm[5]) / ctm[3]);
+        let columns = pixel_range(x0.min(x1), x0.max(x1), width);
+        // Sample rows run from the top of the image
+        for row in pixel_range(1.0 - y0.max(y1), 1.0 - y0.min(y1), height) {
+            let start = (row * width + columns.start) * components;
+            let end = (row * width + columns.end) * components;
+            pixels[start..end].fill(0);
+        }
+    }
+
+    let mut dict = image.dict.clone();
+    dict.remove(b"DecodeParms");
+    flate_stream(dict, &pixels).ok()
+}
+
+/// Samples covering `from..to` of the unit interval, widened to whole samples
+fn pixel_range(from: f64, to: f64, count: usize) -> Range<usize> {
+    let scale = |v: f64| (v * count as f64).clamp(0.0, count as f64);
+    let (start, end) = (scale(from).floor() as usize, scale(to).ceil() as usize);
+    start..end.max(start)
+}
+
+/// XObject stream for a resource name
+fn xobject(doc: &Document, resources: &Dictionary, name: &[u8]) -> Option<Stream> {
+    let entries = resolve(doc, resources.get(b"XObject").ok()?).as_dict().ok()?;
+    resolve(doc, entries.get(name).ok()?).as_stream().ok().cloned()
+}
+
+/// Bounding box and matrix of a form XObject
+fn form_geometry(doc: &Document, form: &Stream) -> Option<([f64; 4], Matrix)> {
+    let bbox = rectangle(doc, form.dict.get(b"BBox").ok()?)?;
+    let matrix = match form.dict.get(b"Matrix") {
+        Ok(Object::Array(values)) => {
+            Matrix::try_from(values.iter().filter_map(number).collect::<Vec<_>>().as_slice()).unwrap_or(IDENTITY)
+        }
+        _ => IDENTITY,
+    };
+    Some((bbox, matrix))
+}
+
+/// Last operator of the inline image whose `BI` is at `index`, when the
+/// image data was parsed as separate `ID` and `EI` operators
+fn inline_image_end(operations: &[Operation], index: usize) -> usize {
+    let next = |offset: usize| operations.get(index + offset).map(|op| op.operator.as_str());
+    match (next(1), next(2)) {
+        (Some("ID"), Some("EI")) => index + 2,
+        (Some("EI"), _) => index + 1,
+        _ => index,
+    }
+}
+
+/// Property list of a `BDC` operator without the keys that spell out the
+/// marked content, or `None` when it has none of them
+fn stripped(doc: &Document, operation: &Operation, resources: &Dictionary) -> Option<Dictionary> {
+    let mut properties = match operation.operands.as_slice() {
+        [_, Object::Dictionary(properties)] => properties.clone(),
+        [_, Object::Name(name)] => {
+            let entries = resolve(doc, resources.get(b"Properties").ok()?).as_dict().ok()?;
+            resolve(doc, entries.get(name).ok()?).as_dict().ok()?.clone()
+        }
+        _ => return None,
+    };
+    if !REPLACEMENT_KEYS.iter().any(|key| properties.has(key)) {
+        return None;
+    }
+    for key in REPLACEMENT_KEYS {
+        properties.remove(key);
+    }
+    Some(properties)
+}
+
+/// Resources with added entries, each a category, name and object
+fn with_resources(doc: &Document, mut resources: Dictionary, added: &[(&str, Vec<u8>, ObjectId)]) -> Dictionary {
+    for (category, name, id) in added {
+        let mut entries = resources
+            .get(category.as_bytes())
+            .map(|entries| resolve(doc, entries))
+            .and_then(Object::as_dict)
+            .cloned()
+            .unwrap_or_else(|_| Dictionary::new());
+        entries.set(name.clone(), *id);
+        resources.set(*category, entries);
+    }
+    resources
+}
+
+/// Effective resources of a page as a direct dictionary the page can own,
+/// so that additions do not leak to pages sharing inherited resources
+pub(super) fn own_resources(doc: &Document, page: ObjectId) -> Dictionary {
+    let (inline, referenced) = doc.get_page_resources(page);
+    let mut layers: Vec<&Dictionary> = inline.into_iter().collect();
+    layers.extend(referenced.into_iter().filter_map(|id| doc.get_dictionary(id).ok()));
+
+    // Resources closest to the page take precedence
+    let mut merged = Dictionary::new();
+    for resources in layers {
+        for (category, value) in resources.iter() {
+            let entries = resolve(doc, value).as_dict().ok();
+            if !merged.has(category) {
+                merged.set(
+                    category.clone(),
+                    entries.cloned().map_or_else(|| value.clone(), Object::Dictionary),
+                );
+            } else if let (Some(entries), Ok(Object::Dictionary(existing))) = (entries, merged.get_mut(category)) {
+                for (name, entry) in entries.iter() {
+                    if !existing.has(name) {
+                        existing.set(name.clone(), entry.clone());
+                    }
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// Operators painting an opaque box in default user space
+fn overlay([x0, y0, x1, y1]: &[f64; 4], color: &Color) -> Vec<Operation> {
+    let color = match *color {
+        Color::Rgb(r, g, b) => Operation::new("rg", vec![real(r), real(g), real(b)]),
+        Color::Cmyk(c, m, y, k) => Operation::new("k", vec![real(c), real(m), real(y), real(k)]),
+        Color::Gray(g) => Operation::new("g", vec![real(g)]),
+        Color::Other => Operation::new("g", vec![real(0.0)]),
+    };
+    vec![
+        Operation::new("q", vec![]),
+        color,
+        Operation::new("re", vec![real(*x0), real(*y0), real(x1 - x0), real(y1 - y0)]),
+        Operation::new("f", vec![]),
+        Operation::new("Q", vec![]),
+    ]
+}
+
+pub(super) fn real(value: f64) -> Object {
+    Object::Real(value as f32)
+}
+
+/// Flate-compressed stream with the given dictionary
+pub(super) fn flate_stream(mut dict: Dictionary, data: &[u8]) -> Result<Stream> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    dict.set("Filter", "FlateDecode");
+    Ok(Stream::new(dict, encoder.finish()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        detectors::redaction,
+        document::fixtures::{self, with_content},
+        limits::ResourceLimits,
+        remediation::run,
+        text,
+    };
+    use tempfile::tempdir;
+
+    fn with_redact_annotation() -> Vec<u8> {
+        let mut doc = with_content(b"BT /F1 10 Tf 72 700 Td (secret public) Tj ET");
+        let redact = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Redact",
+            "Rect" => vec![70.into(), 695.into(), 103.into(), 712.into()],
+            "IC" => vec![0.into(), 0.into(), 0.into()],
+        });
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Annots", vec![redact.into()]);
+        fixtures::save(doc)
+    }
+
+    #[test]
+    fn test_redact_annotation_is_burned_in() {
+        let rewrite = ApplyRedactions.apply(&with_redact_annotation()).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        let pages = text::extract(&doc);
+        assert_eq!(pages[0].text().trim(), "public");
+        // The remaining text keeps its position
+        assert_eq!(pages[0].spans[0].chars[0].bbox[0], 102.0);
+
+        let page = doc.get_dictionary(fixtures::PAGE_ID).unwrap();
+        assert!(!page.has(b"Annots"));
+        assert!(redaction::detect(&doc).is_empty());
+        assert!(!rewrite.output.windows(6).any(|w| w == b"secret"));
+    }
+
+    #[test]
+    fn test_drawn_overlay_only_removes_text_beneath() {
+        let doc = with_content(
+            b"BT /F1 10 Tf 72 700 Td (SSN 123-45-6789) Tj ET 0 g 70 695 100 15 re f \
+              BT /F1 10 Tf 72 705 Td (on top) Tj ET",
+        );
+        let rewrite = ApplyRedactions.apply(&fixtures::save(doc)).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        let text = text::extract(&doc)[0].text();
+        assert!(!text.contains("6789"));
+        assert!(text.contains("on top"));
+        assert!(rewrite.reasons.contains_key(&fixtures::PAGE_ID));
+    }
+
+    #[test]
+    fn test_marked_content_and_inline_images_are_redacted() {
+        let mut doc = with_content(
+            b"/Span <</ActualText (secret)>> BDC BT /F1 10 Tf 72 700 Td (secret public) Tj ET EMC \
+              q 20 0 0 10 75 698 cm BI /W 1 /H 1 /BPC 8 /CS /G /F /AHx ID 00> EI Q",
+        );
+        let redact = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Redact",
+            "Rect" => vec![70.into(), 695.into(), 103.into(), 712.into()],
+        });
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Annots", vec![redact.into()]);
+        let rewrite = ApplyRedactions.apply(&fixtures::save(doc)).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        let content = Content::decode(&doc.get_page_content(fixtures::PAGE_ID).unwrap()).unwrap();
+        assert!(!content.operations.iter().any(|op| op.operator == "BI"));
+        let encoded = content.encode().unwrap();
+        assert!(!encoded.windows(6).any(|w| w == b"secret"));
+        assert!(String::from_utf8_lossy(&encoded).contains("BDC"));
+        assert!(rewrite.reasons[&fixtures::PAGE_ID].contains("1 inline images"));
+    }
+
+    #[test]
+    fn test_overlapping_form_and_appearance_are_clipped() {
+        let form = || {
+            Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Form",
+                    "BBox" => vec![0.into(), 0.into(), 200.into(), 20.into()],
+                },
+                b"BT /F1 10 Tf 2 5 Td (secret public) Tj ET".to_vec(),
+            )
+        };
+        let mut doc = with_content(b"q 1 0 0 1 70 695 cm /Fm0 Do Q");
+        let form_id = doc.add_object(form());
+        let appearance = doc.add_object(form());
+        let resources = with_resources(
+            &doc,
+            own_resources(&doc, fixtures::PAGE_ID),
+            &[("XObject", b"Fm0".to_vec(), form_id)],
+        );
+        let note = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "FreeText",
+            "Rect" => vec![70.into(), 645.into(), 270.into(), 665.into()],
+            "AP" => dictionary! { "N" => appearance },
+        });
+        let redact = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Redact",
+            "Rect" => vec![70.into(), 640.into(), 103.into(), 712.into()],
+        });
+        let page = doc.get_dictionary_mut(fixtures::PAGE_ID).unwrap();
+        page.set("Annots", vec![note.into(), redact.into()]);
+        page.set("Resources", resources);
+        let rewrite = ApplyRedactions.apply(&fixtures::save(doc)).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        let shown = |stream: &Stream| String::from_utf8_lossy(&stream_data(stream).unwrap()).into_owned();
+        let content = Content::decode(&doc.get_page_content(fixtures::PAGE_ID).unwrap()).unwrap();
+        let Some(Object::Name(name)) = content
+            .operations
+            .iter()
+            .find(|op| op.operator == "Do")
+            .and_then(|op| op.operands.first())
+        else {
+            panic!("the form is no longer painted");
+        };
+        assert_ne!(name, b"Fm0");
+        let clipped = xobject(&doc, &own_resources(&doc, fixtures::PAGE_ID), name).unwrap();
+        assert!(shown(&clipped).contains("public"));
+        assert!(!shown(&clipped).contains("secret"));
+
+        let appearance = doc.get_object(appearance).and_then(Object::as_stream).unwrap();
+        assert!(shown(appearance).contains("public"));
+        assert!(!shown(appearance).contains("secret"));
+        let page = doc.get_dictionary(fixtures::PAGE_ID).unwrap();
+        assert_eq!(page.get(b"Annots").and_then(Object::as_array).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_verifies_redaction() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("in.pdf");
+        std::fs::write(&input, with_redact_annotation()).unwrap();
+
+        let output = dir.path().join("out.pdf");
+        let report = run(&ApplyRedactions, &input, &output, true, ResourceLimits::default())
+            .await
+            .unwrap();
+        assert!(report.verification.passed, "{:?}", report.verification);
+        assert_eq!(report.verification.resolved.len(), 1);
+    }
+}
@@ -0,0 +1,450 @@
+//! Reconstruction of damaged documents
+//! Created: 2026-10-16 11:08:01 UTC
+//! Author: kartik4091
+//!
+//! Rebuilds a file whose cross-reference data is missing or broken from the
+//! objects themselves: every `N G obj` … `endobj` body found in the byte
+//! stream is parsed (the last definition of a number wins, as in an
+//! incremental update), object streams are unpacked, stream lengths are
+//! taken from the data, and the trailer is rebuilt from the last trailer or
+//! xref stream naming a usable catalog. Anything that had to be inferred —
+//! a catalog found by type, a dropped body, a corrected length — is recorded
+//! in the report.
+//!
+//! Unlike the [`Remediation`](super::Remediation) operations, repair cannot
+//! diff its input, which by definition does not load. The output is still
+//! verified before it is written: it must analyze, and when the input
+//! analyzes too, no finding may appear that the input did not have. Like
+//! the other operations, it runs under the configured resource limits.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+use super::{
+    check_limits, introduced, verification_error, verifier, write, write_verified, Verification,
+};
+use crate::{
+    document::{
+        name_value,
+        objects::scan_objects,
+        objstm,
+        parser::{parse_indirect, Parser},
+        revisions::find,
+        sha256_hex,
+    },
+    error::{Error, Result},
+    limits::{self, ResourceLimits},
+    PdfAnalysis,
+};
+
+/// Version written when the file has no readable header
+const DEFAULT_VERSION: &str = "1.7";
+
+/// Trailer entries carried over into the rebuilt trailer
+const TRAILER_KEYS: [&[u8]; 3] = [b"Info", b"ID", b"Encrypt"];
+
+/// Something the rebuilt file assumes rather than reads
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairGuess {
+    /// Object the guess is about, if any
+    pub object: Option<ObjectId>,
+
+    /// What was assumed and why
+    pub description: String,
+}
+
+/// Outcome of a repair, or what it would produce in a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairReport {
+    /// Whether output writing was skipped
+    pub dry_run: bool,
+
+    /// SHA-256 of the input
+    pub input_sha256: String,
+
+    /// Input size in bytes
+    pub input_size: usize,
+
+    /// SHA-256 of the output (predicted in a dry run)
+    pub output_sha256: String,
+
+    /// Output size in bytes
+    pub output_size: usize,
+
+    /// Whether the input loaded before repair
+    pub loadable: bool,
+
+    /// Objects written to the output
+    pub recovered: usize,
+
+    /// Objects unpacked from object streams
+    pub from_object_streams: usize,
+
+    /// Catalog of the rebuilt trailer
+    pub root: ObjectId,
+
+    /// Assumptions made while rebuilding
+    pub guesses: Vec<RepairGuess>,
+
+    /// Path the output was written to
+    pub written_to: Option<PathBuf>,
+
+    /// Re-analysis of the output
+    #[serde(default)]
+    pub verification: Verification,
+}
+
+/// Rebuilds a document from its object bodies
+#[instrument(skip(data))]
+pub fn repair(data: &[u8]) -> Result<(RepairReport, Vec<u8>)> {
+    let mut guesses = Vec::new();
+    let mut guess = |object: Option<ObjectId>, description: String| guesses.push(RepairGuess { object, description });
+
+    let mut definitions: BTreeMap<ObjectId, usize> = BTreeMap::new();
+    let mut objects = BTreeMap::new();
+    for raw in scan_objects(data) {
+        *definitions.entry(raw.id).or_default() += 1;
+        match parse_indirect(&data[..raw.end], raw.offset) {
+            Ok(parsed) => {
+                objects.insert(raw.id, parsed.object);
+            }
+            Err(e) => guess(
+                Some(raw.id),
+                format!("Body at offset {} does not parse and was dropped: {}", raw.offset, e),
+            ),
+        }
+    }
+    for (&id, &count) in definitions.iter().filter(|(_, &count)| count > 1) {
+        guess(
+            Some(id),
+            format!("Defined {} times; the last definition was kept", count),
+        );
+    }
+
+    // Cross-reference streams are replaced by the new table; their
+    // dictionaries double as trailers
+    let mut trailers = trailer_dictionaries(data);
+    objects.retain(|_, object| match object {
+        Object::Stream(stream) if name_value(&stream.dict, b"Type") == Some(b"XRef") => {
+            trailers.push(stream.dict.clone());
+            false
+        }
+        _ => true,
+    });
+
+    let mut from_object_streams = 0;
+    let containers: Vec<ObjectId> = objects
+        .iter()
+        .filter(|(_, object)| matches!(object, Object::Stream(stream) if objstm::is_object_stream(stream)))
+        .map(|(&id, _)| id)
+        .collect();
+    for id in containers {
+        let Some(Object::Stream(stream)) = objects.remove(&id) else {
+            continue;
+        };
+        let decoded = match objstm::decode(&stream) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                guess(
+                    Some(id),
+                    format!("Object stream does not decode and was dropped: {}", e),
+                );
+                continue;
+            }
+        };
+        for compressed in decoded.objects {
+            let number = (compressed.number, 0);
+            match compressed.object {
+                // A direct definition is taken to be the newer one
+                Ok(_) if objects.contains_key(&number) => {}
+                Ok(object) => {
+                    objects.insert(number, object);
+                    from_object_streams += 1;
+                }
+                Err(e) => guess(
+                    Some(number),
+                    format!("Object in stream {} {} does not parse: {}", id.0, id.1, e),
+                ),
+            }
+        }
+    }
+
+    for (&id, object) in objects.iter_mut() {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        let actual = stream.content.len() as i64;
+        match stream.dict.get(b"Length") {
+            Ok(Object::Integer(length)) if *length == actual => {}
+            Ok(Object::Integer(length)) => guess(
+                Some(id),
+                format!(
+                    "/Length {} does not match the {} bytes up to endstream; corrected",
+                    length, actual
+                ),
+            ),
+            // Indirect lengths are resolved into the dictionary
+            Ok(Object::Reference(_)) => {}
+            _ => guess(Some(id), format!("/Length missing or invalid; set to {}", actual)),
+        }
+        stream.dict.set("Length", actual);
+    }
+
+    let root = find_root(&mut objects, &trailers, &mut guess)?;
+    let mut trailer = dictionary! { "Root" => root };
+    if let Some(previous) = trailers
+        .iter()
+        .rev()
+        .find(|t| t.get(b"Root").and_then(Object::as_reference).ok() == Some(root))
+    {
+        for key in TRAILER_KEYS {
+            match previous.get(key) {
+                Ok(Object::Reference(id)) if !objects.contains_key(id) => guess(
+                    Some(*id),
+                    format!(
+                        "/{} points at a missing object and was dropped",
+                        String::from_utf8_lossy(key)
+                    ),
+                ),
+                Ok(value) => trailer.set(key, value.clone()),
+                Err(_) => {}
+            }
+        }
+    } else if trailers.is_empty() {
+        guess(
+            None,
+            "No trailer found; the trailer was rebuilt from the objects".to_string(),
+        );
+    }
+
+    let version = header_version(data).unwrap_or_else(|| {
+        guess(None, format!("No %PDF header; wrote version {}", DEFAULT_VERSION));
+        DEFAULT_VERSION.to_string()
+    });
+
+    let mut doc = Document::with_version(version);
+    doc.max_id = objects.keys().map(|id| id.0).max().unwrap_or(0);
+    doc.objects = objects;
+    doc.trailer = trailer;
+    let output = write(&mut doc)?;
+    Document::load_mem(&output).map_err(|e| Error::Pdf(format!("Rebuilt file still does not load: {}", e)))?;
+
+    let report = RepairReport {
+        dry_run: true,
+        input_sha256: sha256_hex(data),
+        input_size: data.len(),
+        output_sha256: sha256_hex(&output),
+        output_size: output.len(),
+        loadable: Document::load_mem(data).is_ok(),
+        recovered: doc.objects.len(),
+        from_object_streams,
+        root,
+        guesses,
+        written_to: None,
+        verification: Verification::default(),
+    };
+    Ok((report, output))
+}
+
+/// Repairs `input`, writing `output` unless `dry_run` is set or the output
+/// fails verification; the input is never overwritten
+pub async fn run(
+    input: &Path,
+    output: &Path,
+    dry_run: bool,
+    resource_limits: ResourceLimits,
+) -> Result<RepairReport> {
+    limits::scope(resource_limits, rebuild(input, output, dry_run)).await
+}
+
+async fn rebuild(input: &Path, output: &Path, dry_run: bool) -> Result<RepairReport> {
+    let data = tokio::fs::read(input).await?;
+    check_limits(&data)?;
+    let (mut report, rebuilt) = repair(&data)?;
+    check_limits(&rebuilt)?;
+    // A damaged input may not analyze at all, leaving nothing to compare to
+    let before = verifier(input)?.analyze_bytes(&data).await.ok();
+    let check = |after: &PdfAnalysis| {
+        let introduced = before
+            .as_ref()
+            .map(|before| introduced(before, after))
+            .unwrap_or_default();
+        Verification {
+            passed: introduced.is_empty(),
+            introduced,
+            ..Default::default()
+        }
+    };
+    if dry_run {
+        let after = verifier(output)?
+            .analyze_bytes(&rebuilt)
+            .await
+            .map_err(verification_error)?;
+        report.verification = check(&after);
+        return Ok(report);
+    }
+
+    if let (Ok(a), Ok(b)) = (
+        tokio::fs::canonicalize(input).await,
+        tokio::fs::canonicalize(output).await,
+    ) {
+        if a == b {
+            return Err(Error::Analysis(format!(
+                "Refusing to overwrite the input {}",
+                input.display()
+            )));
+        }
+    }
+    report.dry_run = false;
+    report.verification = write_verified(output, &rebuilt, check).await?;
+    if report.verification.passed {
+        info!(
+            "Repaired {} into {} ({} objects, {} guesses)",
+            input.display(),
+            output.display(),
+            report.recovered,
+            report.guesses.len()
+        );
+        report.written_to = Some(output.to_path_buf());
+    } else {
+        warn!(
+            "Verification of the repaired {} failed, nothing written: {} findings introduced",
+            output.display(),
+            report.verification.introduced.len()
+        );
+    }
+    Ok(report)
+}
+
+/// Dictionaries following every `trailer` keyword, in file order
+fn trailer_dictionaries(data: &[u8]) -> Vec<Dictionary> {
+    let mut trailers = Vec::new();
+    let mut cursor = 0;
+    while let Some(pos) = find(data, b"trailer", cursor) {
+        cursor = pos + 7;
+        if let Ok(Object::Dictionary(dict)) = Parser::new(data, cursor).parse_object() {
+            trailers.push(dict);
+        }
+    }
+    trailers
+}
+
+/// Catalog named by the last usable trailer, else the last object typed as
+/// a catalog, else a new catalog over the root of the page tree
+fn find_root(
+    objects: &mut BTreeMap<ObjectId, Object>,
+    trailers: &[Dictionary],
+    guess: &mut impl FnMut(Option<ObjectId>, String),
+) -> Result<ObjectId> {
+    let is_catalog = |object: &Object| matches!(object, Object::Dictionary(dict) if dict.has(b"Pages"));
+    let named = trailers
+        .iter()
+        .rev()
+        .filter_map(|trailer| trailer.get(b"Root").and_then(Object::as_reference).ok())
+        .find(|id| objects.get(id).is_some_and(is_catalog));
+    if let Some(root) = named {
+        return Ok(root);
+    }
+
+    let typed = |wanted: &[u8]| {
+        objects
+            .iter()
+            .rev()
+            .find(|(_, object)| matches!(object, Object::Dictionary(dict) if name_value(dict, b"Type") == Some(wanted)))
+            .map(|(&id, _)| id)
+    };
+    if let Some(root) = typed(b"Catalog").filter(|id| is_catalog(&objects[id])) {
+        guess(
+            Some(root),
+            "No trailer names a usable catalog; took the last /Catalog object".to_string(),
+        );
+        return Ok(root);
+    }
+
+    let pages = objects
+        .iter()
+        .rev()
+        .find(|(_, object)| {
+            matches!(object, Object::Dictionary(dict)
+                if name_value(dict, b"Type") == Some(b"Pages") && !dict.has(b"Parent"))
+        })
+        .map(|(&id, _)| id)
+        .ok_or_else(|| Error::Pdf("No catalog or page tree found; nothing to rebuild".into()))?;
+    let root = (objects.keys().map(|id| id.0).max().unwrap_or(0) + 1, 0);
+    objects.insert(
+        root,
+        Object::Dictionary(dictionary! { "Type" => "Catalog", "Pages" => pages }),
+    );
+    guess(
+        Some(root),
+        format!(
+            "No catalog found; created one for the page tree {} {}",
+            pages.0, pages.1
+        ),
+    );
+    Ok(root)
+}
+
+/// Version in the `%PDF-x.y` header, if the file starts with one
+fn header_version(data: &[u8]) -> Option<String> {
+    let start = find(&data[..data.len().min(1024)], b"%PDF-", 0)? + 5;
+    let version: String = data[start..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit() || **b == b'.')
+        .map(|&b| b as char)
+        .collect();
+    (!version.is_empty()).then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures::{self, CATALOG_ID};
+
+    /// Fixture cut just before its xref table, leaving no xref and no trailer
+    fn truncated() -> Vec<u8> {
+        let data = fixtures::simple_pdf();
+        let xref = data.windows(6).position(|w| w == b"\nxref\n").unwrap();
+        data[..=xref].to_vec()
+    }
+
+    #[test]
+    fn test_rebuild_without_xref_or_trailer() {
+        let data = truncated();
+        let (report, output) = repair(&data).unwrap();
+
+        assert!(!report.loadable);
+        assert_eq!(report.recovered, 6);
+        assert_eq!(report.root, CATALOG_ID);
+        assert!(report
+            .guesses
+            .iter()
+            .any(|g| g.description.starts_with("No trailer found")));
+        assert!(report
+            .guesses
+            .iter()
+            .any(|g| g.object == Some(CATALOG_ID) && g.description.contains("/Catalog")));
+        let doc = Document::load_mem(&output).unwrap();
+        assert_eq!(doc.get_pages().len(), 1);
+    }
+
+    #[test]
+    fn test_guesses_lengths_and_duplicates() {
+        let mut data = truncated();
+        data.extend_from_slice(b"4 0 obj\n<< /Length 99 >>\nstream\nBT ET\nendstream\nendobj\n");
+        let (report, output) = repair(&data).unwrap();
+
+        let about = |id: ObjectId| report.guesses.iter().filter(move |g| g.object == Some(id));
+        assert!(about((4, 0)).any(|g| g.description == "Defined 2 times; the last definition was kept"));
+        assert!(about((4, 0)).any(|g| g.description.contains("/Length 99")));
+        let doc = Document::load_mem(&output).unwrap();
+        let stream = doc.get_object((4, 0)).unwrap().as_stream().unwrap();
+        assert_eq!(stream.content, b"BT ET");
+    }
+}
@@ -0,0 +1,466 @@
+//! Anti-forensic cleaning of active content and metadata
+//! Created: 2026-10-16 11:02:55 UTC
+//! Author: kartik4091
+//!
+//! Removes JavaScript (including the scripts of XFA forms), embedded files,
+//! actions, external links and metadata according to a [`SanitizePolicy`].
+//! Unwanted entries are cut
+//! from every dictionary that holds them (catalog, pages, annotations,
+//! name trees and the trailer), objects no longer reachable from the
+//! trailer are dropped, and the document is written as a single fresh
+//! revision: superseded object versions, orphans and the old xref chain do
+//! not survive. Every touched object carries the entries removed from it.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{disarm::strip_xfa_scripts, write, Remediation, Rewrite};
+use crate::{
+    detectors::reader_features::ReaderFeature,
+    document::{collect_references, name_value},
+    error::{Error, Result},
+    PdfAnalysis,
+};
+
+/// Action types removed by [`SanitizePolicy::actions`]
+const ACTIONS: [&[u8]; 14] = [
+    b"Launch",
+    b"SubmitForm",
+    b"ImportData",
+    b"ResetForm",
+    b"Hide",
+    b"Named",
+    b"SetOCGState",
+    b"Rendition",
+    b"Trans",
+    b"GoTo3DView",
+    b"Movie",
+    b"Sound",
+    b"Thread",
+    b"RichMediaExecute",
+];
+
+/// What a sanitizer removes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SanitizePolicy {
+    /// JavaScript actions, document-level scripts and XFA scripts
+    pub javascript: bool,
+
+    /// Embedded files, associated files and file attachment annotations
+    pub embedded_files: bool,
+
+    /// /OpenAction, additional actions and launch, form and media actions
+    pub actions: bool,
+
+    /// URI and remote go-to actions
+    pub external_links: bool,
+
+    /// Document information dictionary and XMP packets
+    pub metadata: bool,
+}
+
+impl Default for SanitizePolicy {
+    /// Removes everything
+    fn default() -> Self {
+        Self {
+            javascript: true,
+            embedded_files: true,
+            actions: true,
+            external_links: true,
+            metadata: true,
+        }
+    }
+}
+
+impl SanitizePolicy {
+    /// Why an entry is removed regardless of its value
+    fn entry(&self, key: &[u8]) -> Option<&'static str> {
+        match key {
+            b"OpenAction" | b"AA" if self.actions => Some("automatic action"),
+            b"JavaScript" | b"JS" if self.javascript => Some("JavaScript"),
+            b"EmbeddedFiles" | b"AF" if self.embedded_files => Some("embedded file"),
+            b"Metadata" if self.metadata => Some("XMP metadata"),
+            _ => None,
+        }
+    }
+
+    /// Why a dictionary is removed wherever it is used
+    fn object(&self, dict: &Dictionary) -> Option<&'static str> {
+        match name_value(dict, b"S") {
+            Some(b"JavaScript") if self.javascript => return Some("JavaScript action"),
+            Some(b"URI") | Some(b"GoToR") if self.external_links => return Some("external link"),
+            Some(b"GoToE") if self.embedded_files => return Some("embedded file action"),
+            Some(action) if self.actions && ACTIONS.contains(&action) => return Some("action"),
+            _ => {}
+        }
+        if self.javascript && dict.has(b"JS") {
+            return Some("JavaScript action");
+        }
+        if self.embedded_files
+            && (dict.has(b"EF")
+                || name_value(dict, b"Type") == Some(b"EmbeddedFile")
+                || name_value(dict, b"Subtype") == Some(b"FileAttachment"))
+        {
+            return Some("embedded file");
+        }
+        None
+    }
+
+    /// Whether the policy removes a reader feature
+    fn removes(&self, feature: ReaderFeature) -> bool {
+        match feature {
+            ReaderFeature::JavaScript => self.javascript,
+            ReaderFeature::Attachments => self.embedded_files,
+            ReaderFeature::ExternalLinks => self.external_links,
+            ReaderFeature::Launch | ReaderFeature::FormSubmission => self.actions,
+            ReaderFeature::Xfa | ReaderFeature::Multimedia => false,
+        }
+    }
+}
+
+/// Removes active content and metadata and rewrites the document as a single revision
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sanitize {
+    /// What to remove
+    pub policy: SanitizePolicy,
+}
+
+impl Remediation for Sanitize {
+    fn name(&self) -> &'static str {
+        "sanitize"
+    }
+
+    #[instrument(skip(self, data))]
+    fn apply(&self, data: &[u8]) -> Result<Rewrite> {
+        let mut doc = Document::load_mem(data).map_err(|e| Error::Pdf(format!("Failed to load input: {}", e)))?;
+        let mut cleaner = Cleaner {
+            policy: &self.policy,
+            unwanted: doc
+                .objects
+                .iter()
+                .filter_map(|(&id, object)| self.policy.object(dictionary(object)?).map(|reason| (id, reason)))
+                .collect(),
+            dropped: Vec::new(),
+        };
+
+        let mut reasons = BTreeMap::new();
+        let ids: Vec<ObjectId> = doc.objects.keys().copied().collect();
+        for id in ids {
+            let mut object = doc.objects[&id].clone();
+            let mut removed = BTreeSet::new();
+            cleaner.clean(&mut object, b"", &mut removed);
+            if !removed.is_empty() {
+                doc.objects.insert(id, object);
+                reasons.insert(
+                    id,
+                    format!("Removed {}", removed.into_iter().collect::<Vec<_>>().join(", ")),
+                );
+            }
+        }
+
+        if self.policy.metadata {
+            if let Some(info) = doc.trailer.remove(b"Info") {
+                cleaner.discard(&info, "document information");
+            }
+        }
+        cleaner.clean_dictionary(&mut doc.trailer, &mut BTreeSet::new());
+        if self.policy.javascript {
+            strip_xfa_scripts(&mut doc, &mut reasons)?;
+        }
+
+        let dropped = cleaner.dropped_objects(&doc);
+        for id in doc.prune_objects() {
+            let reason = dropped.get(&id).copied().unwrap_or("unreachable object");
+            reasons.insert(id, format!("Removed {}", reason));
+        }
+
+        let output = write(&mut doc)?;
+        debug!("Sanitizing touched {} objects", reasons.len());
+        Ok(Rewrite { output, reasons })
+    }
+
+    fn targets(&self, analysis: &PdfAnalysis) -> Vec<String> {
+        let mut targets = Vec::new();
+        for usage in &analysis.reader_features.features {
+            if self.policy.removes(usage.feature) {
+                targets.push(format!("{:?} in use", usage.feature));
+            }
+            if self.policy.actions && usage.auto_triggered {
+                targets.push(format!("Auto-triggered {:?}", usage.feature));
+            }
+        }
+        if self.policy.javascript && analysis.xfa.as_ref().is_some_and(|xfa| !xfa.scripts.is_empty()) {
+            targets.push("XFA scripts".to_string());
+        }
+        if self.policy.metadata {
+            if analysis.metadata.author.is_some() {
+                targets.push("Author metadata".to_string());
+            }
+            if analysis.metadata.title.is_some() {
+                targets.push("Title metadata".to_string());
+            }
+        }
+        targets
+    }
+}
+
+/// Dictionary of a dictionary or stream object
+fn dictionary(object: &Object) -> Option<&Dictionary> {
+    match object {
+        Object::Dictionary(dict) => Some(dict),
+        Object::Stream(stream) => Some(&stream.dict),
+        _ => None,
+    }
+}
+
+/// State of one sanitizing pass
+struct Cleaner<'a> {
+    policy: &'a SanitizePolicy,
+
+    /// Indirect objects removed wherever they are referenced
+    unwanted: BTreeMap<ObjectId, &'static str>,
+
+    /// Objects referenced from removed values, with the reason
+    dropped: Vec<(ObjectId, &'static str)>,
+}
+
+impl Cleaner<'_> {
+    /// Why a value is removed from its container
+    fn unwanted(&self, value: &Object) -> Option<&'static str> {
+        match value {
+            Object::Reference(id) => self.unwanted.get(id).copied(),
+            _ => self.policy.object(dictionary(value)?),
+        }
+    }
+
+    /// Removes unwanted values below `object`, which is stored under `key`,
+    /// noting each removal as `/Key (reason)`
+    fn clean(&mut self, object: &mut Object, key: &[u8], removed: &mut BTreeSet<String>) {
+        match object {
+            Object::Dictionary(dict) => self.clean_dictionary(dict, removed),
+            Object::Stream(stream) => self.clean_dictionary(&mut stream.dict, removed),
+            Object::Array(items) => {
+                let mut kept = Vec::with_capacity(items.len());
+                for mut item in items.drain(..) {
+                    match self.unwanted(&item) {
+                        Some(reason) => {
+                            self.discard(&item, reason);
+                            removed.insert(format!("{} entry ({})", label(key), reason));
+                        }
+                        None => {
+                            self.clean(&mut item, key, removed);
+                            kept.push(item);
+                        }
+                    }
+                }
+                *items = kept;
+            }
+            _ => {}
+        }
+    }
+
+    fn clean_dictionary(&mut self, dict: &mut Dictionary, removed: &mut BTreeSet<String>) {
+        let keys: Vec<Vec<u8>> = dict.iter().map(|(key, _)| key.clone()).collect();
+        for key in keys {
+            let reason = self
+                .policy
+                .entry(&key)
+                .or_else(|| dict.get(&key).ok().and_then(|value| self.unwanted(value)));
+            match reason {
+                Some(reason) => {
+                    if let Some(value) = dict.remove(&key) {
+                        self.discard(&value, reason);
+                    }
+                    removed.insert(format!("{} ({})", label(&key), reason));
+                }
+                None => {
+                    if let Ok(value) = dict.get_mut(&key) {
+                        self.clean(value, &key, removed);
+                    }
+                }
+            }
+        }
+    }
+
+    fn discard(&mut self, value: &Object, reason: &'static str) {
+        let mut ids = Vec::new();
+        collect_references(value, &mut ids);
+        self.dropped.extend(ids.into_iter().map(|id| (id, reason)));
+    }
+
+    /// Objects reachable from removed values, attributed to the first
+    /// removal reaching them
+    fn dropped_objects(&self, doc: &Document) -> BTreeMap<ObjectId, &'static str> {
+        let mut found = BTreeMap::new();
+        let mut pending = self.dropped.clone();
+        pending.reverse();
+        while let Some((id, reason)) = pending.pop() {
+            if found.contains_key(&id) {
+                continue;
+            }
+            found.insert(id, reason);
+            if let Some(object) = doc.objects.get(&id) {
+                let mut ids = Vec::new();
+                collect_references(object, &mut ids);
+                pending.extend(ids.into_iter().rev().map(|id| (id, reason)));
+            }
+        }
+        found
+    }
+}
+
+fn label(key: &[u8]) -> String {
+    if key.is_empty() {
+        "array".to_string()
+    } else {
+        format!("/{}", String::from_utf8_lossy(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{
+        fixtures::{self, CATALOG_ID, PAGE_ID},
+        stream_data,
+    };
+    use lopdf::{dictionary, Stream};
+
+    /// Fixture with an auto-run script, a web link, an attachment, both
+    /// kinds of metadata and an orphan, saved with an incremental update
+    fn loaded() -> (Vec<u8>, ObjectId, ObjectId, ObjectId) {
+        let mut doc = fixtures::document();
+        let js = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.alert(1)"),
+        });
+        let uri = doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::string_literal("https://example.com"),
+        });
+        let link = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            "A" => uri,
+        });
+        let file = doc.add_object(Stream::new(
+            dictionary! { "Type" => "EmbeddedFile" },
+            b"secret".to_vec(),
+        ));
+        let spec = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("secret.txt"),
+            "EF" => dictionary! { "F" => file },
+        });
+        let xmp = doc.add_object(Stream::new(
+            dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+            b"<x:xmpmeta/>".to_vec(),
+        ));
+        let info = doc.add_object(dictionary! { "Author" => Object::string_literal("Jane") });
+        doc.add_object(Object::string_literal("orphan"));
+
+        doc.get_dictionary_mut(PAGE_ID)
+            .unwrap()
+            .set("Annots", vec![link.into()]);
+        let catalog = doc.get_dictionary_mut(CATALOG_ID).unwrap();
+        catalog.set("OpenAction", js);
+        catalog.set("Metadata", xmp);
+        catalog.set(
+            "Names",
+            dictionary! {
+                "EmbeddedFiles" => dictionary! {
+                    "Names" => vec![Object::string_literal("secret.txt"), spec.into()],
+                },
+            },
+        );
+        doc.trailer.set("Info", info);
+        let data = fixtures::append_revision_with_trailer(
+            &fixtures::save(doc),
+            &[(4, "<< /Length 0 >>\nstream\n\nendstream")],
+            &format!("/Info {} {} R ", info.0, info.1),
+        );
+        (data, js, uri, file)
+    }
+
+    #[test]
+    fn test_sanitize_removes_everything() {
+        let (data, js, uri, file) = loaded();
+        let rewrite = Sanitize::default().apply(&data).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        let catalog = doc.get_dictionary(CATALOG_ID).unwrap();
+        assert!(!catalog.has(b"OpenAction") && !catalog.has(b"Metadata"));
+        assert!(!catalog.get(b"Names").unwrap().as_dict().unwrap().has(b"EmbeddedFiles"));
+        let link = doc
+            .get_dictionary(PAGE_ID)
+            .unwrap()
+            .get(b"Annots")
+            .unwrap()
+            .as_array()
+            .unwrap()[0]
+            .as_reference()
+            .unwrap();
+        assert!(!doc.get_dictionary(link).unwrap().has(b"A"));
+        assert!(!doc.trailer.has(b"Info") && !doc.trailer.has(b"Prev"));
+        for id in [js, uri, file] {
+            assert!(!doc.objects.contains_key(&id));
+        }
+
+        let reason = |id: ObjectId| rewrite.reasons[&id].as_str();
+        assert!(reason(CATALOG_ID).contains("/OpenAction (automatic action)"));
+        assert!(reason(CATALOG_ID).contains("/EmbeddedFiles (embedded file)"));
+        assert_eq!(reason(link), "Removed /A (external link)");
+        assert_eq!(reason(js), "Removed automatic action");
+        assert_eq!(reason(file), "Removed embedded file");
+        assert!(rewrite.reasons.values().any(|r| r == "Removed unreachable object"));
+        assert!(rewrite.reasons.values().any(|r| r == "Removed document information"));
+
+        let eof = rewrite.output.windows(5).filter(|w| w == b"%%EOF").count();
+        assert_eq!(eof, 1);
+    }
+
+    #[test]
+    fn test_policy_keeps_selected_content() {
+        let (data, js, uri, _) = loaded();
+        let policy = SanitizePolicy {
+            external_links: false,
+            metadata: false,
+            actions: false,
+            ..Default::default()
+        };
+        let rewrite = Sanitize { policy }.apply(&data).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        // The script is still removed because JavaScript is not kept
+        let catalog = doc.get_dictionary(CATALOG_ID).unwrap();
+        assert!(!catalog.has(b"OpenAction"));
+        assert!(!doc.objects.contains_key(&js));
+        assert_eq!(rewrite.reasons[&js], "Removed JavaScript action");
+        assert!(catalog.has(b"Metadata") && doc.trailer.has(b"Info"));
+        assert!(doc.objects.contains_key(&uri));
+        assert!(!rewrite.reasons.contains_key(&uri));
+    }
+
+    #[test]
+    fn test_javascript_removal_covers_xfa_scripts() {
+        let mut doc = fixtures::document();
+        let template = doc.add_object(Stream::new(
+            dictionary! {},
+            b"<template><field><calculate><script>app.alert(1)</script></calculate></field></template>".to_vec(),
+        ));
+        let packets = vec![Object::string_literal("template"), template.into()];
+        doc.get_dictionary_mut(CATALOG_ID)
+            .unwrap()
+            .set("AcroForm", dictionary! { "Fields" => Vec::<Object>::new(), "XFA" => packets });
+        let rewrite = Sanitize::default().apply(&fixtures::save(doc)).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        let xfa = stream_data(doc.get_object(template).unwrap().as_stream().unwrap()).unwrap();
+        assert_eq!(xfa, b"<template><field><calculate></calculate></field></template>");
+        assert_eq!(rewrite.reasons[&template], "Removed 1 XFA scripts");
+    }
+}
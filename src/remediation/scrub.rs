@@ -0,0 +1,379 @@
+//! Field-level scrubbing of document metadata
+//! Created: 2026-10-16 11:00:54 UTC
+//! Author: kartik4091
+//!
+//! Applies a [`MetadataPolicy`] to the information dictionary and the
+//! catalog's XMP packet. Fields are gathered from both — the information
+//! dictionary wins, XMP fills in what it lacks — and each is kept, dropped
+//! or rewritten. Both places are then written from the same scrubbed
+//! fields: the XMP packet is rebuilt from them, so history, identifiers and
+//! properties without an information dictionary counterpart do not
+//! survive. The output is a full rewrite without earlier revisions.
+
+use std::{collections::BTreeMap, path::Path};
+
+use chrono::{DateTime, TimeZone, Utc};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream, StringFormat};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::{write, Remediation, Rewrite};
+use crate::{
+    document::{
+        chronology::parse_xmp_date,
+        decode_text, display_value, encode_text, info_dictionary, parse_pdf_date, stream_data,
+        xmp::{self, INFO_PROPERTIES},
+    },
+    error::{Error, Result},
+    PdfAnalysis,
+};
+
+/// Information dictionary keys holding dates
+const DATE_KEYS: [&str; 2] = ["CreationDate", "ModDate"];
+
+/// What happens to fields a policy does not name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldRule {
+    /// Left as they are
+    #[default]
+    Keep,
+
+    /// Removed
+    Drop,
+}
+
+/// What happens to creation and modification dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateRule {
+    /// Left as they are
+    #[default]
+    Keep,
+
+    /// Removed
+    Drop,
+
+    /// Replaced by the policy's normalized date
+    Normalize,
+}
+
+/// Metadata scrubbing policy, read from a YAML or TOML file
+///
+/// ```yaml
+/// default: keep
+/// keep: [Title]
+/// drop: [Author, Producer]
+/// set:
+///   Creator: Evidence unit
+/// dates: normalize
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetadataPolicy {
+    /// Rule for fields not named below
+    pub default: FieldRule,
+
+    /// Fields kept as they are
+    pub keep: Vec<String>,
+
+    /// Fields removed
+    pub drop: Vec<String>,
+
+    /// Fields given a fixed value, added when absent
+    pub set: BTreeMap<String, String>,
+
+    /// Rule for dates not named above
+    pub dates: DateRule,
+
+    /// Date written by [`DateRule::Normalize`]
+    pub normalized_date: DateTime<Utc>,
+}
+
+impl Default for MetadataPolicy {
+    fn default() -> Self {
+        Self {
+            default: FieldRule::Keep,
+            keep: Vec::new(),
+            drop: Vec::new(),
+            set: BTreeMap::new(),
+            dates: DateRule::Keep,
+            normalized_date: Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap(),
+        }
+    }
+}
+
+/// Decision on one field
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Keep,
+    Drop,
+    Set(String),
+}
+
+impl MetadataPolicy {
+    /// Reads a policy from YAML, or TOML when the file ends in `.toml`
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(|e| e.to_string()),
+            _ => serde_yaml::from_str(&text).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| Error::Analysis(format!("Metadata policy {}: {}", path.display(), e)))
+    }
+
+    fn outcome(&self, key: &str) -> Outcome {
+        if let Some(value) = self.set.get(key) {
+            return Outcome::Set(value.clone());
+        }
+        if self.drop.iter().any(|k| k == key) {
+            return Outcome::Drop;
+        }
+        if self.keep.iter().any(|k| k == key) {
+            return Outcome::Keep;
+        }
+        if DATE_KEYS.contains(&key) {
+            return match self.dates {
+                DateRule::Keep => Outcome::Keep,
+                DateRule::Drop => Outcome::Drop,
+                DateRule::Normalize => Outcome::Set(pdf_date(self.normalized_date)),
+            };
+        }
+        match self.default {
+            FieldRule::Keep => Outcome::Keep,
+            FieldRule::Drop => Outcome::Drop,
+        }
+    }
+}
+
+/// Rewrites the information dictionary and XMP packet according to a policy
+#[derive(Debug, Clone, Default)]
+pub struct ScrubMetadata {
+    /// What to keep, drop and rewrite
+    pub policy: MetadataPolicy,
+}
+
+impl Remediation for ScrubMetadata {
+    fn name(&self) -> &'static str {
+        "scrub-metadata"
+    }
+
+    #[instrument(skip(self, data))]
+    fn apply(&self, data: &[u8]) -> Result<Rewrite> {
+        let mut doc = Document::load_mem(data).map_err(|e| Error::Pdf(format!("Failed to load input: {}", e)))?;
+        let before = info_dictionary(&doc).cloned().unwrap_or_default();
+        let packet = xmp_packet(&doc);
+
+        let mut fields: Vec<(String, Object)> = before
+            .iter()
+            .map(|(key, value)| (String::from_utf8_lossy(key).into_owned(), value.clone()))
+            .collect();
+        if let Some((_, text)) = &packet {
+            for (key, name) in INFO_PROPERTIES {
+                if fields.iter().any(|(k, _)| k == key) {
+                    continue;
+                }
+                if let Some(value) = xmp::property(text, name) {
+                    let value = if DATE_KEYS.contains(&key) {
+                        parse_xmp_date(&value).map(pdf_date).unwrap_or(value)
+                    } else {
+                        value
+                    };
+                    fields.push((key.to_string(), text_string(&value)));
+                }
+            }
+        }
+
+        let mut scrubbed = Dictionary::new();
+        for (key, value) in fields {
+            match self.policy.outcome(&key) {
+                Outcome::Keep => scrubbed.set(key, value),
+                Outcome::Drop => {}
+                Outcome::Set(value) => scrubbed.set(key, text_string(&value)),
+            }
+        }
+        for (key, value) in &self.policy.set {
+            if !scrubbed.has(key.as_bytes()) {
+                scrubbed.set(key.as_str(), text_string(value));
+            }
+        }
+
+        let mut reasons = BTreeMap::new();
+        if entries(&scrubbed) != entries(&before) {
+            let info = doc.trailer.get(b"Info").ok().and_then(|info| info.as_reference().ok());
+            if scrubbed.is_empty() {
+                doc.trailer.remove(b"Info");
+                if let Some(id) = info {
+                    doc.objects.remove(&id);
+                    reasons.insert(id, "Removed document information".to_string());
+                }
+            } else {
+                let id = info.unwrap_or_else(|| doc.new_object_id());
+                doc.objects.insert(id, Object::Dictionary(scrubbed.clone()));
+                doc.trailer.set("Info", id);
+                reasons.insert(id, "Scrubbed document information".to_string());
+            }
+        }
+
+        if let Some((id, text)) = packet {
+            let properties = xmp_properties(&scrubbed);
+            if properties.is_empty() {
+                doc.objects.remove(&id);
+                reasons.insert(id, "Removed XMP metadata".to_string());
+                if let Ok(root) = doc.trailer.get(b"Root").and_then(Object::as_reference) {
+                    if let Ok(catalog) = doc.get_dictionary_mut(root) {
+                        catalog.remove(b"Metadata");
+                        reasons.insert(root, "Removed /Metadata".to_string());
+                    }
+                }
+            } else {
+                let rebuilt = xmp::packet(&properties);
+                if rebuilt != text {
+                    let dict = dictionary! { "Type" => "Metadata", "Subtype" => "XML" };
+                    doc.objects
+                        .insert(id, Object::Stream(Stream::new(dict, rebuilt.into_bytes())));
+                    reasons.insert(id, "Rebuilt XMP metadata from the scrubbed fields".to_string());
+                }
+            }
+        }
+
+        if reasons.is_empty() {
+            return Ok(Rewrite {
+                output: data.to_vec(),
+                reasons,
+            });
+        }
+        let output = write(&mut doc)?;
+        debug!("Metadata scrubbing touched {} objects", reasons.len());
+        Ok(Rewrite { output, reasons })
+    }
+
+    fn targets(&self, analysis: &PdfAnalysis) -> Vec<String> {
+        let present = [
+            ("Author", analysis.metadata.author.is_some()),
+            ("Title", analysis.metadata.title.is_some()),
+            ("CreationDate", analysis.metadata.created.is_some()),
+            ("ModDate", analysis.metadata.modified.is_some()),
+        ];
+        present
+            .into_iter()
+            .filter(|&(key, present)| present && self.policy.outcome(key) == Outcome::Drop)
+            .map(|(key, _)| format!("{} metadata", key))
+            .collect()
+    }
+}
+
+/// Catalog XMP packet and the stream holding it
+fn xmp_packet(doc: &Document) -> Option<(ObjectId, String)> {
+    let id = doc.catalog().ok()?.get(b"Metadata").ok()?.as_reference().ok()?;
+    let stream = doc.get_object(id).ok()?.as_stream().ok()?;
+    let data = stream_data(stream).ok()?;
+    Some((id, String::from_utf8_lossy(&data).into_owned()))
+}
+
+/// XMP counterparts of the scrubbed fields, with dates in ISO 8601
+fn xmp_properties(info: &Dictionary) -> Vec<(&'static str, String)> {
+    INFO_PROPERTIES
+        .iter()
+        .filter_map(|&(key, name)| {
+            let Ok(Object::String(bytes, _)) = info.get(key.as_bytes()) else {
+                return None;
+            };
+            let value = if DATE_KEYS.contains(&key) {
+                parse_pdf_date(bytes)?.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+            } else {
+                decode_text(bytes)
+            };
+            Some((name, value))
+        })
+        .collect()
+}
+
+/// Comparable form of a dictionary
+fn entries(dict: &Dictionary) -> Vec<(Vec<u8>, String)> {
+    dict.iter()
+        .map(|(key, value)| (key.clone(), display_value(value)))
+        .collect()
+}
+
+fn text_string(text: &str) -> Object {
+    Object::String(encode_text(text), StringFormat::Literal)
+}
+
+fn pdf_date(date: DateTime<Utc>) -> String {
+    date.format("D:%Y%m%d%H%M%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures::{self, CATALOG_ID};
+
+    /// Fixture with an information dictionary and an XMP packet naming a
+    /// creator tool the information dictionary lacks
+    fn with_metadata() -> Vec<u8> {
+        let mut doc = fixtures::document();
+        let info = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Q3 report"),
+            "Author" => Object::string_literal("Jane Doe"),
+            "Producer" => Object::string_literal("Acrobat"),
+            "CreationDate" => Object::string_literal("D:20240301093000+01'00'"),
+            "Department" => Object::string_literal("Finance"),
+        });
+        let packet = xmp::packet(&[
+            ("dc:creator", "Jane Doe".to_string()),
+            ("xmp:CreatorTool", "Word".to_string()),
+        ]);
+        let xmp = doc.add_object(Stream::new(
+            dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+            packet.into_bytes(),
+        ));
+        doc.get_dictionary_mut(CATALOG_ID).unwrap().set("Metadata", xmp);
+        doc.trailer.set("Info", info);
+        fixtures::save(doc)
+    }
+
+    #[test]
+    fn test_scrub_by_policy() {
+        let policy: MetadataPolicy = serde_yaml::from_str(
+            "keep: [Title]\ndrop: [Author, Producer]\nset:\n  Department: Legal\ndates: normalize\n",
+        )
+        .unwrap();
+        let rewrite = ScrubMetadata { policy }.apply(&with_metadata()).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        let info = info_dictionary(&doc).unwrap();
+        let text = |key: &[u8]| info.get(key).ok().map(|v| decode_text(v.as_str().unwrap()));
+        assert_eq!(text(b"Title").as_deref(), Some("Q3 report"));
+        assert_eq!(text(b"Creator").as_deref(), Some("Word"));
+        assert_eq!(text(b"Department").as_deref(), Some("Legal"));
+        assert_eq!(text(b"CreationDate").as_deref(), Some("D:20000101000000Z"));
+        assert!(text(b"Author").is_none() && text(b"Producer").is_none());
+
+        let (id, packet) = xmp_packet(&doc).unwrap();
+        assert_eq!(rewrite.reasons[&id], "Rebuilt XMP metadata from the scrubbed fields");
+        assert!(xmp::property(&packet, "dc:creator").is_none());
+        assert_eq!(xmp::property(&packet, "dc:title").as_deref(), Some("Q3 report"));
+        assert_eq!(
+            xmp::property(&packet, "xmp:CreateDate").as_deref(),
+            Some("2000-01-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_drop_everything() {
+        let policy: MetadataPolicy = serde_yaml::from_str("default: drop\ndates: drop\n").unwrap();
+        let data = with_metadata();
+        let rewrite = ScrubMetadata { policy }.apply(&data).unwrap();
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+
+        assert!(info_dictionary(&doc).is_none());
+        assert!(!doc.get_dictionary(CATALOG_ID).unwrap().has(b"Metadata"));
+        assert_eq!(rewrite.reasons[&CATALOG_ID], "Removed /Metadata");
+        assert!(rewrite.reasons.values().any(|r| r == "Removed document information"));
+
+        let keep_all = ScrubMetadata::default().apply(&rewrite.output).unwrap();
+        assert!(keep_all.reasons.is_empty());
+        assert_eq!(keep_all.output, rewrite.output);
+    }
+}
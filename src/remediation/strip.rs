@@ -0,0 +1,149 @@
+//! Flattening of incremental updates into a single revision
+//! Created: 2026-10-16 11:06:50 UTC
+//! Author: kartik4091
+//!
+//! Rewrites the document from the effective objects of one revision — the
+//! latest by default — with a fresh cross-reference table. Superseded and
+//! freed definitions, objects no xref section indexes, and every revision
+//! after the selected one are left behind, as are objects nothing the
+//! trailer leads to references; the remediation report lists the hidden
+//! definitions that were purged. Selecting an older revision
+//! rolls the document back, so objects changed later are reported against
+//! the latest revision.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Document, ObjectId};
+use tracing::{debug, instrument};
+
+use super::{write, Remediation, Rewrite};
+use crate::{
+    document::{
+        object_digest,
+        revisions::{materialize, split_revisions},
+    },
+    error::{Error, Result},
+    PdfAnalysis,
+};
+
+/// Rewrites the document as a single revision
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripRevisions {
+    /// Zero-based revision to keep; the latest when absent
+    pub revision: Option<usize>,
+}
+
+impl Remediation for StripRevisions {
+    fn name(&self) -> &'static str {
+        "strip-revisions"
+    }
+
+    #[instrument(skip(self, data))]
+    fn apply(&self, data: &[u8]) -> Result<Rewrite> {
+        let revisions = split_revisions(data);
+        let index = self.revision.unwrap_or(revisions.len().saturating_sub(1));
+        let revision = revisions.get(index).ok_or_else(|| {
+            Error::Analysis(format!(
+                "Revision {} out of range (the file has {})",
+                index,
+                revisions.len()
+            ))
+        })?;
+        let latest = Document::load_mem(data).map_err(|e| Error::Pdf(format!("Failed to load input: {}", e)))?;
+        let mut doc = materialize(data, revision)?;
+
+        let mut reasons: BTreeMap<ObjectId, String> = BTreeMap::new();
+        for (id, object) in &latest.objects {
+            let reason = match doc.objects.get(id) {
+                None => "Written after",
+                Some(kept) if object_digest(kept) != object_digest(object) => "Restored as of",
+                Some(_) => continue,
+            };
+            reasons.insert(*id, format!("{} revision {}", reason, index));
+        }
+        for id in doc.objects.keys().filter(|id| !latest.objects.contains_key(id)) {
+            reasons.insert(*id, format!("Deleted after revision {}", index));
+        }
+        for id in doc.prune_objects() {
+            reasons.insert(id, "Removed unreachable object".to_string());
+        }
+
+        let output = write(&mut doc)?;
+        debug!(
+            "Kept revision {} of {} ({} objects differ from the latest)",
+            index,
+            revisions.len(),
+            reasons.len()
+        );
+        Ok(Rewrite { output, reasons })
+    }
+
+    fn targets(&self, analysis: &PdfAnalysis) -> Vec<String> {
+        let mut targets: Vec<String> = analysis
+            .carved_objects
+            .iter()
+            .map(|carved| {
+                format!(
+                    "Recoverable {:?} object {} {} at offset {}",
+                    carved.reason, carved.id.0, carved.id.1, carved.offset
+                )
+            })
+            .collect();
+        if let Some(timeline) = analysis.revision_timeline.as_ref().filter(|t| t.revision_count > 1) {
+            targets.push(format!("{} revisions", timeline.revision_count));
+        }
+        targets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        detectors::carving::carve_objects,
+        document::fixtures::{self, PAGE_ID},
+        remediation::{preview, PurgeReason},
+    };
+
+    /// Fixture with two updates: the first replaces the page, the second adds an object
+    fn updated() -> Vec<u8> {
+        let base = fixtures::simple_pdf();
+        let first = fixtures::append_revision(
+            &base,
+            &[(
+                5,
+                "<< /Type /Page /Parent 1 0 R /MediaBox [0 0 300 300] /Contents 4 0 R >>",
+            )],
+        );
+        fixtures::append_revision(&first, &[(7, "(added later)")])
+    }
+
+    #[test]
+    fn test_strip_to_latest() {
+        let data = updated();
+        let (report, output) = preview(&StripRevisions::default(), &data).unwrap();
+
+        assert_eq!(split_revisions(&output).len(), 1);
+        let doc = Document::load_mem(&output).unwrap();
+        assert!(carve_objects(&output, &doc).is_empty());
+        assert!(!doc.objects.contains_key(&(7, 0)));
+        assert_eq!(report.objects.len(), 1);
+        assert_eq!(report.objects[0].reason.as_deref(), Some("Removed unreachable object"));
+        let purged: Vec<_> = report.purged.iter().map(|object| (object.id, object.reason)).collect();
+        assert_eq!(purged, vec![(PAGE_ID, PurgeReason::Superseded), ((7, 0), PurgeReason::Unreachable)]);
+    }
+
+    #[test]
+    fn test_strip_to_earlier_revision() {
+        let data = updated();
+        let rewrite = StripRevisions { revision: Some(0) }.apply(&data).unwrap();
+
+        let doc = Document::load_mem(&rewrite.output).unwrap();
+        assert!(!doc.objects.contains_key(&(7, 0)));
+        assert_eq!(rewrite.reasons[&PAGE_ID], "Restored as of revision 0");
+        assert_eq!(rewrite.reasons[&(7, 0)], "Written after revision 0");
+
+        let error = StripRevisions { revision: Some(3) }.apply(&data).unwrap_err();
+        assert!(error.to_string().contains("out of range"));
+    }
+}
@@ -0,0 +1,159 @@
+//! Graphviz DOT export of the object graph
+//! Created: 2026-10-16 10:45:05 UTC
+//! Author: kartik4091
+//!
+//! Renders the indirect-reference graph for `dot`, `sfdp` and other
+//! Graphviz layouts. Nodes are filled by object type; objects the trailer
+//! does not reach are dashed, references to missing objects dotted, and
+//! objects named by findings of medium severity or above get a thick
+//! outline in the severity colour with the finding count in their label.
+
+use std::fmt::Write;
+
+use crate::{
+    detectors::Severity,
+    document::{
+        graph::{GraphNode, ObjectGraph},
+        taxonomy::ObjectType,
+    },
+};
+
+/// Renders a graph as a DOT digraph
+pub fn render(graph: &ObjectGraph) -> String {
+    let mut out = String::new();
+    out.push_str("digraph pdf {\n");
+    out.push_str("  graph [rankdir=LR, fontname=\"Helvetica\"];\n");
+    out.push_str("  node [shape=box, style=filled, fontname=\"Helvetica\", fontsize=10];\n");
+    out.push_str("  edge [fontname=\"Helvetica\", fontsize=8, color=\"#808080\"];\n");
+    out.push_str("  trailer [shape=doubleoctagon, fillcolor=\"#d9d9d9\"];\n");
+
+    for node in &graph.nodes {
+        let _ = writeln!(out, "  {} [{}];", id(node.id), attributes(node));
+    }
+    let mut missing: Vec<_> = graph.edges.iter().filter(|e| e.dangling).map(|e| e.to).collect();
+    missing.sort_unstable();
+    missing.dedup();
+    for object in missing {
+        let _ = writeln!(
+            out,
+            "  {} [label=\"{} {}\\nmissing\", style=dotted, fillcolor=\"#ffffff\"];",
+            id(object),
+            object.0,
+            object.1
+        );
+    }
+
+    for (key, root) in &graph.roots {
+        let _ = writeln!(out, "  trailer -> {} [label=\"{}\"];", id(*root), escape(key));
+    }
+    for edge in &graph.edges {
+        let style = if edge.dangling { ", style=dotted" } else { "" };
+        let _ = writeln!(
+            out,
+            "  {} -> {} [label=\"{}\"{}];",
+            id(edge.from),
+            id(edge.to),
+            escape(&edge.key),
+            style
+        );
+    }
+    out.push('}');
+    out
+}
+
+fn attributes(node: &GraphNode) -> String {
+    let mut label = format!("{} {}\\n{}", node.id.0, node.id.1, escape(&node.kind.to_string()));
+    if let Some(length) = node.length {
+        let _ = write!(label, "\\n{} bytes", length);
+    }
+    if node.findings > 0 {
+        let _ = write!(
+            label,
+            "\\n{} finding{}",
+            node.findings,
+            if node.findings == 1 { "" } else { "s" }
+        );
+    }
+
+    let mut attributes = format!("label=\"{}\", fillcolor=\"{}\"", label, fill(&node.kind));
+    if !node.reachable {
+        attributes.push_str(", style=\"filled,dashed\"");
+    }
+    if let Some(color) = node.severity.and_then(outline) {
+        let _ = write!(attributes, ", color=\"{}\", penwidth=3", color);
+    }
+    attributes
+}
+
+/// Fill colour by object type
+fn fill(kind: &ObjectType) -> &'static str {
+    match kind {
+        ObjectType::Catalog | ObjectType::Pages | ObjectType::Page | ObjectType::Outlines => "#a6cee3",
+        ObjectType::Font { .. } | ObjectType::FontDescriptor => "#b2df8a",
+        ObjectType::Annotation { .. } => "#fdbf6f",
+        ObjectType::Action { .. } => "#fb9a99",
+        ObjectType::XObject { .. } => "#cab2d6",
+        ObjectType::EmbeddedFile | ObjectType::FileSpec => "#ff9f4a",
+        ObjectType::Signature => "#ffff99",
+        ObjectType::Metadata | ObjectType::ObjectStream | ObjectType::XrefStream | ObjectType::Stream { .. } => {
+            "#e0e0e0"
+        }
+        _ => "#ffffff",
+    }
+}
+
+/// Outline colour of objects behind findings worth a look
+fn outline(severity: Severity) -> Option<&'static str> {
+    match severity {
+        Severity::Critical => Some("#b30000"),
+        Severity::High => Some("#e34a33"),
+        Severity::Medium => Some("#f0a500"),
+        Severity::Low | Severity::Info => None,
+    }
+}
+
+fn id((number, generation): (u32, u16)) -> String {
+    format!("\"{} {}\"", number, generation)
+}
+
+/// Escapes text for a quoted DOT string
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        detectors::{Finding, RiskCategory},
+        document::{
+            fixtures::{self, PAGE_ID},
+            graph,
+        },
+    };
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_render_graph() {
+        let mut doc = fixtures::document();
+        let orphan = doc.add_object(dictionary! { "Type" => "Action", "S" => "Java\"Script", "Next" => (99, 0) });
+        let mut graph = graph::build(&doc);
+        let mut finding = Finding::new("test", Severity::High, RiskCategory::Structure, "Test");
+        finding.objects.push(PAGE_ID);
+        graph.annotate(&[finding]);
+
+        let dot = render(&graph);
+        assert!(dot.starts_with("digraph pdf {") && dot.ends_with('}'));
+        assert!(dot.contains("  trailer -> \"6 0\" [label=\"/Root\"];"));
+        assert!(dot.contains("\"5 0\" [label=\"5 0\\nPage\\n1 finding\", fillcolor=\"#a6cee3\", color=\"#e34a33\""));
+        let orphan_line = dot
+            .lines()
+            .find(|l| l.starts_with(&format!("  {} [", id(orphan))))
+            .unwrap();
+        assert!(orphan_line.contains("Action/Java\\\"Script") && orphan_line.contains("dashed"));
+        assert!(dot.contains("\"99 0\" [label=\"99 0\\nmissing\", style=dotted"));
+        assert!(dot.contains(&format!("{} -> \"99 0\" [label=\"/Next\", style=dotted];", id(orphan))));
+    }
+}
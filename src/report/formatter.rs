@@ -2,3 +2,1847 @@
 // Author: kartik4091
 // Created: 2025-06-03 08:00:41 UTC
 
+//! Plain-text report formatting
+
+use std::fmt::Write;
+
+use base64::Engine;
+
+use crate::{
+    detectors::{
+        carving::CarvedObject, differential::ParserDivergence, reader_features::ReaderFeatureSummary,
+        actions::ActionReport, annotations::AnnotationReport, forms::FormReport,
+        xfa::XfaReport, multimedia::MediaReport, layers::LayerReport, invisible_text::InvisibleText,
+        redaction::RedactionFailure, fonts::FontReport, uris::UriReport,
+        pii::{PiiLocation, PiiReport, ALL_KINDS}, secrets::SecretFinding, language::LanguageReport,
+        phishing::PhishingAssessment, yara::YaraMatch, custom_rules::RuleMatch, scripting::ScriptFinding, plugins::PluginFinding, Finding,
+        exploits::ExploitMatch,
+        shellcode::ShellcodeFinding,
+        decoders::DecoderAnomaly,
+        xref_anomalies::XrefAnomaly, hybrid_xref::HybridReport, signatures::SignatureTimeline,
+        trailer_id::TrailerIdReport, header::HeaderReport, trailing::TrailingData, polyglot::Polyglot,
+        slack::SlackReport, object_streams::ObjStmReport, orphans::OrphanObject,
+        dangling::DanglingReference, stream_length::StreamLengthFinding,
+        filters::FilterAnomaly, obfuscation::ObfuscatedToken, page_tree::PageTreeAnomaly,
+        linearization::LinearizationReport, entropy::EntropyReport, attachments::AttachmentReport,
+        Severity,
+    },
+    document::{
+        artifacts::ArtifactManifest,
+        chronology::DocumentTimeline,
+        diff::{ChangeKind, FileDiff, RevisionTimeline},
+        dump::StreamManifest,
+        inspect::{hex_dump, ObjectInspection},
+        layout::ByteSpan,
+        listing::ObjectEntry,
+        xref::XrefState,
+    },
+    integrations::{
+        clamav::ClamAvReport,
+        virustotal::{SampleStatus, VirusTotalReport},
+    },
+    known_good::KnownGoodMatch,
+    limits::LimitViolation,
+    remediation::{repair::RepairReport, RemediationReport},
+    report::ScanReport,
+    summary::DocumentSummary,
+    testing::{CaseStatus, CorpusReport},
+    text::PageText,
+    EmbeddedAnalysis, PdfAnalysis,
+};
+
+/// Formats an analysis as plain text; `detailed` expands every section
+pub fn text(analysis: &PdfAnalysis, detailed: bool) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "File: {}", analysis.path);
+    let _ = writeln!(out, "Analyzed: {}", analysis.timestamp.to_rfc3339());
+    if let Some(revision) = analysis.revision {
+        let _ = writeln!(out, "Revision: {} (historical state)", revision);
+    }
+    let _ = writeln!(out, "Size: {} bytes", analysis.metadata.size);
+    if let Some(sha256) = &analysis.metadata.sha256 {
+        let _ = writeln!(out, "SHA-256: {}", sha256);
+    }
+    if let Some(digest) = &analysis.metadata.digest {
+        let _ = writeln!(out, "{}: {}", digest.algorithm, digest.value);
+    }
+    if let Some(title) = &analysis.metadata.title {
+        let _ = writeln!(out, "Title: {}", title);
+    }
+    if let Some(author) = &analysis.metadata.author {
+        let _ = writeln!(out, "Author: {}", author);
+    }
+    let _ = writeln!(out, "Encrypted: {}", analysis.security.encrypted);
+
+    out.push('\n');
+    summary_section(&mut out, &analysis.summary);
+
+    if !analysis.findings.is_empty() {
+        out.push('\n');
+        findings_section(&mut out, &analysis.findings, detailed);
+    }
+
+    if !analysis.warnings.is_empty() {
+        let _ = writeln!(out, "\nWarnings:");
+        for warning in &analysis.warnings {
+            let _ = writeln!(out, "  - {}", warning);
+        }
+    }
+
+    if !analysis.security.risks.is_empty() {
+        let _ = writeln!(out, "\nRisks:");
+        for risk in &analysis.security.risks {
+            let _ = writeln!(out, "  - {}", risk);
+        }
+    }
+
+    if !analysis.header.anomalies.is_empty() {
+        out.push('\n');
+        header_section(&mut out, &analysis.header);
+    }
+
+    if let Some(trailing) = &analysis.trailing_data {
+        out.push('\n');
+        trailing_section(&mut out, trailing);
+    }
+
+    if !analysis.polyglots.is_empty() {
+        out.push('\n');
+        polyglot_section(&mut out, &analysis.polyglots);
+    }
+
+    if !analysis.slack.gaps.is_empty() {
+        out.push('\n');
+        slack_section(&mut out, &analysis.slack, detailed);
+    }
+
+    if let Some(timeline) = &analysis.revision_timeline {
+        out.push('\n');
+        timeline_section(&mut out, timeline, detailed);
+    }
+
+    if !analysis.xref_anomalies.is_empty() {
+        out.push('\n');
+        xref_section(&mut out, &analysis.xref_anomalies);
+    }
+
+    if let Some(hybrid) = &analysis.hybrid_xref {
+        out.push('\n');
+        hybrid_section(&mut out, hybrid);
+    }
+
+    if !analysis.object_streams.findings.is_empty() || (detailed && !analysis.object_streams.streams.is_empty()) {
+        out.push('\n');
+        object_stream_section(&mut out, &analysis.object_streams, detailed);
+    }
+
+    if !analysis.signatures.signatures.is_empty() {
+        out.push('\n');
+        signature_section(&mut out, &analysis.signatures, detailed);
+    }
+
+    if !analysis.trailer_ids.anomalies.is_empty() || (detailed && !analysis.trailer_ids.revisions.is_empty()) {
+        out.push('\n');
+        trailer_id_section(&mut out, &analysis.trailer_ids, detailed);
+    }
+
+    if !analysis.orphan_objects.is_empty() {
+        out.push('\n');
+        orphan_section(&mut out, &analysis.orphan_objects, detailed);
+    }
+
+    if let Some(linearization) = &analysis.linearization {
+        out.push('\n');
+        linearization_section(&mut out, linearization);
+    }
+
+    if !analysis.page_tree.is_empty() {
+        out.push('\n');
+        page_tree_section(&mut out, &analysis.page_tree);
+    }
+
+    if !analysis.stream_lengths.is_empty() {
+        out.push('\n');
+        stream_length_section(&mut out, &analysis.stream_lengths);
+    }
+
+    if !analysis.filter_anomalies.is_empty() {
+        out.push('\n');
+        filter_section(&mut out, &analysis.filter_anomalies);
+    }
+
+    if !analysis.obfuscated_tokens.is_empty() {
+        out.push('\n');
+        obfuscation_section(&mut out, &analysis.obfuscated_tokens);
+    }
+
+    if !analysis.attachments.mismatches.is_empty() || (detailed && !analysis.attachments.files.is_empty()) {
+        out.push('\n');
+        attachments_section(&mut out, &analysis.attachments, detailed);
+    }
+
+    if !analysis.embedded.is_empty() {
+        out.push('\n');
+        embedded_section(&mut out, &analysis.embedded, detailed);
+    }
+
+    if let Some(antivirus) = &analysis.antivirus {
+        out.push('\n');
+        antivirus_section(&mut out, antivirus);
+    }
+
+    if let Some(virustotal) = &analysis.virustotal {
+        out.push('\n');
+        virustotal_section(&mut out, virustotal, detailed);
+    }
+
+    if !analysis.known_good.is_empty() {
+        out.push('\n');
+        known_good_section(&mut out, &analysis.known_good);
+    }
+
+    if !analysis.decoder_anomalies.is_empty() {
+        out.push('\n');
+        decoders_section(&mut out, &analysis.decoder_anomalies);
+    }
+
+    if !analysis.limit_violations.is_empty() {
+        out.push('\n');
+        limits_section(&mut out, &analysis.limit_violations);
+    }
+
+    if !analysis.stream_entropy.packed.is_empty() || (detailed && !analysis.stream_entropy.streams.is_empty()) {
+        out.push('\n');
+        entropy_section(&mut out, &analysis.stream_entropy, detailed);
+    }
+
+    if !analysis.dangling_references.is_empty() {
+        out.push('\n');
+        dangling_section(&mut out, &analysis.dangling_references, detailed);
+    }
+
+    if !analysis.reader_features.features.is_empty() {
+        out.push('\n');
+        reader_feature_section(&mut out, &analysis.reader_features, detailed);
+    }
+
+    if analysis.actions.dangerous().next().is_some() || (detailed && !analysis.actions.actions.is_empty()) {
+        out.push('\n');
+        actions_section(&mut out, &analysis.actions, detailed);
+    }
+
+    if !analysis.annotations.suspicious.is_empty() || (detailed && !analysis.annotations.annotations.is_empty()) {
+        out.push('\n');
+        annotations_section(&mut out, &analysis.annotations, detailed);
+    }
+
+    if let Some(forms) = &analysis.forms {
+        out.push('\n');
+        forms_section(&mut out, forms, detailed);
+    }
+
+    if let Some(xfa) = &analysis.xfa {
+        out.push('\n');
+        xfa_section(&mut out, xfa, detailed);
+    }
+
+    if !analysis.exploits.is_empty() {
+        out.push('\n');
+        exploits_section(&mut out, &analysis.exploits);
+    }
+
+    if !analysis.shellcode.is_empty() {
+        out.push('\n');
+        shellcode_section(&mut out, &analysis.shellcode, detailed);
+    }
+
+    if !analysis.media.annotations.is_empty() || !analysis.media.payloads.is_empty() {
+        out.push('\n');
+        media_section(&mut out, &analysis.media, detailed);
+    }
+
+    if analysis.layers.hidden().next().is_some() || (detailed && !analysis.layers.layers.is_empty()) {
+        out.push('\n');
+        layers_section(&mut out, &analysis.layers, detailed);
+    }
+
+    if !analysis.invisible_text.is_empty() {
+        out.push('\n');
+        invisible_text_section(&mut out, &analysis.invisible_text, detailed);
+    }
+
+    if !analysis.redaction_failures.is_empty() {
+        out.push('\n');
+        redaction_section(&mut out, &analysis.redaction_failures, detailed);
+    }
+
+    if !analysis.fonts.findings.is_empty() || (detailed && !analysis.fonts.fonts.is_empty()) {
+        out.push('\n');
+        fonts_section(&mut out, &analysis.fonts, detailed);
+    }
+
+    if analysis.uris.suspicious().next().is_some() || (detailed && (!analysis.uris.uris.is_empty() || !analysis.uris.qr_codes.is_empty())) {
+        out.push('\n');
+        uris_section(&mut out, &analysis.uris, detailed);
+    }
+
+    if !analysis.yara_matches.is_empty() {
+        out.push('\n');
+        yara_section(&mut out, &analysis.yara_matches, detailed);
+    }
+
+    if !analysis.rule_matches.is_empty() {
+        out.push('\n');
+        rule_matches_section(&mut out, &analysis.rule_matches);
+    }
+
+    if !analysis.script_findings.is_empty() {
+        out.push('\n');
+        script_findings_section(&mut out, &analysis.script_findings);
+    }
+
+    if !analysis.plugin_findings.is_empty() {
+        out.push('\n');
+        plugin_findings_section(&mut out, &analysis.plugin_findings);
+    }
+
+    if !analysis.detector_findings.is_empty() {
+        out.push('\n');
+        detector_findings_section(&mut out, &analysis.detector_findings);
+    }
+
+    if !analysis.secrets.is_empty() {
+        out.push('\n');
+        secrets_section(&mut out, &analysis.secrets, detailed);
+    }
+
+    if analysis.phishing.score > 0 {
+        out.push('\n');
+        phishing_section(&mut out, &analysis.phishing, detailed);
+    }
+
+    if analysis.language.mismatch.is_some() || (detailed && !analysis.language.pages.is_empty()) {
+        out.push('\n');
+        language_section(&mut out, &analysis.language, detailed);
+    }
+
+    if let Some(pii) = &analysis.pii {
+        out.push('\n');
+        pii_section(&mut out, pii, detailed);
+    }
+
+    if !analysis.carved_objects.is_empty() {
+        out.push('\n');
+        carved_section(&mut out, &analysis.carved_objects, detailed);
+    }
+
+    if let Some(divergences) = &analysis.parser_divergences {
+        out.push('\n');
+        divergence_section(&mut out, divergences, detailed);
+    }
+
+    out
+}
+
+/// Formats the revision change timeline
+pub fn timeline_section(out: &mut String, timeline: &RevisionTimeline, detailed: bool) {
+    let _ = writeln!(out, "Revision timeline ({} revisions):", timeline.revision_count);
+
+    for step in &timeline.steps {
+        let diff = &step.diff;
+        let _ = writeln!(
+            out,
+            "  r{} -> r{} @ offset {} (+{} bytes): {} added, {} modified, {} removed",
+            step.from,
+            step.to,
+            step.offset,
+            step.size,
+            diff.count(ChangeKind::Added),
+            diff.count(ChangeKind::Modified),
+            diff.count(ChangeKind::Removed),
+        );
+
+        if detailed {
+            for change in &diff.objects {
+                let _ = writeln!(
+                    out,
+                    "      {} {} {} ({})",
+                    change_marker(change.kind),
+                    change.id.0,
+                    change.id.1,
+                    change.object_type
+                );
+            }
+        }
+
+        for change in &diff.metadata {
+            let _ = writeln!(
+                out,
+                "      metadata {}: {} -> {}",
+                change.key,
+                change.before.as_deref().unwrap_or("<none>"),
+                change.after.as_deref().unwrap_or("<none>"),
+            );
+        }
+
+        for change in &diff.pages {
+            let _ = writeln!(out, "      page {} {}", change.page, change_verb(change.kind));
+        }
+    }
+
+    for error in &timeline.errors {
+        let _ = writeln!(out, "  ! {}", error);
+    }
+}
+
+/// Formats cross-reference anomalies, most severe first
+pub fn xref_section(out: &mut String, anomalies: &[XrefAnomaly]) {
+    let _ = writeln!(out, "Cross-reference anomalies ({}):", anomalies.len());
+
+    let mut sorted: Vec<&XrefAnomaly> = anomalies.iter().collect();
+    sorted.sort_by(|a, b| b.severity.cmp(&a.severity));
+    for anomaly in sorted {
+        let _ = writeln!(
+            out,
+            "  [{}] {:?}: {}",
+            anomaly.severity, anomaly.kind, anomaly.description
+        );
+    }
+}
+
+/// Formats the hybrid-reference consistency check
+pub fn hybrid_section(out: &mut String, report: &HybridReport) {
+    if report.is_consistent() {
+        let _ = writeln!(out, "Hybrid xref: table and stream views agree");
+        return;
+    }
+
+    let _ = writeln!(out, "Hybrid xref: table and stream views DIVERGE");
+    for section in &report.sections {
+        let _ = writeln!(
+            out,
+            "  table @ {} / stream @ {}:",
+            section.table_offset, section.stream_offset
+        );
+        for divergence in &section.divergences {
+            let _ = writeln!(
+                out,
+                "    [{}] object {}: {}",
+                divergence.severity, divergence.number, divergence.description
+            );
+        }
+        for conflict in &section.trailer_conflicts {
+            let _ = writeln!(out, "    trailer {}", conflict);
+        }
+    }
+}
+
+/// Formats signature times against the revision order
+pub fn signature_section(out: &mut String, timeline: &SignatureTimeline, detailed: bool) {
+    let _ = writeln!(out, "Signatures: {}", timeline.signatures.len());
+    if detailed {
+        for signature in &timeline.signatures {
+            let time = |t: Option<chrono::DateTime<chrono::Utc>>| {
+                t.map(|t| t.to_rfc3339()).unwrap_or_else(|| "-".into())
+            };
+            let _ = writeln!(
+                out,
+                "  {} {} (revision {}): signed {}, TSA {}",
+                signature.id.0,
+                signature.id.1,
+                signature.revision.map(|r| r.to_string()).unwrap_or_else(|| "?".into()),
+                time(signature.signing_time),
+                time(signature.tsa_time)
+            );
+        }
+    }
+    for indicator in &timeline.backdating {
+        let _ = writeln!(out, "  [{}] {}", indicator.severity, indicator.description);
+    }
+}
+
+/// Formats header anomalies
+pub fn header_section(out: &mut String, report: &HeaderReport) {
+    let _ = writeln!(
+        out,
+        "Header: PDF {} at offset {}",
+        report.version.as_deref().unwrap_or("?"),
+        report.offset.map(|o| o.to_string()).unwrap_or_else(|| "-".into())
+    );
+    for anomaly in &report.anomalies {
+        let _ = writeln!(out, "  [{}] {}", anomaly.severity, anomaly.description);
+    }
+}
+
+/// Formats data found after the final %%EOF
+pub fn trailing_section(out: &mut String, trailing: &TrailingData) {
+    let _ = writeln!(
+        out,
+        "[{}] {} bytes after final %%EOF at offset {} (entropy {:.2}, {})",
+        trailing.severity,
+        trailing.size,
+        trailing.offset,
+        trailing.entropy,
+        trailing.format.as_deref().unwrap_or("unknown format")
+    );
+    let _ = writeln!(out, "  sha256 {}", trailing.sha256);
+    let _ = writeln!(out, "  \"{}\"", trailing.preview);
+    if let Some(path) = &trailing.dumped_to {
+        let _ = writeln!(out, "  dumped to {}", path);
+    }
+}
+
+/// Formats the secondary formats the file is also valid as
+pub fn polyglot_section(out: &mut String, polyglots: &[Polyglot]) {
+    let _ = writeln!(out, "Polyglot: also valid as {} other formats", polyglots.len());
+    for polyglot in polyglots {
+        let _ = writeln!(out, "  [{}] {} ({})", polyglot.severity, polyglot.description, polyglot.mime);
+    }
+}
+
+/// Formats unclaimed gaps between structures
+pub fn slack_section(out: &mut String, report: &SlackReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "Slack space: {} gaps, {} of {} bytes covered",
+        report.gaps.len(),
+        report.covered,
+        report.analyzed
+    );
+    for gap in &report.gaps {
+        if !detailed && gap.severity == Severity::Info {
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "  [{}] {} bytes at {} ({:?}, entropy {:.2}): \"{}\"",
+            gap.severity, gap.length, gap.start, gap.class, gap.entropy, gap.preview
+        );
+    }
+}
+
+/// Formats the object stream inventory and findings
+pub fn object_stream_section(out: &mut String, report: &ObjStmReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "Object streams: {} streams, {} findings",
+        report.streams.len(),
+        report.findings.len()
+    );
+    if detailed {
+        for stream in &report.streams {
+            let _ = writeln!(
+                out,
+                "  {} {} @ {}: {} objects{}",
+                stream.id.0,
+                stream.id.1,
+                stream.offset,
+                stream.objects.len(),
+                stream.error.as_ref().map(|e| format!(" (error: {})", e)).unwrap_or_default()
+            );
+            for object in &stream.objects {
+                let _ = writeln!(
+                    out,
+                    "    #{} obj {} {}{}",
+                    object.index,
+                    object.number,
+                    object.object_type.as_ref().map_or("?".to_string(), |t| t.to_string()),
+                    if object.live { "" } else { " (unreferenced)" }
+                );
+            }
+        }
+    }
+    for finding in &report.findings {
+        let _ = writeln!(out, "  [{}] {}", finding.severity, finding.description);
+    }
+}
+
+/// Formats the trailer /ID history
+pub fn trailer_id_section(out: &mut String, report: &TrailerIdReport, detailed: bool) {
+    let _ = writeln!(out, "Trailer IDs: {} anomalies", report.anomalies.len());
+    if detailed {
+        for revision in &report.revisions {
+            let _ = writeln!(
+                out,
+                "  #{} @ {}: [{} {}] {}",
+                revision.revision,
+                revision.section_offset,
+                revision.permanent.as_deref().unwrap_or("-"),
+                revision.changing.as_deref().unwrap_or("-"),
+                revision.derivation.map(|d| format!("{:?}", d)).unwrap_or_default()
+            );
+        }
+    }
+    for anomaly in &report.anomalies {
+        let _ = writeln!(out, "  [{}] revision {}: {}", anomaly.severity, anomaly.revision, anomaly.description);
+    }
+}
+
+/// Formats objects unreachable from the trailer
+pub fn orphan_section(out: &mut String, orphans: &[OrphanObject], detailed: bool) {
+    let _ = writeln!(out, "Unreachable objects: {}", orphans.len());
+    for orphan in orphans {
+        if !detailed && orphan.severity == Severity::Info {
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "  [{}] {} {} {:?} ({})",
+            orphan.severity, orphan.id.0, orphan.id.1, orphan.category, orphan.object_type
+        );
+    }
+}
+
+/// Formats stream /Length and decoded size findings
+pub fn stream_length_section(out: &mut String, findings: &[StreamLengthFinding]) {
+    let _ = writeln!(out, "Stream lengths: {} findings", findings.len());
+    for finding in findings {
+        let _ = writeln!(out, "  [{}] @ {}: {}", finding.severity, finding.offset, finding.description);
+    }
+}
+
+/// Formats linearization parameters and their inconsistencies
+pub fn linearization_section(out: &mut String, report: &LinearizationReport) {
+    let params = &report.params;
+    let _ = writeln!(
+        out,
+        "Linearized: object {} {} at offset {}, {} anomalies",
+        params.id.0,
+        params.id.1,
+        params.offset,
+        report.anomalies.len()
+    );
+    for anomaly in &report.anomalies {
+        let _ = writeln!(out, "  [{}] {}", anomaly.severity, anomaly.description);
+    }
+}
+
+/// Formats page tree anomalies
+pub fn page_tree_section(out: &mut String, anomalies: &[PageTreeAnomaly]) {
+    let _ = writeln!(out, "Page tree: {} anomalies", anomalies.len());
+    for anomaly in anomalies {
+        let _ = writeln!(out, "  [{}] {}", anomaly.severity, anomaly.description);
+    }
+}
+
+/// Formats decode filter chain anomalies
+pub fn filter_section(out: &mut String, anomalies: &[FilterAnomaly]) {
+    let _ = writeln!(out, "Filter chains: {} anomalies", anomalies.len());
+    for anomaly in anomalies {
+        let _ = writeln!(out, "  [{}] {} [{}]", anomaly.severity, anomaly.description, anomaly.filters.join(" "));
+    }
+}
+
+/// Formats attachment type mismatches, and every attachment's type when
+/// detailed
+pub fn attachments_section(out: &mut String, report: &AttachmentReport, detailed: bool) {
+    let _ = writeln!(out, "Attachments: {} files, {} type mismatches", report.files.len(), report.mismatches.len());
+    for mismatch in &report.mismatches {
+        let _ = writeln!(out, "  [{}] {}", mismatch.severity, mismatch.description);
+    }
+    if detailed {
+        for file in &report.files {
+            let declared = file.declared_mime.as_deref().unwrap_or("-");
+            let detected = file.detected_mime.as_deref().unwrap_or("unrecognized");
+            let _ = writeln!(
+                out,
+                "    {} {} {} ({} bytes): declared {}, detected {}",
+                file.id.0, file.id.1, file.name, file.size, declared, detected
+            );
+        }
+    }
+}
+
+/// Formats nested analyses of embedded documents; detailed output
+/// includes each nested report, indented
+pub fn embedded_section(out: &mut String, embedded: &[EmbeddedAnalysis], detailed: bool) {
+    let _ = writeln!(out, "Embedded documents: {}", embedded.len());
+    for document in embedded {
+        let summary = &document.analysis.summary;
+        let severity = summary.highest_severity.map_or("none".to_string(), |s| s.to_string());
+        let _ = writeln!(
+            out,
+            "  {} (object {} {}): risk score {:.2} (highest severity: {})",
+            document.name, document.object.0, document.object.1, summary.risk.score, severity
+        );
+        if detailed {
+            for line in text(&document.analysis, true).lines() {
+                let _ = writeln!(out, "    {}", line);
+            }
+        }
+    }
+}
+
+/// Formats JBIG2 and CCITT streams that look crafted for the decoder
+pub fn decoders_section(out: &mut String, anomalies: &[DecoderAnomaly]) {
+    let _ = writeln!(out, "Image decoder abuse: {} indicators", anomalies.len());
+    for anomaly in anomalies {
+        let _ = writeln!(out, "  [{}] {}", anomaly.severity, anomaly.description);
+    }
+}
+
+/// Formats the ClamAV verdicts on the file and its payloads
+pub fn antivirus_section(out: &mut String, report: &ClamAvReport) {
+    let _ = writeln!(
+        out,
+        "ClamAV: {} payloads scanned, {} skipped, {} detections",
+        report.scanned,
+        report.skipped,
+        report.detections.len()
+    );
+    for detection in &report.detections {
+        let _ = writeln!(
+            out,
+            "  [{}] {} in {} {}",
+            Severity::Critical,
+            detection.signature,
+            detection.kind,
+            detection.name
+        );
+    }
+}
+
+/// Formats VirusTotal detection ratios; clean samples only when detailed
+pub fn virustotal_section(out: &mut String, report: &VirusTotalReport, detailed: bool) {
+    let flagged = report.samples.iter().filter(|s| report.severity(s).is_some()).count();
+    let _ = writeln!(out, "VirusTotal: {} samples looked up, {} flagged", report.samples.len(), flagged);
+    for sample in &report.samples {
+        let status = match sample.status {
+            SampleStatus::Known => format!("{}/{}", sample.malicious, sample.engines),
+            SampleStatus::Unknown => "unknown".to_string(),
+            SampleStatus::Submitted => "submitted".to_string(),
+        };
+        match report.severity(sample) {
+            Some(severity) => {
+                let _ = writeln!(out, "  [{}] {} {}: {}", severity, sample.kind, sample.name, status);
+            }
+            None if detailed => {
+                let _ = writeln!(out, "    {} {}: {}", sample.kind, sample.name, status);
+            }
+            None => continue,
+        }
+        if detailed {
+            let _ = writeln!(out, "      {}", sample.permalink);
+        }
+    }
+}
+
+/// Formats content matching known-good hash sets
+pub fn known_good_section(out: &mut String, matches: &[KnownGoodMatch]) {
+    let _ = writeln!(out, "Known-good matches: {} (findings on them are informational)", matches.len());
+    for known in matches {
+        let _ = writeln!(out, "  {} ({} {})", known.name, known.algorithm, known.hash);
+    }
+}
+
+/// Formats exceeded decoding and parsing limits
+pub fn limits_section(out: &mut String, violations: &[LimitViolation]) {
+    let _ = writeln!(out, "Resource limits: {} violations", violations.len());
+    for violation in violations {
+        let skipped = if violation.prevents_loading { " (document not loaded)" } else { "" };
+        let _ = writeln!(out, "  [{}] {}{}", violation.severity, violation.description, skipped);
+    }
+}
+
+/// Formats packed-looking streams, and every stream's entropy when detailed
+pub fn entropy_section(out: &mut String, report: &EntropyReport, detailed: bool) {
+    let _ = writeln!(out, "Stream entropy: {} likely packed payloads", report.packed.len());
+    for packed in &report.packed {
+        let _ = writeln!(out, "  [{}] {}", packed.severity, packed.description);
+    }
+    if detailed {
+        for stream in &report.streams {
+            let decoded = match (stream.decoded_size, stream.decoded_entropy) {
+                (Some(size), Some(entropy)) => format!("{} bytes at {:.2}", size, entropy),
+                _ => "undecodable".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "    {} {} {:<16} stored {} bytes at {:.2}, decoded {}",
+                stream.id.0, stream.id.1, stream.object_type, stream.raw_size, stream.raw_entropy, decoded
+            );
+        }
+    }
+}
+
+/// Formats the one-screen document overview
+pub fn summary_section(out: &mut String, summary: &DocumentSummary) {
+    let severity = summary.highest_severity.map_or("none".to_string(), |s| s.to_string());
+    let _ = writeln!(out, "Summary:");
+    let _ = writeln!(out, "  Risk score: {:.2} (highest severity: {})", summary.risk.score, severity);
+    let _ = writeln!(out, "  Revisions: {}", summary.revisions);
+    let _ = writeln!(out, "  Pages: {}", summary.pages);
+    for size in &summary.page_sizes {
+        let name = size.name.as_ref().map(|n| format!(" ({})", n)).unwrap_or_default();
+        let _ = writeln!(out, "    {} x {} pt{}: {}", size.width, size.height, name, size.pages);
+    }
+    let types: Vec<String> = summary.objects_by_type.iter().map(|(kind, n)| format!("{} {}", n, kind)).collect();
+    let _ = writeln!(out, "  Objects: {} ({})", summary.object_count, types.join(", "));
+    let _ = writeln!(
+        out,
+        "  Streams: {} ({} bytes stored, {} decoded)",
+        summary.streams, summary.stream_bytes_raw, summary.stream_bytes_decoded
+    );
+    if let Some(encryption) = &summary.encryption {
+        let field = |value: Option<String>| value.unwrap_or_else(|| "?".into());
+        let _ = writeln!(
+            out,
+            "  Encryption: {} V{} R{} {} ({} bits)",
+            field(encryption.filter.clone()),
+            field(encryption.version.map(|v| v.to_string())),
+            field(encryption.revision.map(|r| r.to_string())),
+            field(encryption.method.clone()),
+            field(encryption.key_length.map(|l| l.to_string())),
+        );
+    }
+}
+
+/// Formats escaped names and strings hiding their content
+pub fn obfuscation_section(out: &mut String, tokens: &[ObfuscatedToken]) {
+    let _ = writeln!(out, "Obfuscated tokens: {}", tokens.len());
+    for token in tokens {
+        let _ = writeln!(out, "  [{}] @ {}: {}", token.severity, token.offset, token.description);
+    }
+}
+
+/// Formats references that do not resolve
+pub fn dangling_section(out: &mut String, dangling: &[DanglingReference], detailed: bool) {
+    let _ = writeln!(out, "Dangling references: {}", dangling.len());
+    for reference in dangling {
+        let _ = writeln!(
+            out,
+            "  [{}] {} {} R: {:?}, {} referrers",
+            reference.severity,
+            reference.target.0,
+            reference.target.1,
+            reference.kind,
+            reference.sites.len()
+        );
+        if detailed {
+            for site in &reference.sites {
+                let source = site.object.map(|id| format!("{} {} ", id.0, id.1)).unwrap_or_default();
+                let _ = writeln!(out, "      from {}{} {}", source, site.context, site.path);
+            }
+        }
+    }
+}
+
+/// Formats the reader security-setting dependent features
+pub fn reader_feature_section(out: &mut String, summary: &ReaderFeatureSummary, detailed: bool) {
+    let _ = writeln!(out, "Reader setting dependent features:");
+    for usage in &summary.features {
+        let _ = writeln!(
+            out,
+            "  {:?}{} - gated by \"{}\" ({} objects)",
+            usage.feature,
+            if usage.auto_triggered { " [auto]" } else { "" },
+            usage.setting,
+            usage.objects.len(),
+        );
+        if detailed {
+            let ids: Vec<String> = usage.objects.iter().map(|(n, g)| format!("{} {}", n, g)).collect();
+            let _ = writeln!(out, "      objects: {}", ids.join(", "));
+        }
+    }
+}
+
+/// Formats dangerous actions, and every resolved action when detailed
+pub fn actions_section(out: &mut String, report: &ActionReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "Actions: {} resolved, {} dangerous",
+        report.actions.len(),
+        report.dangerous().count()
+    );
+    for action in &report.actions {
+        if detailed || action.severity >= Severity::Medium {
+            let _ = writeln!(out, "  [{}] {}", action.severity, action.description);
+        }
+    }
+    for id in &report.chain_loops {
+        let _ = writeln!(out, "  /Next chain loops back to action {} {}", id.0, id.1);
+    }
+}
+
+/// Formats suspicious annotations, and the full inventory when detailed
+pub fn annotations_section(out: &mut String, report: &AnnotationReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "Annotations: {} total, {} suspicious",
+        report.annotations.len(),
+        report.suspicious.len()
+    );
+    for suspicious in &report.suspicious {
+        let _ = writeln!(out, "  [{}] {}", suspicious.severity, suspicious.description);
+    }
+    if detailed {
+        for annotation in &report.annotations {
+            let id = annotation.id.map_or("inline".to_string(), |(num, gen)| format!("{} {}", num, gen));
+            let page = annotation.page.map_or("none".to_string(), |(num, gen)| format!("{} {}", num, gen));
+            let rect = annotation
+                .rect
+                .map_or("-".to_string(), |[x0, y0, x1, y1]| format!("[{} {} {} {}]", x0, y0, x1, y1));
+            let _ = writeln!(
+                out,
+                "    {} {:<10} page {} rect {} flags {} actions [{}] appearances {}",
+                id,
+                annotation.subtype,
+                page,
+                rect,
+                annotation.flags,
+                annotation.actions.join(", "),
+                annotation.appearance_streams.len()
+            );
+        }
+    }
+}
+
+/// Formats flagged form fields, and every field when detailed
+pub fn forms_section(out: &mut String, report: &FormReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "AcroForm: {} fields, {} flagged{}{}",
+        report.fields.len(),
+        report.findings.len(),
+        if report.has_xfa { ", XFA present" } else { "" },
+        if report.need_appearances { ", NeedAppearances" } else { "" },
+    );
+    for finding in &report.findings {
+        let _ = writeln!(out, "  [{}] {}", finding.severity, finding.description);
+    }
+    if detailed {
+        for field in &report.fields {
+            let _ = writeln!(
+                out,
+                "    {} {} {:<10} {} flags {} value {} default {}",
+                field.id.0,
+                field.id.1,
+                field.field_type,
+                field.name,
+                field.flags,
+                field.value.as_deref().unwrap_or("-"),
+                field.default_value.as_deref().unwrap_or("-"),
+            );
+            for script in &field.scripts {
+                let _ = writeln!(out, "      {}: {}", script.trigger, script.preview);
+            }
+            for target in &field.submit_targets {
+                let _ = writeln!(out, "      submits to {}", target);
+            }
+        }
+    }
+}
+
+/// Formats XFA packets and findings; detailed output includes the XML
+pub fn xfa_section(out: &mut String, report: &XfaReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "XFA form: {} packets, {} scripts{}",
+        report.packets.len(),
+        report.scripts.len(),
+        if report.dynamic { ", dynamic" } else { "" },
+    );
+    for finding in &report.findings {
+        let _ = writeln!(out, "  [{}] {}", finding.severity, finding.description);
+    }
+    for packet in &report.packets {
+        let id = packet.id.map(|(num, gen)| format!(" ({} {})", num, gen)).unwrap_or_default();
+        let _ = writeln!(out, "    {}{}: {} bytes", packet.name, id, packet.size);
+        if detailed {
+            for line in packet.xml.lines() {
+                let _ = writeln!(out, "      {}", line);
+            }
+        }
+    }
+    if detailed {
+        for script in &report.scripts {
+            let _ = writeln!(out, "    {:?} script: {}", script.language, script.preview);
+        }
+    }
+}
+
+/// Formats multimedia annotations and payload streams
+pub fn media_section(out: &mut String, report: &MediaReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "Multimedia: {} annotations, {} payloads",
+        report.annotations.len(),
+        report.payloads.len()
+    );
+    for annotation in &report.annotations {
+        let _ = writeln!(out, "  [{}] {}", annotation.severity, annotation.description);
+    }
+    for payload in &report.payloads {
+        let _ = writeln!(
+            out,
+            "  [{}] {:?} payload in object {} {} ({} bytes)",
+            payload.severity, payload.format, payload.id.0, payload.id.1, payload.size
+        );
+        if detailed {
+            let _ = writeln!(out, "      sha256 {}", payload.sha256);
+        }
+        if let Some(path) = &payload.extracted_to {
+            let _ = writeln!(out, "      extracted to {}", path);
+        }
+    }
+}
+
+/// Formats hidden layers and their content; detailed output lists every layer
+pub fn layers_section(out: &mut String, report: &LayerReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "Optional content: {} layers, {} hidden by default",
+        report.layers.len(),
+        report.hidden().count()
+    );
+    for layer in &report.layers {
+        if detailed || layer.hidden {
+            let _ = writeln!(
+                out,
+                "  {} {} \"{}\"{}{}{}",
+                layer.id.0,
+                layer.id.1,
+                layer.name,
+                if layer.hidden { " [hidden]" } else { "" },
+                if layer.locked { " [locked]" } else { "" },
+                if layer.print_only { " [printed]" } else { "" },
+            );
+        }
+    }
+    for content in &report.hidden_content {
+        let _ = writeln!(out, "  [{}] {}", content.severity, content.description);
+        if detailed {
+            for line in content.text.lines() {
+                let _ = writeln!(out, "      | {}", line);
+            }
+            if !content.excerpt.is_empty() {
+                let _ = writeln!(out, "      {}", content.excerpt);
+            }
+        }
+    }
+}
+
+/// Formats text hidden from view; detailed output includes the full text
+pub fn invisible_text_section(out: &mut String, found: &[InvisibleText], detailed: bool) {
+    let _ = writeln!(out, "Invisible text: {} groups", found.len());
+    for hidden in found {
+        let _ = writeln!(out, "  [{}] {}", hidden.severity, hidden.description);
+        if detailed {
+            for line in hidden.text.lines() {
+                let _ = writeln!(out, "      | {}", line);
+            }
+        }
+    }
+}
+
+/// Formats suspected redaction failures; detailed output includes the recovered text
+pub fn redaction_section(out: &mut String, failures: &[RedactionFailure], detailed: bool) {
+    let _ = writeln!(out, "Redaction failures: {}", failures.len());
+    for failure in failures {
+        let _ = writeln!(out, "  [{}] {}", failure.severity, failure.description);
+        if detailed {
+            let [x0, y0, x1, y1] = failure.region;
+            let _ = writeln!(out, "      region [{} {} {} {}]", x0, y0, x1, y1);
+            for line in failure.recoverable_text.lines() {
+                let _ = writeln!(out, "      | {}", line);
+            }
+        }
+    }
+}
+
+/// Formats font findings; detailed output lists every font
+pub fn fonts_section(out: &mut String, report: &FontReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "Fonts: {} ({} embedded, {} subsets)",
+        report.fonts.len(),
+        report.fonts.iter().filter(|f| f.embedded).count(),
+        report.fonts.iter().filter(|f| f.subset_prefix.is_some()).count()
+    );
+    for finding in &report.findings {
+        let _ = writeln!(out, "  [{}] {}", finding.severity, finding.description);
+    }
+    if detailed {
+        for font in &report.fonts {
+            let _ = writeln!(
+                out,
+                "    {} {} {:<9} {} {}{}",
+                font.id.0,
+                font.id.1,
+                font.subtype,
+                font.base_font,
+                font.program.as_deref().unwrap_or("not embedded"),
+                font.revision.map(|r| format!(", revision {}", r)).unwrap_or_default()
+            );
+        }
+    }
+}
+
+/// Formats suspicious URIs, and every extracted URI when detailed
+pub fn uris_section(out: &mut String, report: &UriReport, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "URIs: {} extracted, {} suspicious, {} QR codes",
+        report.uris.len(),
+        report.suspicious().count(),
+        report.qr_codes.len()
+    );
+    for uri in &report.uris {
+        if detailed || uri.severity > Severity::Info {
+            let _ = writeln!(out, "  [{}] {}", uri.severity, uri.description);
+        }
+        if detailed {
+            let sources: Vec<String> = uri
+                .sources
+                .iter()
+                .map(|(origin, id)| match id {
+                    Some(id) => format!("{:?} in {} {}", origin, id.0, id.1),
+                    None => format!("{:?}", origin),
+                })
+                .collect();
+            let _ = writeln!(out, "      {}", sources.join(", "));
+        }
+    }
+    if detailed {
+        for code in &report.qr_codes {
+            let _ = writeln!(out, "  QR code in image {} {}: {}", code.image.0, code.image.1, code.payload);
+        }
+    }
+}
+
+/// Formats YARA rule matches
+pub fn yara_section(out: &mut String, matches: &[YaraMatch], detailed: bool) {
+    let _ = writeln!(out, "YARA matches: {}", matches.len());
+    for m in matches {
+        let tags = if m.tags.is_empty() { String::new() } else { format!(" [{}]", m.tags.join(", ")) };
+        let _ = writeln!(out, "  [{}] {}{}", m.severity, m.description, tags);
+        if detailed {
+            let offsets: Vec<String> = m.offsets.iter().map(|o| o.to_string()).collect();
+            let _ = writeln!(out, "      offsets: {}", offsets.join(", "));
+        }
+    }
+}
+
+/// Formats matches of the known-exploit signature pack
+pub fn exploits_section(out: &mut String, matches: &[ExploitMatch]) {
+    let _ = writeln!(out, "Known exploit patterns: {}", matches.len());
+    for m in matches {
+        let _ = writeln!(out, "  [{}] {}", m.severity, m.description);
+    }
+}
+
+/// Formats shellcode, sled and heap-spray findings
+pub fn shellcode_section(out: &mut String, findings: &[ShellcodeFinding], detailed: bool) {
+    let _ = writeln!(out, "Shellcode indicators: {}", findings.len());
+    for f in findings {
+        let _ = writeln!(out, "  [{}] {}", f.severity, f.description);
+        if detailed {
+            for line in &f.disassembly {
+                let _ = writeln!(out, "      {}", line);
+            }
+        }
+    }
+}
+
+/// Formats matches of user-defined detection rules
+pub fn rule_matches_section(out: &mut String, matches: &[RuleMatch]) {
+    let _ = writeln!(out, "Custom rule matches: {}", matches.len());
+    for m in matches {
+        let tags = if m.tags.is_empty() { String::new() } else { format!(" [{}]", m.tags.join(", ")) };
+        let _ = writeln!(out, "  [{}] {}{}", m.severity, m.description, tags);
+    }
+}
+
+/// Formats findings reported by analyzer scripts
+pub fn script_findings_section(out: &mut String, findings: &[ScriptFinding]) {
+    let _ = writeln!(out, "Script findings: {}", findings.len());
+    for finding in findings {
+        let location = match finding.object {
+            Some(id) => format!(" (object {} {})", id.0, id.1),
+            None => String::new(),
+        };
+        let _ = writeln!(out, "  [{}] {}: {}{}", finding.severity, finding.script, finding.description, location);
+    }
+}
+
+/// Formats findings returned by analyzer plugins
+pub fn plugin_findings_section(out: &mut String, findings: &[PluginFinding]) {
+    let _ = writeln!(out, "Plugin findings: {}", findings.len());
+    for finding in findings {
+        let location = match finding.object {
+            Some(id) => format!(" (object {} {})", id.0, id.1),
+            None => String::new(),
+        };
+        let _ = writeln!(out, "  [{}] {}: {}{}", finding.severity, finding.plugin, finding.description, location);
+    }
+}
+
+/// Formats findings of registered detectors
+pub fn detector_findings_section(out: &mut String, findings: &[Finding]) {
+    let _ = writeln!(out, "Detector findings: {}", findings.len());
+    for finding in findings {
+        let location = objects(&finding.objects);
+        let _ = writeln!(out, "  [{}] {}: {}{}", finding.severity, finding.id, finding.title, location);
+    }
+}
+
+/// Formats the unified findings, most severe first; detailed output adds
+/// confidence, category, evidence, remediation and ATT&CK techniques
+pub fn findings_section(out: &mut String, findings: &[Finding], detailed: bool) {
+    let _ = writeln!(out, "Findings: {}", findings.len());
+    let mut sorted: Vec<&Finding> = findings.iter().collect();
+    sorted.sort_by(|a, b| b.severity.cmp(&a.severity));
+    for finding in sorted {
+        let location = objects(&finding.objects);
+        let _ = writeln!(out, "  [{}] {}: {}{}", finding.severity, finding.id, finding.title, location);
+        if detailed {
+            let _ = writeln!(out, "    Confidence: {}, category: {}", finding.confidence, finding.category);
+            for evidence in &finding.evidence {
+                let _ = writeln!(out, "    Evidence: {}", evidence);
+            }
+            if let Some(remediation) = &finding.remediation {
+                let _ = writeln!(out, "    Remediation: {}", remediation);
+            }
+            if !finding.techniques.is_empty() {
+                let _ = writeln!(out, "    ATT&CK: {}", finding.techniques.join(", "));
+            }
+        }
+    }
+}
+
+fn objects(ids: &[lopdf::ObjectId]) -> String {
+    if ids.is_empty() {
+        return String::new();
+    }
+    let ids: Vec<String> = ids.iter().map(|id| format!("{} {}", id.0, id.1)).collect();
+    format!(" (objects {})", ids.join(", "))
+}
+
+/// Formats credentials found in text, scripts, metadata and attachments
+pub fn secrets_section(out: &mut String, secrets: &[SecretFinding], detailed: bool) {
+    let _ = writeln!(out, "Secrets: {}", secrets.len());
+    for secret in secrets {
+        let _ = writeln!(out, "  [{}] {}", secret.severity, secret.description);
+        if detailed {
+            let _ = writeln!(out, "      entropy {:.2} bits/char", secret.entropy);
+        }
+    }
+}
+
+/// Formats the phishing likelihood and the signals behind it
+pub fn phishing_section(out: &mut String, assessment: &PhishingAssessment, detailed: bool) {
+    let _ = writeln!(
+        out,
+        "Phishing likelihood: {:?} (score {}/{})",
+        assessment.likelihood,
+        assessment.score,
+        crate::detectors::phishing::MAX_SCORE
+    );
+    for hit in &assessment.signals {
+        if detailed {
+            let _ = writeln!(out, "  +{:<3} {:?}: {}", hit.signal.weight(), hit.signal, hit.evidence);
+        } else {
+            let _ = writeln!(out, "  {:?}: {}", hit.signal, hit.evidence);
+        }
+    }
+}
+
+/// Formats the declared and detected languages
+pub fn language_section(out: &mut String, report: &LanguageReport, detailed: bool) {
+    let claimed: Vec<&str> = report.claimed.iter().map(|c| c.tag.as_str()).collect();
+    let _ = writeln!(
+        out,
+        "Language: {} (declared: {})",
+        report.dominant.as_deref().unwrap_or("unknown"),
+        if claimed.is_empty() { "none".to_string() } else { claimed.join(", ") }
+    );
+    if let Some(mismatch) = &report.mismatch {
+        let _ = writeln!(out, "  [{}] {}", mismatch.severity, mismatch.description);
+    }
+    if detailed {
+        for page in &report.pages {
+            let _ = writeln!(
+                out,
+                "    page {:<4} {} ({}, {:.0}% confidence, {} letters)",
+                page.page,
+                page.name,
+                page.script,
+                page.confidence * 100.0,
+                page.letters
+            );
+        }
+    }
+}
+
+/// Formats personal data counts, and every masked match when detailed
+pub fn pii_section(out: &mut String, report: &PiiReport, detailed: bool) {
+    let counts: Vec<String> = ALL_KINDS
+        .iter()
+        .map(|&kind| (kind, report.count(kind)))
+        .filter(|(_, count)| *count > 0)
+        .map(|(kind, count)| format!("{} {}", count, kind))
+        .collect();
+    let summary = if counts.is_empty() { "none found".to_string() } else { counts.join(", ") };
+    let _ = writeln!(out, "Personal data: {}", summary);
+    if detailed {
+        for m in &report.matches {
+            let location = match &m.location {
+                PiiLocation::Page { page, .. } => format!("page {}", page),
+                PiiLocation::Metadata { key } => format!("metadata {}", key),
+                PiiLocation::Attachment { name } => format!("attachment {}", name),
+            };
+            let _ = writeln!(out, "  {:<11} {} ({})", m.kind, m.masked, location);
+        }
+    }
+}
+
+/// Formats the list of recoverable (carved) objects
+pub fn carved_section(out: &mut String, carved: &[CarvedObject], detailed: bool) {
+    let _ = writeln!(out, "Recoverable artifacts ({} carved objects):", carved.len());
+
+    for object in carved {
+        let _ = writeln!(
+            out,
+            "  {} {} @ offset {} ({} bytes, {:?}){}",
+            object.id.0,
+            object.id.1,
+            object.offset,
+            object.length,
+            object.reason,
+            object.object_type.as_ref().map(|t| format!(" {}", t)).unwrap_or_default(),
+        );
+        if detailed {
+            if let Some(preview) = &object.preview {
+                let _ = writeln!(out, "      {}", preview);
+            }
+            if let Some(error) = &object.error {
+                let _ = writeln!(out, "      ! {}", error);
+            }
+        }
+    }
+}
+
+/// Formats the viewer-differential simulation results
+pub fn divergence_section(out: &mut String, divergences: &[ParserDivergence], detailed: bool) {
+    if divergences.is_empty() {
+        let _ = writeln!(out, "Viewer simulation: all viewer models agree");
+        return;
+    }
+
+    let _ = writeln!(
+        out,
+        "Viewer simulation: {} objects resolve differently across viewers",
+        divergences.len()
+    );
+    for divergence in divergences {
+        let _ = writeln!(
+            out,
+            "  {} {}: {:?}",
+            divergence.id.0, divergence.id.1, divergence.ambiguities
+        );
+        if detailed {
+            for resolution in &divergence.resolutions {
+                let _ = writeln!(
+                    out,
+                    "      {:<8} {}",
+                    resolution.viewer,
+                    resolution.preview.as_deref().unwrap_or("<unresolved>"),
+                );
+            }
+        }
+    }
+}
+
+/// Formats the triage view printed by `scan`
+pub fn scan(report: &ScanReport, detailed: bool) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "File: {}", report.path);
+    let _ = writeln!(out, "Verdict: {}", report.decision.verdict);
+    for reason in &report.decision.reasons {
+        let _ = writeln!(out, "  {}", reason);
+    }
+    let flagged = match report.threshold {
+        Some(threshold) if report.exceeds_threshold() => format!(" (at or above threshold {:.2})", threshold),
+        Some(threshold) => format!(" (below threshold {:.2})", threshold),
+        None => String::new(),
+    };
+    let _ = writeln!(out, "Risk: {:.2}{}", report.risk.score, flagged);
+    for known in &report.known_good {
+        let _ = writeln!(out, "Known-good: {} ({} {})", known.name, known.algorithm, known.hash);
+    }
+    for category in &report.risk.categories {
+        let _ = write!(out, "  {}: {:.2} ({} findings", category.category, category.score, category.findings);
+        if detailed {
+            let _ = write!(out, ", {:.1} points", category.points);
+        }
+        out.push_str(")\n");
+    }
+    out.push('\n');
+    phishing_section(&mut out, &report.phishing, detailed);
+    out
+}
+
+/// Formats the comparison of two files
+pub fn file_diff(diff: &FileDiff, detailed: bool) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Size: {} -> {} bytes", diff.before_size, diff.after_size);
+    if diff.is_empty() {
+        out.push_str("No structural differences\n");
+        return out;
+    }
+
+    let objects = &diff.diff;
+    let _ = writeln!(
+        out,
+        "Objects: {} added, {} modified, {} removed",
+        objects.count(ChangeKind::Added),
+        objects.count(ChangeKind::Modified),
+        objects.count(ChangeKind::Removed)
+    );
+    for change in &objects.objects {
+        let _ = writeln!(
+            out,
+            "  {} {} {} ({})",
+            change_marker(change.kind),
+            change.id.0,
+            change.id.1,
+            change.object_type
+        );
+    }
+    for change in &objects.metadata {
+        let _ = writeln!(
+            out,
+            "Metadata {}: {} -> {}",
+            change.key,
+            change.before.as_deref().unwrap_or("<none>"),
+            change.after.as_deref().unwrap_or("<none>"),
+        );
+    }
+    for change in &objects.pages {
+        let _ = writeln!(out, "Page {} {}", change.page, change_verb(change.kind));
+    }
+    for change in &diff.signatures {
+        let _ = writeln!(out, "Signature {} {} {}", change.id.0, change.id.1, change_verb(change.kind));
+        if detailed {
+            for (label, record) in [("before", &change.before), ("after", &change.after)] {
+                let Some(record) = record else {
+                    continue;
+                };
+                let _ = writeln!(
+                    out,
+                    "  {}: {} signed {}, TSA {}",
+                    label,
+                    record.sub_filter.as_deref().unwrap_or("-"),
+                    record.signing_time.map(|t| t.to_rfc3339()).unwrap_or_else(|| "-".into()),
+                    record.tsa_time.map(|t| t.to_rfc3339()).unwrap_or_else(|| "-".into())
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Formats a single-object inspection with hex dumps of its stream
+pub fn inspection(inspection: &ObjectInspection) -> String {
+    let mut out = String::new();
+    let (number, generation) = inspection.id;
+    let _ = writeln!(out, "Object {} {} ({})", number, generation, inspection.object_type);
+    match inspection.location {
+        Some(XrefState::InUse { offset, .. }) => {
+            let _ = writeln!(out, "Location: offset {}", offset);
+        }
+        Some(XrefState::Compressed { stream, index }) => {
+            let _ = writeln!(out, "Location: object stream {}, index {}", stream, index);
+        }
+        Some(XrefState::Free { .. }) => out.push_str("Location: marked free\n"),
+        None => out.push_str("Location: not in the cross-reference\n"),
+    }
+    match inspection.revisions.split_first() {
+        Some((first, [])) => {
+            let _ = writeln!(out, "Introduced in revision {}", first);
+        }
+        Some((first, rest)) => {
+            let rest: Vec<String> = rest.iter().map(|r| r.to_string()).collect();
+            let _ = writeln!(out, "Introduced in revision {}, rewritten in {}", first, rest.join(", "));
+        }
+        None => out.push_str("Introduced in: unknown\n"),
+    }
+
+    let _ = writeln!(out, "\n{}", inspection.body);
+
+    out.push_str("\nReferences out:\n");
+    if inspection.references.is_empty() {
+        out.push_str("  none\n");
+    }
+    for (key, (number, generation)) in &inspection.references {
+        let _ = writeln!(out, "  {} -> {} {} R", key, number, generation);
+    }
+    out.push_str("References in:\n");
+    if inspection.referenced_by.is_empty() {
+        out.push_str("  none\n");
+    }
+    for ((number, generation), key) in &inspection.referenced_by {
+        let _ = writeln!(out, "  {} {} R {}", number, generation, key);
+    }
+
+    let base64 = base64::engine::general_purpose::STANDARD;
+    for (label, length, data) in [
+        ("Raw stream", inspection.raw_length, &inspection.raw),
+        ("Decoded stream", inspection.decoded_length, &inspection.decoded),
+    ] {
+        let (Some(length), Some(data)) = (length, data) else {
+            continue;
+        };
+        let bytes = base64.decode(data).unwrap_or_default();
+        let shown = if bytes.len() < length {
+            format!(", first {} shown", bytes.len())
+        } else {
+            String::new()
+        };
+        let _ = writeln!(out, "\n{} ({} bytes{}):", label, length, shown);
+        out.push_str(&hex_dump(&bytes, 0));
+    }
+    if !inspection.filters.is_empty() {
+        let _ = writeln!(out, "\nFilters: {}", inspection.filters.join(" -> "));
+    }
+    if let Some(error) = &inspection.decode_error {
+        let _ = writeln!(out, "\nDecoding failed: {}", error);
+    }
+    out
+}
+
+/// Formats an object listing as an aligned table
+pub fn object_list(entries: &[ObjectEntry]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<10} {:<28} {:>10} {:>8} {:>4}  FILTERS",
+        "OBJECT", "TYPE", "SIZE", "ENTROPY", "REV"
+    );
+    for entry in entries {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "-".into());
+        let _ = writeln!(
+            out,
+            "{:<10} {:<28} {:>10} {:>8} {:>4}  {}",
+            format!("{} {}", entry.id.0, entry.id.1),
+            entry.object_type,
+            optional(entry.size.map(|size| size.to_string())),
+            optional(entry.entropy.map(|entropy| format!("{:.3}", entropy))),
+            optional(entry.revision.map(|revision| revision.to_string())),
+            entry.filters.join(" ")
+        );
+    }
+    let _ = writeln!(out, "{} object{}", entries.len(), if entries.len() == 1 { "" } else { "s" });
+    out
+}
+
+/// Formats a reconstructed document history, marking conflicting events
+/// with `!`
+pub fn document_timeline(timeline: &DocumentTimeline) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{} revision{}, {} dated event{}, {} conflict{}",
+        timeline.revisions,
+        if timeline.revisions == 1 { "" } else { "s" },
+        timeline.events.len(),
+        if timeline.events.len() == 1 { "" } else { "s" },
+        timeline.conflicts.len(),
+        if timeline.conflicts.len() == 1 { "" } else { "s" }
+    );
+    for event in &timeline.events {
+        let revision = event
+            .revision
+            .map(|revision| format!("rev {}", revision))
+            .unwrap_or_else(|| "rev ?".into());
+        let _ = writeln!(
+            out,
+            "{} {}  {:<6} {}",
+            if event.conflict { '!' } else { ' ' },
+            event.time.to_rfc3339(),
+            revision,
+            event.description
+        );
+    }
+    if !timeline.conflicts.is_empty() {
+        out.push_str("Conflicts:\n");
+        for conflict in &timeline.conflicts {
+            let _ = writeln!(out, "  [{}] {}", conflict.severity, conflict.description);
+        }
+    }
+    out
+}
+
+/// Formats the artifacts written by an extraction
+pub fn artifact_manifest(manifest: &ArtifactManifest) -> String {
+    let mut out = String::new();
+    let counts: Vec<String> = manifest
+        .classes
+        .iter()
+        .map(|&class| format!("{} {}", manifest.count(class), class))
+        .collect();
+    let _ = writeln!(
+        out,
+        "Extracted {} artifact{} ({})",
+        manifest.artifacts.len(),
+        if manifest.artifacts.len() == 1 { "" } else { "s" },
+        counts.join(", ")
+    );
+    for artifact in &manifest.artifacts {
+        let _ = write!(
+            out,
+            "  {} from {} {} ({} bytes, sha256 {})",
+            artifact.file, artifact.id.0, artifact.id.1, artifact.size, artifact.sha256
+        );
+        if let Some(name) = &artifact.name {
+            let _ = write!(out, " - {}", name);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Formats the streams written by a dump
+pub fn stream_manifest(manifest: &StreamManifest) -> String {
+    let mut out = String::new();
+    let failed = manifest.streams.iter().filter(|stream| stream.error.is_some()).count();
+    let _ = writeln!(
+        out,
+        "Wrote {} stream{}{}",
+        manifest.streams.len(),
+        if manifest.streams.len() == 1 { "" } else { "s" },
+        match (manifest.decode, failed) {
+            (false, _) => " as stored".to_string(),
+            (true, 0) => " decoded".to_string(),
+            (true, failed) => format!(" decoded, {} stored after decoding failed", failed),
+        }
+    );
+    for stream in &manifest.streams {
+        let _ = write!(
+            out,
+            "  {:<14} {} {} {} ({} bytes)",
+            stream.file, stream.id.0, stream.id.1, stream.object_type, stream.size
+        );
+        if !stream.filters.is_empty() {
+            let _ = write!(out, " [{}]", stream.filters.join(" "));
+        }
+        if let Some(detected) = &stream.detected {
+            let _ = write!(out, " - {}", detected);
+        }
+        if let Some(error) = &stream.error {
+            let _ = write!(out, " - not decoded: {}", error);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Formats a hex dump of `data` with each span under a line naming its owner
+pub fn annotated_hexdump(data: &[u8], spans: &[ByteSpan]) -> String {
+    let mut out = String::new();
+    for span in spans {
+        let _ = writeln!(
+            out,
+            "{:08x}-{:08x}  {} ({} bytes)",
+            span.start,
+            span.end,
+            span.owner,
+            span.end - span.start
+        );
+        out.push_str(&hex_dump(&data[span.start..span.end], span.start));
+    }
+    out
+}
+
+/// Formats the impact of a remediation run (or dry run)
+pub fn remediation(report: &RemediationReport) -> String {
+    let mut out = String::new();
+
+    let mode = if report.dry_run { " (dry run)" } else { "" };
+    let _ = writeln!(out, "Operation: {}{}", report.operation, mode);
+    let _ = writeln!(out, "Input:  {} bytes, sha256 {}", report.input_size, report.input_sha256);
+    let label = if report.dry_run { "Predicted output" } else { "Output" };
+    let _ = writeln!(out, "{}: {} bytes, sha256 {}", label, report.output_size, report.output_sha256);
+    if let Some(path) = &report.written_to {
+        let _ = writeln!(out, "Written to: {}", path.display());
+    }
+
+    let verification = &report.verification;
+    let status = if verification.passed { "passed" } else { "FAILED" };
+    let _ = writeln!(
+        out,
+        "Verification: {} ({} resolved, {} remaining, {} introduced)",
+        status,
+        verification.resolved.len(),
+        verification.remaining.len(),
+        verification.introduced.len()
+    );
+    for finding in &verification.remaining {
+        let _ = writeln!(out, "  remaining: {}", finding);
+    }
+    for finding in &verification.introduced {
+        let _ = writeln!(out, "  introduced: {}", finding);
+    }
+
+    if report.is_noop() {
+        let _ = writeln!(out, "\nNo changes.");
+        return out;
+    }
+
+    let _ = writeln!(out, "\nObjects ({}):", report.objects.len());
+    for object in &report.objects {
+        let size = |bytes: Option<usize>| bytes.map(|b| b.to_string()).unwrap_or_else(|| "-".into());
+        let _ = writeln!(
+            out,
+            "  {} {} {} {:<16} {} -> {} bytes{}",
+            change_marker(object.kind),
+            object.id.0,
+            object.id.1,
+            object.object_type,
+            size(object.bytes_before),
+            size(object.bytes_after),
+            object.reason.as_ref().map(|r| format!(": {}", r)).unwrap_or_default()
+        );
+    }
+
+    if !report.metadata.is_empty() {
+        let _ = writeln!(out, "\nMetadata:");
+        for change in &report.metadata {
+            let _ = writeln!(
+                out,
+                "  {}: {} -> {}",
+                change.key,
+                change.before.as_deref().unwrap_or("-"),
+                change.after.as_deref().unwrap_or("-")
+            );
+        }
+    }
+
+    if !report.purged.is_empty() {
+        let bytes: usize = report.purged.iter().map(|object| object.length).sum();
+        let _ = writeln!(out, "\nPurged {} hidden object definitions ({} bytes):", report.purged.len(), bytes);
+        for object in &report.purged {
+            let _ = writeln!(
+                out,
+                "  {} {} @ offset {} ({} bytes, {:?})",
+                object.id.0, object.id.1, object.offset, object.length, object.reason
+            );
+        }
+    }
+
+    out
+}
+
+/// Formats the outcome of a repair
+pub fn repair(report: &RepairReport) -> String {
+    let mut out = String::new();
+
+    let mode = if report.dry_run { " (dry run)" } else { "" };
+    let _ = writeln!(out, "Operation: repair{}", mode);
+    let state = if report.loadable { "loadable" } else { "damaged" };
+    let _ = writeln!(out, "Input:  {} bytes, sha256 {} ({})", report.input_size, report.input_sha256, state);
+    let label = if report.dry_run { "Predicted output" } else { "Output" };
+    let _ = writeln!(out, "{}: {} bytes, sha256 {}", label, report.output_size, report.output_sha256);
+    if let Some(path) = &report.written_to {
+        let _ = writeln!(out, "Written to: {}", path.display());
+    }
+    let _ = writeln!(
+        out,
+        "Recovered {} objects ({} from object streams), catalog {} {}",
+        report.recovered, report.from_object_streams, report.root.0, report.root.1
+    );
+    let status = if report.verification.passed { "passed" } else { "FAILED" };
+    let _ = writeln!(out, "Verification: {} ({} introduced)", status, report.verification.introduced.len());
+    for finding in &report.verification.introduced {
+        let _ = writeln!(out, "  introduced: {}", finding);
+    }
+
+    if report.guesses.is_empty() {
+        let _ = writeln!(out, "\nNothing had to be guessed.");
+        return out;
+    }
+    let _ = writeln!(out, "\nGuesses ({}):", report.guesses.len());
+    for guess in &report.guesses {
+        let object = guess.object.map(|id| format!("{} {}: ", id.0, id.1)).unwrap_or_default();
+        let _ = writeln!(out, "  {}{}", object, guess.description);
+    }
+
+    out
+}
+
+/// Formats a golden-corpus regression run; `detailed` also lists passing fixtures
+pub fn corpus(report: &CorpusReport, detailed: bool) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "Corpus: {} ({} fixtures)", report.corpus.display(), report.cases.len());
+    for case in &report.cases {
+        let status = match case.status {
+            CaseStatus::Passed if !detailed => continue,
+            CaseStatus::Passed => "ok",
+            CaseStatus::Failed => "FAILED",
+            CaseStatus::MissingSnapshot => "NO SNAPSHOT",
+            CaseStatus::Updated => "updated",
+            CaseStatus::Error => "ERROR",
+        };
+        let _ = writeln!(out, "  {}: {}", status, case.fixture.display());
+        if let Some(error) = &case.error {
+            let _ = writeln!(out, "      {}", error);
+        }
+        for finding in &case.missing {
+            let _ = writeln!(out, "      - [{}] {}", finding.severity, finding.description);
+        }
+        for finding in &case.unexpected {
+            let _ = writeln!(out, "      + [{}] {}", finding.severity, finding.description);
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "\n{} passed, {} failed, {} without snapshot, {} updated, {} errors",
+        report.count(CaseStatus::Passed),
+        report.count(CaseStatus::Failed),
+        report.count(CaseStatus::MissingSnapshot),
+        report.count(CaseStatus::Updated),
+        report.count(CaseStatus::Error)
+    );
+    out
+}
+
+/// Formats extracted page text; `detailed` lists every span with its position
+pub fn extracted_text(pages: &[PageText], detailed: bool) -> String {
+    let mut out = String::new();
+
+    for page in pages {
+        let _ = writeln!(out, "--- Page {} ---", page.page_number);
+        let _ = writeln!(out, "{}", page.text());
+        if detailed {
+            for span in &page.spans {
+                let [x0, y0, x1, y1] = span.bbox;
+                let _ = writeln!(
+                    out,
+                    "  [{:.1} {:.1} {:.1} {:.1}] /{} {:.1}pt: {}",
+                    x0, y0, x1, y1, span.font, span.font_size, span.text
+                );
+            }
+        }
+    }
+    out
+}
+
+fn change_marker(kind: ChangeKind) -> char {
+    match kind {
+        ChangeKind::Added => '+',
+        ChangeKind::Removed => '-',
+        ChangeKind::Modified => '~',
+    }
+}
+
+fn change_verb(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Added => "added",
+        ChangeKind::Removed => "removed",
+        ChangeKind::Modified => "content changed",
+    }
+}
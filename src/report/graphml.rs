@@ -0,0 +1,193 @@
+//! GraphML export of the object graph and revision history
+//! Created: 2026-10-16 10:44:59 UTC
+//! Author: kartik4091
+//!
+//! Writes the reference graph as GraphML for Gephi, yEd, Cytoscape and
+//! NetworkX. Objects carry their type, size, entropy, the revision that
+//! last wrote them and the findings naming them as typed attributes, so
+//! large documents can be filtered and laid out by any of them. Revisions
+//! are nodes of their own, chained in append order and linked to every
+//! object they wrote; an edge's `relation` tells the kinds apart.
+
+use std::fmt::Write;
+
+use crate::document::{
+    escape_markup,
+    graph::{GraphNode, ObjectGraph},
+};
+
+/// Attribute declarations: id, domain, type
+const KEYS: [(&str, &str, &str); 11] = [
+    ("label", "node", "string"),
+    ("kind", "node", "string"),
+    ("size", "node", "long"),
+    ("entropy", "node", "double"),
+    ("revision", "node", "int"),
+    ("findings", "node", "int"),
+    ("severity", "node", "string"),
+    ("reachable", "node", "boolean"),
+    ("key", "edge", "string"),
+    ("relation", "edge", "string"),
+    ("dangling", "edge", "boolean"),
+];
+
+/// Renders a graph as a GraphML document
+pub fn render(graph: &ObjectGraph) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    for (id, domain, kind) in KEYS {
+        let _ = writeln!(
+            out,
+            "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+            id, domain, id, kind
+        );
+    }
+    out.push_str("  <graph id=\"pdf\" edgedefault=\"directed\">\n");
+
+    node(
+        &mut out,
+        "trailer",
+        &[("label", "trailer".into()), ("kind", "trailer".into())],
+    );
+    for object in &graph.nodes {
+        node(&mut out, &id(object.id), &attributes(object));
+    }
+    let mut missing: Vec<_> = graph.edges.iter().filter(|e| e.dangling).map(|e| e.to).collect();
+    missing.sort_unstable();
+    missing.dedup();
+    for object in missing {
+        node(
+            &mut out,
+            &id(object),
+            &[
+                ("label", format!("{} {}", object.0, object.1)),
+                ("kind", "missing".into()),
+            ],
+        );
+    }
+    for revision in &graph.revisions {
+        node(
+            &mut out,
+            &format!("r{}", revision.index),
+            &[
+                ("label", format!("Revision {}", revision.index)),
+                ("kind", "revision".into()),
+                ("size", revision.size.to_string()),
+                ("revision", revision.index.to_string()),
+            ],
+        );
+    }
+
+    for (key, root) in &graph.roots {
+        edge(
+            &mut out,
+            "trailer",
+            &id(*root),
+            &[("key", key.clone()), ("relation", "trailer".into())],
+        );
+    }
+    for reference in &graph.edges {
+        edge(
+            &mut out,
+            &id(reference.from),
+            &id(reference.to),
+            &[
+                ("key", reference.key.clone()),
+                ("relation", "reference".into()),
+                ("dangling", reference.dangling.to_string()),
+            ],
+        );
+    }
+    for pair in graph.revisions.windows(2) {
+        edge(
+            &mut out,
+            &format!("r{}", pair[0].index),
+            &format!("r{}", pair[1].index),
+            &[("relation", "next".into())],
+        );
+    }
+    for revision in &graph.revisions {
+        for object in revision.written.iter().filter(|&&object| graph.node(object).is_some()) {
+            edge(
+                &mut out,
+                &format!("r{}", revision.index),
+                &id(*object),
+                &[("relation", "wrote".into())],
+            );
+        }
+    }
+
+    out.push_str("  </graph>\n</graphml>");
+    out
+}
+
+fn attributes(node: &GraphNode) -> Vec<(&'static str, String)> {
+    let mut attributes = vec![
+        ("label", format!("{} {} {}", node.id.0, node.id.1, node.kind)),
+        ("kind", node.kind.to_string()),
+        ("size", node.length.unwrap_or(0).to_string()),
+    ];
+    if let Some(entropy) = node.entropy {
+        attributes.push(("entropy", format!("{:.4}", entropy)));
+    }
+    if let Some(revision) = node.revision {
+        attributes.push(("revision", revision.to_string()));
+    }
+    attributes.push(("findings", node.findings.to_string()));
+    if let Some(severity) = node.severity {
+        attributes.push(("severity", severity.to_string()));
+    }
+    attributes.push(("reachable", node.reachable.to_string()));
+    attributes
+}
+
+fn node(out: &mut String, id: &str, data: &[(&str, String)]) {
+    let _ = writeln!(out, "    <node id=\"{}\">", escape_markup(id));
+    for (key, value) in data {
+        let _ = writeln!(out, "      <data key=\"{}\">{}</data>", key, escape_markup(value));
+    }
+    out.push_str("    </node>\n");
+}
+
+fn edge(out: &mut String, source: &str, target: &str, data: &[(&str, String)]) {
+    let _ = writeln!(
+        out,
+        "    <edge source=\"{}\" target=\"{}\">",
+        escape_markup(source),
+        escape_markup(target)
+    );
+    for (key, value) in data {
+        let _ = writeln!(out, "      <data key=\"{}\">{}</data>", key, escape_markup(value));
+    }
+    out.push_str("    </edge>\n");
+}
+
+fn id((number, generation): (u32, u16)) -> String {
+    format!("{} {}", number, generation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{fixtures, graph};
+
+    #[test]
+    fn test_render_with_history() {
+        let data = fixtures::append_revision(
+            &fixtures::simple_pdf(),
+            &[(7, "<< /Type /Action /S /JavaScript /JS (app.alert(1)) >>")],
+        );
+        let mut graph = graph::build(&lopdf::Document::load_mem(&data).unwrap());
+        graph.add_history(&data);
+
+        let xml = render(&graph);
+        assert!(xml.starts_with("<?xml") && xml.ends_with("</graphml>"));
+        assert_eq!(xml.matches("<key ").count(), KEYS.len());
+        assert!(xml.contains("    <node id=\"7 0\">\n      <data key=\"label\">7 0 Action/JavaScript</data>"));
+        assert!(xml.contains("    <edge source=\"r1\" target=\"7 0\">\n      <data key=\"relation\">wrote</data>"));
+        assert!(xml.contains("    <edge source=\"r0\" target=\"r1\">"));
+        assert!(xml.contains("<data key=\"revision\">1</data>"));
+        assert_eq!(xml.matches("<node ").count(), 1 + graph.nodes.len() + 2);
+    }
+}
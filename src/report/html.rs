@@ -0,0 +1,306 @@
+//! Self-contained HTML report
+//! Created: 2026-10-16 10:34:38 UTC
+//! Author: kartik4091
+//!
+//! Renders an analysis as one HTML file with inline styles and no scripts
+//! or external resources, so it can be attached to a case file and opened
+//! offline: a metadata panel, the findings table colored by severity, and,
+//! when the analysis captured a [`DocumentPreview`], image thumbnails and a
+//! collapsible object tree with the objects findings point at highlighted.
+//! Everything taken from the document is escaped.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use lopdf::ObjectId;
+
+use crate::{
+    detectors::{Finding, Severity},
+    document::{
+        escape_markup,
+        preview::{DocumentPreview, ObjectNode},
+    },
+    PdfAnalysis,
+};
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2em; color: #1f2328; background: #fff; }
+h1 { font-size: 1.4em; word-break: break-all; }
+h2 { font-size: 1.15em; margin-top: 2em; border-bottom: 1px solid #d0d7de; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9em; }
+th, td { text-align: left; vertical-align: top; padding: 4px 8px; border-bottom: 1px solid #d0d7de; }
+th { background: #f6f8fa; }
+.panel th { width: 12em; }
+code, .mono { font-family: ui-monospace, monospace; word-break: break-all; }
+.badge { display: inline-block; padding: 1px 8px; border-radius: 10px; font-weight: 600; color: #fff; }
+.badge.critical { background: #82071e; } .badge.high { background: #cf222e; }
+.badge.medium { background: #bc4c00; } .badge.low { background: #9a6700; } .badge.info { background: #57606a; }
+tr.critical td { background: #ffebe9; } tr.high td { background: #fff1ef; }
+tr.medium td { background: #fff8c5; } tr.low td { background: #fbf8ef; }
+.thumbnails { display: flex; flex-wrap: wrap; gap: 12px; }
+figure { margin: 0; padding: 6px; border: 1px solid #d0d7de; text-align: center; font-size: 0.8em; }
+figure img { display: block; margin: 0 auto 4px; image-rendering: pixelated; }
+.tree details, .tree .leaf { margin-left: 1.2em; }
+.tree summary, .tree .leaf { font-family: ui-monospace, monospace; font-size: 0.9em; }
+.tree .key { color: #0550ae; } .tree .kind { color: #57606a; }
+.tree .critical, .tree .high { color: #cf222e; font-weight: 600; }
+.tree .medium, .tree .low { color: #bc4c00; }
+";
+
+/// Renders an analysis as a standalone HTML page
+pub fn render(analysis: &PdfAnalysis) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>PDx report: {}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escape_markup(&analysis.path),
+        STYLE
+    );
+    let _ = writeln!(out, "<h1>{}</h1>", escape_markup(&analysis.path));
+    let severity = match analysis.summary.highest_severity {
+        Some(severity) => badge(severity),
+        None => "none".to_string(),
+    };
+    let _ = writeln!(
+        out,
+        "<p>Risk score <strong>{:.2}</strong>, highest severity {}, {} findings</p>",
+        analysis.summary.risk.score,
+        severity,
+        analysis.findings.len()
+    );
+
+    metadata_panel(&mut out, analysis);
+    findings_table(&mut out, &analysis.findings);
+    if !analysis.warnings.is_empty() {
+        out.push_str("<h2>Warnings</h2>\n<ul>\n");
+        for warning in &analysis.warnings {
+            let _ = writeln!(out, "<li>{}</li>", escape_markup(warning));
+        }
+        out.push_str("</ul>\n");
+    }
+    if let Some(preview) = &analysis.preview {
+        thumbnails(&mut out, preview);
+        object_tree(&mut out, preview, &analysis.findings);
+    }
+
+    let _ = writeln!(
+        out,
+        "<footer><p><small>Generated by pdx {} on {}</small></p></footer>\n</body>\n</html>",
+        env!("CARGO_PKG_VERSION"),
+        analysis.timestamp.to_rfc3339()
+    );
+    out
+}
+
+fn metadata_panel(out: &mut String, analysis: &PdfAnalysis) {
+    let metadata = &analysis.metadata;
+    let summary = &analysis.summary;
+    let mut rows: Vec<(&str, String)> = vec![("Size", format!("{} bytes", metadata.size))];
+    if let Some(sha256) = &metadata.sha256 {
+        rows.push(("SHA-256", format!("<code>{}</code>", escape_markup(sha256))));
+    }
+    if let Some(digest) = &metadata.digest {
+        rows.push((
+            "Digest",
+            format!("{} <code>{}</code>", digest.algorithm, escape_markup(&digest.value)),
+        ));
+    }
+    if let Some(title) = &metadata.title {
+        rows.push(("Title", escape_markup(title)));
+    }
+    if let Some(author) = &metadata.author {
+        rows.push(("Author", escape_markup(author)));
+    }
+    if let Some(created) = metadata.created {
+        rows.push(("File created", created.to_rfc3339()));
+    }
+    if let Some(modified) = metadata.modified {
+        rows.push(("File modified", modified.to_rfc3339()));
+    }
+    if let Some(revision) = analysis.revision {
+        rows.push(("Revision", format!("{} (historical state)", revision)));
+    }
+    rows.push(("Pages", summary.pages.to_string()));
+    rows.push(("Revisions", summary.revisions.to_string()));
+    rows.push((
+        "Objects",
+        format!("{} ({} streams)", summary.object_count, summary.streams),
+    ));
+    rows.push((
+        "Encryption",
+        match &summary.encryption {
+            Some(encryption) => escape_markup(encryption.filter.as_deref().unwrap_or("unknown filter")),
+            None => "none".to_string(),
+        },
+    ));
+    rows.push(("Attachments", analysis.attachments.files.len().to_string()));
+    rows.push(("URIs", analysis.uris.uris.len().to_string()));
+    rows.push((
+        "Phishing",
+        format!("{:?} (score {})", analysis.phishing.likelihood, analysis.phishing.score),
+    ));
+
+    out.push_str("<h2>Metadata</h2>\n<table class=\"panel\">\n");
+    for (name, value) in rows {
+        let _ = writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", name, value);
+    }
+    out.push_str("</table>\n");
+}
+
+fn findings_table(out: &mut String, findings: &[Finding]) {
+    let _ = writeln!(out, "<h2>Findings ({})</h2>", findings.len());
+    if findings.is_empty() {
+        out.push_str("<p>No findings.</p>\n");
+        return;
+    }
+    let mut sorted: Vec<&Finding> = findings.iter().collect();
+    sorted.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.id.cmp(&b.id)));
+
+    out.push_str(
+        "<table>\n<tr><th>Severity</th><th>Confidence</th><th>Category</th><th>Finding</th>\
+         <th>Objects</th><th>ATT&amp;CK</th></tr>\n",
+    );
+    for finding in sorted {
+        let objects: Vec<String> = finding.objects.iter().map(|(n, g)| format!("{} {}", n, g)).collect();
+        let _ = write!(
+            out,
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td><strong>{}</strong><br><code>{}</code>",
+            finding.severity,
+            badge(finding.severity),
+            finding.confidence,
+            finding.category,
+            escape_markup(&finding.title),
+            escape_markup(&finding.id)
+        );
+        if !finding.evidence.is_empty() {
+            out.push_str("<details><summary>Evidence</summary><ul>");
+            for evidence in &finding.evidence {
+                let _ = write!(out, "<li class=\"mono\">{}</li>", escape_markup(evidence));
+            }
+            out.push_str("</ul></details>");
+        }
+        if let Some(remediation) = &finding.remediation {
+            let _ = write!(out, "<br><em>{}</em>", escape_markup(remediation));
+        }
+        let _ = writeln!(
+            out,
+            "</td><td class=\"mono\">{}</td><td>{}</td></tr>",
+            objects.join(", "),
+            escape_markup(&finding.techniques.join(", "))
+        );
+    }
+    out.push_str("</table>\n");
+}
+
+fn thumbnails(out: &mut String, preview: &DocumentPreview) {
+    if preview.thumbnails.is_empty() {
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "<h2>Images ({})</h2>\n<div class=\"thumbnails\">",
+        preview.thumbnails.len()
+    );
+    for thumbnail in &preview.thumbnails {
+        let (number, generation) = thumbnail.object;
+        let caption = match thumbnail.page {
+            Some(page) => format!("Page {} thumbnail", page),
+            None => format!("Image {} {}", number, generation),
+        };
+        let _ = writeln!(
+            out,
+            "<figure><img src=\"data:image/png;base64,{}\" alt=\"{}\">\
+             <figcaption>{}<br>{}&times;{}</figcaption></figure>",
+            thumbnail.png, caption, caption, thumbnail.width, thumbnail.height
+        );
+    }
+    out.push_str("</div>\n");
+}
+
+fn object_tree(out: &mut String, preview: &DocumentPreview, findings: &[Finding]) {
+    let mut flagged: BTreeMap<ObjectId, Severity> = BTreeMap::new();
+    for finding in findings {
+        for &id in &finding.objects {
+            let severity = flagged.entry(id).or_insert(finding.severity);
+            *severity = (*severity).max(finding.severity);
+        }
+    }
+
+    out.push_str("<h2>Object tree</h2>\n<div class=\"tree\">\n");
+    for node in &preview.objects {
+        tree_node(out, node, &flagged);
+    }
+    if preview.truncated {
+        out.push_str("<p><em>Tree truncated; not every object is shown.</em></p>\n");
+    }
+    out.push_str("</div>\n");
+}
+
+fn tree_node(out: &mut String, node: &ObjectNode, flagged: &BTreeMap<ObjectId, Severity>) {
+    let mut label = String::new();
+    if let Some(key) = &node.key {
+        let _ = write!(label, "<span class=\"key\">{}</span> ", escape_markup(key));
+    }
+    let _ = write!(
+        label,
+        "{} {} R <span class=\"kind\">{}</span>",
+        node.id.0,
+        node.id.1,
+        escape_markup(&node.kind)
+    );
+    if let Some(length) = node.length {
+        let _ = write!(label, " <span class=\"kind\">{} bytes</span>", length);
+    }
+    if let Some(severity) = flagged.get(&node.id) {
+        let _ = write!(label, " <span class=\"{}\">&#9888; {}</span>", severity, severity);
+    }
+
+    if node.children.is_empty() {
+        let _ = writeln!(out, "<div class=\"leaf\">{}</div>", label);
+        return;
+    }
+    let _ = writeln!(out, "<details><summary>{}</summary>", label);
+    for child in &node.children {
+        tree_node(out, child, flagged);
+    }
+    out.push_str("</details>\n");
+}
+
+fn badge(severity: Severity) -> String {
+    format!("<span class=\"badge {}\">{}</span>", severity, severity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detectors::RiskCategory;
+
+    #[tokio::test]
+    async fn test_report_sections() {
+        use crate::{AnalysisOptions, Analyzer, PdfAnalyzer};
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let options = AnalysisOptions {
+            preview: true,
+            ..Default::default()
+        };
+        let mut analysis = PdfAnalyzer::with_options(temp.path(), options)
+            .unwrap()
+            .analyze()
+            .await
+            .unwrap();
+        analysis.findings.push(
+            Finding::new("test.lure", Severity::High, RiskCategory::Phishing, "<b>Click</b> here")
+                .objects([crate::document::fixtures::PAGE_ID]),
+        );
+
+        let html = render(&analysis);
+        assert!(html.starts_with("<!DOCTYPE html>") && html.trim_end().ends_with("</html>"));
+        assert!(html.contains("<tr class=\"high\">"));
+        assert!(html.contains("&lt;b&gt;Click&lt;/b&gt; here"));
+        assert!(html.contains("<span class=\"key\">/Root</span>"));
+        assert!(html.contains("5 0 R <span class=\"kind\">Page</span> <span class=\"high\">"));
+        assert!(!html.contains("<script"));
+    }
+}
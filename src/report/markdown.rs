@@ -0,0 +1,247 @@
+//! Markdown report
+//! Created: 2026-10-16 10:38:27 UTC
+//! Author: kartik4091
+//!
+//! Renders an analysis as GitHub-flavored Markdown for tickets and case
+//! wikis: a metadata table, the findings table ordered by severity with
+//! their evidence, and the JavaScript of actions and form fields in code
+//! blocks. Text from the document is escaped, and code fences are longer
+//! than any backtick run in the script they hold, so a document cannot
+//! break out of its block.
+
+use std::fmt::Write;
+
+use crate::{
+    detectors::{Finding, Severity},
+    PdfAnalysis,
+};
+
+/// Renders an analysis as a Markdown document
+pub fn render(analysis: &PdfAnalysis) -> String {
+    let mut out = String::new();
+    let name = std::path::Path::new(&analysis.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| analysis.path.clone());
+    let _ = writeln!(out, "# PDx report: {}\n", escape(&name));
+    let _ = writeln!(
+        out,
+        "**Risk score:** {:.2} · **Highest severity:** {} · **Findings:** {}\n",
+        analysis.summary.risk.score,
+        analysis
+            .summary
+            .highest_severity
+            .map_or("none".to_string(), |s| s.to_string()),
+        analysis.findings.len()
+    );
+
+    metadata(&mut out, analysis);
+    findings(&mut out, &analysis.findings);
+    scripts(&mut out, analysis);
+
+    if !analysis.warnings.is_empty() {
+        out.push_str("## Warnings\n\n");
+        for warning in &analysis.warnings {
+            let _ = writeln!(out, "- {}", escape(warning));
+        }
+        out.push('\n');
+    }
+    let _ = writeln!(
+        out,
+        "_Generated by pdx {} on {}_",
+        env!("CARGO_PKG_VERSION"),
+        analysis.timestamp.to_rfc3339()
+    );
+    out
+}
+
+fn metadata(out: &mut String, analysis: &PdfAnalysis) {
+    let metadata = &analysis.metadata;
+    let summary = &analysis.summary;
+    let mut rows = vec![
+        ("Path", escape(&analysis.path)),
+        ("Size", format!("{} bytes", metadata.size)),
+    ];
+    if let Some(sha256) = &metadata.sha256 {
+        rows.push(("SHA-256", format!("`{}`", sha256)));
+    }
+    if let Some(digest) = &metadata.digest {
+        rows.push(("Digest", format!("{} `{}`", digest.algorithm, digest.value)));
+    }
+    if let Some(title) = &metadata.title {
+        rows.push(("Title", escape(title)));
+    }
+    if let Some(author) = &metadata.author {
+        rows.push(("Author", escape(author)));
+    }
+    if let Some(revision) = analysis.revision {
+        rows.push(("Revision", format!("{} (historical state)", revision)));
+    }
+    rows.push(("Pages", summary.pages.to_string()));
+    rows.push(("Revisions", summary.revisions.to_string()));
+    rows.push((
+        "Objects",
+        format!("{} ({} streams)", summary.object_count, summary.streams),
+    ));
+    rows.push(("Encrypted", analysis.security.encrypted.to_string()));
+    rows.push(("Attachments", analysis.attachments.files.len().to_string()));
+    rows.push(("URIs", analysis.uris.uris.len().to_string()));
+
+    out.push_str("## Metadata\n\n| Property | Value |\n| --- | --- |\n");
+    for (name, value) in rows {
+        let _ = writeln!(out, "| {} | {} |", name, value);
+    }
+    out.push('\n');
+}
+
+fn findings(out: &mut String, findings: &[Finding]) {
+    let _ = writeln!(out, "## Findings ({})\n", findings.len());
+    if findings.is_empty() {
+        out.push_str("No findings.\n\n");
+        return;
+    }
+    let mut sorted: Vec<&Finding> = findings.iter().collect();
+    sorted.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.id.cmp(&b.id)));
+
+    out.push_str("| Severity | Finding | Id | Objects | ATT&CK |\n| --- | --- | --- | --- | --- |\n");
+    for finding in &sorted {
+        let objects: Vec<String> = finding.objects.iter().map(|(n, g)| format!("{} {}", n, g)).collect();
+        let _ = writeln!(
+            out,
+            "| {} | {} | `{}` | {} | {} |",
+            severity(finding.severity),
+            escape(&finding.title),
+            finding.id,
+            objects.join(", "),
+            finding.techniques.join(", ")
+        );
+    }
+    out.push('\n');
+
+    let evidenced: Vec<&&Finding> = sorted.iter().filter(|f| !f.evidence.is_empty()).collect();
+    if evidenced.is_empty() {
+        return;
+    }
+    out.push_str("### Evidence\n\n");
+    for finding in evidenced {
+        let _ = writeln!(out, "- **{}** (`{}`)", escape(&finding.title), finding.id);
+        for evidence in &finding.evidence {
+            let _ = writeln!(out, "  - {}", code(evidence));
+        }
+    }
+    out.push('\n');
+}
+
+fn scripts(out: &mut String, analysis: &PdfAnalysis) {
+    let mut blocks: Vec<(String, &str)> = analysis
+        .actions
+        .actions
+        .iter()
+        .filter(|action| action.kind == "JavaScript")
+        .filter_map(|action| {
+            let location = match action.id.or(action.owner) {
+                Some((number, generation)) => format!(" in object {} {}", number, generation),
+                None => String::new(),
+            };
+            Some((format!("{}{}", action.trigger, location), action.target.as_deref()?))
+        })
+        .collect();
+    if let Some(forms) = &analysis.forms {
+        for field in &forms.fields {
+            for script in &field.scripts {
+                blocks.push((format!("Field {} {}", field.name, script.trigger), &script.preview));
+            }
+        }
+    }
+    if blocks.is_empty() {
+        return;
+    }
+
+    let _ = writeln!(out, "## Scripts ({})\n", blocks.len());
+    for (title, script) in blocks {
+        let fence = "`".repeat(longest_backtick_run(script).max(2) + 1);
+        let _ = writeln!(
+            out,
+            "### {}\n\n{}javascript\n{}\n{}\n",
+            escape(&title),
+            fence,
+            script,
+            fence
+        );
+    }
+}
+
+fn severity(severity: Severity) -> String {
+    match severity {
+        Severity::Critical | Severity::High => format!("**{}**", severity),
+        _ => severity.to_string(),
+    }
+}
+
+/// Inline code span that survives backticks in the text
+fn code(text: &str) -> String {
+    let text = text.replace(['\r', '\n'], " ");
+    let ticks = "`".repeat(longest_backtick_run(&text) + 1);
+    let pad = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}{}{}{}", ticks, pad, text, pad, ticks)
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Escapes Markdown and table syntax in text from the document; line
+/// breaks become spaces so a value stays in its table cell or list item
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\r' | '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(escape("a|b *c*\nd"), "a\\|b \\*c\\* d");
+        assert_eq!(code("x = `y`"), "`` x = `y` ``");
+        assert_eq!(longest_backtick_run("a ``` b ` c"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_findings_table() {
+        use crate::{detectors::RiskCategory, Analyzer, PdfAnalyzer};
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let mut analysis = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+        analysis.findings.push(
+            Finding::new(
+                "actions.launch",
+                Severity::Critical,
+                RiskCategory::ActiveContent,
+                "Launch | cmd.exe",
+            )
+            .evidence("/F (cmd.exe)"),
+        );
+
+        let markdown = render(&analysis);
+        assert!(markdown.starts_with("# PDx report: "));
+        assert!(markdown.contains("| **critical** | Launch \\| cmd.exe | `actions.launch` |"));
+        assert!(markdown.contains("  - `/F (cmd.exe)`"));
+    }
+}
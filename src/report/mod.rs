@@ -2,3 +2,543 @@
 // Author: kartik4091
 // Created: 2025-06-03 08:00:41 UTC
 
+//! Rendering of analysis results into the supported output formats
+
+pub mod dot;
+pub mod formatter;
+pub mod graphml;
+pub mod html;
+pub mod markdown;
+pub mod pdf;
+pub mod schema;
+pub mod stix;
+pub mod table;
+pub mod template;
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    detectors::{phishing::PhishingAssessment, uris::UriReport},
+    document::{
+        artifacts::ArtifactManifest, chronology::DocumentTimeline, diff::FileDiff, dump::StreamManifest, graph::ObjectGraph, inspect::ObjectInspection, layout::ByteSpan,
+        listing::ObjectEntry,
+    },
+    error::{Error, Result},
+    integrations::policy::{Decision, Policy},
+    known_good::KnownGoodMatch,
+    remediation::{repair::RepairReport, RemediationReport},
+    scoring::RiskScore,
+    testing::CorpusReport,
+    text::PageText,
+    PdfAnalysis,
+};
+
+/// Output formats supported by the report renderer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputFormat {
+    /// Short human-readable summary
+    Text,
+
+    /// Pretty-printed JSON of the full analysis model
+    Json,
+
+    /// Human-readable report including every section
+    Detailed,
+
+    /// YAML of the full analysis model
+    Yaml,
+
+    /// Comma-separated rows of key indicators, for spreadsheets
+    Csv,
+
+    /// Tab-separated rows of key indicators
+    Tsv,
+
+    /// Standalone HTML page with the findings, metadata, thumbnails and
+    /// object tree
+    Html,
+
+    /// STIX 2.1 bundle for threat intelligence platforms
+    Stix,
+
+    /// Newline-delimited JSON: one compact line per result, so batch
+    /// output can be consumed as it is produced
+    Jsonl,
+
+    /// GitHub-flavored Markdown for tickets and wikis
+    Markdown,
+
+    /// CBOR of the full analysis model (`binary` feature)
+    Cbor,
+
+    /// MessagePack of the full analysis model, with field names
+    /// (`binary` feature)
+    MessagePack,
+
+    /// Paginated PDF report for readers without tooling
+    Pdf,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "detailed" => Ok(Self::Detailed),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            "html" | "htm" => Ok(Self::Html),
+            "stix" => Ok(Self::Stix),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "cbor" => Ok(Self::Cbor),
+            "msgpack" | "messagepack" => Ok(Self::MessagePack),
+            "pdf" => Ok(Self::Pdf),
+            other => Err(format!("Unknown output format: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Text => "text",
+            Self::Json => "json",
+            Self::Detailed => "detailed",
+            Self::Yaml => "yaml",
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::Html => "html",
+            Self::Stix => "stix",
+            Self::Jsonl => "jsonl",
+            Self::Markdown => "markdown",
+            Self::Cbor => "cbor",
+            Self::MessagePack => "msgpack",
+            Self::Pdf => "pdf",
+        };
+        f.write_str(name)
+    }
+}
+
+impl OutputFormat {
+    /// Field delimiter of the tabular formats
+    pub fn delimiter(self) -> Option<char> {
+        match self {
+            Self::Csv => Some(','),
+            Self::Tsv => Some('\t'),
+            _ => None,
+        }
+    }
+
+    /// Whether output is binary and has to go through [`encode`]
+    pub fn is_binary(self) -> bool {
+        matches!(self, Self::Cbor | Self::MessagePack | Self::Pdf)
+    }
+}
+
+/// Triage view of an analysis: the policy verdict and the phishing likelihood
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanReport {
+    /// Analyzed file
+    pub path: String,
+
+    /// Verdict under the policy, with the findings behind it
+    pub decision: Decision,
+
+    /// Risk from the analysis summary, with its per-category breakdown
+    pub risk: RiskScore,
+
+    /// Risk at which the scan flags the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+
+    /// Phishing likelihood and signals
+    pub phishing: PhishingAssessment,
+
+    /// The file or attachments matching a known-good hash set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_good: Vec<KnownGoodMatch>,
+}
+
+impl ScanReport {
+    /// Builds the triage view of an analysis
+    pub fn new(analysis: &PdfAnalysis, policy: &Policy) -> Self {
+        Self {
+            path: analysis.path.clone(),
+            decision: policy.evaluate(analysis),
+            risk: analysis.summary.risk.clone(),
+            threshold: None,
+            phishing: analysis.phishing.clone(),
+            known_good: analysis.known_good.clone(),
+        }
+    }
+
+    /// Triage view of a file whose analysis failed, carrying the policy's
+    /// decision for errors
+    pub fn failed(path: impl Into<String>, decision: Decision) -> Self {
+        Self {
+            path: path.into(),
+            decision,
+            risk: RiskScore::default(),
+            threshold: None,
+            phishing: PhishingAssessment::default(),
+            known_good: Vec::new(),
+        }
+    }
+
+    /// Flags the file when its risk reaches `threshold`
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Whether the risk reaches the threshold, if one is set
+    pub fn exceeds_threshold(&self) -> bool {
+        self.threshold.map_or(false, |threshold| self.risk.score >= threshold)
+    }
+}
+
+/// Output formats of the object graph
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+
+    /// GraphML with object and revision attributes, for Gephi and similar
+    /// tools
+    GraphMl,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dot" | "gv" => Ok(Self::Dot),
+            "graphml" => Ok(Self::GraphMl),
+            other => Err(format!("Unknown graph format: {}", other)),
+        }
+    }
+}
+
+/// Renders the object graph of a document
+pub fn render_graph(graph: &ObjectGraph, format: GraphFormat) -> String {
+    match format {
+        GraphFormat::Dot => dot::render(graph),
+        GraphFormat::GraphMl => graphml::render(graph),
+    }
+}
+
+/// Renders an analysis in the requested format; tabular formats get one
+/// row for the file under a header
+pub fn render(analysis: &PdfAnalysis, format: OutputFormat) -> Result<String> {
+    render_rows(analysis, format, table::Rows::Files, true)
+}
+
+/// Renders an analysis, choosing what a row stands for and whether a header
+/// precedes the rows when the format is tabular
+pub fn render_rows(analysis: &PdfAnalysis, format: OutputFormat, rows: table::Rows, header: bool) -> Result<String> {
+    match format {
+        OutputFormat::Text => Ok(formatter::text(analysis, false)),
+        OutputFormat::Detailed => Ok(formatter::text(analysis, true)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(analysis)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(analysis)?),
+        OutputFormat::Csv | OutputFormat::Tsv => Ok(table::analysis(analysis, tabular(format)?, rows, header)),
+        OutputFormat::Html => Ok(html::render(analysis)),
+        OutputFormat::Markdown => Ok(markdown::render(analysis)),
+        OutputFormat::Stix => Ok(serde_json::to_string_pretty(&stix::bundle(analysis))?),
+        OutputFormat::Jsonl => Ok(serde_json::to_string(analysis)?),
+        OutputFormat::Cbor | OutputFormat::MessagePack | OutputFormat::Pdf => Err(Error::Analysis(format!(
+            "{} is a binary format and cannot be rendered as text",
+            format
+        ))),
+    }
+}
+
+/// Serializes an analysis in any format; CBOR and MessagePack need the
+/// `binary` feature, text formats are returned as UTF-8
+pub fn encode(analysis: &PdfAnalysis, format: OutputFormat) -> Result<Vec<u8>> {
+    match format {
+        #[cfg(feature = "binary")]
+        OutputFormat::Cbor => {
+            let mut out = Vec::new();
+            ciborium::into_writer(analysis, &mut out).map_err(|e| Error::Encoding(e.to_string()))?;
+            Ok(out)
+        }
+        // Named fields: positional arrays cannot represent skipped fields
+        #[cfg(feature = "binary")]
+        OutputFormat::MessagePack => rmp_serde::to_vec_named(analysis).map_err(|e| Error::Encoding(e.to_string())),
+        #[cfg(not(feature = "binary"))]
+        OutputFormat::Cbor | OutputFormat::MessagePack => Err(Error::Analysis(format!(
+            "This build of pdx has no {} support (enable the `binary` feature)",
+            format
+        ))),
+        OutputFormat::Pdf => pdf::render(analysis),
+        _ => Ok(render(analysis, format)?.into_bytes()),
+    }
+}
+
+/// Renders the triage view of the `scan` command
+pub fn render_scan(report: &ScanReport, format: OutputFormat) -> Result<String> {
+    render_scan_rows(report, format, true)
+}
+
+/// Renders the triage view, leaving out the header of tabular formats
+/// unless `header` is set
+pub fn render_scan_rows(report: &ScanReport, format: OutputFormat, header: bool) -> Result<String> {
+    match format {
+        OutputFormat::Text => Ok(formatter::scan(report, false)),
+        OutputFormat::Detailed => Ok(formatter::scan(report, true)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(report)?),
+        OutputFormat::Csv | OutputFormat::Tsv => Ok(table::scan(report, tabular(format)?, header)),
+        OutputFormat::Jsonl => Ok(serde_json::to_string(report)?),
+        _ => Err(unsupported("Scan reports", format)),
+    }
+}
+
+/// Renders the scan reports of a multi-file run as one JSON or YAML list
+pub fn render_scans(reports: &[ScanReport], format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(reports)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(reports)?),
+        _ => Err(unsupported("Scan report lists", format)),
+    }
+}
+
+/// Renders the comparison of two files
+pub fn render_diff(diff: &FileDiff, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text => Ok(formatter::file_diff(diff, false)),
+        OutputFormat::Detailed => Ok(formatter::file_diff(diff, true)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(diff)?),
+        OutputFormat::Jsonl => Ok(serde_json::to_string(diff)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(diff)?),
+        _ => Err(unsupported("Diffs", format)),
+    }
+}
+
+/// Renders a single-object inspection; text and detailed are identical
+pub fn render_inspection(inspection: &ObjectInspection, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => Ok(formatter::inspection(inspection)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(inspection)?),
+        OutputFormat::Jsonl => Ok(serde_json::to_string(inspection)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(inspection)?),
+        _ => Err(unsupported("Object inspections", format)),
+    }
+}
+
+/// Renders an object listing; text and detailed are identical
+pub fn render_objects(entries: &[ObjectEntry], format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => Ok(formatter::object_list(entries)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(entries)?),
+        OutputFormat::Jsonl => json_lines(entries),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(entries)?),
+        OutputFormat::Csv | OutputFormat::Tsv => Ok(table::objects(entries, tabular(format)?)),
+        _ => Err(unsupported("Object listings", format)),
+    }
+}
+
+/// Renders a reconstructed document history; text and detailed are
+/// identical
+pub fn render_timeline(timeline: &DocumentTimeline, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => Ok(formatter::document_timeline(timeline)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(timeline)?),
+        OutputFormat::Jsonl => json_lines(&timeline.events),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(timeline)?),
+        OutputFormat::Csv | OutputFormat::Tsv => Ok(table::timeline(timeline, tabular(format)?)),
+        _ => Err(unsupported("Timelines", format)),
+    }
+}
+
+/// Renders the manifest of an artifact extraction; text and detailed are
+/// identical
+pub fn render_artifacts(manifest: &ArtifactManifest, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => Ok(formatter::artifact_manifest(manifest)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(manifest)?),
+        OutputFormat::Jsonl => json_lines(&manifest.artifacts),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(manifest)?),
+        _ => Err(unsupported("Artifact manifests", format)),
+    }
+}
+
+/// Renders the manifest of a stream dump; text and detailed are identical
+pub fn render_manifest(manifest: &StreamManifest, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => Ok(formatter::stream_manifest(manifest)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(manifest)?),
+        OutputFormat::Jsonl => json_lines(&manifest.streams),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(manifest)?),
+        _ => Err(unsupported("Stream manifests", format)),
+    }
+}
+
+/// Renders an annotated hex dump; structured formats give the span map
+/// without the bytes
+pub fn render_hexdump(data: &[u8], spans: &[ByteSpan], format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => Ok(formatter::annotated_hexdump(data, spans)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(spans)?),
+        OutputFormat::Jsonl => json_lines(spans),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(spans)?),
+        _ => Err(unsupported("Hex dumps", format)),
+    }
+}
+
+/// Renders a remediation report; text and detailed are identical
+pub fn render_remediation(report: &RemediationReport, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => Ok(formatter::remediation(report)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+        OutputFormat::Jsonl => Ok(serde_json::to_string(report)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(report)?),
+        _ => Err(unsupported("Remediation reports", format)),
+    }
+}
+
+/// Renders a repair report; text and detailed are identical
+pub fn render_repair(report: &RepairReport, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => Ok(formatter::repair(report)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+        OutputFormat::Jsonl => Ok(serde_json::to_string(report)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(report)?),
+        _ => Err(unsupported("Repair reports", format)),
+    }
+}
+
+/// Renders the result of a golden-corpus regression run
+pub fn render_corpus(report: &CorpusReport, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => Ok(formatter::corpus(report, format == OutputFormat::Detailed)),
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+        OutputFormat::Jsonl => Ok(serde_json::to_string(report)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(report)?),
+        _ => Err(unsupported("Corpus reports", format)),
+    }
+}
+
+/// Renders text extracted from the document's pages
+pub fn render_text(pages: &[PageText], format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text | OutputFormat::Detailed => {
+            Ok(formatter::extracted_text(pages, format == OutputFormat::Detailed))
+        }
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(pages)?),
+        OutputFormat::Jsonl => json_lines(pages),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(pages)?),
+        OutputFormat::Csv | OutputFormat::Tsv => Ok(table::text(pages, tabular(format)?)),
+        _ => Err(unsupported("Extracted text", format)),
+    }
+}
+
+/// Renders extracted URIs; the text format is a plain IOC list
+pub fn render_uris(report: &UriReport, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Text => Ok(report.ioc_list().trim_end().to_string()),
+        OutputFormat::Detailed => {
+            let mut out = String::new();
+            formatter::uris_section(&mut out, report, true);
+            Ok(out)
+        }
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(&report.uris)?),
+        OutputFormat::Jsonl => json_lines(&report.uris),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(&report.uris)?),
+        OutputFormat::Csv | OutputFormat::Tsv => Ok(table::uris(report, tabular(format)?)),
+        _ => Err(unsupported("Extracted URIs", format)),
+    }
+}
+
+/// One compact JSON document per item and line
+fn json_lines<T: Serialize>(items: &[T]) -> Result<String> {
+    let lines = items.iter().map(serde_json::to_string).collect::<serde_json::Result<Vec<_>>>()?;
+    Ok(lines.join("\n"))
+}
+
+fn tabular(format: OutputFormat) -> Result<char> {
+    format.delimiter().ok_or_else(|| unsupported("This output", format))
+}
+
+fn unsupported(what: &str, format: OutputFormat) -> Error {
+    Error::Analysis(format!("{} cannot be rendered as {}; use text, json or yaml", what, format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!("JSON".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!("detailed".parse::<OutputFormat>().unwrap(), OutputFormat::Detailed);
+        assert_eq!("yml".parse::<OutputFormat>().unwrap(), OutputFormat::Yaml);
+        assert_eq!("tsv".parse::<OutputFormat>().unwrap().delimiter(), Some('\t'));
+        assert_eq!("HTML".parse::<OutputFormat>().unwrap(), OutputFormat::Html);
+        assert_eq!("stix".parse::<OutputFormat>().unwrap(), OutputFormat::Stix);
+        assert_eq!("ndjson".parse::<OutputFormat>().unwrap(), OutputFormat::Jsonl);
+        assert_eq!("md".parse::<OutputFormat>().unwrap(), OutputFormat::Markdown);
+        assert!("msgpack".parse::<OutputFormat>().unwrap().is_binary());
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_yaml_round_trip() {
+        use crate::{Analyzer, PdfAnalyzer};
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let analysis = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+
+        let yaml = render(&analysis, OutputFormat::Yaml).unwrap();
+        let parsed: PdfAnalysis = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.path, analysis.path);
+        assert_eq!(parsed.findings.len(), analysis.findings.len());
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_is_one_line_per_result() {
+        use crate::{Analyzer, PdfAnalyzer};
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let analysis = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+
+        let line = render(&analysis, OutputFormat::Jsonl).unwrap();
+        assert!(!line.contains('\n'));
+        let parsed: PdfAnalysis = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.path, analysis.path);
+
+        let uris = render_uris(&analysis.uris, OutputFormat::Jsonl).unwrap();
+        assert_eq!(uris.lines().count(), analysis.uris.uris.len());
+    }
+
+    #[cfg(feature = "binary")]
+    #[tokio::test]
+    async fn test_binary_round_trip() {
+        use crate::{Analyzer, PdfAnalyzer};
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let analysis = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+        let json = encode(&analysis, OutputFormat::Jsonl).unwrap();
+
+        let cbor = encode(&analysis, OutputFormat::Cbor).unwrap();
+        let parsed: PdfAnalysis = ciborium::from_reader(cbor.as_slice()).unwrap();
+        assert_eq!(parsed.findings, analysis.findings);
+
+        let msgpack = encode(&analysis, OutputFormat::MessagePack).unwrap();
+        let parsed: PdfAnalysis = rmp_serde::from_slice(&msgpack).unwrap();
+        assert_eq!(parsed.path, analysis.path);
+        assert!(cbor.len() < json.len() && msgpack.len() < json.len());
+    }
+}
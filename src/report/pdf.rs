@@ -0,0 +1,448 @@
+//! PDF report
+//! Created: 2026-10-16 10:41:41 UTC
+//! Author: kartik4091
+//!
+//! Renders an analysis as a paginated A4 document for stakeholders who
+//! will not read JSON: a verdict line, the file metadata, and the findings
+//! ordered by severity with their evidence. It is written with lopdf using
+//! the standard Helvetica and Courier fonts, so nothing is embedded and
+//! the report holds no scripts, actions or attachments. Text from the
+//! analyzed document is reduced to what WinAnsiEncoding can show.
+
+use lopdf::{
+    content::{Content, Operation},
+    dictionary, Document, Object, Stream,
+};
+
+use crate::{
+    detectors::{Finding, Severity},
+    error::{Error, Result},
+    PdfAnalysis,
+};
+
+/// A4 width in points
+const PAGE_WIDTH: f32 = 595.0;
+
+/// A4 height in points
+const PAGE_HEIGHT: f32 = 842.0;
+
+/// Margin on every side
+const MARGIN: f32 = 50.0;
+
+/// Height reserved above the bottom margin for the page footer
+const FOOTER: f32 = 20.0;
+
+/// Indent of metadata values, past their labels
+const VALUE_INDENT: f32 = 80.0;
+
+/// Evidence lines printed per finding
+const MAX_EVIDENCE: usize = 5;
+
+/// Characters of a single evidence line
+const EVIDENCE_LENGTH: usize = 400;
+
+const BLACK: [f32; 3] = [0.0, 0.0, 0.0];
+
+const GRAY: [f32; 3] = [0.4, 0.4, 0.4];
+
+#[derive(Debug, Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Self::Regular => "F1",
+            Self::Bold => "F2",
+            Self::Mono => "F3",
+        }
+    }
+
+    /// Average advance in ems; Courier is exact, Helvetica is rounded up
+    /// so wrapped lines stay inside the margins
+    fn advance(self) -> f32 {
+        match self {
+            Self::Regular => 0.55,
+            Self::Bold => 0.6,
+            Self::Mono => 0.6,
+        }
+    }
+}
+
+/// Pages of content operations, filled top to bottom
+struct Layout {
+    pages: Vec<Vec<Operation>>,
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self {
+            pages: vec![Vec::new()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    /// Starts a new page unless `height` still fits on the current one
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < MARGIN + FOOTER {
+            self.pages.push(Vec::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// Writes wrapped text, breaking pages as needed
+    fn text(&mut self, font: Font, size: f32, indent: f32, color: [f32; 3], text: &str) {
+        let leading = size * 1.3;
+        let columns = ((PAGE_WIDTH - 2.0 * MARGIN - indent) / (size * font.advance())) as usize;
+        for line in wrap(text, columns.max(1)) {
+            self.reserve(leading);
+            self.y -= leading;
+            let y = self.y;
+            let page = self.pages.last_mut().expect("layout has a page");
+            show(page, font, size, MARGIN + indent, y, color, &line);
+        }
+    }
+
+    /// Writes a bold label with its wrapped value beside it
+    fn field(&mut self, label: &str, font: Font, value: &str) {
+        let size = 9.0;
+        let leading = size * 1.3;
+        let columns = ((PAGE_WIDTH - 2.0 * MARGIN - VALUE_INDENT) / (size * font.advance())) as usize;
+        for (index, line) in wrap(value, columns.max(1)).into_iter().enumerate() {
+            self.reserve(leading);
+            self.y -= leading;
+            let y = self.y;
+            let page = self.pages.last_mut().expect("layout has a page");
+            if index == 0 {
+                show(page, Font::Bold, size, MARGIN, y, BLACK, label);
+            }
+            show(page, font, size, MARGIN + VALUE_INDENT, y, BLACK, &line);
+        }
+    }
+}
+
+/// Renders an analysis as a PDF document
+pub fn render(analysis: &PdfAnalysis) -> Result<Vec<u8>> {
+    let name = std::path::Path::new(&analysis.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| analysis.path.clone());
+    let mut layout = Layout::new();
+    layout.text(Font::Bold, 18.0, 0.0, BLACK, &format!("PDx report: {}", name));
+    layout.gap(4.0);
+    let highest = analysis.summary.highest_severity;
+    layout.text(
+        Font::Bold,
+        11.0,
+        0.0,
+        highest.map_or(BLACK, color),
+        &format!(
+            "Risk score {:.2} - highest severity {} - {} findings",
+            analysis.summary.risk.score,
+            highest.map_or("none".to_string(), |s| s.to_string()),
+            analysis.findings.len()
+        ),
+    );
+
+    heading(&mut layout, "Metadata");
+    metadata(&mut layout, analysis);
+
+    heading(&mut layout, &format!("Findings ({})", analysis.findings.len()));
+    findings(&mut layout, &analysis.findings);
+
+    if !analysis.warnings.is_empty() {
+        heading(&mut layout, "Warnings");
+        for warning in &analysis.warnings {
+            layout.text(Font::Regular, 9.0, 0.0, GRAY, &format!("- {}", warning));
+        }
+    }
+
+    let footer = format!(
+        "Generated by pdx {} on {}",
+        env!("CARGO_PKG_VERSION"),
+        analysis.timestamp.to_rfc3339()
+    );
+    write(analysis, &name, layout.pages, &footer)
+}
+
+fn heading(layout: &mut Layout, title: &str) {
+    layout.gap(10.0);
+    // Keep a heading with at least a few lines of its section
+    layout.reserve(60.0);
+    layout.text(Font::Bold, 13.0, 0.0, BLACK, title);
+    layout.gap(2.0);
+}
+
+fn metadata(layout: &mut Layout, analysis: &PdfAnalysis) {
+    let metadata = &analysis.metadata;
+    let summary = &analysis.summary;
+    let mut rows = vec![
+        ("Path", analysis.path.clone(), Font::Regular),
+        ("Size", format!("{} bytes", metadata.size), Font::Regular),
+    ];
+    if let Some(sha256) = &metadata.sha256 {
+        rows.push(("SHA-256", sha256.clone(), Font::Mono));
+    }
+    if let Some(digest) = &metadata.digest {
+        rows.push(("Digest", format!("{} {}", digest.algorithm, digest.value), Font::Mono));
+    }
+    if let Some(title) = &metadata.title {
+        rows.push(("Title", title.clone(), Font::Regular));
+    }
+    if let Some(author) = &metadata.author {
+        rows.push(("Author", author.clone(), Font::Regular));
+    }
+    if let Some(revision) = analysis.revision {
+        rows.push(("Revision", format!("{} (historical state)", revision), Font::Regular));
+    }
+    rows.push(("Pages", summary.pages.to_string(), Font::Regular));
+    rows.push(("Revisions", summary.revisions.to_string(), Font::Regular));
+    rows.push((
+        "Objects",
+        format!("{} ({} streams)", summary.object_count, summary.streams),
+        Font::Regular,
+    ));
+    rows.push(("Encrypted", analysis.security.encrypted.to_string(), Font::Regular));
+    rows.push((
+        "Attachments",
+        analysis.attachments.files.len().to_string(),
+        Font::Regular,
+    ));
+    rows.push(("URIs", analysis.uris.uris.len().to_string(), Font::Regular));
+
+    for (label, value, font) in rows {
+        layout.field(label, font, &value);
+    }
+}
+
+fn findings(layout: &mut Layout, findings: &[Finding]) {
+    if findings.is_empty() {
+        layout.text(Font::Regular, 10.0, 0.0, BLACK, "No findings.");
+        return;
+    }
+    let mut sorted: Vec<&Finding> = findings.iter().collect();
+    sorted.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.id.cmp(&b.id)));
+
+    for finding in sorted {
+        layout.gap(6.0);
+        layout.reserve(30.0);
+        layout.text(
+            Font::Bold,
+            10.0,
+            0.0,
+            color(finding.severity),
+            &format!("[{}] {}", finding.severity.to_string().to_uppercase(), finding.title),
+        );
+        let mut details = vec![finding.id.clone(), format!("{} confidence", finding.confidence)];
+        if !finding.objects.is_empty() {
+            let objects: Vec<String> = finding.objects.iter().map(|(n, g)| format!("{} {}", n, g)).collect();
+            details.push(format!("objects {}", objects.join(", ")));
+        }
+        if !finding.techniques.is_empty() {
+            details.push(format!("ATT&CK {}", finding.techniques.join(", ")));
+        }
+        layout.text(Font::Regular, 8.5, 12.0, GRAY, &details.join(" - "));
+        for evidence in finding.evidence.iter().take(MAX_EVIDENCE) {
+            let evidence: String = evidence.chars().take(EVIDENCE_LENGTH).collect();
+            layout.text(Font::Mono, 8.0, 12.0, BLACK, &evidence);
+        }
+        if finding.evidence.len() > MAX_EVIDENCE {
+            let more = format!("... {} more", finding.evidence.len() - MAX_EVIDENCE);
+            layout.text(Font::Regular, 8.0, 12.0, GRAY, &more);
+        }
+    }
+}
+
+/// Assembles the document, adding page numbers and the footer
+fn write(analysis: &PdfAnalysis, name: &str, pages: Vec<Vec<Operation>>, footer: &str) -> Result<Vec<u8>> {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font = |base: &str| {
+        Object::Dictionary(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => base,
+            "Encoding" => "WinAnsiEncoding",
+        })
+    };
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! {
+            "F1" => font("Helvetica"),
+            "F2" => font("Helvetica-Bold"),
+            "F3" => font("Courier"),
+        },
+    });
+
+    let count = pages.len();
+    let mut kids = Vec::with_capacity(count);
+    for (index, mut operations) in pages.into_iter().enumerate() {
+        let y = MARGIN;
+        show(&mut operations, Font::Regular, 7.5, MARGIN, y, GRAY, footer);
+        let number = format!("Page {} of {}", index + 1, count);
+        let x = PAGE_WIDTH - MARGIN - number.len() as f32 * 7.5 * Font::Regular.advance();
+        show(&mut operations, Font::Regular, 7.5, x, y, GRAY, &number);
+
+        let content = Content { operations }
+            .encode()
+            .map_err(|e| Error::Pdf(format!("Failed to encode report page: {}", e)))?;
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        kids.push(page_id.into());
+    }
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => count as i64,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), PAGE_WIDTH.into(), PAGE_HEIGHT.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    let info_id = doc.add_object(dictionary! {
+        "Title" => Object::string_literal(encode(&format!("PDx report: {}", name))),
+        "Producer" => Object::string_literal(format!("pdx {}", env!("CARGO_PKG_VERSION"))),
+        "CreationDate" => Object::string_literal(analysis.timestamp.format("D:%Y%m%d%H%M%SZ").to_string()),
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.trailer.set("Info", info_id);
+    doc.compress();
+
+    let mut out = Vec::new();
+    doc.save_to(&mut out)
+        .map_err(|e| Error::Pdf(format!("Failed to write report: {}", e)))?;
+    Ok(out)
+}
+
+/// Appends the operations showing one line of text
+fn show(operations: &mut Vec<Operation>, font: Font, size: f32, x: f32, y: f32, color: [f32; 3], text: &str) {
+    operations.extend([
+        Operation::new("BT", vec![]),
+        Operation::new("rg", color.iter().map(|&c| c.into()).collect()),
+        Operation::new("Tf", vec![font.resource().into(), size.into()]),
+        Operation::new("Td", vec![x.into(), y.into()]),
+        Operation::new("Tj", vec![Object::string_literal(encode(text))]),
+        Operation::new("ET", vec![]),
+    ]);
+}
+
+fn color(severity: Severity) -> [f32; 3] {
+    match severity {
+        Severity::Critical => [0.7, 0.0, 0.0],
+        Severity::High => [0.85, 0.3, 0.0],
+        Severity::Medium => [0.7, 0.5, 0.0],
+        Severity::Low | Severity::Info => BLACK,
+    }
+}
+
+/// Greedy word wrap to `columns` characters; words longer than a line are
+/// split
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while !word.is_empty() {
+            let used = line.chars().count();
+            let room = if used == 0 {
+                columns
+            } else {
+                columns.saturating_sub(used + 1)
+            };
+            if word.len() <= room {
+                if used > 0 {
+                    line.push(' ');
+                }
+                line.extend(word.drain(..));
+            } else if used > 0 {
+                lines.push(std::mem::take(&mut line));
+            } else {
+                lines.push(word.drain(..columns).collect());
+            }
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// WinAnsi bytes of text; Latin-1 maps directly, anything else becomes `?`
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x20..=0x7e | 0xa0..=0xff => c as u8,
+            _ if c.is_whitespace() => b' ',
+            _ => b'?',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_and_encode() {
+        assert_eq!(wrap("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 10), vec![""]);
+        assert_eq!(encode("é\u{2014}\tx"), vec![0xe9, b'?', b' ', b'x']);
+    }
+
+    #[tokio::test]
+    async fn test_report_pages() {
+        use crate::{detectors::RiskCategory, Analyzer, PdfAnalyzer};
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let mut analysis = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+        for index in 0..80 {
+            analysis.findings.push(
+                Finding::new(
+                    "actions.launch",
+                    Severity::Critical,
+                    RiskCategory::ActiveContent,
+                    &format!("Launch {}", index),
+                )
+                .evidence("/F (cmd.exe)"),
+            );
+        }
+
+        let report = render(&analysis).unwrap();
+        let doc = Document::load_mem(&report).unwrap();
+        let pages = doc.get_pages();
+        assert!(pages.len() > 1);
+        let shown: Vec<String> = doc
+            .get_and_decode_page_content(pages[&1])
+            .unwrap()
+            .operations
+            .iter()
+            .filter(|op| op.operator == "Tj")
+            .filter_map(|op| op.operands[0].as_str().ok())
+            .map(|text| String::from_utf8_lossy(text).into_owned())
+            .collect();
+        assert_eq!(
+            shown[0],
+            format!("PDx report: {}", temp.path().file_name().unwrap().to_string_lossy())
+        );
+        assert!(shown.iter().any(|line| line == "[CRITICAL] Launch 0"));
+        assert!(shown.contains(&format!("Page 1 of {}", pages.len())));
+    }
+}
@@ -0,0 +1,64 @@
+//! JSON Schema of the analysis output
+//! Created: 2026-10-16 10:42:04 UTC
+//! Author: kartik4091
+//!
+//! The JSON, YAML, JSONL and binary outputs all serialize `PdfAnalysis`,
+//! and every analysis records the model version it was produced with.
+//! The version follows semver: the minor version moves when fields or
+//! variants are added, which consumers are expected to ignore, and the
+//! major version when anything is renamed, removed or changes type. The
+//! schema is derived from the types themselves, so it cannot drift from
+//! what is serialized.
+
+use schemars::schema_for;
+use serde_json::{json, Value};
+
+use crate::{error::Result, PdfAnalysis};
+
+/// Version of the analysis output model
+pub const SCHEMA_VERSION: &str = "1.0.0";
+
+/// JSON Schema (draft-07) of an analysis
+pub fn analysis() -> Result<Value> {
+    let mut schema = serde_json::to_value(schema_for!(PdfAnalysis))?;
+    schema["$id"] = json!(format!("urn:pdx:schema:analysis:{}", SCHEMA_VERSION));
+    schema["title"] = json!(format!("PDx analysis {}", SCHEMA_VERSION));
+    Ok(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_covers_the_model() {
+        let schema = analysis().unwrap();
+        assert_eq!(schema["$id"], format!("urn:pdx:schema:analysis:{}", SCHEMA_VERSION));
+        let properties = schema["properties"].as_object().unwrap();
+        for field in ["schema_version", "path", "metadata", "findings", "embedded"] {
+            assert!(properties.contains_key(field), "{}", field);
+        }
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(Value::as_str)
+            .collect();
+        // Fields added after 1.0 must be optional so older outputs still validate
+        assert!(!required.contains(&"schema_version"));
+        assert!(schema["definitions"]["Finding"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_analyses_carry_the_version() {
+        use crate::{Analyzer, PdfAnalyzer};
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let analysis = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+        assert_eq!(analysis.schema_version, SCHEMA_VERSION);
+
+        let value = serde_json::to_value(&analysis).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+    }
+}
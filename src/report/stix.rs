@@ -0,0 +1,249 @@
+//! STIX 2.1 bundle export
+//! Created: 2026-10-16 10:34:03 UTC
+//! Author: kartik4091
+//!
+//! Converts an analysis into a STIX 2.1 bundle for threat intelligence
+//! platforms and TAXII collections: a `file` observable for the document,
+//! `url` observables for suspicious links, a `malware-analysis` object
+//! with the verdict, and, when the document is not benign, an `indicator`
+//! on its SHA-256 linked to `attack-pattern` objects for the ATT&CK
+//! techniques behind the findings. Observables get the deterministic
+//! (UUIDv5) ids the specification asks for, so exports of the same file
+//! or URL merge in the receiving platform.
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{attack, detectors::Severity, document::hashing::HashAlgorithm, PdfAnalysis};
+
+/// Namespace of deterministic STIX Cyber-observable Object ids
+const SCO_NAMESPACE: Uuid = Uuid::from_u128(0x00abedb4_aa42_466c_9c01_fed23315a9b7);
+
+/// Findings summarized in the indicator description
+const DESCRIBED_FINDINGS: usize = 10;
+
+/// Verdict of the `malware-analysis` object, from the `malware-result-ov`
+/// vocabulary
+pub fn verdict(analysis: &PdfAnalysis) -> &'static str {
+    if analysis.known_good.iter().any(|m| m.object.is_none()) {
+        return "benign";
+    }
+    match analysis.summary.highest_severity {
+        Some(Severity::Critical | Severity::High) => "malicious",
+        Some(Severity::Medium) => "suspicious",
+        Some(Severity::Low | Severity::Info) | None => "benign",
+    }
+}
+
+/// Builds the bundle for an analysis
+pub fn bundle(analysis: &PdfAnalysis) -> Value {
+    let now = timestamp(Utc::now());
+    let analyzed = timestamp(analysis.timestamp);
+    let mut objects = Vec::new();
+
+    let mut hashes = serde_json::Map::new();
+    if let Some(sha256) = &analysis.metadata.sha256 {
+        hashes.insert("SHA-256".to_string(), json!(sha256));
+    }
+    if let Some(digest) = &analysis.metadata.digest {
+        hashes.insert(hash_name(digest.algorithm).to_string(), json!(digest.value));
+    }
+    let name = std::path::Path::new(&analysis.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| analysis.path.clone());
+    let file_id = match &analysis.metadata.sha256 {
+        Some(sha256) => observable_id("file", &json!({ "hashes": { "SHA-256": sha256 } })),
+        None => observable_id("file", &json!({ "name": name })),
+    };
+    objects.push(json!({
+        "type": "file",
+        "spec_version": "2.1",
+        "id": file_id,
+        "hashes": hashes,
+        "size": analysis.metadata.size,
+        "name": name,
+        "mime_type": "application/pdf",
+    }));
+
+    let mut observed = Vec::new();
+    for uri in analysis.uris.suspicious() {
+        let id = observable_id("url", &json!({ "value": uri.decoded }));
+        if observed.contains(&id) {
+            continue;
+        }
+        objects.push(json!({
+            "type": "url",
+            "spec_version": "2.1",
+            "id": id,
+            "value": uri.decoded,
+        }));
+        observed.push(id);
+    }
+
+    let result = verdict(analysis);
+    objects.push(json!({
+        "type": "malware-analysis",
+        "spec_version": "2.1",
+        "id": format!("malware-analysis--{}", Uuid::new_v4()),
+        "created": now,
+        "modified": now,
+        "product": "pdx",
+        "version": env!("CARGO_PKG_VERSION"),
+        "analysis_started": analyzed,
+        "analysis_ended": analyzed,
+        "result": result,
+        "sample_ref": file_id,
+        "analysis_sco_refs": observed,
+    }));
+
+    if result != "benign" {
+        let Some(sha256) = &analysis.metadata.sha256 else {
+            return wrap(objects);
+        };
+        let indicator_id = format!("indicator--{}", Uuid::new_v4());
+        let mut findings: Vec<_> = analysis
+            .findings
+            .iter()
+            .filter(|f| f.severity > Severity::Low)
+            .collect();
+        findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+        let description: Vec<String> = findings
+            .iter()
+            .take(DESCRIBED_FINDINGS)
+            .map(|f| format!("[{}] {}", f.severity, f.title))
+            .collect();
+        objects.push(json!({
+            "type": "indicator",
+            "spec_version": "2.1",
+            "id": indicator_id,
+            "created": now,
+            "modified": now,
+            "name": format!("{} PDF {}", result, name),
+            "description": description.join("\n"),
+            "indicator_types": ["malicious-activity"],
+            "pattern": format!("[file:hashes.'SHA-256' = '{}']", sha256),
+            "pattern_type": "stix",
+            "valid_from": analyzed,
+        }));
+        objects.push(relationship(&now, "based-on", &indicator_id, &file_id));
+
+        let mut techniques: Vec<&str> = findings
+            .iter()
+            .flat_map(|f| f.techniques.iter().map(String::as_str))
+            .collect();
+        techniques.sort_unstable();
+        techniques.dedup();
+        for id in techniques {
+            // Stable per technique, so repeated exports update one object
+            let pattern_id = format!("attack-pattern--{}", Uuid::new_v5(&SCO_NAMESPACE, id.as_bytes()));
+            let technique = attack::technique(id);
+            let mut reference = json!({ "source_name": "mitre-attack", "external_id": id });
+            if let Some(technique) = technique {
+                reference["url"] = json!(technique.url());
+            }
+            objects.push(json!({
+                "type": "attack-pattern",
+                "spec_version": "2.1",
+                "id": pattern_id,
+                "created": now,
+                "modified": now,
+                "name": technique.map_or(id, |t| t.name),
+                "external_references": [reference],
+            }));
+            objects.push(relationship(&now, "indicates", &indicator_id, &pattern_id));
+        }
+    }
+    wrap(objects)
+}
+
+fn wrap(objects: Vec<Value>) -> Value {
+    json!({
+        "type": "bundle",
+        "id": format!("bundle--{}", Uuid::new_v4()),
+        "objects": objects,
+    })
+}
+
+fn relationship(now: &str, kind: &str, source: &str, target: &str) -> Value {
+    json!({
+        "type": "relationship",
+        "spec_version": "2.1",
+        "id": format!("relationship--{}", Uuid::new_v4()),
+        "created": now,
+        "modified": now,
+        "relationship_type": kind,
+        "source_ref": source,
+        "target_ref": target,
+    })
+}
+
+/// UUIDv5 id of an observable from its ID-contributing properties; the
+/// canonical form is serde_json's, which sorts keys and adds no whitespace
+fn observable_id(kind: &str, properties: &Value) -> String {
+    let canonical = properties.to_string();
+    format!("{}--{}", kind, Uuid::new_v5(&SCO_NAMESPACE, canonical.as_bytes()))
+}
+
+/// Name of a hash in the STIX `hash-algorithm-ov` vocabulary
+fn hash_name(algorithm: HashAlgorithm) -> &'static str {
+    match algorithm {
+        HashAlgorithm::Md5 => "MD5",
+        HashAlgorithm::Sha1 => "SHA-1",
+        HashAlgorithm::Sha256 => "SHA-256",
+        // Not in the vocabulary, which allows custom names
+        HashAlgorithm::Blake3 => "BLAKE3",
+    }
+}
+
+/// STIX timestamps are UTC with millisecond precision
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observable_ids_are_deterministic() {
+        let a = observable_id("url", &json!({ "value": "https://example.com/" }));
+        let b = observable_id("url", &json!({ "value": "https://example.com/" }));
+        assert_eq!(a, b);
+        assert!(a.starts_with("url--"));
+        assert_ne!(a, observable_id("url", &json!({ "value": "https://example.org/" })));
+        assert_eq!(hash_name(HashAlgorithm::Sha1), "SHA-1");
+    }
+
+    #[tokio::test]
+    async fn test_bundle_of_flagged_document() {
+        use crate::{detectors::RiskCategory, Analyzer, PdfAnalyzer};
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let mut analysis = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+        analysis.summary.highest_severity = Some(Severity::High);
+        analysis.findings.push(
+            crate::detectors::Finding::new("actions.launch", Severity::High, RiskCategory::ActiveContent, "Launch")
+                .techniques(["T1204.002".to_string()]),
+        );
+
+        let bundle = bundle(&analysis);
+        let objects = bundle["objects"].as_array().unwrap();
+        let of = |kind: &str| objects.iter().filter(|o| o["type"] == kind).collect::<Vec<_>>();
+        assert_eq!(of("file").len(), 1);
+        assert_eq!(of("malware-analysis")[0]["result"], "malicious");
+        assert_eq!(of("malware-analysis")[0]["sample_ref"], of("file")[0]["id"]);
+        let indicator = of("indicator")[0];
+        assert!(indicator["pattern"]
+            .as_str()
+            .unwrap()
+            .contains(analysis.metadata.sha256.as_deref().unwrap()));
+        assert_eq!(
+            of("attack-pattern")[0]["external_references"][0]["external_id"],
+            "T1204.002"
+        );
+        assert_eq!(of("relationship").len(), 1 + of("attack-pattern").len());
+    }
+}
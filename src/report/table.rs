@@ -0,0 +1,355 @@
+//! CSV and TSV output
+//! Created: 2026-10-16 10:31:48 UTC
+//! Author: kartik4091
+//!
+//! Flattens analyses into delimited rows for spreadsheets and batch
+//! triage: one row per file with its key indicators and scores, or one
+//! row per finding. Rows of several runs can be concatenated by leaving
+//! out the header. Document-controlled text (titles, paths, finding
+//! titles) is neutralized so a spreadsheet never evaluates it as a
+//! formula.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::ScanReport;
+use crate::{
+    detectors::{reader_features::ReaderFeature, uris::UriReport, Severity},
+    document::{chronology::DocumentTimeline, listing::ObjectEntry},
+    text::PageText,
+    PdfAnalysis,
+};
+
+/// Columns of the per-file table
+const FILE_COLUMNS: [&str; 20] = [
+    "path",
+    "sha256",
+    "size",
+    "pages",
+    "revisions",
+    "encrypted",
+    "risk_score",
+    "highest_severity",
+    "findings",
+    "critical",
+    "high",
+    "medium",
+    "low",
+    "javascript",
+    "launch",
+    "attachments",
+    "embedded_documents",
+    "uris",
+    "phishing_score",
+    "techniques",
+];
+
+/// Columns of the per-finding table
+const FINDING_COLUMNS: [&str; 10] = [
+    "path",
+    "sha256",
+    "id",
+    "severity",
+    "confidence",
+    "category",
+    "title",
+    "objects",
+    "techniques",
+    "evidence",
+];
+
+/// Columns of the scan table
+const SCAN_COLUMNS: [&str; 6] = [
+    "path",
+    "verdict",
+    "severity",
+    "risk_score",
+    "phishing_score",
+    "reasons",
+];
+
+/// What a table row stands for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rows {
+    /// One row per analyzed file
+    #[default]
+    Files,
+
+    /// One row per finding
+    Findings,
+}
+
+impl FromStr for Rows {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "files" | "file" => Ok(Self::Files),
+            "findings" | "finding" => Ok(Self::Findings),
+            other => Err(format!("Unknown row mode: {}", other)),
+        }
+    }
+}
+
+/// Renders an analysis as delimited rows
+pub fn analysis(analysis: &PdfAnalysis, delimiter: char, rows: Rows, header: bool) -> String {
+    let mut out = String::new();
+    match rows {
+        Rows::Files => {
+            if header {
+                row(&mut out, delimiter, FILE_COLUMNS);
+            }
+            row(&mut out, delimiter, file_row(analysis));
+        }
+        Rows::Findings => {
+            if header {
+                row(&mut out, delimiter, FINDING_COLUMNS);
+            }
+            let sha256 = analysis.metadata.sha256.clone().unwrap_or_default();
+            for finding in &analysis.findings {
+                let objects: Vec<String> = finding.objects.iter().map(|(n, g)| format!("{} {}", n, g)).collect();
+                row(
+                    &mut out,
+                    delimiter,
+                    [
+                        analysis.path.clone(),
+                        sha256.clone(),
+                        finding.id.clone(),
+                        finding.severity.to_string(),
+                        finding.confidence.to_string(),
+                        finding.category.to_string(),
+                        finding.title.clone(),
+                        objects.join(";"),
+                        finding.techniques.join(";"),
+                        finding.evidence.join(" | "),
+                    ],
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Renders the triage view as one delimited row
+pub fn scan(report: &ScanReport, delimiter: char, header: bool) -> String {
+    let mut out = String::new();
+    if header {
+        row(&mut out, delimiter, SCAN_COLUMNS);
+    }
+    row(
+        &mut out,
+        delimiter,
+        [
+            report.path.clone(),
+            report.decision.verdict.to_string(),
+            report.decision.severity.map(|s| s.to_string()).unwrap_or_default(),
+            format!("{:.2}", report.risk.score),
+            report.phishing.score.to_string(),
+            report.decision.reasons.join(";"),
+        ],
+    );
+    out
+}
+
+/// Renders extracted URIs, one row each
+pub fn uris(report: &UriReport, delimiter: char) -> String {
+    let mut out = String::new();
+    row(
+        &mut out,
+        delimiter,
+        ["uri", "host", "severity", "lookalike_of", "sources"],
+    );
+    for uri in &report.uris {
+        let sources: Vec<String> = uri
+            .sources
+            .iter()
+            .map(|(origin, id)| match id {
+                Some(id) => format!("{:?} {} {}", origin, id.0, id.1),
+                None => format!("{:?}", origin),
+            })
+            .collect();
+        row(
+            &mut out,
+            delimiter,
+            [
+                uri.decoded.clone(),
+                uri.host.clone().unwrap_or_default(),
+                uri.severity.to_string(),
+                uri.lookalike_of.clone().unwrap_or_default(),
+                sources.join(";"),
+            ],
+        );
+    }
+    out
+}
+
+/// Renders extracted text, one row per page
+pub fn text(pages: &[PageText], delimiter: char) -> String {
+    let mut out = String::new();
+    row(&mut out, delimiter, ["page", "text"]);
+    for page in pages {
+        row(&mut out, delimiter, [page.page_number.to_string(), page.text()]);
+    }
+    out
+}
+
+/// Renders a document history, one row per event
+pub fn timeline(timeline: &DocumentTimeline, delimiter: char) -> String {
+    let mut out = String::new();
+    row(
+        &mut out,
+        delimiter,
+        ["time", "source", "revision", "conflict", "description"],
+    );
+    for event in &timeline.events {
+        row(
+            &mut out,
+            delimiter,
+            [
+                event.time.to_rfc3339(),
+                format!("{:?}", event.source),
+                event.revision.map(|revision| revision.to_string()).unwrap_or_default(),
+                event.conflict.to_string(),
+                event.description.clone(),
+            ],
+        );
+    }
+    out
+}
+
+/// Renders an object listing, one row per object
+pub fn objects(entries: &[ObjectEntry], delimiter: char) -> String {
+    let mut out = String::new();
+    row(
+        &mut out,
+        delimiter,
+        ["object", "type", "size", "entropy", "revision", "filters"],
+    );
+    for entry in entries {
+        row(
+            &mut out,
+            delimiter,
+            [
+                format!("{} {}", entry.id.0, entry.id.1),
+                entry.object_type.to_string(),
+                entry.size.map(|size| size.to_string()).unwrap_or_default(),
+                entry.entropy.map(|entropy| format!("{:.4}", entropy)).unwrap_or_default(),
+                entry.revision.map(|revision| revision.to_string()).unwrap_or_default(),
+                entry.filters.join(";"),
+            ],
+        );
+    }
+    out
+}
+
+fn file_row(analysis: &PdfAnalysis) -> [String; 20] {
+    let count = |severity: Severity| {
+        analysis
+            .findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+            .to_string()
+    };
+    let uses = |feature: ReaderFeature| {
+        analysis
+            .reader_features
+            .features
+            .iter()
+            .any(|usage| usage.feature == feature)
+            .to_string()
+    };
+    let mut techniques: Vec<&str> = analysis
+        .findings
+        .iter()
+        .flat_map(|f| f.techniques.iter().map(String::as_str))
+        .collect();
+    techniques.sort_unstable();
+    techniques.dedup();
+
+    let summary = &analysis.summary;
+    [
+        analysis.path.clone(),
+        analysis.metadata.sha256.clone().unwrap_or_default(),
+        analysis.metadata.size.to_string(),
+        summary.pages.to_string(),
+        summary.revisions.to_string(),
+        summary.encryption.is_some().to_string(),
+        format!("{:.2}", summary.risk.score),
+        summary.highest_severity.map(|s| s.to_string()).unwrap_or_default(),
+        analysis.findings.len().to_string(),
+        count(Severity::Critical),
+        count(Severity::High),
+        count(Severity::Medium),
+        count(Severity::Low),
+        uses(ReaderFeature::JavaScript),
+        uses(ReaderFeature::Launch),
+        analysis.attachments.files.len().to_string(),
+        analysis.embedded.len().to_string(),
+        analysis.uris.uris.len().to_string(),
+        analysis.phishing.score.to_string(),
+        techniques.join(";"),
+    ]
+}
+
+/// Appends a row; rows are separated, not terminated, by newlines so the
+/// output can be printed like the other formats
+fn row<S: AsRef<str>>(out: &mut String, delimiter: char, fields: impl IntoIterator<Item = S>) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    let fields: Vec<String> = fields.into_iter().map(|f| field(f.as_ref(), delimiter)).collect();
+    out.push_str(&fields.join(&delimiter.to_string()));
+}
+
+/// Escapes one field: CSV quoting, or whitespace folding for TSV, after
+/// prefixing formula triggers with an apostrophe
+fn field(value: &str, delimiter: char) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) && value.parse::<f64>().is_err() {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if delimiter == '\t' {
+        return value.replace(['\t', '\r', '\n'], " ");
+    }
+    if value.contains([delimiter, '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_escaping() {
+        assert_eq!(field("plain", ','), "plain");
+        assert_eq!(field("a,b \"c\"", ','), "\"a,b \"\"c\"\"\"");
+        assert_eq!(field("=HYPERLINK(\"x\")", '\t'), "'=HYPERLINK(\"x\")");
+        assert_eq!(field("-1.5", ','), "-1.5");
+        assert_eq!(field("line\tone\ntwo", '\t'), "line one two");
+    }
+
+    #[tokio::test]
+    async fn test_file_and_finding_rows() {
+        use crate::{Analyzer, PdfAnalyzer};
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let result = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+
+        let files = analysis(&result, ',', Rows::Files, true);
+        let lines: Vec<&str> = files.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].split(',').count(), FILE_COLUMNS.len());
+        assert!(analysis(&result, ',', Rows::Files, false).lines().count() == 1);
+
+        let findings = analysis(&result, '\t', Rows::Findings, true);
+        assert_eq!(findings.lines().count(), result.findings.len() + 1);
+        assert!(findings.starts_with("path\tsha256\tid"));
+    }
+}
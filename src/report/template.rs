@@ -0,0 +1,127 @@
+//! User-supplied report templates
+//! Created: 2026-10-16 10:44:38 UTC
+//! Author: kartik4091
+//!
+//! Renders an analysis with a Tera template so organizations can produce
+//! their own report layouts without post-processing JSON. The template
+//! receives the full model as `analysis` (the same fields as the JSON
+//! output), the findings ordered by severity as `findings`, the STIX
+//! `verdict`, and `pdx_version` and `generated` for footers. Templates whose
+//! file name ends in `.html`, `.htm` or `.xml` have every value escaped, so
+//! text from a hostile document cannot inject markup into the report.
+
+use std::path::Path;
+
+use chrono::Utc;
+use tera::{Context, Tera};
+
+use super::stix;
+use crate::{
+    detectors::Finding,
+    error::{Error, Result},
+    PdfAnalysis,
+};
+
+/// A loaded report template
+pub struct ReportTemplate {
+    tera: Tera,
+    name: String,
+}
+
+impl ReportTemplate {
+    /// Loads and compiles a template file
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "report".to_string());
+        Self::from_source(&name, &source)
+    }
+
+    /// Compiles a template; `name` decides escaping by its extension
+    pub fn from_source(name: &str, source: &str) -> Result<Self> {
+        let mut tera = Tera::default();
+        tera.add_raw_template(name, source).map_err(template_error)?;
+        Ok(Self {
+            tera,
+            name: name.to_string(),
+        })
+    }
+
+    /// Renders an analysis
+    pub fn render(&self, analysis: &PdfAnalysis) -> Result<String> {
+        let mut findings: Vec<&Finding> = analysis.findings.iter().collect();
+        findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.id.cmp(&b.id)));
+
+        let mut context = Context::new();
+        context.insert("analysis", analysis);
+        context.insert("findings", &findings);
+        context.insert("verdict", stix::verdict(analysis));
+        context.insert("pdx_version", env!("CARGO_PKG_VERSION"));
+        context.insert("generated", &Utc::now().to_rfc3339());
+        self.tera.render(&self.name, &context).map_err(template_error)
+    }
+}
+
+/// Tera reports the cause (line, missing variable) in the error source
+fn template_error(error: tera::Error) -> Error {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    Error::Analysis(format!("Template error: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        detectors::{RiskCategory, Severity},
+        Analyzer, PdfAnalyzer,
+    };
+
+    async fn analysis() -> PdfAnalysis {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), crate::document::fixtures::simple_pdf()).unwrap();
+        let mut analysis = PdfAnalyzer::new(temp.path()).unwrap().analyze().await.unwrap();
+        analysis.findings.push(Finding::new(
+            "actions.launch",
+            Severity::Critical,
+            RiskCategory::ActiveContent,
+            "<script>Launch</script>",
+        ));
+        analysis
+    }
+
+    #[tokio::test]
+    async fn test_render_with_escaping() {
+        let analysis = analysis().await;
+        let source = "{{ verdict }}:{% for f in findings %}{{ f.severity }} {{ f.title }};{% endfor %}";
+
+        let text = ReportTemplate::from_source("report.txt", source).unwrap();
+        let rendered = text.render(&analysis).unwrap();
+        assert!(rendered.starts_with(&format!(
+            "{}:Critical <script>Launch</script>;",
+            stix::verdict(&analysis)
+        )));
+
+        let html = ReportTemplate::from_source("report.html", source).unwrap();
+        assert!(html
+            .render(&analysis)
+            .unwrap()
+            .contains("&lt;script&gt;Launch&lt;&#x2F;script&gt;"));
+    }
+
+    #[tokio::test]
+    async fn test_template_errors() {
+        assert!(ReportTemplate::from_source("bad.txt", "{% for %}").is_err());
+
+        let missing = ReportTemplate::from_source("missing.txt", "{{ analysis.nonexistent }}").unwrap();
+        let error = missing.render(&analysis().await).unwrap_err().to_string();
+        assert!(error.contains("nonexistent"), "{}", error);
+    }
+}
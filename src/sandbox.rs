@@ -0,0 +1,626 @@
+//! Privilege-separated analysis in a locked-down child process
+//! Created: 2026-10-16 09:14:51 UTC
+//! Author: kartik4091
+//!
+//! Every byte the analyzer parses is attacker-controlled, and the decoders
+//! it relies on live in third-party crates. In sandbox mode the analysis
+//! runs in a re-executed copy of the binary that, before touching the
+//! document, caps its own resources with rlimits and (on Linux) drops all
+//! filesystem access with Landlock and every syscall beyond memory
+//! management, threads and its stdio pipes with seccomp. An exploited
+//! parser is left with a process that can only write a report to stdout.
+//!
+//! The parent writes a one-line JSON [`WorkerRequest`] followed by the
+//! document to the child's stdin and reads the JSON [`PdfAnalysis`] back
+//! from its stdout, no more than [`SandboxConfig::max_output`] bytes of it.
+
+use std::{
+    io::{Read, Write},
+    path::PathBuf,
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{debug, info, instrument};
+
+use crate::{
+    budget::AnalysisBudget,
+    detectors::{
+        custom_rules::{RuleDefinition, RuleSet},
+        pii::PiiConfig,
+    },
+    document::hashing::HashAlgorithm,
+    error::{Error, Result},
+    limits::ResourceLimits,
+    registry::DetectorRegistry,
+    AnalysisOptions, PdfAnalysis, PdfAnalyzer,
+};
+
+/// First argument that makes the binary act as a sandbox worker
+pub const WORKER_ARG: &str = "__sandbox-worker";
+
+/// Exit code of a worker that could not lock itself down
+pub const EXIT_LOCKDOWN_FAILED: i32 = 70;
+
+/// Exit code of a worker whose analysis failed
+pub const EXIT_ANALYSIS_FAILED: i32 = 65;
+
+/// Default cap on the report a worker may write back (256 MiB)
+pub const MAX_WORKER_OUTPUT: u64 = 256 * 1024 * 1024;
+
+/// Whether this platform can lock a worker down; elsewhere every sandboxed
+/// analysis fails
+pub const SUPPORTED: bool = cfg!(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")));
+
+/// Resource limits and worker location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxConfig {
+    /// Binary to re-execute; defaults to the current executable, which
+    /// must dispatch [`WORKER_ARG`] to [`worker_main`]
+    pub worker: Option<PathBuf>,
+
+    /// Address space limit in bytes
+    pub memory_limit: u64,
+
+    /// CPU time limit in seconds
+    pub cpu_seconds: u64,
+
+    /// Wall-clock limit, after which the worker is killed
+    pub timeout: Duration,
+
+    /// Largest report read back from the worker, in bytes; a worker that
+    /// writes more is killed and the analysis fails
+    pub max_output: u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            worker: None,
+            memory_limit: 2 << 30,
+            cpu_seconds: 120,
+            timeout: Duration::from_secs(300),
+            max_output: MAX_WORKER_OUTPUT,
+        }
+    }
+}
+
+/// Analysis settings passed to the worker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkerRequest {
+    /// Label used as the report path
+    pub path: String,
+
+    /// Address space limit in bytes
+    pub memory_limit: u64,
+
+    /// CPU time limit in seconds
+    pub cpu_seconds: u64,
+
+    /// See [`AnalysisOptions::diff_revisions`]
+    pub diff_revisions: bool,
+
+    /// See [`AnalysisOptions::simulate_viewers`]
+    pub simulate_viewers: bool,
+
+    /// See [`AnalysisOptions::budget`]
+    pub budget: Option<u64>,
+
+    /// See [`AnalysisOptions::revision`]
+    pub revision: Option<usize>,
+
+    /// See [`AnalysisOptions::hash`]
+    pub hash: HashAlgorithm,
+
+    /// See [`AnalysisOptions::limits`]
+    #[serde(default)]
+    pub limits: ResourceLimits,
+
+    /// See [`AnalysisOptions::embedded_depth`]
+    #[serde(default)]
+    pub embedded_depth: usize,
+
+    /// See [`AnalysisOptions::pii`]
+    #[serde(default)]
+    pub pii: Option<PiiConfig>,
+
+    /// Definitions of [`AnalysisOptions::rules`], recompiled by the worker
+    #[serde(default)]
+    pub rules: Vec<RuleDefinition>,
+
+    /// Detectors disabled in [`AnalysisOptions::detectors`]
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+impl WorkerRequest {
+    /// Request for analyzing under `options`. Side effects on the host
+    /// (trailing data dumps, media extraction, reputation lookups) are not
+    /// forwarded; the worker cannot perform them.
+    pub fn new(path: &str, options: &AnalysisOptions, config: &SandboxConfig) -> Self {
+        Self {
+            path: path.to_string(),
+            memory_limit: config.memory_limit,
+            cpu_seconds: config.cpu_seconds,
+            diff_revisions: options.diff_revisions,
+            simulate_viewers: options.simulate_viewers,
+            budget: options.budget.map(|b| b.max_cost),
+            revision: options.revision,
+            hash: options.hash,
+            limits: options.limits,
+            embedded_depth: options.embedded_depth,
+            pii: options.pii.clone(),
+            rules: options.rules.iter().flat_map(|rules| rules.definitions().cloned()).collect(),
+            disabled: options.detectors.disabled().map(String::from).collect(),
+        }
+    }
+
+    fn options(&self) -> AnalysisOptions {
+        let mut detectors = DetectorRegistry::default();
+        for name in &self.disabled {
+            // Registered detectors are not run by the worker, so only the
+            // built-in names are known here
+            let _ = detectors.disable(name);
+        }
+        AnalysisOptions {
+            diff_revisions: self.diff_revisions,
+            simulate_viewers: self.simulate_viewers,
+            budget: self.budget.map(AnalysisBudget::new),
+            revision: self.revision,
+            hash: self.hash,
+            limits: self.limits,
+            embedded_depth: self.embedded_depth,
+            pii: self.pii.clone(),
+            // Validated when the host loaded them
+            rules: match self.rules.as_slice() {
+                [] => None,
+                definitions => RuleSet::new(definitions.to_vec()).ok().map(Arc::new),
+            },
+            detectors,
+            ..Default::default()
+        }
+    }
+}
+
+/// Runs the analysis of `data` in a sandboxed worker process
+#[instrument(skip(data, options, config))]
+pub async fn analyze(path: &str, data: &[u8], options: &AnalysisOptions, config: &SandboxConfig) -> Result<PdfAnalysis> {
+    if !SUPPORTED {
+        return Err(Error::Analysis("Sandbox mode is only supported on Linux (x86_64 and aarch64)".into()));
+    }
+    let worker = match &config.worker {
+        Some(worker) => worker.clone(),
+        None => std::env::current_exe()?,
+    };
+    let request = WorkerRequest::new(path, options, config);
+
+    let mut child = tokio::process::Command::new(&worker)
+        .arg(WORKER_ARG)
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()?;
+    debug!("Spawned sandbox worker {} for {}", worker.display(), path);
+
+    let mut stdin = child.stdin.take().ok_or_else(|| Error::Analysis("Worker stdin unavailable".into()))?;
+    let mut stdout = child.stdout.take().ok_or_else(|| Error::Analysis("Worker stdout unavailable".into()))?;
+    let mut input = serde_json::to_vec(&request)?;
+    input.push(b'\n');
+    input.extend_from_slice(data);
+
+    let exchange = async {
+        // Feed stdin while draining stdout so neither pipe can fill up
+        let write = async {
+            stdin.write_all(&input).await?;
+            drop(stdin);
+            Ok::<_, std::io::Error>(())
+        };
+        // A compromised worker must not be able to fill host memory
+        let mut output = Vec::new();
+        let mut bounded = (&mut stdout).take(config.max_output.saturating_add(1));
+        let (written, read) = tokio::join!(write, bounded.read_to_end(&mut output));
+        if let Err(e) = written {
+            // A worker that died early closes stdin; its exit status says why
+            debug!("Writing to sandbox worker failed: {}", e);
+        }
+        read?;
+        if output.len() as u64 > config.max_output {
+            // Dropping the child kills it
+            return Err(Error::Analysis(format!(
+                "Sandboxed analysis of {} failed: worker output exceeds {} bytes",
+                path, config.max_output
+            )));
+        }
+        let status = child.wait().await?;
+        Ok::<_, Error>((status, output))
+    };
+
+    let (status, output) = match tokio::time::timeout(config.timeout, exchange).await {
+        Ok(result) => result?,
+        Err(_) => {
+            return Err(Error::Analysis(format!(
+                "Sandboxed analysis of {} exceeded {}s and was killed",
+                path,
+                config.timeout.as_secs()
+            )))
+        }
+    };
+
+    if !status.success() {
+        return Err(Error::Analysis(format!("Sandboxed analysis of {} failed: worker {}", path, status)));
+    }
+    info!("Sandboxed analysis of {} complete", path);
+    Ok(serde_json::from_slice(&output)?)
+}
+
+/// Entry point of the worker process; returns the exit code.
+///
+/// Must be called before any async runtime or worker threads are started
+/// by the host binary.
+pub fn worker_main() -> i32 {
+    let mut input = Vec::new();
+    if let Err(e) = std::io::stdin().lock().read_to_end(&mut input) {
+        eprintln!("sandbox worker: failed to read request: {}", e);
+        return EXIT_ANALYSIS_FAILED;
+    }
+    serve(&input, lockdown, &mut std::io::stdout().lock())
+}
+
+/// Answers one request read from stdin, locking down with `restrict` once
+/// everything that needs the filesystem is set up
+fn serve(input: &[u8], restrict: impl FnOnce(&WorkerRequest) -> Result<()>, output: &mut impl Write) -> i32 {
+    let Some(split) = input.iter().position(|&b| b == b'\n') else {
+        eprintln!("sandbox worker: malformed request");
+        return EXIT_ANALYSIS_FAILED;
+    };
+    let request: WorkerRequest = match serde_json::from_slice(&input[..split]) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("sandbox worker: malformed request: {}", e);
+            return EXIT_ANALYSIS_FAILED;
+        }
+    };
+    let data = &input[split + 1..];
+
+    // Everything that may touch the filesystem is set up before lockdown
+    let runtime = tokio::runtime::Builder::new_current_thread().build();
+    let analyzer = PdfAnalyzer::with_options(&request.path, request.options());
+    let (Ok(runtime), Ok(analyzer)) = (runtime, analyzer) else {
+        eprintln!("sandbox worker: failed to initialize");
+        return EXIT_ANALYSIS_FAILED;
+    };
+
+    if let Err(e) = restrict(&request) {
+        eprintln!("sandbox worker: lockdown failed: {}", e);
+        return EXIT_LOCKDOWN_FAILED;
+    }
+
+    let analysis = match runtime.block_on(analyzer.analyze_bytes(data)) {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            eprintln!("sandbox worker: analysis failed: {}", e);
+            return EXIT_ANALYSIS_FAILED;
+        }
+    };
+    match serde_json::to_writer(&mut *output, &analysis).map(|_| output.flush()) {
+        Ok(Ok(())) => 0,
+        _ => EXIT_ANALYSIS_FAILED,
+    }
+}
+
+/// Applies every available restriction to the current process
+fn lockdown(request: &WorkerRequest) -> Result<()> {
+    #[cfg(unix)]
+    limits::apply(request)?;
+    #[cfg(not(unix))]
+    let _ = request;
+
+    filter_syscalls()
+}
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn filter_syscalls() -> Result<()> {
+    linux::lockdown()
+}
+
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+fn filter_syscalls() -> Result<()> {
+    Err(Error::Analysis("Syscall filtering is not supported on this platform".into()))
+}
+
+#[cfg(unix)]
+mod limits {
+    use super::WorkerRequest;
+    use crate::error::{Error, Result};
+
+    pub fn apply(request: &WorkerRequest) -> Result<()> {
+        set(libc::RLIMIT_AS, request.memory_limit)?;
+        set(libc::RLIMIT_CPU, request.cpu_seconds)?;
+        // The worker never writes files or dumps core
+        set(libc::RLIMIT_FSIZE, 0)?;
+        set(libc::RLIMIT_CORE, 0)
+    }
+
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    type Resource = libc::__rlimit_resource_t;
+    #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+    type Resource = libc::c_int;
+
+    fn set(resource: Resource, value: u64) -> Result<()> {
+        let limit = libc::rlimit { rlim_cur: value as libc::rlim_t, rlim_max: value as libc::rlim_t };
+        // SAFETY: setrlimit only reads the struct passed by reference
+        if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod linux {
+    use tracing::warn;
+
+    use crate::error::{Error, Result};
+
+    /// BPF_LD | BPF_W | BPF_ABS
+    const BPF_LD_W_ABS: u16 = 0x20;
+    /// BPF_JMP | BPF_JEQ | BPF_K
+    const BPF_JMP_JEQ_K: u16 = 0x15;
+    /// BPF_JMP | BPF_JSET | BPF_K
+    const BPF_JMP_JSET_K: u16 = 0x45;
+    /// BPF_RET | BPF_K
+    const BPF_RET_K: u16 = 0x06;
+
+    const SECCOMP_SET_MODE_FILTER: libc::c_long = 1;
+    const SECCOMP_FILTER_FLAG_TSYNC: libc::c_long = 1;
+    const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+    const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+    const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+
+    /// Offsets into `struct seccomp_data`
+    const DATA_NR: u32 = 0;
+    const DATA_ARCH: u32 = 4;
+    const DATA_ARG0: u32 = 16;
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xC000_003E;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xC000_00B7;
+
+    /// Landlock syscalls share one number on every architecture
+    const SYS_LANDLOCK_CREATE_RULESET: libc::c_long = 444;
+    const SYS_LANDLOCK_RESTRICT_SELF: libc::c_long = 446;
+
+    /// Every filesystem access right of Landlock ABI 1
+    const LANDLOCK_ACCESS_FS_ALL: u64 = (1 << 13) - 1;
+
+    /// Syscalls the analysis needs once its input is in memory
+    const ALLOWED: &[libc::c_long] = &[
+        libc::SYS_read,
+        libc::SYS_write,
+        libc::SYS_writev,
+        libc::SYS_close,
+        libc::SYS_exit,
+        libc::SYS_exit_group,
+        libc::SYS_brk,
+        libc::SYS_mmap,
+        libc::SYS_munmap,
+        libc::SYS_mremap,
+        libc::SYS_mprotect,
+        libc::SYS_madvise,
+        libc::SYS_futex,
+        libc::SYS_rt_sigreturn,
+        libc::SYS_rt_sigprocmask,
+        libc::SYS_rt_sigaction,
+        libc::SYS_sigaltstack,
+        libc::SYS_clock_gettime,
+        libc::SYS_clock_nanosleep,
+        libc::SYS_getrandom,
+        libc::SYS_sched_yield,
+        libc::SYS_sched_getaffinity,
+        libc::SYS_gettid,
+        libc::SYS_getpid,
+        libc::SYS_set_robust_list,
+        libc::SYS_rseq,
+        libc::SYS_prctl,
+    ];
+
+    fn last_error() -> Error {
+        Error::Io(std::io::Error::last_os_error())
+    }
+
+    pub fn lockdown() -> Result<()> {
+        no_new_privs()?;
+        if let Err(e) = landlock() {
+            // Landlock needs Linux 5.13; seccomp still denies opening files
+            warn!("Landlock unavailable, continuing with seccomp only: {}", e);
+        }
+        seccomp()
+    }
+
+    fn no_new_privs() -> Result<()> {
+        // SAFETY: plain prctl without pointer arguments
+        if unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } != 0 {
+            return Err(last_error());
+        }
+        Ok(())
+    }
+
+    /// Denies every filesystem access to the process
+    fn landlock() -> Result<()> {
+        #[repr(C)]
+        struct RulesetAttr {
+            handled_access_fs: u64,
+        }
+        let attr = RulesetAttr { handled_access_fs: LANDLOCK_ACCESS_FS_ALL };
+
+        // SAFETY: the attribute struct outlives the call and its size is passed
+        let fd = unsafe {
+            libc::syscall(SYS_LANDLOCK_CREATE_RULESET, &attr as *const RulesetAttr, std::mem::size_of::<RulesetAttr>(), 0)
+        };
+        if fd < 0 {
+            return Err(last_error());
+        }
+        // SAFETY: fd is the ruleset created above and is closed exactly once
+        let restricted = unsafe { libc::syscall(SYS_LANDLOCK_RESTRICT_SELF, fd, 0) };
+        unsafe { libc::close(fd as libc::c_int) };
+        if restricted != 0 {
+            return Err(last_error());
+        }
+        Ok(())
+    }
+
+    /// Installs the syscall allowlist on every thread of the process
+    fn seccomp() -> Result<()> {
+        let mut program = filter();
+        let fprog = libc::sock_fprog { len: program.len() as libc::c_ushort, filter: program.as_mut_ptr() };
+
+        // SAFETY: the program outlives the call; the kernel copies it
+        let installed = unsafe {
+            libc::syscall(
+                libc::SYS_seccomp,
+                SECCOMP_SET_MODE_FILTER,
+                SECCOMP_FILTER_FLAG_TSYNC,
+                &fprog as *const libc::sock_fprog,
+            )
+        };
+        if installed != 0 {
+            return Err(last_error());
+        }
+        Ok(())
+    }
+
+    fn stmt(code: u16, k: u32) -> libc::sock_filter {
+        libc::sock_filter { code, jt: 0, jf: 0, k }
+    }
+
+    fn jump(code: u16, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+        libc::sock_filter { code, jt, jf, k }
+    }
+
+    /// BPF program: foreign architectures are killed, thread-creating
+    /// `clone` and the allowlist pass, everything else fails with EPERM
+    pub(super) fn filter() -> Vec<libc::sock_filter> {
+        let deny = SECCOMP_RET_ERRNO | libc::EPERM as u32;
+        let mut program = vec![
+            stmt(BPF_LD_W_ABS, DATA_ARCH),
+            jump(BPF_JMP_JEQ_K, AUDIT_ARCH, 1, 0),
+            stmt(BPF_RET_K, SECCOMP_RET_KILL_PROCESS),
+            stmt(BPF_LD_W_ABS, DATA_NR),
+            // clone is only allowed for threads, never for new processes
+            jump(BPF_JMP_JEQ_K, libc::SYS_clone as u32, 0, 4),
+            stmt(BPF_LD_W_ABS, DATA_ARG0),
+            jump(BPF_JMP_JSET_K, libc::CLONE_THREAD as u32, 0, 1),
+            stmt(BPF_RET_K, SECCOMP_RET_ALLOW),
+            stmt(BPF_RET_K, deny),
+            // clone3 hides its flags in memory; ENOSYS makes libc fall back to clone
+            jump(BPF_JMP_JEQ_K, libc::SYS_clone3 as u32, 0, 1),
+            stmt(BPF_RET_K, SECCOMP_RET_ERRNO | libc::ENOSYS as u32),
+        ];
+        for &nr in ALLOWED {
+            program.push(jump(BPF_JMP_JEQ_K, nr as u32, 0, 1));
+            program.push(stmt(BPF_RET_K, SECCOMP_RET_ALLOW));
+        }
+        program.push(stmt(BPF_RET_K, deny));
+        program
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trip() {
+        let options = AnalysisOptions {
+            diff_revisions: true,
+            budget: Some(AnalysisBudget::new(500)),
+            dump_trailing: Some(PathBuf::from("/tmp/tail.bin")),
+            hash: HashAlgorithm::Blake3,
+            limits: ResourceLimits { max_objects: 10, ..Default::default() },
+            ..Default::default()
+        };
+        let request = WorkerRequest::new("upload.pdf", &options, &SandboxConfig::default());
+        let decoded: WorkerRequest = serde_json::from_slice(&serde_json::to_vec(&request).unwrap()).unwrap();
+        assert_eq!(decoded, request);
+
+        let forwarded = decoded.options();
+        assert!(forwarded.diff_revisions);
+        assert_eq!(forwarded.budget, Some(AnalysisBudget::new(500)));
+        assert_eq!(forwarded.hash, HashAlgorithm::Blake3);
+        assert_eq!(forwarded.limits.max_objects, 10);
+        // Host side effects stay with the parent
+        assert!(forwarded.dump_trailing.is_none());
+    }
+
+    #[test]
+    fn test_worker_serves_request() {
+        let request = WorkerRequest::new("upload.pdf", &AnalysisOptions::default(), &SandboxConfig::default());
+        let mut input = serde_json::to_vec(&request).unwrap();
+        input.push(b'\n');
+        let data = crate::document::fixtures::simple_pdf();
+        input.extend_from_slice(&data);
+
+        let mut output = Vec::new();
+        assert_eq!(serve(&input, |_| Ok(()), &mut output), 0);
+        let analysis: PdfAnalysis = serde_json::from_slice(&output).unwrap();
+        assert_eq!(analysis.metadata.sha256, Some(crate::document::sha256_hex(&data)));
+
+        let mut output = Vec::new();
+        let refused = serve(&input, |_| Err(Error::Analysis("no seccomp".into())), &mut output);
+        assert_eq!(refused, EXIT_LOCKDOWN_FAILED);
+        assert!(output.is_empty());
+        assert_eq!(serve(b"{}", |_| Ok(()), &mut output), EXIT_ANALYSIS_FAILED);
+    }
+
+    #[test]
+    fn test_worker_skips_disabled_detectors() {
+        let mut detectors = DetectorRegistry::default();
+        detectors.disable("header").unwrap();
+        let options = AnalysisOptions { detectors, ..Default::default() };
+        let request = WorkerRequest::new("upload.pdf", &options, &SandboxConfig::default());
+        assert_eq!(request.disabled, vec!["header".to_string()]);
+
+        let mut input = serde_json::to_vec(&request).unwrap();
+        input.push(b'\n');
+        input.extend_from_slice(&crate::document::fixtures::simple_pdf());
+        let mut output = Vec::new();
+        assert_eq!(serve(&input, |_| Ok(()), &mut output), 0);
+        let analysis: PdfAnalysis = serde_json::from_slice(&output).unwrap();
+        assert!(analysis.header.version.is_none());
+    }
+
+    #[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    #[tokio::test]
+    async fn test_oversized_worker_output_fails() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let worker = dir.path().join("worker.sh");
+        std::fs::write(&worker, "#!/bin/sh\nwhile :; do printf 0123456789; done\n").unwrap();
+        std::fs::set_permissions(&worker, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let config = SandboxConfig { worker: Some(worker), max_output: 4096, ..Default::default() };
+        let data = crate::document::fixtures::simple_pdf();
+        let err = analyze("upload.pdf", &data, &AnalysisOptions::default(), &config).await.unwrap_err();
+        assert!(err.to_string().contains("worker output exceeds 4096 bytes"), "{}", err);
+    }
+
+    #[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    #[test]
+    fn test_filter_layout() {
+        let program = linux::filter();
+        // Architecture check first, default deny last
+        assert_eq!(program[0].k, 4);
+        assert_eq!(program.last().unwrap().k & 0xffff_0000, 0x0005_0000);
+        // Every conditional jump lands inside the program
+        for (i, insn) in program.iter().enumerate() {
+            assert!(i + 1 + (insn.jt.max(insn.jf) as usize) < program.len());
+        }
+    }
+}
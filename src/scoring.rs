@@ -0,0 +1,293 @@
+//! Weighted document risk scoring
+//! Created: 2026-10-16 10:12:01 UTC
+//! Author: kartik4091
+//!
+//! Turns the findings of an analysis into a risk score between 0.0 and
+//! 1.0, with a breakdown per [`RiskCategory`]. Each finding earns points
+//! by severity; a category's points saturate towards 1.0
+//! (`1 - e^(-points / scale)`), so one high finding weighs a lot and the
+//! twentieth low one barely matters. Category scores are scaled by the
+//! category weight and combined like independent probabilities, which
+//! keeps the total within 0.0–1.0 and lets any single category drive it
+//! up.
+//!
+//! This is the document's only risk score: the analyzer scores its
+//! findings once with the model in [`crate::AnalysisOptions::scoring`] and stores
+//! the result in the summary, where reports and scans read it.
+//!
+//! All weights can be overridden from a TOML file:
+//!
+//! ```toml
+//! scale = 10.0
+//!
+//! [severity]
+//! high = 12.0
+//!
+//! [categories]
+//! structure = 0.3
+//! ```
+
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    detectors::{Finding, RiskCategory, Severity},
+    error::{Error, Result},
+    PdfAnalysis,
+};
+
+/// Points a finding earns by severity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SeverityPoints {
+    /// Informational findings
+    pub info: f64,
+
+    /// Low severity findings
+    pub low: f64,
+
+    /// Medium severity findings
+    pub medium: f64,
+
+    /// High severity findings
+    pub high: f64,
+
+    /// Critical findings
+    pub critical: f64,
+}
+
+impl Default for SeverityPoints {
+    fn default() -> Self {
+        Self { info: 0.0, low: 1.0, medium: 4.0, high: 10.0, critical: 25.0 }
+    }
+}
+
+impl SeverityPoints {
+    /// Points for one finding of the given severity
+    pub fn of(&self, severity: Severity) -> f64 {
+        match severity {
+            Severity::Info => self.info,
+            Severity::Low => self.low,
+            Severity::Medium => self.medium,
+            Severity::High => self.high,
+            Severity::Critical => self.critical,
+        }
+    }
+}
+
+/// How much each category can contribute to the total, from 0.0 to 1.0
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CategoryWeights {
+    /// See [`RiskCategory::Structure`]
+    pub structure: f64,
+
+    /// See [`RiskCategory::Concealment`]
+    pub concealment: f64,
+
+    /// See [`RiskCategory::ActiveContent`]
+    pub active_content: f64,
+
+    /// See [`RiskCategory::Phishing`]
+    pub phishing: f64,
+
+    /// See [`RiskCategory::DataExposure`]
+    pub data_exposure: f64,
+
+    /// See [`RiskCategory::Custom`]
+    pub custom: f64,
+}
+
+impl Default for CategoryWeights {
+    fn default() -> Self {
+        Self {
+            structure: 0.5,
+            concealment: 0.8,
+            active_content: 1.0,
+            phishing: 0.9,
+            data_exposure: 0.6,
+            custom: 0.8,
+        }
+    }
+}
+
+impl CategoryWeights {
+    /// Weight of the given category
+    pub fn of(&self, category: RiskCategory) -> f64 {
+        match category {
+            RiskCategory::Structure => self.structure,
+            RiskCategory::Concealment => self.concealment,
+            RiskCategory::ActiveContent => self.active_content,
+            RiskCategory::Phishing => self.phishing,
+            RiskCategory::DataExposure => self.data_exposure,
+            RiskCategory::Custom => self.custom,
+        }
+    }
+
+    fn all(&self) -> [f64; 6] {
+        [self.structure, self.concealment, self.active_content, self.phishing, self.data_exposure, self.custom]
+    }
+}
+
+/// Scoring weights
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoringModel {
+    /// Points per finding, by severity
+    pub severity: SeverityPoints,
+
+    /// Contribution of each category
+    pub categories: CategoryWeights,
+
+    /// Points at which a category reaches about 0.63
+    pub scale: f64,
+}
+
+impl Default for ScoringModel {
+    fn default() -> Self {
+        Self {
+            severity: SeverityPoints::default(),
+            categories: CategoryWeights::default(),
+            scale: 10.0,
+        }
+    }
+}
+
+/// Score of one category
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CategoryScore {
+    /// Category
+    pub category: RiskCategory,
+
+    /// Category score from 0.0 to 1.0, before weighting
+    pub score: f64,
+
+    /// Points earned by the category's findings
+    pub points: f64,
+
+    /// Number of findings in the category
+    pub findings: usize,
+}
+
+/// Normalized document risk
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RiskScore {
+    /// Overall score from 0.0 (nothing found) to 1.0
+    pub score: f64,
+
+    /// Categories with at least one finding
+    pub categories: Vec<CategoryScore>,
+}
+
+impl ScoringModel {
+    /// Reads weight overrides from a TOML file; omitted weights keep their
+    /// defaults
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let model: Self = toml::from_str(&text)
+            .map_err(|e| Error::Analysis(format!("Scoring config {}: {}", path.display(), e)))?;
+        model.validate()?;
+        Ok(model)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.scale.is_nan() || self.scale <= 0.0 {
+            return Err(Error::Analysis("Scoring scale must be positive".into()));
+        }
+        if self.categories.all().iter().any(|w| !(0.0..=1.0).contains(w)) {
+            return Err(Error::Analysis("Category weights must be between 0.0 and 1.0".into()));
+        }
+        let points = [
+            self.severity.info,
+            self.severity.low,
+            self.severity.medium,
+            self.severity.high,
+            self.severity.critical,
+        ];
+        if points.iter().any(|p| p.is_nan() || *p < 0.0) {
+            return Err(Error::Analysis("Severity points must not be negative".into()));
+        }
+        Ok(())
+    }
+
+    /// Scores the collected findings of an analysis, including embedded
+    /// documents
+    pub fn score(&self, analysis: &PdfAnalysis) -> RiskScore {
+        self.score_findings(&analysis.findings)
+    }
+
+    /// Scores a list of findings
+    pub fn score_findings(&self, findings: &[Finding]) -> RiskScore {
+        let indicators: Vec<_> = findings.iter().map(|finding| (finding.category, finding.severity)).collect();
+        self.score_indicators(&indicators)
+    }
+
+    /// Scores categorized finding severities
+    pub fn score_indicators(&self, indicators: &[(RiskCategory, Severity)]) -> RiskScore {
+        let mut categories: Vec<CategoryScore> = Vec::new();
+        for &(category, severity) in indicators {
+            let index = match categories.iter().position(|c| c.category == category) {
+                Some(index) => index,
+                None => {
+                    categories.push(CategoryScore { category, score: 0.0, points: 0.0, findings: 0 });
+                    categories.len() - 1
+                }
+            };
+            categories[index].points += self.severity.of(severity);
+            categories[index].findings += 1;
+        }
+        categories.sort_by_key(|c| c.category);
+
+        let mut clean = 1.0;
+        for category in &mut categories {
+            category.score = 1.0 - (-category.points / self.scale).exp();
+            clean *= 1.0 - self.categories.of(category.category) * category.score;
+        }
+        RiskScore { score: 1.0 - clean, categories }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_findings_scores_zero() {
+        let risk = ScoringModel::default().score_indicators(&[]);
+        assert_eq!(risk.score, 0.0);
+        assert!(risk.categories.is_empty());
+
+        let info = ScoringModel::default().score_indicators(&[(RiskCategory::Structure, Severity::Info)]);
+        assert_eq!(info.score, 0.0);
+        assert_eq!(info.categories[0].findings, 1);
+    }
+
+    #[test]
+    fn test_scores_saturate_and_combine() {
+        let model = ScoringModel::default();
+        let one = model.score_indicators(&[(RiskCategory::ActiveContent, Severity::High)]);
+        assert!((one.score - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+
+        let many = vec![(RiskCategory::ActiveContent, Severity::Critical); 50];
+        assert!(model.score_indicators(&many).score <= 1.0);
+
+        let two = model.score_indicators(&[
+            (RiskCategory::ActiveContent, Severity::High),
+            (RiskCategory::Structure, Severity::High),
+        ]);
+        assert!(two.score > one.score);
+        assert_eq!(two.categories.len(), 2);
+    }
+
+    #[test]
+    fn test_weights_are_overridable() {
+        let model: ScoringModel = toml::from_str("[categories]\nstructure = 0.0\n").unwrap();
+        assert_eq!(model.severity, SeverityPoints::default());
+        assert_eq!(model.score_indicators(&[(RiskCategory::Structure, Severity::Critical)]).score, 0.0);
+
+        let invalid = ScoringModel { scale: 0.0, ..Default::default() };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! One-screen document overview
+//! Created: 2026-10-16 09:12:36 UTC
+//! Author: kartik4091
+//!
+//! Dashboards and the text report want a handful of headline numbers
+//! without walking every detector's output: what the file is made of, how
+//! often it was revised, what its pages look like, how it is encrypted and
+//! how worried to be about it.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    detectors::{Finding, Severity},
+    document::{name_value, number, resolve, revisions::split_revisions, stream_data, taxonomy::ObjectType},
+    scoring::{RiskScore, ScoringModel},
+};
+
+/// Named paper sizes in points (portrait)
+const PAPER_SIZES: [(&str, u32, u32); 5] = [
+    ("Letter", 612, 792),
+    ("Legal", 612, 1008),
+    ("A3", 842, 1191),
+    ("A4", 595, 842),
+    ("A5", 420, 595),
+];
+
+/// Headline statistics for an analyzed document
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentSummary {
+    /// Indirect objects by type, keyed by [`ObjectType::kind`]
+    pub objects_by_type: BTreeMap<String, usize>,
+
+    /// Total indirect objects
+    pub object_count: usize,
+
+    /// Stream objects
+    pub streams: usize,
+
+    /// Stream data as stored in the file
+    pub stream_bytes_raw: u64,
+
+    /// Stream data after decoding (streams that fail to decode count as raw)
+    pub stream_bytes_decoded: u64,
+
+    /// Revisions (original plus incremental updates)
+    pub revisions: usize,
+
+    /// Pages in the page tree
+    pub pages: usize,
+
+    /// Distinct page sizes, most common first
+    pub page_sizes: Vec<PageSize>,
+
+    /// Encryption dictionary summary, if the file is encrypted
+    pub encryption: Option<EncryptionSummary>,
+
+    /// Risk from 0.0 (nothing found) to 1.0, with its per-category breakdown
+    #[serde(default)]
+    pub risk: RiskScore,
+
+    /// Highest finding severity
+    pub highest_severity: Option<Severity>,
+}
+
+/// Pages sharing one media box size
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PageSize {
+    /// Width in points, rounded
+    pub width: u32,
+
+    /// Height in points, rounded
+    pub height: u32,
+
+    /// Paper name, if the size matches a standard one in either orientation
+    pub name: Option<String>,
+
+    /// Number of pages with this size
+    pub pages: usize,
+}
+
+/// Encryption settings from the trailer's /Encrypt dictionary
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct EncryptionSummary {
+    /// Security handler (/Filter)
+    pub filter: Option<String>,
+
+    /// Algorithm version (/V)
+    pub version: Option<i64>,
+
+    /// Handler revision (/R)
+    pub revision: Option<i64>,
+
+    /// Key length in bits (/Length)
+    pub key_length: Option<i64>,
+
+    /// Cipher, from the default crypt filter or implied by /V
+    pub method: Option<String>,
+}
+
+impl DocumentSummary {
+    /// Sets the risk and highest severity from the analysis findings
+    pub fn score(&mut self, findings: &[Finding], model: &ScoringModel) {
+        self.risk = model.score_findings(findings);
+        self.highest_severity = findings.iter().map(|finding| finding.severity).max();
+    }
+}
+
+/// Builds the summary; object, page and encryption statistics need the
+/// loaded document and are left empty without it. The risk score is set
+/// separately by [`DocumentSummary::score`] once all findings are known.
+pub fn summarize(data: &[u8], doc: Option<&Document>) -> DocumentSummary {
+    let mut summary = DocumentSummary {
+        revisions: split_revisions(data).len(),
+        ..Default::default()
+    };
+    let Some(doc) = doc else {
+        return summary;
+    };
+
+    summary.object_count = doc.objects.len();
+    for object in doc.objects.values() {
+        *summary.objects_by_type.entry(ObjectType::of(object).kind().to_string()).or_default() += 1;
+        if let Object::Stream(stream) = object {
+            summary.streams += 1;
+            summary.stream_bytes_raw += stream.content.len() as u64;
+            summary.stream_bytes_decoded += stream_data(stream).map_or(stream.content.len(), |d| d.len()) as u64;
+        }
+    }
+
+    let pages = doc.get_pages();
+    summary.pages = pages.len();
+    let mut sizes: BTreeMap<(u32, u32), usize> = BTreeMap::new();
+    for &id in pages.values() {
+        if let Some(size) = media_box_size(doc, id) {
+            *sizes.entry(size).or_default() += 1;
+        }
+    }
+    summary.page_sizes = sizes
+        .into_iter()
+        .map(|((width, height), pages)| PageSize { width, height, name: paper_name(width, height), pages })
+        .collect();
+    summary.page_sizes.sort_by(|a, b| b.pages.cmp(&a.pages));
+
+    summary.encryption = encryption(doc);
+    summary
+}
+
+/// Rounded media box size, inherited through the page tree
+fn media_box_size(doc: &Document, page: ObjectId) -> Option<(u32, u32)> {
+    let mut node = doc.get_dictionary(page).ok()?;
+    // Bounded to guard against /Parent loops
+    for _ in 0..64 {
+        if let Ok(media_box) = node.get(b"MediaBox") {
+            let coords: Vec<f64> = resolve(doc, media_box)
+                .as_array()
+                .ok()?
+                .iter()
+                .filter_map(|c| number(resolve(doc, c)))
+                .collect();
+            let [x0, y0, x1, y1] = coords[..] else {
+                return None;
+            };
+            return Some(((x1 - x0).abs().round() as u32, (y1 - y0).abs().round() as u32));
+        }
+        node = doc.get_dictionary(node.get(b"Parent").ok()?.as_reference().ok()?).ok()?;
+    }
+    None
+}
+
+fn paper_name(width: u32, height: u32) -> Option<String> {
+    let close = |a: u32, b: u32| a.abs_diff(b) <= 1;
+    PAPER_SIZES
+        .iter()
+        .find(|(_, w, h)| (close(width, *w) && close(height, *h)) || (close(width, *h) && close(height, *w)))
+        .map(|(name, _, _)| name.to_string())
+}
+
+fn encryption(doc: &Document) -> Option<EncryptionSummary> {
+    let encrypt = doc.trailer.get(b"Encrypt").ok()?;
+    let Some(dict) = resolve(doc, encrypt).as_dict().ok() else {
+        // Referenced but unreadable: still encrypted, details unknown
+        return Some(EncryptionSummary::default());
+    };
+
+    let name = |dict: &Dictionary, key: &[u8]| name_value(dict, key).map(|n| String::from_utf8_lossy(n).into_owned());
+    let integer = |key: &[u8]| dict.get(key).ok().and_then(|v| v.as_i64().ok());
+    let version = integer(b"V");
+
+    let crypt_filter = name_value(dict, b"StmF")
+        .and_then(|cf| dict.get(b"CF").ok()?.as_dict().ok()?.get(cf).ok()?.as_dict().ok())
+        .and_then(|cf| name(cf, b"CFM"));
+    let method = crypt_filter.or_else(|| match version {
+        Some(1..=3) => Some("RC4".to_string()),
+        _ => None,
+    });
+
+    Some(EncryptionSummary {
+        filter: name(dict, b"Filter"),
+        version,
+        revision: integer(b"R"),
+        key_length: integer(b"Length"),
+        method,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{detectors::RiskCategory, document::fixtures, scoring::CategoryWeights};
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_summarize_simple_document() {
+        let data = fixtures::simple_pdf();
+        let doc = Document::load_mem(&data).unwrap();
+        let summary = summarize(&data, Some(&doc));
+
+        assert_eq!(summary.object_count, 6);
+        assert_eq!(summary.objects_by_type["Page"], 1);
+        assert_eq!(summary.objects_by_type["Font"], 1);
+        assert_eq!(summary.streams, 1);
+        assert_eq!(summary.revisions, 1);
+        assert_eq!(summary.pages, 1);
+        assert_eq!(
+            summary.page_sizes,
+            vec![PageSize { width: 595, height: 842, name: Some("A4".into()), pages: 1 }]
+        );
+        assert!(summary.encryption.is_none());
+    }
+
+    #[test]
+    fn test_encryption_summary() {
+        let mut doc = fixtures::document();
+        let encrypt = doc.add_object(dictionary! {
+            "Filter" => "Standard",
+            "V" => 4,
+            "R" => 4,
+            "Length" => 128,
+            "StmF" => "StdCF",
+            "CF" => dictionary! { "StdCF" => dictionary! { "CFM" => "AESV2" } },
+        });
+        doc.trailer.set("Encrypt", encrypt);
+
+        let encryption = encryption(&doc).unwrap();
+        assert_eq!(encryption.filter.as_deref(), Some("Standard"));
+        assert_eq!(encryption.key_length, Some(128));
+        assert_eq!(encryption.method.as_deref(), Some("AESV2"));
+    }
+
+    #[test]
+    fn test_risk_follows_the_scoring_model() {
+        let mut summary = DocumentSummary::default();
+        let finding = |severity| Finding::new("test.finding", severity, RiskCategory::Structure, "finding");
+        let findings = [finding(Severity::Low), finding(Severity::Medium)];
+        let model = ScoringModel::default();
+        summary.score(&findings, &model);
+        assert_eq!(summary.risk, model.score_findings(&findings));
+        assert!(summary.risk.score > 0.0 && summary.risk.score < 1.0);
+        assert_eq!(summary.highest_severity, Some(Severity::Medium));
+
+        let critical = finding(Severity::Critical);
+        summary.score(&[critical.clone(), critical.clone(), critical], &model);
+        assert!(summary.risk.score <= 1.0);
+
+        let ignored = ScoringModel { categories: CategoryWeights { structure: 0.0, ..Default::default() }, ..model };
+        summary.score(&findings, &ignored);
+        assert_eq!(summary.risk.score, 0.0);
+    }
+}
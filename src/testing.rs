@@ -0,0 +1,338 @@
+//! Golden-corpus regression harness
+//! Created: 2026-10-16 09:10:33 UTC
+//! Author: kartik4091
+//!
+//! Detection content drifts as detectors and policies change. This module
+//! runs the full detector suite over a directory of fixture PDFs and
+//! compares each file's findings with an expected-findings snapshot stored
+//! next to it (`sample.pdf` → `sample.expected.json`), so a change that
+//! loses or adds a detection shows up as a failing case.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument};
+
+use crate::{
+    detectors::Severity,
+    error::{Error, Result},
+    integrations::policy::indicators,
+    AnalysisOptions, Analyzer, PdfAnalysis, PdfAnalyzer,
+};
+
+/// Suffix replacing `.pdf` in snapshot file names
+pub const SNAPSHOT_SUFFIX: &str = "expected.json";
+
+/// A finding as recorded in a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ExpectedFinding {
+    /// Finding severity
+    pub severity: Severity,
+
+    /// Finding description
+    pub description: String,
+}
+
+/// Expected findings for one fixture
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Findings, sorted by severity then description
+    pub findings: Vec<ExpectedFinding>,
+}
+
+/// Outcome of one fixture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaseStatus {
+    /// Findings match the snapshot
+    Passed,
+
+    /// Findings differ from the snapshot
+    Failed,
+
+    /// No snapshot exists for the fixture
+    MissingSnapshot,
+
+    /// The snapshot was (re)written from the current findings
+    Updated,
+
+    /// The fixture could not be analyzed, or its snapshot not read
+    Error,
+}
+
+/// Result for one fixture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResult {
+    /// Fixture PDF
+    pub fixture: PathBuf,
+
+    /// Snapshot compared against
+    pub snapshot: PathBuf,
+
+    /// Outcome
+    pub status: CaseStatus,
+
+    /// Expected findings that were not produced
+    pub missing: Vec<ExpectedFinding>,
+
+    /// Produced findings that were not expected
+    pub unexpected: Vec<ExpectedFinding>,
+
+    /// Analysis error, for [`CaseStatus::Error`]
+    pub error: Option<String>,
+}
+
+/// Results for a whole corpus
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CorpusReport {
+    /// Corpus directory
+    pub corpus: PathBuf,
+
+    /// Per-fixture results, in path order
+    pub cases: Vec<CaseResult>,
+}
+
+impl CorpusReport {
+    /// Number of cases with the given status
+    pub fn count(&self, status: CaseStatus) -> usize {
+        self.cases.iter().filter(|c| c.status == status).count()
+    }
+
+    /// Whether every fixture matched (or had its snapshot updated)
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|c| matches!(c.status, CaseStatus::Passed | CaseStatus::Updated))
+    }
+}
+
+/// Regression run over a fixture directory
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    dir: PathBuf,
+    options: AnalysisOptions,
+    update: bool,
+}
+
+impl Corpus {
+    /// Corpus rooted at `dir`; fixtures are searched recursively
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self { dir: dir.as_ref().to_path_buf(), options: AnalysisOptions::default(), update: false }
+    }
+
+    /// Analysis options used for every fixture
+    pub fn options(mut self, options: AnalysisOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Rewrite snapshots from the current findings instead of comparing
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Analyzes every fixture and compares it with its snapshot
+    #[instrument(skip(self), fields(corpus = %self.dir.display()))]
+    pub async fn run(&self) -> Result<CorpusReport> {
+        let fixtures = find_fixtures(&self.dir).await?;
+        info!("Running {} corpus fixtures", fixtures.len());
+
+        let mut report = CorpusReport { corpus: self.dir.clone(), cases: Vec::new() };
+        for fixture in fixtures {
+            report.cases.push(self.run_case(fixture).await?);
+        }
+        Ok(report)
+    }
+
+    async fn run_case(&self, fixture: PathBuf) -> Result<CaseResult> {
+        let snapshot = snapshot_path(&fixture);
+        let mut case = CaseResult {
+            fixture,
+            snapshot,
+            status: CaseStatus::Passed,
+            missing: Vec::new(),
+            unexpected: Vec::new(),
+            error: None,
+        };
+
+        let analysis = match self.analyze(&case.fixture).await {
+            Ok(analysis) => analysis,
+            Err(e) => {
+                case.status = CaseStatus::Error;
+                case.error = Some(e.to_string());
+                return Ok(case);
+            }
+        };
+        let actual = findings(&analysis);
+
+        if self.update {
+            let json = serde_json::to_string_pretty(&Snapshot { findings: actual })?;
+            tokio::fs::write(&case.snapshot, json + "\n").await?;
+            case.status = CaseStatus::Updated;
+            return Ok(case);
+        }
+
+        let expected: Snapshot = match tokio::fs::read(&case.snapshot).await {
+            Ok(bytes) => match serde_json::from_slice(&bytes) {
+                Ok(snapshot) => snapshot,
+                // One bad snapshot fails its case, not the whole run
+                Err(e) => {
+                    case.status = CaseStatus::Error;
+                    case.error = Some(format!("Malformed snapshot {}: {}", case.snapshot.display(), e));
+                    return Ok(case);
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                case.status = CaseStatus::MissingSnapshot;
+                case.unexpected = actual;
+                return Ok(case);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        (case.missing, case.unexpected) = compare(&expected.findings, &actual);
+        if !case.missing.is_empty() || !case.unexpected.is_empty() {
+            case.status = CaseStatus::Failed;
+        }
+        debug!("{}: {:?}", case.fixture.display(), case.status);
+        Ok(case)
+    }
+
+    async fn analyze(&self, fixture: &Path) -> anyhow::Result<PdfAnalysis> {
+        PdfAnalyzer::with_options(fixture, self.options.clone())?.analyze().await
+    }
+}
+
+/// Snapshot path for a fixture (`dir/name.pdf` → `dir/name.expected.json`)
+pub fn snapshot_path(fixture: &Path) -> PathBuf {
+    fixture.with_extension(SNAPSHOT_SUFFIX)
+}
+
+/// Findings of an analysis in snapshot form, sorted
+pub fn findings(analysis: &PdfAnalysis) -> Vec<ExpectedFinding> {
+    let mut findings: Vec<_> = indicators(analysis)
+        .into_iter()
+        .map(|(severity, description)| ExpectedFinding { severity, description })
+        .collect();
+    findings.sort();
+    findings
+}
+
+/// Splits the difference between expected and actual findings into
+/// `(missing, unexpected)`, counting repeated findings individually
+pub fn compare(expected: &[ExpectedFinding], actual: &[ExpectedFinding]) -> (Vec<ExpectedFinding>, Vec<ExpectedFinding>) {
+    let mut remaining: BTreeMap<&ExpectedFinding, usize> = BTreeMap::new();
+    for finding in expected {
+        *remaining.entry(finding).or_default() += 1;
+    }
+
+    let mut unexpected = Vec::new();
+    for finding in actual {
+        match remaining.get_mut(finding) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => unexpected.push(finding.clone()),
+        }
+    }
+
+    let missing = remaining
+        .into_iter()
+        .flat_map(|(finding, count)| std::iter::repeat(finding.clone()).take(count))
+        .collect();
+    (missing, unexpected)
+}
+
+/// PDF files under `dir`, recursively, in path order
+async fn find_fixtures(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !tokio::fs::metadata(dir).await?.is_dir() {
+        return Err(Error::Analysis(format!("Corpus {} is not a directory", dir.display())));
+    }
+
+    let mut fixtures = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                pending.push(path);
+            } else if path.extension().map_or(false, |e| e.eq_ignore_ascii_case("pdf")) {
+                fixtures.push(path);
+            }
+        }
+    }
+
+    fixtures.sort();
+    Ok(fixtures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    fn finding(severity: Severity, description: &str) -> ExpectedFinding {
+        ExpectedFinding { severity, description: description.to_string() }
+    }
+
+    #[test]
+    fn test_compare_counts_duplicates() {
+        let expected = [finding(Severity::Low, "a"), finding(Severity::Low, "a"), finding(Severity::High, "b")];
+        let actual = [finding(Severity::Low, "a"), finding(Severity::Medium, "c")];
+
+        let (missing, unexpected) = compare(&expected, &actual);
+        assert_eq!(missing, vec![finding(Severity::Low, "a"), finding(Severity::High, "b")]);
+        assert_eq!(unexpected, vec![finding(Severity::Medium, "c")]);
+    }
+
+    #[tokio::test]
+    async fn test_update_then_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("incremental");
+        std::fs::create_dir(&nested).unwrap();
+        std::fs::write(dir.path().join("clean.pdf"), fixtures::simple_pdf()).unwrap();
+        std::fs::write(
+            nested.join("annotated.pdf"),
+            fixtures::append_revision(&fixtures::simple_pdf(), &[(7, "<< /S /J#61vaScript >>")]),
+        )
+        .unwrap();
+
+        let missing = Corpus::new(dir.path()).run().await.unwrap();
+        assert_eq!(missing.count(CaseStatus::MissingSnapshot), 2);
+        assert!(!missing.passed());
+
+        let updated = Corpus::new(dir.path()).update(true).run().await.unwrap();
+        assert_eq!(updated.count(CaseStatus::Updated), 2);
+        assert!(nested.join("annotated.expected.json").exists());
+
+        let rerun = Corpus::new(dir.path()).run().await.unwrap();
+        assert!(rerun.passed(), "{:?}", rerun.cases);
+    }
+
+    #[tokio::test]
+    async fn test_changed_expectation_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = dir.path().join("clean.pdf");
+        std::fs::write(&fixture, fixtures::simple_pdf()).unwrap();
+        let snapshot = Snapshot { findings: vec![finding(Severity::High, "Escaped keyword")] };
+        std::fs::write(snapshot_path(&fixture), serde_json::to_vec(&snapshot).unwrap()).unwrap();
+
+        let report = Corpus::new(dir.path()).run().await.unwrap();
+        assert_eq!(report.cases[0].status, CaseStatus::Failed);
+        assert_eq!(report.cases[0].missing, snapshot.findings);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_snapshot_fails_its_case() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.pdf", "b.pdf"] {
+            std::fs::write(dir.path().join(name), fixtures::simple_pdf()).unwrap();
+        }
+        std::fs::write(snapshot_path(&dir.path().join("a.pdf")), b"{ not json").unwrap();
+
+        let report = Corpus::new(dir.path()).run().await.unwrap();
+        let statuses: Vec<_> = report.cases.iter().map(|case| case.status).collect();
+        assert_eq!(statuses, vec![CaseStatus::Error, CaseStatus::MissingSnapshot]);
+        assert!(report.cases[0].error.as_deref().unwrap().starts_with("Malformed snapshot"));
+    }
+}
@@ -0,0 +1,684 @@
+//! Font encodings, ToUnicode maps and glyph widths
+//! Created: 2026-10-16 09:42:56 UTC
+//! Author: kartik4091
+//!
+//! Maps the character codes shown by text operators to Unicode and to
+//! glyph advances. ToUnicode CMaps take precedence; simple fonts fall back
+//! to their base encoding and /Differences, composite fonts to nothing
+//! (U+FFFD), since their codes identify glyphs rather than characters.
+
+use std::collections::BTreeMap;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+use crate::document::{
+    content::{TextMetrics, DEFAULT_ADVANCE},
+    name_value, number, resolve, stream_data,
+};
+
+/// Largest bfrange expanded from a ToUnicode CMap
+const MAX_RANGE: u32 = 0x1_0000;
+
+/// Most ToUnicode mappings or glyph widths loaded for one font, however
+/// many ranges declare them
+const MAX_ENTRIES: usize = 0x10_0000;
+
+/// Default width of composite font glyphs (/DW), in glyph space units
+const DEFAULT_CID_WIDTH: f64 = 1000.0;
+
+/// Windows-1252 characters for codes 0x80–0x9F; NUL marks undefined codes
+const WIN_ANSI_HIGH: &str = "€\0‚ƒ„…†‡ˆ‰Š‹Œ\0Ž\0\0‘’“”•–—˜™š›œ\0žŸ";
+
+/// Mac OS Roman characters for codes 0x80–0xFF
+const MAC_ROMAN_HIGH: &str = "ÄÅÇÉÑÖÜáàâäãåçéèêëíìîïñóòôöõúùûü†°¢£§•¶ß®©™´¨≠ÆØ∞±≤≥¥µ∂∑∏π∫ªºΩæø¿¡¬√ƒ≈∆«»…\u{a0}ÀÃÕŒœ–—“”‘’÷◊ÿŸ⁄€‹›ﬁﬂ‡·‚„‰ÂÊÁËÈÍÎÏÌÓÔ\u{f8ff}ÒÚÛÙıˆ˜¯˘˙˚¸˝˛ˇ";
+
+/// Base encoding of a simple font
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseEncoding {
+    /// Adobe StandardEncoding (approximated by Latin-1 above 0x7F)
+    Standard,
+
+    /// WinAnsiEncoding (Windows-1252)
+    WinAnsi,
+
+    /// MacRomanEncoding
+    MacRoman,
+}
+
+impl BaseEncoding {
+    fn from_name(name: &[u8]) -> Option<Self> {
+        match name {
+            b"StandardEncoding" => Some(Self::Standard),
+            b"WinAnsiEncoding" => Some(Self::WinAnsi),
+            b"MacRomanEncoding" => Some(Self::MacRoman),
+            _ => None,
+        }
+    }
+
+    /// Character for a code, `None` for control and undefined codes
+    pub fn char(&self, code: u8) -> Option<char> {
+        match (self, code) {
+            (_, 0x00..=0x1F) | (_, 0x7F) => None,
+            (Self::Standard, b'\'') => Some('’'),
+            (Self::Standard, b'`') => Some('‘'),
+            (_, 0x20..=0x7E) => Some(code as char),
+            (Self::WinAnsi, 0x80..=0x9F) => WIN_ANSI_HIGH.chars().nth(code as usize - 0x80).filter(|&c| c != '\0'),
+            (Self::MacRoman, _) => MAC_ROMAN_HIGH.chars().nth(code as usize - 0x80),
+            (_, 0x80..=0x9F) => None,
+            _ => Some(code as char),
+        }
+    }
+}
+
+/// A font resource, reduced to what text extraction needs
+#[derive(Debug, Clone, Default)]
+pub struct Font {
+    /// /BaseFont name
+    pub base_font: String,
+
+    /// Composite (Type0) font
+    pub composite: bool,
+
+    /// Code space ranges from the ToUnicode CMap
+    code_space: Vec<(Vec<u8>, Vec<u8>)>,
+
+    /// ToUnicode mappings
+    to_unicode: BTreeMap<Vec<u8>, String>,
+
+    /// Simple font encoding, after /Differences
+    encoding: BTreeMap<u8, char>,
+
+    /// Glyph widths in ems
+    widths: BTreeMap<u32, f64>,
+
+    /// Width of glyphs missing from `widths`, in ems
+    default_width: f64,
+}
+
+impl Font {
+    /// Reads a font dictionary
+    pub fn load(doc: &Document, dict: &Dictionary) -> Self {
+        let composite = name_value(dict, b"Subtype") == Some(b"Type0");
+        let mut font = Font {
+            base_font: name_value(dict, b"BaseFont")
+                .map(|n| String::from_utf8_lossy(n).into_owned())
+                .unwrap_or_default(),
+            composite,
+            default_width: DEFAULT_ADVANCE,
+            ..Default::default()
+        };
+
+        if let Some(Object::Stream(cmap)) = dict.get(b"ToUnicode").ok().map(|c| resolve(doc, c)) {
+            if let Ok(data) = stream_data(cmap) {
+                font.parse_cmap(&data);
+            }
+        }
+
+        if composite {
+            font.load_cid_widths(doc, dict);
+        } else {
+            font.load_encoding(doc, dict);
+            font.load_simple_widths(doc, dict);
+        }
+        font
+    }
+
+    /// Splits shown bytes into character codes
+    pub fn codes<'b>(&self, bytes: &'b [u8]) -> Vec<&'b [u8]> {
+        let mut codes = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let len = self.code_length(rest).min(rest.len());
+            codes.push(&rest[..len]);
+            rest = &rest[len..];
+        }
+        codes
+    }
+
+    /// Unicode text of one character code
+    pub fn decode_code(&self, code: &[u8]) -> String {
+        if let Some(text) = self.to_unicode.get(code) {
+            return text.clone();
+        }
+        match code {
+            [byte] if !self.composite => self
+                .encoding
+                .get(byte)
+                .copied()
+                .or_else(|| BaseEncoding::Standard.char(*byte))
+                .map(String::from)
+                .unwrap_or_default(),
+            _ => char::REPLACEMENT_CHARACTER.to_string(),
+        }
+    }
+
+    /// Unicode text of shown bytes
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        self.codes(bytes)
+            .into_iter()
+            .map(|code| self.decode_code(code))
+            .collect()
+    }
+
+    /// Character the simple-font encoding assigns to a code, ignoring ToUnicode
+    pub fn encoded_char(&self, code: u8) -> Option<char> {
+        self.encoding.get(&code).copied()
+    }
+
+    /// ToUnicode mappings, by character code
+    pub fn unicode_map(&self) -> impl Iterator<Item = (&[u8], &str)> {
+        self.to_unicode
+            .iter()
+            .map(|(code, text)| (code.as_slice(), text.as_str()))
+    }
+
+    /// Advance of one character code, in ems
+    pub fn width(&self, code: &[u8]) -> f64 {
+        self.widths
+            .get(&code_value(code))
+            .copied()
+            .unwrap_or(self.default_width)
+    }
+
+    fn code_length(&self, bytes: &[u8]) -> usize {
+        for (low, high) in &self.code_space {
+            let n = low.len();
+            if bytes.len() >= n && (0..n).all(|i| low[i] <= bytes[i] && bytes[i] <= high[i]) {
+                return n;
+            }
+        }
+        if self.composite {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Reads codespacerange, bfchar and bfrange sections of a ToUnicode CMap
+    fn parse_cmap(&mut self, data: &[u8]) {
+        let tokens = cmap_tokens(data);
+        let mut section = "";
+        let mut i = 0;
+        while i < tokens.len() && self.to_unicode.len() < MAX_ENTRIES {
+            match &tokens[i] {
+                CmapToken::Keyword(word) if word.starts_with("begin") || word.starts_with("end") => {
+                    section = if word.starts_with("begin") { word.as_str() } else { "" };
+                    i += 1;
+                }
+                CmapToken::Hex(low) if section == "begincodespacerange" => {
+                    if let Some(CmapToken::Hex(high)) = tokens.get(i + 1) {
+                        self.code_space.push((low.clone(), high.clone()));
+                    }
+                    i += 2;
+                }
+                CmapToken::Hex(code) if section == "beginbfchar" => {
+                    if let Some(CmapToken::Hex(target)) = tokens.get(i + 1) {
+                        self.to_unicode.insert(code.clone(), utf16(target));
+                    }
+                    i += 2;
+                }
+                CmapToken::Hex(low) if section == "beginbfrange" => {
+                    let (Some(CmapToken::Hex(high)), Some(target)) = (tokens.get(i + 1), tokens.get(i + 2)) else {
+                        break;
+                    };
+                    let (first, last) = (code_value(low), code_value(high));
+                    let count = last.saturating_sub(first).min(MAX_RANGE);
+                    for offset in (0..=count).take_while(|_| self.to_unicode.len() < MAX_ENTRIES) {
+                        let code = code_bytes(first + offset, low.len());
+                        let text = match target {
+                            CmapToken::Hex(start) => Some(increment(start, offset)),
+                            CmapToken::Array(items) => items.get(offset as usize).map(|t| utf16(t)),
+                            CmapToken::Keyword(_) => None,
+                        };
+                        if let Some(text) = text {
+                            self.to_unicode.insert(code, text);
+                        }
+                    }
+                    i += 3;
+                }
+                _ => i += 1,
+            }
+        }
+        // Longer code lengths are tried first only when shorter ones do not match
+        self.code_space.sort_by_key(|(low, _)| low.len());
+    }
+
+    fn load_encoding(&mut self, doc: &Document, dict: &Dictionary) {
+        let (base, differences) = match dict.get(b"Encoding").map(|e| resolve(doc, e)) {
+            Ok(Object::Name(name)) => (BaseEncoding::from_name(name), None),
+            Ok(Object::Dictionary(encoding)) => (
+                name_value(encoding, b"BaseEncoding").and_then(BaseEncoding::from_name),
+                encoding
+                    .get(b"Differences")
+                    .ok()
+                    .and_then(|d| resolve(doc, d).as_array().ok()),
+            ),
+            _ => (None, None),
+        };
+        let base = base.unwrap_or(BaseEncoding::Standard);
+        for code in 0..=u8::MAX {
+            if let Some(c) = base.char(code) {
+                self.encoding.insert(code, c);
+            }
+        }
+
+        let mut code: i64 = 0;
+        for item in differences.into_iter().flatten() {
+            match item {
+                Object::Integer(start) => code = *start,
+                Object::Name(name) => {
+                    // A glyph name without a known character (afii*, g12 in
+                    // subsets) no longer shows the base encoding's character
+                    if let Ok(byte) = u8::try_from(code) {
+                        match glyph_char(name) {
+                            Some(c) => self.encoding.insert(byte, c),
+                            None => self.encoding.remove(&byte),
+                        };
+                    }
+                    code += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn load_simple_widths(&mut self, doc: &Document, dict: &Dictionary) {
+        // Type 3 glyph space is defined by /FontMatrix rather than 1/1000
+        let font_matrix = match dict.get(b"FontMatrix").map(|m| resolve(doc, m)) {
+            Ok(Object::Array(matrix)) => matrix.first().and_then(number),
+            _ => None,
+        };
+        let to_ems = |width: f64| font_matrix.map_or(width / 1000.0, |scale| width * scale);
+        if let Some(missing) = dict
+            .get(b"FontDescriptor")
+            .ok()
+            .and_then(|d| resolve(doc, d).as_dict().ok())
+            .and_then(|d| d.get(b"MissingWidth").ok())
+            .and_then(number)
+            .filter(|&w| w > 0.0)
+        {
+            self.default_width = to_ems(missing);
+        }
+
+        let first = dict.get(b"FirstChar").ok().and_then(number).unwrap_or(0.0) as u32;
+        if let Ok(widths) = dict.get(b"Widths").map(|w| resolve(doc, w)).and_then(Object::as_array) {
+            for (code, width) in (first..=u32::MAX).zip(widths.iter().take(MAX_ENTRIES)) {
+                if let Some(width) = number(resolve(doc, width)) {
+                    self.widths.insert(code, to_ems(width));
+                }
+            }
+        }
+    }
+
+    fn load_cid_widths(&mut self, doc: &Document, dict: &Dictionary) {
+        let Some(descendant) = dict
+            .get(b"DescendantFonts")
+            .ok()
+            .and_then(|d| resolve(doc, d).as_array().ok())
+            .and_then(|d| d.first())
+            .and_then(|d| resolve(doc, d).as_dict().ok())
+        else {
+            return;
+        };
+        self.default_width = descendant.get(b"DW").ok().and_then(number).unwrap_or(DEFAULT_CID_WIDTH) / 1000.0;
+
+        // /W: c [w1 w2 ...] or c_first c_last w
+        let items = descendant.get(b"W").ok().and_then(|w| resolve(doc, w).as_array().ok());
+        let items: Vec<&Object> = items.into_iter().flatten().map(|item| resolve(doc, item)).collect();
+        let mut i = 0;
+        while i < items.len() && self.widths.len() < MAX_ENTRIES {
+            let Some(first) = number(items[i]).map(|n| n as u32) else {
+                break;
+            };
+            match (items.get(i + 1), items.get(i + 2)) {
+                (Some(Object::Array(widths)), _) => {
+                    for (cid, width) in (first..=u32::MAX).zip(widths) {
+                        if let Some(width) = number(resolve(doc, width)) {
+                            self.widths.insert(cid, width / 1000.0);
+                        }
+                    }
+                    i += 2;
+                }
+                (Some(last), Some(width)) => {
+                    if let (Some(last), Some(width)) = (number(last), number(width)) {
+                        for cid in first..=(last as u32).min(first.saturating_add(MAX_RANGE)) {
+                            self.widths.insert(cid, width / 1000.0);
+                        }
+                    }
+                    i += 3;
+                }
+                _ => break,
+            }
+        }
+    }
+}
+
+/// Fonts of one page, by resource name
+#[derive(Debug, Clone, Default)]
+pub struct PageFonts {
+    fonts: BTreeMap<Vec<u8>, Font>,
+}
+
+impl PageFonts {
+    /// Loads the fonts in a page's (inherited) resources
+    pub fn load(doc: &Document, page: ObjectId) -> Self {
+        let (inline, referenced) = doc.get_page_resources(page);
+        let mut resources: Vec<&Dictionary> = inline.into_iter().collect();
+        resources.extend(referenced.into_iter().filter_map(|id| doc.get_dictionary(id).ok()));
+        Self::from_resources(doc, &resources)
+    }
+
+    /// Loads the fonts in resource dictionaries, the first taking precedence
+    pub fn from_resources(doc: &Document, resources: &[&Dictionary]) -> Self {
+        let mut fonts = BTreeMap::new();
+        for resources in resources {
+            let Ok(entries) = resources
+                .get(b"Font")
+                .map(|f| resolve(doc, f))
+                .and_then(Object::as_dict)
+            else {
+                continue;
+            };
+            for (name, font) in entries.iter() {
+                if let Ok(dict) = resolve(doc, font).as_dict() {
+                    // Resources closest to the page take precedence
+                    fonts.entry(name.clone()).or_insert_with(|| Font::load(doc, dict));
+                }
+            }
+        }
+        Self { fonts }
+    }
+
+    /// Font for a resource name
+    pub fn get(&self, name: &[u8]) -> Option<&Font> {
+        self.fonts.get(name)
+    }
+
+    /// Unicode text of bytes shown with a font resource
+    pub fn decode(&self, font: &[u8], bytes: &[u8]) -> String {
+        match self.get(font) {
+            Some(font) => font.decode(bytes),
+            None => Font::default().decode(bytes),
+        }
+    }
+}
+
+impl TextMetrics for PageFonts {
+    fn codes<'b>(&self, font: &[u8], bytes: &'b [u8]) -> Vec<&'b [u8]> {
+        match self.get(font) {
+            Some(font) => font.codes(bytes),
+            None => bytes.chunks(1).collect(),
+        }
+    }
+
+    fn width(&self, font: &[u8], code: &[u8]) -> f64 {
+        self.get(font).map_or(DEFAULT_ADVANCE, |font| font.width(code))
+    }
+}
+
+enum CmapToken {
+    Hex(Vec<u8>),
+    Array(Vec<Vec<u8>>),
+    Keyword(String),
+}
+
+fn cmap_tokens(data: &[u8]) -> Vec<CmapToken> {
+    let mut tokens = Vec::new();
+    let mut array: Option<Vec<Vec<u8>>> = None;
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'<' if data.get(i + 1) == Some(&b'<') => i += 2,
+            b'<' => {
+                let end = data[i..].iter().position(|&b| b == b'>').map_or(data.len(), |p| i + p);
+                let hex = hex_bytes(&data[i + 1..end]);
+                match &mut array {
+                    Some(items) => items.push(hex),
+                    None => tokens.push(CmapToken::Hex(hex)),
+                }
+                i = end + 1;
+            }
+            b'[' => {
+                array = Some(Vec::new());
+                i += 1;
+            }
+            b']' => {
+                tokens.extend(array.take().map(CmapToken::Array));
+                i += 1;
+            }
+            b'%' => {
+                i = data[i..]
+                    .iter()
+                    .position(|&b| b == b'\n' || b == b'\r')
+                    .map_or(data.len(), |p| i + p)
+            }
+            b if b.is_ascii_alphabetic() => {
+                let end = data[i..]
+                    .iter()
+                    .position(|b| !b.is_ascii_alphanumeric())
+                    .map_or(data.len(), |p| i + p);
+                tokens.push(CmapToken::Keyword(String::from_utf8_lossy(&data[i..end]).into_owned()));
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    tokens
+}
+
+fn hex_bytes(hex: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .iter()
+        .filter_map(|&b| (b as char).to_digit(16).map(|d| d as u8))
+        .collect();
+    digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
+fn utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// bfrange destination advanced by `offset` in its last UTF-16 unit
+fn increment(start: &[u8], offset: u32) -> String {
+    let mut units: Vec<u16> = start
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+        .collect();
+    if let Some(last) = units.last_mut() {
+        *last = last.wrapping_add(offset as u16);
+    }
+    String::from_utf16_lossy(&units)
+}
+
+fn code_value(code: &[u8]) -> u32 {
+    code.iter().take(4).fold(0, |value, &b| (value << 8) | b as u32)
+}
+
+fn code_bytes(value: u32, len: usize) -> Vec<u8> {
+    (0..len).rev().map(|i| (value >> (8 * i)) as u8).collect()
+}
+
+/// Unicode character for a glyph name (common Adobe Glyph List names,
+/// `uniXXXX` and `uXXXX[XX]`)
+pub fn glyph_char(name: &[u8]) -> Option<char> {
+    let name = std::str::from_utf8(name).ok()?;
+    let name = name.split('.').next().unwrap_or(name);
+    if let [c] = name.as_bytes() {
+        return Some(*c as char).filter(char::is_ascii_alphabetic);
+    }
+    if let Some(hex) = name.strip_prefix("uni").or_else(|| name.strip_prefix('u')) {
+        if (4..=6).contains(&hex.len()) {
+            if let Some(c) = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+                return Some(c);
+            }
+        }
+    }
+    let c = match name {
+        "space" => ' ',
+        "exclam" => '!',
+        "quotedbl" => '"',
+        "numbersign" => '#',
+        "dollar" => '$',
+        "percent" => '%',
+        "ampersand" => '&',
+        "quotesingle" => '\'',
+        "parenleft" => '(',
+        "parenright" => ')',
+        "asterisk" => '*',
+        "plus" => '+',
+        "comma" => ',',
+        "hyphen" | "minus" => '-',
+        "period" => '.',
+        "slash" => '/',
+        "zero" => '0',
+        "one" => '1',
+        "two" => '2',
+        "three" => '3',
+        "four" => '4',
+        "five" => '5',
+        "six" => '6',
+        "seven" => '7',
+        "eight" => '8',
+        "nine" => '9',
+        "colon" => ':',
+        "semicolon" => ';',
+        "less" => '<',
+        "equal" => '=',
+        "greater" => '>',
+        "question" => '?',
+        "at" => '@',
+        "bracketleft" => '[',
+        "backslash" => '\\',
+        "bracketright" => ']',
+        "asciicircum" => '^',
+        "underscore" => '_',
+        "grave" => '`',
+        "braceleft" => '{',
+        "bar" => '|',
+        "braceright" => '}',
+        "asciitilde" => '~',
+        "quoteleft" => '‘',
+        "quoteright" => '’',
+        "quotedblleft" => '“',
+        "quotedblright" => '”',
+        "endash" => '–',
+        "emdash" => '—',
+        "bullet" => '•',
+        "ellipsis" => '…',
+        "fi" => 'ﬁ',
+        "fl" => 'ﬂ',
+        "Euro" => '€',
+        "copyright" => '©',
+        "registered" => '®',
+        "trademark" => '™',
+        "degree" => '°',
+        "section" => '§',
+        "paragraph" => '¶',
+        "eacute" => 'é',
+        "egrave" => 'è',
+        "agrave" => 'à',
+        "ccedilla" => 'ç',
+        "udieresis" => 'ü',
+        "odieresis" => 'ö',
+        "adieresis" => 'ä',
+        "germandbls" => 'ß',
+        "nbspace" | "uni00A0" => '\u{a0}',
+        _ => return None,
+    };
+    Some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{dictionary, Stream};
+
+    const CMAP: &[u8] = b"/CIDInit /ProcSet findresource begin 12 dict begin begincmap\n\
+        1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+        2 beginbfchar <0003> <0020> <0011> <00660069> endbfchar\n\
+        2 beginbfrange <0024> <0026> <0041> <0044> <0045> [<0078> <0079>] endbfrange\n\
+        endcmap CMapName currentdict /CMap defineresource pop end end";
+
+    #[test]
+    fn test_to_unicode_cmap() {
+        let mut doc = Document::with_version("1.5");
+        let cmap = doc.add_object(Stream::new(dictionary! {}, CMAP.to_vec()));
+        let descendant = dictionary! { "DW" => 600, "W" => vec![36.into(), vec![Object::Integer(722)].into()] };
+        let font = Font::load(
+            &doc,
+            &dictionary! {
+                "Subtype" => "Type0",
+                "BaseFont" => "ABCDEF+Arial",
+                "ToUnicode" => cmap,
+                "DescendantFonts" => vec![descendant.into()],
+            },
+        );
+
+        assert_eq!(
+            font.decode(b"\x00\x24\x00\x25\x00\x26\x00\x03\x00\x11\x00\x45"),
+            "ABC fiy"
+        );
+        assert_eq!(font.width(b"\x00\x24"), 0.722);
+        assert_eq!(font.width(b"\x00\x25"), 0.6);
+    }
+
+    #[test]
+    fn test_simple_font_differences() {
+        let doc = Document::with_version("1.5");
+        let font = Font::load(
+            &doc,
+            &dictionary! {
+                "Subtype" => "Type1",
+                "BaseFont" => "Custom",
+                "Encoding" => dictionary! {
+                    "BaseEncoding" => "WinAnsiEncoding",
+                    "Differences" => vec![65.into(), "Z".into(), "uni263A".into(), 68.into(), "g12".into()],
+                },
+                "FirstChar" => 65,
+                "Widths" => vec![250.into(), 500.into()],
+            },
+        );
+        assert_eq!(font.decode(b"ABC\x80"), "Z☺C€");
+        // Unknown glyph names replace the base encoding's character
+        assert_eq!(font.encoded_char(b'D'), None);
+        assert_eq!(font.width(b"A"), 0.25);
+        assert_eq!(font.width(b"C"), DEFAULT_ADVANCE);
+    }
+
+    #[test]
+    fn test_oversized_cmap_and_widths() {
+        let mut doc = Document::with_version("1.5");
+        let ranges: String = (0..17).map(|high| format!("<{:02X}0000> <{:02X}FFFF> <0041>\n", high, high)).collect();
+        let cmap = format!("17 beginbfrange\n{}endbfrange", ranges);
+        let cmap = doc.add_object(Stream::new(dictionary! {}, cmap.into_bytes()));
+        // Widths starting at the last CID, and a range running to it
+        let last = Object::Integer(u32::MAX as i64);
+        let widths = vec![last.clone(), vec![Object::Integer(500); 3].into(), 0.into(), last, 250.into()];
+        let font = Font::load(
+            &doc,
+            &dictionary! {
+                "Subtype" => "Type0",
+                "ToUnicode" => cmap,
+                "DescendantFonts" => vec![dictionary! { "W" => widths }.into()],
+            },
+        );
+
+        assert_eq!(font.unicode_map().count(), MAX_ENTRIES);
+        assert_eq!(font.widths.len(), MAX_RANGE as usize + 2);
+    }
+
+    #[test]
+    fn test_glyph_names() {
+        assert_eq!(glyph_char(b"quoteright"), Some('’'));
+        assert_eq!(glyph_char(b"a.sc"), Some('a'));
+        assert_eq!(glyph_char(b"u1F600"), Some('😀'));
+        assert_eq!(glyph_char(b"g123"), None);
+    }
+}
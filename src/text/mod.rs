@@ -0,0 +1,225 @@
+//! Text extraction with positional data
+//! Created: 2026-10-16 09:38:42 UTC
+//! Author: kartik4091
+//!
+//! Interprets page content streams with the page's fonts loaded, mapping
+//! shown character codes to Unicode through ToUnicode CMaps and font
+//! encodings, and keeps the device-space box of every character. The
+//! redaction, invisible-text and PII passes work on this output rather
+//! than on raw string operands.
+
+pub mod font;
+pub mod truetype;
+
+use std::ops::Range;
+
+use lopdf::{content::Content, Document, ObjectId};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::document::content::{interpret, Color, ContentEvent, TextShow, IDENTITY};
+use font::PageFonts;
+
+/// Fraction of the font height separating words on one line
+const WORD_GAP: f64 = 0.15;
+
+/// One character with its position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextChar {
+    /// Unicode text (a ligature can map to several characters)
+    pub text: String,
+
+    /// Device-space box in default user space
+    pub bbox: [f64; 4],
+}
+
+/// Text shown by one text operator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextSpan {
+    /// Unicode text
+    pub text: String,
+
+    /// Device-space box covering the span
+    pub bbox: [f64; 4],
+
+    /// Base font name, or the resource name when the font is missing
+    pub font: String,
+
+    /// Font size operand of Tf
+    pub font_size: f64,
+
+    /// Text render mode (Tr)
+    pub render_mode: i64,
+
+    /// Fill color
+    pub fill: Color,
+
+    /// Characters in order
+    pub chars: Vec<TextChar>,
+}
+
+/// Extracted text of one page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageText {
+    /// Page object
+    pub page: ObjectId,
+
+    /// One-based page number
+    pub page_number: u32,
+
+    /// Spans in content stream order
+    pub spans: Vec<TextSpan>,
+}
+
+impl PageText {
+    /// Plain text, with line breaks where the baseline moves and spaces
+    /// between spans separated horizontally
+    pub fn text(&self) -> String {
+        self.text_with_offsets().0
+    }
+
+    /// Plain text as [`text`](Self::text), with the byte range each span
+    /// occupies in it
+    pub fn text_with_offsets(&self) -> (String, Vec<Range<usize>>) {
+        let mut out = String::new();
+        let mut offsets = Vec::with_capacity(self.spans.len());
+        let mut previous: Option<&TextSpan> = None;
+        for span in &self.spans {
+            if let Some(previous) = previous {
+                let height = (span.bbox[3] - span.bbox[1]).max(f64::EPSILON);
+                if (span.bbox[1] - previous.bbox[1]).abs() > height / 2.0 {
+                    out.push('\n');
+                } else if span.bbox[0] - previous.bbox[2] > height * WORD_GAP
+                    && !out.ends_with(' ')
+                    && !span.text.starts_with(' ')
+                {
+                    out.push(' ');
+                }
+            }
+            let start = out.len();
+            out.push_str(&span.text);
+            offsets.push(start..out.len());
+            previous = Some(span);
+        }
+        (out, offsets)
+    }
+
+    /// Bounding box of the spans covering a byte range of the plain text,
+    /// given the offsets from [`text_with_offsets`](Self::text_with_offsets)
+    pub fn bbox_of(&self, offsets: &[Range<usize>], range: Range<usize>) -> Option<[f64; 4]> {
+        self.spans
+            .iter()
+            .zip(offsets)
+            .filter(|(_, offset)| offset.start < range.end && range.start < offset.end)
+            .map(|(span, _)| span.bbox)
+            .reduce(|a, b| [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])])
+    }
+}
+
+/// Extracts the text of every page
+#[instrument(skip(doc))]
+pub fn extract(doc: &Document) -> Vec<PageText> {
+    let pages: Vec<PageText> = doc
+        .get_pages()
+        .into_iter()
+        .map(|(page_number, page)| extract_page(doc, page_number, page))
+        .collect();
+    debug!("Extracted text from {} pages", pages.len());
+    pages
+}
+
+/// Extracts the text of one page
+pub fn extract_page(doc: &Document, page_number: u32, page: ObjectId) -> PageText {
+    let fonts = PageFonts::load(doc, page);
+    let spans = doc
+        .get_page_content(page)
+        .ok()
+        .and_then(|data| Content::decode(&data).ok())
+        .map(|content| interpret(&content.operations, IDENTITY, &fonts))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|event| match event {
+            ContentEvent::Text(text) => Some(span(&fonts, text)),
+            _ => None,
+        })
+        .collect();
+    PageText {
+        page,
+        page_number,
+        spans,
+    }
+}
+
+/// Decodes a text operator with the page's fonts
+pub fn span(fonts: &PageFonts, shown: &TextShow) -> TextSpan {
+    let chars: Vec<TextChar> = shown
+        .glyphs
+        .iter()
+        .map(|glyph| TextChar {
+            text: fonts.decode(&shown.font, &glyph.code),
+            bbox: glyph.bbox,
+        })
+        .collect();
+    TextSpan {
+        text: chars.iter().map(|c| c.text.as_str()).collect(),
+        bbox: shown.bbox,
+        font: fonts
+            .get(&shown.font)
+            .map(|font| font.base_font.clone())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| String::from_utf8_lossy(&shown.font).into_owned()),
+        font_size: shown.font_size,
+        render_mode: shown.render_mode,
+        fill: shown.fill,
+        chars,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_simple_document() {
+        let pages = extract(&fixtures::document());
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].text(), "Hello World!");
+
+        let span = &pages[0].spans[0];
+        assert_eq!(span.font, "Helvetica");
+        assert_eq!(span.chars.len(), 12);
+        assert_eq!(span.chars[0].bbox[0], 100.0);
+    }
+
+    #[test]
+    fn test_lines_and_to_unicode() {
+        let mut doc = fixtures::document();
+        let cmap = doc.add_object(Stream::new(
+            dictionary! {},
+            b"1 begincodespacerange <0000> <FFFF> endcodespacerange 1 beginbfrange <0001> <0003> <0061> endbfrange"
+                .to_vec(),
+        ));
+        let font = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => "Identity",
+            "ToUnicode" => cmap,
+            "DescendantFonts" => vec![dictionary! { "DW" => 500 }.into()],
+        });
+        let resources = doc.get_dictionary_mut((3, 0)).unwrap();
+        let fonts = resources.get_mut(b"Font").unwrap().as_dict_mut().unwrap();
+        fonts.set("F2", font);
+
+        let content = b"BT /F1 10 Tf 72 700 Td (first) Tj (line) Tj 0 -20 Td /F2 10 Tf <000100020003> Tj ET";
+        let contents = doc.add_object(Stream::new(dictionary! {}, content.to_vec()));
+        doc.get_dictionary_mut(fixtures::PAGE_ID)
+            .unwrap()
+            .set("Contents", contents);
+
+        let page = &extract(&doc)[0];
+        assert_eq!(page.text(), "firstline\nabc");
+        assert_eq!(page.spans[2].chars[1].bbox, [77.0, 678.0, 82.0, 688.0]);
+    }
+}
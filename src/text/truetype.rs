@@ -0,0 +1,247 @@
+//! Minimal TrueType glyph outline reader
+//! Created: 2026-10-16 09:53:09 UTC
+//! Author: kartik4091
+//!
+//! Reads just enough of an embedded TrueType program (FontFile2) to find a
+//! glyph for a character code and to fingerprint its outline. Fingerprints
+//! cover the contour end points, on-curve flags and absolute coordinates,
+//! so the same glyph digests identically whether it was embedded by one
+//! subsetter or another, with or without hinting instructions.
+
+use std::collections::BTreeMap;
+
+use sha2::{Digest, Sha256};
+
+use crate::document::to_hex;
+
+/// Flags of a simple glyph point
+const ON_CURVE: u8 = 0x01;
+const X_SHORT: u8 = 0x02;
+const Y_SHORT: u8 = 0x04;
+const REPEAT: u8 = 0x08;
+const X_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_SAME_OR_POSITIVE: u8 = 0x20;
+
+/// Most points decoded from one glyph
+const MAX_POINTS: usize = 0x1_0000;
+
+/// An embedded TrueType font program
+#[derive(Debug, Clone)]
+pub struct TrueType<'a> {
+    tables: BTreeMap<[u8; 4], &'a [u8]>,
+    long_offsets: bool,
+}
+
+impl<'a> TrueType<'a> {
+    /// Reads the table directory; `None` unless head, loca and glyf are present
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let count = u16_at(data, 4)? as usize;
+        let mut tables = BTreeMap::new();
+        for i in 0..count {
+            let record = 12 + i * 16;
+            let tag: [u8; 4] = data.get(record..record + 4)?.try_into().ok()?;
+            let offset = u32_at(data, record + 8)? as usize;
+            let length = u32_at(data, record + 12)? as usize;
+            if let Some(table) = data.get(offset..offset.checked_add(length)?) {
+                tables.insert(tag, table);
+            }
+        }
+
+        let long_offsets = u16_at(tables.get(b"head")?, 50)? == 1;
+        if !tables.contains_key(b"loca") || !tables.contains_key(b"glyf") {
+            return None;
+        }
+        Some(Self { tables, long_offsets })
+    }
+
+    /// Number of glyphs described by the loca table
+    pub fn glyph_count(&self) -> usize {
+        let entry = if self.long_offsets { 4 } else { 2 };
+        (self.tables[b"loca"].len() / entry).saturating_sub(1)
+    }
+
+    /// Raw glyf data of a glyph
+    pub fn glyph(&self, gid: u16) -> Option<&'a [u8]> {
+        let loca = self.tables[b"loca"];
+        let gid = gid as usize;
+        let (start, end) = if self.long_offsets {
+            (u32_at(loca, gid * 4)? as usize, u32_at(loca, gid * 4 + 4)? as usize)
+        } else {
+            (
+                u16_at(loca, gid * 2)? as usize * 2,
+                u16_at(loca, gid * 2 + 2)? as usize * 2,
+            )
+        };
+        self.tables[b"glyf"].get(start..end)
+    }
+
+    /// Number of contours of a simple glyph; `None` for empty and composite glyphs
+    pub fn contours(&self, gid: u16) -> Option<usize> {
+        let glyph = self.glyph(gid)?;
+        let contours = i16::from_be_bytes([*glyph.first()?, *glyph.get(1)?]);
+        usize::try_from(contours).ok().filter(|&c| c > 0)
+    }
+
+    /// Hex SHA-256 of a simple glyph's outline; `None` for empty and composite glyphs
+    pub fn outline_digest(&self, gid: u16) -> Option<String> {
+        let glyph = self.glyph(gid)?;
+        let contours = i16::from_be_bytes([*glyph.first()?, *glyph.get(1)?]);
+        if contours <= 0 {
+            return None;
+        }
+
+        let contours = contours as usize;
+        let end_points: Vec<u16> = (0..contours)
+            .map(|i| u16_at(glyph, 10 + i * 2))
+            .collect::<Option<_>>()?;
+        let points = (*end_points.last()? as usize + 1).min(MAX_POINTS);
+        let instructions = u16_at(glyph, 10 + contours * 2)? as usize;
+        let mut pos = 12 + contours * 2 + instructions;
+
+        let mut flags = Vec::with_capacity(points);
+        while flags.len() < points {
+            let flag = *glyph.get(pos)?;
+            pos += 1;
+            flags.push(flag);
+            if flag & REPEAT != 0 {
+                let repeat = *glyph.get(pos)?;
+                pos += 1;
+                flags.extend(std::iter::repeat(flag).take(repeat as usize));
+            }
+        }
+        flags.truncate(points);
+
+        let xs = coordinates(glyph, &mut pos, &flags, X_SHORT, X_SAME_OR_POSITIVE)?;
+        let ys = coordinates(glyph, &mut pos, &flags, Y_SHORT, Y_SAME_OR_POSITIVE)?;
+
+        let mut hasher = Sha256::new();
+        for end in &end_points {
+            hasher.update(end.to_be_bytes());
+        }
+        for ((flag, x), y) in flags.iter().zip(xs).zip(ys) {
+            hasher.update([flag & ON_CURVE]);
+            hasher.update(x.to_be_bytes());
+            hasher.update(y.to_be_bytes());
+        }
+        Some(to_hex(&hasher.finalize()))
+    }
+
+    /// Glyph for a single-byte code through the (3,0) or (1,0) cmap
+    pub fn glyph_for_code(&self, code: u8) -> Option<u16> {
+        // Symbol cmaps place codes at U+F000–U+F0FF, or directly
+        self.lookup(3, 0, 0xF000 + code as u32)
+            .or_else(|| self.lookup(3, 0, code as u32))
+            .or_else(|| self.lookup(1, 0, code as u32))
+    }
+
+    /// Glyph for a character through the (3,1) Unicode cmap
+    pub fn glyph_for_char(&self, c: char) -> Option<u16> {
+        self.lookup(3, 1, c as u32)
+    }
+
+    /// Looks a code up in the cmap subtable for a platform and encoding
+    fn lookup(&self, platform: u16, encoding: u16, code: u32) -> Option<u16> {
+        let cmap = self.tables.get(b"cmap")?;
+        let count = u16_at(cmap, 2)? as usize;
+        let offset = (0..count).find_map(|i| {
+            let record = 4 + i * 8;
+            (u16_at(cmap, record)? == platform && u16_at(cmap, record + 2)? == encoding)
+                .then(|| u32_at(cmap, record + 4))
+                .flatten()
+        })? as usize;
+        let subtable = cmap.get(offset..)?;
+
+        let gid = match u16_at(subtable, 0)? {
+            0 => *subtable.get(6 + usize::try_from(code).ok().filter(|&c| c < 256)?)? as u16,
+            4 => format4(subtable, u16::try_from(code).ok()?)?,
+            _ => return None,
+        };
+        (gid != 0).then_some(gid)
+    }
+}
+
+/// Segment mapping to delta values
+fn format4(subtable: &[u8], code: u16) -> Option<u16> {
+    let segments = u16_at(subtable, 6)? as usize / 2;
+    let ends = 14;
+    let starts = ends + segments * 2 + 2;
+    let deltas = starts + segments * 2;
+    let ranges = deltas + segments * 2;
+
+    for i in 0..segments {
+        if code > u16_at(subtable, ends + i * 2)? {
+            continue;
+        }
+        let start = u16_at(subtable, starts + i * 2)?;
+        if code < start {
+            return None;
+        }
+        let delta = u16_at(subtable, deltas + i * 2)?;
+        let range = u16_at(subtable, ranges + i * 2)? as usize;
+        if range == 0 {
+            return Some(code.wrapping_add(delta));
+        }
+        let address = ranges + i * 2 + range + (code - start) as usize * 2;
+        let gid = u16_at(subtable, address)?;
+        return (gid != 0).then(|| gid.wrapping_add(delta));
+    }
+    None
+}
+
+/// Decodes one coordinate array into absolute values
+fn coordinates(glyph: &[u8], pos: &mut usize, flags: &[u8], short: u8, same_or_positive: u8) -> Option<Vec<i32>> {
+    let mut value = 0i32;
+    let mut values = Vec::with_capacity(flags.len());
+    for flag in flags {
+        if flag & short != 0 {
+            let delta = *glyph.get(*pos)? as i32;
+            *pos += 1;
+            value += if flag & same_or_positive != 0 { delta } else { -delta };
+        } else if flag & same_or_positive == 0 {
+            value += i16::from_be_bytes([*glyph.get(*pos)?, *glyph.get(*pos + 1)?]) as i32;
+            *pos += 2;
+        }
+        values.push(value);
+    }
+    Some(values)
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::fixtures;
+
+    #[test]
+    fn test_outline_digest_ignores_instructions() {
+        let triangle = fixtures::glyph(&[(0, 0), (500, 0), (250, 700)], &[]);
+        let hinted = fixtures::glyph(&[(0, 0), (500, 0), (250, 700)], &[0xB0, 0x01]);
+        let square = fixtures::glyph(&[(0, 0), (500, 0), (500, 500), (0, 500)], &[]);
+        let data = fixtures::truetype(&[triangle, hinted, square]);
+        let font = TrueType::parse(&data).unwrap();
+
+        assert_eq!(font.glyph_count(), 4);
+        assert_eq!(font.outline_digest(0), None);
+        assert_eq!(font.contours(1), Some(1));
+        assert!(font.outline_digest(1).is_some());
+        assert_eq!(font.outline_digest(1), font.outline_digest(2));
+        assert_ne!(font.outline_digest(1), font.outline_digest(3));
+    }
+
+    #[test]
+    fn test_cmap_lookup() {
+        let data = fixtures::truetype(&[fixtures::glyph(&[(0, 0), (1, 0), (1, 1)], &[])]);
+        let font = TrueType::parse(&data).unwrap();
+        assert_eq!(font.glyph_for_char('A'), Some(1));
+        assert_eq!(font.glyph_for_char('B'), None);
+        assert_eq!(font.glyph_for_code(b'A'), None);
+        assert!(TrueType::parse(b"not a font").is_none());
+    }
+}